@@ -0,0 +1,14 @@
+-- Add a per-group sequence number to sessions so commands can use short references like "#12"
+ALTER TABLE sessions ADD COLUMN short_id INTEGER;
+
+-- Backfill existing sessions in creation order within each group
+UPDATE sessions SET short_id = (
+    SELECT COUNT(*)
+    FROM sessions s2
+    WHERE s2.group_id = sessions.group_id
+      AND (s2.created_at < sessions.created_at
+           OR (s2.created_at = sessions.created_at AND s2.id <= sessions.id))
+);
+
+-- Short IDs are unique within a group
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sessions_group_short_id ON sessions(group_id, short_id);
@@ -0,0 +1,2 @@
+-- Anonymous polls hide vote tallies until the session is confirmed or closed
+ALTER TABLE sessions ADD COLUMN anonymous BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,15 @@
+-- Per-group opt-out of storing Telegram usernames, with stable "Player #N" pseudonyms instead
+
+ALTER TABLE groups ADD COLUMN store_usernames BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Secret used to hash user IDs into player_pseudonyms, generated on first use
+ALTER TABLE groups ADD COLUMN pseudonym_key TEXT;
+
+CREATE TABLE IF NOT EXISTS player_pseudonyms (
+    group_id INTEGER NOT NULL,
+    user_hash TEXT NOT NULL, -- hex SHA-256 of the group's pseudonym_key and the user ID
+    number INTEGER NOT NULL,
+    PRIMARY KEY (group_id, user_hash),
+    UNIQUE (group_id, number),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
@@ -0,0 +1,17 @@
+-- The last reversible action per group, so /undo can restore it for a short while
+
+CREATE TABLE IF NOT EXISTS undo_actions (
+    id TEXT PRIMARY KEY,
+    group_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL, -- who performed the action, and the only one who may undo it
+    kind TEXT NOT NULL, -- 'cancel', 'close', 'deadline'
+    session_ids TEXT NOT NULL, -- comma separated, several for /closeall
+    previous_status TEXT, -- status before 'cancel'
+    previous_deadline TEXT, -- deadline before 'deadline', NULL if there was none
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_undo_actions_group_id ON undo_actions(group_id);
+CREATE INDEX IF NOT EXISTS idx_undo_actions_expires_at ON undo_actions(expires_at);
@@ -0,0 +1,2 @@
+-- Minimum hours between scheduling a session and its earliest option (0 = no restriction)
+ALTER TABLE groups ADD COLUMN min_notice_hours INTEGER NOT NULL DEFAULT 0;
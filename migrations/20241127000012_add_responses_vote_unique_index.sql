@@ -0,0 +1,8 @@
+-- Conflict target for the single-statement vote upsert. Databases whose responses table
+-- predates the UNIQUE constraint may hold duplicate votes, so keep only the newest first.
+DELETE FROM responses
+WHERE rowid NOT IN (
+    SELECT MAX(rowid) FROM responses GROUP BY session_id, option_id, user_id
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_responses_session_option_user ON responses(session_id, option_id, user_id);
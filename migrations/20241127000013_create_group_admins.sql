@@ -0,0 +1,10 @@
+-- Users allowed to manage any session in a group, in addition to its creator and the chat administrators
+
+CREATE TABLE IF NOT EXISTS group_admins (
+    group_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    added_by INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (group_id, user_id),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
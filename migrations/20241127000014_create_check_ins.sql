@@ -0,0 +1,9 @@
+-- "📍 Check in" taps on the game day reminder of a confirmed session
+
+CREATE TABLE IF NOT EXISTS check_ins (
+    session_id TEXT NOT NULL,
+    user_id INTEGER NOT NULL,
+    checked_in_at TEXT NOT NULL,
+    PRIMARY KEY (session_id, user_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
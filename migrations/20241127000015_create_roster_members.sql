@@ -0,0 +1,10 @@
+-- Players who joined a group's roster, e.g. through an /invite link
+
+CREATE TABLE IF NOT EXISTS roster_members (
+    group_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    username TEXT,
+    joined_at TEXT NOT NULL,
+    PRIMARY KEY (group_id, user_id),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- Free-form notes on a session, e.g. where the group is meeting
+ALTER TABLE sessions ADD COLUMN notes TEXT;
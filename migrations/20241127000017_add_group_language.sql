@@ -0,0 +1,2 @@
+-- Language of the bot's replies in a group, one of the codes in utils::i18n::SUPPORTED_LANGUAGES
+ALTER TABLE groups ADD COLUMN language TEXT NOT NULL DEFAULT 'en';
@@ -0,0 +1,14 @@
+-- What the bot sent (or tried to send), so "the bot never reminded us" can be checked.
+-- No foreign key on session_id: the record should outlive a deleted session.
+
+CREATE TABLE IF NOT EXISTS outbound_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chat_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    session_id TEXT,
+    sent_at TEXT NOT NULL,
+    success BOOLEAN NOT NULL,
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbound_messages_session ON outbound_messages(session_id, sent_at);
@@ -0,0 +1,7 @@
+-- Key sent reminders by how many minutes before the session they go out, so intervals
+-- shorter than a day (e.g. 2 hours before) can be told apart; existing rows were whole days
+ALTER TABLE reminders RENAME COLUMN days_before TO offset_minutes;
+UPDATE reminders SET offset_minutes = offset_minutes * 1440;
+
+DROP INDEX IF EXISTS idx_reminders_session_days;
+CREATE INDEX IF NOT EXISTS idx_reminders_session_offset ON reminders(session_id, offset_minutes);
@@ -0,0 +1,4 @@
+-- Optional extra reminders for sessions confirmed far ahead: every long_range_every_days days
+-- until long_range_until_days before the start. NULL turns them off.
+ALTER TABLE groups ADD COLUMN long_range_every_days INTEGER;
+ALTER TABLE groups ADD COLUMN long_range_until_days INTEGER NOT NULL DEFAULT 14;
@@ -0,0 +1,4 @@
+-- When a vote was last changed; created_at stays the time it was first cast.
+-- Existing votes only have the time of their last change, so both start out the same.
+ALTER TABLE responses ADD COLUMN updated_at TEXT NOT NULL DEFAULT '';
+UPDATE responses SET updated_at = created_at;
@@ -0,0 +1,8 @@
+-- The latest name seen for each Telegram user, so stats can show who someone is now
+-- rather than whatever username they voted under
+CREATE TABLE user_profiles (
+    user_id INTEGER PRIMARY KEY,
+    username TEXT,
+    display_name TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,17 @@
+-- How each attempt to deliver a reminder ended, so failed ones are retried and groups the bot
+-- can no longer post in are left alone for a while.
+
+CREATE TABLE IF NOT EXISTS reminder_deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL,
+    group_id INTEGER NOT NULL,
+    offset_minutes INTEGER NOT NULL,
+    outcome TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    error TEXT,
+    attempted_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminder_deliveries_session ON reminder_deliveries(session_id, offset_minutes);
+CREATE INDEX IF NOT EXISTS idx_reminder_deliveries_group ON reminder_deliveries(group_id, attempted_at);
@@ -0,0 +1,12 @@
+-- Who actually came to a confirmed session, marked afterwards with /attended. Players who
+-- voted yes for the confirmed time but weren't marked get a row with attended = 0 (a no-show).
+
+CREATE TABLE IF NOT EXISTS attendance (
+    session_id TEXT NOT NULL,
+    user_id INTEGER NOT NULL,
+    attended BOOLEAN NOT NULL,
+    marked_by INTEGER NOT NULL,
+    marked_at TEXT NOT NULL,
+    PRIMARY KEY (session_id, user_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
@@ -0,0 +1,27 @@
+-- Who belongs to each group, so polls can say how many of the players have voted. Filled in
+-- from Telegram's member updates, from anyone who sends a message or votes, and by /players add.
+-- Members who leave or are removed stay with active = 0.
+
+CREATE TABLE IF NOT EXISTS group_members (
+    group_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    username TEXT,
+    display_name TEXT,
+    active BOOLEAN NOT NULL DEFAULT 1,
+    last_seen TEXT NOT NULL,
+    PRIMARY KEY (group_id, user_id),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
+
+-- Everyone who voted or joined a roster before members were tracked
+INSERT OR IGNORE INTO group_members (group_id, user_id, username, display_name, active, last_seen)
+SELECT s.group_id, r.user_id,
+    (SELECT latest.username FROM responses latest JOIN sessions ls ON ls.id = latest.session_id
+     WHERE ls.group_id = s.group_id AND latest.user_id = r.user_id
+     ORDER BY latest.updated_at DESC LIMIT 1),
+    NULL, 1, MAX(r.updated_at)
+FROM responses r JOIN sessions s ON s.id = r.session_id
+GROUP BY s.group_id, r.user_id;
+
+INSERT OR IGNORE INTO group_members (group_id, user_id, username, display_name, active, last_seen)
+SELECT group_id, user_id, username, NULL, 1, joined_at FROM roster_members;
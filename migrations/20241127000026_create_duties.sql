@@ -0,0 +1,22 @@
+-- Chores a group rotates between sessions, such as hosting or bringing snacks, and who got
+-- each one for which confirmed session. The assignments decide whose turn it is next.
+
+CREATE TABLE IF NOT EXISTS duties (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    group_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (group_id, name COLLATE NOCASE),
+    FOREIGN KEY (group_id) REFERENCES groups(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS duty_assignments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL,
+    duty_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    assigned_at TEXT NOT NULL,
+    UNIQUE (session_id, duty_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (duty_id) REFERENCES duties(id) ON DELETE CASCADE
+);
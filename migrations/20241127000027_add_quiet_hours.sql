@@ -0,0 +1,4 @@
+-- Hours of the day, on the group's clock, in which no reminders go out: from quiet_start_hour
+-- until quiet_end_hour, wrapping around midnight when the start is later. NULL turns them off.
+ALTER TABLE groups ADD COLUMN quiet_start_hour INTEGER;
+ALTER TABLE groups ADD COLUMN quiet_end_hour INTEGER;
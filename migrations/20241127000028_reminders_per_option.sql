@@ -0,0 +1,23 @@
+-- A session can be confirmed for more than one option, e.g. two tables on different nights, and
+-- each confirmed option gets its own reminders. option_id is '' for reminders recorded for the
+-- whole session; the ones sent so far belong to the session's (only) confirmed option.
+CREATE TABLE reminders_per_option (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    option_id TEXT NOT NULL DEFAULT '',
+    offset_minutes INTEGER NOT NULL,
+    sent_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    UNIQUE(session_id, option_id, offset_minutes)
+);
+
+INSERT INTO reminders_per_option (id, session_id, option_id, offset_minutes, sent_at)
+SELECT r.id, r.session_id,
+       COALESCE((SELECT o.id FROM session_options o
+                 WHERE o.session_id = r.session_id AND o.confirmed = 1
+                 ORDER BY o.datetime LIMIT 1), ''),
+       r.offset_minutes, r.sent_at
+FROM reminders r;
+
+DROP TABLE reminders;
+ALTER TABLE reminders_per_option RENAME TO reminders;
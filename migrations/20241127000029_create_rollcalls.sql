@@ -0,0 +1,9 @@
+-- Roll calls: single-option sessions for a game later the same day, confirmed as soon as enough
+-- players say yes and cancelled if they haven't by the time the roll call expires.
+
+CREATE TABLE IF NOT EXISTS rollcalls (
+    session_id TEXT PRIMARY KEY,
+    quorum INTEGER NOT NULL,
+    expires_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
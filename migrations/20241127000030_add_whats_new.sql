@@ -0,0 +1,4 @@
+-- "What's new" announcements after upgrades. announced_version is the bot version a group last
+-- heard about, NULL for groups from before the announcements; whats_new lets a group opt out.
+ALTER TABLE groups ADD COLUMN announced_version TEXT;
+ALTER TABLE groups ADD COLUMN whats_new BOOLEAN NOT NULL DEFAULT TRUE;
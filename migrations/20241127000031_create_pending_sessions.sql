@@ -0,0 +1,12 @@
+-- Sessions /schedule held back because they look like one the group already has, until the
+-- scheduler picks "Create anyway" or "Cancel". options is a JSON array of
+-- {"starts_at": RFC 3339, "duration": minutes or null}.
+CREATE TABLE IF NOT EXISTS pending_sessions (
+    id TEXT PRIMARY KEY,
+    group_id INTEGER NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    created_by INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    anonymous BOOLEAN NOT NULL DEFAULT FALSE,
+    options TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
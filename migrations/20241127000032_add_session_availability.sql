@@ -0,0 +1,2 @@
+-- Availability polls ask which days work; their options get a start time once one is confirmed
+ALTER TABLE sessions ADD COLUMN availability BOOLEAN NOT NULL DEFAULT FALSE;
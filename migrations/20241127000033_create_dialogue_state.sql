@@ -0,0 +1,7 @@
+-- The question the bot is waiting for a reply to in each chat, so it survives a restart.
+-- state is the chat's DialogueState as JSON.
+CREATE TABLE IF NOT EXISTS dialogue_state (
+    chat_id INTEGER PRIMARY KEY,
+    state TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
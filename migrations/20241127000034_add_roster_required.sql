@@ -0,0 +1,3 @@
+-- Roster members the group can't play without: options they say no to aren't picked by /confirm
+
+ALTER TABLE roster_members ADD COLUMN required BOOLEAN NOT NULL DEFAULT FALSE;
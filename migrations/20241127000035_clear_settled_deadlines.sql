@@ -0,0 +1,3 @@
+-- Confirmed and cancelled sessions take no more responses, so a deadline on them means nothing
+
+UPDATE sessions SET deadline = NULL WHERE status IN ('confirmed', 'cancelled');
@@ -0,0 +1,3 @@
+-- Yes votes at which an option of a poll with a deadline confirms itself; NULL turns auto-confirm off
+
+ALTER TABLE groups ADD COLUMN autoconfirm_threshold INTEGER;
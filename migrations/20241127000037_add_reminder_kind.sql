@@ -0,0 +1,22 @@
+-- Reminders to vote on polls nobody answers go in the same table as the session reminders, told
+-- apart by kind: 'session' for reminders before a confirmed session, 'vote' for nudges on an
+-- active poll. A vote reminder's offset_minutes is how long the poll had been open when it went
+-- out, so the unique key has to include the kind.
+CREATE TABLE reminders_with_kind (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'session',
+    option_id TEXT NOT NULL DEFAULT '',
+    offset_minutes INTEGER NOT NULL,
+    sent_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    UNIQUE(session_id, kind, option_id, offset_minutes)
+);
+
+INSERT INTO reminders_with_kind (id, session_id, kind, option_id, offset_minutes, sent_at)
+SELECT id, session_id, 'session', option_id, offset_minutes, sent_at FROM reminders;
+
+DROP TABLE reminders;
+ALTER TABLE reminders_with_kind RENAME TO reminders;
+
+CREATE INDEX IF NOT EXISTS idx_reminders_session_kind ON reminders(session_id, kind);
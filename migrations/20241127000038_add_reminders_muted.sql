@@ -0,0 +1,4 @@
+-- /mute keeps a session's reminders from being sent; the sweep still marks them as sent, so
+-- /unmute only lets the later ones through
+
+ALTER TABLE sessions ADD COLUMN reminders_muted BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- Whether /stats shows the group's achievement badges; groups can turn the section off.
+ALTER TABLE groups ADD COLUMN badges BOOLEAN NOT NULL DEFAULT TRUE;
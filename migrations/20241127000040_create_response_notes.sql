@@ -0,0 +1,15 @@
+-- Short notes voters attach to an option of a poll, like "only after 20:00", with the 📝 button.
+-- Kept apart from the votes, so a note stays when the vote on the option changes.
+
+CREATE TABLE IF NOT EXISTS response_notes (
+    session_id TEXT NOT NULL,
+    option_id TEXT NOT NULL,
+    user_id INTEGER NOT NULL,
+    note TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (option_id, user_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (option_id) REFERENCES session_options(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_response_notes_session ON response_notes(session_id);
@@ -0,0 +1,31 @@
+-- A poll message only changes when the bot edits it, so it can drift from its session: options
+-- added, a deadline set. `last_rendered_at` is when the poll was last posted or edited and
+-- `updated_at` when the session or its options last changed, kept up by the triggers below. A
+-- vote on a poll rendered before the latest change refreshes it first.
+
+ALTER TABLE sessions ADD COLUMN last_rendered_at TEXT;
+ALTER TABLE sessions ADD COLUMN updated_at TEXT;
+
+CREATE TRIGGER sessions_touch_on_update
+AFTER UPDATE OF title, status, deadline, notes, anonymous, availability ON sessions
+BEGIN
+    UPDATE sessions SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER sessions_touch_on_option_insert
+AFTER INSERT ON session_options
+BEGIN
+    UPDATE sessions SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.session_id;
+END;
+
+CREATE TRIGGER sessions_touch_on_option_update
+AFTER UPDATE OF datetime, duration, confirmed ON session_options
+BEGIN
+    UPDATE sessions SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.session_id;
+END;
+
+CREATE TRIGGER sessions_touch_on_option_delete
+AFTER DELETE ON session_options
+BEGIN
+    UPDATE sessions SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = OLD.session_id;
+END;
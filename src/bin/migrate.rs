@@ -1,5 +1,8 @@
 use anyhow::{Result, anyhow};
 use dnd_scheduler_bot::database::connection::DatabaseManager;
+use dnd_scheduler_bot::database::dedupe::{self, DedupeAction};
+use dnd_scheduler_bot::database::models::Group;
+use dnd_scheduler_bot::database::transfer::{self, GroupArchive};
 use dnd_scheduler_bot::config::Config;
 use std::env;
 use std::io;
@@ -18,6 +21,9 @@ async fn main() -> Result<()> {
         "migrate" | "up" => run_migrations().await,
         "check" => check_database().await,
         "reset" => reset_database().await,
+        "dedupe-groups" => dedupe_groups(&args[2..]).await,
+        "export-group" => export_group(&args[2..]).await,
+        "import-group" => import_group(&args[2..]).await,
         "help" | "--help" | "-h" => {
             print_help();
             Ok(())
@@ -40,20 +46,10 @@ async fn run_migrations() -> Result<()> {
     
     println!("📊 Database URL: {}", mask_url(&config.database_url));
     
-    // Ensure data directory exists for SQLite
-    if config.database_url.starts_with("sqlite:") {
-        let db_path = config.database_url.strip_prefix("sqlite:").unwrap_or(&config.database_url);
-        if let Some(parent) = Path::new(db_path).parent() {
-            if !parent.exists() {
-                println!("📁 Creating directory: {}", parent.display());
-                std::fs::create_dir_all(parent)?;
-            }
-        }
-    }
-    
+    // Opening the database creates its directory if needed (unless RUNNING_IN_DOCKER expects a volume)
     println!("🚀 Running database migrations...");
     
-    let db_manager = DatabaseManager::new(&config.database_url).await
+    let db_manager = DatabaseManager::open(&config.database_url, config.pool_settings(), config.running_in_docker).await
         .map_err(|e| anyhow!("Failed to connect to database: {}", e))?;
     
     match db_manager.run_migrations().await {
@@ -78,7 +74,7 @@ async fn check_database() -> Result<()> {
     
     println!("📊 Database URL: {}", mask_url(&config.database_url));
     
-    let db_manager = DatabaseManager::new(&config.database_url).await
+    let db_manager = DatabaseManager::open(&config.database_url, config.pool_settings(), config.running_in_docker).await
         .map_err(|e| anyhow!("Failed to connect to database: {}", e))?;
     
     // Try to query the database to check if tables exist
@@ -134,6 +130,128 @@ async fn reset_database() -> Result<()> {
     Ok(())
 }
 
+async fn dedupe_groups(args: &[String]) -> Result<()> {
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let pairs = args.iter()
+        .filter(|arg| *arg != "--dry-run")
+        .map(|arg| parse_chat_id_pair(arg))
+        .collect::<Result<Vec<_>>>()?;
+    
+    println!("🧹 Merging duplicate groups{}", if dry_run { " (dry run, nothing is saved)" } else { "" });
+    
+    dotenvy::dotenv().ok();
+    let config = Config::from_env()?;
+    
+    println!("📊 Database URL: {}", mask_url(&config.database_url));
+    
+    let db_manager = DatabaseManager::open(&config.database_url, config.pool_settings(), config.running_in_docker).await
+        .map_err(|e| anyhow!("Failed to connect to database: {}", e))?;
+    
+    let mut actions = dedupe::find_shared_chat_id_duplicates(&db_manager.pool).await?;
+    for (old_chat_id, new_chat_id) in pairs {
+        match dedupe::plan_chat_id_migration(&db_manager.pool, old_chat_id, new_chat_id).await? {
+            Some(action) => actions.push(action),
+            None => println!("  • No group for chat {old_chat_id}, nothing to move to {new_chat_id}"),
+        }
+    }
+    
+    if actions.is_empty() {
+        println!("✅ No duplicate groups found");
+        return Ok(());
+    }
+    
+    for action in &actions {
+        let report = dedupe::apply(&db_manager.pool, action, dry_run).await
+            .map_err(|e| anyhow!("Failed to merge groups: {}", e))?;
+        match action {
+            DedupeAction::Merge { survivor, duplicate } => println!(
+                "  • Group {} (chat {}) into group {} (chat {}): {} sessions, {} bot admins, {} roster members, {} pseudonyms",
+                duplicate.id, duplicate.telegram_chat_id, survivor.id, survivor.telegram_chat_id,
+                report.sessions_moved, report.admins_moved, report.roster_moved, report.pseudonyms_moved
+            ),
+            DedupeAction::Rechat { group, new_chat_id } => println!(
+                "  • Group {} moves from chat {} to chat {}",
+                group.id, group.telegram_chat_id, new_chat_id
+            ),
+        }
+    }
+    
+    if dry_run {
+        println!("💡 Run again without --dry-run to apply these changes");
+    } else {
+        println!("✅ Merged {} duplicate groups", actions.len());
+    }
+    
+    Ok(())
+}
+
+async fn export_group(args: &[String]) -> Result<()> {
+    let chat_id = args.first()
+        .ok_or_else(|| anyhow!("Usage: migrate export-group CHAT_ID [FILE]"))?;
+    let chat_id = chat_id.trim().parse::<i64>().map_err(|_| anyhow!("Invalid chat ID '{}'", chat_id))?;
+    let file = args.get(1).cloned().unwrap_or_else(|| format!("dnd_group_{chat_id}.json"));
+    
+    println!("📦 Exporting the group of chat {chat_id}");
+    
+    dotenvy::dotenv().ok();
+    let config = Config::from_env()?;
+    
+    println!("📊 Database URL: {}", mask_url(&config.database_url));
+    
+    let db_manager = DatabaseManager::open(&config.database_url, config.pool_settings(), config.running_in_docker).await
+        .map_err(|e| anyhow!("Failed to connect to database: {}", e))?;
+    
+    let group = Group::find_by_chat_id(&db_manager.pool, chat_id).await?
+        .ok_or_else(|| anyhow!("No group for chat {}", chat_id))?;
+    let archive = transfer::export_group(&db_manager.pool, &group).await?;
+    std::fs::write(&file, serde_json::to_vec_pretty(&archive)?)?;
+    
+    println!(
+        "✅ Wrote {} sessions, {} options, {} votes and {} reminders to {file}",
+        archive.sessions.len(), archive.options.len(), archive.responses.len(), archive.reminders.len()
+    );
+    
+    Ok(())
+}
+
+async fn import_group(args: &[String]) -> Result<()> {
+    let file = args.first()
+        .ok_or_else(|| anyhow!("Usage: migrate import-group FILE [CHAT_ID]"))?;
+    let chat_id = args.get(1)
+        .map(|id| id.trim().parse::<i64>().map_err(|_| anyhow!("Invalid chat ID '{}'", id)))
+        .transpose()?;
+    let archive: GroupArchive = serde_json::from_slice(&std::fs::read(file)?)
+        .map_err(|e| anyhow!("{} is not a group export: {}", file, e))?;
+    
+    println!("📥 Importing {file} into chat {}", chat_id.unwrap_or(archive.group.telegram_chat_id));
+    
+    dotenvy::dotenv().ok();
+    let config = Config::from_env()?;
+    
+    println!("📊 Database URL: {}", mask_url(&config.database_url));
+    
+    let db_manager = DatabaseManager::open(&config.database_url, config.pool_settings(), config.running_in_docker).await
+        .map_err(|e| anyhow!("Failed to connect to database: {}", e))?;
+    
+    let report = transfer::import_group(&db_manager.pool, &archive, chat_id).await
+        .map_err(|e| anyhow!("Failed to import group: {}", e))?;
+    
+    println!(
+        "✅ Created group {} with {} sessions, {} options, {} votes and {} reminders",
+        report.group_id, report.sessions, report.options, report.responses, report.reminders
+    );
+    
+    Ok(())
+}
+
+/// Parse an `OLD_CHAT_ID:NEW_CHAT_ID` argument
+fn parse_chat_id_pair(arg: &str) -> Result<(i64, i64)> {
+    let (old, new) = arg.split_once(':')
+        .ok_or_else(|| anyhow!("Expected OLD_CHAT_ID:NEW_CHAT_ID, got '{}'", arg))?;
+    let parse = |id: &str| id.trim().parse::<i64>().map_err(|_| anyhow!("Invalid chat ID '{}' in '{}'", id, arg));
+    Ok((parse(old)?, parse(new)?))
+}
+
 async fn check_tables(db_manager: &DatabaseManager) -> Result<Vec<String>> {
     let rows = sqlx::query!("SELECT name FROM sqlite_master WHERE type='table'")
         .fetch_all(&db_manager.pool)
@@ -166,6 +284,14 @@ fn print_help() {
     println!("    migrate, up    Run database migrations (default)");
     println!("    check          Check database connection and schema");
     println!("    reset          Reset database (SQLite only) - DESTRUCTIVE!");
+    println!("    dedupe-groups [--dry-run] [OLD:NEW ...]");
+    println!("                   Merge groups sharing a chat ID, and groups of chats that moved");
+    println!("                   from chat ID OLD to NEW (e.g. after a supergroup upgrade)");
+    println!("    export-group CHAT_ID [FILE]");
+    println!("                   Write everything of a chat's group to a JSON file, the same");
+    println!("                   format /exportgroup sends (default: dnd_group_CHAT_ID.json)");
+    println!("    import-group FILE [CHAT_ID]");
+    println!("                   Create a group from such a file, for its own chat or CHAT_ID");
     println!("    help           Show this help message");
     println!();
     println!("ENVIRONMENT:");
@@ -175,5 +301,7 @@ fn print_help() {
     println!("    migrate                    # Run migrations");
     println!("    migrate check              # Check database status");
     println!("    migrate reset              # Reset database (careful!)");
+    println!("    migrate dedupe-groups --dry-run -123:-100123  # Preview merging a chat's old group");
+    println!("    migrate import-group dnd_group_-100123.json   # Restore a group export");
     println!();
 }
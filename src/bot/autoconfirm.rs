@@ -0,0 +1,75 @@
+//! Auto-confirm: polls with a deadline lock themselves in once an option gets as many "yes"
+//! votes as the group asked for under /settings
+
+use chrono::{DateTime, Utc};
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use crate::bot::commands::results::results_message;
+use crate::bot::duties::assign_session_duties;
+use crate::bot::poll::refresh_poll_message;
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::scheduling::{AutoConfirmation, SchedulerApi, SchedulingError};
+use crate::services::outbound_queue::{outbound_queue, telegram_result};
+use crate::utils::{datetime::format_datetime, feedback::CommandFeedback, i18n};
+
+/// Confirm the polls that reached their group's auto-confirm threshold by `now` and announce
+/// them; returns how many were confirmed
+pub async fn autoconfirm_sessions(bot: &Bot, db: &DatabaseManager, now: DateTime<Utc>) -> Result<usize, SchedulingError> {
+    let confirmed = SchedulerApi::new(db.pool.clone()).autoconfirm(now).await?;
+    for confirmation in &confirmed {
+        let session_id = &confirmation.session.id;
+        tracing::info!(
+            "Session {} reached {} yes votes and is confirmed automatically",
+            session_id, confirmation.threshold
+        );
+        if let Err(e) = announce(bot, db, confirmation).await {
+            tracing::warn!("Failed to announce auto-confirmed session {}: {}", session_id, e);
+        }
+    }
+    Ok(confirmed.len())
+}
+
+/// Tell the group its session is confirmed, the way /confirm would
+async fn announce(bot: &Bot, db: &DatabaseManager, confirmation: &AutoConfirmation) -> Result<(), BotError> {
+    let AutoConfirmation { session, selection, threshold } = confirmation;
+    let Some(group) = Group::find_by_id(&db.pool, session.group_id).await? else {
+        return Ok(());
+    };
+    let lang = group.lang();
+    let chat_id = ChatId(group.telegram_chat_id);
+
+    let option = &selection.winner.option;
+    let datetime = DateTime::parse_from_rfc3339(&option.datetime)
+        .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
+        .unwrap_or_else(|_| option.datetime.clone());
+    let mut details = vec![
+        i18n::t_autoconfirmed(lang, *threshold),
+        i18n::t_confirm_breakdown(lang, &selection.winner.counts, None),
+    ];
+    details.extend(session.notes.as_deref().map(|notes| i18n::t_notes(lang, notes)));
+    match assign_session_duties(&db.pool, &group, &session.id, &option.id).await {
+        Ok(duties) => details.extend(duties),
+        Err(e) => tracing::warn!("Failed to assign duties for session {}: {}", session.id, e),
+    }
+    let text = i18n::t_session_confirmed(lang, &session.title, &datetime, selection.winner.counts.yes, &details);
+    CommandFeedback::new(bot.clone(), chat_id)
+        .with_language(lang)
+        .logged(&db.pool, "confirmation", &session.id)
+        .success(&text)
+        .await?;
+
+    // The table and the revealed tallies follow the announcement through the chat's queue
+    let results = results_message(&db.pool, &group, session).await?;
+    let request = bot.send_message(chat_id, results).parse_mode(ParseMode::MarkdownV2);
+    outbound_queue().enqueue(chat_id, "results", move || {
+        let request = request.clone();
+        async move { request.await.map(drop) }
+    });
+    let (bot, db, session) = (bot.clone(), db.clone(), session.clone());
+    outbound_queue().enqueue(chat_id, "poll", move || {
+        let (bot, db, session) = (bot.clone(), db.clone(), session.clone());
+        async move { telegram_result("poll", refresh_poll_message(&bot, &db, &session, chat_id).await) }
+    });
+    Ok(())
+}
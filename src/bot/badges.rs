@@ -0,0 +1,98 @@
+//! Achievement badges for the "Badges" section of /stats
+//!
+//! Nothing is stored: the badges are worked out on every /stats from a few aggregates over the
+//! group's polls (see [`BadgeTallies`]), so they follow the votes as they change.
+
+/// An achievement a group or one of its players can earn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Badge {
+    /// A session everyone who voted said "yes" to its confirmed time (earned by the group)
+    FullHouse,
+    /// Scheduled [`IRON_DM_SESSIONS`] sessions that got confirmed
+    IronDm,
+    /// First to vote on [`EARLY_BIRD_POLLS`] polls
+    EarlyBird,
+}
+
+/// Voters a session needs before everyone saying "yes" makes it a full house
+pub const FULL_HOUSE_MIN_VOTERS: i64 = 3;
+
+/// Confirmed sessions a player has to have scheduled for the Iron DM badge
+pub const IRON_DM_SESSIONS: i64 = 10;
+
+/// Polls a player has to have voted on first for the Early bird badge
+pub const EARLY_BIRD_POLLS: i64 = 5;
+
+impl Badge {
+    /// Emoji shown in front of the badge
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Badge::FullHouse => "🏠",
+            Badge::IronDm => "🛡️",
+            Badge::EarlyBird => "🐦",
+        }
+    }
+
+    /// The badge's name
+    pub fn name(self) -> &'static str {
+        match self {
+            Badge::FullHouse => "Full house",
+            Badge::IronDm => "Iron DM",
+            Badge::EarlyBird => "Early bird",
+        }
+    }
+}
+
+/// The aggregates the badges are worked out from, one per badge
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BadgeTallies {
+    /// `(voters, yes_voters)` of each session with a confirmed time, where `yes_voters` said
+    /// "yes" to that time (see `Response::confirmed_turnout`)
+    pub confirmed_turnout: Vec<(i64, i64)>,
+    /// `(user_id, count)` of confirmed sessions per player who scheduled them
+    pub confirmed_by_creator: Vec<(i64, i64)>,
+    /// `(user_id, count)` of polls per player who voted on them first
+    pub first_votes: Vec<(i64, i64)>,
+}
+
+/// A badge someone has earned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Award {
+    /// Which badge
+    pub badge: Badge,
+    /// The player who earned it, `None` when the whole group did
+    pub user_id: Option<i64>,
+    /// How many times over: full houses, confirmed sessions or first votes
+    pub count: i64,
+}
+
+/// Number of full houses: sessions with at least [`FULL_HOUSE_MIN_VOTERS`] voters who all said
+/// "yes" to the confirmed time
+pub fn full_houses(confirmed_turnout: &[(i64, i64)]) -> i64 {
+    confirmed_turnout.iter()
+        .filter(|&&(voters, yes_voters)| voters >= FULL_HOUSE_MIN_VOTERS && yes_voters == voters)
+        .count() as i64
+}
+
+/// Players whose `count` reaches `threshold`, highest count first, ties by user ID
+fn holders(badge: Badge, counts: &[(i64, i64)], threshold: i64) -> Vec<Award> {
+    let mut awards: Vec<Award> = counts.iter()
+        .filter(|&&(_, count)| count >= threshold)
+        .map(|&(user_id, count)| Award { badge, user_id: Some(user_id), count })
+        .collect();
+    awards.sort_by(|a, b| b.count.cmp(&a.count).then(a.user_id.cmp(&b.user_id)));
+    awards
+}
+
+/// Every badge earned according to `tallies`: the group's full houses first, then Iron DMs and
+/// early birds, each with the highest count first
+pub fn award_badges(tallies: &BadgeTallies) -> Vec<Award> {
+    let mut awards = Vec::new();
+    let full_houses = full_houses(&tallies.confirmed_turnout);
+    if full_houses > 0 {
+        awards.push(Award { badge: Badge::FullHouse, user_id: None, count: full_houses });
+    }
+    awards.extend(holders(Badge::IronDm, &tallies.confirmed_by_creator, IRON_DM_SESSIONS));
+    awards.extend(holders(Badge::EarlyBird, &tallies.first_votes, EARLY_BIRD_POLLS));
+    awards
+}
@@ -0,0 +1,139 @@
+//! `/attended`: mark who actually came to a confirmed session, for the reliability in `/stats`
+
+use teloxide::prelude::*;
+use teloxide::types::MessageEntityKind;
+use crate::bot::commands::session_management::resolve_session;
+use crate::bot::permissions::{permission_denied_message, session_manager_role};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::utils::feedback::CommandFeedback;
+use std::collections::HashMap;
+
+/// Match `/attended` arguments to user IDs: `@username`s among `known` (ignoring case) and bare
+/// numeric user IDs
+///
+/// Returns the user IDs found, without duplicates, and the arguments that matched nobody.
+pub fn resolve_players(args: &[&str], known: &[(i64, Option<&str>)]) -> (Vec<i64>, Vec<String>) {
+    let mut found = Vec::new();
+    let mut unknown = Vec::new();
+    for arg in args {
+        let arg = arg.trim_matches(|c: char| c == ',' || c.is_whitespace());
+        if arg.is_empty() {
+            continue;
+        }
+        let user_id = match arg.strip_prefix('@') {
+            Some(username) => known.iter()
+                .find(|(_, known_name)| known_name.is_some_and(|name| name.eq_ignore_ascii_case(username)))
+                .map(|&(user_id, _)| user_id),
+            None => arg.parse::<i64>().ok().filter(|id| *id > 0),
+        };
+        match user_id {
+            Some(user_id) if !found.contains(&user_id) => found.push(user_id),
+            Some(_) => {}
+            None => unknown.push(arg.to_string()),
+        }
+    }
+    (found, unknown)
+}
+
+/// Pull the players picked from Telegram's mention list out of `args`, the text of `msg`
+///
+/// Players without a username can only be named this way. Returns their user IDs and what's
+/// left of `args`.
+pub fn take_text_mentions(msg: &Message, mut args: String) -> (Vec<i64>, String) {
+    let mut user_ids = Vec::new();
+    for entity in msg.parse_entities().unwrap_or_default() {
+        if let MessageEntityKind::TextMention { user } = entity.kind() {
+            user_ids.push(user.id.0 as i64);
+            args = args.replacen(entity.text(), "", 1);
+        }
+    }
+    (user_ids, args)
+}
+
+/// Record who came to a confirmed session; yes voters who aren't named become no-shows
+pub async fn handle_attended(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    players: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let Some((group, session)) = resolve_session(&feedback, db, chat_id, &session_id).await? else {
+        return Ok(());
+    };
+
+    if session_manager_role(&bot, db, msg.chat.id, &session, user_id).await.is_none() {
+        let error_msg = permission_denied_message("mark attendance");
+        feedback.validation_error(&error_msg, "Ask one of them to run this command.").await?;
+        return Ok(());
+    }
+
+    if session.status != "confirmed" {
+        let error_msg = format!("Session {} is {}, not confirmed", session.display_ref(), session.status);
+        feedback.validation_error(&error_msg, "Attendance can only be marked for confirmed sessions.").await?;
+        return Ok(());
+    }
+
+    let (mut attended, players) = take_text_mentions(&msg, players);
+
+    let args: Vec<&str> = players.split_whitespace().collect();
+    let usernames: Vec<&str> = args.iter().filter_map(|arg| arg.trim_end_matches(',').strip_prefix('@')).collect();
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    let roster = RosterMember::find_by_group(&db.pool, group.id).await?;
+    let profiles = UserProfile::find_by_usernames(&db.pool, &usernames).await?;
+    // Names from this session's votes win over older ones from the roster or other chats
+    let known: Vec<(i64, Option<&str>)> = responses.iter()
+        .map(|r| (r.user_id, r.username.as_deref()))
+        .chain(roster.iter().map(|member| (member.user_id, member.username.as_deref())))
+        .chain(profiles.iter().map(|profile| (profile.user_id, profile.username.as_deref())))
+        .collect();
+    let (found, unknown) = resolve_players(&args, &known);
+    for user_id in found {
+        if !attended.contains(&user_id) {
+            attended.push(user_id);
+        }
+    }
+
+    if attended.is_empty() {
+        let error_msg = format!("I don't know who {} is", unknown.join(", "));
+        let suggestion = "Mention players by @username as they voted, or by their numeric user ID.";
+        feedback.validation_error(&error_msg, suggestion).await?;
+        return Ok(());
+    }
+
+    let marked = Attendance::mark(&db.pool, &session.id, &attended, user_id).await?;
+    tracing::info!(
+        "User {} marked {} players as attending session {}",
+        user_id, attended.len(), session.id
+    );
+
+    let people: Vec<(i64, Option<&str>)> = marked.iter()
+        .map(|row| {
+            let username = known.iter().find(|(id, name)| *id == row.user_id && name.is_some()).and_then(|(_, name)| *name);
+            (row.user_id, username)
+        })
+        .collect();
+    let names: HashMap<i64, String> = display_names(&db.pool, &group, &people).await?;
+    let name_of = |user_id: i64| names.get(&user_id).cloned().unwrap_or_else(|| format!("User {user_id}"));
+    let came: Vec<String> = marked.iter().filter(|row| row.attended).map(|row| name_of(row.user_id)).collect();
+    let no_shows: Vec<String> = marked.iter().filter(|row| !row.attended).map(|row| name_of(row.user_id)).collect();
+
+    let mut text = format!(
+        "Attendance for {} ({}) saved\n\n✅ Came: {}\n🚫 No-shows: {}",
+        session.title,
+        session.display_ref(),
+        came.join(", "),
+        if no_shows.is_empty() { "none".to_string() } else { no_shows.join(", ") },
+    );
+    if !unknown.is_empty() {
+        text.push_str(&format!("\n❓ Not found, left out: {}", unknown.join(", ")));
+    }
+    feedback.success(&text).await?;
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//! `/availability`: ask which days of a week work instead of proposing start times
+//!
+//! The poll has one option per day. Confirming it asks whoever confirmed for the start time on
+//! the picked day, and the confirmation goes through once they reply.
+
+use chrono::{NaiveTime, Utc};
+use teloxide::prelude::*;
+use teloxide::types::MessageId;
+use crate::bot::commands::schedule::send_poll;
+use crate::bot::commands::session_management::handle_confirm;
+use crate::bot::dialogue::{exit, BotDialogue, DialogueState};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::scheduling::{SchedulerApi, SchedulingError};
+use crate::utils::{
+    datetime::{expand_days, extract_time_24h, format_date},
+    feedback::{CommandFeedback, FeedbackType},
+};
+
+const AVAILABILITY_USAGE: &str = "Give a title and a range: this week, next week, this weekend, next weekend or a week number like 2024-W51, e.g. /availability \"Session 12\" next week evenings";
+
+const START_TIME_HINT: &str = "Reply with a time like 19:30, or 'cancel' to leave the poll open.";
+
+/// Post a poll asking which days of `range` work for the group
+pub async fn handle_availability(
+    bot: Bot,
+    msg: Message,
+    title: String,
+    range: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let now = Utc::now();
+    let Some(days) = expand_days(&range, now.date_naive()) else {
+        feedback.validation_error(&format!("'{range}' isn't a range of days"), AVAILABILITY_USAGE).await?;
+        return Ok(());
+    };
+
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+
+    let api = SchedulerApi::new(db.pool.clone());
+    let (session, options) = match api.create_availability_poll(group.id, &title, user_id, &days, now).await {
+        Ok(created) => created,
+        Err(SchedulingError::Invalid(e)) => {
+            feedback.validation_error(&e, AVAILABILITY_USAGE).await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    tracing::info!("User {} started availability poll {} for {} days in group {}", user_id, session.id, options.len(), group.id);
+
+    send_poll(&bot, db, msg.chat.id, &session, &options).await?;
+    feedback.success(&format!(
+        "Availability poll {} is open for {} days. Once the votes are in, /confirm {} and I'll ask what time the game starts",
+        session.display_ref(), options.len(), session.display_ref(),
+    )).await?;
+
+    Ok(())
+}
+
+/// Instead of confirming an availability poll right away, pick the day and ask for its start time
+///
+/// The day is option `number`, or else the one `/confirm` would pick. The question replaces the
+/// "processing" message `question`; the reply is handled by [`handle_start_time_reply`].
+pub async fn ask_start_time(
+    feedback: &CommandFeedback,
+    question: MessageId,
+    session: &Session,
+    number: Option<usize>,
+    user_id: i64,
+    dialogue: BotDialogue,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let tally = SchedulerApi::new(db.pool.clone()).tally(&session.id).await?;
+    let option = match number {
+        Some(number) => number.checked_sub(1).and_then(|i| tally.options.get(i)),
+        None => tally.leader.as_ref().map(|selection| &selection.winner.option),
+    };
+    let Some(option) = option else {
+        let error = match number {
+            Some(number) => format!("The session has no option {number}; pick one from 1 to {}", tally.options.len()),
+            None => "Cannot confirm session: No days have 'yes' votes".to_string(),
+        };
+        feedback.validation_error(&error, "Ask players to vote first, or give the number of the day as on the poll.").await?;
+        return Ok(());
+    };
+
+    let day = chrono::DateTime::parse_from_rfc3339(&option.datetime)
+        .map(|dt| format_date(&dt.with_timezone(&Utc)))
+        .unwrap_or_else(|_| option.datetime.clone());
+    let state = DialogueState::AwaitingStartTime {
+        session_id: session.id.clone(),
+        option_id: option.id.clone(),
+        user_id,
+    };
+    if let Err(e) = dialogue.update(state).await {
+        tracing::error!("Failed to wait for the start time of session {}: {}", session.id, e);
+        feedback.error("Failed to ask for the start time").await?;
+        return Ok(());
+    }
+    tracing::info!("Asked user {} for the start time of session {} on {}", user_id, session.id, day);
+
+    let text = format!("🕖 {day} it is! What time does '{}' start? {START_TIME_HINT}", session.title);
+    feedback.update_message(question, FeedbackType::Info, &text).await?;
+    Ok(())
+}
+
+/// Handle the reply to [`ask_start_time`]: move the day's option to that time and confirm it
+pub async fn handle_start_time_reply(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    state: DialogueState,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let DialogueState::AwaitingStartTime { session_id, option_id, .. } = state else {
+        return Ok(());
+    };
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let text = msg.text().unwrap_or_default().trim();
+
+    if text.eq_ignore_ascii_case("cancel") {
+        exit(&dialogue).await;
+        feedback.info("Okay, the poll stays open").await?;
+        return Ok(());
+    }
+    let Some(time) = extract_time_24h(text).and_then(|(hour, minute)| NaiveTime::from_hms_opt(hour, minute, 0)) else {
+        feedback.validation_error(&format!("'{text}' isn't a time"), START_TIME_HINT).await?;
+        return Ok(());
+    };
+
+    let Some(option) = SessionOption::find_by_session(&db.pool, &session_id).await?
+        .into_iter()
+        .find(|option| option.id == option_id)
+    else {
+        exit(&dialogue).await;
+        feedback.validation_error("That day is no longer on the poll", "Use /list to see the session's current state.").await?;
+        return Ok(());
+    };
+    let day = chrono::DateTime::parse_from_rfc3339(&option.datetime)
+        .map(|dt| dt.with_timezone(&Utc).date_naive())
+        .map_err(|_| BotError::Validation("The picked day has no readable date".to_string()))?;
+    let starts_at = day.and_time(time).and_utc();
+
+    let api = SchedulerApi::new(db.pool.clone());
+    let number = match api.set_option_start(&session_id, &option_id, starts_at, Utc::now()).await {
+        Ok(number) => number,
+        Err(e @ SchedulingError::Invalid(_)) => {
+            feedback.validation_error(&e.to_string(), START_TIME_HINT).await?;
+            return Ok(());
+        }
+        Err(e @ (SchedulingError::NotActive { .. } | SchedulingError::NotFound { .. })) => {
+            exit(&dialogue).await;
+            feedback.validation_error(&e.to_string(), "Use /list to see the session's current state.").await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    exit(&dialogue).await;
+    tracing::info!("Session {} starts at {} on the picked day; confirming option {}", session_id, starts_at, number);
+
+    handle_confirm(bot, msg, session_id, Some(number), None, db).await
+}
@@ -0,0 +1,200 @@
+//! Game day check-ins: the "📍 Check in" button on the day-of reminder and `/checkins`
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode};
+use crate::bot::commands::session_management::resolve_session;
+use crate::bot::permissions::{permission_denied_message, session_manager_role};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::utils::{feedback::CommandFeedback, markdown::escape_markdown};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Keyboard with the "📍 Check in" button for a session's game day reminder
+pub fn checkin_keyboard(session_id: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("📍 Check in", format!("checkin:{session_id}")),
+    ]])
+}
+
+/// Render the `/checkins` overview as MarkdownV2
+///
+/// `checked_in` holds names with their check-in time, `silent` the names of players who voted
+/// yes for the confirmed time but haven't checked in.
+pub fn render_check_ins(
+    session: &Session,
+    checked_in: &[(String, DateTime<Utc>)],
+    silent: &[String],
+) -> String {
+    let mut text = format!(
+        "📍 **Check\\-ins for {}** {}\n\n",
+        escape_markdown(&session.title),
+        escape_markdown(&session.display_ref())
+    );
+
+    text.push_str(&format!("✅ **Checked in \\({}\\):**\n", checked_in.len()));
+    if checked_in.is_empty() {
+        text.push_str("• Nobody yet\n");
+    }
+    for (name, at) in checked_in {
+        text.push_str(&format!(
+            "• {} at {} UTC\n",
+            escape_markdown(name),
+            escape_markdown(&at.format("%H:%M").to_string())
+        ));
+    }
+
+    text.push_str(&format!("\n🤐 **Voted yes, no check\\-in \\({}\\):**\n", silent.len()));
+    if silent.is_empty() {
+        text.push_str("• Everyone who said yes has checked in\n");
+    }
+    for name in silent {
+        text.push_str(&format!("• {}\n", escape_markdown(name)));
+    }
+
+    text
+}
+
+/// Show who has checked in for a confirmed session and which yes voters haven't
+pub async fn handle_checkins(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let (group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    if session_manager_role(&bot, db, msg.chat.id, &session, user_id).await.is_none() {
+        let error_msg = permission_denied_message("view check-ins");
+        let suggestion = "Ask one of them to run this command, or use /list to see who created each session.";
+        feedback.validation_error(&error_msg, suggestion).await?;
+        return Ok(());
+    }
+
+    if session.status != "confirmed" {
+        let error_msg = format!("Session {} is {}, not confirmed", session.display_ref(), session.status);
+        let suggestion = "Check-ins are collected on the day of confirmed sessions. Use /confirm first.";
+        feedback.validation_error(&error_msg, suggestion).await?;
+        return Ok(());
+    }
+
+    let data = async {
+        let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+        let responses = Response::find_by_session(&db.pool, &session.id).await?;
+        let check_ins = CheckIn::find_by_session(&db.pool, &session.id).await?;
+        Ok::<_, sqlx::Error>((options, responses, check_ins))
+    }.await;
+    let (options, responses, check_ins) = match data {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::error!("Failed to load check-ins for session {}: {}", session.id, e);
+            feedback.error("Failed to retrieve check-ins from database").await?;
+            return Ok(());
+        }
+    };
+
+    let confirmed: Vec<&str> = options.iter().filter(|opt| opt.confirmed).map(|opt| opt.id.as_str()).collect();
+    let mut yes_voters: Vec<i64> = Vec::new();
+    for r in &responses {
+        if confirmed.contains(&r.option_id.as_str()) && r.response == "yes" && !yes_voters.contains(&r.user_id) {
+            yes_voters.push(r.user_id);
+        }
+    }
+
+    // Resolve names the same way as votes, so username privacy applies here too
+    let usernames: HashMap<i64, &str> = responses.iter()
+        .filter_map(|r| r.username.as_deref().map(|username| (r.user_id, username)))
+        .collect();
+    let people: Vec<(i64, Option<&str>)> = check_ins.iter()
+        .map(|check_in| check_in.user_id)
+        .chain(yes_voters.iter().copied())
+        .map(|user_id| (user_id, usernames.get(&user_id).copied()))
+        .collect();
+    let names = display_names(&db.pool, &group, &people).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to resolve names for session {}: {}", session.id, e);
+        HashMap::new()
+    });
+    let name_of = |user_id: i64| names.get(&user_id).cloned().unwrap_or_else(|| format!("User {user_id}"));
+
+    let checked_in: Vec<(String, DateTime<Utc>)> = check_ins.iter()
+        .map(|check_in| {
+            let at = DateTime::parse_from_rfc3339(&check_in.checked_in_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now());
+            (name_of(check_in.user_id), at)
+        })
+        .collect();
+    let silent: Vec<String> = yes_voters.iter()
+        .filter(|&&user_id| !check_ins.iter().any(|check_in| check_in.user_id == user_id))
+        .map(|&user_id| name_of(user_id))
+        .collect();
+
+    bot.send_message(msg.chat.id, render_check_ins(&session, &checked_in, &silent))
+        .parse_mode(ParseMode::MarkdownV2)
+        .await?;
+
+    Ok(())
+}
+
+/// Handle a `checkin:<session_id>` button press
+pub async fn handle_checkin_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This reminder has expired").await?;
+        return Ok(());
+    };
+    let session_id = data.strip_prefix("checkin:").unwrap_or(&data);
+
+    // Only sessions of this chat's group can be checked in to from here
+    let session = match (
+        Group::find_by_chat_id(&db.pool, message.chat.id.0).await,
+        Session::find_by_id(&db.pool, session_id).await,
+    ) {
+        (Ok(Some(group)), Ok(Some(session))) if session.group_id == group.id => session,
+        (Ok(_), Ok(_)) => {
+            bot.answer_callback_query(q.id).text("This session no longer exists").await?;
+            return Ok(());
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to load session {} for check-in: {}", session_id, e);
+            bot.answer_callback_query(q.id).text("Failed to retrieve the session").await?;
+            return Ok(());
+        }
+    };
+
+    if session.status != "confirmed" {
+        bot.answer_callback_query(q.id)
+            .text(format!("This session is {}, check-ins are closed", session.status))
+            .await?;
+        return Ok(());
+    }
+
+    let user_id = q.from.id.0 as i64;
+    match CheckIn::record(&db.pool, &session.id, user_id).await {
+        Ok(true) => {
+            tracing::info!("User {} checked in for session {}", user_id, session.id);
+            bot.answer_callback_query(q.id)
+                .text(format!("📍 Checked in for {}, see you there!", session.title))
+                .await?;
+        }
+        Ok(false) => {
+            bot.answer_callback_query(q.id).text("You've already checked in").await?;
+        }
+        Err(e) => {
+            tracing::error!("Failed to record check-in for session {}: {}", session.id, e);
+            bot.answer_callback_query(q.id).text("Failed to record your check-in").await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! `/duty`: the chores a group rotates between sessions, such as hosting or bringing snacks
+
+use teloxide::prelude::*;
+use crate::bot::duties::{duty_label, MAX_DUTY_NAME_LENGTH};
+use crate::bot::permissions::group_manager_role;
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::utils::feedback::CommandFeedback;
+
+const DUTY_USAGE: &str = "Use /duty to see the duties, or /duty add <name> and /duty remove <name>, e.g. /duty add hosting";
+
+/// A `/duty ...` subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DutyCommand {
+    /// Show the group's duties and who had each one last
+    List,
+    /// Add a duty to the rotation
+    Add(String),
+    /// Take a duty out of the rotation
+    Remove(String),
+}
+
+/// Parse the arguments of `/duty`, e.g. "add bring snacks"
+pub fn parse_duty_command(args: &str) -> Result<DutyCommand, String> {
+    let args = args.trim();
+    let (action, name) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    match action.to_lowercase().as_str() {
+        "" | "list" if name.is_empty() => Ok(DutyCommand::List),
+        "add" | "remove" if name.is_empty() => Err("No duty name given".to_string()),
+        "add" if name.chars().count() > MAX_DUTY_NAME_LENGTH => {
+            Err(format!("Duty names can be at most {MAX_DUTY_NAME_LENGTH} characters"))
+        }
+        "add" => Ok(DutyCommand::Add(name)),
+        "remove" => Ok(DutyCommand::Remove(name)),
+        "list" => Err("Too many arguments".to_string()),
+        action => Err(format!("Unknown duty action '{action}'")),
+    }
+}
+
+/// Handle `/duty [add|remove <name>]`
+pub async fn handle_duty(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let command = match parse_duty_command(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            feedback.validation_error(&e, DUTY_USAGE).await?;
+            return Ok(());
+        }
+    };
+
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+
+    let (adding, name) = match command {
+        DutyCommand::List => {
+            let duties = Duty::find_by_group(&db.pool, group.id).await?;
+            let history = DutyAssignment::find_by_group(&db.pool, group.id).await?;
+            let members = GroupMember::find_active_by_group(&db.pool, group.id).await?;
+            let holders: Vec<(i64, Option<&str>)> = history.iter()
+                .map(|a| {
+                    let username = members.iter().find(|m| m.user_id == a.user_id).and_then(|m| m.username.as_deref());
+                    (a.user_id, username)
+                })
+                .collect();
+            let names = display_names(&db.pool, &group, &holders).await?;
+            let lines: Vec<(String, Option<String>)> = duties.iter()
+                .map(|duty| {
+                    let last = history.iter().rev().find(|a| a.duty_id == duty.id);
+                    (duty_label(&duty.name), last.and_then(|a| names.get(&a.user_id).cloned()))
+                })
+                .collect();
+            feedback.info(&duties_summary(&lines)).await?;
+            return Ok(());
+        }
+        DutyCommand::Add(name) => (true, name),
+        DutyCommand::Remove(name) => (false, name),
+    };
+
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can change the duties";
+        let suggestion = "Ask a chat administrator to run this command.";
+        feedback.validation_error(error_msg, suggestion).await?;
+        return Ok(());
+    }
+
+    let label = duty_label(&name);
+    if adding {
+        if Duty::add(&db.pool, group.id, &name).await? {
+            tracing::info!("User {} added duty '{}' to group {}", from.id, name, group.id);
+            let text = format!("Added {label}. From the next confirmed session on, it goes round the players who said yes");
+            feedback.success(&text).await?;
+        } else {
+            feedback.info(&format!("The group already has {label}")).await?;
+        }
+    } else if Duty::remove(&db.pool, group.id, &name).await? {
+        tracing::info!("User {} removed duty '{}' from group {}", from.id, name, group.id);
+        feedback.success(&format!("Removed {label} and its past turns")).await?;
+    } else {
+        feedback.validation_error(&format!("The group has no duty called '{name}'"), DUTY_USAGE).await?;
+    }
+
+    Ok(())
+}
+
+/// The `/duty` list: each duty's label with who had it last, if anyone
+pub fn duties_summary(duties: &[(String, Option<String>)]) -> String {
+    if duties.is_empty() {
+        return "No duties yet. Add one with /duty add hosting; whenever a session is confirmed, \
+                each duty goes to one of the players who said yes, taking turns".to_string();
+    }
+
+    let mut text = String::from("🧾 Duties, taking turns among the yes voters of each confirmed session:\n");
+    for (label, last) in duties {
+        match last {
+            Some(name) => text.push_str(&format!("{label} - last: {name}\n")),
+            None => text.push_str(&format!("{label} - nobody yet\n")),
+        }
+    }
+    text
+}
@@ -0,0 +1,206 @@
+//! `/export`: send the group's sessions and votes as a CSV file, and `/exportgroup`: send
+//! everything the group has as a [`GroupArchive`] that `migrate import-group` can load
+
+use teloxide::prelude::*;
+use teloxide::types::InputFile;
+use teloxide::{ApiError, RequestError};
+use crate::bot::permissions::group_manager_role;
+use crate::database::{connection::DatabaseManager, models::*, transfer::{export_group, GroupArchive}};
+use crate::error::BotError;
+use crate::utils::{csv::push_csv_row, feedback::{CommandFeedback, ProgressTracker}};
+use std::collections::HashMap;
+
+/// Header row of the exported CSV
+pub const EXPORT_COLUMNS: [&str; 5] = ["session_title", "option_datetime", "username", "response", "created_at"];
+
+/// Largest group archive `/exportgroup` sends; Telegram takes documents of up to 50 MB from bots
+pub const MAX_GROUP_EXPORT_BYTES: usize = 20 * 1024 * 1024;
+
+/// Send all of the group's sessions, options and votes as `dnd_schedule_<chat_id>.csv`
+pub async fn handle_export(
+    bot: Bot,
+    msg: Message,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    let chat_id = msg.chat.id.0;
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    tracing::info!("Export requested in chat {}", chat_id);
+    let processing_msg = feedback.send_processing("Collecting session data...").await?;
+
+    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            let error_msg = "No sessions found for this group";
+            let suggestion = "Create your first session with /schedule \"Session Title\" \"Friday 19:00, Saturday 14:30\"";
+            feedback.validation_error(error_msg, suggestion).await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            feedback.error("Failed to retrieve group information from database").await?;
+            return Ok(());
+        }
+    };
+
+    let export_data = async {
+        let sessions = Session::find_by_group(&db.pool, group.id).await?;
+        let session_ids: Vec<String> = sessions.iter().map(|s| s.id.clone()).collect();
+        let options = SessionOption::find_by_sessions(&db.pool, &session_ids).await?;
+        let responses = Response::find_all_by_group(&db.pool, group.id).await?;
+        Ok::<_, sqlx::Error>((sessions, options, responses))
+    }.await;
+
+    let (sessions, options, responses) = match export_data {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::error!("Failed to collect export data for group {}: {}", group.id, e);
+            feedback.error("Failed to retrieve session data from database").await?;
+            return Ok(());
+        }
+    };
+
+    if sessions.is_empty() {
+        feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Info,
+            "Nothing to export yet. Create a session with /schedule first.").await?;
+        return Ok(());
+    }
+
+    let csv = build_export_csv(&sessions, &options, &responses);
+    let file_name = format!("dnd_schedule_{chat_id}.csv");
+
+    bot.send_document(msg.chat.id, InputFile::memory(csv.into_bytes()).file_name(file_name))
+        .caption(format!("📎 {} sessions, {} responses", sessions.len(), responses.len()))
+        .await?;
+
+    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success,
+        &format!("Exported {} sessions and {} responses", sessions.len(), responses.len())).await?;
+
+    Ok(())
+}
+
+/// Build the export CSV: one row per response, plus a row with empty vote columns for options nobody answered
+pub fn build_export_csv(
+    sessions: &[Session],
+    options: &[SessionOption],
+    responses: &[Response],
+) -> String {
+    let mut options_by_session: HashMap<&str, Vec<&SessionOption>> = HashMap::new();
+    for option in options {
+        options_by_session.entry(option.session_id.as_str()).or_default().push(option);
+    }
+
+    let mut responses_by_option: HashMap<&str, Vec<&Response>> = HashMap::new();
+    for response in responses {
+        responses_by_option.entry(response.option_id.as_str()).or_default().push(response);
+    }
+
+    let mut csv = String::new();
+    push_csv_row(&mut csv, &EXPORT_COLUMNS);
+
+    for session in sessions {
+        for option in options_by_session.get(session.id.as_str()).into_iter().flatten() {
+            match responses_by_option.get(option.id.as_str()) {
+                Some(option_responses) => {
+                    for response in option_responses {
+                        push_csv_row(&mut csv, &[
+                            session.title.as_str(),
+                            option.datetime.as_str(),
+                            response.username.as_deref().unwrap_or(""),
+                            response.response.as_str(),
+                            response.created_at.as_str(),
+                        ]);
+                    }
+                }
+                None => push_csv_row(&mut csv, &[session.title.as_str(), option.datetime.as_str(), "", "", ""]),
+            }
+        }
+    }
+
+    csv
+}
+
+/// The archive as the pretty-printed JSON file `/exportgroup` sends
+pub fn archive_file(archive: &GroupArchive) -> Result<Vec<u8>, serde_json::Error> {
+    serde_json::to_vec_pretty(archive)
+}
+
+/// Send the group's whole archive as `dnd_group_<chat_id>.json`, to the chat or, with `private`,
+/// to the admin who asked (chat administrators and the group's bot admins only)
+pub async fn handle_export_group(
+    bot: Bot,
+    msg: Message,
+    private: bool,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+
+    let Some(group) = Group::find_by_chat_id(&db.pool, chat_id).await? else {
+        let suggestion = "Create your first session with /schedule \"Session Title\" \"Friday 19:00, Saturday 14:30\"";
+        feedback.validation_error("This group has nothing to export yet", suggestion).await?;
+        return Ok(());
+    };
+
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can export the whole group";
+        feedback.validation_error(error_msg, "Use /export for a CSV of the sessions and votes.").await?;
+        return Ok(());
+    }
+
+    tracing::info!("Group export of group {} requested by user {} (private: {})", group.id, from.id, private);
+    let mut progress = ProgressTracker::new(feedback, 3);
+    progress.start("Collecting sessions, votes and reminders...").await?;
+
+    let archive = match export_group(&db.pool, &group).await {
+        Ok(archive) => archive,
+        Err(e) => {
+            tracing::error!("Failed to collect the archive of group {}: {}", group.id, e);
+            progress.error("Failed to retrieve the group's data from the database").await?;
+            return Ok(());
+        }
+    };
+
+    progress.next_step("Packing the export...").await?;
+    let file = match archive_file(&archive) {
+        Ok(file) => file,
+        Err(e) => {
+            tracing::error!("Failed to serialize the archive of group {}: {}", group.id, e);
+            progress.error("Failed to pack the export").await?;
+            return Ok(());
+        }
+    };
+    if file.len() > MAX_GROUP_EXPORT_BYTES {
+        progress.error(&format!(
+            "The export is {:.1} MB, more than the {} MB I can send. Ask the bot's operator to run `migrate export-group {chat_id}` instead.",
+            file.len() as f64 / (1024.0 * 1024.0),
+            MAX_GROUP_EXPORT_BYTES / (1024 * 1024),
+        )).await?;
+        return Ok(());
+    }
+
+    progress.next_step("Sending the file...").await?;
+    let destination = if private { ChatId(from.id.0 as i64) } else { msg.chat.id };
+    let caption = format!(
+        "📦 {} sessions, {} options, {} votes, {} reminders",
+        archive.sessions.len(), archive.options.len(), archive.responses.len(), archive.reminders.len()
+    );
+    let sent = bot.send_document(destination, InputFile::memory(file).file_name(format!("dnd_group_{chat_id}.json")))
+        .caption(caption)
+        .await;
+    match sent {
+        Ok(_) => {}
+        Err(RequestError::Api(ApiError::CantInitiateConversation | ApiError::BotBlocked)) if private => {
+            progress.error("I can't message you privately. Open a chat with me, press Start and try again.").await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    let completion = if private { "Sent the group export to you privately" } else { "Exported the whole group" };
+    progress.complete(completion).await?;
+    Ok(())
+}
@@ -0,0 +1,314 @@
+//! `/invite`: a forwardable introduction for new players, with a deep link onto the group's roster,
+//! and the `/start` deep links that the bot's messages carry
+
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode};
+use crate::bot::poll::{expected_voters, render_private_poll};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::scheduling::VOTABLE_STATUSES;
+use crate::services::reminder::next_confirmed_session;
+use crate::utils::{
+    datetime::{format_datetime, format_duration},
+    feedback::CommandFeedback,
+    markdown::escape_markdown,
+    outbound::{logged_send, plain_fallback},
+};
+use chrono::{DateTime, Utc};
+use std::sync::OnceLock;
+
+/// `/start` payload prefix of invite links, followed by the group's Telegram chat ID
+pub const JOIN_PAYLOAD_PREFIX: &str = "join_";
+
+/// `/start` payload prefix of vote links, followed by the session's ID
+pub const VOTE_PAYLOAD_PREFIX: &str = "vote_";
+
+/// What a `/start <payload>` deep link asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartPayload {
+    /// Join a group's roster
+    Join {
+        /// The group's Telegram chat ID
+        chat_id: i64,
+    },
+    /// Get a session's poll in the private chat to vote there
+    Vote {
+        /// The session's ID
+        session_id: String,
+    },
+}
+
+/// Parse the payload of a `/start` deep link, `None` if it isn't one we know
+pub fn parse_start_payload(payload: &str) -> Option<StartPayload> {
+    let payload = payload.trim();
+    if let Some(chat_id) = payload.strip_prefix(JOIN_PAYLOAD_PREFIX) {
+        return chat_id.parse().ok().map(|chat_id| StartPayload::Join { chat_id });
+    }
+    if let Some(session_id) = payload.strip_prefix(VOTE_PAYLOAD_PREFIX) {
+        // Telegram only passes letters, digits, '_' and '-' in payloads, which session IDs keep to
+        let valid = !session_id.is_empty() && session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        return valid.then(|| StartPayload::Vote { session_id: session_id.to_string() });
+    }
+    None
+}
+
+/// Deep link that opens a private chat with the bot and joins the group's roster
+pub fn invite_link(bot_username: &str, chat_id: i64) -> String {
+    format!("https://t.me/{bot_username}?start={JOIN_PAYLOAD_PREFIX}{chat_id}")
+}
+
+/// Deep link that opens a private chat with the bot and delivers the session's poll there
+pub fn vote_link(bot_username: &str, session_id: &str) -> String {
+    format!("https://t.me/{bot_username}?start={VOTE_PAYLOAD_PREFIX}{session_id}")
+}
+
+/// The bot's username for deep links, asked from Telegram once and then remembered
+pub async fn bot_username(bot: &Bot) -> Option<String> {
+    static USERNAME: OnceLock<String> = OnceLock::new();
+    if let Some(username) = USERNAME.get() {
+        return Some(username.clone());
+    }
+    match bot.get_me().await {
+        Ok(me) => me.username.clone().map(|username| USERNAME.get_or_init(|| username).clone()),
+        Err(e) => {
+            tracing::warn!("Failed to get the bot's username for a deep link: {}", e);
+            None
+        }
+    }
+}
+
+/// Render the invitation as MarkdownV2
+///
+/// `next_session` is the upcoming confirmed session with its start, `join_link` the roster deep
+/// link (left out when the bot's username is unknown).
+pub fn render_invite(
+    group: &Group,
+    next_session: Option<(&Session, DateTime<Utc>)>,
+    join_link: Option<&str>,
+) -> String {
+    let mut text = String::from("👋 **Welcome to our D&D group\\!**\n\nHere's how we schedule our sessions\\.\n\n");
+
+    text.push_str("🗳️ **How voting works**\n");
+    text.push_str(&escape_markdown("• Someone posts a poll with /schedule listing a few possible times\n"));
+    text.push_str(&escape_markdown("• Tap ✅ Yes, ❓ Maybe or ❌ No on every time\n"));
+    text.push_str(&escape_markdown("• The best time gets locked in with /confirm, and reminders follow\n\n"));
+
+    text.push_str("⚙️ **Our setup**\n");
+    text.push_str(&format!("• Timezone: {}\n", escape_markdown(&group.timezone)));
+    text.push_str(&format!(
+        "• Sessions usually last {}\n",
+        escape_markdown(&format_duration(group.default_duration))
+    ));
+    if group.min_notice_hours > 0 {
+        text.push_str(&format!("• New times need at least {} hours' notice\n", group.min_notice_hours));
+    }
+    text.push('\n');
+
+    match next_session {
+        Some((session, starts_at)) => text.push_str(&format!(
+            "🎲 **Next session:** {} on {} UTC\n",
+            escape_markdown(&session.title),
+            escape_markdown(&format_datetime(&starts_at))
+        )),
+        None => text.push_str("🎲 **Next session:** nothing confirmed yet, watch out for the next poll\n"),
+    }
+
+    if let Some(link) = join_link {
+        let link = link.replace('\\', "\\\\").replace(')', "\\)");
+        text.push_str(&format!("\n👉 [Join the roster]({link}) to get scheduled with us\n"));
+    }
+
+    text
+}
+
+/// Post the invitation for this group, ready to be forwarded to a new player
+pub async fn handle_invite(
+    bot: Bot,
+    msg: Message,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    let chat_id = msg.chat.id.0;
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    if msg.chat.id.is_user() {
+        let suggestion = "Run /invite in your group chat, then forward the message to the new player.";
+        feedback.validation_error("Invitations are made from a group chat", suggestion).await?;
+        return Ok(());
+    }
+
+    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
+        Ok(Some(group)) => group,
+        Ok(None) => match Group::create(&db.pool, chat_id).await {
+            Ok(group) => group,
+            Err(e) => {
+                tracing::error!("Failed to create group for chat {}: {}", chat_id, e);
+                feedback.error("Failed to set up group information").await?;
+                return Ok(());
+            }
+        },
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            feedback.error("Failed to retrieve group information from database").await?;
+            return Ok(());
+        }
+    };
+
+    let next = next_confirmed_session(&db.pool, group.id, Utc::now()).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to find the next session of group {}: {}", group.id, e);
+        None
+    });
+
+    let join_link = bot_username(&bot).await.map(|username| invite_link(&username, chat_id));
+
+    let text = render_invite(
+        &group,
+        next.as_ref().map(|(session, _, starts_at)| (session, *starts_at)),
+        join_link.as_deref(),
+    );
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::MarkdownV2)
+        .await?;
+
+    Ok(())
+}
+
+/// Handle `/start <payload>` deep links, such as the roster link from `/invite` and the vote
+/// links in reminders
+pub async fn handle_start_payload(
+    bot: Bot,
+    msg: Message,
+    payload: String,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    match parse_start_payload(&payload) {
+        Some(StartPayload::Join { chat_id }) => handle_join_link(bot, msg, chat_id, db).await,
+        Some(StartPayload::Vote { session_id }) => handle_vote_link(bot, msg, session_id, db).await,
+        None => {
+            tracing::debug!("Ignoring unknown start payload '{}'", payload);
+            CommandFeedback::new(bot, msg.chat.id)
+                .info("This link isn't valid (anymore). Use /help to see what I can do.")
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+/// Put the user on the roster of the group with Telegram chat ID `chat_id`
+async fn handle_join_link(
+    bot: Bot,
+    msg: Message,
+    chat_id: i64,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let Some(user) = msg.from() else {
+        return Ok(());
+    };
+
+    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            let suggestion = "Ask the group for a fresh link; someone can post one with /invite.";
+            feedback.validation_error("This invite link doesn't belong to a known group", suggestion).await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group for invite link: {}", e);
+            feedback.error("Failed to retrieve group information from database").await?;
+            return Ok(());
+        }
+    };
+
+    // Same privacy rule as for votes: no usernames for groups that opted out
+    let username = if group.store_usernames { user.username.clone() } else { None };
+    match RosterMember::add(&db.pool, group.id, user.id.0 as i64, username).await {
+        Ok(true) => {
+            tracing::info!("User {} joined the roster of group {}", user.id, group.id);
+            feedback.success("You're on the roster! Polls are posted in the group chat, vote there with the buttons.").await?;
+        }
+        Ok(false) => {
+            feedback.info("You're already on the roster of this group.").await?;
+        }
+        Err(e) => {
+            tracing::error!("Failed to add user {} to the roster of group {}: {}", user.id, group.id, e);
+            feedback.error("Failed to add you to the roster").await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `user_id` is in `group`'s chat; a failed lookup counts as no
+pub(crate) async fn is_group_member(bot: &Bot, group: &Group, user_id: UserId) -> bool {
+    bot.get_chat_member(ChatId(group.telegram_chat_id), user_id).await
+        .is_ok_and(|member| member.is_present())
+}
+
+/// Send the session's poll into the private chat, with buttons that vote just like the group's
+///
+/// Only members of the session's group get it, since the link can be forwarded anywhere.
+async fn handle_vote_link(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let Some(user) = msg.from() else {
+        return Ok(());
+    };
+    if !msg.chat.is_private() {
+        feedback.info("Vote links open the poll in a private chat with me; tap the link there.").await?;
+        return Ok(());
+    }
+
+    let found = match Session::find_by_id(&db.pool, &session_id).await {
+        Ok(Some(session)) => Group::find_by_id(&db.pool, session.group_id).await.map(|group| group.map(|group| (session, group))),
+        Ok(None) => Ok(None),
+        Err(e) => Err(e),
+    };
+    let (session, group) = match found {
+        Ok(Some(found)) => found,
+        Ok(None) => {
+            feedback.info("This poll no longer exists.").await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find session {} for a vote link: {}", session_id, e);
+            feedback.error("Failed to retrieve session information from database").await?;
+            return Ok(());
+        }
+    };
+
+    if !is_group_member(&bot, &group, user.id).await {
+        tracing::info!("User {} followed a vote link to session {} outside their groups", user.id, session.id);
+        feedback.validation_error(
+            "Only members of the group can vote on this session",
+            "Ask someone in the group for an /invite link.",
+        ).await?;
+        return Ok(());
+    }
+    if !VOTABLE_STATUSES.contains(&session.status.as_str()) {
+        feedback.info(&format!("Voting on {} has ended; the session is {}.", session.title, session.status)).await?;
+        return Ok(());
+    }
+
+    let (options, responses, expected) = match (
+        SessionOption::find_by_session(&db.pool, &session.id).await,
+        Response::find_by_session(&db.pool, &session.id).await,
+        expected_voters(&db.pool, &session).await,
+    ) {
+        (Ok(options), Ok(responses), Ok(expected)) => (options, responses, expected),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            tracing::error!("Failed to load the poll of session {}: {}", session.id, e);
+            feedback.error("Failed to retrieve session time options and responses").await?;
+            return Ok(());
+        }
+    };
+    let (text, keyboard) = render_private_poll(&session, &options, &responses, expected);
+    let request = bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(keyboard);
+    logged_send(&db.pool, msg.chat.id, "private_poll", Some(&session.id), plain_fallback(request)).await?;
+
+    Ok(())
+}
@@ -1,14 +1,25 @@
 use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode};
+use crate::error::BotError;
+use crate::bot::poll::label_options;
+use crate::bot::selection::select_confirmable_option;
+use crate::services::metrics::timed;
 use crate::database::{connection::DatabaseManager, models::*};
-use crate::utils::{datetime::format_datetime, markdown::escape_markdown, feedback::CommandFeedback};
-use chrono::Utc;
+use crate::utils::{datetime::{format_datetime, format_duration, format_relative_time}, markdown::escape_markdown, feedback::CommandFeedback};
+use chrono::{DateTime, Duration, Utc};
 use std::collections::HashMap;
 
+/// Number of sessions shown per `/list` page, keeping messages well under Telegram's 4096-character limit
+pub const LIST_PAGE_SIZE: usize = 5;
+
+/// Statuses of the sessions `/list` shows
+pub const LIST_STATUSES: [&str; 2] = ["active", "confirmed"];
+
 pub async fn handle_list(
     bot: Bot,
     msg: Message,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
+) -> Result<(), BotError> {
     let chat_id = msg.chat.id.0;
     let user_id = msg.from().map(|u| u.id.0).unwrap_or(0);
     let username = msg.from().and_then(|u| u.username.as_ref()).map_or("unknown", |v| v);
@@ -46,18 +57,18 @@ pub async fn handle_list(
     
     // Get all active sessions for this group with timeout
     tracing::debug!("Fetching sessions for group_id: {}", group.id);
-    let sessions = match tokio::time::timeout(
+    let (page_sessions, page, total_pages, total_sessions) = match tokio::time::timeout(
         std::time::Duration::from_secs(10),
-        get_sessions_by_group(&db.pool, group.id)
+        timed("list_sessions", load_list_page(&db.pool, group.id, 0))
     ).await {
-        Ok(Ok(sessions)) => {
+        Ok(Ok(loaded)) => {
             tracing::info!(
                 "Found {} sessions for group {} in chat {}",
-                sessions.len(), group.id, chat_id
+                loaded.3, group.id, chat_id
             );
             feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-                &format!("Found {} sessions, loading details...", sessions.len())).await?;
-            sessions
+                &format!("Found {} sessions, loading details...", loaded.3)).await?;
+            loaded
         },
         Ok(Err(e)) => {
             tracing::error!("Database error fetching sessions for group {}: {}", group.id, e);
@@ -71,23 +82,21 @@ pub async fn handle_list(
         }
     };
     
-    if sessions.is_empty() {
+    if total_sessions == 0 {
         tracing::info!("No active sessions found for group {} in chat {}", group.id, chat_id);
         let info_message = "No active sessions found\\n\\n📋 This group doesn't have any active or confirmed sessions\\n\\n💡 Create your first session with:\\n`/schedule \"Session Title\" \"Friday 19:00, Saturday 14:30\"`";
         feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Info, info_message).await?;
         return Ok(());
     }
     
-    let mut message_text = String::from("📋 **Active Sessions**\n\n");
-    
-    // Batch fetch all session options and responses to avoid N+1 queries
-    let session_ids: Vec<String> = sessions.iter().map(|s| s.id.clone()).collect();
+    // Batch fetch session options and responses for this page to avoid N+1 queries
+    let session_ids: Vec<String> = page_sessions.iter().map(|s| s.id.clone()).collect();
     
     // Add timeout and better error handling for batch operations
     tracing::debug!("Fetching session options for {} sessions: {:?}", session_ids.len(), session_ids);
     let all_options = match tokio::time::timeout(
         std::time::Duration::from_secs(10),
-        SessionOption::find_by_sessions(&db.pool, &session_ids)
+        timed("list_options", SessionOption::find_by_sessions(&db.pool, &session_ids))
     ).await {
         Ok(Ok(options)) => {
             tracing::debug!("Successfully fetched {} session options", options.len());
@@ -111,7 +120,7 @@ pub async fn handle_list(
     tracing::debug!("Fetching responses for {} sessions: {:?}", session_ids.len(), session_ids);
     let all_responses = match tokio::time::timeout(
         std::time::Duration::from_secs(10),
-        Response::find_by_sessions(&db.pool, &session_ids)
+        timed("list_responses", Response::find_by_sessions(&db.pool, &session_ids))
     ).await {
         Ok(Ok(responses)) => {
             tracing::debug!("Successfully fetched {} responses", responses.len());
@@ -131,111 +140,393 @@ pub async fn handle_list(
         }
     };
     
+    // Without the member count the list just leaves out how many of them voted
+    let members = match GroupMember::count_active(&db.pool, group.id).await {
+        Ok(members) => members as usize,
+        Err(e) => {
+            tracing::warn!("Failed to count the members of group {}: {}", group.id, e);
+            0
+        }
+    };
+    let required = RosterMember::required_user_ids(&db.pool, group.id).await?;
+    // Notes are extra; without them the list still shows the votes
+    let notes = NotesByOption::load(&db.pool, &group, &session_ids).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load the voters' notes for sessions {:?}: {}", session_ids, e);
+        NotesByOption::default()
+    });
+    
+    let (message_text, keyboard) = render_list_page(&page_sessions, &all_options, &all_responses, &notes, members, &required, page, total_pages, total_sessions);
+    
+    // Send the first page, with navigation buttons if there is more than one.
+    // The page is already MarkdownV2, so it's sent directly rather than through the feedback helper.
+    let result = bot.edit_message_text(msg.chat.id, processing_msg.id, message_text)
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(keyboard)
+        .await;
+    if let Err(e) = result {
+        tracing::error!("Failed to show list page for group {}: {}", group.id, e);
+        feedback.error("Failed to display the session list, please try again").await?;
+    }
+    
+    Ok(())
+}
+
+/// Handle "◀ Prev / Next ▶" presses on a paginated `/list` message (`list:page:N`)
+pub async fn handle_list_page_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This list has expired, use /list again").await?;
+        return Ok(());
+    };
+    
+    let Some(requested_page) = data.strip_prefix("list:page:").and_then(|p| p.parse::<usize>().ok()) else {
+        bot.answer_callback_query(q.id).text("Invalid request").await?;
+        return Ok(());
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            bot.answer_callback_query(q.id).text("No sessions found for this group").await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to retrieve group information").await?;
+            return Ok(());
+        }
+    };
+    
+    // Re-query so the page reflects sessions created or closed since the list was sent
+    let page_data = async {
+        let (page_sessions, page, total_pages, total) = timed("list_sessions", load_list_page(&db.pool, group.id, requested_page)).await?;
+        let session_ids: Vec<String> = page_sessions.iter().map(|s| s.id.clone()).collect();
+        let options = timed("list_options", SessionOption::find_by_sessions(&db.pool, &session_ids)).await?;
+        let responses = timed("list_responses", Response::find_by_sessions(&db.pool, &session_ids)).await?;
+        let members = GroupMember::count_active(&db.pool, group.id).await? as usize;
+        let required = RosterMember::required_user_ids(&db.pool, group.id).await?;
+        let notes = NotesByOption::load(&db.pool, &group, &session_ids).await?;
+        let (text, keyboard) = render_list_page(&page_sessions, &options, &responses, &notes, members, &required, page, total_pages, total);
+        Ok::<_, sqlx::Error>((text, keyboard, total == 0))
+    }.await;
+    
+    let (message_text, keyboard, is_empty) = match page_data {
+        Ok(page_data) => page_data,
+        Err(e) => {
+            tracing::error!("Failed to load list page {} for group {}: {}", requested_page, group.id, e);
+            bot.answer_callback_query(q.id).text("Failed to load sessions").await?;
+            return Ok(());
+        }
+    };
+    
+    bot.answer_callback_query(q.id).await?;
+    
+    if is_empty {
+        bot.edit_message_text(message.chat.id, message.id, "📋 No active or confirmed sessions left\\.")
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+        return Ok(());
+    }
+    
+    let result = bot.edit_message_text(message.chat.id, message.id, message_text)
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(keyboard)
+        .await;
+    if let Err(e) = result {
+        // Telegram rejects edits that don't change anything, e.g. a double tap on the same button
+        tracing::warn!("Failed to edit list message to page {}: {}", requested_page, e);
+    }
+    
+    Ok(())
+}
+
+/// Clamp `page` to the available pages and return `(page, total_pages, index range)`
+///
+/// Pages are zero-based; an empty list still has a single (empty) page.
+pub fn paginate(total: usize, page: usize) -> (usize, usize, std::ops::Range<usize>) {
+    let total_pages = total.div_ceil(LIST_PAGE_SIZE).max(1);
+    let page = page.min(total_pages - 1);
+    let start = page * LIST_PAGE_SIZE;
+    let end = (start + LIST_PAGE_SIZE).min(total);
+    (page, total_pages, start..end)
+}
+
+/// Build the "◀ Prev / Next ▶" row for a list page, or `None` if everything fits on one page
+pub fn list_page_keyboard(page: usize, total_pages: usize) -> Option<InlineKeyboardMarkup> {
+    if total_pages <= 1 {
+        return None;
+    }
+    
+    let mut row = Vec::new();
+    if page > 0 {
+        row.push(InlineKeyboardButton::callback("◀ Prev", format!("list:page:{}", page - 1)));
+    }
+    if page + 1 < total_pages {
+        row.push(InlineKeyboardButton::callback("Next ▶", format!("list:page:{}", page + 1)));
+    }
+    
+    Some(InlineKeyboardMarkup::new(vec![row]))
+}
+
+/// Telegram's limit on the length of a message, in UTF-16 code units
+pub const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
+
+/// Length of `text` as Telegram counts it
+fn telegram_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Render one page of the session list as MarkdownV2, with its navigation keyboard
+///
+/// The text always fits in a Telegram message: each session gets an equal share of the space
+/// and sessions with too many options to fit in theirs are cut short with a note. Active polls
+/// say how many of the group's `members` voted, unless that's 0 (unknown). The option /confirm
+/// would pick is crowned, leaving out those a player in `required` said no to. Voters' `notes`
+/// are listed under their options.
+#[allow(clippy::too_many_arguments)] // The page's position comes straight from load_list_page
+pub fn render_list_page(
+    sessions: &[Session],
+    all_options: &[SessionOption],
+    all_responses: &[Response],
+    notes: &NotesByOption,
+    members: usize,
+    required: &[i64],
+    page: usize,
+    total_pages: usize,
+    total_sessions: usize,
+) -> (String, InlineKeyboardMarkup) {
+    let mut message_text = String::from("📋 **Active Sessions**\n");
+    if total_pages > 1 {
+        message_text.push_str(&format!(
+            "Page {} of {} • {} sessions\n",
+            page + 1,
+            total_pages,
+            total_sessions
+        ));
+    }
+    message_text.push('\n');
+    
+    // Add command usage hints
+    let mut footer = String::from("💡 **Commands:** \\(`<session_id>` can be a number like `#12`\\)\n");
+    footer.push_str("• `/confirm <session_id> [option_number]` \\- Confirm session, or one more of its options\n");
+    footer.push_str("• `/cancel <session_id>` \\- Cancel session\n");
+    footer.push_str("• `/deadline <session_id> <time>` \\- Set deadline\n");
+    
     // Group options and responses by session ID for efficient lookup
     let mut options_by_session: HashMap<String, Vec<&SessionOption>> = HashMap::new();
-    for option in &all_options {
+    for option in all_options {
         options_by_session.entry(option.session_id.clone()).or_default().push(option);
     }
     
     let mut responses_by_session: HashMap<String, Vec<&Response>> = HashMap::new();
-    for response in &all_responses {
+    for response in all_responses {
         responses_by_session.entry(response.session_id.clone()).or_default().push(response);
     }
     
+    let available = TELEGRAM_MESSAGE_LIMIT.saturating_sub(telegram_len(&message_text) + telegram_len(&footer));
+    let session_budget = available / sessions.len().max(1);
+    
     for session in sessions {
-        // Get session options from pre-fetched data
-        let empty_options = Vec::new();
-        let options = options_by_session.get(&session.id).unwrap_or(&empty_options);
-        
-        // Get responses for vote counts from pre-fetched data
-        let empty_responses = Vec::new();
-        let responses = responses_by_session.get(&session.id).unwrap_or(&empty_responses);
-        
-        // Group responses by option
-        let mut responses_by_option: HashMap<String, Vec<&Response>> = HashMap::new();
-        for response in responses {
-            responses_by_option.entry(response.option_id.clone())
-                .or_default()
-                .push(response);
+        // Get session options and responses from pre-fetched data
+        let options = options_by_session.get(&session.id).map(Vec::as_slice).unwrap_or_default();
+        let responses = responses_by_session.get(&session.id).map(Vec::as_slice).unwrap_or_default();
+        message_text.push_str(&render_session_entry(session, options, responses, notes, members, required, session_budget));
+    }
+    
+    message_text.push_str(&footer);
+    
+    let keyboard = list_page_keyboard(page, total_pages).unwrap_or_default();
+    (message_text, keyboard)
+}
+
+/// Render one session of the list, dropping trailing options that don't fit in `budget`
+fn render_session_entry(
+    session: &Session,
+    options: &[&SessionOption],
+    responses: &[&Response],
+    notes: &NotesByOption,
+    members: usize,
+    required: &[i64],
+    budget: usize,
+) -> String {
+    // Group responses by option
+    let mut responses_by_option: HashMap<String, Vec<&Response>> = HashMap::new();
+    for response in responses {
+        responses_by_option.entry(response.option_id.clone())
+            .or_default()
+            .push(response);
+    }
+    
+    // Add session info
+    let status_emoji = match session.status.as_str() {
+        "active" => "🟢",
+        "confirmed" => "✅",
+        "cancelled" => "❌",
+        "closed" => "🔒",
+        "archived" => "📦",
+        _ => "⚪"
+    };
+    
+    let mut entry = format!(
+        "{} **{}** {}{}\n📧 ID: `{}`\n",
+        status_emoji,
+        escape_markdown(&session.title),
+        escape_markdown(&session.display_ref()),
+        if session.reminders_muted { " 🔕" } else { "" },
+        session.id
+    );
+    
+    // Show deadline if set
+    if let Some(deadline_str) = &session.deadline {
+        if let Ok(deadline) = chrono::DateTime::parse_from_rfc3339(deadline_str) {
+            let deadline_formatted = format_datetime(&deadline.with_timezone(&Utc));
+            entry.push_str(&format!("⏰ Deadline: {}\n", escape_markdown(&deadline_formatted)));
         }
+    }
+    
+    if let Some(notes) = &session.notes {
+        entry.push_str(&format!("📝 Notes: {}\n", escape_markdown(notes)));
+    }
+    
+    entry.push_str(&activity_line(session, responses, members, Utc::now()));
+    
+    // Crown the option /confirm would pick right now
+    let leader = if session.status == "active" && !session.hides_votes() {
+        let options: Vec<SessionOption> = options.iter().map(|&option| option.clone()).collect();
+        let responses: Vec<Response> = responses.iter().map(|&response| response.clone()).collect();
+        select_confirmable_option(&options, &responses, required).map(|selection| selection.winner.option.id)
+    } else {
+        None
+    };
+    
+    // Show options and vote counts
+    entry.push_str("📅 **Options:**\n");
+    let labels = label_options(options);
+    let option_lines = options.iter().zip(labels).enumerate().map(|(i, (option, label))| {
+        let datetime_str = format!("{} ⏱️ {}", label.when, format_duration(option.duration));
         
-        // Add session info
-        let status_emoji = match session.status.as_str() {
-            "active" => "🟢",
-            "confirmed" => "✅",
-            "cancelled" => "❌",
-            _ => "⚪"
+        let confirmed_marker = if option.confirmed { " ✅" } else { "" };
+        // Options sharing a day are listed under it, indented
+        let prefix = match &label.day_heading {
+            Some(day) => format!("  📅 {}\n    ", escape_markdown(day)),
+            None if label.under_heading => "    ".to_string(),
+            None => "  ".to_string(),
         };
         
-        message_text.push_str(&format!(
-            "{} **{}**\n📧 ID: `{}`\n",
-            status_emoji,
-            escape_markdown(&session.title),
-            session.id
-        ));
-        
-        // Show deadline if set
-        if let Some(deadline_str) = &session.deadline {
-            if let Ok(deadline) = chrono::DateTime::parse_from_rfc3339(deadline_str) {
-                let deadline_formatted = format_datetime(&deadline.with_timezone(&Utc));
-                message_text.push_str(&format!("⏰ Deadline: {}\n", escape_markdown(&deadline_formatted)));
-            }
-        }
-        
-        // Show options and vote counts
-        message_text.push_str("📅 **Options:**\n");
-        for (i, option) in options.iter().enumerate() {
-            let datetime_str = chrono::DateTime::parse_from_rfc3339(&option.datetime)
-                .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
-                .unwrap_or_else(|_| option.datetime.clone());
-            
-            let empty_vec = Vec::new();
-            let option_responses = responses_by_option.get(&option.id).unwrap_or(&empty_vec);
-            let yes_count = option_responses.iter().filter(|r| r.response == "yes").count();
-            let no_count = option_responses.iter().filter(|r| r.response == "no").count();
-            let maybe_count = option_responses.iter().filter(|r| r.response == "maybe").count();
-            
-            let confirmed_marker = if option.confirmed { " ✅" } else { "" };
-            
-            message_text.push_str(&format!(
-                "  {}\\. {} \\(✅ {} • ❌ {} • ❓ {}\\){}\n",
+        // Anonymous polls keep their tallies hidden until confirmation
+        if session.hides_votes() {
+            return format!(
+                "{}{}\\. {} \\(🙈 votes hidden\\){}\n",
+                prefix,
                 i + 1,
                 escape_markdown(&datetime_str),
-                yes_count,
-                no_count,
-                maybe_count,
                 confirmed_marker
-            ));
+            );
         }
         
-        message_text.push('\n');
+        let option_responses = responses_by_option.get(&option.id).map(Vec::as_slice).unwrap_or_default();
+        let yes_count = option_responses.iter().filter(|r| r.response == "yes").count();
+        let no_count = option_responses.iter().filter(|r| r.response == "no").count();
+        let maybe_count = option_responses.iter().filter(|r| r.response == "maybe").count();
+        
+        let leader_marker = if leader.as_ref() == Some(&option.id) { " 👑" } else { "" };
+        
+        let mut line = format!(
+            "{}{}\\. {} \\(✅ {} • ❌ {} • ❓ {}\\){}{}\n",
+            prefix,
+            i + 1,
+            escape_markdown(&datetime_str),
+            yes_count,
+            no_count,
+            maybe_count,
+            confirmed_marker,
+            leader_marker
+        );
+        for (author, note) in notes.for_option(&option.id) {
+            line.push_str(&format!("      📝 {}: {}\n", escape_markdown(author), escape_markdown(note)));
+        }
+        line
+    });
+    
+    // Leave room for the truncation note and the blank line after the entry
+    let truncation_note = |hidden: usize| format!("  … and {hidden} more options, see the poll message\n");
+    let reserve = telegram_len(&truncation_note(options.len())) + 1;
+    for (shown, line) in option_lines.enumerate() {
+        if telegram_len(&entry) + telegram_len(&line) + reserve > budget {
+            entry.push_str(&truncation_note(options.len() - shown));
+            break;
+        }
+        entry.push_str(&line);
     }
     
-    // Add command usage hints
-    message_text.push_str("💡 **Commands:**\n");
-    message_text.push_str("• `/confirm <session_id>` \\- Confirm session\n");
-    message_text.push_str("• `/cancel <session_id>` \\- Cancel session\n");
-    message_text.push_str("• `/deadline <session_id> <time>` \\- Set deadline\n");
+    entry.push('\n');
+    entry
+}
+
+/// "🗳️ Last vote 6 days ago" or "🗳️ No votes yet", flagged with ⚠️ for active polls that went quiet
+///
+/// Active polls with votes also say how many of the group's `members` cast them, e.g.
+/// "🗳️ 4/6 voted, last vote 2 hours ago".
+fn activity_line(session: &Session, responses: &[&Response], members: usize, now: DateTime<Utc>) -> String {
+    let parse = |at: &str| DateTime::parse_from_rfc3339(at).ok().map(|dt| dt.with_timezone(&Utc));
+    let last_vote = responses.iter().filter_map(|r| parse(&r.updated_at)).max();
+    let quiet_since = last_vote.or_else(|| parse(&session.created_at));
+    let stale = session.status == "active"
+        && quiet_since.is_some_and(|at| now - at > Duration::days(STALE_SESSION_DAYS));
+    let marker = if stale { "⚠️ " } else { "" };
     
-    // Send the complete session list
-    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, &message_text).await?;
+    let mut voters: Vec<i64> = responses.iter().map(|r| r.user_id).collect();
+    voters.sort_unstable();
+    voters.dedup();
     
-    Ok(())
+    match last_vote {
+        // Voters who have since left still count, so the share never goes over 100%
+        Some(at) if session.status == "active" && members > 0 => format!(
+            "🗳️ {marker}{}/{} voted, last vote {}\n",
+            voters.len(),
+            members.max(voters.len()),
+            escape_markdown(&format_relative_time(at, now))
+        ),
+        Some(at) => format!("🗳️ {marker}Last vote {}\n", escape_markdown(&format_relative_time(at, now))),
+        None if stale => format!(
+            "🗳️ {marker}No votes yet, created {}\n",
+            escape_markdown(&format_relative_time(quiet_since.unwrap_or(now), now))
+        ),
+        None => "🗳️ No votes yet\n".to_string(),
+    }
 }
 
-// Database helper function
-async fn get_sessions_by_group(
+/// Load `page` of the list, clamped to the last page
+///
+/// Returns the page's sessions with the page number, the number of pages and the total number
+/// of sessions; polls gone stale in `STALE_SESSION_DAYS` get a ⚠️ and sink to the end.
+pub async fn load_list_page(
     pool: &sqlx::SqlitePool,
     group_id: i64,
-) -> Result<Vec<Session>, sqlx::Error> {
-    sqlx::query_as::<_, Session>(
-        "SELECT id, group_id, title, message_id, status, deadline, created_by, created_at 
-         FROM sessions 
-         WHERE group_id = ? AND status IN ('active', 'confirmed') 
-         ORDER BY created_at DESC"
-    )
-    .bind(group_id)
-    .fetch_all(pool)
-    .await
+    page: usize,
+) -> Result<(Vec<Session>, usize, usize, usize), sqlx::Error> {
+    let fetch = |page: usize| Session::find_by_group_paginated(
+        pool,
+        group_id,
+        LIST_PAGE_SIZE as i64,
+        (page * LIST_PAGE_SIZE) as i64,
+        &LIST_STATUSES,
+    );
+
+    let (sessions, total) = fetch(page).await?;
+    let total = total as usize;
+    let (clamped, total_pages, _) = paginate(total, page);
+    if clamped == page {
+        return Ok((sessions, page, total_pages, total));
+    }
+
+    // Past the end, e.g. after sessions were closed since the list was sent
+    let (sessions, total) = fetch(clamped).await?;
+    let (page, total_pages, _) = paginate(total as usize, clamped);
+    Ok((sessions, page, total_pages, total as usize))
 }
 
@@ -4,65 +4,112 @@ pub mod list;
 pub mod settings;
 pub mod stats;
 pub mod reminders;
+pub mod export;
+pub mod undo;
+pub mod checkins;
+pub mod sent;
+pub mod invite;
+pub mod attendance;
+pub mod availability;
+pub mod players;
+pub mod duty;
+pub mod results;
+pub mod sessioninfo;
+pub mod rollcall;
+pub mod vote;
+pub mod voter_notes;
 
+use std::borrow::Cow;
 use teloxide::utils::command::BotCommands;
 
+/// What commands start with unless `COMMAND_PREFIX` says otherwise
+pub const DEFAULT_COMMAND_PREFIX: &str = "/";
+
+/// Quotes phones put in place of `"`, which the `/schedule` arguments accept just the same
+const CURLY_QUOTES: [char; 4] = ['“', '”', '„', '‟'];
+
+/// Words that belong to the time option right after them, as in "next Friday 19:00"
+const TIME_OPTION_QUALIFIERS: [&str; 2] = ["next", "this"];
+
+/// Split `/schedule` arguments into the title and the time options
+///
+/// A quoted first argument is the title; curly quotes count as quotes and `\"` is a quote
+/// inside it. Otherwise the title runs up to quoted options or the first day, time or date
+/// ("Lost Mines Friday 19:00" is titled "Lost Mines"), or is the first word if there is none.
+/// Input starting with a day or time ("Friday 19:00, Sunday 15:00") is all options and the
+/// title comes back empty, for the handler to make one up, so a title starting with a day
+/// name has to be quoted.
 fn parse_schedule_args(input: String) -> Result<(String, String), teloxide::utils::command::ParseError> {
-    let input = input.trim();
+    let input: String = input.trim().chars()
+        .map(|c| if CURLY_QUOTES.contains(&c) { '"' } else { c })
+        .collect();
     
     if input.is_empty() {
         return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /schedule Title Time options".into()));
     }
     
-    // Handle case where first argument is quoted
     if let Some(stripped) = input.strip_prefix('"') {
-        // Find the closing quote for the first argument
-        if let Some(closing_quote_pos) = stripped.find('"') {
-            let title = stripped[..closing_quote_pos].to_string();
-            let rest = input[closing_quote_pos + 2..].trim();
-            
-            // Allow empty options for quoted title case
-            if rest.is_empty() {
-                return Ok((title, String::new()));
-            }
-            
-            // Check if the rest is also quoted
-            if rest.starts_with('"') && rest.ends_with('"') && rest.len() > 1 {
-                let options = rest[1..rest.len()-1].to_string();
-                Ok((title, options))
-            } else {
-                // Rest is not quoted, use as-is
-                Ok((title, rest.to_string()))
-            }
-        } else {
+        let Some((title, rest)) = split_quoted(stripped) else {
             // Unclosed quote - treat everything after quote as title (graceful handling)
-            let title = input[1..].to_string();
-            Ok((title, String::new()))
-        }
-    } else {
-        // Handle unquoted arguments: /schedule Title Rest of the options
-        match input.split_once(' ') {
-            Some((title, options)) => {
-                let title = title.trim();
-                let options = options.trim();
-                
-                if title.is_empty() {
-                    return Err(teloxide::utils::command::ParseError::IncorrectFormat("Title cannot be empty".into()));
-                }
-                
-                // If options are quoted, remove the quotes
-                if options.starts_with('"') && options.ends_with('"') && options.len() > 1 {
-                    Ok((title.to_string(), options[1..options.len()-1].to_string()))
-                } else {
-                    Ok((title.to_string(), options.to_string()))
+            return Ok((stripped.to_string(), String::new()));
+        };
+        return Ok((title, strip_quotes(rest.trim()).to_string()));
+    }
+    
+    match options_start(&input) {
+        // Only time options: /schedule Friday 19:00, Sunday 15:00
+        Some(0) => Ok((String::new(), input)),
+        Some(start) => Ok((input[..start].trim().to_string(), strip_quotes(input[start..].trim()).to_string())),
+        // Without a time the first word is the title: /schedule Title whenever suits
+        None => match input.split_once(' ') {
+            Some((title, options)) => Ok((title.to_string(), strip_quotes(options.trim()).to_string())),
+            None => Ok((input, String::new())),
+        },
+    }
+}
+
+/// Split `text`, which follows an opening quote, at its closing quote: the quoted text with
+/// `\"` and `\\` unescaped, and what comes after the quote. `None` if the quote isn't closed.
+fn split_quoted(text: &str) -> Option<(String, &str)> {
+    let mut quoted = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.clone().next() {
+                Some((_, escaped @ ('"' | '\\'))) => {
+                    quoted.push(escaped);
+                    chars.next();
                 }
+                _ => quoted.push(c),
             },
-            None => {
-                // Only one argument provided, use as title with empty options
-                Ok((input.to_string(), String::new()))
-            }
+            '"' => return Some((quoted, &text[i + 1..])),
+            _ => quoted.push(c),
         }
     }
+    None
+}
+
+/// `text` without the quotes around it, if it is quoted as a whole
+fn strip_quotes(text: &str) -> &str {
+    match text.strip_prefix('"').and_then(|text| text.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => text,
+    }
+}
+
+/// Byte offset of where the options start in unquoted `/schedule` arguments: the first quoted
+/// word, or the first day, time or date, taking a "next" or "this" in front of it along
+fn options_start(input: &str) -> Option<usize> {
+    let words: Vec<(usize, &str)> = input.split_whitespace()
+        .map(|word| (word.as_ptr() as usize - input.as_ptr() as usize, word))
+        .collect();
+    let found = words.iter()
+        .position(|(_, word)| word.starts_with('"') || crate::utils::datetime::is_time_option_word(word))?;
+    let qualified = found.checked_sub(1)
+        .filter(|_| !words[found].1.starts_with('"'))
+        .filter(|&before| TIME_OPTION_QUALIFIERS.contains(&words[before].1.to_lowercase().as_str()))
+        .unwrap_or(found);
+    Some(words[qualified].0)
 }
 
 fn parse_deadline_args(input: String) -> Result<(String, String), teloxide::utils::command::ParseError> {
@@ -72,43 +119,478 @@ fn parse_deadline_args(input: String) -> Result<(String, String), teloxide::util
     }
 }
 
-fn parse_confirm_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+fn parse_availability_args(input: String) -> Result<(String, String), teloxide::utils::command::ParseError> {
+    match parse_schedule_args(input) {
+        Ok((title, range)) if !range.trim().is_empty() => Ok((title, range)),
+        _ => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /availability \"Title\" next week".into())),
+    }
+}
+
+fn parse_notes_args(input: String) -> Result<(String, String), teloxide::utils::command::ParseError> {
+    match input.trim().split_once(' ') {
+        Some((session_id, text)) => Ok((session_id.to_string(), text.trim().to_string())),
+        None => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /notes <session_id> <text>".into())),
+    }
+}
+
+fn parse_confirm_args(input: String) -> Result<(String, Option<usize>), teloxide::utils::command::ParseError> {
+    let incorrect = || teloxide::utils::command::ParseError::IncorrectFormat("Expected: /confirm <session_id> [option_number]".into());
+    let mut args = input.split_whitespace();
+    let session_id = args.next().ok_or_else(incorrect)?;
+    let option = match args.next() {
+        Some(number) => Some(number.parse::<usize>().ok().filter(|&n| n > 0).ok_or_else(incorrect)?),
+        None => None,
+    };
+    if args.next().is_some() {
+        return Err(incorrect());
+    }
+    Ok((session_id.to_string(), option))
+}
+
+fn parse_cancel_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
     let session_id = input.trim();
     if session_id.is_empty() {
-        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /confirm <session_id>".into()));
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /cancel <session_id>".into()));
     }
     Ok((session_id.to_string(),))
 }
 
-fn parse_cancel_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+fn parse_delete_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
     let session_id = input.trim();
     if session_id.is_empty() {
-        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /cancel <session_id>".into()));
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /delete <session_id>".into()));
+    }
+    Ok((session_id.to_string(),))
+}
+
+fn parse_attended_args(input: String) -> Result<(String, String), teloxide::utils::command::ParseError> {
+    match input.trim().split_once(char::is_whitespace) {
+        Some((session_id, players)) if !players.trim().is_empty() => {
+            Ok((session_id.to_string(), players.trim().to_string()))
+        }
+        _ => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /attended <session_id> @player ...".into())),
+    }
+}
+
+fn parse_checkins_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    let session_id = input.trim();
+    if session_id.is_empty() {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /checkins <session_id>".into()));
+    }
+    Ok((session_id.to_string(),))
+}
+
+fn parse_sent_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    let session_id = input.trim();
+    if session_id.is_empty() {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /sent <session_id>".into()));
+    }
+    Ok((session_id.to_string(),))
+}
+
+fn parse_results_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    let session_id = input.trim();
+    if session_id.is_empty() {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /results <session_id>".into()));
+    }
+    Ok((session_id.to_string(),))
+}
+
+fn parse_sessioninfo_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    let session_id = input.trim();
+    if session_id.is_empty() {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /sessioninfo <session_id>".into()));
+    }
+    Ok((session_id.to_string(),))
+}
+
+fn parse_mute_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    let session_id = input.trim();
+    if session_id.is_empty() {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /mute <session_id>".into()));
+    }
+    Ok((session_id.to_string(),))
+}
+
+fn parse_unmute_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    let session_id = input.trim();
+    if session_id.is_empty() {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /unmute <session_id>".into()));
+    }
+    Ok((session_id.to_string(),))
+}
+
+fn parse_repost_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    let session_id = input.trim();
+    if session_id.is_empty() {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /repost <session_id>".into()));
+    }
+    Ok((session_id.to_string(),))
+}
+
+fn parse_archive_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    let session_id = input.trim();
+    if session_id.is_empty() {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /archive <session_id>".into()));
     }
     Ok((session_id.to_string(),))
 }
 
+/// Default age in days after which `/closeall` treats an active poll as stale
+pub const DEFAULT_STALE_DAYS: u32 = 30;
+
+fn parse_closeall_args(input: String) -> Result<(u32,), teloxide::utils::command::ParseError> {
+    let input = input.trim().to_lowercase();
+    let input = input.strip_prefix("older than").unwrap_or(&input).trim();
+    
+    if input.is_empty() {
+        return Ok((DEFAULT_STALE_DAYS,));
+    }
+    
+    let days = input.strip_suffix('d').unwrap_or(input).trim();
+    match days.parse::<u32>() {
+        Ok(days) if days > 0 => Ok((days,)),
+        _ => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /closeall [older than 30d]".into())),
+    }
+}
+
+fn parse_exportgroup_args(input: String) -> Result<(bool,), teloxide::utils::command::ParseError> {
+    match input.trim().to_lowercase().as_str() {
+        "" => Ok((false,)),
+        "dm" => Ok((true,)),
+        _ => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /exportgroup [dm]".into())),
+    }
+}
+
+fn parse_testreminders_args(input: String) -> Result<(bool,), teloxide::utils::command::ParseError> {
+    match input.trim().to_lowercase().as_str() {
+        "" => Ok((false,)),
+        "send" => Ok((true,)),
+        _ => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /testreminders [send]".into())),
+    }
+}
+
+fn parse_rollcall_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    match rollcall::parse_rollcall_args(&input) {
+        Ok(_) => Ok((input.trim().to_string(),)),
+        Err(_) => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /rollcall [time] [players]".into())),
+    }
+}
+
+fn parse_vote_args(input: String) -> Result<(String, usize, String), teloxide::utils::command::ParseError> {
+    match vote::parse_vote_args(&input) {
+        Ok(args) => Ok((args.session_id, args.option, args.response)),
+        Err(_) => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /vote <session_id> <option_number> <yes|no|maybe>".into())),
+    }
+}
+
+fn parse_duty_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    match duty::parse_duty_command(&input) {
+        Ok(_) => Ok((input.trim().to_string(),)),
+        Err(_) => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /duty [add|remove <name>]".into())),
+    }
+}
+
+fn parse_players_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    match players::parse_players_command(&input) {
+        Ok(_) => Ok((input.trim().to_string(),)),
+        Err(_) => Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /players [add|remove|require|unrequire @player ...]".into())),
+    }
+}
+
+#[allow(clippy::unnecessary_wraps)] // `parse_with` requires a `Result`
+fn parse_start_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    Ok((input.trim().to_string(),))
+}
+
+#[allow(clippy::unnecessary_wraps)] // `parse_with` requires a `Result`
+fn parse_settings_args(input: String) -> Result<(String,), teloxide::utils::command::ParseError> {
+    Ok((input.trim().to_string(),))
+}
+
+fn parse_stats_args(input: String) -> Result<(Option<String>,), teloxide::utils::command::ParseError> {
+    let input = input.trim();
+    
+    if input.is_empty() {
+        return Ok((None,));
+    }
+    
+    if input.split_whitespace().count() > 1 {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /stats [session_id]".into()));
+    }
+    
+    Ok((Some(input.to_string()),))
+}
+
+fn parse_help_args(input: String) -> Result<(Option<String>,), teloxide::utils::command::ParseError> {
+    let input = input.trim();
+    let name = input.strip_prefix('/').unwrap_or(input).to_lowercase();
+    
+    if name.is_empty() {
+        return Ok((None,));
+    }
+    
+    if name.split_whitespace().count() > 1 {
+        return Err(teloxide::utils::command::ParseError::IncorrectFormat("Expected: /help [command]".into()));
+    }
+    
+    Ok((Some(name),))
+}
+
 #[derive(BotCommands, Clone, Debug)]
 #[command(description = "D&D Scheduler Bot commands:", rename_rule = "lowercase")]
 pub enum Command {
-    #[command(description = "Display this help message")]
-    Help,
-    #[command(description = "Start the bot")]
-    Start,
+    #[command(description = "Display this help message, or details and examples for one command with /help <command>", parse_with = parse_help_args)]
+    Help { command: Option<String> },
+    #[command(description = "Start the bot", parse_with = parse_start_args)]
+    Start { payload: String },
     #[command(description = "Create a new session poll", parse_with = parse_schedule_args)]
     Schedule { title: String, options: String },
-    #[command(description = "Confirm a session and set it as final", parse_with = parse_confirm_args)]
-    Confirm { session_id: String },
+    #[command(description = "Confirm a session for its best option, or for the option with the given number; a session can have several", parse_with = parse_confirm_args)]
+    Confirm { session_id: String, option: Option<usize> },
     #[command(description = "Cancel a session", parse_with = parse_cancel_args)]
     Cancel { session_id: String },
+    #[command(description = "Permanently delete a session and all its votes", parse_with = parse_delete_args)]
+    Delete { session_id: String },
+    #[command(description = "Post a fresh copy of a session's poll with the current votes", parse_with = parse_repost_args)]
+    Repost { session_id: String },
+    #[command(description = "Archive a finished session so it no longer shows in /list", parse_with = parse_archive_args)]
+    Archive { session_id: String },
     #[command(description = "Set a deadline for responses", parse_with = parse_deadline_args)]
     Deadline { session_id: String, datetime: String },
+    #[command(description = "Add notes to a session such as where you meet, or remove them with /notes <session_id> clear", parse_with = parse_notes_args)]
+    Notes { session_id: String, text: String },
+    #[command(description = "Stop a session's reminders, e.g. for a surprise; the ones due meanwhile are skipped for good", parse_with = parse_mute_args)]
+    Mute { session_id: String },
+    #[command(description = "Turn a muted session's reminders back on, from the next one due", parse_with = parse_unmute_args)]
+    Unmute { session_id: String },
     #[command(description = "List active sessions")]
     List,
-    #[command(description = "Test reminder system (admin only)")]
-    TestReminders,
-    #[command(description = "Configure group settings")]
-    Settings,
-    #[command(description = "Show attendance statistics")]
-    Stats,
+    #[command(description = "Show which reminders are due without sending them; /testreminders send sends them (admin only)", parse_with = parse_testreminders_args)]
+    TestReminders { send: bool },
+    #[command(description = "Configure group settings; manage bot admins with /settings admins add|remove <user_id>", parse_with = parse_settings_args)]
+    Settings { args: String },
+    #[command(description = "Show attendance statistics, or a single session's breakdown with /stats <session_id>", parse_with = parse_stats_args)]
+    Stats { session_id: Option<String> },
+    #[command(description = "Close stale active polls, e.g. /closeall older than 30d (admin only)", parse_with = parse_closeall_args)]
+    CloseAll { older_than_days: u32 },
+    #[command(description = "Export all sessions and votes as a CSV file")]
+    Export,
+    #[command(description = "Export everything the group scheduled as a JSON file for moving elsewhere; /exportgroup dm sends it to you privately (admin only)", parse_with = parse_exportgroup_args)]
+    ExportGroup { private: bool },
+    #[command(description = "Undo your last cancel, close or deadline change (within 10 minutes)")]
+    Undo,
+    #[command(description = "Post an introduction for new players, with a link to join the roster")]
+    Invite,
+    #[command(description = "See who checked in for a confirmed session and who stayed silent", parse_with = parse_checkins_args)]
+    CheckIns { session_id: String },
+    #[command(description = "Mark who came to a confirmed session; yes voters you leave out count as no-shows in /stats", parse_with = parse_attended_args)]
+    Attended { session_id: String, players: String },
+    #[command(description = "Show who counts as a player in this group; add or remove them with /players add|remove @player, or mark who the group can't play without with /players require|unrequire @player (admin only)", parse_with = parse_players_args)]
+    Players { args: String },
+    #[command(description = "Show the duties that rotate among a confirmed session's players, like hosting; add or remove them with /duty add|remove <name> (admin only)", parse_with = parse_duty_args)]
+    Duty { args: String },
+    #[command(description = "Show which polls, announcements and reminders the bot sent for a session (admin only)", parse_with = parse_sent_args)]
+    Sent { session_id: String },
+    #[command(description = "Show a session's votes as a table, one row per option and one column per player", parse_with = parse_results_args)]
+    Results { session_id: String },
+    #[command(description = "Show everything about one session: who created it and when, its deadline and status, and every option with its voters by name", parse_with = parse_sessioninfo_args)]
+    SessionInfo { session_id: String },
+    #[command(description = "Ask who can play later today, 20:00 unless you give a time; it's on once enough players say yes (3 unless you say), and called off after 3 hours", parse_with = parse_rollcall_args)]
+    RollCall { args: String },
+    #[command(description = "Vote without the buttons: yes, no or maybe for the option with the given number on the poll", parse_with = parse_vote_args)]
+    Vote { session_id: String, option: usize, response: String },
+    #[command(description = "Ask which days of a week work instead of proposing times, e.g. /availability \"Title\" next week evenings; /confirm then asks for the start time", parse_with = parse_availability_args)]
+    Availability { title: String, range: String },
+}
+
+impl Command {
+    /// The command as typed, without the slash, e.g. "closeall"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Help { .. } => "help",
+            Command::Start { .. } => "start",
+            Command::Schedule { .. } => "schedule",
+            Command::Confirm { .. } => "confirm",
+            Command::Cancel { .. } => "cancel",
+            Command::Delete { .. } => "delete",
+            Command::Repost { .. } => "repost",
+            Command::Archive { .. } => "archive",
+            Command::Deadline { .. } => "deadline",
+            Command::Notes { .. } => "notes",
+            Command::Mute { .. } => "mute",
+            Command::Unmute { .. } => "unmute",
+            Command::List => "list",
+            Command::TestReminders { .. } => "testreminders",
+            Command::Settings { .. } => "settings",
+            Command::Stats { .. } => "stats",
+            Command::CloseAll { .. } => "closeall",
+            Command::Export => "export",
+            Command::ExportGroup { .. } => "exportgroup",
+            Command::Undo => "undo",
+            Command::Invite => "invite",
+            Command::CheckIns { .. } => "checkins",
+            Command::Attended { .. } => "attended",
+            Command::Players { .. } => "players",
+            Command::Duty { .. } => "duty",
+            Command::Sent { .. } => "sent",
+            Command::Results { .. } => "results",
+            Command::SessionInfo { .. } => "sessioninfo",
+            Command::RollCall { .. } => "rollcall",
+            Command::Vote { .. } => "vote",
+            Command::Availability { .. } => "availability",
+        }
+    }
+}
+
+/// Example invocations shown by `/help <command>`, keyed by [`Command::name`]
+const COMMAND_EXAMPLES: [(&str, &[&str]); 31] = [
+    ("help", &["/help", "/help schedule"]),
+    ("start", &["/start"]),
+    ("schedule", &[
+        "/schedule \"Curse of Strahd\" Friday 19:00, Saturday 14:00",
+        "/schedule \"One-shot\" 2024-12-20 19:00 (3h), 2024-12-21 18:00",
+        "/schedule \"Session 12\" Friday 19:00, Sunday 15:00 --anonymous",
+    ]),
+    ("confirm", &["/confirm a1b2c3d4", "/confirm a1b2c3d4 2"]),
+    ("cancel", &["/cancel a1b2c3d4"]),
+    ("delete", &["/delete a1b2c3d4"]),
+    ("repost", &["/repost a1b2c3d4"]),
+    ("archive", &["/archive a1b2c3d4"]),
+    ("deadline", &["/deadline a1b2c3d4 2024-12-19 18:00", "/deadline a1b2c3d4 2024-12-26 18:00 --force"]),
+    ("notes", &["/notes a1b2c3d4 At Mira's place, bring snacks", "/notes a1b2c3d4 clear"]),
+    ("mute", &["/mute a1b2c3d4", "/mute #3"]),
+    ("unmute", &["/unmute a1b2c3d4", "/unmute #3"]),
+    ("list", &["/list"]),
+    ("testreminders", &["/testreminders", "/testreminders send"]),
+    ("settings", &["/settings", "/settings language de", "/settings admins add 123456789"]),
+    ("stats", &["/stats", "/stats a1b2c3d4"]),
+    ("closeall", &["/closeall", "/closeall older than 14d"]),
+    ("export", &["/export"]),
+    ("exportgroup", &["/exportgroup", "/exportgroup dm"]),
+    ("undo", &["/undo"]),
+    ("invite", &["/invite"]),
+    ("checkins", &["/checkins a1b2c3d4"]),
+    ("attended", &["/attended a1b2c3d4 @mira @bob_the_bard"]),
+    ("players", &["/players", "/players add @mira @bob_the_bard", "/players remove @bob_the_bard", "/players require @dm_dana"]),
+    ("duty", &["/duty", "/duty add hosting", "/duty add snacks", "/duty remove snacks"]),
+    ("sent", &["/sent a1b2c3d4"]),
+    ("results", &["/results a1b2c3d4", "/results #12"]),
+    ("sessioninfo", &["/sessioninfo #12", "/sessioninfo a1b2c3d4"]),
+    ("rollcall", &["/rollcall", "/rollcall 19:30", "/rollcall 19:30 4"]),
+    ("vote", &["/vote #12 2 yes", "/vote a1b2c3d4 1 maybe"]),
+    ("availability", &[
+        "/availability \"Session 12\" next week evenings",
+        "/availability \"One-shot\" this weekend",
+        "/availability \"Session 13\" 2024-W51",
+    ]),
+];
+
+/// Description and examples of the command called `name` (with or without the slash), `None` if there is none
+pub fn command_help(name: &str) -> Option<(String, &'static [&'static str])> {
+    let name = name.trim().trim_start_matches('/').to_lowercase();
+    let (_, examples) = COMMAND_EXAMPLES.iter().find(|(command, _)| *command == name)?;
+    let prefix = format!("/{name} — ");
+    let description = Command::descriptions()
+        .to_string()
+        .lines()
+        .find_map(|line| line.strip_prefix(&prefix).map(str::to_string))?;
+    Some((description, examples))
+}
+
+/// Argument syntax of the commands whose arguments can fail to parse, keyed by [`Command::name`]
+const COMMAND_USAGE: [(&str, &str); 25] = [
+    ("help", "/help [command]"),
+    ("schedule", "/schedule \"Title\" option1, option2, ..."),
+    ("confirm", "/confirm <session_id> [option_number]"),
+    ("cancel", "/cancel <session_id>"),
+    ("delete", "/delete <session_id>"),
+    ("repost", "/repost <session_id>"),
+    ("archive", "/archive <session_id>"),
+    ("deadline", "/deadline <session_id> <datetime> [--force]"),
+    ("notes", "/notes <session_id> <text>"),
+    ("mute", "/mute <session_id>"),
+    ("unmute", "/unmute <session_id>"),
+    ("stats", "/stats [session_id]"),
+    ("closeall", "/closeall [older than 30d]"),
+    ("testreminders", "/testreminders [send]"),
+    ("exportgroup", "/exportgroup [dm]"),
+    ("checkins", "/checkins <session_id>"),
+    ("attended", "/attended <session_id> @player ..."),
+    ("players", "/players [add|remove|require|unrequire @player ...]"),
+    ("duty", "/duty [add|remove <name>]"),
+    ("sent", "/sent <session_id>"),
+    ("results", "/results <session_id>"),
+    ("sessioninfo", "/sessioninfo <session_id>"),
+    ("rollcall", "/rollcall [time] [players]"),
+    ("vote", "/vote <session_id> <option_number> <yes|no|maybe>"),
+    ("availability", "/availability \"Title\" <this week|next week|this weekend|next weekend|2024-W51>"),
+];
+
+/// Argument syntax of the command called `name`, e.g. "/confirm <session_id>"
+pub fn command_usage(name: &str) -> Option<&'static str> {
+    COMMAND_USAGE.iter().find(|(command, _)| *command == name).map(|(_, usage)| *usage)
+}
+
+/// `text` with a leading `prefix` swapped for the "/" [`Command`] parses, so with "!" the message
+/// "!confirm@bot 3" reads as "/confirm@bot 3"
+///
+/// Commands sent with "/", as Telegram's command menu does, are left as they are.
+pub fn slash_command<'a>(text: &'a str, prefix: &str) -> Cow<'a, str> {
+    match text.strip_prefix(prefix) {
+        Some(rest) if prefix != DEFAULT_COMMAND_PREFIX => Cow::Owned(format!("/{rest}")),
+        _ => Cow::Borrowed(text),
+    }
+}
+
+/// Whether `text` is meant as a command: it starts with "/", or with `prefix` right before a letter
+///
+/// "!!!" or "?" on their own are chat, not commands.
+pub fn looks_like_command(text: &str, prefix: &str) -> bool {
+    text.starts_with('/')
+        || text.strip_prefix(prefix).is_some_and(|rest| rest.starts_with(|c: char| c.is_alphabetic()))
+}
+
+/// `text` with the "/" of every command it mentions replaced by `prefix`, for help and usage texts
+///
+/// Only slashes at the start of a word and before a letter count, so links and dates are untouched.
+pub fn with_command_prefix(text: &str, prefix: &str) -> String {
+    if prefix == DEFAULT_COMMAND_PREFIX {
+        return text.to_string();
+    }
+    let mut prefixed = String::with_capacity(text.len());
+    let mut previous = None;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let starts_word = previous.is_none_or(|p: char| p.is_whitespace() || matches!(p, '`' | '"' | '(' | '*' | '_'));
+        if c == '/' && starts_word && chars.peek().is_some_and(|next| next.is_ascii_lowercase()) {
+            prefixed.push_str(prefix);
+        } else {
+            prefixed.push(c);
+        }
+        previous = Some(c);
+    }
+    prefixed
+}
+
+/// The name of our command that `text` invokes, if its arguments are missing or don't parse
+///
+/// Messages that aren't commands, name an unknown command or another bot, or parse fine give `None`.
+pub fn failed_command(text: &str, bot_username: &str) -> Option<&'static str> {
+    use teloxide::utils::command::ParseError;
+    
+    match Command::parse(text, bot_username) {
+        Ok(_) | Err(ParseError::UnknownCommand(_) | ParseError::WrongBotName(_)) => return None,
+        Err(_) => {}
+    }
+    let name = text.split_whitespace().next()?.trim_start_matches('/');
+    let name = name.split('@').next()?.to_lowercase();
+    COMMAND_USAGE.iter().find(|(command, _)| *command == name).map(|(command, _)| *command)
+}
+
+/// `usage` with its `<session_id>` filled in with `reference` and any optional arguments after it
+/// left out, `None` if it doesn't take one
+pub fn suggest_invocation(usage: &str, reference: &str) -> Option<String> {
+    let required = usage.split(" [").next().unwrap_or(usage);
+    required.contains("<session_id>").then(|| required.replace("<session_id>", reference))
 }
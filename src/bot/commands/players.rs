@@ -0,0 +1,199 @@
+//! `/players`: the group's members, and adding or removing them by hand for chats where the bot
+//! can't see people join and leave
+//!
+//! Players can also be marked as required: options one of them says no to are struck in
+//! `/results` and aren't picked by `/confirm` or a roll call (see [`select_confirmable_option`]).
+//!
+//! [`select_confirmable_option`]: crate::bot::selection::select_confirmable_option
+
+use teloxide::prelude::*;
+use crate::bot::commands::attendance::{resolve_players, take_text_mentions};
+use crate::bot::permissions::group_manager_role;
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::utils::feedback::CommandFeedback;
+use std::collections::HashMap;
+
+const PLAYERS_USAGE: &str = "Use /players to see the players, /players add @player ... and /players remove @player ..., \
+or /players require @player ... and /players unrequire @player ... for players the group can't play without";
+
+/// A `/players ...` subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayersCommand {
+    /// Show the group's active members
+    List,
+    /// Count the named players as members
+    Add(String),
+    /// Stop counting the named players as members
+    Remove(String),
+    /// Mark the named players as required, putting them on the roster
+    Require(String),
+    /// Stop requiring the named players
+    Unrequire(String),
+}
+
+/// Parse the arguments of `/players`, e.g. "add @mira @bob"
+pub fn parse_players_command(args: &str) -> Result<PlayersCommand, String> {
+    let args = args.trim();
+    let (action, players) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let players = players.trim().to_string();
+
+    match action.to_lowercase().as_str() {
+        "" | "list" if players.is_empty() => Ok(PlayersCommand::List),
+        "add" | "remove" | "require" | "unrequire" if players.is_empty() => {
+            Err(format!("No players given to {}", action.to_lowercase()))
+        }
+        "add" => Ok(PlayersCommand::Add(players)),
+        "remove" => Ok(PlayersCommand::Remove(players)),
+        "require" => Ok(PlayersCommand::Require(players)),
+        "unrequire" => Ok(PlayersCommand::Unrequire(players)),
+        "list" => Err("Too many arguments".to_string()),
+        action => Err(format!("Unknown players action '{action}'")),
+    }
+}
+
+/// Handle `/players [add|remove|require|unrequire @player ...]`
+pub async fn handle_players(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let command = match parse_players_command(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            feedback.validation_error(&e, PLAYERS_USAGE).await?;
+            return Ok(());
+        }
+    };
+
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    let members = GroupMember::find_active_by_group(&db.pool, group.id).await?;
+
+    let players = match &command {
+        PlayersCommand::List => {
+            let people: Vec<(i64, Option<&str>)> = members.iter()
+                .map(|member| (member.user_id, member.username.as_deref().or(member.display_name.as_deref())))
+                .collect();
+            let names = display_names(&db.pool, &group, &people).await?;
+            let required = RosterMember::required_user_ids(&db.pool, group.id).await?;
+            feedback.info(&members_summary(&members, &names, &required)).await?;
+            return Ok(());
+        }
+        PlayersCommand::Add(players)
+        | PlayersCommand::Remove(players)
+        | PlayersCommand::Require(players)
+        | PlayersCommand::Unrequire(players) => players.clone(),
+    };
+
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can change the players";
+        let suggestion = "Ask a chat administrator to run this command.";
+        feedback.validation_error(error_msg, suggestion).await?;
+        return Ok(());
+    }
+
+    let (mut user_ids, players) = take_text_mentions(&msg, players);
+    let args: Vec<&str> = players.split_whitespace().collect();
+    let usernames: Vec<&str> = args.iter().filter_map(|arg| arg.trim_end_matches(',').strip_prefix('@')).collect();
+    let roster = RosterMember::find_by_group(&db.pool, group.id).await?;
+    let profiles = UserProfile::find_by_usernames(&db.pool, &usernames).await?;
+    let known: Vec<(i64, Option<&str>)> = members.iter()
+        .map(|member| (member.user_id, member.username.as_deref()))
+        .chain(roster.iter().map(|member| (member.user_id, member.username.as_deref())))
+        .chain(profiles.iter().map(|profile| (profile.user_id, profile.username.as_deref())))
+        .collect();
+    let (found, unknown) = resolve_players(&args, &known);
+    for user_id in found {
+        if !user_ids.contains(&user_id) {
+            user_ids.push(user_id);
+        }
+    }
+
+    if user_ids.is_empty() {
+        let error_msg = format!("I don't know who {} is", unknown.join(", "));
+        let suggestion = "Players need to have written in this chat or to the bot once; you can also give their numeric user ID.";
+        feedback.validation_error(&error_msg, suggestion).await?;
+        return Ok(());
+    }
+
+    let mut changed = 0;
+    for &user_id in &user_ids {
+        let is_member = members.iter().any(|member| member.user_id == user_id);
+        let username = known.iter().find(|(id, name)| *id == user_id && name.is_some()).and_then(|(_, name)| *name);
+        let done = match command {
+            PlayersCommand::Add(_) if !is_member => {
+                let display_name = profiles.iter().find(|profile| profile.user_id == user_id).map(|profile| profile.display_name.as_str());
+                GroupMember::record_activity(&db.pool, group.telegram_chat_id, user_id, username, display_name).await?;
+                true
+            }
+            PlayersCommand::Remove(_) => GroupMember::mark_left(&db.pool, group.telegram_chat_id, user_id).await?,
+            PlayersCommand::Require(_) => RosterMember::require(&db.pool, group.id, user_id, username.map(str::to_string)).await?,
+            PlayersCommand::Unrequire(_) => RosterMember::unrequire(&db.pool, group.id, user_id).await?,
+            _ => false,
+        };
+        if done {
+            changed += 1;
+        }
+    }
+    let action = match command {
+        PlayersCommand::Add(_) => "added",
+        PlayersCommand::Remove(_) => "removed",
+        PlayersCommand::Require(_) => "required",
+        _ => "stopped requiring",
+    };
+    tracing::info!("User {} {} {} players of group {}", from.id, action, changed, group.id);
+
+    let mut text = match (&command, changed) {
+        (PlayersCommand::Add(_), 0) => "They're all players already".to_string(),
+        (PlayersCommand::Add(_), n) => format!("Added {}", players_count(n)),
+        (PlayersCommand::Remove(_), 0) => "None of them were players".to_string(),
+        (PlayersCommand::Remove(_), n) => format!("Removed {}", players_count(n)),
+        (PlayersCommand::Require(_), 0) => "They're all required already".to_string(),
+        (PlayersCommand::Require(_), n) => format!("{} now required", players_count(n)),
+        (_, 0) => "None of them were required".to_string(),
+        (_, n) => format!("{} no longer required", players_count(n)),
+    };
+    if matches!(command, PlayersCommand::Require(_) | PlayersCommand::Unrequire(_)) {
+        let required = RosterMember::required_user_ids(&db.pool, group.id).await?;
+        text.push_str(&format!("; the group now requires {}", players_count(required.len())));
+    } else {
+        let count = GroupMember::count_active(&db.pool, group.id).await?;
+        text.push_str(&format!("; the group now has {}", players_count(count as usize)));
+    }
+    if !unknown.is_empty() {
+        text.push_str(&format!("\n❓ Not found, left out: {}", unknown.join(", ")));
+    }
+    feedback.success(&text).await?;
+
+    Ok(())
+}
+
+/// The `/players` list: one line per active member, most recently seen first, marking those in `required`
+pub fn members_summary(members: &[GroupMember], names: &HashMap<i64, String>, required: &[i64]) -> String {
+    if members.is_empty() {
+        return "No players known yet. Players are counted once they write in this chat or vote, \
+                or add them with /players add @player".to_string();
+    }
+
+    let mut text = format!("👥 {}:\n", players_count(members.len()));
+    for member in members {
+        let name = names.get(&member.user_id).cloned().unwrap_or_else(|| format!("User {}", member.user_id));
+        let marker = if required.contains(&member.user_id) { " (required)" } else { "" };
+        text.push_str(&format!("• {name}{marker}\n"));
+    }
+    text
+}
+
+/// "1 player" or "3 players"
+fn players_count(count: usize) -> String {
+    if count == 1 { "1 player".to_string() } else { format!("{count} players") }
+}
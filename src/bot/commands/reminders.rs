@@ -1,54 +1,111 @@
+//! `/testreminders`: which reminders are due for the group right now, and sending them on demand
+
+use chrono::Utc;
 use teloxide::prelude::*;
-use crate::database::connection::DatabaseManager;
-use crate::services::reminder::ReminderService;
+use crate::bot::permissions::group_manager_role;
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::services::reminder::{pending_reminders, send_reminders, PendingReminder, ReminderSweep, REMINDER_SWEEP_MINUTES};
 use crate::utils::feedback::CommandFeedback;
-use std::sync::Arc;
 
+/// Handle `/testreminders [send]`: report the group's due reminders, and with `send` deliver them
 pub async fn handle_test_reminders(
     bot: Bot,
     msg: Message,
+    send: bool,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
+) -> Result<(), BotError> {
     let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
     
-    // Send processing message
-    let processing_msg = feedback.send_processing("Testing reminder system...").await?;
-    
-    // For security, only allow certain users to test reminders
-    // In a real implementation, you might check if user is group admin
-    let _user_id = msg.from().map(|u| u.id.0).unwrap_or(0);
-    
-    // Add a simple check - in a real implementation, you'd want proper admin verification
-    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-        "Initializing reminder service...").await?;
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can test reminders";
+        let suggestion = "Ask a chat administrator to run this command.";
+        feedback.validation_error(error_msg, suggestion).await?;
+        return Ok(());
+    }
     
-    // Create a temporary reminder service to test
-    let db_arc = Arc::new(db.clone());
-    let reminder_service = match ReminderService::new(bot.clone(), db_arc).await {
-        Ok(service) => {
-            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-                "Checking for sessions that need reminders...").await?;
-            service
-        },
+    let pending = match pending_reminders(&db.pool, Utc::now(), Some(group.id)).await {
+        Ok(pending) => pending,
         Err(e) => {
-            tracing::error!("Failed to create reminder service for testing: {}", e);
-            feedback.error("Could not initialize reminder service for testing").await?;
+            tracing::error!("Failed to work out the due reminders of group {}: {}", group.id, e);
+            feedback.error("Could not check which reminders are due").await?;
             return Ok(());
         }
     };
     
-    match reminder_service.check_reminders_now().await {
-        Ok(_) => {
-            let success_message = "Reminder system test completed successfully!\\n\\n✅ All due reminders have been processed and sent\\n\\n💡 **How reminders work:**\\n• 2 weeks before confirmed sessions\\n• 1 week before confirmed sessions\\n• 3 days before confirmed sessions\\n\\n🔧 Reminders run automatically every hour";
-            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, success_message).await?;
+    if !send {
+        feedback.info(&render_pending_reminders(&pending)).await?;
+        return Ok(());
+    }
+    
+    tracing::info!("User {} is sending {} due reminders of group {} by hand", from.id, pending.len(), group.id);
+    match send_reminders(&bot, db, pending).await {
+        Ok(sweep) => {
+            feedback.success(&render_sweep(&sweep)).await?;
         }
         Err(e) => {
-            tracing::error!("Manual reminder check failed: {}", e);
-            let error_msg = "Reminder system test failed";
-            let suggestion = "There was an error during the reminder check. Please contact an administrator or check the system logs for details.";
+            tracing::error!("Manual reminder sweep for group {} failed: {}", group.id, e);
+            let error_msg = "Sending the reminders failed";
+            let suggestion = "Some may have gone out; check the logs, or wait for the next regular check.";
             feedback.validation_error(error_msg, suggestion).await?;
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// The dry run report: each due reminder with its session and the chat it goes to
+pub fn render_pending_reminders(pending: &[PendingReminder]) -> String {
+    if pending.is_empty() {
+        return format!(
+            "No reminders are due for this group right now. Reminders are checked every {REMINDER_SWEEP_MINUTES} minutes."
+        );
+    }
+    
+    let count = if pending.len() == 1 { "1 reminder is".to_string() } else { format!("{} reminders are", pending.len()) };
+    let mut text = format!("🔎 Dry run: {count} due right now\n");
+    for reminder in pending {
+        let due = &reminder.due;
+        let target = match reminder.chat_id {
+            Some(_) if due.session.reminders_muted => "nowhere, the session is muted".to_string(),
+            Some(chat_id) if reminder.paused => format!("chat {chat_id}, paused because it couldn't be reached"),
+            Some(chat_id) => format!("chat {chat_id}"),
+            None => "nowhere, the group is gone".to_string(),
+        };
+        text.push_str(&format!(
+            "• {} ({}): {} to {}\n",
+            due.session.title,
+            due.session.display_ref(),
+            due.interval.label(),
+            target,
+        ));
+    }
+    text.push_str("\nNothing was sent. Use /testreminders send to send them now.");
+    text
+}
+
+/// What `/testreminders send` reports after sending
+fn render_sweep(sweep: &ReminderSweep) -> String {
+    if sweep.sent + sweep.failed + sweep.skipped + sweep.muted == 0 {
+        return "No reminders were due, nothing was sent".to_string();
+    }
+    
+    let mut text = format!("Sent {} of the due reminders", sweep.sent);
+    if sweep.failed > 0 {
+        text.push_str(&format!("; {} failed and will be retried", sweep.failed));
+    }
+    if sweep.skipped > 0 {
+        text.push_str(&format!("; {} skipped", sweep.skipped));
+    }
+    if sweep.muted > 0 {
+        text.push_str(&format!("; {} muted with /mute", sweep.muted));
+    }
+    text
+}
@@ -0,0 +1,80 @@
+//! `/results`: a session's votes as a table, one row per option, for sessions in any state
+
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use crate::bot::commands::session_management::resolve_session;
+use crate::bot::poll::{expected_voters, participation_line};
+use crate::bot::selection::{confirmable_maybe_swing, vetoed_options};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::utils::{datetime::format_datetime, feedback::CommandFeedback, i18n, markdown::{code_block, escape_markdown}, results_table::render_results_table};
+
+/// The results of `session` as MarkdownV2: a heading and the vote table in a code block
+///
+/// Voters are named the same way as on the poll, so username privacy applies here too. Options a
+/// required player said no to are struck through, and the poll's participation footer follows it.
+/// While the session is still undecided, a warning follows if outstanding maybes could change
+/// which option /confirm picks.
+pub async fn results_message(
+    pool: &sqlx::SqlitePool,
+    group: &Group,
+    session: &Session,
+) -> Result<String, sqlx::Error> {
+    let options = SessionOption::find_by_session(pool, &session.id).await?;
+    let mut responses = Response::find_by_session(pool, &session.id).await?;
+    let voters: Vec<(i64, Option<String>)> = responses.iter().map(|r| (r.user_id, r.username.clone())).collect();
+    let voters: Vec<(i64, Option<&str>)> = voters.iter().map(|(user_id, username)| (*user_id, username.as_deref())).collect();
+    let names = display_names(pool, group, &voters).await?;
+    for response in &mut responses {
+        if let Some(name) = names.get(&response.user_id) {
+            response.username = Some(name.clone());
+        }
+    }
+
+    let required = RosterMember::required_user_ids(pool, group.id).await?;
+    let vetoed = vetoed_options(&options, &responses, &required);
+    let expected = expected_voters(pool, session).await?;
+
+    let mut text = format!(
+        "📊 **Results for {}** {}\n{}{}",
+        escape_markdown(&session.title),
+        escape_markdown(&session.display_ref()),
+        code_block(&render_results_table(&options, &responses, &vetoed)),
+        escape_markdown(&participation_line(&responses, expected)),
+    );
+    // Only while there's still a pick to make; `session` may predate the confirmation
+    let undecided = session.status == "active" && !options.iter().any(|option| option.confirmed);
+    if let Some((option, swing)) = confirmable_maybe_swing(&options, &responses, &required).filter(|_| undecided) {
+        let when = chrono::DateTime::parse_from_rfc3339(&option.datetime)
+            .map(|dt| format_datetime(&dt.with_timezone(&chrono::Utc)))
+            .unwrap_or_else(|_| option.datetime.clone());
+        text.push_str(&format!("\n{}", escape_markdown(&i18n::t_maybe_swing(group.lang(), &when, &swing))));
+    }
+    Ok(text)
+}
+
+/// Show the vote table of a session; anonymous ones only once they're no longer being voted on
+pub async fn handle_results(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let (group, session) = match resolve_session(&feedback, db, msg.chat.id.0, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    if session.hides_votes() {
+        feedback.info("Votes on this session are hidden until it is confirmed").await?;
+        return Ok(());
+    }
+
+    bot.send_message(msg.chat.id, results_message(&db.pool, &group, &session).await?)
+        .parse_mode(ParseMode::MarkdownV2)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,260 @@
+//! `/rollcall`: who can play later today, without the full poll ceremony
+//!
+//! A roll call is a session with a single option that confirms itself as soon as enough players
+//! say yes, and is called off by the scheduler if they haven't within `ROLLCALL_EXPIRY_HOURS`.
+
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use crate::bot::poll::{expected_voters, refresh_poll_message, render_poll};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::scheduling::{SchedulerApi, SchedulingError};
+use crate::services::outbound_queue::{outbound_queue, telegram_result};
+use crate::utils::{feedback::CommandFeedback, markdown::escape_markdown, outbound::{logged_send, plain_fallback}};
+use chrono::{DateTime, NaiveTime, Utc};
+
+/// Hour a roll call is for when no time is given
+pub const DEFAULT_ROLLCALL_HOUR: u32 = 20;
+
+/// Players a roll call needs when no number is given
+pub const DEFAULT_ROLLCALL_QUORUM: i64 = 3;
+
+const ROLLCALL_USAGE: &str = "Use /rollcall for tonight at 20:00, or give the time and how many players you need, e.g. /rollcall 19:30 4";
+
+/// The arguments of `/rollcall`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollcallArgs {
+    /// When the game starts today, `DEFAULT_ROLLCALL_HOUR` if not given
+    pub time: NaiveTime,
+    /// How many players need to say yes, `DEFAULT_ROLLCALL_QUORUM` if not given
+    pub quorum: i64,
+}
+
+/// Parse the arguments of `/rollcall`, e.g. "19:30 4"
+pub fn parse_rollcall_args(args: &str) -> Result<RollcallArgs, String> {
+    let mut parts = args.split_whitespace();
+    let time = match parts.next() {
+        Some(time) => parse_time(time).ok_or_else(|| format!("'{time}' isn't a time like 19:30"))?,
+        None => NaiveTime::from_hms_opt(DEFAULT_ROLLCALL_HOUR, 0, 0).ok_or("Invalid default time")?,
+    };
+    let quorum = match parts.next() {
+        Some(quorum) => quorum.parse().map_err(|_| format!("'{quorum}' isn't a number of players"))?,
+        None => DEFAULT_ROLLCALL_QUORUM,
+    };
+    if parts.next().is_some() {
+        return Err("Too many arguments".to_string());
+    }
+    Ok(RollcallArgs { time, quorum })
+}
+
+/// "19:30" or "19.30"
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    let (hour, minute) = text.split_once([':', '.'])?;
+    NaiveTime::from_hms_opt(hour.parse().ok()?, minute.parse().ok()?, 0)
+}
+
+/// Handle `/rollcall [time] [players]`: post a single-option poll for later today and call the roster
+pub async fn handle_rollcall(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let args = match parse_rollcall_args(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            feedback.validation_error(&e, ROLLCALL_USAGE).await?;
+            return Ok(());
+        }
+    };
+
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+
+    let now = Utc::now();
+    let starts_at = now.date_naive().and_time(args.time).and_utc();
+    let api = SchedulerApi::new(db.pool.clone());
+    let (session, option, rollcall) = match api.create_rollcall(group.id, user_id, starts_at, args.quorum, now).await {
+        Ok(created) => created,
+        Err(SchedulingError::Invalid(e)) => {
+            feedback.validation_error(&e, ROLLCALL_USAGE).await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    tracing::info!("User {} started roll call {} for {} in group {}", user_id, session.id, starts_at, group.id);
+
+    let roster = RosterMember::find_by_group(&db.pool, group.id).await?;
+    let expires_at = rollcall.expires_at_utc().unwrap_or(starts_at);
+    let call = render_rollcall_call(starts_at, rollcall.quorum, expires_at, &roster, user_id);
+    let request = bot.send_message(msg.chat.id, call).parse_mode(ParseMode::MarkdownV2);
+    logged_send(&db.pool, msg.chat.id, "rollcall", Some(&session.id), request).await?;
+
+    let expected = expected_voters(&db.pool, &session).await?;
+    let (text, keyboard) = render_poll(&session, &[option], &[], expected);
+    let request = bot.send_message(msg.chat.id, text)
+        .reply_markup(keyboard)
+        .parse_mode(ParseMode::MarkdownV2);
+    let sent = logged_send(&db.pool, msg.chat.id, "poll", Some(&session.id), plain_fallback(request)).await?;
+    if let Err(e) = Session::set_message_id(&db.pool, &session.id, sent.id.0 as i64).await {
+        tracing::warn!("Failed to store the poll message of roll call {}: {}", session.id, e);
+    }
+
+    Ok(())
+}
+
+/// The roll call itself, in MarkdownV2: when, how many players it needs, and a mention of
+/// everyone on the roster except `caller`
+pub fn render_rollcall_call(
+    starts_at: DateTime<Utc>,
+    quorum: i64,
+    expires_at: DateTime<Utc>,
+    roster: &[RosterMember],
+    caller: i64,
+) -> String {
+    let mut text = format!(
+        "📣 **Roll call\\!** Who can play today at {}? Once {} say yes it's on; if not, it's called off at {}\\.",
+        escape_markdown(&starts_at.format("%H:%M").to_string()),
+        escape_markdown(&players(quorum)),
+        escape_markdown(&expires_at.format("%H:%M").to_string()),
+    );
+    let mentions: Vec<String> = roster.iter()
+        .filter(|member| member.user_id != caller)
+        .map(|member| match &member.username {
+            Some(username) => escape_markdown(&format!("@{username}")),
+            None => format!("[player](tg://user?id={})", member.user_id),
+        })
+        .collect();
+    if !mentions.is_empty() {
+        text.push_str(&format!("\n\n{}", mentions.join(" ")));
+    }
+    text
+}
+
+/// "1 player" or "3 players"
+fn players(count: i64) -> String {
+    if count == 1 { "1 player".to_string() } else { format!("{count} players") }
+}
+
+/// Confirm the roll call behind `session_id` if it has enough players now, and tell its group
+///
+/// Does nothing for other sessions. Failures are logged, since the vote itself was saved.
+pub async fn confirm_if_quorum(bot: &Bot, db: &DatabaseManager, session_id: &str) {
+    let api = SchedulerApi::new(db.pool.clone());
+    let selection = match api.confirm_rollcall(session_id).await {
+        Ok(Some(selection)) => selection,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Failed to check the quorum of roll call {}: {}", session_id, e);
+            return;
+        }
+    };
+    tracing::info!("Roll call {} reached its quorum and is confirmed", session_id);
+
+    let announced = async {
+        let Some(session) = Session::find_by_id(&db.pool, session_id).await? else {
+            return Ok(());
+        };
+        let Some(group) = Group::find_by_id(&db.pool, session.group_id).await? else {
+            return Ok(());
+        };
+        let responses = Response::find_by_session(&db.pool, session_id).await?;
+        let names = yes_voter_names(&db.pool, &group, &responses, &selection.winner.option.id).await?;
+        let starts_at = DateTime::parse_from_rfc3339(&selection.winner.option.datetime)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now());
+        let chat_id = ChatId(group.telegram_chat_id);
+        let request = bot.send_message(chat_id, render_rollcall_confirmed(starts_at, &names))
+            .parse_mode(ParseMode::MarkdownV2);
+        logged_send(&db.pool, chat_id, "confirmation", Some(session_id), request).await?;
+        Ok::<_, BotError>(())
+    }.await;
+    if let Err(e) = announced {
+        tracing::warn!("Failed to announce confirmed roll call {}: {}", session_id, e);
+    }
+}
+
+/// Cancel the roll calls that expired by `now` without enough players and tell their groups;
+/// returns how many were cancelled
+pub async fn expire_rollcalls(bot: &Bot, db: &DatabaseManager, now: DateTime<Utc>) -> Result<usize, SchedulingError> {
+    let expired = SchedulerApi::new(db.pool.clone()).expire_rollcalls(now).await?;
+    for rollcall in &expired {
+        let session = &rollcall.tally.session;
+        let announced = async {
+            let Some(group) = Group::find_by_id(&db.pool, session.group_id).await? else {
+                return Ok(());
+            };
+            let Some(option) = rollcall.tally.options.first() else {
+                return Ok(());
+            };
+            let names = yes_voter_names(&db.pool, &group, &rollcall.tally.responses, &option.id).await?;
+            let starts_at = DateTime::parse_from_rfc3339(&option.datetime)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or(now);
+            // Queued, so the poll loses its buttons only after the group heard why
+            let chat_id = ChatId(group.telegram_chat_id);
+            let request = bot.send_message(chat_id, render_rollcall_expired(starts_at, rollcall.rollcall.quorum, &names))
+                .parse_mode(ParseMode::MarkdownV2);
+            let (pool, session_id) = (db.pool.clone(), session.id.clone());
+            outbound_queue().enqueue(chat_id, "cancellation", move || {
+                let (pool, session_id, request) = (pool.clone(), session_id.clone(), request.clone());
+                async move { logged_send(&pool, chat_id, "cancellation", Some(&session_id), request).await.map(drop) }
+            });
+            let (bot, db, session) = (bot.clone(), db.clone(), session.clone());
+            outbound_queue().enqueue(chat_id, "poll", move || {
+                let (bot, db, session) = (bot.clone(), db.clone(), session.clone());
+                async move { telegram_result("poll", refresh_poll_message(&bot, &db, &session, chat_id).await) }
+            });
+            Ok::<_, BotError>(())
+        }.await;
+        if let Err(e) = announced {
+            tracing::warn!("Failed to announce expired roll call {}: {}", session.id, e);
+        }
+    }
+    Ok(expired.len())
+}
+
+/// Names of the players who said yes to `option_id`, named as on the poll
+async fn yes_voter_names(
+    pool: &sqlx::SqlitePool,
+    group: &Group,
+    responses: &[Response],
+    option_id: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    let voters: Vec<(i64, Option<&str>)> = responses.iter()
+        .filter(|r| r.option_id == option_id && r.response == "yes")
+        .map(|r| (r.user_id, r.username.as_deref()))
+        .collect();
+    let names = display_names(pool, group, &voters).await?;
+    Ok(voters.iter()
+        .map(|(user_id, _)| names.get(user_id).cloned().unwrap_or_else(|| format!("User {user_id}")))
+        .collect())
+}
+
+/// MarkdownV2 announcement of a roll call that got enough players
+pub fn render_rollcall_confirmed(starts_at: DateTime<Utc>, names: &[String]) -> String {
+    format!(
+        "✅ **Game on today at {}\\!** {} said yes: {}",
+        escape_markdown(&starts_at.format("%H:%M").to_string()),
+        escape_markdown(&players(names.len() as i64)),
+        escape_markdown(&names.join(", ")),
+    )
+}
+
+/// MarkdownV2 summary of a roll call called off for lack of players
+pub fn render_rollcall_expired(starts_at: DateTime<Utc>, quorum: i64, names: &[String]) -> String {
+    let said_yes = match names.len() {
+        0 => "nobody said yes".to_string(),
+        count => format!("only {count} of the {} needed said yes: {}", players(quorum), names.join(", ")),
+    };
+    format!(
+        "⌛ **Roll call for {} called off**, {}\\. Maybe another time\\!",
+        escape_markdown(&starts_at.format("%H:%M").to_string()),
+        escape_markdown(&said_yes),
+    )
+}
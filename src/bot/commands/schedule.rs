@@ -1,13 +1,56 @@
 use teloxide::prelude::*;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use crate::error::BotError;
+use crate::bot::poll::{expected_voters, render_poll};
+use chrono::{DateTime, Datelike, Duration, Utc};
+use std::collections::HashSet;
 use crate::database::{connection::DatabaseManager, models::*};
+use crate::scheduling::{
+    find_unschedulable_option, likely_year, OptionTimeError, SchedulerApi, SuspectedDuplicate, MAX_OPTION_MONTHS_AHEAD,
+    MIN_OPTION_LEAD_MINUTES,
+};
+pub use crate::scheduling::{find_past_option, find_too_soon_option};
 use crate::utils::{
-    datetime::{parse_datetime, format_datetime}, 
-    markdown::escape_markdown,
+    datetime::{format_datetime, format_datetime_with_year, invalid_european_date, parse_datetime, split_duration_suffix, ParseOutcome},
     validation::{validate_session_title, validate_time_options, validate_telegram_chat_id},
-    feedback::{CommandFeedback, ProgressTracker}
+    feedback::{CommandFeedback, ProgressTracker},
+    i18n,
+    outbound::{logged_send, plain_fallback},
 };
-use chrono::Utc;
+
+/// Optional `--flag` switches accepted after the `/schedule` time options
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScheduleFlags {
+    /// `--anonymous`: hide vote tallies until the session is confirmed
+    pub anonymous: bool,
+}
+
+/// Split `--flag` tokens out of the raw `/schedule` options string
+///
+/// Returns the remaining options (with any surrounding quotes removed) and the parsed flags,
+/// or an error naming the first unknown flag.
+pub fn split_schedule_flags(options: &str) -> Result<(String, ScheduleFlags), String> {
+    let mut flags = ScheduleFlags::default();
+    let mut remaining = Vec::new();
+    
+    for token in options.split_whitespace() {
+        match token.strip_prefix("--") {
+            Some("anonymous") => flags.anonymous = true,
+            Some(unknown) => return Err(format!("Unknown option '--{unknown}'")),
+            None => remaining.push(token),
+        }
+    }
+    
+    let remaining = remaining.join(" ");
+    let remaining = remaining.trim();
+    let remaining = if remaining.starts_with('"') && remaining.ends_with('"') && remaining.len() > 1 {
+        &remaining[1..remaining.len() - 1]
+    } else {
+        remaining
+    };
+    
+    Ok((remaining.to_string(), flags))
+}
 
 pub async fn handle_schedule(
     bot: Bot,
@@ -15,7 +58,7 @@ pub async fn handle_schedule(
     title: String,
     options: String,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
+) -> Result<(), BotError> {
     let chat_id = msg.chat.id.0;
     let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
     let username = msg.from().and_then(|u| u.username.as_ref()).map_or("unknown", |v| v);
@@ -32,6 +75,17 @@ pub async fn handle_schedule(
     // Start progress tracking
     progress.start("Creating new D&D session...").await?;
     
+    // Pull out --flags before validating the time options
+    let (options, flags) = match split_schedule_flags(&options) {
+        Ok(split) => split,
+        Err(e) => {
+            let suggestion = "Supported options: --anonymous (hide votes until the session is confirmed).";
+            CommandFeedback::new(bot.clone(), msg.chat.id).validation_error(&e, suggestion).await?;
+            progress.error("Failed to create session due to an unknown option").await?;
+            return Ok(());
+        }
+    };
+    
     // Validate inputs
     tracing::debug!("Validating chat_id: {}", chat_id);
     if let Err(e) = validate_telegram_chat_id(chat_id) {
@@ -43,8 +97,12 @@ pub async fn handle_schedule(
         return Ok(());
     }
     
+    // Without a title, one is made up from the options' week once they are parsed
+    let auto_titled = title.trim().is_empty();
     tracing::debug!("Validating session title: '{}'", title);
-    if let Err(e) = validate_session_title(&title) {
+    if auto_titled {
+        tracing::debug!("No title given, naming the session after its week");
+    } else if let Err(e) = validate_session_title(&title) {
         tracing::warn!("Session title validation failed: '{}' - {}", title, e);
         let error_msg = format!("Invalid session title: {e}");
         let suggestion = "Use a title between 3-100 characters. Example: 'Weekly D&D Session'";
@@ -81,9 +139,8 @@ pub async fn handle_schedule(
         Ok(None) => {
             tracing::debug!("No group found for chat {}, creating new group", chat_id);
             progress.next_step("Setting up new group and creating session...").await?;
-            let new_group = Group::create(&db.pool, chat_id).await.map_err(|e| {
+            let new_group = Group::create(&db.pool, chat_id).await.inspect_err(|e| {
                 tracing::error!("Failed to create group for chat {}: {}", chat_id, e);
-                teloxide::RequestError::Api(teloxide::ApiError::Unknown(e.to_string()))
             })?;
             tracing::info!("Created new group {} for chat {}", new_group.id, chat_id);
             new_group
@@ -91,116 +148,379 @@ pub async fn handle_schedule(
         Err(e) => {
             tracing::error!("Database error looking up group for chat {}: {}", chat_id, e);
             progress.error("Failed to access group information").await?;
-            return Err(teloxide::RequestError::Api(teloxide::ApiError::Unknown(e.to_string())));
+            return Err(e.into());
         }
     };
     
-    // Create session
-    tracing::debug!("Creating session '{}' for group {} by user {}", title, group.id, user_id);
-    let session = Session::create(&db.pool, group.id, title.clone(), user_id).await.map_err(|e| {
-        tracing::error!("Failed to create session '{}' for group {}: {}", title, group.id, e);
-        teloxide::RequestError::Api(teloxide::ApiError::Unknown(e.to_string()))
-    })?;
-    tracing::info!("Created session {} ('{}') for group {} by user {}", session.id, title, group.id, user_id);
-    
-    // Parse and create session options
-    let mut session_options = Vec::new();
+    // Parse all time options before creating the session
+    let mut datetimes = Vec::new();
+    let mut durations = Vec::new();
+    let mut guesses = Vec::new();
     let total_options = validated_options.len();
     
     for (i, option_str) in validated_options.iter().enumerate() {
-        // Parse the datetime from the option string
-        let datetime = match parse_datetime(option_str) {
-            Ok(dt) => dt,
-            Err(_e) => {
-                let error_msg = format!("Could not parse date/time: '{option_str}'");
-                let suggestion = "Please use formats like 'Friday 19:00', 'Monday 14:30', or 'Tuesday 20:00'";
-                CommandFeedback::new(bot.clone(), msg.chat.id).validation_error(&error_msg, suggestion).await?;
-                progress.error(&format!("Failed to parse time option {}/{}", i + 1, total_options)).await?;
-                return Ok(());
+        // Parse the datetime from the option string, minus any "(3h)" style duration
+        let (time_text, duration) = split_duration_suffix(option_str);
+        if let Some(reason) = invalid_european_date(time_text) {
+            let error_msg = format!("Time option '{option_str}' isn't a date: {reason}");
+            let suggestion = "Check the day and month. Dates are written day first, like '15.08.25 19:00'.";
+            CommandFeedback::new(bot.clone(), msg.chat.id).validation_error(&error_msg, suggestion).await?;
+            progress.error(&format!("Failed to create session: option {}/{} isn't a date", i + 1, total_options)).await?;
+            return Ok(());
+        }
+        let datetime = match parse_datetime(time_text) {
+            ParseOutcome::Exact(dt) => dt,
+            ParseOutcome::Fallback(dt) => {
+                tracing::info!("Guessed {} for unrecognised time option '{}' (option {}/{})", dt, option_str, i + 1, total_options);
+                guesses.push((option_str.clone(), dt));
+                dt
             }
         };
-        
-        let option = SessionOption::create(&db.pool, session.id.clone(), datetime, 240).await.map_err(|e| {
+        datetimes.push(datetime);
+        durations.push(duration);
+    }
+    
+    let title = match datetimes.iter().min() {
+        Some(first) if auto_titled => {
+            let day = first.date_naive();
+            i18n::t_auto_title(group.lang(), day - Duration::days(day.weekday().num_days_from_monday().into()))
+        }
+        _ => title,
+    };
+    
+    // A poll for a time that has passed, or is years off, is almost always a mistyped date
+    let now = Utc::now();
+    if let Some((i, error)) = find_unschedulable_option(&datetimes, now) {
+        let (error_msg, suggestion) = option_time_message(&validated_options[i], datetimes[i], now, error);
+        CommandFeedback::new(bot.clone(), msg.chat.id).validation_error(&error_msg, &suggestion).await?;
+        progress.error(&format!("Failed to create session: option {}/{} can't be scheduled", i + 1, total_options)).await?;
+        return Ok(());
+    }
+    
+    // Respect the group's minimum notice period
+    if let Some(i) = find_too_soon_option(&datetimes, Utc::now(), group.min_notice_hours) {
+        let error_msg = format!(
+            "Time option '{}' is less than {} hours away",
+            validated_options[i], group.min_notice_hours
+        );
+        let suggestion = format!(
+            "This group requires at least {} hours' notice. Pick a time further ahead, or change the minimum in /settings.",
+            group.min_notice_hours
+        );
+        CommandFeedback::new(bot.clone(), msg.chat.id).validation_error(&error_msg, &suggestion).await?;
+        progress.error(&format!("Failed to create session: option {}/{} is too soon", i + 1, total_options)).await?;
+        return Ok(());
+    }
+    
+    // Identical times would only split the votes, so keep the first of each
+    let (datetimes, duplicates) = dedupe_parsed_options(&datetimes);
+    let durations: Vec<Option<i64>> = durations.into_iter()
+        .enumerate()
+        .filter(|(i, _)| !duplicates.contains(i))
+        .map(|(_, duration)| duration)
+        .collect();
+    if !duplicates.is_empty() {
+        let dropped = duplicates.iter()
+            .map(|&i| format!("'{}' (option {})", validated_options[i], i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+        tracing::info!("Dropped {} duplicate time options for session '{}'", duplicates.len(), title);
+        CommandFeedback::new(bot.clone(), msg.chat.id)
+            .warning(&format!("Skipped duplicate time options: {dropped} — each is the same time as an earlier option"))
+            .await?;
+    }
+    
+    let options: Vec<PendingOption> = datetimes.iter()
+        .zip(&durations)
+        .map(|(starts_at, duration)| PendingOption { starts_at: *starts_at, duration: *duration })
+        .collect();
+    
+    // Two polls for the same game split the votes, so ask first when this looks like one
+    let api = SchedulerApi::new(db.pool.clone());
+    let suspects = api.find_suspected_duplicates(group.id, &title, &datetimes).await?;
+    if !suspects.is_empty() {
+        let pending = PendingSession::create(&db.pool, group.id, user_id, &title, flags.anonymous, &options).await?;
+        tracing::info!("Held back session '{}' for group {}: it looks like {} active sessions", title, group.id, suspects.len());
+        progress.error("Not created yet: the group may already have this session").await?;
+        bot.send_message(msg.chat.id, render_duplicate_warning(&title, &suspects))
+            .reply_markup(duplicate_warning_keyboard(&pending.id))
+            .await?;
+        return Ok(());
+    }
+    
+    let (session, session_options) = post_new_session(&bot, db, &group, user_id, &title, flags.anonymous, &options).await?;
+    progress.next_step(&format!("Created session with {} time options", session_options.len())).await?;
+    
+    // Complete progress and send detailed success feedback
+    let mut success_message = i18n::t_session_created(
+        group.lang(),
+        &title,
+        session_options.len(),
+        &session.display_ref(),
+        &session.id[..8], // Show first 8 chars of ID
+        session.anonymous,
+    );
+    
+    // A stray year (e.g. "01.02.26" meant for this year) shows up as one option far out
+    let outliers = find_far_future_outliers(&datetimes, Utc::now());
+    if !outliers.is_empty() {
+        let named = outliers.iter()
+            .map(|&i| format_datetime(&datetimes[i]))
+            .collect::<Vec<_>>()
+            .join(", ");
+        success_message.push_str(&format!(
+            "\n\n⚠️ Please double-check {named}: more than {FAR_FUTURE_OUTLIER_DAYS} days away while other options are within {NEAR_TERM_OPTION_DAYS} days. Was the year right?"
+        ));
+    }
+    
+    if auto_titled {
+        success_message.push_str(&i18n::t_auto_titled(group.lang(), &title, &session.display_ref()));
+    }
+    
+    for (input, guessed) in &guesses {
+        success_message.push_str(&format!("\n\n{}", guessed_time_warning(input, *guessed)));
+    }
+    
+    progress.complete(&success_message).await?;
+    
+    Ok(())
+}
+
+/// Create a session with `options` in `group` and post its poll to the group's chat
+pub async fn post_new_session(
+    bot: &Bot,
+    db: &DatabaseManager,
+    group: &Group,
+    created_by: i64,
+    title: &str,
+    anonymous: bool,
+    options: &[PendingOption],
+) -> Result<(Session, Vec<SessionOption>), BotError> {
+    let chat_id = ChatId(group.telegram_chat_id);
+    tracing::debug!("Creating session '{}' for group {} by user {}", title, group.id, created_by);
+    let api = SchedulerApi::new(db.pool.clone());
+    let session = api.create_session(group.id, title, created_by, anonymous).await.inspect_err(|e| {
+        tracing::error!("Failed to create session '{}' for group {}: {}", title, group.id, e);
+    })?;
+    tracing::info!("Created session {} ('{}') for group {} by user {}", session.id, title, group.id, created_by);
+    
+    // Options without an inline duration last the group's default
+    let mut session_options = Vec::with_capacity(options.len());
+    for option in options {
+        let option = api.add_option(&session.id, option.starts_at, option.duration).await.inspect_err(|e| {
             tracing::error!("Failed to create session option: {}", e);
-            teloxide::RequestError::Api(teloxide::ApiError::Unknown(e.to_string()))
         })?;
         session_options.push(option);
     }
     
-    progress.next_step(&format!("Created session with {} time options", session_options.len())).await?;
+    send_poll(bot, db, chat_id, &session, &session_options).await?;
+    Ok((session, session_options))
+}
+
+/// Post the poll of a new session with no votes yet, and remember its message for updates
+pub async fn send_poll(
+    bot: &Bot,
+    db: &DatabaseManager,
+    chat_id: ChatId,
+    session: &Session,
+    options: &[SessionOption],
+) -> Result<(), BotError> {
+    let expected = expected_voters(&db.pool, session).await?;
+    let (message_text, keyboard) = render_poll(session, options, &[], expected);
     
-    // Create inline keyboard
-    let mut keyboard_rows = Vec::new();
-    for option in session_options.iter() {
-        let row = vec![
-            InlineKeyboardButton::callback(
-                "✅ Yes",
-                format!("{}:{}:yes", session.id, option.id),
-            ),
-            InlineKeyboardButton::callback(
-                "❌ No", 
-                format!("{}:{}:no", session.id, option.id),
-            ),
-            InlineKeyboardButton::callback(
-                "❓ Maybe",
-                format!("{}:{}:maybe", session.id, option.id),
-            ),
-        ];
-        keyboard_rows.push(row);
-    }
-    
-    let keyboard = InlineKeyboardMarkup::new(keyboard_rows);
-    
-    let mut message_text = format!("🎲 **{title}**\n\nSelect your availability for each option:\n\n");
-    
-    for (i, option) in session_options.iter().enumerate() {
-        let datetime_str = chrono::DateTime::parse_from_rfc3339(&option.datetime)
-            .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
-            .unwrap_or_else(|_| option.datetime.clone());
-        
-        message_text.push_str(&format!("**{}\\. {}**\n", i + 1, escape_markdown(&datetime_str)));
-        message_text.push_str("✅ 0 • ❌ 0 • ❓ 0\n\n");
-    }
-    
-    let sent_message = bot.send_message(msg.chat.id, message_text)
+    let request = bot.send_message(chat_id, message_text)
         .reply_markup(keyboard)
-        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-        .await?;
+        .parse_mode(teloxide::types::ParseMode::MarkdownV2);
+    let sent_message = logged_send(&db.pool, chat_id, "poll", Some(&session.id), plain_fallback(request)).await?;
     
     // Store the message ID in the session for future updates
-    if let Err(e) = update_session_message_id(&db.pool, &session.id, sent_message.id.0).await {
+    if let Err(e) = Session::set_message_id(&db.pool, &session.id, sent_message.id.0 as i64).await {
         tracing::warn!("Failed to store message ID: {}", e);
-        CommandFeedback::new(bot.clone(), msg.chat.id).warning("Session created but message tracking may not work perfectly").await?;
+        CommandFeedback::new(bot.clone(), chat_id).warning("Session created but message tracking may not work perfectly").await?;
     }
     
-    // Complete progress and send detailed success feedback
-    let success_message = format!(
-        "Session '{}' created successfully!\n\n📊 Session Details:\n• {} time options available\n• Session ID: {}\n• Voting is now open!\n\n💡 Use /list to see all active sessions",
-        title,
-        session_options.len(),
-        &session.id[..8] // Show first 8 chars of ID
-    );
+    Ok(())
+}
+
+/// The warning `/schedule` sends instead of creating `title`, listing the sessions it looks like
+pub fn render_duplicate_warning(title: &str, suspects: &[SuspectedDuplicate]) -> String {
+    let mut text = String::from("⚠️ The group may already have this session:\n");
+    for suspect in suspects {
+        let mut reasons = Vec::new();
+        if suspect.similar_title {
+            reasons.push("similar title".to_string());
+        }
+        if !suspect.shared_dates.is_empty() {
+            let dates: Vec<String> = suspect.shared_dates.iter().map(|date| date.format("%a %d.%m").to_string()).collect();
+            reasons.push(format!("also on {}", dates.join(", ")));
+        }
+        text.push_str(&format!("• {} {} ({})\n", suspect.session.display_ref(), suspect.session.title, reasons.join(", ")));
+    }
+    text.push_str(&format!("\nCreate '{title}' anyway?"));
+    text
+}
+
+/// "Create anyway" and "Cancel" buttons for the held-back session with ID `pending_id`
+pub fn duplicate_warning_keyboard(pending_id: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback("✅ Create anyway", format!("schedule_confirm:create:{pending_id}"))],
+        vec![InlineKeyboardButton::callback("❌ Cancel", format!("schedule_confirm:cancel:{pending_id}"))],
+    ])
+}
+
+/// Handle the "Create anyway" and "Cancel" buttons under a duplicate warning
+///
+/// Only whoever ran `/schedule` can settle it. The database is updated before Telegram is told,
+/// so a failed answer doesn't leave the session half made.
+pub async fn handle_schedule_confirm_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let action = data.strip_prefix("schedule_confirm:").unwrap_or(&data);
+    let (creating, pending_id) = match action.split_once(':') {
+        Some(("create", pending_id)) => (true, pending_id),
+        Some(("cancel", pending_id)) => (false, pending_id),
+        _ => {
+            bot.answer_callback_query(q.id).text("Invalid request").await?;
+            return Ok(());
+        }
+    };
     
-    progress.complete(&success_message).await?;
+    let prompt = q.message.as_ref().map(|message| (message.chat.id, message.id));
+    let Some(pending) = PendingSession::find_by_id(&db.pool, pending_id).await? else {
+        bot.answer_callback_query(q.id).text("This prompt has expired, use /schedule again").await?;
+        if let Some((chat_id, message_id)) = prompt {
+            bot.edit_message_reply_markup(chat_id, message_id).await?;
+        }
+        return Ok(());
+    };
+    if pending.created_by != q.from.id.0 as i64 {
+        bot.answer_callback_query(q.id).text("Only the person who ran /schedule can decide this").await?;
+        return Ok(());
+    }
+    // Whoever removes it settles it, so a double tap creates one session
+    if !PendingSession::delete(&db.pool, &pending.id).await? {
+        bot.answer_callback_query(q.id).await?;
+        return Ok(());
+    }
+    
+    let text = if !creating {
+        tracing::info!("User {} dropped held-back session '{}'", q.from.id, pending.title);
+        format!("👍 '{}' was not created", pending.title)
+    } else {
+        let options = pending.parsed_options();
+        let datetimes: Vec<DateTime<Utc>> = options.iter().map(|option| option.starts_at).collect();
+        match Group::find_by_id(&db.pool, pending.group_id).await? {
+            None => "This group no longer exists".to_string(),
+            Some(_) if options.is_empty() => "This prompt has expired, use /schedule again".to_string(),
+            Some(_) if find_past_option(&datetimes, Utc::now()).is_some() => {
+                format!("A time option of '{}' has passed in the meantime, use /schedule again", pending.title)
+            }
+            Some(group) => {
+                let (session, session_options) =
+                    post_new_session(&bot, db, &group, pending.created_by, &pending.title, pending.anonymous, &options).await?;
+                let created = i18n::t_session_created(
+                    group.lang(),
+                    &session.title,
+                    session_options.len(),
+                    &session.display_ref(),
+                    &session.id[..8],
+                    session.anonymous,
+                );
+                format!("✅ {created}")
+            }
+        }
+    };
+    
+    bot.answer_callback_query(q.id).await?;
+    if let Some((chat_id, message_id)) = prompt {
+        bot.edit_message_text(chat_id, message_id, text).await?;
+    }
     
     Ok(())
 }
 
-async fn update_session_message_id(
-    pool: &sqlx::SqlitePool,
-    session_id: &str,
-    message_id: i32,
-) -> Result<(), sqlx::Error> {
-    let message_id_i64 = message_id as i64;
-    sqlx::query!(
-        "UPDATE sessions SET message_id = ? WHERE id = ?",
-        message_id_i64,
-        session_id
-    )
-    .execute(pool)
-    .await?;
+/// Warning for a time option the parser didn't understand and filled in with its default
+pub fn guessed_time_warning(input: &str, guessed: DateTime<Utc>) -> String {
+    format!("⚠️ I guessed {} for '{input}' — please re-schedule if wrong", format_datetime(&guessed))
+}
+
+/// Remove repeated start times, keeping the first occurrence of each
+///
+/// Returns the unique times in their original order, and the indices of the dropped duplicates.
+pub fn dedupe_parsed_options(datetimes: &[DateTime<Utc>]) -> (Vec<DateTime<Utc>>, Vec<usize>) {
+    let mut seen = HashSet::new();
+    let mut unique = Vec::with_capacity(datetimes.len());
+    let mut duplicates = Vec::new();
     
-    Ok(())
+    for (i, datetime) in datetimes.iter().enumerate() {
+        if seen.insert(*datetime) {
+            unique.push(*datetime);
+        } else {
+            duplicates.push(i);
+        }
+    }
+    
+    (unique, duplicates)
+}
+
+/// Options further out than this are suspicious when others are near term
+pub const FAR_FUTURE_OUTLIER_DAYS: i64 = 90;
+/// Options within this many days count as near term for outlier detection
+pub const NEAR_TERM_OPTION_DAYS: i64 = 21;
+
+/// The error and suggestion for time option `option`, read as `starts_at`, that
+/// [`check_option_time`](crate::scheduling::check_option_time) turned down at `now`
+///
+/// A past option shows the date it was read as, with the year, since a two-digit year ("01.01.20")
+/// is the usual mistake; see [`likely_year`].
+pub fn option_time_message(
+    option: &str,
+    starts_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    error: OptionTimeError,
+) -> (String, String) {
+    let when = format_datetime_with_year(&starts_at);
+    match error {
+        OptionTimeError::Past => (
+            format!("Time option '{option}' is in the past: it reads as {when}"),
+            match likely_year(starts_at, now) {
+                Some(year) => format!(
+                    "Did you mean {year}? Writing the year in full, like '{}', avoids the mix-up.",
+                    starts_at.with_year(year).unwrap_or(starts_at).format("%d.%m.%Y %H:%M")
+                ),
+                None => "Session times must be in the future. Check the date, or use a weekday like 'Friday 19:00' for the next one.".to_string(),
+            },
+        ),
+        OptionTimeError::TooSoon => (
+            format!("Time option '{option}' starts less than {MIN_OPTION_LEAD_MINUTES} minutes from now"),
+            "Give players time to see the poll: pick a time at least an hour ahead.".to_string(),
+        ),
+        OptionTimeError::TooFarAhead => (
+            format!("Time option '{option}' is more than {MAX_OPTION_MONTHS_AHEAD} months away: it reads as {when}"),
+            format!("Polls plan at most {MAX_OPTION_MONTHS_AHEAD} months ahead. Check the year."),
+        ),
+    }
+}
+
+/// Indices of options more than `FAR_FUTURE_OUTLIER_DAYS` away, if any other option is within
+/// `NEAR_TERM_OPTION_DAYS` of `now`
+///
+/// A mix like that usually means one date got the wrong year, while a set of options that are
+/// all far out is deliberate and produces no outliers.
+pub fn find_far_future_outliers(datetimes: &[DateTime<Utc>], now: DateTime<Utc>) -> Vec<usize> {
+    let has_near_term = datetimes.iter().any(|dt| *dt - now <= Duration::days(NEAR_TERM_OPTION_DAYS));
+    if !has_near_term {
+        return Vec::new();
+    }
+    
+    datetimes.iter()
+        .enumerate()
+        .filter(|(_, dt)| **dt - now > Duration::days(FAR_FUTURE_OUTLIER_DAYS))
+        .map(|(i, _)| i)
+        .collect()
 }
 
 // Helper function to escape markdown characters
@@ -0,0 +1,73 @@
+//! `/sent`: what the bot actually sent about a session, from the outbound message log
+
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use crate::bot::commands::session_management::resolve_session;
+use crate::bot::permissions::session_manager_role;
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::utils::{datetime::format_datetime, feedback::CommandFeedback, markdown::escape_markdown};
+use chrono::{DateTime, Utc};
+
+/// How many of the latest log entries `/sent` shows
+pub const SENT_LOG_LIMIT: usize = 30;
+
+/// Render a session's outbound log as MarkdownV2, showing the newest `SENT_LOG_LIMIT` entries
+pub fn render_sent_log(session: &Session, entries: &[OutboundMessage]) -> String {
+    let mut text = format!(
+        "📨 **Sent for {}** {}\n\n",
+        escape_markdown(&session.title),
+        escape_markdown(&session.display_ref())
+    );
+
+    if entries.is_empty() {
+        text.push_str("Nothing has been sent for this session yet\\.\n");
+        return text;
+    }
+
+    let skipped = entries.len().saturating_sub(SENT_LOG_LIMIT);
+    if skipped > 0 {
+        text.push_str(&format!("_{skipped} earlier messages not shown_\n"));
+    }
+    for entry in &entries[skipped..] {
+        let at = DateTime::parse_from_rfc3339(&entry.sent_at)
+            .map(|dt| format!("{} UTC", format_datetime(&dt.with_timezone(&Utc))))
+            .unwrap_or_else(|_| entry.sent_at.clone());
+        let outcome = match (entry.success, &entry.error) {
+            (true, _) => "✅".to_string(),
+            (false, Some(error)) => format!("❌ {}", escape_markdown(error)),
+            (false, None) => "❌".to_string(),
+        };
+        text.push_str(&format!("• {} · {} {}\n", escape_markdown(&at), escape_markdown(&entry.kind), outcome));
+    }
+
+    text
+}
+
+/// List the polls, announcements and reminders the bot sent for a session (session managers only)
+pub async fn handle_sent(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let (_group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    if session_manager_role(&bot, db, msg.chat.id, &session, user_id).await.is_none() {
+        return Err(BotError::PermissionDenied);
+    }
+
+    let entries = OutboundMessage::find_by_session(&db.pool, &session.id).await?;
+    bot.send_message(msg.chat.id, render_sent_log(&session, &entries))
+        .parse_mode(ParseMode::MarkdownV2)
+        .await?;
+
+    Ok(())
+}
@@ -1,18 +1,43 @@
 use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode};
+use crate::error::BotError;
+use crate::bot::commands::availability::ask_start_time;
+use crate::bot::commands::results::results_message;
+use crate::bot::commands::undo::{record_undo, undo_keyboard};
+use crate::bot::dialogue::BotDialogue;
+use crate::bot::duties::assign_session_duties;
+use crate::bot::permissions::{group_manager_role, session_manager_role};
+use crate::bot::poll::{refresh_poll_message, repost_poll_message};
+use crate::bot::selection::DEFAULT_VOTE_WEIGHTS;
+use crate::scheduling::{
+    check_option_time, likely_year, OptionTimeError, SchedulerApi, SchedulingError, MAX_OPTION_MONTHS_AHEAD, VOTABLE_STATUSES,
+};
+use crate::services::outbound_queue::{outbound_queue, telegram_result};
 use crate::database::{connection::DatabaseManager, models::*};
 use crate::utils::{
-    datetime::{parse_datetime, format_datetime},
+    datetime::{parse_datetime, format_datetime, format_datetime_with_year, invalid_european_date, ParseOutcome},
     feedback::CommandFeedback,
-    validation::validate_session_id
+    i18n,
+    markdown::escape_markdown,
+    validation::{validate_session_id, validate_session_notes, MAX_NOTES_LENGTH}
 };
 use chrono::Utc;
 
+/// Maximum number of sessions listed individually in the /closeall prompt and summary
+const CLOSEALL_MAX_LISTED: usize = 15;
+
+/// Confirm a session for its best option, or for option number `option`
+///
+/// With a `dialogue`, an active availability poll isn't confirmed yet: the day is picked and
+/// the user is asked for its start time first. Without one it's confirmed as it is.
 pub async fn handle_confirm(
     bot: Bot,
     msg: Message,
     session_id: String,
+    option: Option<usize>,
+    dialogue: Option<BotDialogue>,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
+) -> Result<(), BotError> {
     let chat_id = msg.chat.id.0;
     let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
     let username = msg.from().and_then(|u| u.username.as_ref()).map_or("unknown", |v| v);
@@ -27,157 +52,148 @@ pub async fn handle_confirm(
     // Send processing message
     let processing_msg = feedback.send_processing("Confirming session...").await?;
     
-    // Validate session ID format
-    tracing::debug!("Validating session ID format: '{}'", session_id);
-    if let Err(e) = validate_session_id(&session_id) {
-        tracing::warn!("Session ID validation failed for '{}': {}", session_id, e);
-        let suggestion = "Session IDs must be 8-50 characters long and contain only letters, numbers, and hyphens. Use /list to see valid session IDs.";
-        feedback.validation_error(&e.to_string(), suggestion).await?;
-        return Ok(());
-    }
-    
-    // Validate session exists and belongs to this group
-    tracing::debug!("Looking up session: '{}'", session_id);
-    let session = match Session::find_by_id(&db.pool, &session_id).await {
-        Ok(Some(session)) => {
-            tracing::debug!("Found session '{}' with title '{}' in group {}", session_id, session.title, session.group_id);
-            session
-        },
-        Ok(None) => {
-            tracing::warn!("Session not found: '{}'", session_id);
-            let error_msg = "Session not found";
-            let suggestion = "Please check the session ID. Use /list to see active sessions.".to_string();
-            feedback.validation_error(error_msg, &suggestion).await?;
-            return Ok(());
-        }
-        Err(e) => {
-            tracing::error!("Failed to find session: {}", e);
-            feedback.error("Failed to retrieve session information from database").await?;
-            return Ok(());
-        }
+    // Resolve the session reference within this group
+    tracing::debug!("Resolving session reference: '{}'", session_id);
+    let (group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
     };
+    tracing::debug!("Found session '{}' with title '{}' in group {}", session.id, session.title, session.group_id);
+    let session_id = session.id.clone();
+    let lang = group.lang();
+    let feedback = feedback.logged(&db.pool, "confirmation", &session.id).with_language(lang);
     
-    // Check if user is the session creator
-    if session.created_by != user_id {
-        let error_msg = "Permission denied: Only the session creator can confirm sessions";
-        let suggestion = "Ask the session creator to run this command, or use /list to see who created each session.";
-        feedback.validation_error(error_msg, suggestion).await?;
-        return Ok(());
-    }
-    
-    // Check if session belongs to this group
-    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
-        Ok(Some(group)) => group,
-        Ok(None) => {
-            feedback.error("Group not found in database").await?;
-            return Ok(());
-        }
-        Err(e) => {
-            tracing::error!("Failed to find group: {}", e);
-            feedback.error("Failed to retrieve group information").await?;
-            return Ok(());
-        }
+    // Check if user may manage this session
+    let Some(role) = session_manager_role(&bot, db, msg.chat.id, &session, user_id).await else {
+        return Err(BotError::PermissionDenied);
     };
+    tracing::debug!("User {} may manage session '{}' as {:?}", user_id, session.id, role);
     
-    if session.group_id != group.id {
-        let error_msg = "Session doesn't belong to this group";
-        let suggestion = "This session was created in a different group. Use /list to see sessions for this group.";
-        feedback.validation_error(error_msg, suggestion).await?;
-        return Ok(());
-    }
-    
-    // Check if session is already confirmed or cancelled
-    if session.status != "active" {
+    // Check if session is already confirmed or cancelled; a confirmed one can take more options
+    let already_confirmed = session.status == "confirmed";
+    if session.status != "active" && !(already_confirmed && option.is_some()) {
         let error_msg = format!("Session is already {}", session.status);
         let suggestion = match session.status.as_str() {
-            "confirmed" => "This session has already been confirmed. Use /list to see current status.",
+            "confirmed" => "This session has already been confirmed. Use /confirm <session_id> <option_number> to confirm another of its options too, or /list to see current status.",
             "cancelled" => "This session has been cancelled. Create a new session with /schedule if needed.",
+            "closed" => "Voting on this session has been closed. Create a new session with /schedule if needed.",
+            "archived" => "This session has been archived. Create a new session with /schedule if needed.",
             _ => "This session is not in active status. Use /list to check current status."
         };
         feedback.validation_error(&error_msg, suggestion).await?;
         return Ok(());
     }
     
-    // Get session options to find the most popular one
-    let options = match SessionOption::find_by_session(&db.pool, &session_id).await {
-        Ok(options) => {
-            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-                &format!("Analyzing {} time options...", options.len())).await?;
-            options
-        },
-        Err(e) => {
-            tracing::error!("Failed to get session options: {}", e);
-            feedback.error("Failed to retrieve session time options").await?;
-            return Ok(());
-        }
-    };
+    // An availability poll's days don't have their start time yet
+    if let Some(dialogue) = dialogue.filter(|_| session.availability && session.status == "active") {
+        return ask_start_time(&feedback, processing_msg.id, &session, option, user_id, dialogue, db).await;
+    }
     
-    // Get all responses
-    let responses = match Response::find_by_session(&db.pool, &session_id).await {
-        Ok(responses) => {
+    // Confirm the option asked for, or else the one with the best weighted score
+    let api = SchedulerApi::new(db.pool.clone());
+    let swing = match api.tally(&session_id).await {
+        Ok(tally) => {
             feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-                &format!("Processing {} player responses...", responses.len())).await?;
-            responses
-        },
+                &format!("Analyzing {} time options and {} player responses...", tally.options.len(), tally.responses.len())).await?;
+            tally.swing
+        }
         Err(e) => {
-            tracing::error!("Failed to get responses: {}", e);
-            feedback.error("Failed to retrieve player responses").await?;
+            tracing::error!("Failed to tally session {}: {}", session_id, e);
+            feedback.error("Failed to retrieve session time options and responses").await?;
             return Ok(());
         }
     };
     
-    // Find the option with the most "yes" votes
-    let mut best_option_id = None;
-    let mut max_yes_votes = 0;
-    
-    for option in &options {
-        let yes_count = responses.iter()
-            .filter(|r| r.option_id == option.id && r.response == "yes")
-            .count();
-        
-        if yes_count > max_yes_votes {
-            max_yes_votes = yes_count;
-            best_option_id = Some(option.id.clone());
-        }
-    }
-    
-    match best_option_id {
-        Some(option_id) => {
-            // Mark the winning option as confirmed and update session status
-            if let Err(e) = confirm_session_and_option(&db.pool, &session_id, &option_id).await {
-                tracing::error!("Failed to confirm session: {}", e);
-                feedback.error("Failed to save session confirmation to database").await?;
-                return Ok(());
-            }
-            
-            // Find the confirmed option for display
-            let confirmed_option = match options.iter().find(|o| o.id == option_id) {
-                Some(option) => option,
-                None => {
-                    tracing::error!("Confirmed option not found in session options");
-                    feedback.error("Internal error: Confirmed option not found").await?;
-                    return Ok(());
-                }
-            };
+    let confirmed = match option {
+        Some(number) => api.confirm_option(&session_id, number).await,
+        None => api.confirm(&session_id).await,
+    };
+    match confirmed {
+        Ok(selection) => {
+            let yes_votes = selection.winner.counts.yes;
+            let confirmed_option = &selection.winner.option;
             let datetime_str = chrono::DateTime::parse_from_rfc3339(&confirmed_option.datetime)
                 .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
                 .unwrap_or_else(|_| confirmed_option.datetime.clone());
             
             // Send detailed success message
-            let success_message = format!(
-                "Session '{}' confirmed successfully!\n\n📅 Confirmed Time: {}\n👥 {} players will attend\n\n🎯 All participants have been notified. The session is now locked in!",
-                session.title, 
-                datetime_str, 
-                max_yes_votes
-            );
+            let runner_up = selection.runner_up.as_ref().map(|runner_up| {
+                let datetime = chrono::DateTime::parse_from_rfc3339(&runner_up.option.datetime)
+                    .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
+                    .unwrap_or_else(|_| runner_up.option.datetime.clone());
+                (datetime, runner_up.counts)
+            });
+            let mut details = vec![
+                i18n::t_confirm_breakdown(lang, &selection.winner.counts, runner_up.as_ref().map(|(datetime, counts)| (datetime.as_str(), counts))),
+                i18n::t_confirm_score(lang, selection.winner.score, DEFAULT_VOTE_WEIGHTS.yes, DEFAULT_VOTE_WEIGHTS.maybe),
+            ];
+            // Picking an option by number is a choice made knowing the votes
+            if option.is_none() {
+                details.extend(swing.map(|(other, swing)| {
+                    let when = chrono::DateTime::parse_from_rfc3339(&other.datetime)
+                        .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
+                        .unwrap_or_else(|_| other.datetime.clone());
+                    i18n::t_maybe_swing(lang, &when, &swing)
+                }));
+            }
+            details.extend(session.notes.as_deref().map(|notes| i18n::t_notes(lang, notes)));
+            // The session stays confirmed even if nobody could be given the duties; they go round
+            // once per session, so confirming another option keeps them as they are
+            if !already_confirmed {
+                match assign_session_duties(&db.pool, &group, &session_id, &confirmed_option.id).await {
+                    Ok(duties) => details.extend(duties),
+                    Err(e) => tracing::warn!("Failed to assign duties for session {}: {}", session_id, e),
+                }
+            }
+            let success_message = i18n::t_session_confirmed(lang, &session.title, &datetime_str, yes_votes, &details);
             
             feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, &success_message).await?;
+            
+            // The table is easier to scan than the tallies; the confirmation stands without it.
+            // Both go through the chat's queue so a retried send can't arrive out of order
+            match results_message(&db.pool, &group, &session).await {
+                Ok(results) => {
+                    let (bot, chat_id) = (bot.clone(), msg.chat.id);
+                    outbound_queue().enqueue(chat_id, "results", move || {
+                        let request = bot.send_message(chat_id, results.clone()).parse_mode(ParseMode::MarkdownV2);
+                        async move { request.await.map(drop) }
+                    });
+                }
+                Err(e) => tracing::warn!("Failed to load the results of session {}: {}", session_id, e),
+            }
+            
+            // Anonymous polls reveal their tallies once the session is confirmed
+            if session.anonymous {
+                let mut confirmed_session = session.clone();
+                confirmed_session.status = "confirmed".to_string();
+                let (bot, db, chat_id) = (bot.clone(), db.clone(), msg.chat.id);
+                outbound_queue().enqueue(chat_id, "poll", move || {
+                    let (bot, db, session) = (bot.clone(), db.clone(), confirmed_session.clone());
+                    async move { telegram_result("poll", refresh_poll_message(&bot, &db, &session, chat_id).await) }
+                });
+            }
         }
-        None => {
+        Err(SchedulingError::NoYesVotes) => {
             let error_msg = "Cannot confirm session: No time options have 'yes' votes";
             let suggestion = "Ask players to vote on the available time options first. Use /list to see current voting status.";
             feedback.validation_error(error_msg, suggestion).await?;
         }
+        Err(SchedulingError::RequiredPlayerMissing) => {
+            let error_msg = "Cannot confirm session: A required player said no to every time option with 'yes' votes";
+            let suggestion = "Use /results to see who can't make it, or /confirm <session_id> <option_number> to confirm an option anyway.";
+            feedback.validation_error(error_msg, suggestion).await?;
+        }
+        Err(SchedulingError::NotActive { status }) => {
+            let error_msg = format!("Session is already {status}");
+            feedback.validation_error(&error_msg, "Someone else changed this session just now. Use /list to see its current status.").await?;
+        }
+        Err(SchedulingError::Invalid(error_msg)) => {
+            feedback.validation_error(&error_msg, "The options are numbered as on the poll. Use /list to see them.").await?;
+        }
+        Err(e) => {
+            tracing::error!("Failed to confirm session: {}", e);
+            feedback.error("Failed to save session confirmation to database").await?;
+        }
     }
     
     Ok(())
@@ -188,7 +204,7 @@ pub async fn handle_cancel(
     msg: Message,
     session_id: String,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
+) -> Result<(), BotError> {
     let chat_id = msg.chat.id.0;
     let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
     let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
@@ -196,140 +212,616 @@ pub async fn handle_cancel(
     // Send processing message
     let processing_msg = feedback.send_processing("Cancelling session...").await?;
     
-    // Validate session ID format
-    if let Err(e) = validate_session_id(&session_id) {
-        let suggestion = "Session IDs must be 8-50 characters long and contain only letters, numbers, and hyphens. Use /list to see valid session IDs.";
-        feedback.validation_error(&e.to_string(), suggestion).await?;
+    // Resolve the session reference within this group
+    let (group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+    let feedback = feedback.with_language(group.lang());
+    
+    // Check if user may manage this session
+    let Some(role) = session_manager_role(&bot, db, msg.chat.id, &session, user_id).await else {
+        return Err(BotError::PermissionDenied);
+    };
+    tracing::debug!("User {} may manage session '{}' as {:?}", user_id, session.id, role);
+    
+    // Check if session is already cancelled
+    if session.status == "cancelled" {
+        let error_msg = "Session is already cancelled";
+        let suggestion = "This session has already been cancelled. Use /list to see current status of all sessions.";
+        feedback.validation_error(error_msg, suggestion).await?;
+        return Ok(());
+    }
+    if session.status == "archived" {
+        let error_msg = "Session is archived";
+        let suggestion = "Archived sessions can't be changed anymore. Create a new session with /schedule if needed.";
+        feedback.validation_error(error_msg, suggestion).await?;
+        return Ok(());
+    }
+
+    // A confirmed session has players counting on it, so ask first
+    if session.status == "confirmed" {
+        let prompt = format!(
+            "Cancel {} {}? It is already confirmed, and everyone who planned to come will see it called off.",
+            session.display_ref(),
+            session.title
+        );
+        CommandFeedback::new(bot.clone(), msg.chat.id)
+            .with_language(group.lang())
+            .update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Warning, &prompt)
+            .await?;
+        bot.edit_message_reply_markup(msg.chat.id, processing_msg.id)
+            .reply_markup(cancel_confirmation_keyboard(&session))
+            .await?;
         return Ok(());
     }
     
-    // Validate session exists and belongs to this group
-    let session = match Session::find_by_id(&db.pool, &session_id).await {
-        Ok(Some(session)) => session,
-        Ok(None) => {
-            let error_msg = "Session not found";
-            let suggestion = "Please check the session ID. Use /list to see active sessions.";
-            feedback.validation_error(error_msg, suggestion).await?;
+    cancel_and_report(&bot, db, msg.chat.id, &group, &session, user_id, processing_msg.id).await
+}
+
+/// Buttons of the prompt before cancelling a confirmed session
+fn cancel_confirmation_keyboard(session: &Session) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback("🚫 Yes, cancel it", format!("cancel:confirm:{}", session.id))],
+        vec![InlineKeyboardButton::callback("❌ Keep it", "cancel:abort")],
+    ])
+}
+
+/// Cancel `session` and turn message `message_id` into the success message, with an undo button
+async fn cancel_and_report(
+    bot: &Bot,
+    db: &DatabaseManager,
+    chat_id: ChatId,
+    group: &Group,
+    session: &Session,
+    user_id: i64,
+    message_id: MessageId,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), chat_id)
+        .logged(&db.pool, "cancellation", &session.id)
+        .with_language(group.lang());
+    
+    if let Err(e) = cancel_session(&db.pool, &session.id).await {
+        tracing::error!("Failed to cancel session: {}", e);
+        feedback.error("Failed to save session cancellation to database").await?;
+        return Ok(());
+    }
+    let undo = record_undo(db, group.id, user_id, &UndoableAction::Cancel {
+        session_id: session.id.clone(),
+        previous_status: session.status.clone(),
+        previous_deadline: session.deadline.clone(),
+    }).await;
+    
+    // Send detailed success message
+    let success_message = i18n::t_session_cancelled(group.lang(), &session.title);
+    
+    feedback.update_message(message_id, crate::utils::feedback::FeedbackType::Success, &success_message).await?;
+    if let Some(undo) = undo {
+        bot.edit_message_reply_markup(chat_id, message_id)
+            .reply_markup(undo_keyboard(&undo))
+            .await?;
+    }
+    
+    Ok(())
+}
+
+/// Handles the inline confirmation buttons shown before cancelling a confirmed session
+pub async fn handle_cancel_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This prompt has expired").await?;
+        return Ok(());
+    };
+    
+    let action = data.strip_prefix("cancel:").unwrap_or(&data);
+    if action == "abort" {
+        bot.answer_callback_query(q.id).text("Nothing was cancelled").await?;
+        bot.edit_message_text(message.chat.id, message.id, "👍 The session stays confirmed\\.")
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+        return Ok(());
+    }
+    
+    let Some(session_id) = action.strip_prefix("confirm:") else {
+        bot.answer_callback_query(q.id).text("Invalid request").await?;
+        return Ok(());
+    };
+    
+    // Only sessions of this chat's group can be cancelled from here
+    let (group, session) = match (
+        Group::find_by_chat_id(&db.pool, message.chat.id.0).await,
+        Session::find_by_id(&db.pool, session_id).await,
+    ) {
+        (Ok(Some(group)), Ok(Some(session))) if session.group_id == group.id => (group, session),
+        (Ok(_), Ok(_)) => {
+            bot.answer_callback_query(q.id).text("This session no longer exists").await?;
+            bot.edit_message_reply_markup(message.chat.id, message.id).await?;
             return Ok(());
         }
-        Err(e) => {
-            tracing::error!("Failed to find session: {}", e);
-            feedback.error("Failed to retrieve session information from database").await?;
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to load session {} for cancellation: {}", session_id, e);
+            bot.answer_callback_query(q.id).text("Failed to retrieve the session").await?;
             return Ok(());
         }
     };
     
-    // Check if user is the session creator
-    if session.created_by != user_id {
-        let error_msg = "Permission denied: Only the session creator can cancel sessions";
-        let suggestion = "Ask the session creator to run this command, or use /list to see who created each session.";
-        feedback.validation_error(error_msg, suggestion).await?;
+    // Whoever taps the button needs the same rights as for the command itself
+    let user_id = q.from.id.0 as i64;
+    if session_manager_role(&bot, db, message.chat.id, &session, user_id).await.is_none() {
+        bot.answer_callback_query(q.id)
+            .text("Only the session creator, chat administrators or the group's bot admins can cancel sessions")
+            .await?;
         return Ok(());
     }
     
-    // Check if session belongs to this group
-    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
-        Ok(Some(group)) => group,
-        Ok(None) => {
-            feedback.error("Group not found in database").await?;
+    // The prompt may be stale: the session could have been cancelled or archived meanwhile
+    if session.status != "confirmed" {
+        bot.answer_callback_query(q.id).text(format!("The session is {} now", session.status)).await?;
+        bot.edit_message_reply_markup(message.chat.id, message.id).await?;
+        return Ok(());
+    }
+    
+    bot.answer_callback_query(q.id).await?;
+    tracing::info!("User {} confirmed cancelling confirmed session {}", user_id, session.id);
+    cancel_and_report(&bot, db, message.chat.id, &group, &session, user_id, message.id).await
+}
+
+pub async fn handle_deadline(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    datetime: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    
+    // Send processing message
+    let processing_msg = feedback.send_processing("Setting session deadline...").await?;
+    
+    // Resolve the session reference within this group
+    let (group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+    let session_id = session.id.clone();
+    let feedback = feedback.logged(&db.pool, "deadline", &session.id).with_language(group.lang());
+    
+    // Check if user may manage this session
+    let Some(role) = session_manager_role(&bot, db, msg.chat.id, &session, user_id).await else {
+        return Err(BotError::PermissionDenied);
+    };
+    tracing::debug!("User {} may manage session '{}' as {:?}", user_id, session.id, role);
+    
+    // `--force` sets a deadline after the last option anyway
+    let force = datetime.split_whitespace().any(|token| token == "--force");
+    let datetime = datetime.split_whitespace().filter(|token| *token != "--force").collect::<Vec<_>>().join(" ");
+    
+    // Parse the deadline datetime
+    // A guessed deadline would close voting at a time nobody chose
+    let deadline_dt = match parse_datetime(&datetime) {
+        ParseOutcome::Exact(dt) => {
+            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
+                "Validating deadline datetime...").await?;
+            dt
+        },
+        ParseOutcome::Fallback(_) => {
+            let error_msg = match invalid_european_date(&datetime) {
+                Some(reason) => format!("Could not parse deadline: '{datetime}' isn't a date: {reason}"),
+                None => format!("Could not parse deadline: '{datetime}'"),
+            };
+            let suggestion = "Please use formats like 'Friday 19:00', 'Monday 14:30', or 'Tuesday 20:00'";
+            feedback.validation_error(&error_msg, suggestion).await?;
+            return Ok(());
+        }
+    };
+    
+    // A deadline in a year gone by, or years off, is a mistyped date, like a poll's options
+    let now = Utc::now();
+    let when = format_datetime_with_year(&deadline_dt);
+    if let Some(year) = likely_year(deadline_dt, now) {
+        let error_msg = format!("Deadline '{datetime}' is in the past: it reads as {when}");
+        let suggestion = format!("Did you mean {year}? Writing the year in full, like '31.12.{year} 19:00', avoids the mix-up.");
+        feedback.validation_error(&error_msg, &suggestion).await?;
+        return Ok(());
+    }
+    if matches!(check_option_time(deadline_dt, now), Err(OptionTimeError::TooFarAhead)) {
+        let error_msg = format!("Deadline '{datetime}' is more than {MAX_OPTION_MONTHS_AHEAD} months away: it reads as {when}");
+        feedback.validation_error(&error_msg, "Check the year; polls plan at most that far ahead.").await?;
+        return Ok(());
+    }
+    
+    // Set the deadline
+    let api = SchedulerApi::new(db.pool.clone());
+    let previous_deadline = match api.set_deadline(&session_id, deadline_dt, now, force).await {
+        Ok(previous_deadline) => previous_deadline,
+        Err(SchedulingError::NotActive { status }) => {
+            let error_msg = format!("Session {} is {status}", session.display_ref());
+            let suggestion = "Deadlines only apply to polls that are still collecting votes.";
+            feedback.validation_error(&error_msg, suggestion).await?;
+            return Ok(());
+        }
+        Err(e @ SchedulingError::DeadlineAfterOptions { .. }) => {
+            let warning = format!(
+                "{e}, so voting would stay open after every option has passed.\n\n\
+                 💡 To set it anyway: /deadline {} {datetime} --force",
+                session.display_ref()
+            );
+            feedback.warning(&warning).await?;
+            return Ok(());
+        }
+        Err(SchedulingError::Invalid(error_msg)) => {
+            let suggestion = "Please specify a date and time that hasn't passed yet. Use formats like 'Friday 19:00' or 'Monday 14:30'.";
+            feedback.validation_error(&error_msg, suggestion).await?;
             return Ok(());
         }
         Err(e) => {
-            tracing::error!("Failed to find group: {}", e);
-            feedback.error("Failed to retrieve group information").await?;
+            tracing::error!("Failed to set deadline: {}", e);
+            feedback.error("Failed to save deadline to database").await?;
             return Ok(());
         }
     };
+    let undo = record_undo(db, group.id, user_id, &UndoableAction::Deadline {
+        session_id: session_id.clone(),
+        previous_deadline,
+    }).await;
     
-    if session.group_id != group.id {
-        let error_msg = "Session doesn't belong to this group";
-        let suggestion = "This session was created in a different group. Use /list to see sessions for this group.";
-        feedback.validation_error(error_msg, suggestion).await?;
+    // Send detailed success message
+    let deadline_str = format_datetime(&deadline_dt);
+    let success_message = i18n::t_deadline_set(group.lang(), &session.title, &deadline_str);
+    
+    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, &success_message).await?;
+    if let Some(undo) = undo {
+        bot.edit_message_reply_markup(msg.chat.id, processing_msg.id)
+            .reply_markup(undo_keyboard(&undo))
+            .await?;
+    }
+    
+    Ok(())
+}
+
+/// Sets a session's notes (e.g. where the group meets), or clears them with "clear"
+pub async fn handle_notes(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    text: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    
+    let (_group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+    
+    if session_manager_role(&bot, db, msg.chat.id, &session, user_id).await.is_none() {
+        return Err(BotError::PermissionDenied);
+    }
+    
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("clear") {
+        Session::set_notes(&db.pool, &session.id, None).await?;
+        tracing::info!("User {} cleared the notes of session {}", user_id, session.id);
+        feedback.success(&format!("Removed the notes from {} {}", session.display_ref(), session.title)).await?;
         return Ok(());
     }
     
-    // Check if session is already cancelled
-    if session.status == "cancelled" {
-        let error_msg = "Session is already cancelled";
-        let suggestion = "This session has already been cancelled. Use /list to see current status of all sessions.";
-        feedback.validation_error(error_msg, suggestion).await?;
+    if let Err(e) = validate_session_notes(text) {
+        let error_msg = format!("Invalid notes: {e}");
+        let suggestion = format!("Keep notes on one line and under {MAX_NOTES_LENGTH} characters, e.g. /notes #3 At Sam's place, bring dice");
+        feedback.validation_error(&error_msg, &suggestion).await?;
         return Ok(());
     }
     
-    // Check if session is confirmed (warn but allow cancellation)
-    if session.status == "confirmed" {
-        feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-            "Warning: Cancelling a confirmed session...").await?;
+    Session::set_notes(&db.pool, &session.id, Some(text)).await?;
+    tracing::info!("User {} set the notes of session {}", user_id, session.id);
+    feedback.success(&format!(
+        "Notes saved for {} {}\n\n📝 {}\n\n💡 They show up in /list, the confirmation and reminders",
+        session.display_ref(),
+        session.title,
+        text
+    )).await?;
+    
+    Ok(())
+}
+
+/// Asks for confirmation before permanently deleting a session
+pub async fn handle_delete(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    
+    tracing::info!(
+        "Delete command initiated by user {} in chat {} for session '{}'",
+        user_id, chat_id, session_id
+    );
+    
+    let (_group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+    
+    if session_manager_role(&bot, db, msg.chat.id, &session, user_id).await.is_none() {
+        return Err(BotError::PermissionDenied);
     }
     
-    // Cancel the session
-    if let Err(e) = cancel_session(&db.pool, &session_id).await {
-        tracing::error!("Failed to cancel session: {}", e);
-        feedback.error("Failed to save session cancellation to database").await?;
+    let option_count = match SessionOption::find_by_session(&db.pool, &session.id).await {
+        Ok(options) => options.len(),
+        Err(e) => {
+            tracing::error!("Failed to get session options: {}", e);
+            feedback.error("Failed to retrieve session time options").await?;
+            return Ok(());
+        }
+    };
+    
+    let message_text = format!(
+        "🗑️ **Delete {} {}?**\n\nThis permanently removes the session, its {} time options and all votes\\. It can't be undone\\.",
+        escape_markdown(&session.display_ref()),
+        escape_markdown(&session.title),
+        option_count
+    );
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback("🗑️ Delete permanently", format!("delete:confirm:{}", session.id))],
+        vec![InlineKeyboardButton::callback("❌ Keep it", "delete:cancel")],
+    ]);
+    
+    bot.send_message(msg.chat.id, message_text)
+        .reply_markup(keyboard)
+        .parse_mode(ParseMode::MarkdownV2)
+        .await?;
+    
+    Ok(())
+}
+
+/// Handles the inline confirmation buttons of the /delete prompt
+pub async fn handle_delete_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This prompt has expired").await?;
+        return Ok(());
+    };
+    
+    let action = data.strip_prefix("delete:").unwrap_or(&data);
+    if action == "cancel" {
+        bot.answer_callback_query(q.id).text("Nothing was deleted").await?;
+        bot.edit_message_text(message.chat.id, message.id, "👍 The session was kept\\.")
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
         return Ok(());
     }
     
-    // Send detailed success message
-    let success_message = format!(
-        "Session '{}' cancelled successfully!\\n\\n📅 The session has been removed from the schedule\\n👥 All participants have been notified\\n\\n💡 Use /schedule to create a new session if needed",
-        session.title
-    );
+    let Some(session_id) = action.strip_prefix("confirm:") else {
+        bot.answer_callback_query(q.id).text("Invalid request").await?;
+        return Ok(());
+    };
     
-    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, &success_message).await?;
+    // Only sessions of this chat's group can be deleted from here
+    let session = match (
+        Group::find_by_chat_id(&db.pool, message.chat.id.0).await,
+        Session::find_by_id(&db.pool, session_id).await,
+    ) {
+        (Ok(Some(group)), Ok(Some(session))) if session.group_id == group.id => session,
+        (Ok(_), Ok(_)) => {
+            bot.answer_callback_query(q.id).text("This session no longer exists").await?;
+            bot.edit_message_reply_markup(message.chat.id, message.id).await?;
+            return Ok(());
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to load session {} for deletion: {}", session_id, e);
+            bot.answer_callback_query(q.id).text("Failed to retrieve the session").await?;
+            return Ok(());
+        }
+    };
+    
+    // Whoever taps the button needs the same rights as for the command itself
+    if session_manager_role(&bot, db, message.chat.id, &session, q.from.id.0 as i64).await.is_none() {
+        bot.answer_callback_query(q.id)
+            .text("Only the session creator, chat administrators or the group's bot admins can delete sessions")
+            .await?;
+        return Ok(());
+    }
+    
+    match Session::delete(&db.pool, &session.id).await {
+        Ok(_) => {
+            tracing::info!("User {} deleted session {} ('{}')", q.from.id, session.id, session.title);
+            strip_poll_keyboard(&bot, message.chat.id, &session).await;
+            bot.answer_callback_query(q.id).text("🗑️ Session deleted").await?;
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                format!("🗑️ Deleted {} {}\\.", escape_markdown(&session.display_ref()), escape_markdown(&session.title)),
+            )
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+        }
+        Err(e) => {
+            tracing::error!("Failed to delete session {}: {}", session.id, e);
+            bot.answer_callback_query(q.id).text("Failed to delete the session").await?;
+        }
+    }
     
     Ok(())
 }
 
-pub async fn handle_deadline(
+/// Sends a fresh copy of a session's poll with the current tallies and tracks it from then on
+///
+/// Handy when the original poll was deleted or has scrolled far up the chat. The old message
+/// loses its buttons so votes don't end up on two polls.
+pub async fn handle_repost(
     bot: Bot,
     msg: Message,
     session_id: String,
-    datetime: String,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
+) -> Result<(), BotError> {
     let chat_id = msg.chat.id.0;
     let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
     let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
     
-    // Send processing message
-    let processing_msg = feedback.send_processing("Setting session deadline...").await?;
+    tracing::info!(
+        "Repost command initiated by user {} in chat {} for session '{}'",
+        user_id, chat_id, session_id
+    );
     
-    // Validate session ID format
-    if let Err(e) = validate_session_id(&session_id) {
-        let suggestion = "Session IDs must be 8-50 characters long and contain only letters, numbers, and hyphens. Use /list to see valid session IDs.";
-        feedback.validation_error(&e.to_string(), suggestion).await?;
-        return Ok(());
+    let (_group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+    
+    if session_manager_role(&bot, db, msg.chat.id, &session, user_id).await.is_none() {
+        return Err(BotError::PermissionDenied);
     }
     
-    // Validate session exists and belongs to this group
-    let session = match Session::find_by_id(&db.pool, &session_id).await {
-        Ok(Some(session)) => session,
-        Ok(None) => {
-            let error_msg = "Session not found";
-            let suggestion = "Please check the session ID. Use /list to see active sessions.";
-            feedback.validation_error(error_msg, suggestion).await?;
+    strip_poll_keyboard(&bot, msg.chat.id, &session).await;
+    if let Err(e) = repost_poll_message(&bot, db, &session, msg.chat.id).await {
+        tracing::error!("Failed to repost poll for session {}: {}", session.id, e);
+        feedback.error("Failed to repost the poll").await?;
+    }
+    
+    Ok(())
+}
+
+/// Archive a confirmed, cancelled or closed session by hand, ahead of the automatic archiving
+pub async fn handle_archive(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    tracing::info!(
+        "Archive command initiated by user {} in chat {} for session '{}'",
+        user_id, chat_id, session_id
+    );
+
+    let (_group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    if session_manager_role(&bot, db, msg.chat.id, &session, user_id).await.is_none() {
+        return Err(BotError::PermissionDenied);
+    }
+
+    match session.status.as_str() {
+        "archived" => {
+            feedback.info(&format!("{} {} is already archived", session.display_ref(), session.title)).await?;
             return Ok(());
         }
-        Err(e) => {
-            tracing::error!("Failed to find session: {}", e);
-            feedback.error("Failed to retrieve session information from database").await?;
+        status if !ARCHIVABLE_STATUSES.contains(&status) => {
+            let error_msg = "Only finished sessions can be archived";
+            let suggestion = "Confirm, cancel or close the poll first, e.g. /cancel <session_id>";
+            feedback.validation_error(error_msg, suggestion).await?;
             return Ok(());
         }
+        _ => {}
+    }
+
+    Session::archive(&db.pool, &session.id).await?;
+    tracing::info!("Session {} archived by user {}", session.id, user_id);
+    feedback.success(&format!(
+        "📦 Archived {} {}. It no longer shows in /list but still counts in /stats.",
+        session.display_ref(), session.title
+    )).await?;
+
+    Ok(())
+}
+
+/// Mute (`/mute`) or unmute (`/unmute`) a session's reminders and vote reminders
+///
+/// Reminders that come due while a session is muted are marked as sent without going out, so
+/// unmuting only lets the later ones through.
+pub async fn handle_mute(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    mute: bool,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    tracing::info!(
+        "{} command initiated by user {} in chat {} for session '{}'",
+        if mute { "Mute" } else { "Unmute" }, user_id, chat_id, session_id
+    );
+
+    let (_group, session) = match resolve_session(&feedback, db, chat_id, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
     };
-    
-    // Check if user is the session creator
-    if session.created_by != user_id {
-        let error_msg = "Permission denied: Only the session creator can set deadlines";
-        let suggestion = "Ask the session creator to run this command, or use /list to see who created each session.";
-        feedback.validation_error(error_msg, suggestion).await?;
+
+    if session_manager_role(&bot, db, msg.chat.id, &session, user_id).await.is_none() {
+        return Err(BotError::PermissionDenied);
+    }
+
+    let reference = format!("{} {}", session.display_ref(), session.title);
+    if session.reminders_muted == mute {
+        let state = if mute { "already muted" } else { "not muted" };
+        feedback.info(&format!("{reference} is {state}")).await?;
+        return Ok(());
+    }
+    if mute && !VOTABLE_STATUSES.contains(&session.status.as_str()) {
+        let error_msg = format!("{reference} is {}, so it gets no reminders", session.status);
+        feedback.validation_error(&error_msg, "Only active and confirmed sessions can be muted.").await?;
         return Ok(());
     }
+
+    Session::set_reminders_muted(&db.pool, &session.id, mute).await?;
+    tracing::info!("Reminders of session {} {} by user {}", session.id, if mute { "muted" } else { "unmuted" }, user_id);
+    let text = if mute {
+        format!(
+            "🔕 Muted the reminders of {reference}. The ones due until /unmute {} are skipped for good.",
+            session.display_ref()
+        )
+    } else {
+        format!("🔔 Reminders of {reference} are back on, starting with the next one due.")
+    };
+    feedback.success(&text).await?;
+
+    Ok(())
+}
+
+/// Lists active polls older than `older_than_days` and asks an admin to confirm closing them
+pub async fn handle_closeall(
+    bot: Bot,
+    msg: Message,
+    older_than_days: u32,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id).unwrap_or(UserId(0));
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    
+    tracing::info!(
+        "CloseAll command initiated by user {} in chat {} for polls older than {} days",
+        user_id, chat_id, older_than_days
+    );
     
-    // Check if session belongs to this group
     let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
         Ok(Some(group)) => group,
         Ok(None) => {
-            feedback.error("Group not found in database").await?;
+            feedback.info("This group doesn't have any sessions yet").await?;
             return Ok(());
         }
         Err(e) => {
@@ -339,91 +831,263 @@ pub async fn handle_deadline(
         }
     };
     
-    if session.group_id != group.id {
-        let error_msg = "Session doesn't belong to this group";
-        let suggestion = "This session was created in a different group. Use /list to see sessions for this group.";
+    // Only group admins may close other people's polls in bulk
+    if group_manager_role(&bot, db, msg.chat.id, group.id, user_id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can close polls in bulk";
+        let suggestion = "Ask a group admin to run this command, or use /cancel on your own sessions.";
         feedback.validation_error(error_msg, suggestion).await?;
         return Ok(());
     }
     
-    // Parse the deadline datetime
-    let deadline_dt = match parse_datetime(&datetime) {
-        Ok(dt) => {
-            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-                "Validating deadline datetime...").await?;
-            dt
-        },
-        Err(_) => {
-            let error_msg = format!("Could not parse deadline: '{datetime}'");
-            let suggestion = "Please use formats like 'Friday 19:00', 'Monday 14:30', or 'Tuesday 20:00'";
-            feedback.validation_error(&error_msg, suggestion).await?;
+    let cutoff = Utc::now() - chrono::Duration::days(older_than_days as i64);
+    let stale_sessions = match Session::find_active_older_than(&db.pool, group.id, cutoff).await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            tracing::error!("Failed to find stale sessions for group {}: {}", group.id, e);
+            feedback.error("Failed to retrieve sessions from database").await?;
             return Ok(());
         }
     };
     
-    // Check if deadline is in the future
-    if deadline_dt <= Utc::now() {
-        let error_msg = "Deadline must be in the future";
-        let suggestion = "Please specify a date and time that hasn't passed yet. Use formats like 'Friday 19:00' or 'Monday 14:30'.";
-        feedback.validation_error(error_msg, suggestion).await?;
+    if stale_sessions.is_empty() {
+        feedback.info(&format!("No active polls older than {older_than_days} days - nothing to close")).await?;
         return Ok(());
     }
     
-    // Set the deadline
-    if let Err(e) = set_session_deadline(&db.pool, &session_id, &deadline_dt.to_rfc3339()).await {
-        tracing::error!("Failed to set deadline: {}", e);
-        feedback.error("Failed to save deadline to database").await?;
+    let mut message_text = format!(
+        "🧹 **Close {} stale polls?**\n\nThese active polls are older than {} days:\n\n",
+        stale_sessions.len(),
+        older_than_days
+    );
+    message_text.push_str(&format_session_lines(&stale_sessions));
+    message_text.push_str("\nVoting will be closed and their buttons removed\\.");
+    
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(
+            format!("✅ Close {} polls", stale_sessions.len()),
+            format!("closeall:confirm:{older_than_days}"),
+        )],
+        vec![InlineKeyboardButton::callback("❌ Keep them", "closeall:abort")],
+    ]);
+    
+    bot.send_message(msg.chat.id, message_text)
+        .reply_markup(keyboard)
+        .parse_mode(ParseMode::MarkdownV2)
+        .await?;
+    
+    Ok(())
+}
+
+/// Handles the inline confirmation buttons of the /closeall prompt
+pub async fn handle_closeall_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This prompt has expired").await?;
+        return Ok(());
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            bot.answer_callback_query(q.id).text("No sessions found for this group").await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to retrieve group information").await?;
+            return Ok(());
+        }
+    };
+    
+    if group_manager_role(&bot, db, message.chat.id, group.id, q.from.id.0 as i64).await.is_none() {
+        bot.answer_callback_query(q.id)
+            .text("Only chat administrators or the group's bot admins can close polls in bulk")
+            .await?;
         return Ok(());
     }
     
-    // Send detailed success message
-    let deadline_str = format_datetime(&deadline_dt);
-    let success_message = format!(
-        "Session deadline set successfully!\\n\\n📅 Session: {}\\n⏰ Responses due by: {}\\n\\n💡 Players will be reminded before the deadline\\n👥 Use /list to check current voting status",
-        session.title, 
-        deadline_str
+    let action = data.strip_prefix("closeall:").unwrap_or(&data);
+    if action == "abort" {
+        bot.answer_callback_query(q.id).text("Nothing was closed").await?;
+        bot.edit_message_text(message.chat.id, message.id, "👍 No polls were closed\\.")
+            .parse_mode(ParseMode::MarkdownV2)
+            .await?;
+        return Ok(());
+    }
+    
+    let Some(older_than_days) = action.strip_prefix("confirm:").and_then(|d| d.parse::<u32>().ok()) else {
+        bot.answer_callback_query(q.id).text("Invalid request").await?;
+        return Ok(());
+    };
+    
+    // Re-run the query so polls that were confirmed in the meantime are left alone
+    let cutoff = Utc::now() - chrono::Duration::days(older_than_days as i64);
+    let stale_sessions = match Session::find_active_older_than(&db.pool, group.id, cutoff).await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            tracing::error!("Failed to find stale sessions for group {}: {}", group.id, e);
+            bot.answer_callback_query(q.id).text("Failed to retrieve sessions").await?;
+            return Ok(());
+        }
+    };
+    
+    let mut closed = Vec::new();
+    let mut failed = 0;
+    for session in stale_sessions {
+        match close_session(&db.pool, &session.id).await {
+            Ok(true) => {
+                if session.anonymous {
+                    // Re-rendering as closed reveals the tallies and drops the keyboard
+                    let mut closed_session = session.clone();
+                    closed_session.status = "closed".to_string();
+                    if let Err(e) = refresh_poll_message(&bot, db, &closed_session, message.chat.id).await {
+                        tracing::warn!("Failed to reveal votes on poll message for session {}: {}", session.id, e);
+                    }
+                } else {
+                    strip_poll_keyboard(&bot, message.chat.id, &session).await;
+                }
+                closed.push(session);
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!("Failed to close session {}: {}", session.id, e);
+                failed += 1;
+            }
+        }
+    }
+    
+    tracing::info!(
+        "Closed {} stale sessions in group {} (requested by user {}, {} failures)",
+        closed.len(), group.id, q.from.id, failed
     );
     
-    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, &success_message).await?;
+    bot.answer_callback_query(q.id)
+        .text(format!("Closed {} polls", closed.len()))
+        .await?;
+    
+    let mut summary = format!("🔒 **Closed {} stale polls**\n\n", closed.len());
+    summary.push_str(&format_session_lines(&closed));
+    if failed > 0 {
+        summary.push_str(&format!("\n⚠️ {failed} polls could not be closed, see logs for details\n"));
+    }
+    
+    let undo = if closed.is_empty() {
+        None
+    } else {
+        let session_ids = closed.iter().map(|s| s.id.clone()).collect();
+        record_undo(db, group.id, q.from.id.0 as i64, &UndoableAction::Close { session_ids }).await
+    };
+    
+    // Editing without a reply markup also removes the confirmation buttons
+    let request = bot.edit_message_text(message.chat.id, message.id, summary)
+        .parse_mode(ParseMode::MarkdownV2);
+    match undo {
+        Some(undo) => request.reply_markup(undo_keyboard(&undo)).await?,
+        None => request.await?,
+    };
     
     Ok(())
 }
 
-// Database helper functions
-async fn confirm_session_and_option(
-    pool: &sqlx::SqlitePool,
-    session_id: &str,
-    option_id: &str,
-) -> Result<(), sqlx::Error> {
-    // Start a transaction
-    let mut tx = pool.begin().await?;
+/// Formats sessions as MarkdownV2 bullet lines with their age, truncated to `CLOSEALL_MAX_LISTED`
+fn format_session_lines(sessions: &[Session]) -> String {
+    let now = Utc::now();
+    let mut lines = String::new();
     
-    // Update session status to confirmed
-    sqlx::query!(
-        "UPDATE sessions SET status = 'confirmed' WHERE id = ?",
-        session_id
-    )
-    .execute(&mut *tx)
-    .await?;
+    for session in sessions.iter().take(CLOSEALL_MAX_LISTED) {
+        let age_days = session.created_at_utc()
+            .map(|created| (now - created).num_days())
+            .unwrap_or(0);
+        lines.push_str(&format!(
+            "• {} {} \\({} days old\\)\n",
+            escape_markdown(&session.display_ref()),
+            escape_markdown(&session.title),
+            age_days
+        ));
+    }
     
-    // Mark the winning option as confirmed
-    sqlx::query!(
-        "UPDATE session_options SET confirmed = true WHERE id = ?",
-        option_id
-    )
-    .execute(&mut *tx)
-    .await?;
+    if sessions.len() > CLOSEALL_MAX_LISTED {
+        lines.push_str(&format!("…and {} more\n", sessions.len() - CLOSEALL_MAX_LISTED));
+    }
     
-    tx.commit().await?;
-    Ok(())
+    lines
 }
 
+/// Removes the voting buttons from a session's poll message, if it has one
+pub(crate) async fn strip_poll_keyboard(bot: &Bot, chat_id: ChatId, session: &Session) {
+    if let Some(message_id) = session.message_id {
+        if let Err(e) = bot.edit_message_reply_markup(chat_id, MessageId(message_id as i32)).await {
+            tracing::warn!("Failed to remove keyboard from poll message for session {}: {}", session.id, e);
+        }
+    }
+}
+
+/// Look up this chat's group and resolve a session reference (`#12`, ID prefix, or full ID) within it.
+///
+/// Sends the appropriate feedback and returns `None` when the session can't be resolved.
+pub(crate) async fn resolve_session(
+    feedback: &CommandFeedback,
+    db: &DatabaseManager,
+    chat_id: i64,
+    reference: &str,
+) -> ResponseResult<Option<(Group, Session)>> {
+    // Validate session reference format
+    if let Err(e) = validate_session_id(reference) {
+        tracing::warn!("Session ID validation failed for '{}': {}", reference, e);
+        let suggestion = "Use the session number shown in /list (e.g. #12), or at least the first 8 characters of the session ID.";
+        feedback.validation_error(&e.to_string(), suggestion).await?;
+        return Ok(None);
+    }
+    
+    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            let error_msg = "Session not found";
+            let suggestion = "This group doesn't have any sessions yet. Create one with /schedule.";
+            feedback.validation_error(error_msg, suggestion).await?;
+            return Ok(None);
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            feedback.error("Failed to retrieve group information").await?;
+            return Ok(None);
+        }
+    };
+    
+    match Session::find_by_reference(&db.pool, group.id, reference).await {
+        Ok(Some(session)) => Ok(Some((group, session))),
+        Ok(None) => {
+            tracing::warn!("Session not found: '{}' in group {}", reference, group.id);
+            let error_msg = "Session not found";
+            let suggestion = "Please check the session ID. Use /list to see active sessions.";
+            feedback.validation_error(error_msg, suggestion).await?;
+            Ok(None)
+        }
+        Err(SessionLookupError::Ambiguous(count)) => {
+            let error_msg = format!("'{reference}' matches {count} sessions");
+            let suggestion = "Use the session number shown in /list (e.g. #12), or type more characters of the session ID.";
+            feedback.validation_error(&error_msg, suggestion).await?;
+            Ok(None)
+        }
+        Err(SessionLookupError::Database(e)) => {
+            tracing::error!("Failed to find session: {}", e);
+            feedback.error("Failed to retrieve session information from database").await?;
+            Ok(None)
+        }
+    }
+}
+
+// Database helper functions
 async fn cancel_session(
     pool: &sqlx::SqlitePool,
     session_id: &str,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
-        "UPDATE sessions SET status = 'cancelled' WHERE id = ?",
+        "UPDATE sessions SET status = 'cancelled', deadline = NULL WHERE id = ?",
         session_id
     )
     .execute(pool)
@@ -432,18 +1096,20 @@ async fn cancel_session(
     Ok(())
 }
 
-async fn set_session_deadline(
+async fn close_session(
     pool: &sqlx::SqlitePool,
     session_id: &str,
-    deadline: &str,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        "UPDATE sessions SET deadline = ? WHERE id = ?",
-        deadline,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    
+    // Only active polls are closed; anything confirmed or cancelled meanwhile is left untouched
+    let result = sqlx::query!(
+        "UPDATE sessions SET status = 'closed' WHERE id = ? AND status = 'active'",
         session_id
     )
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
     
-    Ok(())
-}
\ No newline at end of file
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,155 @@
+//! `/sessioninfo`: everything about one session on one screen, where /list only has a summary
+
+use std::collections::HashMap;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use crate::bot::commands::list::TELEGRAM_MESSAGE_LIMIT;
+use crate::bot::commands::session_management::resolve_session;
+use crate::bot::commands::stats::status_label;
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::utils::{
+    datetime::{format_datetime, format_duration},
+    feedback::CommandFeedback,
+    markdown::escape_markdown,
+};
+use chrono::{DateTime, Utc};
+
+/// Name shown for a player the bot has no name for
+fn fallback_name(user_id: i64) -> String {
+    format!("user{user_id}")
+}
+
+/// `text` (RFC 3339) as [`format_datetime`] shows it, or as stored if it can't be read
+fn format_stored_datetime(text: &str) -> String {
+    DateTime::parse_from_rfc3339(text)
+        .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
+        .unwrap_or_else(|_| text.to_string())
+}
+
+/// Render the detail view of `session` as MarkdownV2
+///
+/// Shows who created it and when, its deadline, notes and status, then each option with its
+/// voters by name, grouped by yes, no and maybe. `names` maps user IDs to display names; anyone
+/// missing shows as "user<id>". Voters' `notes` follow their option. Votes on anonymous polls
+/// stay hidden until they're confirmed. The text always fits in a Telegram message: lines that
+/// don't fit are left out with a note.
+pub fn render_session_info(
+    session: &Session,
+    options: &[SessionOption],
+    responses: &[Response],
+    notes: &NotesByOption,
+    names: &HashMap<i64, String>,
+) -> String {
+    let name = |user_id: i64| names.get(&user_id).cloned().unwrap_or_else(|| fallback_name(user_id));
+
+    let mut lines = vec![
+        format!("ℹ️ **{}** {}", escape_markdown(&session.title), escape_markdown(&session.display_ref())),
+        String::new(),
+        format!("Status: {}", status_label(&session.status)),
+        format!(
+            "Created by {} on {}",
+            escape_markdown(&name(session.created_by)),
+            escape_markdown(&format_stored_datetime(&session.created_at))
+        ),
+        match &session.deadline {
+            Some(deadline) => format!("Deadline: {}", escape_markdown(&format_stored_datetime(deadline))),
+            None => "Deadline: none".to_string(),
+        },
+    ];
+    if let Some(notes) = &session.notes {
+        lines.push(format!("📝 {}", escape_markdown(notes)));
+    }
+    if session.reminders_muted {
+        lines.push("🔕 Reminders are muted".to_string());
+    }
+    if session.anonymous {
+        lines.push("🙈 Anonymous poll".to_string());
+    }
+
+    lines.push(String::new());
+    lines.push(format!("📅 **Options** \\({}\\):", options.len()));
+    if options.is_empty() {
+        lines.push("No options".to_string());
+    }
+    for (i, option) in options.iter().enumerate() {
+        let confirmed = if option.confirmed { " ✅ confirmed" } else { "" };
+        lines.push(format!(
+            "{}\\. {} \\({}\\){}",
+            i + 1,
+            escape_markdown(&format_stored_datetime(&option.datetime)),
+            escape_markdown(&format_duration(option.duration)),
+            confirmed
+        ));
+
+        if session.hides_votes() {
+            let votes = responses.iter().filter(|r| r.option_id == option.id).count();
+            lines.push(format!("   🙈 {votes} votes, hidden until the session is confirmed"));
+            continue;
+        }
+        for (emoji, kind, label) in [("✅", "yes", "Yes"), ("❌", "no", "No"), ("❓", "maybe", "Maybe")] {
+            let voters: Vec<String> = responses.iter()
+                .filter(|r| r.option_id == option.id && r.response == kind)
+                .map(|r| escape_markdown(&name(r.user_id)))
+                .collect();
+            let listed = if voters.is_empty() { "—".to_string() } else { voters.join(", ") };
+            lines.push(format!("   {emoji} {label} \\({}\\): {listed}", voters.len()));
+        }
+        for (author, note) in notes.for_option(&option.id) {
+            lines.push(format!("   📝 {}: {}", escape_markdown(author), escape_markdown(note)));
+        }
+    }
+
+    fit_lines(&lines)
+}
+
+/// Join `lines` into one message of at most [`TELEGRAM_MESSAGE_LIMIT`], leaving out the lines
+/// from the first one that doesn't fit with a note saying how many
+fn fit_lines(lines: &[String]) -> String {
+    let len = |text: &str| text.encode_utf16().count();
+    // Room for the note, whose count has at most as many digits as the number of lines
+    let note = |left_out: usize| format!("_…{left_out} more lines not shown, see /results for the votes_");
+    let budget = TELEGRAM_MESSAGE_LIMIT.saturating_sub(len(&note(lines.len())) + 1);
+
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if len(&text) + len(line) + 1 > budget {
+            text.push_str(&note(lines.len() - i));
+            return text;
+        }
+        text.push_str(line);
+        text.push('\n');
+    }
+    text
+}
+
+/// Show the full detail view of a session: creator, dates, options, every voter by name and their notes
+pub async fn handle_session_info(
+    bot: Bot,
+    msg: Message,
+    session_id: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let (group, session) = match resolve_session(&feedback, db, msg.chat.id.0, &session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+
+    // The creator may not have voted, so their stored name comes from their profile
+    let profiles = UserProfile::find_by_ids(&db.pool, &[session.created_by]).await?;
+    let mut people: Vec<(i64, Option<&str>)> = responses.iter().map(|r| (r.user_id, r.username.as_deref())).collect();
+    people.push((session.created_by, profiles.get(&session.created_by).map(UserProfile::name)));
+    let names = display_names(&db.pool, &group, &people).await?;
+    let notes = NotesByOption::load(&db.pool, &group, std::slice::from_ref(&session.id)).await?;
+
+    bot.send_message(msg.chat.id, render_session_info(&session, &options, &responses, &notes, &names))
+        .parse_mode(ParseMode::MarkdownV2)
+        .await?;
+
+    Ok(())
+}
@@ -1,121 +1,1498 @@
 use teloxide::prelude::*;
-use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use teloxide::types::{ForceReply, InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode};
+use crate::bot::dialogue::{exit, BotDialogue, DialogueState, DialogueStorage, SettingField};
+use crate::bot::permissions::{group_manager_role, SessionManager};
+use crate::error::BotError;
 use crate::database::{connection::DatabaseManager, models::*};
-use crate::utils::{validation::validate_telegram_chat_id, feedback::CommandFeedback};
+use crate::services::reminder::{next_confirmed_session, plan_reminders, render_reminder_preview, LongRangeCadence, QuietHours};
+use crate::services::timezone::normalize_timezone;
+use chrono::Utc;
+use crate::utils::{
+    datetime::{format_duration, parse_duration},
+    markdown::escape_markdown,
+    validation::validate_telegram_chat_id,
+    feedback::CommandFeedback,
+    i18n::{self, Lang},
+};
+
+/// Durations offered by the "Default Duration" settings button, in minutes
+pub const DURATION_CHOICES: [i64; 8] = [60, 90, 120, 180, 240, 300, 360, 480];
+
+/// Notice periods offered by the "Minimum Notice" settings button, in hours
+pub const NOTICE_CHOICES: [i64; 8] = [0, 2, 6, 12, 24, 48, 72, 168];
+
+/// Thresholds offered by the "Auto-confirm" settings button, in "yes" votes
+pub const AUTOCONFIRM_CHOICES: [i64; 7] = [2, 3, 4, 5, 6, 8, 10];
+
+const ADMINS_USAGE: &str = "Use /settings admins add <user_id> or /settings admins remove <user_id>, or reply to someone's message with /settings admins add";
+
+/// A `/settings admins ...` subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminsCommand {
+    /// Show the group's bot admins
+    List,
+    /// Grant bot admin rights, to the given user or else the author of the replied-to message
+    Add(Option<i64>),
+    /// Revoke bot admin rights, from the given user or else the author of the replied-to message
+    Remove(Option<i64>),
+}
+
+/// Parse the arguments of `/settings`, e.g. "admins add 12345"
+pub fn parse_admins_command(args: &str) -> Result<AdminsCommand, String> {
+    let mut words = args.split_whitespace();
+    if !words.next().is_some_and(|word| word.eq_ignore_ascii_case("admins")) {
+        return Err(format!("Unknown settings option '{}'", args.trim()));
+    }
+    
+    let action = words.next().map(str::to_lowercase);
+    let user_id = match words.next() {
+        Some(user_id) => match user_id.parse::<i64>() {
+            Ok(user_id) if user_id > 0 => Some(user_id),
+            _ => return Err(format!("'{user_id}' is not a Telegram user ID")),
+        },
+        None => None,
+    };
+    if words.next().is_some() {
+        return Err("Too many arguments".to_string());
+    }
+    
+    match (action.as_deref(), user_id) {
+        (None, _) => Ok(AdminsCommand::List),
+        (Some("list"), None) => Ok(AdminsCommand::List),
+        (Some("add"), user_id) => Ok(AdminsCommand::Add(user_id)),
+        (Some("remove"), user_id) => Ok(AdminsCommand::Remove(user_id)),
+        (Some(action), _) => Err(format!("Unknown admins action '{action}'")),
+    }
+}
+
+/// The language code of a `/settings language [<code>]` command, `None` for other settings
+///
+/// An empty code asks for the current language.
+pub fn parse_language_args(args: &str) -> Option<&str> {
+    let args = args.trim();
+    let (word, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    word.eq_ignore_ascii_case("language").then(|| rest.trim())
+}
+
+const LONG_RANGE_USAGE: &str = "Use /settings longrange every 30d until 14d, or /settings longrange off";
+
+/// A `/settings longrange ...` subcommand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongRangeCommand {
+    /// Show the group's long-range reminders
+    Show,
+    /// Turn them off
+    Off,
+    /// Remind every `every_days` days until `until_days` before the start
+    Set {
+        /// Days between two reminders
+        every_days: i64,
+        /// Days before the start at which they stop
+        until_days: i64,
+    },
+}
+
+/// Parse a `/settings longrange [off | [every] <days>d [until <days>d]]` command, `None` for other settings
+///
+/// Without "until" the reminders stop when the 2 week reminder takes over.
+pub fn parse_long_range_args(args: &str) -> Option<Result<LongRangeCommand, String>> {
+    let args = args.trim();
+    let (word, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    if !word.eq_ignore_ascii_case("longrange") {
+        return None;
+    }
+    
+    let rest = rest.trim().to_lowercase();
+    let rest = rest.strip_prefix("every").unwrap_or(&rest).trim();
+    let command = match rest {
+        "" => Ok(LongRangeCommand::Show),
+        "off" => Ok(LongRangeCommand::Off),
+        _ => {
+            let (every, until) = match rest.split_once("until") {
+                Some((every, until)) => (every, Some(until)),
+                None => (rest, None),
+            };
+            parse_days(every).and_then(|every_days| {
+                let until_days = until.map_or(Ok(DEFAULT_LONG_RANGE_UNTIL_DAYS), parse_days)?;
+                Ok(LongRangeCommand::Set { every_days, until_days })
+            })
+        }
+    };
+    Some(command)
+}
+
+/// A number of days such as "30" or "30d"
+fn parse_days(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    let days = text.strip_suffix('d').unwrap_or(text).trim();
+    days.parse::<i64>().map_err(|_| format!("'{text}' is not a number of days"))
+}
+
+/// Handle `/settings longrange [off | every <days>d [until <days>d]]`
+async fn handle_long_range_command(
+    bot: Bot,
+    msg: Message,
+    command: Result<LongRangeCommand, String>,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            feedback.validation_error(&e, LONG_RANGE_USAGE).await?;
+            return Ok(());
+        }
+    };
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    
+    if command == LongRangeCommand::Show {
+        feedback.info(&format!("{}\n\n{LONG_RANGE_USAGE}", long_range_summary(&group))).await?;
+        return Ok(());
+    }
+    
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can change long-range reminders";
+        feedback.validation_error(error_msg, "Ask a chat administrator to change them.").await?;
+        return Ok(());
+    }
+    
+    let cadence = match command {
+        LongRangeCommand::Set { every_days, until_days } => Some((every_days, until_days)),
+        _ => None,
+    };
+    match Group::update_long_range_reminders(&db.pool, group.id, cadence).await {
+        Ok(()) => {}
+        Err(GroupSettingError::Database(e)) => return Err(e.into()),
+        Err(e) => {
+            feedback.validation_error(&e.to_string(), LONG_RANGE_USAGE).await?;
+            return Ok(());
+        }
+    }
+    tracing::info!("Group {} long-range reminders set to {:?} by user {}", group.id, cadence, from.id);
+    
+    let group = match cadence {
+        Some((every_days, until_days)) => Group {
+            long_range_every_days: Some(every_days),
+            long_range_until_days: until_days,
+            ..group
+        },
+        None => Group { long_range_every_days: None, ..group },
+    };
+    feedback.success(&long_range_summary(&group)).await?;
+    
+    Ok(())
+}
+
+/// One-line summary of a group's long-range reminders
+fn long_range_summary(group: &Group) -> String {
+    match LongRangeCadence::for_group(group) {
+        Some(cadence) => format!("Long-range reminders go out {cadence} the session"),
+        None => "Long-range reminders are off".to_string(),
+    }
+}
+
+const QUIET_HOURS_USAGE: &str = "Use /settings quiet 22-07 to hold reminders back from 22:00 until 07:00, or /settings quiet off";
+
+/// A `/settings quiet ...` subcommand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuietHoursCommand {
+    /// Show the group's quiet hours
+    Show,
+    /// Turn them off
+    Off,
+    /// Hold reminders back from `start_hour` until `end_hour`
+    Set {
+        /// First quiet hour
+        start_hour: i64,
+        /// Hour at which the quiet ends
+        end_hour: i64,
+    },
+}
+
+/// Parse a `/settings quiet [off | <start>-<end>]` command, e.g. "quiet 22-07", `None` for other settings
+pub fn parse_quiet_hours_args(args: &str) -> Option<Result<QuietHoursCommand, String>> {
+    let args = args.trim();
+    let (word, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    if !word.eq_ignore_ascii_case("quiet") {
+        return None;
+    }
+    
+    let rest = rest.trim().to_lowercase();
+    let command = match rest.as_str() {
+        "" => Ok(QuietHoursCommand::Show),
+        "off" => Ok(QuietHoursCommand::Off),
+        _ => match rest.split_once('-').or_else(|| rest.split_once(" to ")) {
+            Some((start, end)) => parse_hour(start).and_then(|start_hour| {
+                Ok(QuietHoursCommand::Set { start_hour, end_hour: parse_hour(end)? })
+            }),
+            None => Err(format!("'{rest}' is not a range of hours like 22-07")),
+        },
+    };
+    Some(command)
+}
+
+/// An hour of the day such as "7", "07" or "07:00"
+fn parse_hour(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    let hour = text.strip_suffix(":00").unwrap_or(text);
+    hour.parse::<i64>().map_err(|_| format!("'{text}' is not a whole hour"))
+}
+
+/// Handle `/settings quiet [off | <start>-<end>]`
+async fn handle_quiet_hours_command(
+    bot: Bot,
+    msg: Message,
+    command: Result<QuietHoursCommand, String>,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            feedback.validation_error(&e, QUIET_HOURS_USAGE).await?;
+            return Ok(());
+        }
+    };
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    
+    if command == QuietHoursCommand::Show {
+        feedback.info(&format!("{}\n\n{QUIET_HOURS_USAGE}", quiet_hours_summary(&group))).await?;
+        return Ok(());
+    }
+    
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can change the quiet hours";
+        feedback.validation_error(error_msg, "Ask a chat administrator to change them.").await?;
+        return Ok(());
+    }
+    
+    let hours = match command {
+        QuietHoursCommand::Set { start_hour, end_hour } => Some((start_hour, end_hour)),
+        _ => None,
+    };
+    match Group::update_quiet_hours(&db.pool, group.id, hours).await {
+        Ok(()) => {}
+        Err(GroupSettingError::Database(e)) => return Err(e.into()),
+        Err(e) => {
+            feedback.validation_error(&e.to_string(), QUIET_HOURS_USAGE).await?;
+            return Ok(());
+        }
+    }
+    tracing::info!("Group {} quiet hours set to {:?} by user {}", group.id, hours, from.id);
+    
+    let group = Group {
+        quiet_start_hour: hours.map(|(start_hour, _)| start_hour),
+        quiet_end_hour: hours.map(|(_, end_hour)| end_hour),
+        ..group
+    };
+    feedback.success(&quiet_hours_summary(&group)).await?;
+    
+    Ok(())
+}
+
+/// One-line summary of a group's quiet hours
+fn quiet_hours_summary(group: &Group) -> String {
+    match QuietHours::for_group(group) {
+        Some(quiet) => format!(
+            "Quiet hours: {quiet} ({}); reminders that fall in them go out when they end",
+            group.timezone
+        ),
+        None => "Quiet hours are off".to_string(),
+    }
+}
+
+const WHATS_NEW_USAGE: &str = "Use /settings whatsnew off to stop the \"What's new\" messages after upgrades, or /settings whatsnew on";
+
+/// Parse a `/settings whatsnew [on|off]` command, `None` for other settings
+///
+/// `Ok(None)` asks whether the messages are on.
+pub fn parse_whats_new_args(args: &str) -> Option<Result<Option<bool>, String>> {
+    parse_toggle_args(args, "whatsnew")
+}
+
+/// Parse `/settings <setting> [on|off]` for a setting that is either on or off, `None` for
+/// other settings
+fn parse_toggle_args(args: &str, setting: &str) -> Option<Result<Option<bool>, String>> {
+    let args = args.trim();
+    let (word, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    if !word.eq_ignore_ascii_case(setting) {
+        return None;
+    }
+    
+    let command = match rest.trim().to_lowercase().as_str() {
+        "" => Ok(None),
+        "on" => Ok(Some(true)),
+        "off" => Ok(Some(false)),
+        rest => Err(format!("'{rest}' is neither on nor off")),
+    };
+    Some(command)
+}
+
+/// Handle `/settings whatsnew [on|off]`
+async fn handle_whats_new_command(
+    bot: Bot,
+    msg: Message,
+    command: Result<Option<bool>, String>,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            feedback.validation_error(&e, WHATS_NEW_USAGE).await?;
+            return Ok(());
+        }
+    };
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    
+    let Some(whats_new) = command else {
+        feedback.info(&format!("{}\n\n{WHATS_NEW_USAGE}", whats_new_summary(group.whats_new))).await?;
+        return Ok(());
+    };
+    
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can turn the \"What's new\" messages on or off";
+        feedback.validation_error(error_msg, "Ask a chat administrator to change it.").await?;
+        return Ok(());
+    }
+    
+    Group::update_whats_new(&db.pool, group.id, whats_new).await?;
+    tracing::info!("Group {} whats_new set to {} by user {}", group.id, whats_new, from.id);
+    feedback.success(&whats_new_summary(whats_new)).await?;
+    
+    Ok(())
+}
+
+/// One-line summary of whether a group gets "What's new" messages
+fn whats_new_summary(whats_new: bool) -> String {
+    if whats_new {
+        "After each upgrade the group gets a short \"What's new\" message".to_string()
+    } else {
+        "\"What's new\" messages are off".to_string()
+    }
+}
+
+const BADGES_USAGE: &str = "Use /settings badges off to hide the achievement badges in /stats, or /settings badges on";
+
+/// Parse a `/settings badges [on|off]` command, `None` for other settings
+///
+/// `Ok(None)` asks whether the badges are shown.
+pub fn parse_badges_args(args: &str) -> Option<Result<Option<bool>, String>> {
+    parse_toggle_args(args, "badges")
+}
+
+/// Handle `/settings badges [on|off]`
+async fn handle_badges_command(
+    bot: Bot,
+    msg: Message,
+    command: Result<Option<bool>, String>,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            feedback.validation_error(&e, BADGES_USAGE).await?;
+            return Ok(());
+        }
+    };
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    
+    let Some(badges) = command else {
+        feedback.info(&format!("{}\n\n{BADGES_USAGE}", badges_summary(group.badges))).await?;
+        return Ok(());
+    };
+    
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = "Permission denied: Only chat administrators or the group's bot admins can show or hide the badges";
+        feedback.validation_error(error_msg, "Ask a chat administrator to change it.").await?;
+        return Ok(());
+    }
+    
+    Group::update_badges(&db.pool, group.id, badges).await?;
+    tracing::info!("Group {} badges set to {} by user {}", group.id, badges, from.id);
+    feedback.success(&badges_summary(badges)).await?;
+    
+    Ok(())
+}
+
+/// One-line summary of whether a group's /stats shows badges
+fn badges_summary(badges: bool) -> String {
+    if badges {
+        "/stats shows the group's achievement badges".to_string()
+    } else {
+        "Badges are hidden from /stats".to_string()
+    }
+}
+
+/// Handle `/settings language [<code>]`
+async fn handle_language_command(
+    bot: Bot,
+    msg: Message,
+    code: &str,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? {
+        Some(group) => group,
+        None => Group::create(&db.pool, msg.chat.id.0).await?,
+    };
+    let lang = group.lang();
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id).with_language(lang);
+    let usage = i18n::tr_with(lang, "language.usage", &[("choices", &i18n::SUPPORTED_LANGUAGES.join(", "))]);
+    
+    if code.is_empty() {
+        let current = i18n::tr_with(lang, "language.current", &[("language", lang.name())]);
+        feedback.info(&format!("{current} {usage}")).await?;
+        return Ok(());
+    }
+    
+    let Some(new_lang) = Lang::from_code(code) else {
+        let error_msg = i18n::tr_with(lang, "language.unsupported", &[("code", &code.to_lowercase())]);
+        feedback.validation_error(&error_msg, &usage).await?;
+        return Ok(());
+    };
+    
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await.is_none() {
+        let error_msg = i18n::tr(lang, "language.admins_only");
+        feedback.validation_error(error_msg, i18n::tr(lang, "language.ask_admin")).await?;
+        return Ok(());
+    }
+    
+    match Group::update_language(&db.pool, group.id, new_lang.code()).await {
+        Ok(()) => {}
+        Err(GroupSettingError::Database(e)) => return Err(e.into()),
+        Err(e) => return Err(BotError::Validation(e.to_string())),
+    }
+    tracing::info!("Group {} language set to {} by user {}", group.id, new_lang.code(), from.id);
+    
+    // Confirm in the new language
+    let feedback = feedback.with_language(new_lang);
+    feedback.success(&i18n::tr_with(new_lang, "language.changed", &[("language", new_lang.name())])).await?;
+    
+    Ok(())
+}
+
+/// Handle `/settings admins [add|remove <user_id>]`
+async fn handle_admins_command(
+    bot: Bot,
+    msg: Message,
+    args: &str,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    
+    let command = match parse_admins_command(args) {
+        Ok(command) => command,
+        Err(e) => {
+            feedback.validation_error(&e, ADMINS_USAGE).await?;
+            return Ok(());
+        }
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, msg.chat.id.0).await {
+        Ok(Some(group)) => group,
+        Ok(None) => match Group::create(&db.pool, msg.chat.id.0).await {
+            Ok(group) => group,
+            Err(e) => {
+                tracing::error!("Failed to create group: {}", e);
+                feedback.error("Failed to initialize group settings in database").await?;
+                return Ok(());
+            }
+        },
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            feedback.error("Failed to access group information from database").await?;
+            return Ok(());
+        }
+    };
+    
+    let (adding, user_id) = match command {
+        AdminsCommand::List => {
+            match GroupAdmin::find_by_group(&db.pool, group.id).await {
+                Ok(admins) => {
+                    feedback.info(&admins_summary(&admins)).await?;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to list bot admins of group {}: {}", group.id, e);
+                    feedback.error("Failed to retrieve bot admins from database").await?;
+                }
+            }
+            return Ok(());
+        }
+        AdminsCommand::Add(user_id) => (true, user_id),
+        AdminsCommand::Remove(user_id) => (false, user_id),
+    };
+    
+    // Only the chat's own administrators hand out bot admin rights
+    let Some(from) = msg.from() else {
+        return Ok(());
+    };
+    if group_manager_role(&bot, db, msg.chat.id, group.id, from.id.0 as i64).await != Some(SessionManager::ChatAdmin) {
+        let error_msg = "Permission denied: Only chat administrators can change the bot admins";
+        let suggestion = "Ask a chat administrator to run this command.";
+        feedback.validation_error(error_msg, suggestion).await?;
+        return Ok(());
+    }
+    
+    let target = user_id.or_else(|| {
+        msg.reply_to_message()
+            .and_then(|reply| reply.from())
+            .map(|user| user.id.0 as i64)
+    });
+    let Some(target) = target else {
+        feedback.validation_error("No user given", ADMINS_USAGE).await?;
+        return Ok(());
+    };
+    
+    let result = if adding {
+        GroupAdmin::add(&db.pool, group.id, target, from.id.0 as i64).await
+    } else {
+        GroupAdmin::remove(&db.pool, group.id, target).await
+    };
+    match (adding, result) {
+        (true, Ok(true)) => {
+            tracing::info!("User {} made {} a bot admin of group {}", from.id, target, group.id);
+            feedback.success(&format!("User {target} can now confirm, cancel and set deadlines on any session")).await?;
+        }
+        (true, Ok(false)) => {
+            feedback.info(&format!("User {target} is already a bot admin")).await?;
+        }
+        (false, Ok(true)) => {
+            tracing::info!("User {} removed {} as bot admin of group {}", from.id, target, group.id);
+            feedback.success(&format!("User {target} is no longer a bot admin")).await?;
+        }
+        (false, Ok(false)) => {
+            feedback.info(&format!("User {target} is not a bot admin")).await?;
+        }
+        (_, Err(e)) => {
+            tracing::error!("Failed to update bot admins of group {}: {}", group.id, e);
+            feedback.error("Failed to save the bot admins").await?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Handle a `settings:admins` callback by listing the group's bot admins
+pub async fn handle_admins_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This settings message has expired, use /settings again").await?;
+        return Ok(());
+    };
+    
+    let admins = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => GroupAdmin::find_by_group(&db.pool, group.id).await,
+        Ok(None) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    };
+    let text = match admins {
+        Ok(admins) => format!("{}\n\n{ADMINS_USAGE}", admins_summary(&admins)),
+        Err(e) => {
+            tracing::error!("Failed to list bot admins: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to load the bot admins").await?;
+            return Ok(());
+        }
+    };
+    
+    bot.answer_callback_query(q.id).text(text).show_alert(true).await?;
+    
+    Ok(())
+}
+
+/// Handle a `settings:reminders` callback by previewing the reminders for the next confirmed session
+pub async fn handle_reminder_preview_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This settings message has expired, use /settings again").await?;
+        return Ok(());
+    };
+    
+    let now = Utc::now();
+    let group = Group::find_by_chat_id(&db.pool, message.chat.id.0).await;
+    let cadence = group.as_ref().ok().and_then(Option::as_ref).and_then(LongRangeCadence::for_group);
+    let quiet = group.as_ref().ok().and_then(Option::as_ref).and_then(QuietHours::for_group);
+    let timezone = group.as_ref().ok().and_then(Option::as_ref).map(|group| group.timezone.clone()).unwrap_or_default();
+    let next = match group {
+        Ok(Some(group)) => next_confirmed_session(&db.pool, group.id, now).await,
+        Ok(None) => Ok(None),
+        Err(e) => Err(e),
+    };
+    let (session, option, starts_at) = match next {
+        Ok(Some(next)) => next,
+        Ok(None) => {
+            bot.answer_callback_query(q.id)
+                .text("No upcoming confirmed session to preview reminders for")
+                .await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find the next confirmed session: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to load the next confirmed session").await?;
+            return Ok(());
+        }
+    };
+    
+    let sent_offsets = match Reminder::find_by_session(&db.pool, &session.id).await {
+        Ok(reminders) => reminders.iter()
+            .filter(|reminder| reminder.covers(&option.id))
+            .map(|reminder| reminder.offset_minutes)
+            .collect::<Vec<_>>(),
+        Err(e) => {
+            tracing::error!("Failed to load sent reminders for session {}: {}", session.id, e);
+            bot.answer_callback_query(q.id).text("Failed to load the sent reminders").await?;
+            return Ok(());
+        }
+    };
+    
+    let failed_offsets = match ReminderDelivery::failed_offsets(&db.pool, &session.id).await {
+        Ok(offsets) => offsets,
+        Err(e) => {
+            tracing::error!("Failed to load failed reminders for session {}: {}", session.id, e);
+            bot.answer_callback_query(q.id).text("Failed to load the sent reminders").await?;
+            return Ok(());
+        }
+    };
+    
+    bot.answer_callback_query(q.id).await?;
+    let plan = plan_reminders(starts_at, now, &sent_offsets, &failed_offsets, cadence, quiet);
+    bot.send_message(message.chat.id, render_reminder_preview(&session, starts_at, &plan, &timezone))
+        .parse_mode(ParseMode::MarkdownV2)
+        .await?;
+    
+    Ok(())
+}
+
+/// One-line summary of a group's bot admins
+fn admins_summary(admins: &[GroupAdmin]) -> String {
+    if admins.is_empty() {
+        return "No bot admins yet. Session creators and chat administrators can always manage sessions.".to_string();
+    }
+    
+    let ids = admins.iter().map(|admin| admin.user_id.to_string()).collect::<Vec<_>>().join(", ");
+    format!("Bot admins: {ids}")
+}
 
 pub async fn handle_settings(
     bot: Bot,
     msg: Message,
+    args: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    if let Some(code) = parse_language_args(&args) {
+        return handle_language_command(bot, msg, code, db).await;
+    }
+    if let Some(command) = parse_long_range_args(&args) {
+        return handle_long_range_command(bot, msg, command, db).await;
+    }
+    if let Some(command) = parse_quiet_hours_args(&args) {
+        return handle_quiet_hours_command(bot, msg, command, db).await;
+    }
+    if let Some(command) = parse_whats_new_args(&args) {
+        return handle_whats_new_command(bot, msg, command, db).await;
+    }
+    if let Some(command) = parse_badges_args(&args) {
+        return handle_badges_command(bot, msg, command, db).await;
+    }
+    if !args.is_empty() {
+        return handle_admins_command(bot, msg, &args, db).await;
+    }
+    
+    let chat_id = msg.chat.id.0;
+    let _user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    
+    // Send processing message
+    let processing_msg = feedback.send_processing("Loading group settings...").await?;
+    
+    // Validate chat ID
+    if let Err(e) = validate_telegram_chat_id(chat_id) {
+        let error_msg = format!("Invalid chat configuration: {e}");
+        let suggestion = "This command can only be used in properly configured chat groups.";
+        feedback.validation_error(&error_msg, suggestion).await?;
+        return Ok(());
+    }
+    
+    // Check if user is admin (for now, anyone can access settings)
+    // In a real implementation, you might want to check if user is a group admin
+    
+    // Get or create group
+    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
+        Ok(Some(group)) => {
+            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
+                "Loading group statistics...").await?;
+            group
+        },
+        Ok(None) => {
+            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
+                "Setting up new group...").await?;
+            // Create group if it doesn't exist
+            match Group::create(&db.pool, chat_id).await {
+                Ok(group) => group,
+                Err(e) => {
+                    tracing::error!("Failed to create group: {}", e);
+                    feedback.error("Failed to initialize group settings in database").await?;
+                    return Ok(());
+                }
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            feedback.error("Failed to access group information from database").await?;
+            return Ok(());
+        }
+    };
+    
+    // Get group statistics
+    let stats = match get_group_stats(&db.pool, group.id).await {
+        Ok(stats) => stats,
+        Err(e) => {
+            tracing::warn!("Failed to get group stats: {}", e);
+            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
+                "Loading settings (statistics unavailable)...").await?;
+            GroupStats::default()
+        }
+    };
+    
+    let message_text = render_settings_text(&group, &stats);
+    let keyboard = settings_keyboard();
+    
+    // Send the settings message with enhanced feedback
+    let _settings_response = bot.send_message(msg.chat.id, message_text)
+        .reply_markup(keyboard)
+        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+        .await?;
+    
+    // Update processing message to show completion
+    let completion_message = format!(
+        "Group settings loaded successfully!\\n\\n📊 Found {} total sessions and {} responses\\n\\n💡 Use the buttons below to configure settings",
+        stats.total_sessions, 
+        stats.total_responses
+    );
+    
+    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, &completion_message).await?;
+    
+    Ok(())
+}
+
+/// Handle a `settings:duration` (show choices) or `settings:duration:<minutes>` (apply) callback
+///
+/// Anyone may open the choices, but only chat administrators and the group's bot admins may pick one.
+pub async fn handle_duration_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This settings message has expired, use /settings again").await?;
+        return Ok(());
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            bot.answer_callback_query(q.id).text("Group settings not found, use /settings again").await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to load group settings").await?;
+            return Ok(());
+        }
+    };
+    
+    // Without a value, swap the keyboard for the list of choices
+    let Some(minutes) = data.strip_prefix("settings:duration:") else {
+        bot.answer_callback_query(q.id).await?;
+        bot.edit_message_reply_markup(message.chat.id, message.id)
+            .reply_markup(duration_keyboard(group.default_duration))
+            .await?;
+        return Ok(());
+    };
+    
+    let Ok(minutes) = minutes.parse::<i64>() else {
+        bot.answer_callback_query(q.id).text("Invalid duration").await?;
+        return Ok(());
+    };
+    
+    if group_manager_role(&bot, db, message.chat.id, group.id, q.from.id.0 as i64).await.is_none() {
+        bot.answer_callback_query(q.id).text("Only chat administrators or the group's bot admins can change the default duration").await?;
+        return Ok(());
+    }
+    
+    if let Err(e) = Group::update_default_duration(&db.pool, group.id, minutes).await {
+        match e {
+            GroupSettingError::Database(e) => {
+                tracing::error!("Failed to update default duration for group {}: {}", group.id, e);
+                bot.answer_callback_query(q.id).text("Failed to save the default duration").await?;
+            }
+            e => {
+                bot.answer_callback_query(q.id).text(e.to_string()).await?;
+            }
+        }
+        return Ok(());
+    }
+    tracing::info!("Group {} default duration set to {} minutes by user {}", group.id, minutes, q.from.id);
+    
+    bot.answer_callback_query(q.id)
+        .text(format!("⏱️ New sessions will last {}", format_duration(minutes)))
+        .await?;
+    
+    // Show the main settings view again with the new value
+    let group = Group { default_duration: minutes, ..group };
+    let stats = get_group_stats(&db.pool, group.id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to get group stats: {}", e);
+        GroupStats::default()
+    });
+    bot.edit_message_text(message.chat.id, message.id, render_settings_text(&group, &stats))
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(settings_keyboard())
+        .await?;
+    
+    Ok(())
+}
+
+/// What to reply to the question for `field`'s new value
+fn setting_hint(field: SettingField) -> &'static str {
+    match field {
+        SettingField::Timezone => "Reply with a UTC offset like UTC, UTC+2 or UTC-5:30, or 'cancel' to keep it. Region names like Europe/Berlin aren't supported yet.",
+        SettingField::DefaultDuration => "Reply with a length like 150, 2h30 or 90m, or 'cancel' to keep it.",
+    }
+}
+
+/// Parse a typed value for `field`; ranges are checked by [`Group::update_setting`]
+pub fn parse_setting_value(field: SettingField, text: &str) -> Result<GroupSetting, String> {
+    match field {
+        SettingField::Timezone => normalize_timezone(text)
+            .map(GroupSetting::Timezone)
+            .ok_or_else(|| format!("'{}' isn't a UTC offset", text.trim())),
+        SettingField::DefaultDuration => parse_duration(text)
+            .map(GroupSetting::DefaultDuration)
+            .ok_or_else(|| format!("'{}' isn't a length of time", text.trim())),
+    }
+}
+
+/// Handle a `settings:timezone` or `settings:duration:custom` callback by asking for the new value
+///
+/// The reply from whoever pressed the button is handled by [`handle_setting_reply`]. Only chat
+/// administrators and the group's bot admins may start editing.
+pub async fn handle_setting_prompt_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    field: SettingField,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This settings message has expired, use /settings again").await?;
+        return Ok(());
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            bot.answer_callback_query(q.id).text("Group settings not found, use /settings again").await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to load group settings").await?;
+            return Ok(());
+        }
+    };
+    
+    if group_manager_role(&bot, db, message.chat.id, group.id, q.from.id.0 as i64).await.is_none() {
+        bot.answer_callback_query(q.id).text("Only chat administrators or the group's bot admins can change the settings").await?;
+        return Ok(());
+    }
+    
+    // The storage is the database, so this is the same dialogue the dispatcher hands to the reply
+    let dialogue = BotDialogue::new(DialogueStorage::new(db.pool.clone()), message.chat.id);
+    let state = DialogueState::AwaitingSetting { setting: field, user_id: q.from.id.0 as i64, message_id: message.id.0 };
+    if let Err(e) = dialogue.update(state).await {
+        tracing::error!("Failed to wait for a new {:?} in group {}: {}", field, group.id, e);
+        bot.answer_callback_query(q.id).text("Failed to start editing the setting").await?;
+        return Ok(());
+    }
+    bot.answer_callback_query(q.id).await?;
+    
+    let current = match field {
+        SettingField::Timezone => format!("🕐 The group's clock is {}.", group.timezone),
+        SettingField::DefaultDuration => format!("⏱️ New sessions last {}.", format_duration(group.default_duration)),
+    };
+    // A forced reply reaches the bot even when it can't read the rest of the chat
+    bot.send_message(message.chat.id, format!("{current} {}", setting_hint(field)))
+        .reply_markup(ForceReply::new())
+        .await?;
+    
+    Ok(())
+}
+
+/// Handle the reply to [`handle_setting_prompt_callback`]: save the new value and show the settings with it
+pub async fn handle_setting_reply(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    state: DialogueState,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
-    let chat_id = msg.chat.id.0;
-    let _user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+) -> Result<(), BotError> {
+    let DialogueState::AwaitingSetting { setting: field, message_id, .. } = state else {
+        return Ok(());
+    };
     let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let text = msg.text().unwrap_or_default().trim();
     
-    // Send processing message
-    let processing_msg = feedback.send_processing("Loading group settings...").await?;
+    if text.eq_ignore_ascii_case("cancel") {
+        exit(&dialogue).await;
+        feedback.info("Okay, the setting stays as it was").await?;
+        return Ok(());
+    }
+    let setting = match parse_setting_value(field, text) {
+        Ok(setting) => setting,
+        Err(e) => {
+            feedback.validation_error(&e, setting_hint(field)).await?;
+            return Ok(());
+        }
+    };
     
-    // Validate chat ID
-    if let Err(e) = validate_telegram_chat_id(chat_id) {
-        let error_msg = format!("Invalid chat configuration: {e}");
-        let suggestion = "This command can only be used in properly configured chat groups.";
-        feedback.validation_error(&error_msg, suggestion).await?;
+    let Some(group) = Group::find_by_chat_id(&db.pool, msg.chat.id.0).await? else {
+        exit(&dialogue).await;
+        feedback.validation_error("Group settings not found", "Use /settings again.").await?;
         return Ok(());
+    };
+    match Group::update_setting(&db.pool, group.id, &setting).await {
+        Ok(()) => {}
+        Err(GroupSettingError::Database(e)) => return Err(e.into()),
+        Err(e) => {
+            feedback.validation_error(&e.to_string(), setting_hint(field)).await?;
+            return Ok(());
+        }
     }
+    exit(&dialogue).await;
+    let user_id = msg.from().map(|u| u.id.0).unwrap_or(0);
+    tracing::info!("Group {} setting {:?} changed by user {}", group.id, setting, user_id);
     
-    // Check if user is admin (for now, anyone can access settings)
-    // In a real implementation, you might want to check if user is a group admin
+    let confirmation = match &setting {
+        GroupSetting::Timezone(timezone) => format!("🕐 The group's clock is now {timezone}"),
+        GroupSetting::DefaultDuration(minutes) => format!("⏱️ New sessions will last {}", format_duration(*minutes)),
+        GroupSetting::MinNoticeHours(hours) => format!("⏳ Options must now be at least {} away", notice_label(*hours)),
+        GroupSetting::AutoconfirmThreshold(threshold) => autoconfirm_confirmation(*threshold),
+    };
+    feedback.success(&confirmation).await?;
     
-    // Get or create group
-    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
-        Ok(Some(group)) => {
-            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-                "Loading group statistics...").await?;
-            group
-        },
+    // Show the settings message again with the new value; it may have been closed meanwhile
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap_or(group);
+    let stats = get_group_stats(&db.pool, group.id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to get group stats: {}", e);
+        GroupStats::default()
+    });
+    let edited = bot.edit_message_text(msg.chat.id, MessageId(message_id), render_settings_text(&group, &stats))
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(settings_keyboard())
+        .await;
+    if let Err(e) = edited {
+        tracing::debug!("Failed to show the new settings of group {}: {}", group.id, e);
+    }
+    
+    Ok(())
+}
+
+/// Handle a `settings:notice` (show choices) or `settings:notice:<hours>` (apply) callback
+///
+/// Anyone may open the choices, but only chat administrators and the group's bot admins may pick one.
+pub async fn handle_notice_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This settings message has expired, use /settings again").await?;
+        return Ok(());
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => group,
         Ok(None) => {
-            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-                "Setting up new group...").await?;
-            // Create group if it doesn't exist
-            match Group::create(&db.pool, chat_id).await {
-                Ok(group) => group,
-                Err(e) => {
-                    tracing::error!("Failed to create group: {}", e);
-                    feedback.error("Failed to initialize group settings in database").await?;
-                    return Ok(());
-                }
+            bot.answer_callback_query(q.id).text("Group settings not found, use /settings again").await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to load group settings").await?;
+            return Ok(());
+        }
+    };
+    
+    // Without a value, swap the keyboard for the list of choices
+    let Some(hours) = data.strip_prefix("settings:notice:") else {
+        bot.answer_callback_query(q.id).await?;
+        bot.edit_message_reply_markup(message.chat.id, message.id)
+            .reply_markup(notice_keyboard(group.min_notice_hours))
+            .await?;
+        return Ok(());
+    };
+    
+    let Ok(hours) = hours.parse::<i64>() else {
+        bot.answer_callback_query(q.id).text("Invalid notice period").await?;
+        return Ok(());
+    };
+    
+    if group_manager_role(&bot, db, message.chat.id, group.id, q.from.id.0 as i64).await.is_none() {
+        bot.answer_callback_query(q.id).text("Only chat administrators or the group's bot admins can change the minimum notice").await?;
+        return Ok(());
+    }
+    
+    if let Err(e) = Group::update_min_notice_hours(&db.pool, group.id, hours).await {
+        match e {
+            GroupSettingError::Database(e) => {
+                tracing::error!("Failed to update minimum notice for group {}: {}", group.id, e);
+                bot.answer_callback_query(q.id).text("Failed to save the minimum notice").await?;
             }
+            e => {
+                bot.answer_callback_query(q.id).text(e.to_string()).await?;
+            }
+        }
+        return Ok(());
+    }
+    tracing::info!("Group {} minimum notice set to {} hours by user {}", group.id, hours, q.from.id);
+    
+    let confirmation = if hours == 0 {
+        "⏳ Sessions can be scheduled at any time".to_string()
+    } else {
+        format!("⏳ Options must now be at least {} away", format_duration(hours * 60))
+    };
+    bot.answer_callback_query(q.id).text(confirmation).await?;
+    
+    // Show the main settings view again with the new value
+    let group = Group { min_notice_hours: hours, ..group };
+    let stats = get_group_stats(&db.pool, group.id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to get group stats: {}", e);
+        GroupStats::default()
+    });
+    bot.edit_message_text(message.chat.id, message.id, render_settings_text(&group, &stats))
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(settings_keyboard())
+        .await?;
+    
+    Ok(())
+}
+
+/// Handle a `settings:autoconfirm` (show choices) or `settings:autoconfirm:<votes|off>` (apply)
+/// callback
+///
+/// Auto-confirm locks sessions in without anyone running /confirm, so only chat administrators
+/// and the group's bot admins may change it.
+pub async fn handle_autoconfirm_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This settings message has expired, use /settings again").await?;
+        return Ok(());
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            bot.answer_callback_query(q.id).text("Group settings not found, use /settings again").await?;
+            return Ok(());
         }
         Err(e) => {
             tracing::error!("Failed to find group: {}", e);
-            feedback.error("Failed to access group information from database").await?;
+            bot.answer_callback_query(q.id).text("Failed to load group settings").await?;
             return Ok(());
         }
     };
     
-    // Get group statistics
-    let stats = match get_group_stats(&db.pool, group.id).await {
-        Ok(stats) => stats,
+    // Without a value, swap the keyboard for the list of choices
+    let Some(value) = data.strip_prefix("settings:autoconfirm:") else {
+        bot.answer_callback_query(q.id).await?;
+        bot.edit_message_reply_markup(message.chat.id, message.id)
+            .reply_markup(autoconfirm_keyboard(group.autoconfirm_threshold))
+            .await?;
+        return Ok(());
+    };
+    
+    let threshold = match value {
+        "off" => None,
+        votes => match votes.parse::<i64>() {
+            Ok(votes) => Some(votes),
+            Err(_) => {
+                bot.answer_callback_query(q.id).text("Invalid auto-confirm threshold").await?;
+                return Ok(());
+            }
+        },
+    };
+    
+    if group_manager_role(&bot, db, message.chat.id, group.id, q.from.id.0 as i64).await.is_none() {
+        bot.answer_callback_query(q.id).text("Only chat administrators or the group's bot admins can change auto-confirm").await?;
+        return Ok(());
+    }
+    
+    if let Err(e) = Group::update_setting(&db.pool, group.id, &GroupSetting::AutoconfirmThreshold(threshold)).await {
+        match e {
+            GroupSettingError::Database(e) => {
+                tracing::error!("Failed to update auto-confirm for group {}: {}", group.id, e);
+                bot.answer_callback_query(q.id).text("Failed to save auto-confirm").await?;
+            }
+            e => {
+                bot.answer_callback_query(q.id).text(e.to_string()).await?;
+            }
+        }
+        return Ok(());
+    }
+    tracing::info!("Group {} auto-confirm threshold set to {:?} by user {}", group.id, threshold, q.from.id);
+    
+    bot.answer_callback_query(q.id).text(autoconfirm_confirmation(threshold)).await?;
+    
+    // Show the main settings view again with the new value
+    let group = Group { autoconfirm_threshold: threshold, ..group };
+    let stats = get_group_stats(&db.pool, group.id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to get group stats: {}", e);
+        GroupStats::default()
+    });
+    bot.edit_message_text(message.chat.id, message.id, render_settings_text(&group, &stats))
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(settings_keyboard())
+        .await?;
+    
+    Ok(())
+}
+
+/// Handle a `settings:language` (show choices) or `settings:language:<code>` (apply) callback
+///
+/// Like `/settings language`, only chat administrators and the group's bot admins may change it.
+pub async fn handle_language_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This settings message has expired, use /settings again").await?;
+        return Ok(());
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            bot.answer_callback_query(q.id).text("Group settings not found, use /settings again").await?;
+            return Ok(());
+        }
         Err(e) => {
-            tracing::warn!("Failed to get group stats: {}", e);
-            feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
-                "Loading settings (statistics unavailable)...").await?;
-            GroupStats::default()
+            tracing::error!("Failed to find group: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to load group settings").await?;
+            return Ok(());
         }
     };
     
-    let message_text = format!(
-        "⚙️ **Group Settings**\n\n\
-        📊 **Group Statistics:**\n\
-        • Total Sessions: {}\n\
-        • Active Sessions: {}\n\
-        • Confirmed Sessions: {}\n\
-        • Total Responses: {}\n\n\
-        🔧 **Available Settings:**\n\
-        • Timezone: UTC \\(coming soon\\)\n\
-        • Default Duration: 4 hours \\(coming soon\\)\n\
-        • Auto\\-confirm: Disabled \\(coming soon\\)\n\n\
-        💡 **Tips:**\n\
-        • Use `/list` to see all active sessions\n\
-        • Session creators can use `/confirm` and `/cancel`\n\
-        • Set deadlines with `/deadline <session_id> <time>`",
-        stats.total_sessions,
-        stats.active_sessions,
-        stats.confirmed_sessions,
-        stats.total_responses
-    );
+    // Without a value, swap the keyboard for the list of choices
+    let Some(code) = data.strip_prefix("settings:language:") else {
+        bot.answer_callback_query(q.id).await?;
+        bot.edit_message_reply_markup(message.chat.id, message.id)
+            .reply_markup(language_keyboard(group.lang()))
+            .await?;
+        return Ok(());
+    };
+    
+    let Some(lang) = Lang::from_code(code) else {
+        bot.answer_callback_query(q.id)
+            .text(i18n::tr_with(group.lang(), "language.unsupported", &[("code", code)]))
+            .await?;
+        return Ok(());
+    };
+    
+    if group_manager_role(&bot, db, message.chat.id, group.id, q.from.id.0 as i64).await.is_none() {
+        bot.answer_callback_query(q.id).text(i18n::tr(group.lang(), "language.admins_only")).await?;
+        return Ok(());
+    }
+    
+    if let Err(e) = Group::update_language(&db.pool, group.id, lang.code()).await {
+        tracing::error!("Failed to update language for group {}: {}", group.id, e);
+        bot.answer_callback_query(q.id).text("Failed to save the language").await?;
+        return Ok(());
+    }
+    tracing::info!("Group {} language set to {} by user {}", group.id, lang.code(), q.from.id);
+    
+    bot.answer_callback_query(q.id)
+        .text(format!("🌐 {}", i18n::tr_with(lang, "language.changed", &[("language", lang.name())])))
+        .await?;
+    
+    // Show the main settings view again with the new value
+    let group = Group { language: lang.code().to_string(), ..group };
+    let stats = get_group_stats(&db.pool, group.id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to get group stats: {}", e);
+        GroupStats::default()
+    });
+    bot.edit_message_text(message.chat.id, message.id, render_settings_text(&group, &stats))
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(settings_keyboard())
+        .await?;
+    
+    Ok(())
+}
+
+/// Handle a `settings:usernames` callback by flipping the group's `store_usernames` setting
+///
+/// Turning it off erases the stored usernames, so only chat administrators and the group's bot
+/// admins may flip it.
+pub async fn handle_usernames_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This settings message has expired, use /settings again").await?;
+        return Ok(());
+    };
+    
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            bot.answer_callback_query(q.id).text("Group settings not found, use /settings again").await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to load group settings").await?;
+            return Ok(());
+        }
+    };
+    
+    if group_manager_role(&bot, db, message.chat.id, group.id, q.from.id.0 as i64).await.is_none() {
+        bot.answer_callback_query(q.id).text("Only chat administrators or the group's bot admins can change username privacy").await?;
+        return Ok(());
+    }
+    
+    let store_usernames = !group.store_usernames;
+    if let Err(e) = Group::update_store_usernames(&db.pool, group.id, store_usernames).await {
+        tracing::error!("Failed to update username setting for group {}: {}", group.id, e);
+        bot.answer_callback_query(q.id).text("Failed to save the username setting").await?;
+        return Ok(());
+    }
+    tracing::info!("Group {} store_usernames set to {} by user {}", group.id, store_usernames, q.from.id);
+    
+    let confirmation = if store_usernames {
+        "👤 Usernames will be stored for new votes"
+    } else {
+        "🕶️ Usernames erased, voters now show as Player #N"
+    };
+    bot.answer_callback_query(q.id).text(confirmation).await?;
+    
+    let group = Group { store_usernames, ..group };
+    let stats = get_group_stats(&db.pool, group.id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to get group stats: {}", e);
+        GroupStats::default()
+    });
+    bot.edit_message_text(message.chat.id, message.id, render_settings_text(&group, &stats))
+        .parse_mode(ParseMode::MarkdownV2)
+        .reply_markup(settings_keyboard())
+        .await?;
     
-    // Create inline keyboard for future settings
-    let keyboard = InlineKeyboardMarkup::new(vec![
+    Ok(())
+}
+
+/// The main settings keyboard
+pub(crate) fn settings_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![
         vec![
             InlineKeyboardButton::callback("🕐 Timezone Settings", "settings:timezone"),
             InlineKeyboardButton::callback("⏱️ Default Duration", "settings:duration"),
         ],
+        vec![
+            InlineKeyboardButton::callback("⏳ Minimum Notice", "settings:notice"),
+            InlineKeyboardButton::callback("👤 Username Privacy", "settings:usernames"),
+        ],
         vec![
             InlineKeyboardButton::callback("🤖 Auto-confirm", "settings:autoconfirm"),
             InlineKeyboardButton::callback("📊 Full Stats", "settings:stats"),
         ],
         vec![
+            InlineKeyboardButton::callback("👮 Bot Admins", "settings:admins"),
+            InlineKeyboardButton::callback("🔎 Preview reminders", "settings:reminders"),
+        ],
+        vec![
+            InlineKeyboardButton::callback("🌐 Language", "settings:language"),
             InlineKeyboardButton::callback("❌ Close", "settings:close"),
         ],
-    ]);
+    ])
+}
+
+/// Keyboard listing every language by its own name, with the current one ticked
+fn language_keyboard(current: Lang) -> InlineKeyboardMarkup {
+    let row = Lang::ALL.iter()
+        .map(|&lang| {
+            let label = if lang == current {
+                format!("✓ {}", lang.name())
+            } else {
+                lang.name().to_string()
+            };
+            InlineKeyboardButton::callback(label, format!("settings:language:{}", lang.code()))
+        })
+        .collect::<Vec<_>>();
     
-    // Send the settings message with enhanced feedback
-    let _settings_response = bot.send_message(msg.chat.id, message_text)
-        .reply_markup(keyboard)
-        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-        .await?;
+    InlineKeyboardMarkup::new(vec![row])
+}
+
+/// Keyboard listing `DURATION_CHOICES`, with the current value ticked, and a button to type another
+fn duration_keyboard(current: i64) -> InlineKeyboardMarkup {
+    let mut rows = DURATION_CHOICES
+        .chunks(4)
+        .map(|chunk| {
+            chunk.iter()
+                .map(|&minutes| {
+                    let label = if minutes == current {
+                        format!("✓ {}", format_duration(minutes))
+                    } else {
+                        format_duration(minutes)
+                    };
+                    InlineKeyboardButton::callback(label, format!("settings:duration:{minutes}"))
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    let other = if DURATION_CHOICES.contains(&current) {
+        "✏️ Other…".to_string()
+    } else {
+        format!("✓ {} ✏️", format_duration(current))
+    };
+    rows.push(vec![InlineKeyboardButton::callback(other, "settings:duration:custom")]);
     
-    // Update processing message to show completion
-    let completion_message = format!(
-        "Group settings loaded successfully!\\n\\n📊 Found {} total sessions and {} responses\\n\\n💡 Use the buttons below to configure settings",
-        stats.total_sessions, 
-        stats.total_responses
-    );
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Keyboard listing `NOTICE_CHOICES`, with the current value ticked
+fn notice_keyboard(current: i64) -> InlineKeyboardMarkup {
+    let rows = NOTICE_CHOICES
+        .chunks(4)
+        .map(|chunk| {
+            chunk.iter()
+                .map(|&hours| {
+                    let label = if hours == current {
+                        format!("✓ {}", notice_label(hours))
+                    } else {
+                        notice_label(hours)
+                    };
+                    InlineKeyboardButton::callback(label, format!("settings:notice:{hours}"))
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
     
-    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, &completion_message).await?;
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Keyboard listing `AUTOCONFIRM_CHOICES` and "Off", with the current value ticked
+fn autoconfirm_keyboard(current: Option<i64>) -> InlineKeyboardMarkup {
+    let tick = |label: String, selected: bool| if selected { format!("✓ {label}") } else { label };
+    let mut rows = AUTOCONFIRM_CHOICES
+        .chunks(4)
+        .map(|chunk| {
+            chunk.iter()
+                .map(|&votes| {
+                    let label = tick(format!("{votes} yes"), current == Some(votes));
+                    InlineKeyboardButton::callback(label, format!("settings:autoconfirm:{votes}"))
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    rows.push(vec![InlineKeyboardButton::callback(tick("Off".to_string(), current.is_none()), "settings:autoconfirm:off")]);
     
-    Ok(())
+    InlineKeyboardMarkup::new(rows)
+}
+
+fn autoconfirm_confirmation(threshold: Option<i64>) -> String {
+    match threshold {
+        Some(votes) => format!("🤖 Polls with a deadline now confirm themselves at {votes} yes votes"),
+        None => "🤖 Auto-confirm is off; sessions wait for /confirm".to_string(),
+    }
+}
+
+fn autoconfirm_label(threshold: Option<i64>) -> String {
+    match threshold {
+        Some(votes) => format!("at {votes} yes votes, for polls with a deadline"),
+        None => "Off".to_string(),
+    }
+}
+
+fn notice_label(hours: i64) -> String {
+    if hours == 0 {
+        "None".to_string()
+    } else {
+        format_duration(hours * 60)
+    }
+}
+
+fn render_settings_text(group: &Group, stats: &GroupStats) -> String {
+    format!(
+        "⚙️ **Group Settings**\n\n\
+        📊 **Group Statistics:**\n\
+        • Total Sessions: {}\n\
+        • Active Sessions: {}\n\
+        • Confirmed Sessions: {}\n\
+        • Total Responses: {}\n\n\
+        🔧 **Available Settings:**\n\
+        • Timezone: {}\n\
+        • Default Duration: {}\n\
+        • Minimum Notice: {}\n\
+        • Usernames: {}\n\
+        • Language: {}\n\
+        • Long\\-range Reminders: {}\n\
+        • Quiet Hours: {}\n\
+        • Auto\\-confirm: {}\n\n\
+        💡 **Tips:**\n\
+        • Use `/list` to see all active sessions\n\
+        • Session creators can use `/confirm` and `/cancel`\n\
+        • Set deadlines with `/deadline <session_id> <time>`",
+        stats.total_sessions,
+        stats.active_sessions,
+        stats.confirmed_sessions,
+        stats.total_responses,
+        escape_markdown(&group.timezone),
+        escape_markdown(&format_duration(group.default_duration)),
+        escape_markdown(&notice_label(group.min_notice_hours)),
+        if group.store_usernames { "Stored" } else { "Hidden \\(shown as Player \\#N\\)" },
+        escape_markdown(group.lang().name()),
+        escape_markdown(&LongRangeCadence::for_group(group).map_or("Off".to_string(), |cadence| cadence.to_string())),
+        escape_markdown(&QuietHours::for_group(group).map_or("Off".to_string(), |quiet| quiet.to_string())),
+        escape_markdown(&autoconfirm_label(group.autoconfirm_threshold))
+    )
 }
 
 #[derive(Default)]
@@ -1,17 +1,25 @@
 use teloxide::prelude::*;
+use crate::error::BotError;
+use crate::bot::badges::{award_badges, Award, Badge, BadgeTallies};
+use crate::bot::commands::session_management::resolve_session;
 use crate::database::{connection::DatabaseManager, models::*};
 use crate::utils::{datetime::format_datetime, feedback::CommandFeedback};
-use chrono::Utc;
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 
 pub async fn handle_stats(
     bot: Bot,
     msg: Message,
+    session_id: Option<String>,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
+) -> Result<(), BotError> {
     let chat_id = msg.chat.id.0;
     let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
     
+    if let Some(session_id) = session_id {
+        return handle_session_stats(&feedback, chat_id, &session_id, db).await;
+    }
+    
     // Send processing message
     let processing_msg = feedback.send_processing("Generating group statistics...").await?;
     
@@ -36,7 +44,7 @@ pub async fn handle_stats(
     };
     
     // Get detailed statistics
-    let stats = match get_detailed_stats(&db.pool, group.id).await {
+    let stats = match get_detailed_stats(&db.pool, &group).await {
         Ok(stats) => {
             feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Processing, 
                 "Calculating response statistics...").await?;
@@ -57,11 +65,13 @@ pub async fn handle_stats(
         • Total Sessions: {}\n\
         • Active Sessions: {}\n\
         • Confirmed Sessions: {}\n\
-        • Cancelled Sessions: {}\n\n",
+        • Cancelled Sessions: {}\n\
+        • Archived Sessions: {}\n\n",
         stats.total_sessions,
         stats.active_sessions,
         stats.confirmed_sessions,
-        stats.cancelled_sessions
+        stats.cancelled_sessions,
+        stats.archived_sessions
     ));
     
     // Response Statistics
@@ -70,7 +80,7 @@ pub async fn handle_stats(
         • Total Responses: {}\n\
         • Yes Responses: {} \\({:.1}%\\)\n\
         • No Responses: {} \\({:.1}%\\)\n\
-        • Maybe Responses: {} \\({:.1}%\\)\n\n",
+        • Maybe Responses: {} \\({:.1}%\\)\n",
         stats.total_responses,
         stats.yes_responses,
         if stats.total_responses > 0 { stats.yes_responses as f64 / stats.total_responses as f64 * 100.0 } else { 0.0 },
@@ -79,22 +89,114 @@ pub async fn handle_stats(
         stats.maybe_responses,
         if stats.total_responses > 0 { stats.maybe_responses as f64 / stats.total_responses as f64 * 100.0 } else { 0.0 }
     ));
+    if let Some(percentage) = stats.turnout.percentage() {
+        message_text.push_str(&format!(
+            "• Participation: {}% of the {} members vote on a typical poll\n",
+            percentage,
+            stats.turnout.members
+        ));
+    }
+    message_text.push('\n');
     
     // User Participation
     if !stats.user_participation.is_empty() {
         message_text.push_str("👥 **Top Participants:**\n");
-        let mut participants: Vec<_> = stats.user_participation.iter().collect();
-        participants.sort_by(|a, b| b.1.cmp(a.1));
+        let participants = &stats.user_participation[..stats.user_participation.len().min(5)];
+        let voters: Vec<_> = participants.iter().map(|p| (p.user_id, p.username.as_deref())).collect();
+        let names = current_names(&db.pool, &group, &voters).await;
         
-        for (i, (username, count)) in participants.iter().take(5).enumerate() {
+        for (i, participant) in participants.iter().enumerate() {
             let medal = match i {
                 0 => "🥇",
                 1 => "🥈", 
                 2 => "🥉",
                 _ => "🏅"
             };
-            let display_name = username.as_deref().unwrap_or("Anonymous");
-            message_text.push_str(&format!("  {} {} \\({} responses\\)\n", medal, escape_markdown(display_name), count));
+            let display_name = names.get(&participant.user_id).map_or("Anonymous", String::as_str);
+            message_text.push_str(&format!(
+                "  {} {} \\({} responses\\)\n",
+                medal,
+                escape_markdown(display_name),
+                participant.response_count
+            ));
+        }
+        message_text.push('\n');
+    }
+    
+    // Attendance streaks
+    if !stats.streaks.is_empty() {
+        message_text.push_str("🔥 **Longest streaks:**\n");
+        let streaks = &stats.streaks[..stats.streaks.len().min(STREAK_LEADERS)];
+        let voters: Vec<_> = streaks.iter()
+            .map(|&(user_id, _)| {
+                let username = stats.user_participation.iter()
+                    .find(|p| p.user_id == user_id)
+                    .and_then(|p| p.username.as_deref());
+                (user_id, username)
+            })
+            .collect();
+        let names = current_names(&db.pool, &group, &voters).await;
+        
+        for (user_id, streak) in streaks {
+            let display_name = names.get(user_id).map_or("Anonymous", String::as_str);
+            let sessions = if *streak == 1 { "session" } else { "sessions in a row" };
+            message_text.push_str(&format!("  🔥 {} \\({} {}\\)\n", escape_markdown(display_name), streak, sessions));
+        }
+        message_text.push('\n');
+    }
+    
+    // Reliability, from the attendance marked with /attended
+    if !stats.reliability.is_empty() {
+        message_text.push_str("🎯 **Reliability \\(came / said yes\\):**\n");
+        let rows = &stats.reliability[..stats.reliability.len().min(RELIABILITY_ROWS)];
+        let voters: Vec<_> = rows.iter()
+            .map(|row| {
+                let username = stats.user_participation.iter()
+                    .find(|p| p.user_id == row.user_id)
+                    .and_then(|p| p.username.as_deref());
+                (row.user_id, username)
+            })
+            .collect();
+        let names = current_names(&db.pool, &group, &voters).await;
+        
+        for row in rows {
+            let display_name = names.get(&row.user_id).map_or("Anonymous", String::as_str);
+            message_text.push_str(&format!(
+                "  • {} {}% \\({}/{}\\)\n",
+                escape_markdown(display_name),
+                row.percentage(),
+                row.attended,
+                row.said_yes
+            ));
+        }
+        message_text.push('\n');
+    }
+    
+    // Badges, unless the group turned them off with /settings badges off
+    if !stats.badges.is_empty() {
+        message_text.push_str("🏆 **Badges:**\n");
+        let voters: Vec<_> = stats.badges.iter()
+            .filter_map(|award| award.user_id)
+            .map(|user_id| {
+                let username = stats.user_participation.iter()
+                    .find(|p| p.user_id == user_id)
+                    .and_then(|p| p.username.as_deref());
+                (user_id, username)
+            })
+            .collect();
+        let names = current_names(&db.pool, &group, &voters).await;
+        
+        for award in &stats.badges {
+            let holder = award.user_id
+                .map(|user_id| names.get(&user_id).map_or("Anonymous", String::as_str))
+                .unwrap_or("the whole group");
+            message_text.push_str(&format!(
+                "  {} {}: {} \\({}\\)\n",
+                award.badge.emoji(),
+                award.badge.name(),
+                escape_markdown(holder),
+                badge_count_label(award)
+            ));
         }
         message_text.push('\n');
     }
@@ -112,12 +214,7 @@ pub async fn handle_stats(
             • Status: {}\n\n",
             escape_markdown(&recent_session.title),
             escape_markdown(&created_at),
-            match recent_session.status.as_str() {
-                "active" => "🟢 Active",
-                "confirmed" => "✅ Confirmed",
-                "cancelled" => "❌ Cancelled",
-                _ => "⚪ Unknown"
-            }
+            status_label(&recent_session.status)
         ));
     }
     
@@ -130,23 +227,287 @@ pub async fn handle_stats(
     Ok(())
 }
 
+async fn handle_session_stats(
+    feedback: &CommandFeedback,
+    chat_id: i64,
+    session_ref: &str,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    tracing::info!("Session stats requested for '{}' in chat {}", session_ref, chat_id);
+    
+    let processing_msg = feedback.send_processing("Generating session statistics...").await?;
+    
+    let (group, session) = match resolve_session(feedback, db, chat_id, session_ref).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+    
+    // Reuse the batch queries from /list for a single session
+    let session_ids = vec![session.id.clone()];
+    let options = match SessionOption::find_by_sessions(&db.pool, &session_ids).await {
+        Ok(options) => options,
+        Err(e) => {
+            tracing::error!("Failed to get options for session {}: {}", session.id, e);
+            feedback.error("Failed to retrieve session time options").await?;
+            return Ok(());
+        }
+    };
+    let responses = match Response::find_by_sessions(&db.pool, &session_ids).await {
+        Ok(responses) => responses,
+        Err(e) => {
+            tracing::error!("Failed to get responses for session {}: {}", session.id, e);
+            feedback.error("Failed to retrieve player responses").await?;
+            return Ok(());
+        }
+    };
+    let members = match GroupMember::find_active_by_group(&db.pool, group.id).await {
+        Ok(members) => members,
+        Err(e) => {
+            tracing::error!("Failed to get members for group {}: {}", group.id, e);
+            feedback.error("Failed to retrieve group members").await?;
+            return Ok(());
+        }
+    };
+    
+    let voters: Vec<_> = responses.iter().map(|r| (r.user_id, r.username.as_deref())).collect();
+    let names = match display_names(&db.pool, &group, &voters).await {
+        Ok(names) => names,
+        Err(e) => {
+            tracing::error!("Failed to resolve voter names for group {}: {}", group.id, e);
+            feedback.error("Failed to retrieve player names").await?;
+            return Ok(());
+        }
+    };
+    
+    let hide_votes = session.hides_votes();
+    let mut message_text = format!(
+        "Session Stats: {} ({})\n\nStatus: {}\n",
+        session.title,
+        session.display_ref(),
+        status_label(&session.status)
+    );
+    
+    // Deadline and time remaining
+    match session.deadline.as_deref().map(chrono::DateTime::parse_from_rfc3339) {
+        Some(Ok(deadline)) => {
+            let deadline = deadline.with_timezone(&Utc);
+            message_text.push_str(&format!(
+                "⏰ Deadline: {} ({})\n",
+                format_datetime(&deadline),
+                format_time_remaining(deadline - Utc::now())
+            ));
+        }
+        Some(Err(_)) => message_text.push_str("⏰ Deadline: set, but could not be read\n"),
+        None => message_text.push_str("⏰ Deadline: not set\n"),
+    }
+    
+    // Leading option by "yes" votes, ties go to the earlier option
+    if !hide_votes {
+        let mut leader: Option<(&SessionOption, usize)> = None;
+        for option in &options {
+            let yes_count = responses.iter()
+                .filter(|r| r.option_id == option.id && r.response == "yes")
+                .count();
+            if yes_count > leader.map_or(0, |(_, count)| count) {
+                leader = Some((option, yes_count));
+            }
+        }
+        match leader {
+            Some((option, yes_count)) => message_text.push_str(&format!(
+                "🏆 Leading option: {} ({} yes)\n",
+                format_option_datetime(option),
+                yes_count
+            )),
+            None => message_text.push_str("🏆 Leading option: no \"yes\" votes yet\n"),
+        }
+    }
+    
+    let session_voters: HashSet<i64> = responses.iter().map(|r| r.user_id).collect();
+    let members: HashSet<i64> = members.into_iter().map(|member| member.user_id).collect();
+    let missing = members.difference(&session_voters).count();
+    message_text.push_str(&format!(
+        "👥 {} voted, {} of {} group members haven't voted yet\n\n",
+        session_voters.len(),
+        missing,
+        members.len()
+    ));
+    
+    // Option-by-option breakdown
+    message_text.push_str("📅 Options:\n");
+    for (i, option) in options.iter().enumerate() {
+        let confirmed_marker = if option.confirmed { " ✅ confirmed" } else { "" };
+        message_text.push_str(&format!("{}. {}{}\n", i + 1, format_option_datetime(option), confirmed_marker));
+        
+        if hide_votes {
+            message_text.push_str("   🙈 votes hidden until the session is confirmed\n");
+            continue;
+        }
+        
+        for (emoji, kind) in [("✅", "yes"), ("❌", "no"), ("❓", "maybe")] {
+            let voters: Vec<String> = responses.iter()
+                .filter(|r| r.option_id == option.id && r.response == kind)
+                .map(|r| voter_name(&names, r))
+                .collect();
+            let voters = if voters.is_empty() { "—".to_string() } else { voters.join(", ") };
+            message_text.push_str(&format!("   {} {}: {}\n", emoji, voters_count_label(kind), voters));
+        }
+    }
+    
+    feedback.update_message(processing_msg.id, crate::utils::feedback::FeedbackType::Success, &message_text).await?;
+    
+    Ok(())
+}
+
+/// How many players the "Longest streaks" section of `/stats` shows
+const STREAK_LEADERS: usize = 5;
+
+/// How many players the "Reliability" section of `/stats` shows
+const RELIABILITY_ROWS: usize = 10;
+
+/// Each player's current attendance streak: how many of the group's most recent confirmed
+/// sessions that started before `now` they said "yes" to, without a gap
+///
+/// Longest streaks first, ties by user ID; players without a streak are left out.
+pub fn attendance_streaks(attendance: &[ConfirmedAttendance], now: DateTime<Utc>) -> Vec<(i64, usize)> {
+    let mut yes_voters: HashMap<&str, HashSet<i64>> = HashMap::new();
+    let mut sessions: Vec<(DateTime<Utc>, &str)> = Vec::new();
+    for row in attendance {
+        // Option times keep the offset they were parsed in, so order by instant, not text
+        let Ok(starts_at) = DateTime::parse_from_rfc3339(&row.starts_at) else {
+            continue;
+        };
+        let voters = yes_voters.entry(row.session_id.as_str()).or_insert_with(|| {
+            sessions.push((starts_at.with_timezone(&Utc), row.session_id.as_str()));
+            HashSet::new()
+        });
+        if let (Some(user_id), Some("yes")) = (row.user_id, row.response.as_deref()) {
+            voters.insert(user_id);
+        }
+    }
+    sessions.retain(|(starts_at, _)| *starts_at <= now);
+    sessions.sort_by(|a, b| b.cmp(a));
+    
+    let players: HashSet<i64> = yes_voters.values().flatten().copied().collect();
+    let mut streaks: Vec<(i64, usize)> = players.into_iter()
+        .map(|user_id| {
+            let streak = sessions.iter()
+                .take_while(|(_, session_id)| yes_voters[session_id].contains(&user_id))
+                .count();
+            (user_id, streak)
+        })
+        .filter(|&(_, streak)| streak > 0)
+        .collect();
+    streaks.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    streaks
+}
+
+/// Names to show for `voters`, preferring the name each player goes by now (from their
+/// profile) over the one they voted under; failures are logged and leave names out
+async fn current_names(
+    pool: &sqlx::SqlitePool,
+    group: &Group,
+    voters: &[(i64, Option<&str>)],
+) -> HashMap<i64, String> {
+    let user_ids: Vec<i64> = voters.iter().map(|&(user_id, _)| user_id).collect();
+    let profiles = UserProfile::find_by_ids(pool, &user_ids).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load user profiles for group {}: {}", group.id, e);
+        HashMap::new()
+    });
+    let voters: Vec<_> = voters.iter()
+        .map(|&(user_id, username)| (user_id, profiles.get(&user_id).map(UserProfile::name).or(username)))
+        .collect();
+    display_names(pool, group, &voters).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to resolve participant names for group {}: {}", group.id, e);
+        HashMap::new()
+    })
+}
+
+/// What an award's count stands for, e.g. "12 sessions confirmed"
+fn badge_count_label(award: &Award) -> String {
+    let plural = if award.count == 1 { "" } else { "s" };
+    match award.badge {
+        Badge::FullHouse => format!("{} session{plural} everyone said yes to", award.count),
+        Badge::IronDm => format!("{} session{plural} confirmed", award.count),
+        Badge::EarlyBird => format!("first vote on {} poll{plural}", award.count),
+    }
+}
+
+/// A session status with its emoji, e.g. "✅ Confirmed"
+pub fn status_label(status: &str) -> &'static str {
+    match status {
+        "active" => "🟢 Active",
+        "confirmed" => "✅ Confirmed",
+        "cancelled" => "❌ Cancelled",
+        "closed" => "🔒 Closed",
+        "archived" => "📦 Archived",
+        _ => "⚪ Unknown"
+    }
+}
+
+fn voters_count_label(kind: &str) -> &'static str {
+    match kind {
+        "yes" => "Yes",
+        "no" => "No",
+        _ => "Maybe",
+    }
+}
+
+fn voter_name(names: &HashMap<i64, String>, response: &Response) -> String {
+    names.get(&response.user_id).cloned().unwrap_or_else(|| format!("user {}", response.user_id))
+}
+
+fn format_option_datetime(option: &SessionOption) -> String {
+    chrono::DateTime::parse_from_rfc3339(&option.datetime)
+        .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
+        .unwrap_or_else(|_| option.datetime.clone())
+}
+
+fn format_time_remaining(remaining: chrono::Duration) -> String {
+    if remaining <= chrono::Duration::zero() {
+        return "passed".to_string();
+    }
+    
+    let days = remaining.num_days();
+    let hours = remaining.num_hours() % 24;
+    let minutes = remaining.num_minutes() % 60;
+    
+    if days > 0 {
+        format!("{days}d {hours}h remaining")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m remaining")
+    } else {
+        format!("{minutes}m remaining")
+    }
+}
+
 struct DetailedStats {
     total_sessions: i32,
     active_sessions: i32,
     confirmed_sessions: i32,
     cancelled_sessions: i32,
+    archived_sessions: i32,
     total_responses: i32,
     yes_responses: i32,
     no_responses: i32,
     maybe_responses: i32,
-    user_participation: HashMap<Option<String>, i32>,
+    /// Responses per user, most active first
+    user_participation: Vec<Participation>,
+    /// Current attendance streaks, longest first
+    streaks: Vec<(i64, usize)>,
+    /// Attended / said yes per player, most reliable first
+    reliability: Vec<Reliability>,
+    /// How many of the group's members vote on its polls
+    turnout: Turnout,
+    /// Badges earned, empty when the group turned them off
+    badges: Vec<Award>,
     most_recent_session: Option<Session>,
 }
 
 async fn get_detailed_stats(
     pool: &sqlx::SqlitePool,
-    group_id: i64,
+    group: &Group,
 ) -> Result<DetailedStats, sqlx::Error> {
+    let group_id = group.id;
     // Get session counts by status
     let session_counts = sqlx::query!(
         r#"
@@ -154,7 +515,8 @@ async fn get_detailed_stats(
             COUNT(*) as total,
             COALESCE(SUM(CASE WHEN status = 'active' THEN 1 ELSE 0 END), 0) as active,
             COALESCE(SUM(CASE WHEN status = 'confirmed' THEN 1 ELSE 0 END), 0) as confirmed,
-            COALESCE(SUM(CASE WHEN status = 'cancelled' THEN 1 ELSE 0 END), 0) as cancelled
+            COALESCE(SUM(CASE WHEN status = 'cancelled' THEN 1 ELSE 0 END), 0) as cancelled,
+            COALESCE(SUM(CASE WHEN status = 'archived' THEN 1 ELSE 0 END), 0) as archived
         FROM sessions 
         WHERE group_id = ?
         "#,
@@ -180,33 +542,31 @@ async fn get_detailed_stats(
     .fetch_one(pool)
     .await?;
     
-    // Get user participation
-    let user_responses = sqlx::query!(
-        r#"
-        SELECT r.username, CAST(COUNT(*) AS INTEGER) as response_count
-        FROM responses r
-        JOIN sessions s ON r.session_id = s.id
-        WHERE s.group_id = ?
-        GROUP BY r.username
-        "#,
-        group_id
-    )
-    .fetch_all(pool)
-    .await?;
+    // Keyed on user ID, so a renamed player still counts once
+    let user_participation = Response::participation_by_group(pool, group_id).await?;
     
-    let mut user_participation = HashMap::new();
-    for row in user_responses {
-        user_participation.insert(row.username, row.response_count as i32);
-    }
+    let attendance = Response::find_confirmed_attendance(pool, group_id).await?;
+    let streaks = attendance_streaks(&attendance, Utc::now());
+    let reliability = Attendance::reliability_by_group(pool, group_id).await?;
+    let turnout = GroupMember::turnout(pool, group_id).await?;
+    let badges = if group.badges {
+        award_badges(&BadgeTallies {
+            confirmed_turnout: Response::confirmed_turnout(pool, group_id).await?,
+            confirmed_by_creator: Session::count_confirmed_by_creator(pool, group_id).await?,
+            first_votes: Response::count_first_votes(pool, group_id).await?,
+        })
+    } else {
+        Vec::new()
+    };
     
     // Get most recent session
-    let most_recent_session = sqlx::query_as::<_, Session>(
-        "SELECT id, group_id, title, message_id, status, deadline, created_by, created_at 
+    let most_recent_session = sqlx::query_as::<_, Session>(&format!(
+        "SELECT {SESSION_COLUMNS} 
          FROM sessions 
          WHERE group_id = ? 
          ORDER BY created_at DESC 
          LIMIT 1"
-    )
+    ))
     .bind(group_id)
     .fetch_optional(pool)
     .await?;
@@ -216,11 +576,16 @@ async fn get_detailed_stats(
         active_sessions: session_counts.active.unwrap_or(0) as i32,
         confirmed_sessions: session_counts.confirmed.unwrap_or(0) as i32,
         cancelled_sessions: session_counts.cancelled.unwrap_or(0) as i32,
+        archived_sessions: session_counts.archived.unwrap_or(0) as i32,
         total_responses: response_counts.total as i32,
         yes_responses: response_counts.yes_count.unwrap_or(0) as i32,
         no_responses: response_counts.no_count.unwrap_or(0) as i32,
         maybe_responses: response_counts.maybe_count.unwrap_or(0) as i32,
         user_participation,
+        streaks,
+        reliability,
+        turnout,
+        badges,
         most_recent_session,
     })
 }
@@ -0,0 +1,186 @@
+//! `/undo` and the "↩️ Undo" button: reverse the group's last cancel, close or deadline change
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use crate::bot::poll::refresh_poll_message;
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::utils::feedback::CommandFeedback;
+
+/// Record `action` so it can be undone, logging instead of failing the command if that doesn't work
+pub(crate) async fn record_undo(
+    db: &DatabaseManager,
+    group_id: i64,
+    user_id: i64,
+    action: &UndoableAction,
+) -> Option<UndoRecord> {
+    match UndoRecord::record(&db.pool, group_id, user_id, action).await {
+        Ok(record) => Some(record),
+        Err(e) => {
+            tracing::warn!("Failed to record undo information for group {}: {}", group_id, e);
+            None
+        }
+    }
+}
+
+/// Keyboard with a single "↩️ Undo" button for `record`
+pub fn undo_keyboard(record: &UndoRecord) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("↩️ Undo", format!("undo:{}", record.id)),
+    ]])
+}
+
+/// Undo the group's last reversible action, if the caller performed it
+pub async fn handle_undo(
+    bot: Bot,
+    msg: Message,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    let chat_id = msg.chat.id.0;
+    let user_id = msg.from().map(|u| u.id.0 as i64).unwrap_or(0);
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    let group = match Group::find_by_chat_id(&db.pool, chat_id).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            feedback.info("Nothing to undo").await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            feedback.error("Failed to retrieve group information from database").await?;
+            return Ok(());
+        }
+    };
+
+    let record = match UndoRecord::find_latest(&db.pool, group.id).await {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            feedback.info(&format!(
+                "Nothing to undo. Cancelling, closing and deadline changes can be undone for {UNDO_WINDOW_MINUTES} minutes."
+            )).await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find undo record for group {}: {}", group.id, e);
+            feedback.error("Failed to retrieve the last action from database").await?;
+            return Ok(());
+        }
+    };
+
+    if record.user_id != user_id {
+        let error_msg = "Permission denied: Only the person who performed the last action can undo it";
+        let suggestion = "Ask them to run /undo, or tap the Undo button under their message.";
+        feedback.validation_error(error_msg, suggestion).await?;
+        return Ok(());
+    }
+
+    match apply_undo(&bot, db, &record, msg.chat.id).await {
+        Ok(summary) => {
+            feedback.success(&summary).await?;
+        }
+        Err(e) => {
+            tracing::error!("Failed to undo action {}: {}", record.id, e);
+            feedback.error("Failed to undo the last action").await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle an `undo:<record_id>` button press
+pub async fn handle_undo_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> ResponseResult<()> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("This can no longer be undone").await?;
+        return Ok(());
+    };
+    let record_id = data.strip_prefix("undo:").unwrap_or(&data);
+
+    // Only actions of the group the button was pressed in can be undone from it
+    let group = match Group::find_by_chat_id(&db.pool, message.chat.id.0).await {
+        Ok(group) => group,
+        Err(e) => {
+            tracing::error!("Failed to find group: {}", e);
+            bot.answer_callback_query(q.id).text("Failed to load the action to undo").await?;
+            return Ok(());
+        }
+    };
+    let found = match &group {
+        Some(group) => UndoRecord::find_by_id(&db.pool, group.id, record_id).await,
+        None => Ok(None),
+    };
+
+    let record = match found {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            bot.answer_callback_query(q.id)
+                .text(format!("This can no longer be undone (actions can be undone for {UNDO_WINDOW_MINUTES} minutes)"))
+                .await?;
+            bot.edit_message_reply_markup(message.chat.id, message.id).await?;
+            return Ok(());
+        }
+        Err(e) => {
+            tracing::error!("Failed to find undo record {}: {}", record_id, e);
+            bot.answer_callback_query(q.id).text("Failed to load the action to undo").await?;
+            return Ok(());
+        }
+    };
+
+    if record.user_id != q.from.id.0 as i64 {
+        bot.answer_callback_query(q.id)
+            .text("Only the person who performed this action can undo it")
+            .await?;
+        return Ok(());
+    }
+
+    match apply_undo(&bot, db, &record, message.chat.id).await {
+        Ok(summary) => {
+            bot.answer_callback_query(q.id).text("↩️ Undone").await?;
+            bot.edit_message_reply_markup(message.chat.id, message.id).await?;
+            CommandFeedback::new(bot.clone(), message.chat.id).success(&summary).await?;
+        }
+        Err(e) => {
+            tracing::error!("Failed to undo action {}: {}", record.id, e);
+            bot.answer_callback_query(q.id).text("Failed to undo this action").await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reverse `record`, refresh the affected poll messages and describe the outcome
+async fn apply_undo(
+    bot: &Bot,
+    db: &DatabaseManager,
+    record: &UndoRecord,
+    chat_id: ChatId,
+) -> Result<String, sqlx::Error> {
+    let description = record.action()
+        .map(|action| action.describe())
+        .unwrap_or_else(|| "last action".to_string());
+    let restored = record.undo(&db.pool).await?;
+    tracing::info!("Undid {} ({}) in group {}, restored {} sessions", record.kind, record.id, record.group_id, restored.len());
+
+    // Bring back the voting buttons on polls that are active again
+    for session_id in &restored {
+        match Session::find_by_id(&db.pool, session_id).await {
+            Ok(Some(session)) => {
+                if let Err(e) = refresh_poll_message(bot, db, &session, chat_id).await {
+                    tracing::warn!("Failed to refresh poll message for session {}: {}", session_id, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to reload session {}: {}", session_id, e),
+        }
+    }
+
+    if restored.is_empty() {
+        Ok(format!("Nothing to restore for the {description}: the sessions have changed since"))
+    } else {
+        Ok(format!("Undid the {description}"))
+    }
+}
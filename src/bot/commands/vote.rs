@@ -0,0 +1,118 @@
+//! `/vote`: answer a poll by typing, for when the buttons are hard to tap or to reach with a
+//! screen reader
+
+use std::time::Instant;
+use teloxide::prelude::*;
+use crate::bot::commands::session_management::resolve_session;
+use crate::bot::poll::{refresh_poll_message, vote_throttle};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::scheduling::{SchedulerApi, SchedulingError};
+use crate::services::metrics::metrics;
+use crate::utils::{datetime::format_datetime, feedback::CommandFeedback, validation::validate_response_type};
+
+const VOTE_USAGE: &str = "Use /vote <session_id> <option_number> <yes|no|maybe>, e.g. /vote #12 2 yes";
+
+/// The arguments of `/vote`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteArgs {
+    /// The session as typed, e.g. "#12"
+    pub session_id: String,
+    /// The option's number on the poll, starting at 1
+    pub option: usize,
+    /// The answer as typed, lowercased; checked by the handler
+    pub response: String,
+}
+
+/// Parse the arguments of `/vote`, e.g. "#12 2 yes"
+pub fn parse_vote_args(args: &str) -> Result<VoteArgs, String> {
+    let mut parts = args.split_whitespace();
+    let (Some(session_id), Some(option), Some(response)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err("Expected a session, an option number and yes, no or maybe".to_string());
+    };
+    let option = option.trim_start_matches('#').parse::<usize>().ok().filter(|&n| n > 0)
+        .ok_or_else(|| format!("'{option}' isn't an option number like 2"))?;
+    if parts.next().is_some() {
+        return Err("Too many arguments".to_string());
+    }
+    Ok(VoteArgs { session_id: session_id.to_string(), option, response: response.to_lowercase() })
+}
+
+/// Record the sender's answer to one option of a session and update its poll
+pub async fn handle_vote(
+    bot: Bot,
+    msg: Message,
+    args: VoteArgs,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+
+    if let Err(e) = validate_response_type(&args.response) {
+        feedback.validation_error(&e.to_string(), VOTE_USAGE).await?;
+        return Ok(());
+    }
+
+    let (_, session) = match resolve_session(&feedback, db, msg.chat.id.0, &args.session_id).await? {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    let Some(option) = args.option.checked_sub(1).and_then(|i| options.get(i)) else {
+        let error = match options.len() {
+            1 => format!("Session {} has only 1 option", session.display_ref()),
+            count => format!("Session {} has {count} options", session.display_ref()),
+        };
+        let suggestion = format!("Pick an option number from 1 to {}, as numbered on the poll.", options.len());
+        feedback.validation_error(&error, &suggestion).await?;
+        return Ok(());
+    };
+
+    let Some(user) = msg.from() else {
+        return Ok(());
+    };
+    let user_id = user.id.0 as i64;
+    // Shares the cooldown with the buttons, so typing and tapping can't be mixed to go faster
+    if !vote_throttle().try_acquire(user_id, &session.id, Instant::now()) {
+        feedback.info("🐢 Slow down! Wait a moment before voting again").await?;
+        return Ok(());
+    }
+
+    let api = SchedulerApi::new(db.pool.clone());
+    match api.record_response(&session.id, &option.id, user_id, user.username.clone(), &args.response).await {
+        Ok(_) => {}
+        Err(e @ (SchedulingError::NotActive { .. } | SchedulingError::NotFound { .. } | SchedulingError::Invalid(_))) => {
+            feedback.validation_error(&format!("Your vote wasn't saved: {e}"), "Use /list to see the polls that are open.").await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    }
+    metrics().record_vote(&args.response);
+    tracing::info!("User {} voted {} on option {} of session {} by command", user_id, args.response, args.option, session.id);
+    if args.response == "yes" {
+        crate::bot::commands::rollcall::confirm_if_quorum(&bot, db, &session.id).await;
+    }
+
+    // The vote stands even if the poll can't be edited, e.g. after it was deleted
+    let note = match Session::find_by_id(&db.pool, &session.id).await? {
+        Some(session) => match refresh_poll_message(&bot, db, &session, msg.chat.id).await {
+            Ok(()) => "",
+            Err(e) => {
+                tracing::warn!("Failed to update the poll of session {} after a typed vote: {}", session.id, e);
+                "; the poll couldn't be updated, use /repost to show it again"
+            }
+        },
+        None => "",
+    };
+    let datetime = chrono::DateTime::parse_from_rfc3339(&option.datetime)
+        .map(|dt| format_datetime(&dt.with_timezone(&chrono::Utc)))
+        .unwrap_or_else(|_| option.datetime.clone());
+    let emoji = match args.response.as_str() {
+        "yes" => "✅",
+        "no" => "❌",
+        _ => "❓",
+    };
+    feedback.success(&format!("{emoji} Marked as {} for option {} ({datetime}){note}", args.response, args.option)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Notes voters leave on an option of a poll with its 📝 button, like "only after 20:00"
+//!
+//! Pressing the button makes the bot wait for the presser's next message in the chat (see
+//! [`DialogueState::AwaitingNote`]); that message becomes the note, shown in /list and
+//! /sessioninfo under the option.
+
+use teloxide::prelude::*;
+use teloxide::types::ForceReply;
+use chrono::Utc;
+use crate::bot::dialogue::{exit, BotDialogue, DialogueState, DialogueStorage};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::scheduling::VOTABLE_STATUSES;
+use crate::utils::{datetime::format_datetime, feedback::CommandFeedback};
+
+const NOTE_HINT: &str = "Send \"clear\" to remove your note, or \"cancel\" to leave it as it is.";
+
+/// Handle a "note:<option_id>" press by asking the presser for their note on that option
+pub async fn handle_note_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    data: String,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let Some(message) = q.message.clone() else {
+        bot.answer_callback_query(q.id).text("Open the poll in its group to leave a note").await?;
+        return Ok(());
+    };
+    let option_id = data.strip_prefix("note:").unwrap_or_default();
+    let Some(option) = SessionOption::find_by_id(&db.pool, option_id).await? else {
+        bot.answer_callback_query(q.id).text("This option no longer exists").await?;
+        return Ok(());
+    };
+    let session = Session::find_by_id(&db.pool, &option.session_id).await?;
+    let Some(session) = session.filter(|session| VOTABLE_STATUSES.contains(&session.status.as_str())) else {
+        bot.answer_callback_query(q.id).text("This poll is closed").await?;
+        return Ok(());
+    };
+
+    // The storage is the database, so this is the same dialogue the dispatcher hands to the reply
+    let user_id = q.from.id.0 as i64;
+    let dialogue = BotDialogue::new(DialogueStorage::new(db.pool.clone()), message.chat.id);
+    let state = DialogueState::AwaitingNote { session_id: session.id.clone(), option_id: option.id.clone(), user_id };
+    if let Err(e) = dialogue.update(state).await {
+        tracing::error!("Failed to wait for a note on option {} of session {}: {}", option.id, session.id, e);
+        bot.answer_callback_query(q.id).text("Failed to start the note").await?;
+        return Ok(());
+    }
+    bot.answer_callback_query(q.id).await?;
+
+    let when = chrono::DateTime::parse_from_rfc3339(&option.datetime)
+        .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
+        .unwrap_or_else(|_| option.datetime.clone());
+    let text = format!(
+        "📝 {}, what's your note on {when} for '{}'? Up to {NOTE_MAX_CHARS} characters. {NOTE_HINT}",
+        q.from.first_name, session.title
+    );
+    // A forced reply reaches the bot even when it can't read the rest of the chat
+    bot.send_message(message.chat.id, text)
+        .reply_markup(ForceReply::new())
+        .await?;
+
+    Ok(())
+}
+
+/// Handle the reply to [`handle_note_callback`]: save, replace or clear the note
+pub async fn handle_note_reply(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    state: DialogueState,
+    db: &DatabaseManager,
+) -> Result<(), BotError> {
+    let DialogueState::AwaitingNote { session_id, option_id, user_id } = state else {
+        return Ok(());
+    };
+    let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
+    let text = msg.text().unwrap_or_default().trim();
+
+    if text.eq_ignore_ascii_case("cancel") {
+        exit(&dialogue).await;
+        feedback.info("Okay, your note stays as it was").await?;
+        return Ok(());
+    }
+    if text.eq_ignore_ascii_case("clear") {
+        exit(&dialogue).await;
+        if ResponseNote::clear(&db.pool, &option_id, user_id).await? {
+            feedback.success("📝 Note removed").await?;
+        } else {
+            feedback.info("You had no note on that option").await?;
+        }
+        return Ok(());
+    }
+    let note = match clean_note(text) {
+        Ok(note) => note,
+        Err(e) => {
+            feedback.validation_error(&e, NOTE_HINT).await?;
+            return Ok(());
+        }
+    };
+
+    exit(&dialogue).await;
+    // The option may have gone while we waited, e.g. with the whole session
+    if SessionOption::find_by_id(&db.pool, &option_id).await?.is_none() {
+        feedback.validation_error("The option no longer exists", "Leave a note on the current poll instead.").await?;
+        return Ok(());
+    }
+    ResponseNote::set(&db.pool, &session_id, &option_id, user_id, &note).await?;
+    tracing::info!("User {} left a note on option {} of session {}", user_id, option_id, session_id);
+    feedback.success("📝 Note saved; it shows under the option in /list and /sessioninfo").await?;
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! Questions the bot asked in a chat and is waiting for a reply to
+//!
+//! The state is kept per chat in the `dialogue_state` table as JSON, so a question asked right
+//! before a restart can still be answered after it.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use teloxide::dispatching::dialogue::{Dialogue, Storage};
+use teloxide::types::ChatId;
+
+/// What the bot is waiting for in a chat
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DialogueState {
+    /// Nothing; messages are handled as usual
+    #[default]
+    Idle,
+    /// The start time of the day an availability poll is being confirmed for
+    AwaitingStartTime {
+        /// The session being confirmed
+        session_id: String,
+        /// The day's option
+        option_id: String,
+        /// Who ran `/confirm`; only their reply counts
+        user_id: i64,
+    },
+    /// A new value for a group setting, after its button in `/settings` was pressed
+    AwaitingSetting {
+        /// The setting being changed
+        setting: SettingField,
+        /// Who pressed the button; only their reply counts
+        user_id: i64,
+        /// The settings message, shown again with the new value
+        message_id: i32,
+    },
+    /// A voter's note on an option of a poll, after they pressed its 📝 button
+    AwaitingNote {
+        /// The poll's session
+        session_id: String,
+        /// The option the note is about
+        option_id: String,
+        /// Who pressed the button; only their reply counts
+        user_id: i64,
+    },
+}
+
+/// A group setting that is changed by typing its new value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SettingField {
+    /// The group's UTC offset
+    Timezone,
+    /// The default duration of new session options
+    DefaultDuration,
+}
+
+impl DialogueState {
+    /// Whether a message from `user_id` is the reply this state waits for
+    pub fn awaits_reply_from(&self, user_id: i64) -> bool {
+        match self {
+            DialogueState::Idle => false,
+            DialogueState::AwaitingStartTime { user_id: asked, .. }
+            | DialogueState::AwaitingSetting { user_id: asked, .. }
+            | DialogueState::AwaitingNote { user_id: asked, .. } => *asked == user_id,
+        }
+    }
+}
+
+type StorageFuture<T> = Pin<Box<dyn Future<Output = Result<T, sqlx::Error>> + Send>>;
+
+/// Dialogue storage in the bot's own database, one JSON row per chat
+pub struct SqliteStorage {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    /// Storage using the `dialogue_state` table of `pool`
+    pub fn new(pool: SqlitePool) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl<D> Storage<D> for SqliteStorage
+where
+    D: Serialize + DeserializeOwned + Send + 'static,
+{
+    type Error = sqlx::Error;
+
+    fn remove_dialogue(self: Arc<Self>, chat_id: ChatId) -> StorageFuture<()> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM dialogue_state WHERE chat_id = ?")
+                .bind(chat_id.0)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn update_dialogue(self: Arc<Self>, chat_id: ChatId, dialogue: D) -> StorageFuture<()> {
+        Box::pin(async move {
+            let state = serde_json::to_string(&dialogue).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+            sqlx::query(
+                "INSERT INTO dialogue_state (chat_id, state, updated_at) VALUES (?, ?, ?) \
+                 ON CONFLICT(chat_id) DO UPDATE SET state = excluded.state, updated_at = excluded.updated_at"
+            )
+            .bind(chat_id.0)
+            .bind(state)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn get_dialogue(self: Arc<Self>, chat_id: ChatId) -> StorageFuture<Option<D>> {
+        Box::pin(async move {
+            let row = sqlx::query("SELECT state FROM dialogue_state WHERE chat_id = ?")
+                .bind(chat_id.0)
+                .fetch_optional(&self.pool)
+                .await?;
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            let state: String = row.try_get("state")?;
+            // A state written by an older version that no longer parses is as good as none
+            match serde_json::from_str(&state) {
+                Ok(dialogue) => Ok(Some(dialogue)),
+                Err(e) => {
+                    tracing::warn!("Dropping unreadable dialogue state of chat {}: {}", chat_id, e);
+                    Ok(None)
+                }
+            }
+        })
+    }
+}
+
+/// Where the dispatcher keeps each chat's [`DialogueState`]
+pub type DialogueStorage = SqliteStorage;
+
+/// A chat's [`DialogueState`], as handed to handlers
+pub type BotDialogue = Dialogue<DialogueState, DialogueStorage>;
+
+/// Stop waiting for a reply; a chat that was waiting for nothing is fine too
+pub async fn exit(dialogue: &BotDialogue) {
+    if let Err(e) = dialogue.exit().await {
+        tracing::debug!("Failed to reset the dialogue of chat {}: {}", dialogue.chat_id(), e);
+    }
+}
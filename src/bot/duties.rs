@@ -0,0 +1,136 @@
+//! Who gets which duty when a session is confirmed: a round robin among its yes voters
+//!
+//! Each duty goes to the voter whose turn at it is longest ago, so players who miss a session
+//! keep their place in line instead of being skipped for good.
+
+use crate::database::models::*;
+
+/// Maximum length of a duty name, in characters
+pub const MAX_DUTY_NAME_LENGTH: usize = 32;
+
+/// Hand each of `duties` to one of `voters`, given the group's earlier assignments, oldest first
+///
+/// Every duty goes to whoever has had it least recently, never being first in line. Nobody gets
+/// a second duty while another voter has none, and remaining ties go to the lowest user ID, so
+/// the same inputs always give the same result. Returns `(duty_id, user_id)` pairs in the
+/// order of `duties`, or nothing if there are no voters.
+pub fn assign_duties(duties: &[Duty], voters: &[i64], history: &[DutyAssignment]) -> Vec<(i64, i64)> {
+    let mut voters = voters.to_vec();
+    voters.sort_unstable();
+    voters.dedup();
+    if voters.is_empty() {
+        return Vec::new();
+    }
+
+    let mut assigned: Vec<(i64, i64)> = Vec::new();
+    for duty in duties {
+        let turn = |user_id: i64| {
+            let duties_now = assigned.iter().filter(|(_, holder)| *holder == user_id).count();
+            let last_turn = history.iter().rposition(|a| a.duty_id == duty.id && a.user_id == user_id);
+            (duties_now, last_turn, user_id)
+        };
+        if let Some(user_id) = voters.iter().copied().min_by_key(|&user_id| turn(user_id)) {
+            assigned.push((duty.id, user_id));
+        }
+    }
+    assigned
+}
+
+/// A duty's name for display: capitalized, after an emoji that fits it
+///
+/// Names that start with their own emoji are shown as they are.
+pub fn duty_label(name: &str) -> String {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+    if !first.is_alphanumeric() {
+        return name.to_string();
+    }
+
+    let lower = name.to_lowercase();
+    let emoji = if lower.starts_with("host") {
+        "🏠"
+    } else if ["snack", "food", "pizza", "cook"].iter().any(|word| lower.contains(word)) {
+        "🍕"
+    } else if lower.contains("drink") {
+        "🥤"
+    } else if lower.contains("clean") {
+        "🧹"
+    } else {
+        "📌"
+    };
+    format!("{emoji} {}{}", first.to_uppercase(), chars.as_str())
+}
+
+/// "🏠 Hosting: alice, 🍕 Snacks: bob" for `assignments`, naming players with `name_of`
+///
+/// Assignments of duties not in `duties` are left out; `None` if nothing is left.
+pub fn render_duties(
+    duties: &[Duty],
+    assignments: &[DutyAssignment],
+    name_of: impl Fn(i64) -> String,
+) -> Option<String> {
+    let parts: Vec<String> = duties.iter()
+        .filter_map(|duty| {
+            let assignment = assignments.iter().find(|a| a.duty_id == duty.id)?;
+            Some(format!("{}: {}", duty_label(&duty.name), name_of(assignment.user_id)))
+        })
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Rotate the group's duties among the yes voters of `option_id`, the option a session was
+/// just confirmed for, and save who got them
+///
+/// Returns the duties line for the confirmation, `None` if the group has no duties.
+pub async fn assign_session_duties(
+    pool: &sqlx::SqlitePool,
+    group: &Group,
+    session_id: &str,
+    option_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let duties = Duty::find_by_group(pool, group.id).await?;
+    if duties.is_empty() {
+        return Ok(None);
+    }
+
+    let responses = Response::find_by_session(pool, session_id).await?;
+    let voters: Vec<i64> = responses.iter()
+        .filter(|r| r.option_id == option_id && r.response == "yes")
+        .map(|r| r.user_id)
+        .collect();
+    let history = DutyAssignment::find_by_group(pool, group.id).await?;
+    let assignments = assign_duties(&duties, &voters, &history);
+    DutyAssignment::save(pool, session_id, &assignments).await?;
+    tracing::info!("Assigned {} duties for session {}", assignments.len(), session_id);
+
+    session_duties_line(pool, group, session_id).await
+}
+
+/// Who has which duty for a session, e.g. "🏠 Hosting: alice", `None` if nobody has any
+pub async fn session_duties_line(
+    pool: &sqlx::SqlitePool,
+    group: &Group,
+    session_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let assignments = DutyAssignment::find_by_session(pool, session_id).await?;
+    if assignments.is_empty() {
+        return Ok(None);
+    }
+
+    let duties = Duty::find_by_group(pool, group.id).await?;
+    let responses = Response::find_by_session(pool, session_id).await?;
+    let holders: Vec<(i64, Option<&str>)> = assignments.iter()
+        .map(|a| {
+            let username = responses.iter()
+                .find(|r| r.user_id == a.user_id && r.username.is_some())
+                .and_then(|r| r.username.as_deref());
+            (a.user_id, username)
+        })
+        .collect();
+    let names = display_names(pool, group, &holders).await?;
+    Ok(render_duties(&duties, &assignments, |user_id| {
+        names.get(&user_id).cloned().unwrap_or_else(|| format!("User {user_id}"))
+    }))
+}
@@ -1,20 +1,108 @@
 use teloxide::prelude::*;
-use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode};
+use teloxide::types::{MessageId, ParseMode};
+use teloxide::{ApiError, RequestError};
+use crate::bot::commands::invite::is_group_member;
+use crate::bot::dialogue::SettingField;
+use crate::bot::handlers::report_error;
+use crate::error::BotError;
+use crate::services::metrics::{self, metrics, timed};
+use crate::bot::poll::{
+    expected_voters, mark_rendered, refresh_poll_message, render_poll, render_private_poll, repost_poll_message, repost_throttle,
+    vote_throttle,
+};
 use crate::database::connection::DatabaseManager;
 use crate::database::models::*;
-use crate::utils::{
-    datetime::format_datetime, 
-    markdown::escape_markdown,
-    validation::validate_response_type
-};
+use crate::scheduling::{SchedulerApi, SchedulingError};
+use crate::services::message_updater::{MessageUpdater, EDIT_INTERVAL};
+use crate::utils::outbound::{plain_fallback, DEFAULT_RETRY_POLICY};
 use chrono::Utc;
 use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long a callback query's ID is remembered; Telegram redelivers within minutes if at all
+pub const CALLBACK_DEDUP_WINDOW: Duration = Duration::from_secs(10 * 60);
 
+/// Most callback query IDs remembered at once; the oldest is forgotten to make room
+pub const CALLBACK_DEDUP_CAPACITY: usize = 10_000;
+
+/// Remembers the callback queries already handled, so one that Telegram delivers again after a
+/// network hiccup doesn't rewrite the vote (or flip the setting) and edit the message twice
+#[derive(Debug, Default)]
+pub struct SeenCallbacks {
+    seen: Mutex<HashMap<String, Instant>>,
+}
+
+impl SeenCallbacks {
+    /// Whether the callback query `query_id` arrives for the first time at `now`; if so, it's recorded
+    pub fn first_delivery(&self, query_id: &str, now: Instant) -> bool {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, at| now.duration_since(*at) < CALLBACK_DEDUP_WINDOW);
+        if seen.contains_key(query_id) {
+            return false;
+        }
+        if seen.len() >= CALLBACK_DEDUP_CAPACITY {
+            let oldest = seen.iter().min_by_key(|(_, at)| **at).map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                seen.remove(&oldest);
+            }
+        }
+        seen.insert(query_id.to_string(), now);
+        true
+    }
+}
+
+/// The process-wide record of handled callback queries
+pub fn seen_callbacks() -> &'static SeenCallbacks {
+    static SEEN: OnceLock<SeenCallbacks> = OnceLock::new();
+    SEEN.get_or_init(SeenCallbacks::default)
+}
+
+/// Dispatch endpoint for callback queries; failures are reported to the chat the button was in
+///
+/// A query already handled is ignored: the first delivery answered it.
 pub async fn callback_handler(
     bot: Bot,
     q: CallbackQuery,
     db: DatabaseManager,
 ) -> ResponseResult<()> {
+    if !seen_callbacks().first_delivery(&q.id, Instant::now()) {
+        tracing::debug!("Callback query {} was delivered again, ignoring it", q.id);
+        return Ok(());
+    }
+    let chat_id = q.message.as_ref().map(|m| m.chat.id);
+    let kind = q.data.as_deref().map_or("unknown", callback_kind);
+    let result = handle_callback(bot.clone(), q, db).await;
+    metrics().record_callback(kind, metrics::outcome(&result));
+    if let Err(e) = result {
+        report_error(&bot, chat_id, &e).await;
+    }
+    Ok(())
+}
+
+/// What a button press is for, by the prefix of its callback data; anything else is a vote
+pub fn callback_kind(data: &str) -> &'static str {
+    const PREFIXES: [(&str, &str); 9] = [
+        ("settings:", "settings"),
+        ("closeall:", "closeall"),
+        ("delete:", "delete"),
+        ("cancel:", "cancel"),
+        ("checkin:", "checkin"),
+        ("undo:", "undo"),
+        ("list:page:", "list_page"),
+        ("schedule_confirm:", "schedule_confirm"),
+        ("note:", "note"),
+    ];
+    PREFIXES.iter()
+        .find(|(prefix, _)| data.starts_with(prefix))
+        .map_or("vote", |(_, kind)| kind)
+}
+
+async fn handle_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    db: DatabaseManager,
+) -> Result<(), BotError> {
     let user_id = q.from.id.0;
     let username = q.from.username.as_ref().map_or("unknown", |v| v);
     let chat_id = q.message.as_ref().map(|m| m.chat.id.0).unwrap_or(0);
@@ -29,14 +117,51 @@ pub async fn callback_handler(
             return handle_settings_callback(bot, q, data, &db).await;
         }
         
+        // Handle bulk-close confirmation callbacks
+        if data.starts_with("closeall:") {
+            return crate::bot::commands::session_management::handle_closeall_callback(bot, q, data, &db).await;
+        }
+        
+        // Handle /delete confirmation callbacks
+        if data.starts_with("delete:") {
+            return crate::bot::commands::session_management::handle_delete_callback(bot, q, data, &db).await;
+        }
+        
+        // Handle the prompt before cancelling a confirmed session
+        if data.starts_with("cancel:") {
+            return crate::bot::commands::session_management::handle_cancel_callback(bot, q, data, &db).await;
+        }
+        
+        // Handle "📍 Check in" buttons on game day reminders
+        if data.starts_with("checkin:") {
+            return Ok(crate::bot::commands::checkins::handle_checkin_callback(bot, q, data, &db).await?);
+        }
+        
+        // Handle "↩️ Undo" buttons
+        if data.starts_with("undo:") {
+            return Ok(crate::bot::commands::undo::handle_undo_callback(bot, q, data, &db).await?);
+        }
+        
+        // Handle /list page navigation callbacks
+        if data.starts_with("list:page:") {
+            return crate::bot::commands::list::handle_list_page_callback(bot, q, data, &db).await;
+        }
+        
+        // Handle "Create anyway" / "Cancel" under a duplicate session warning
+        if data.starts_with("schedule_confirm:") {
+            return crate::bot::commands::schedule::handle_schedule_confirm_callback(bot, q, data, &db).await;
+        }
+        
+        // Handle "📝" buttons on polls
+        if data.starts_with("note:") {
+            return crate::bot::commands::voter_notes::handle_note_callback(bot, q, data, &db).await;
+        }
+        
         // Parse callback data: "session_id:option_id:response"
         // Validate the callback data format first
         let parts: Vec<&str> = data.split(':').collect();
         if parts.len() != 3 {
-            if let Some(msg) = q.message {
-                bot.send_message(msg.chat.id, "❌ Invalid callback data format").await?;
-            }
-            return Ok(());
+            return Err(BotError::Validation("Invalid callback data format".to_string()));
         }
         
         let session_id = parts[0];
@@ -47,61 +172,126 @@ pub async fn callback_handler(
         let user_id = user.id.0 as i64;
         let username = user.username.clone();
         
-        // Validate response type
-        if let Err(e) = validate_response_type(response) {
-            if let Some(msg) = q.message {
-                bot.send_message(msg.chat.id, format!("❌ Invalid response: {e}")).await?;
-            }
+        // Validate session_id and option_id are not empty
+        if session_id.is_empty() || option_id.is_empty() {
+            return Err(BotError::Validation("Invalid session or option ID".to_string()));
+        }
+        
+        // Each vote rewrites the response and edits the poll, so rapid taps are turned away
+        if !vote_throttle().try_acquire(user_id, session_id, Instant::now()) {
+            tracing::debug!("User {} is voting on session {} too fast, ignoring a vote", user_id, session_id);
+            bot.answer_callback_query(q.id)
+                .text("🐢 Slow down! Wait a moment before voting again")
+                .await?;
             return Ok(());
         }
         
-        // Validate session_id and option_id are not empty
-        if session_id.is_empty() || option_id.is_empty() {
-            if let Some(msg) = q.message {
-                bot.send_message(msg.chat.id, "❌ Invalid session or option ID").await?;
+        // Poll messages outlive their sessions after /delete or cleanup; a tap on one writes nothing
+        let Some(session) = timed("poll_session", Session::find_by_id(&db.pool, session_id)).await? else {
+            return close_deleted_poll(&bot, &q, session_id).await;
+        };
+        
+        // A tap on a poll that no longer shows the session as it is may not mean what it showed
+        let on_group_poll = q.message.as_ref().is_some_and(|m| Some(m.id.0 as i64) == session.message_id);
+        if on_group_poll && session.status == "active" && session.poll_is_outdated(Utc::now()) {
+            if let Some(message) = &q.message {
+                match refresh_poll_message(&bot, &db, &session, message.chat.id).await {
+                    Ok(()) => {
+                        tracing::info!("Poll of session {} was out of date, refreshed it instead of taking a vote", session_id);
+                        bot.answer_callback_query(q.id)
+                            .text("🔄 This poll was out of date, so I refreshed it. Check the options and vote again")
+                            .await?;
+                        return Ok(());
+                    }
+                    // The vote still counts; the edit after it has another go
+                    Err(e) => tracing::warn!("Failed to refresh the out of date poll of session {}: {}", session_id, e),
+                }
             }
-            return Ok(());
         }
         
-        // Additional validation can be added here for session existence
+        // Only /start checked the membership of a private copy's voter, who may have left since
+        if q.message.as_ref().is_some_and(|m| m.chat.is_private()) {
+            let is_member = match Group::find_by_id(&db.pool, session.group_id).await? {
+                Some(group) => is_group_member(&bot, &group, q.from.id).await,
+                None => false,
+            };
+            if !is_member {
+                tracing::info!("User {} voted on a private copy of session {} outside its group", user_id, session_id);
+                bot.answer_callback_query(q.id)
+                    .text("Only members of the group can vote on this session")
+                    .await?;
+                return Ok(());
+            }
+        }
         
-        // Update response in database
-        let _response_record = match Response::upsert(
-            &db.pool,
-            session_id.to_string(),
-            option_id.to_string(),
-            user_id,
-            username,
-            response.to_string(),
-        ).await {
-            Ok(r) => r,
-            Err(e) => {
+        // Update response in database; the session must exist and still take votes
+        let api = SchedulerApi::new(db.pool.clone());
+        match timed("vote_upsert", api.record_response(session_id, option_id, user_id, username, response)).await {
+            Ok(_) => {
+                metrics().record_vote(response);
+                // A roll call confirms itself with the yes that completes its quorum
+                if response == "yes" {
+                    crate::bot::commands::rollcall::confirm_if_quorum(&bot, &db, session_id).await;
+                }
+            }
+            Err(SchedulingError::Database(e)) => {
                 bot.answer_callback_query(q.id)
                     .text("Failed to save response")
                     .await?;
                 tracing::error!("Failed to save response: {}", e);
                 return Ok(());
             }
-        };
+            Err(e @ (SchedulingError::NotActive { .. } | SchedulingError::NotFound { .. })) => {
+                bot.answer_callback_query(q.id)
+                    .text(format!("Your vote wasn't saved: {e}"))
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
         
-        // Get session and update the message
-        match update_session_message(&bot, &db, session_id, &q).await {
-            Ok(_) => {
+        // A private copy from a vote link refreshes at once; the group's poll follows below
+        if let Some(message) = q.message.as_ref().filter(|m| m.chat.is_private()) {
+            if let Err(e) = refresh_private_poll(&bot, &db, session_id, message).await {
+                tracing::warn!("Failed to update the private poll of session {}: {}", session_id, e);
+            }
+        }
+        
+        // The vote is saved; the poll is re-rendered at most every few seconds during a burst
+        // The group's poll is never in a private chat, so its chat is looked up for those votes
+        let chat_id = q.message.as_ref().map(|m| m.chat.id).filter(|chat_id| !chat_id.is_user());
+        let update = poll_updater().request(session_id, {
+            let (bot, db, session_id) = (bot.clone(), db.clone(), session_id.to_string());
+            move || {
+                let (bot, db, session_id) = (bot.clone(), db.clone(), session_id.clone());
+                async move { update_session_message(&bot, &db, &session_id, chat_id).await }
+            }
+        });
+        match update.await {
+            Ok(Ok(update)) => {
                 let response_emoji = match response {
                     "yes" => "✅",
                     "no" => "❌", 
                     "maybe" => "❓",
                     _ => "👍"
                 };
+                let note = match update {
+                    PollUpdate::Edited => "",
+                    PollUpdate::Reposted => ". Your vote is saved; the poll was gone, so I posted it again",
+                    PollUpdate::Missing => ". Your vote is saved, but the poll is gone; use /repost to show it again",
+                };
                 bot.answer_callback_query(q.id)
-                    .text(format!("{response_emoji} Marked as {response}"))
+                    .text(format!("{response_emoji} Marked as {response}{note}"))
                     .await?;
             },
-            Err(e) => {
+            failed => {
                 bot.answer_callback_query(q.id)
                     .text("Response saved but couldn't update message")
                     .await?;
-                tracing::error!("Failed to update message: {}", e);
+                match failed {
+                    Ok(Err(e)) => tracing::error!("Failed to update message: {}", e),
+                    _ => tracing::error!("The update of session {}'s poll was dropped", session_id),
+                }
             }
         }
     } else {
@@ -113,85 +303,135 @@ pub async fn callback_handler(
     Ok(())
 }
 
+/// Tell the voter that the session behind a poll is gone, and take the buttons off its message
+async fn close_deleted_poll(bot: &Bot, q: &CallbackQuery, session_id: &str) -> Result<(), BotError> {
+    tracing::info!("Ignoring a vote on session {}, which no longer exists", session_id);
+    bot.answer_callback_query(q.id.clone())
+        .text("This poll no longer exists")
+        .await?;
+    if let Some(message) = &q.message {
+        if let Err(e) = bot.edit_message_reply_markup(message.chat.id, message.id).await {
+            tracing::debug!("Failed to remove the buttons of deleted session {}'s poll: {}", session_id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Re-render a private copy of a session's poll after a vote on it
+async fn refresh_private_poll(
+    bot: &Bot,
+    db: &DatabaseManager,
+    session_id: &str,
+    message: &Message,
+) -> Result<(), BotError> {
+    let session = Session::find_by_id(&db.pool, session_id)
+        .await?
+        .ok_or_else(|| BotError::NotFound { what: "Session".to_string() })?;
+    let options = SessionOption::find_by_session(&db.pool, session_id).await?;
+    let responses = Response::find_by_session(&db.pool, session_id).await?;
+    let expected = expected_voters(&db.pool, &session).await?;
+    let (message_text, keyboard) = render_private_poll(&session, &options, &responses, expected);
+    let edited = plain_fallback(
+        bot.edit_message_text(message.chat.id, message.id, message_text)
+            .reply_markup(keyboard)
+            .parse_mode(ParseMode::MarkdownV2)
+    ).await;
+    match edited {
+        // Tapping the answer already shown changes nothing
+        Ok(_) | Err(RequestError::Api(ApiError::MessageNotModified)) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// What happened to the poll message after a vote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PollUpdate {
+    /// The existing message now shows the vote
+    Edited,
+    /// The message was gone, so a fresh one was posted and tracked instead
+    Reposted,
+    /// The message was gone, but it was already reposted moments ago
+    Missing,
+}
+
+/// Post a fresh poll in place of a missing one, unless that already happened within
+/// `AUTO_REPOST_COOLDOWN`
+async fn repost_missing_poll(
+    bot: &Bot,
+    db: &DatabaseManager,
+    session: &Session,
+    chat_id: ChatId,
+) -> Result<PollUpdate, BotError> {
+    if !repost_throttle().try_acquire(&session.id, Instant::now()) {
+        tracing::info!("Poll of session {} was reposted moments ago, not posting another", session.id);
+        return Ok(PollUpdate::Missing);
+    }
+    repost_poll_message(bot, db, session, chat_id).await?;
+    Ok(PollUpdate::Reposted)
+}
+
+/// The poll edits after votes, coalesced per session
+fn poll_updater() -> &'static MessageUpdater<PollUpdate> {
+    static UPDATER: OnceLock<MessageUpdater<PollUpdate>> = OnceLock::new();
+    UPDATER.get_or_init(|| MessageUpdater::new(EDIT_INTERVAL, DEFAULT_RETRY_POLICY))
+}
+
+/// Re-render the session's poll message after a vote
+///
+/// `chat_id` is the chat the button was in, if known. If the poll message is gone (deleted, or
+/// never stored), a fresh one is posted and tracked instead.
 async fn update_session_message(
     bot: &Bot,
     db: &DatabaseManager,
     session_id: &str,
-    q: &CallbackQuery,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    chat_id: Option<ChatId>,
+) -> Result<PollUpdate, BotError> {
     // Get session details
-    let session = Session::find_by_id(&db.pool, session_id)
+    let session = timed("poll_session", Session::find_by_id(&db.pool, session_id))
         .await?
-        .ok_or("Session not found")?;
+        .ok_or_else(|| BotError::NotFound { what: "Session".to_string() })?;
     
     // Get session options
-    let session_options = SessionOption::find_by_session(&db.pool, session_id).await?;
+    let session_options = timed("poll_options", SessionOption::find_by_session(&db.pool, session_id)).await?;
     
     // Get all responses for this session
-    let responses = Response::find_by_session(&db.pool, session_id).await?;
+    let responses = timed("poll_responses", Response::find_by_session(&db.pool, session_id)).await?;
+    let expected = timed("poll_expected_voters", expected_voters(&db.pool, &session)).await?;
     
-    // Group responses by option_id
-    let mut responses_by_option: HashMap<String, Vec<&Response>> = HashMap::new();
-    for response in &responses {
-        responses_by_option.entry(response.option_id.clone())
-            .or_default()
-            .push(response);
-    }
-    
-    // Build the updated message text
-    let mut message_text = format!("🎲 **{}**\n\nSelect your availability for each option:\n\n", session.title);
+    // Build the updated message text and keyboard (tallies stay hidden for anonymous polls)
+    let (message_text, keyboard) = render_poll(&session, &session_options, &responses, expected);
     
-    // Create inline keyboard
-    let mut keyboard_rows = Vec::new();
-    
-    for (i, option) in session_options.iter().enumerate() {
-        // Parse datetime and format it
-        let datetime_str = chrono::DateTime::parse_from_rfc3339(&option.datetime)
-            .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
-            .unwrap_or_else(|_| option.datetime.clone());
-        
-        message_text.push_str(&format!("**{}\\. {}**\n", i + 1, escape_markdown(&datetime_str)));
-        
-        // Count responses for this option
-        let empty_vec = Vec::new();
-        let option_responses = responses_by_option.get(&option.id).unwrap_or(&empty_vec);
-        let yes_count = option_responses.iter().filter(|r| r.response == "yes").count();
-        let no_count = option_responses.iter().filter(|r| r.response == "no").count();
-        let maybe_count = option_responses.iter().filter(|r| r.response == "maybe").count();
-        
-        message_text.push_str(&format!(
-            "✅ {yes_count} • ❌ {no_count} • ❓ {maybe_count}\n\n"
-        ));
-        
-        // Add the inline keyboard row for this option
-        let row = vec![
-            InlineKeyboardButton::callback(
-                format!("✅ {yes_count}"),
-                format!("{}:{}:yes", session.id, option.id),
-            ),
-            InlineKeyboardButton::callback(
-                format!("❌ {no_count}"), 
-                format!("{}:{}:no", session.id, option.id),
-            ),
-            InlineKeyboardButton::callback(
-                format!("❓ {maybe_count}"),
-                format!("{}:{}:maybe", session.id, option.id),
-            ),
-        ];
-        keyboard_rows.push(row);
-    }
+    let chat_id = match chat_id {
+        Some(chat_id) => chat_id,
+        None => {
+            let group = Group::find_by_id(&db.pool, session.group_id)
+                .await?
+                .ok_or_else(|| BotError::NotFound { what: "Group".to_string() })?;
+            ChatId(group.telegram_chat_id)
+        }
+    };
     
-    let keyboard = InlineKeyboardMarkup::new(keyboard_rows);
+    let Some(message_id) = session.message_id else {
+        tracing::warn!("Session {} has no poll message, posting a new one", session.id);
+        return repost_missing_poll(bot, db, &session, chat_id).await;
+    };
     
-    // Update the message if we have message info from the callback
-    if let Some(message) = q.message.as_ref() {
-        bot.edit_message_text(message.chat.id, message.id, message_text)
+    let edited = plain_fallback(
+        bot.edit_message_text(chat_id, MessageId(message_id as i32), message_text)
             .reply_markup(keyboard)
             .parse_mode(ParseMode::MarkdownV2)
-            .await?;
+    ).await;
+    match edited {
+        Ok(_) => {
+            mark_rendered(db, &session.id).await;
+            Ok(PollUpdate::Edited)
+        }
+        Err(RequestError::Api(ApiError::MessageToEditNotFound)) => {
+            tracing::warn!("Poll message {} of session {} is gone, posting a new one", message_id, session.id);
+            repost_missing_poll(bot, db, &session, chat_id).await
+        }
+        Err(e) => Err(e.into()),
     }
-    
-    Ok(())
 }
 
 // Helper function to escape markdown characters
@@ -201,24 +441,48 @@ async fn handle_settings_callback(
     q: CallbackQuery,
     data: String,
     db: &DatabaseManager,
-) -> ResponseResult<()> {
+) -> Result<(), BotError> {
     let setting = data.strip_prefix("settings:").unwrap_or(&data);
     
     match setting {
         "timezone" => {
-            bot.answer_callback_query(q.id)
-                .text("🕐 Timezone settings will be available in a future update!")
-                .await?;
+            crate::bot::commands::settings::handle_setting_prompt_callback(bot, q, SettingField::Timezone, db).await?;
+        }
+        "duration:custom" => {
+            crate::bot::commands::settings::handle_setting_prompt_callback(bot, q, SettingField::DefaultDuration, db).await?;
         }
         "duration" => {
-            bot.answer_callback_query(q.id)
-                .text("⏱️ Default duration settings will be available in a future update!")
-                .await?;
+            crate::bot::commands::settings::handle_duration_callback(bot, q, data.clone(), db).await?;
+        }
+        s if s.starts_with("duration:") => {
+            crate::bot::commands::settings::handle_duration_callback(bot, q, data.clone(), db).await?;
+        }
+        "notice" => {
+            crate::bot::commands::settings::handle_notice_callback(bot, q, data.clone(), db).await?;
+        }
+        s if s.starts_with("notice:") => {
+            crate::bot::commands::settings::handle_notice_callback(bot, q, data.clone(), db).await?;
+        }
+        "admins" => {
+            crate::bot::commands::settings::handle_admins_callback(bot, q, db).await?;
+        }
+        "reminders" => {
+            crate::bot::commands::settings::handle_reminder_preview_callback(bot, q, db).await?;
+        }
+        "usernames" => {
+            crate::bot::commands::settings::handle_usernames_callback(bot, q, db).await?;
+        }
+        "language" => {
+            crate::bot::commands::settings::handle_language_callback(bot, q, data.clone(), db).await?;
+        }
+        s if s.starts_with("language:") => {
+            crate::bot::commands::settings::handle_language_callback(bot, q, data.clone(), db).await?;
         }
         "autoconfirm" => {
-            bot.answer_callback_query(q.id)
-                .text("🤖 Auto-confirm settings will be available in a future update!")
-                .await?;
+            crate::bot::commands::settings::handle_autoconfirm_callback(bot, q, data.clone(), db).await?;
+        }
+        s if s.starts_with("autoconfirm:") => {
+            crate::bot::commands::settings::handle_autoconfirm_callback(bot, q, data.clone(), db).await?;
         }
         "stats" => {
             bot.answer_callback_query(q.id)
@@ -226,7 +490,7 @@ async fn handle_settings_callback(
                 .await?;
             
             if let Some(message) = q.message {
-                crate::bot::commands::stats::handle_stats(bot, message.clone(), db).await?;
+                crate::bot::commands::stats::handle_stats(bot, message.clone(), None, db).await?;
             }
         }
         "close" => {
@@ -1,9 +1,11 @@
 use teloxide::prelude::*;
+use crate::bot::commands::{looks_like_command, with_command_prefix};
 use crate::utils::feedback::CommandFeedback;
 
 pub async fn handle_general_message(
     bot: Bot,
     msg: Message,
+    prefix: &str,
 ) -> ResponseResult<()> {
     let chat_id = msg.chat.id.0;
     let user_id = msg.from().map(|u| u.id.0).unwrap_or(0);
@@ -17,14 +19,14 @@ pub async fn handle_general_message(
         );
         
         // Check if it looks like a malformed command
-        if text.starts_with('/') {
+        if looks_like_command(text, prefix) {
             tracing::info!(
                 "Unknown command '{}' from user {} ({}) in chat {}",
                 text, username, user_id, chat_id
             );
             let error_msg = format!("Unknown command: {}", text.split_whitespace().next().unwrap_or(text));
-            let suggestion = "Use /help to see all available commands, or check your command syntax.";
-            feedback.validation_error(&error_msg, suggestion).await?;
+            let suggestion = with_command_prefix("Use /help to see all available commands, or check your command syntax.", prefix);
+            feedback.validation_error(&error_msg, &suggestion).await?;
         } else if text.to_lowercase().contains("schedule") || text.to_lowercase().contains("session") {
             // Helpful hint for users trying to schedule
             let suggestion = "Looking to schedule a session? Try:\n• `/schedule \"Session Title\" \"Friday 19:00, Saturday 14:30\"`\n• Use /help for more examples";
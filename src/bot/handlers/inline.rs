@@ -0,0 +1,154 @@
+//! Inline queries: typing `@bot <title>` in any chat offers the user's active session polls,
+//! so one can be shared there with a link back to the group's poll
+
+use teloxide::prelude::*;
+use teloxide::types::{
+    InlineQueryResult, InlineQueryResultArticle, InputMessageContent, InputMessageContentText, ParseMode,
+};
+use crate::bot::handlers::report_error;
+use crate::bot::poll::VoteCounts;
+use crate::database::connection::DatabaseManager;
+use crate::database::models::*;
+use crate::error::BotError;
+use crate::utils::{datetime::format_datetime, markdown::escape_markdown};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Most results an inline query returns
+pub const MAX_INLINE_RESULTS: usize = 10;
+
+/// How many matching sessions are looked at before filtering by membership
+const CANDIDATE_LIMIT: i64 = 50;
+
+/// An active session offered as an inline result, with what its summary shows
+#[derive(Debug, Clone)]
+pub struct InlineSession {
+    /// The session
+    pub session: Session,
+    /// The Telegram chat its poll was posted in
+    pub chat_id: i64,
+    /// Its options
+    pub options: Vec<SessionOption>,
+    /// Every vote cast on it
+    pub responses: Vec<Response>,
+}
+
+/// Link to message `message_id` in chat `chat_id`, `None` unless the chat is a supergroup
+///
+/// Basic groups have no message links; supergroup IDs are `-100` followed by the ID `t.me/c/` uses.
+pub fn poll_link(chat_id: i64, message_id: i64) -> Option<String> {
+    let channel_id = chat_id.to_string().strip_prefix("-100")?.to_string();
+    Some(format!("https://t.me/c/{channel_id}/{message_id}"))
+}
+
+/// The message text (MarkdownV2) shared for a session: title, options with tallies, notes and
+/// a link to vote in the group
+pub fn render_session_summary(entry: &InlineSession) -> String {
+    let session = &entry.session;
+    let mut text = format!(
+        "🎲 **{}** {}\n\n",
+        escape_markdown(&session.title),
+        escape_markdown(&session.display_ref())
+    );
+    for (i, option) in entry.options.iter().enumerate() {
+        let datetime = DateTime::parse_from_rfc3339(&option.datetime)
+            .map(|dt| format_datetime(&dt.with_timezone(&Utc)))
+            .unwrap_or_else(|_| option.datetime.clone());
+        text.push_str(&format!("{}\\. {}", i + 1, escape_markdown(&datetime)));
+        if !session.hides_votes() {
+            let counts = VoteCounts::for_option(&entry.responses, &option.id);
+            text.push_str(&format!(" — ✅ {} • ❌ {} • ❓ {}", counts.yes, counts.no, counts.maybe));
+        }
+        text.push('\n');
+    }
+    if let Some(notes) = session.notes.as_deref().filter(|notes| !notes.trim().is_empty()) {
+        text.push_str(&format!("\n📝 {}\n", escape_markdown(notes)));
+    }
+    match session.message_id.and_then(|message_id| poll_link(entry.chat_id, message_id)) {
+        Some(link) => text.push_str(&format!("\n[🗳️ Vote in the group poll]({link})")),
+        None => text.push_str("\n🗳️ Vote in the group's poll"),
+    }
+    text
+}
+
+/// One article per session whose title contains `query` (ignoring case), at most
+/// `MAX_INLINE_RESULTS`; an empty query matches every session
+pub fn build_inline_results(sessions: &[InlineSession], query: &str) -> Vec<InlineQueryResult> {
+    let query = query.trim().to_lowercase();
+    sessions.iter()
+        .filter(|entry| entry.session.title.to_lowercase().contains(&query))
+        .take(MAX_INLINE_RESULTS)
+        .map(|entry| {
+            let content = InputMessageContentText::new(render_session_summary(entry))
+                .parse_mode(ParseMode::MarkdownV2)
+                .disable_web_page_preview(true);
+            let options = match entry.options.len() {
+                1 => "1 option".to_string(),
+                count => format!("{count} options"),
+            };
+            let article = InlineQueryResultArticle::new(
+                entry.session.id.clone(),
+                entry.session.title.clone(),
+                InputMessageContent::Text(content),
+            )
+            .description(format!("{} · {options}", entry.session.display_ref()));
+            InlineQueryResult::Article(article)
+        })
+        .collect()
+}
+
+/// Dispatch endpoint for inline queries; failures are only logged, as there is no chat to tell
+pub async fn inline_query_handler(
+    bot: Bot,
+    q: InlineQuery,
+    db: DatabaseManager,
+) -> ResponseResult<()> {
+    if let Err(e) = handle_inline_query(&bot, q, &db).await {
+        report_error(&bot, None, &e).await;
+    }
+    Ok(())
+}
+
+async fn handle_inline_query(bot: &Bot, q: InlineQuery, db: &DatabaseManager) -> Result<(), BotError> {
+    let user_id = q.from.id;
+    let candidates = Session::find_active_by_title(&db.pool, &q.query, CANDIDATE_LIMIT).await?;
+    let known_groups = Group::find_ids_with_member(&db.pool, user_id.0 as i64).await?;
+
+    // Whether the user belongs to each group, and the group's chat if so
+    let mut membership: HashMap<i64, Option<i64>> = HashMap::new();
+    let mut sessions = Vec::new();
+    for session in candidates {
+        if sessions.len() == MAX_INLINE_RESULTS {
+            break;
+        }
+        let chat_id = match membership.get(&session.group_id) {
+            Some(chat_id) => *chat_id,
+            None => {
+                let chat_id = match Group::find_by_id(&db.pool, session.group_id).await? {
+                    Some(group) if known_groups.contains(&group.id) => Some(group.telegram_chat_id),
+                    // Users who never voted or joined the roster can still be members of the chat
+                    Some(group) => bot.get_chat_member(ChatId(group.telegram_chat_id), user_id).await
+                        .is_ok_and(|member| member.is_present())
+                        .then_some(group.telegram_chat_id),
+                    None => None,
+                };
+                membership.insert(session.group_id, chat_id);
+                chat_id
+            }
+        };
+        let Some(chat_id) = chat_id else {
+            continue;
+        };
+        let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+        let responses = Response::find_by_session(&db.pool, &session.id).await?;
+        sessions.push(InlineSession { session, chat_id, options, responses });
+    }
+
+    let results = build_inline_results(&sessions, &q.query);
+    // Results depend on who is asking, so Telegram mustn't share them between users
+    bot.answer_inline_query(q.id, results)
+        .is_personal(true)
+        .cache_time(30)
+        .await?;
+    Ok(())
+}
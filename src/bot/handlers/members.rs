@@ -0,0 +1,70 @@
+//! Group membership: who joined, who left and who is active in each group chat
+//!
+//! Telegram only sends `chat_member` updates to bots that are chat administrators, so members
+//! are also picked up from join and leave messages and from anyone who writes or votes.
+
+use teloxide::prelude::*;
+use teloxide::types::{Chat, ChatMemberUpdated, User};
+use crate::database::{connection::DatabaseManager, models::GroupMember};
+
+/// Count `user` as an active member of `chat`, if it's a group chat the bot has a group for
+pub async fn record_member(db: &DatabaseManager, chat: &Chat, user: &User) {
+    if user.is_bot || !(chat.is_group() || chat.is_supergroup()) {
+        return;
+    }
+    let recorded = GroupMember::record_activity(
+        &db.pool,
+        chat.id.0,
+        user.id.0 as i64,
+        user.username.as_deref(),
+        Some(&user.full_name()),
+    ).await;
+    if let Err(e) = recorded {
+        tracing::warn!("Failed to record user {} as a member of chat {}: {}", user.id, chat.id, e);
+    }
+}
+
+/// Record that `user` is no longer in `chat`
+pub async fn record_departure(db: &DatabaseManager, chat: &Chat, user: &User) {
+    match GroupMember::mark_left(&db.pool, chat.id.0, user.id.0 as i64).await {
+        Ok(true) => tracing::info!("User {} left chat {}", user.id, chat.id),
+        Ok(false) => {}
+        Err(e) => tracing::warn!("Failed to record user {} leaving chat {}: {}", user.id, chat.id, e),
+    }
+}
+
+/// Keep members up to date from a message: its sender, and anyone it says joined or left
+pub async fn track_message(db: &DatabaseManager, msg: &Message) {
+    if let Some(left) = msg.left_chat_member() {
+        record_departure(db, &msg.chat, left).await;
+    }
+    for joined in msg.new_chat_members().unwrap_or_default() {
+        record_member(db, &msg.chat, joined).await;
+    }
+    // Whoever leaves is also the sender of the message saying so
+    let Some(sender) = msg.from() else {
+        return;
+    };
+    if msg.left_chat_member().map(|left| left.id) != Some(sender.id) {
+        record_member(db, &msg.chat, sender).await;
+    }
+}
+
+/// Handle a `chat_member` update: someone joined, left, was removed or changed role
+pub async fn handle_chat_member(update: ChatMemberUpdated, db: DatabaseManager) -> ResponseResult<()> {
+    let member = &update.new_chat_member;
+    if member.kind.is_present() {
+        record_member(&db, &update.chat, &member.user).await;
+    } else {
+        record_departure(&db, &update.chat, &member.user).await;
+    }
+    Ok(())
+}
+
+/// Handle a `my_chat_member` update: whoever adds the bot to a chat is a member of it
+pub async fn handle_my_chat_member(update: ChatMemberUpdated, db: DatabaseManager) -> ResponseResult<()> {
+    if update.new_chat_member.kind.is_present() {
+        record_member(&db, &update.chat, &update.from).await;
+    }
+    Ok(())
+}
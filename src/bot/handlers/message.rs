@@ -1,15 +1,46 @@
 use teloxide::prelude::*;
 use teloxide::utils::command::BotCommands;
-use crate::bot::commands::Command;
-use crate::database::connection::DatabaseManager;
-use crate::utils::feedback::CommandFeedback;
+use crate::bot::commands::{command_help, command_usage, suggest_invocation, with_command_prefix, Command};
+use crate::bot::dialogue::{BotDialogue, DialogueState};
+use crate::bot::handlers::report_error;
+use crate::bot::whats_new;
+use crate::database::{connection::DatabaseManager, models::{Group, Session}};
+use crate::error::BotError;
+use crate::services::metrics::{self, metrics};
+use crate::utils::{feedback::CommandFeedback, i18n::{self, Lang}};
 
+/// Dispatch endpoint for commands; failures are reported back to the chat
 pub async fn command_handler(
     bot: Bot,
     msg: Message,
     cmd: Command,
+    dialogue: BotDialogue,
     db: DatabaseManager,
+    prefix: &str,
 ) -> ResponseResult<()> {
+    let chat_id = msg.chat.id;
+    let in_group = msg.chat.is_group() || msg.chat.is_supergroup();
+    let command = cmd.name();
+    let start = std::time::Instant::now();
+    let result = handle_command(bot.clone(), msg, cmd, dialogue, db.clone(), prefix).await;
+    metrics().record_command(command, metrics::outcome(&result), start.elapsed());
+    if let Err(e) = result {
+        report_error(&bot, Some(chat_id), &e).await;
+    }
+    if in_group {
+        whats_new::announce_whats_new(&bot, &db, chat_id).await;
+    }
+    Ok(())
+}
+
+async fn handle_command(
+    bot: Bot,
+    msg: Message,
+    cmd: Command,
+    dialogue: BotDialogue,
+    db: DatabaseManager,
+    prefix: &str,
+) -> Result<(), BotError> {
     let chat_id = msg.chat.id.0;
     let user_id = msg.from().map(|u| u.id.0).unwrap_or(0);
     let username = msg.from().and_then(|u| u.username.as_ref()).map_or("unknown", |v| v);
@@ -21,43 +52,217 @@ pub async fn command_handler(
     );
     
     match cmd {
-        Command::Help => {
-            let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
-            let help_text = format!(
-                "🎲 **D&D Scheduler Bot Commands**\n\n{}\n\n💡 **Quick Start:**\n• Use `/schedule \"Session Title\" \"Friday 19:00, Saturday 14:30\"` to create a poll\n• Players click buttons to vote\n• Use `/confirm <session_id>` to lock in the winning time\n\n📚 **Need more help?** Each command provides detailed error messages and suggestions when used incorrectly.",
-                Command::descriptions()
-            );
+        Command::Help { command: Some(name) } => {
+            let lang = chat_language(&db, chat_id).await;
+            let feedback = CommandFeedback::new(bot.clone(), msg.chat.id).with_language(lang);
+            let name = name.trim().trim_start_matches(prefix);
+            match command_help(name) {
+                Some((description, examples)) => {
+                    let examples: Vec<String> = examples.iter().map(|example| with_command_prefix(example, prefix)).collect();
+                    let examples: Vec<&str> = examples.iter().map(String::as_str).collect();
+                    feedback.send_command_help(&format!("{prefix}{name}"), &description, &examples).await?;
+                }
+                None => {
+                    let error = format!("There is no {prefix}{name} command");
+                    feedback.validation_error(&error, &with_command_prefix("Send /help to see all commands.", prefix)).await?;
+                }
+            }
+        }
+        Command::Help { command: None } => {
+            let lang = chat_language(&db, chat_id).await;
+            let feedback = CommandFeedback::new(bot.clone(), msg.chat.id).with_language(lang);
+            let help_text = with_command_prefix(&i18n::t_help(lang, &Command::descriptions().to_string()), prefix);
             feedback.info(&help_text).await?;
         }
-        Command::Start => {
-            let feedback = CommandFeedback::new(bot.clone(), msg.chat.id);
-            let welcome_text = "Welcome to D&D Scheduler Bot!\n\nI help you schedule D&D sessions by creating polls where players can vote on their preferred times.\n\n🚀 **Get Started:**\n• Use /schedule to create your first session poll\n• Use /help to see all available commands\n\n🎯 **Pro Tip:** I provide detailed feedback and suggestions for every command!";
-            feedback.success(welcome_text).await?;
+        Command::Start { payload } if !payload.is_empty() => {
+            crate::bot::commands::invite::handle_start_payload(bot, msg, payload, &db).await?;
+        }
+        Command::Start { .. } => {
+            let lang = chat_language(&db, chat_id).await;
+            let feedback = CommandFeedback::new(bot.clone(), msg.chat.id).with_language(lang);
+            feedback.success(i18n::t_welcome(lang)).await?;
         }
         Command::Schedule { title, options } => {
             crate::bot::commands::schedule::handle_schedule(bot, msg, title, options, &db).await?;
         }
-        Command::Confirm { session_id } => {
-            crate::bot::commands::session_management::handle_confirm(bot, msg, session_id, &db).await?;
+        Command::Confirm { session_id, option } => {
+            crate::bot::commands::session_management::handle_confirm(bot, msg, session_id, option, Some(dialogue), &db).await?;
         }
         Command::Cancel { session_id } => {
             crate::bot::commands::session_management::handle_cancel(bot, msg, session_id, &db).await?;
         }
+        Command::Delete { session_id } => {
+            crate::bot::commands::session_management::handle_delete(bot, msg, session_id, &db).await?;
+        }
+        Command::Repost { session_id } => {
+            crate::bot::commands::session_management::handle_repost(bot, msg, session_id, &db).await?;
+        }
+        Command::Archive { session_id } => {
+            crate::bot::commands::session_management::handle_archive(bot, msg, session_id, &db).await?;
+        }
         Command::Deadline { session_id, datetime } => {
             crate::bot::commands::session_management::handle_deadline(bot, msg, session_id, datetime, &db).await?;
         }
+        Command::Notes { session_id, text } => {
+            crate::bot::commands::session_management::handle_notes(bot, msg, session_id, text, &db).await?;
+        }
+        Command::Mute { session_id } => {
+            crate::bot::commands::session_management::handle_mute(bot, msg, session_id, true, &db).await?;
+        }
+        Command::Unmute { session_id } => {
+            crate::bot::commands::session_management::handle_mute(bot, msg, session_id, false, &db).await?;
+        }
         Command::List => {
             crate::bot::commands::list::handle_list(bot, msg, &db).await?;
         }
-        Command::TestReminders => {
-            crate::bot::commands::reminders::handle_test_reminders(bot, msg, &db).await?;
+        Command::TestReminders { send } => {
+            crate::bot::commands::reminders::handle_test_reminders(bot, msg, send, &db).await?;
+        }
+        Command::Settings { args } => {
+            crate::bot::commands::settings::handle_settings(bot, msg, args, &db).await?;
+        }
+        Command::Stats { session_id } => {
+            crate::bot::commands::stats::handle_stats(bot, msg, session_id, &db).await?;
+        }
+        Command::CloseAll { older_than_days } => {
+            crate::bot::commands::session_management::handle_closeall(bot, msg, older_than_days, &db).await?;
+        }
+        Command::Export => {
+            crate::bot::commands::export::handle_export(bot, msg, &db).await?;
+        }
+        Command::ExportGroup { private } => {
+            crate::bot::commands::export::handle_export_group(bot, msg, private, &db).await?;
+        }
+        Command::Undo => {
+            crate::bot::commands::undo::handle_undo(bot, msg, &db).await?;
+        }
+        Command::Invite => {
+            crate::bot::commands::invite::handle_invite(bot, msg, &db).await?;
+        }
+        Command::CheckIns { session_id } => {
+            crate::bot::commands::checkins::handle_checkins(bot, msg, session_id, &db).await?;
+        }
+        Command::Attended { session_id, players } => {
+            crate::bot::commands::attendance::handle_attended(bot, msg, session_id, players, &db).await?;
+        }
+        Command::Players { args } => {
+            crate::bot::commands::players::handle_players(bot, msg, args, &db).await?;
+        }
+        Command::Duty { args } => {
+            crate::bot::commands::duty::handle_duty(bot, msg, args, &db).await?;
+        }
+        Command::Sent { session_id } => {
+            crate::bot::commands::sent::handle_sent(bot, msg, session_id, &db).await?;
+        }
+        Command::Results { session_id } => {
+            crate::bot::commands::results::handle_results(bot, msg, session_id, &db).await?;
+        }
+        Command::SessionInfo { session_id } => {
+            crate::bot::commands::sessioninfo::handle_session_info(bot, msg, session_id, &db).await?;
         }
-        Command::Settings => {
-            crate::bot::commands::settings::handle_settings(bot, msg, &db).await?;
+        Command::RollCall { args } => {
+            crate::bot::commands::rollcall::handle_rollcall(bot, msg, args, &db).await?;
         }
-        Command::Stats => {
-            crate::bot::commands::stats::handle_stats(bot, msg, &db).await?;
+        Command::Vote { session_id, option, response } => {
+            let args = crate::bot::commands::vote::VoteArgs { session_id, option, response };
+            crate::bot::commands::vote::handle_vote(bot, msg, args, &db).await?;
+        }
+        Command::Availability { title, range } => {
+            crate::bot::commands::availability::handle_availability(bot, msg, title, range, &db).await?;
         }
     }
     Ok(())
 }
+
+/// Dispatch endpoint for the reply to a question the bot asked in the chat (see [`DialogueState`])
+pub async fn reply_handler(
+    bot: Bot,
+    msg: Message,
+    dialogue: BotDialogue,
+    state: DialogueState,
+    db: DatabaseManager,
+) -> ResponseResult<()> {
+    let chat_id = msg.chat.id;
+    let result = match state {
+        DialogueState::AwaitingSetting { .. } => {
+            crate::bot::commands::settings::handle_setting_reply(bot.clone(), msg, dialogue, state, &db).await
+        }
+        DialogueState::AwaitingNote { .. } => {
+            crate::bot::commands::voter_notes::handle_note_reply(bot.clone(), msg, dialogue, state, &db).await
+        }
+        _ => crate::bot::commands::availability::handle_start_time_reply(bot.clone(), msg, dialogue, state, &db).await,
+    };
+    if let Err(e) = result {
+        report_error(&bot, Some(chat_id), &e).await;
+    }
+    Ok(())
+}
+
+/// Reply to one of our commands whose arguments didn't parse with how to use it
+///
+/// Commands that take a session suggest the group's active one, if it has exactly one.
+pub async fn usage_handler(
+    bot: Bot,
+    msg: Message,
+    command: &'static str,
+    db: DatabaseManager,
+    prefix: &str,
+) -> ResponseResult<()> {
+    let Some(usage) = command_usage(command) else {
+        return Ok(());
+    };
+    tracing::info!(
+        "Command /{} with missing or invalid arguments in chat {}: '{}'",
+        command, msg.chat.id, msg.text().unwrap_or_default()
+    );
+    
+    let example = command_help(command).and_then(|(_, examples)| {
+        examples.iter().find(|example| example.contains(' ')).or(examples.first()).copied()
+    });
+    let suggestion = only_active_session(&db, msg.chat.id.0).await
+        .and_then(|session| suggest_invocation(usage, &session.display_ref()));
+    let lang = chat_language(&db, msg.chat.id.0).await;
+    CommandFeedback::new(bot, msg.chat.id)
+        .with_language(lang)
+        .send_usage_help(
+            &format!("{prefix}{command}"),
+            &with_command_prefix(usage, prefix),
+            example.map(|example| with_command_prefix(example, prefix)).as_deref(),
+            suggestion.map(|suggestion| with_command_prefix(&suggestion, prefix)).as_deref(),
+        )
+        .await?;
+    
+    Ok(())
+}
+
+/// The chat's group's active session, if it has exactly one
+async fn only_active_session(db: &DatabaseManager, chat_id: i64) -> Option<Session> {
+    let sessions = match Group::find_by_chat_id(&db.pool, chat_id).await {
+        Ok(Some(group)) => Session::find_by_group(&db.pool, group.id).await,
+        Ok(None) => return None,
+        Err(e) => Err(e),
+    };
+    let mut active = match sessions {
+        Ok(sessions) => sessions.into_iter().filter(|session| session.status == "active"),
+        Err(e) => {
+            tracing::warn!("Failed to look up the active sessions of chat {}: {}", chat_id, e);
+            return None;
+        }
+    };
+    match (active.next(), active.next()) {
+        (Some(session), None) => Some(session),
+        _ => None,
+    }
+}
+
+/// Language of the chat's group; English for private chats, unknown groups and failed lookups
+async fn chat_language(db: &DatabaseManager, chat_id: i64) -> Lang {
+    match Group::find_by_chat_id(&db.pool, chat_id).await {
+        Ok(Some(group)) => group.lang(),
+        Ok(None) => Lang::default(),
+        Err(e) => {
+            tracing::warn!("Failed to look up the language of chat {}: {}", chat_id, e);
+            Lang::default()
+        }
+    }
+}
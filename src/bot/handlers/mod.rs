@@ -1,44 +1,193 @@
 pub mod callback;
 pub mod message;
 pub mod general_message;
+pub mod inline;
+pub mod members;
 
 use teloxide::{
     dispatching::{dialogue, UpdateHandler},
     prelude::*,
 };
-use crate::database::connection::DatabaseManager;
+use teloxide::types::UpdateKind;
+use teloxide::utils::command::BotCommands;
+use crate::bot::commands::{failed_command, looks_like_command, slash_command, Command, DEFAULT_COMMAND_PREFIX};
+use crate::bot::dialogue::{DialogueState, DialogueStorage};
+use crate::database::{connection::DatabaseManager, models::UserProfile};
+use crate::error::{error_log, BotError};
+use crate::services::health::RuntimeStatus;
+use chrono::Utc;
+use std::sync::Arc;
+use crate::utils::feedback::CommandFeedback;
+
+/// Report a failed command or callback: log it by category and tell the chat, if there is one
+///
+/// This is the single place where `BotError`s become user-facing messages.
+pub async fn report_error(bot: &Bot, chat_id: Option<ChatId>, error: &BotError) {
+    let chat = chat_id.map(|id| id.0).unwrap_or(0);
+    if error.is_internal() {
+        tracing::error!(
+            error.kind = error.kind(),
+            error.db_code = error.database_code().as_deref(),
+            chat_id = chat,
+            "Handler failed: {}", error
+        );
+        error_log().record(error, chat_id.map(|id| id.0), Utc::now());
+    } else {
+        tracing::warn!(error.kind = error.kind(), chat_id = chat, "Handler rejected request: {}", error);
+    }
+
+    let Some(chat_id) = chat_id else {
+        return;
+    };
+    if let Err(e) = CommandFeedback::new(bot.clone(), chat_id).error(&error.user_message()).await {
+        tracing::warn!("Failed to report error to chat {}: {}", chat_id, e);
+    }
+}
+
+/// Keep the name of whoever sent a message or pressed a button fresh in `user_profiles`, and
+/// count them as a member of the group they did it in
+pub async fn remember_sender(db: &DatabaseManager, update: &Update) {
+    if !matches!(update.kind, UpdateKind::Message(_) | UpdateKind::CallbackQuery(_)) {
+        return;
+    }
+    let Some(user) = update.user().filter(|user| !user.is_bot) else {
+        return;
+    };
+    let chat_id = update.chat().map(|chat| chat.id.0);
+    let recorded = UserProfile::record(
+        &db.pool,
+        chat_id,
+        user.id.0 as i64,
+        user.username.as_deref(),
+        &user.full_name(),
+    ).await;
+    if let Err(e) = recorded {
+        tracing::warn!("Failed to update the profile of user {}: {}", user.id, e);
+    }
+
+    match &update.kind {
+        UpdateKind::Message(msg) => members::track_message(db, msg).await,
+        UpdateKind::CallbackQuery(q) => {
+            if let Some(message) = &q.message {
+                members::record_member(db, &message.chat, user).await;
+            }
+        }
+        _ => {}
+    }
+}
 
 pub struct BotHandler {
     pub db: DatabaseManager,
+    /// Told about every update, so the health endpoints know the dispatcher is polling
+    pub status: Arc<RuntimeStatus>,
+    /// What commands start with besides "/", from `COMMAND_PREFIX`
+    pub command_prefix: String,
 }
 
 impl BotHandler {
     pub fn new(db: DatabaseManager) -> Self {
-        Self { db }
+        Self {
+            db,
+            status: Arc::new(RuntimeStatus::default()),
+            command_prefix: DEFAULT_COMMAND_PREFIX.to_string(),
+        }
+    }
+
+    /// Report received updates to `status`
+    pub fn with_status(mut self, status: Arc<RuntimeStatus>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Accept commands starting with `prefix` as well as "/", and show it in help texts
+    pub fn with_command_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.command_prefix = prefix.into();
+        self
     }
 
     pub fn schema(&self) -> UpdateHandler<teloxide::RequestError> {
         use teloxide::dispatching::UpdateFilterExt;
         
         let db = self.db.clone();
+        let db_usage = self.db.clone();
+        let db_reply = self.db.clone();
         let db_callback = self.db.clone();
+        let db_inline = self.db.clone();
+        let db_profiles = self.db.clone();
+        let db_members = self.db.clone();
+        let db_my_member = self.db.clone();
+        let status = self.status.clone();
+        let prefix = self.command_prefix.clone();
+        let prefix_command = prefix.clone();
+        let prefix_usage = prefix.clone();
+        let prefix_failed = prefix.clone();
+        let prefix_reply = prefix.clone();
+        let prefix_general = prefix.clone();
         
-        dialogue::enter::<Update, teloxide::dispatching::dialogue::InMemStorage<()>, (), _>()
+        dptree::inspect(move |_: Update| status.record_update(Utc::now()))
+            .inspect_async(move |update: Update| {
+                let db = db_profiles.clone();
+                async move { remember_sender(&db, &update).await }
+            })
+            // Inline queries come from no chat, so `dialogue::enter` would stop them short
+            .branch(Update::filter_inline_query().endpoint(move |bot, q| {
+                let db = db_inline.clone();
+                async move { inline::inline_query_handler(bot, q, db).await }
+            }))
+            .branch(dialogue::enter::<Update, DialogueStorage, DialogueState, _>()
             .branch(
                 Update::filter_message()
-                    .filter_command::<crate::bot::commands::Command>()
-                    .endpoint(move |bot, msg, cmd| {
+                    .filter_map(move |msg: Message, me: teloxide::types::Me| {
+                        Command::parse(&slash_command(msg.text()?, &prefix), me.username()).ok()
+                    })
+                    .endpoint(move |bot, msg, cmd, dialogue| {
                         let db = db.clone();
-                        async move { message::command_handler(bot, msg, cmd, db).await }
+                        let prefix = prefix_command.clone();
+                        async move { message::command_handler(bot, msg, cmd, dialogue, db, &prefix).await }
+                    }),
+            )
+            .branch(
+                // One of our commands, but its arguments didn't parse
+                Update::filter_message()
+                    .filter_map(move |msg: Message, me: teloxide::types::Me| {
+                        failed_command(&slash_command(msg.text()?, &prefix_failed), me.username())
+                    })
+                    .endpoint(move |bot, msg, command: &'static str| {
+                        let db = db_usage.clone();
+                        let prefix = prefix_usage.clone();
+                        async move { message::usage_handler(bot, msg, command, db, &prefix).await }
                     }),
             )
             .branch(
+                // The reply to a question the bot asked the sender in this chat
                 Update::filter_message()
-                    .endpoint(general_message::handle_general_message)
+                    .filter(move |msg: Message, state: DialogueState| {
+                        let sender = msg.from().map(|user| user.id.0 as i64);
+                        msg.text().is_some_and(|text| !looks_like_command(text, &prefix_reply))
+                            && sender.is_some_and(|sender| state.awaits_reply_from(sender))
+                    })
+                    .endpoint(move |bot, msg, dialogue, state| {
+                        let db = db_reply.clone();
+                        async move { message::reply_handler(bot, msg, dialogue, state, db).await }
+                    }),
+            )
+            .branch(
+                Update::filter_message().endpoint(move |bot, msg| {
+                    let prefix = prefix_general.clone();
+                    async move { general_message::handle_general_message(bot, msg, &prefix).await }
+                })
             )
             .branch(Update::filter_callback_query().endpoint(move |bot, q| {
                 let db = db_callback.clone();
                 async move { callback::callback_handler(bot, q, db).await }
             }))
+            .branch(Update::filter_chat_member().endpoint(move |update| {
+                let db = db_members.clone();
+                async move { members::handle_chat_member(update, db).await }
+            }))
+            .branch(Update::filter_my_chat_member().endpoint(move |update| {
+                let db = db_my_member.clone();
+                async move { members::handle_my_chat_member(update, db).await }
+            })))
     }
 }
@@ -1,2 +1,11 @@
+pub mod autoconfirm;
+pub mod badges;
 pub mod commands;
+pub mod dialogue;
+pub mod duties;
 pub mod handlers;
+pub mod permissions;
+pub mod poll;
+pub mod selection;
+pub mod vote_reminders;
+pub mod whats_new;
@@ -0,0 +1,130 @@
+//! Who may manage a session: its creator, the chat's administrators and the group's bot admins
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+use crate::database::{connection::DatabaseManager, models::*};
+
+/// How long a chat's administrator list is reused before asking Telegram again
+pub const CHAT_ADMIN_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Why a user is allowed to manage a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionManager {
+    /// They created the session
+    Creator,
+    /// They own or administrate the Telegram chat
+    ChatAdmin,
+    /// They were made a bot admin of the group through `/settings`
+    BotAdmin,
+}
+
+/// Recently fetched chat administrator lists, so commands don't hit Telegram every time
+pub struct AdminCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<ChatId, (Instant, Vec<UserId>)>>,
+}
+
+impl AdminCache {
+    /// An empty cache whose entries stay valid for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cached administrators of `chat_id`, if fetched less than `ttl` ago
+    pub fn get(&self, chat_id: ChatId) -> Option<Vec<UserId>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(&chat_id) {
+            Some((fetched_at, admins)) if fetched_at.elapsed() < self.ttl => Some(admins.clone()),
+            Some(_) => {
+                entries.remove(&chat_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Remember the administrators of `chat_id` as of now
+    pub fn insert(&self, chat_id: ChatId, admins: Vec<UserId>) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(chat_id, (Instant::now(), admins));
+    }
+}
+
+fn chat_admin_cache() -> &'static AdminCache {
+    static CACHE: OnceLock<AdminCache> = OnceLock::new();
+    CACHE.get_or_init(|| AdminCache::new(CHAT_ADMIN_CACHE_TTL))
+}
+
+/// User IDs of a chat's owner and administrators, cached for `CHAT_ADMIN_CACHE_TTL`
+pub async fn chat_administrators(bot: &Bot, chat_id: ChatId) -> ResponseResult<Vec<UserId>> {
+    if let Some(admins) = chat_admin_cache().get(chat_id) {
+        return Ok(admins);
+    }
+
+    let admins: Vec<UserId> = bot.get_chat_administrators(chat_id)
+        .await?
+        .into_iter()
+        .map(|member| member.user.id)
+        .collect();
+    chat_admin_cache().insert(chat_id, admins.clone());
+    Ok(admins)
+}
+
+/// Why `user_id` may confirm, cancel or set a deadline on `session`, or `None` if they may not
+///
+/// Checked in order: the session creator, the chat's administrators (in a private chat, its
+/// only member) and the group's bot admins. A failed lookup counts as "no" for that step.
+pub async fn session_manager_role(
+    bot: &Bot,
+    db: &DatabaseManager,
+    chat_id: ChatId,
+    session: &Session,
+    user_id: i64,
+) -> Option<SessionManager> {
+    if session.created_by == user_id {
+        return Some(SessionManager::Creator);
+    }
+
+    group_manager_role(bot, db, chat_id, session.group_id, user_id).await
+}
+
+/// Why `user_id` may run group-wide admin commands in `chat_id`, whose group has ID `group_id`,
+/// or `None` if they may not
+///
+/// Like [`session_manager_role`] without the creator step: the chat's administrators (in a
+/// private chat, its only member) and then the group's bot admins.
+pub async fn group_manager_role(
+    bot: &Bot,
+    db: &DatabaseManager,
+    chat_id: ChatId,
+    group_id: i64,
+    user_id: i64,
+) -> Option<SessionManager> {
+    if chat_id.is_user() {
+        return Some(SessionManager::ChatAdmin);
+    }
+    match chat_administrators(bot, chat_id).await {
+        Ok(admins) if admins.contains(&UserId(user_id as u64)) => return Some(SessionManager::ChatAdmin),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to get administrators of chat {}: {}", chat_id, e),
+    }
+
+    match GroupAdmin::is_admin(&db.pool, group_id, user_id).await {
+        Ok(true) => Some(SessionManager::BotAdmin),
+        Ok(false) => None,
+        Err(e) => {
+            tracing::warn!("Failed to check bot admins of group {}: {}", group_id, e);
+            None
+        }
+    }
+}
+
+/// Error shown when someone without management rights tries to `action`, e.g. "confirm sessions"
+pub fn permission_denied_message(action: &str) -> String {
+    format!("Permission denied: Only the session creator, chat administrators or the group's bot admins can {action}")
+}
@@ -0,0 +1,400 @@
+//! Rendering and refreshing of the interactive session poll message
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode};
+use crate::bot::selection::{pick_winning_option, DEFAULT_VOTE_WEIGHTS};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::BotError;
+use crate::utils::{datetime::{format_date, format_datetime}, heatmap::render_availability_heatmap, markdown::escape_markdown, outbound::{logged_send, plain_fallback}};
+use chrono::{DateTime, Utc};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Minimum time between two automatic reposts of the same session's poll
+pub const AUTO_REPOST_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Minimum time between two votes of the same user on the same session
+pub const VOTE_COOLDOWN: Duration = Duration::from_secs(2);
+
+/// Yes/no/maybe tallies for a single session option
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VoteCounts {
+    /// Number of "yes" responses
+    pub yes: usize,
+    /// Number of "no" responses
+    pub no: usize,
+    /// Number of "maybe" responses
+    pub maybe: usize,
+}
+
+impl VoteCounts {
+    /// Count the responses given for one option
+    pub fn for_option(responses: &[Response], option_id: &str) -> Self {
+        let mut counts = Self::default();
+        for response in responses.iter().filter(|r| r.option_id == option_id) {
+            match response.response.as_str() {
+                "yes" => counts.yes += 1,
+                "no" => counts.no += 1,
+                "maybe" => counts.maybe += 1,
+                _ => {}
+            }
+        }
+        counts
+    }
+}
+
+/// IDs of the options currently in the lead, ranked like /confirm ranks them: highest weighted
+/// score, ties broken by more "yes" votes
+///
+/// Options still tied after that all lead. Empty until someone has voted "yes".
+pub fn leading_option_ids(options: &[SessionOption], responses: &[Response]) -> Vec<String> {
+    let weights = &DEFAULT_VOTE_WEIGHTS;
+    let counts: Vec<VoteCounts> = options.iter()
+        .map(|option| VoteCounts::for_option(responses, &option.id))
+        .collect();
+    
+    let Some((best, _)) = pick_winning_option(&counts, weights) else {
+        return Vec::new();
+    };
+    
+    options.iter()
+        .zip(&counts)
+        .filter(|(_, option_counts)| !weights.beats(&counts[best], option_counts))
+        .map(|(option, _)| option.id.clone())
+        .collect()
+}
+
+/// How an option's start is shown in polls and lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionLabel {
+    /// The day, e.g. "Saturday, 14 December", on the first of several options in a row that
+    /// start on the same day (UTC)
+    pub day_heading: Option<String>,
+    /// Whether the option sits under such a heading, the first one included
+    pub under_heading: bool,
+    /// The time only for options under a heading, otherwise the full date and time
+    pub when: String,
+}
+
+/// Labels for `options`, which are in start order
+///
+/// Options starting on the same day share a day heading and only show their times, so
+/// "Saturday 14:00" and "Saturday 19:00" don't read as two near-identical lines. Options alone
+/// on their day, and ones whose time can't be read, keep the full date and time.
+pub fn label_options<O: Borrow<SessionOption>>(options: &[O]) -> Vec<OptionLabel> {
+    let starts: Vec<Option<DateTime<Utc>>> = options.iter()
+        .map(|option| DateTime::parse_from_rfc3339(&option.borrow().datetime).ok().map(|dt| dt.with_timezone(&Utc)))
+        .collect();
+    let same_day = |a: usize, b: usize| match (starts.get(a).copied().flatten(), starts.get(b).copied().flatten()) {
+        (Some(a), Some(b)) => a.date_naive() == b.date_naive(),
+        _ => false,
+    };
+    
+    options.iter()
+        .enumerate()
+        .map(|(i, option)| {
+            let Some(start) = starts[i] else {
+                return OptionLabel { day_heading: None, under_heading: false, when: option.borrow().datetime.clone() };
+            };
+            let continues_day = i > 0 && same_day(i - 1, i);
+            if !continues_day && !same_day(i, i + 1) {
+                return OptionLabel { day_heading: None, under_heading: false, when: format_datetime(&start) };
+            }
+            OptionLabel {
+                day_heading: (!continues_day).then(|| format_date(&start)),
+                under_heading: true,
+                when: start.format("%H:%M").to_string(),
+            }
+        })
+        .collect()
+}
+
+/// How many players a session's poll is waiting for: the group's roster, or without one
+/// everyone who voted on its earlier polls; `None` if the group has neither
+pub async fn expected_voters(pool: &sqlx::SqlitePool, session: &Session) -> Result<Option<usize>, sqlx::Error> {
+    let roster = RosterMember::find_by_group(pool, session.group_id).await?;
+    if !roster.is_empty() {
+        return Ok(Some(roster.len()));
+    }
+    let past_voters = Response::count_past_voters(pool, session.group_id, &session.id).await?;
+    Ok((past_voters > 0).then_some(past_voters as usize))
+}
+
+/// "📊 5 of 8 players have voted", counting each voter once however many options they answered
+///
+/// Without an `expected` number it's just "📊 5 players have voted". Voters beyond `expected`
+/// (say, someone not on the roster) still count, so it never reads "9 of 8".
+pub fn participation_line(responses: &[Response], expected: Option<usize>) -> String {
+    let mut voters: Vec<i64> = responses.iter().map(|r| r.user_id).collect();
+    voters.sort_unstable();
+    voters.dedup();
+    let voted = voters.len();
+    
+    match expected {
+        Some(expected) => {
+            let expected = expected.max(voted);
+            let verb = if voted == 1 { "has" } else { "have" };
+            let noun = if expected == 1 { "player" } else { "players" };
+            format!("📊 {voted} of {expected} {noun} {verb} voted")
+        }
+        None if voted == 0 => "📊 Nobody has voted yet".to_string(),
+        None if voted == 1 => "📊 1 player has voted".to_string(),
+        None => format!("📊 {voted} players have voted"),
+    }
+}
+
+/// Build the poll message text (MarkdownV2) and voting keyboard for a session
+///
+/// Anonymous sessions that are still active show "votes hidden" instead of tallies. While
+/// voting is open, the leading options are marked with a star and a footer says how many of
+/// the `expected` players (see [`expected_voters`]) voted.
+pub fn render_poll(
+    session: &Session,
+    options: &[SessionOption],
+    responses: &[Response],
+    expected: Option<usize>,
+) -> (String, InlineKeyboardMarkup) {
+    let hide_votes = session.hides_votes();
+    
+    // Availability polls ask about whole days until one is confirmed with its start time
+    let days_only = session.availability && session.status == "active";
+    let question = if days_only {
+        "Which days work for you? The start time is set when the session is confirmed:"
+    } else {
+        "Select your availability for each option:"
+    };
+    let mut message_text = format!("🎲 **{}**\n\n{}\n", escape_markdown(&session.title), escape_markdown(question));
+    if hide_votes {
+        message_text.push_str("🙈 Votes are hidden until the session is confirmed\n");
+    }
+    message_text.push('\n');
+    
+    // Highlighting the leader would give away hidden tallies
+    let leading = if session.status == "active" && !hide_votes {
+        leading_option_ids(options, responses)
+    } else {
+        Vec::new()
+    };
+    
+    let labels = label_options(options);
+    for (i, (option, label)) in options.iter().zip(&labels).enumerate() {
+        if let Some(day) = &label.day_heading {
+            message_text.push_str(&format!("📅 **{}**\n", escape_markdown(day)));
+        }
+        
+        let leading_marker = if leading.contains(&option.id) { "⭐ Leading: " } else { "" };
+        let when = match DateTime::parse_from_rfc3339(&option.datetime) {
+            Ok(start) if days_only => format_date(&start.with_timezone(&Utc)),
+            _ => label.when.clone(),
+        };
+        message_text.push_str(&format!("{}**{}\\. {}**\n", leading_marker, i + 1, escape_markdown(&when)));
+        
+        let counts = VoteCounts::for_option(responses, &option.id);
+        if hide_votes {
+            message_text.push_str("🙈 votes hidden\n\n");
+        } else {
+            message_text.push_str(&format!(
+                "✅ {} • ❌ {} • ❓ {}\n\n",
+                counts.yes, counts.no, counts.maybe
+            ));
+        }
+        
+    }
+    
+    if session.availability && !hide_votes {
+        if let Some(heatmap) = render_availability_heatmap(options, responses) {
+            message_text.push_str(&format!("🔥 **Best days**\n{}\n", escape_markdown(&heatmap)));
+        }
+    }
+    
+    if session.status == "active" {
+        message_text.push_str(&format!("{}\n", escape_markdown(&participation_line(responses, expected))));
+    }
+    
+    (message_text, poll_keyboard(session, options, responses))
+}
+
+/// The voting keyboard of a session's poll: a row of yes/no/maybe buttons per option
+///
+/// The buttons carry "session_id:option_id:response" and vote wherever the poll is shown. Each
+/// row ends in a 📝 button ("note:option_id") for leaving a note on the option, except on
+/// anonymous polls, where a note would give the vote away.
+pub fn poll_keyboard(
+    session: &Session,
+    options: &[SessionOption],
+    responses: &[Response],
+) -> InlineKeyboardMarkup {
+    // Fresh polls show "Yes/No/Maybe" labels rather than a row of zeros
+    let show_counts_on_buttons = !session.hides_votes() && !responses.is_empty();
+    let rows = options.iter().map(|option| {
+        let counts = VoteCounts::for_option(responses, &option.id);
+        let (yes_label, no_label, maybe_label) = if show_counts_on_buttons {
+            (format!("✅ {}", counts.yes), format!("❌ {}", counts.no), format!("❓ {}", counts.maybe))
+        } else {
+            ("✅ Yes".to_string(), "❌ No".to_string(), "❓ Maybe".to_string())
+        };
+        let mut row = vec![
+            InlineKeyboardButton::callback(yes_label, format!("{}:{}:yes", session.id, option.id)),
+            InlineKeyboardButton::callback(no_label, format!("{}:{}:no", session.id, option.id)),
+            InlineKeyboardButton::callback(maybe_label, format!("{}:{}:maybe", session.id, option.id)),
+        ];
+        if !session.anonymous {
+            row.push(InlineKeyboardButton::callback("📝", format!("note:{}", option.id)));
+        }
+        row
+    });
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Build a copy of the poll for a player's private chat, reached through a vote link
+pub fn render_private_poll(
+    session: &Session,
+    options: &[SessionOption],
+    responses: &[Response],
+    expected: Option<usize>,
+) -> (String, InlineKeyboardMarkup) {
+    let (poll_text, keyboard) = render_poll(session, options, responses, expected);
+    let message_text = format!(
+        "🔒 _{}_\n\n{}",
+        escape_markdown("Your private copy of the poll; votes here also update the group's poll"),
+        poll_text
+    );
+    (message_text, keyboard)
+}
+
+/// Build a poll message that replaces a lost or buried one, with the current tallies
+pub fn render_reposted_poll(
+    session: &Session,
+    options: &[SessionOption],
+    responses: &[Response],
+    expected: Option<usize>,
+) -> (String, InlineKeyboardMarkup) {
+    let (poll_text, keyboard) = render_poll(session, options, responses, expected);
+    let message_text = format!(
+        "🔁 _{}_\n\n{}",
+        escape_markdown("Reposted poll, please vote on this message from now on"),
+        poll_text
+    );
+    (message_text, keyboard)
+}
+
+/// Send a fresh poll message for `session` and track it instead of the previous one
+///
+/// Used when the original message was deleted or is no longer known. The voting keyboard is
+/// only attached while the session is active.
+pub async fn repost_poll_message(
+    bot: &Bot,
+    db: &DatabaseManager,
+    session: &Session,
+    chat_id: ChatId,
+) -> Result<Message, BotError> {
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    let expected = expected_voters(&db.pool, session).await?;
+    let (message_text, keyboard) = render_reposted_poll(session, &options, &responses, expected);
+    
+    let request = bot
+        .send_message(chat_id, message_text)
+        .parse_mode(ParseMode::MarkdownV2);
+    let request = if session.status == "active" { request.reply_markup(keyboard) } else { request };
+    let message = logged_send(&db.pool, chat_id, "poll", Some(&session.id), plain_fallback(request)).await?;
+    
+    Session::set_message_id(&db.pool, &session.id, message.id.0 as i64).await?;
+    tracing::info!("Reposted poll for session {} as message {}", session.id, message.id);
+    
+    Ok(message)
+}
+
+/// Remembers when each session's poll was last reposted automatically, so a poll that keeps
+/// getting deleted doesn't make every vote post yet another copy
+#[derive(Debug, Default)]
+pub struct RepostThrottle {
+    last_repost: Mutex<HashMap<String, Instant>>,
+}
+
+impl RepostThrottle {
+    /// Whether `session_id` may be reposted at `now`; if so, the repost is recorded
+    pub fn try_acquire(&self, session_id: &str, now: Instant) -> bool {
+        let mut last_repost = self.last_repost.lock().unwrap_or_else(|e| e.into_inner());
+        last_repost.retain(|_, at| now.duration_since(*at) < AUTO_REPOST_COOLDOWN);
+        if last_repost.contains_key(session_id) {
+            return false;
+        }
+        last_repost.insert(session_id.to_string(), now);
+        true
+    }
+}
+
+/// The process-wide throttle for automatic poll reposts
+pub fn repost_throttle() -> &'static RepostThrottle {
+    static THROTTLE: OnceLock<RepostThrottle> = OnceLock::new();
+    THROTTLE.get_or_init(RepostThrottle::default)
+}
+
+/// Remembers when each user last voted on each session, so tapping through the buttons doesn't
+/// rewrite the vote and edit the poll message on every tap
+#[derive(Debug, Default)]
+pub struct VoteThrottle {
+    last_vote: Mutex<HashMap<(i64, String), Instant>>,
+}
+
+impl VoteThrottle {
+    /// Whether `user_id` may vote on `session_id` at `now`; if so, the vote is recorded
+    pub fn try_acquire(&self, user_id: i64, session_id: &str, now: Instant) -> bool {
+        let mut last_vote = self.last_vote.lock().unwrap_or_else(|e| e.into_inner());
+        last_vote.retain(|_, at| now.duration_since(*at) < VOTE_COOLDOWN);
+        let key = (user_id, session_id.to_string());
+        if last_vote.contains_key(&key) {
+            return false;
+        }
+        last_vote.insert(key, now);
+        true
+    }
+}
+
+/// The process-wide throttle for votes
+pub fn vote_throttle() -> &'static VoteThrottle {
+    static THROTTLE: OnceLock<VoteThrottle> = OnceLock::new();
+    THROTTLE.get_or_init(VoteThrottle::default)
+}
+
+/// Re-render a session's original poll message from the current database state
+///
+/// Does nothing if the session has no stored poll message. The voting keyboard is only
+/// kept while the session is active.
+pub async fn refresh_poll_message(
+    bot: &Bot,
+    db: &DatabaseManager,
+    session: &Session,
+    chat_id: ChatId,
+) -> Result<(), BotError> {
+    let Some(message_id) = session.message_id else {
+        return Ok(());
+    };
+    
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    let expected = expected_voters(&db.pool, session).await?;
+    let (message_text, keyboard) = render_poll(session, &options, &responses, expected);
+    
+    let request = bot
+        .edit_message_text(chat_id, MessageId(message_id as i32), message_text)
+        .parse_mode(ParseMode::MarkdownV2);
+    if session.status == "active" {
+        plain_fallback(request.reply_markup(keyboard)).await?;
+    } else {
+        plain_fallback(request).await?;
+    }
+    mark_rendered(db, &session.id).await;
+    
+    Ok(())
+}
+
+/// Record that a session's poll was just edited; the edit stands even if this fails
+pub async fn mark_rendered(db: &DatabaseManager, session_id: &str) {
+    if let Err(e) = Session::mark_rendered(&db.pool, session_id).await {
+        tracing::warn!("Failed to record when the poll of session {} was rendered: {}", session_id, e);
+    }
+}
@@ -0,0 +1,231 @@
+//! Which option of a session wins: weighted vote scores with deterministic tie-breaking
+//!
+//! Used by /confirm to pick the time to lock in, by /list to crown the current leader and by
+//! the poll message to star it, so all three always agree.
+
+use chrono::{DateTime, Utc};
+use crate::bot::poll::VoteCounts;
+use crate::database::models::*;
+
+/// How much each kind of vote counts towards an option's score when confirming a session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoteWeights {
+    /// Weight of a "yes" vote
+    pub yes: f64,
+    /// Weight of a "maybe" vote
+    pub maybe: f64,
+}
+
+/// Weights used by /confirm: a "maybe" is worth half a "yes"
+pub const DEFAULT_VOTE_WEIGHTS: VoteWeights = VoteWeights { yes: 1.0, maybe: 0.5 };
+
+impl VoteWeights {
+    /// Weighted score of one option's votes; "no" votes count for nothing
+    pub fn score(&self, counts: &VoteCounts) -> f64 {
+        counts.yes as f64 * self.yes + counts.maybe as f64 * self.maybe
+    }
+
+    /// Whether `a` ranks above `b`: a higher score, or the same score with more "yes" votes
+    pub(crate) fn beats(&self, a: &VoteCounts, b: &VoteCounts) -> bool {
+        let (score_a, score_b) = (self.score(a), self.score(b));
+        score_a > score_b || (score_a == score_b && a.yes > b.yes)
+    }
+}
+
+/// Index and score of the option to confirm, or `None` if nobody voted "yes" anywhere
+///
+/// The highest weighted score wins, ties go to the option with more "yes" votes, and the
+/// earlier option wins any remaining tie.
+pub fn pick_winning_option(counts: &[VoteCounts], weights: &VoteWeights) -> Option<(usize, f64)> {
+    if counts.iter().all(|c| c.yes == 0) {
+        return None;
+    }
+    best_index(counts, weights, None).map(|i| (i, weights.score(&counts[i])))
+}
+
+/// The first of the top-ranked `counts`, leaving out `skip`
+fn best_index(counts: &[VoteCounts], weights: &VoteWeights, skip: Option<usize>) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (i, option_counts) in counts.iter().enumerate() {
+        if Some(i) == skip {
+            continue;
+        }
+        if best.is_none_or(|best| weights.beats(option_counts, &counts[best])) {
+            best = Some(i);
+        }
+    }
+    best
+}
+
+/// An option with its tallies and weighted score
+#[derive(Debug, Clone)]
+pub struct ScoredOption {
+    /// The option
+    pub option: SessionOption,
+    /// Its yes/no/maybe tallies
+    pub counts: VoteCounts,
+    /// Its weighted score
+    pub score: f64,
+}
+
+/// The option [`select_best_option`] picked, and the one that came closest
+#[derive(Debug, Clone)]
+pub struct SelectionResult {
+    /// The winning option
+    pub winner: ScoredOption,
+    /// The best of the other options, `None` if the session only has one
+    pub runner_up: Option<ScoredOption>,
+}
+
+/// The option a session should be confirmed for, or `None` if nobody voted "yes" anywhere
+///
+/// Scores use [`DEFAULT_VOTE_WEIGHTS`]. Ties go to the option with more "yes" votes, then to
+/// the earliest one, whatever order `options` come in.
+pub fn select_best_option(options: &[SessionOption], responses: &[Response]) -> Option<SelectionResult> {
+    let options = earliest_first(options);
+    let counts: Vec<VoteCounts> = options.iter()
+        .map(|option| VoteCounts::for_option(responses, &option.id))
+        .collect();
+
+    let weights = &DEFAULT_VOTE_WEIGHTS;
+    let scored = |i: usize| ScoredOption {
+        option: options[i].clone(),
+        counts: counts[i],
+        score: weights.score(&counts[i]),
+    };
+    let (winner, _) = pick_winning_option(&counts, weights)?;
+    Some(SelectionResult {
+        winner: scored(winner),
+        runner_up: best_index(&counts, weights, Some(winner)).map(scored),
+    })
+}
+
+/// `options` by start time, the order ties are broken in
+fn earliest_first(options: &[SessionOption]) -> Vec<&SessionOption> {
+    let mut options: Vec<&SessionOption> = options.iter().collect();
+    options.sort_by_key(|option| {
+        let starts_at = DateTime::parse_from_rfc3339(&option.datetime).ok().map(|dt| dt.with_timezone(&Utc));
+        (starts_at, option.datetime.clone(), option.id.clone())
+    });
+    options
+}
+
+/// An option that would take the lead from the winner if enough of its "maybe" voters said yes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaybeSwing {
+    /// Index of the option that would win instead
+    pub option: usize,
+    /// The fewest of its maybes that have to turn to yes for it to win
+    pub converts: usize,
+    /// All of its maybes
+    pub maybes: usize,
+    /// Its yes votes once they have
+    pub yes: usize,
+    /// The current winner's yes votes
+    pub winner_yes: usize,
+}
+
+/// What the outstanding maybes could do to [`pick_winning_option`]'s pick, or `None` if they can't
+/// change it
+///
+/// Each other option's maybes are turned to yes one at a time, leaving every other vote as it is,
+/// and the winner is picked again by the same rules. So an option that only draws level on
+/// score and yes votes takes the lead only if it's the earlier one. When several options could
+/// win, it's the one needing the fewest maybes to convert, then the earliest.
+pub fn maybe_swing(counts: &[VoteCounts], weights: &VoteWeights) -> Option<MaybeSwing> {
+    let (winner, _) = pick_winning_option(counts, weights)?;
+    let mut swing: Option<MaybeSwing> = None;
+    for (i, option_counts) in counts.iter().enumerate() {
+        if i == winner {
+            continue;
+        }
+        let wins_with = |converts: usize| {
+            let mut what_if = counts.to_vec();
+            what_if[i] = VoteCounts {
+                yes: option_counts.yes + converts,
+                no: option_counts.no,
+                maybe: option_counts.maybe - converts,
+            };
+            pick_winning_option(&what_if, weights).map(|(best, _)| best) == Some(i)
+        };
+        let Some(converts) = (1..=option_counts.maybe).find(|&converts| wins_with(converts)) else {
+            continue;
+        };
+        if swing.is_none_or(|swing| converts < swing.converts) {
+            swing = Some(MaybeSwing {
+                option: i,
+                converts,
+                maybes: option_counts.maybe,
+                yes: option_counts.yes + converts,
+                winner_yes: counts[winner].yes,
+            });
+        }
+    }
+    swing
+}
+
+/// [`maybe_swing`] over the options [`select_confirmable_option`] picks from, with the option
+/// that would win instead
+pub fn confirmable_maybe_swing(
+    options: &[SessionOption],
+    responses: &[Response],
+    required: &[i64],
+) -> Option<(SessionOption, MaybeSwing)> {
+    let vetoed = vetoed_options(options, responses, required);
+    let options: Vec<&SessionOption> = earliest_first(options).into_iter()
+        .filter(|option| !vetoed.contains(&option.id))
+        .collect();
+    let counts: Vec<VoteCounts> = options.iter()
+        .map(|option| VoteCounts::for_option(responses, &option.id))
+        .collect();
+    let swing = maybe_swing(&counts, &DEFAULT_VOTE_WEIGHTS)?;
+    Some((options[swing.option].clone(), swing))
+}
+
+/// IDs of the options one of the `required` players voted "no" on, in the order of `options`
+///
+/// A required player's "maybe", or no vote at all, doesn't rule an option out.
+pub fn vetoed_options(options: &[SessionOption], responses: &[Response], required: &[i64]) -> Vec<String> {
+    options.iter()
+        .filter(|option| responses.iter().any(|r| {
+            r.option_id == option.id && r.response == "no" && required.contains(&r.user_id)
+        }))
+        .map(|option| option.id.clone())
+        .collect()
+}
+
+/// Like [`select_best_option`], leaving out the options one of the `required` players said no to
+///
+/// This is the option /confirm picks and /list crowns; confirming an option by its number
+/// still works for the others.
+pub fn select_confirmable_option(
+    options: &[SessionOption],
+    responses: &[Response],
+    required: &[i64],
+) -> Option<SelectionResult> {
+    let vetoed = vetoed_options(options, responses, required);
+    let options: Vec<SessionOption> = options.iter()
+        .filter(|option| !vetoed.contains(&option.id))
+        .cloned()
+        .collect();
+    select_best_option(&options, responses)
+}
+
+/// The option a session is confirmed for automatically once `threshold` players said yes to it,
+/// or `None` while no option has that many
+///
+/// Picks like [`select_confirmable_option`] among the options that reached the threshold, so an
+/// option a required player said no to is never confirmed this way.
+pub fn select_autoconfirm_option(
+    options: &[SessionOption],
+    responses: &[Response],
+    required: &[i64],
+    threshold: i64,
+) -> Option<SelectionResult> {
+    let reached: Vec<SessionOption> = options.iter()
+        .filter(|option| VoteCounts::for_option(responses, &option.id).yes as i64 >= threshold)
+        .cloned()
+        .collect();
+    select_confirmable_option(&reached, responses, required)
+}
+
@@ -0,0 +1,64 @@
+//! Vote reminders: polls that sit unanswered, or whose deadline is close, get a nudge in their
+//! group that replies to the poll message so it's one tap away
+
+use chrono::{DateTime, Duration, Utc};
+use teloxide::prelude::*;
+use teloxide::types::{MessageId, ParseMode};
+use crate::bot::commands::invite::{bot_username, vote_link};
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::scheduling::{DueVoteReminder, SchedulerApi, SchedulingError, VoteReminderSettings};
+use crate::utils::{
+    datetime::format_datetime,
+    i18n,
+    markdown::escape_markdown,
+    outbound::{logged_send, plain_fallback},
+};
+
+/// Remind the groups of the polls [`SchedulerApi::due_vote_reminders`] picks at `now` to vote,
+/// and record each reminder sent; returns how many went out
+///
+/// Groups the bot couldn't post in lately are skipped, and a reminder that fails to send is
+/// tried again on the next run.
+pub async fn send_vote_reminders(
+    bot: &Bot,
+    db: &DatabaseManager,
+    settings: VoteReminderSettings,
+    now: DateTime<Utc>,
+) -> Result<usize, SchedulingError> {
+    let due = SchedulerApi::new(db.pool.clone()).due_vote_reminders(now, settings).await?;
+    let unreachable = ReminderDelivery::unreachable_group_ids(
+        &db.pool,
+        now - Duration::days(UNREACHABLE_GROUP_PAUSE_DAYS),
+    ).await?;
+
+    let mut sent = 0;
+    for reminder in due.iter().filter(|reminder| !unreachable.contains(&reminder.group.id)) {
+        let session_id = &reminder.session.id;
+        if let Err(e) = send_vote_reminder(bot, db, reminder).await {
+            tracing::warn!("Failed to send the vote reminder for session {}: {}", session_id, e);
+            continue;
+        }
+        Reminder::create_kind(&db.pool, session_id.clone(), ReminderKind::Vote, reminder.open_minutes).await?;
+        tracing::info!("Reminded group {} to vote on session {}", reminder.group.id, session_id);
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+/// Post the reminder in reply to the poll, or on its own if the poll message is gone
+async fn send_vote_reminder(bot: &Bot, db: &DatabaseManager, reminder: &DueVoteReminder) -> ResponseResult<()> {
+    let DueVoteReminder { session, group, voters, deadline, .. } = reminder;
+    let deadline = deadline.as_ref().map(|deadline| escape_markdown(&format_datetime(deadline)));
+    let vote_link = bot_username(bot).await.map(|username| vote_link(&username, &session.id));
+    let text = i18n::t_vote_reminder(group.lang(), &escape_markdown(&session.title), *voters, deadline.as_deref(), vote_link.as_deref());
+
+    let chat_id = ChatId(group.telegram_chat_id);
+    let mut request = bot.send_message(chat_id, text)
+        .parse_mode(ParseMode::MarkdownV2)
+        .allow_sending_without_reply(true);
+    if let Some(message_id) = session.message_id {
+        request = request.reply_to_message_id(MessageId(message_id as i32));
+    }
+    logged_send(&db.pool, chat_id, "vote_reminder", Some(&session.id), plain_fallback(request)).await?;
+    Ok(())
+}
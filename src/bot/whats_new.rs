@@ -0,0 +1,57 @@
+//! Telling each group once what changed after the bot is upgraded
+//!
+//! The check runs on a group's first command after the bot starts. The announced version is
+//! claimed before the message goes out, so a group hears about each release at most once even
+//! when several commands race, and groups that opted out move along silently.
+
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+use teloxide::prelude::*;
+use crate::database::{connection::DatabaseManager, models::Group};
+use crate::error::BotError;
+use crate::utils::changelog::{self, CHANGELOG, CURRENT_VERSION};
+use crate::utils::outbound::logged_send;
+
+/// Chats already checked since the bot started
+fn checked_chats() -> &'static Mutex<HashSet<i64>> {
+    static CHECKED: OnceLock<Mutex<HashSet<i64>>> = OnceLock::new();
+    CHECKED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Send the group in `chat_id` a "What's new" message if it hasn't heard about this version yet
+///
+/// Only the first call per chat since the bot started looks at the database. Failures are
+/// logged, since they shouldn't get in the way of the command that triggered the check.
+pub async fn announce_whats_new(bot: &Bot, db: &DatabaseManager, chat_id: ChatId) {
+    let first_check = match checked_chats().lock() {
+        Ok(mut checked) => checked.insert(chat_id.0),
+        Err(_) => false,
+    };
+    if !first_check {
+        return;
+    }
+    if let Err(e) = announce(bot, db, chat_id).await {
+        tracing::warn!("Failed to announce version {} in chat {}: {}", CURRENT_VERSION, chat_id, e);
+    }
+}
+
+async fn announce(bot: &Bot, db: &DatabaseManager, chat_id: ChatId) -> Result<(), BotError> {
+    let Some(group) = Group::find_by_chat_id(&db.pool, chat_id.0).await? else {
+        return Ok(());
+    };
+    let announced = group.announced_version.as_deref();
+    if !changelog::is_due(announced, CURRENT_VERSION) {
+        return Ok(());
+    }
+    if !Group::mark_announced(&db.pool, group.id, announced, CURRENT_VERSION).await? || !group.whats_new {
+        return Ok(());
+    }
+
+    let entries = changelog::unannounced(CHANGELOG, announced, CURRENT_VERSION);
+    let Some(text) = changelog::render_whats_new(group.lang(), &entries) else {
+        return Ok(());
+    };
+    tracing::info!("Announcing version {} to group {}", CURRENT_VERSION, group.id);
+    logged_send(&db.pool, chat_id, "whats_new", None, bot.send_message(chat_id, text)).await?;
+    Ok(())
+}
@@ -1,40 +1,379 @@
-use anyhow::{anyhow, Result};
+//! Settings from environment variables and an optional TOML file
+//!
+//! Every setting has a key in the file named like its field (`http_port`) and an environment
+//! variable of the same name in capitals (`HTTP_PORT`). The file is the one `CONFIG_FILE`
+//! points to, or `./config.toml` if it exists; environment variables override its values, and
+//! empty ones count as unset. Errors name the setting and where its bad value came from.
+
+use anyhow::{anyhow, Context, Result};
 use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::bot::commands::DEFAULT_COMMAND_PREFIX;
+use crate::database::connection::{PoolSettings, DEFAULT_BUSY_TIMEOUT_MS, DEFAULT_MAX_CONNECTIONS};
+use crate::scheduling::VoteReminderSettings;
+
+/// The config file read when `CONFIG_FILE` isn't set, if it exists
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+const DEFAULT_DATABASE_URL: &str = "sqlite:./data/scheduler.db";
+
+const DEFAULT_HTTP_PORT: u16 = 3000;
+
+/// The keys a config file may set
+const KEYS: [&str; 15] = [
+    "telegram_bot_token",
+    "database_url",
+    "http_port",
+    "db_max_connections",
+    "db_busy_timeout_ms",
+    "admin_token",
+    "running_in_docker",
+    "bot_mode",
+    "webhook_url",
+    "backup_dir",
+    "log_format",
+    "command_prefix",
+    "vote_reminder_after_hours",
+    "vote_reminder_min_voters",
+    "vote_reminder_interval_hours",
+];
+
+/// How the bot gets its updates from Telegram
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BotMode {
+    /// Ask Telegram for updates in a loop
+    #[default]
+    Polling,
+    /// Have Telegram post updates to `webhook_url`
+    Webhook,
+}
+
+/// How log lines are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One line per event with all its fields
+    #[default]
+    Full,
+    /// Shorter lines, for terminals
+    Compact,
+    /// Several lines per event, for reading during development
+    Pretty,
+    /// One JSON object per event, for log aggregators such as Loki
+    Json,
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub telegram_bot_token: String,
     pub database_url: String,
     pub http_port: u16,
+    /// `DB_MAX_CONNECTIONS`: size limit of the SQLite connection pool
+    pub db_max_connections: u32,
+    /// `DB_BUSY_TIMEOUT_MS`: how long a query waits for a locked database
+    pub db_busy_timeout_ms: u64,
+    /// `ADMIN_TOKEN`: token for the `/admin` dashboard, which stays off while it's unset
+    pub admin_token: Option<String>,
+    /// `RUNNING_IN_DOCKER`: the database directory must be a mounted volume, so it isn't created on the fly
+    pub running_in_docker: bool,
+    /// `BOT_MODE`: "polling" (the default) or "webhook"
+    pub bot_mode: BotMode,
+    /// `WEBHOOK_URL`: the public URL Telegram posts updates to; required in webhook mode
+    #[allow(dead_code)] // The bot only polls so far
+    pub webhook_url: Option<String>,
+    /// `BACKUP_DIR`: where database backups are written
+    #[allow(dead_code)] // Nothing writes backups yet
+    pub backup_dir: Option<PathBuf>,
+    /// `LOG_FORMAT`: "full" or "text" (the default), "compact", "pretty" or "json"
+    pub log_format: LogFormat,
+    /// `COMMAND_PREFIX`: what commands start with besides "/", such as "!"; "/" by default
+    pub command_prefix: String,
+    /// `VOTE_REMINDER_AFTER_HOURS`: how long a poll is open before too few voters get it a reminder to vote
+    pub vote_reminder_after_hours: i64,
+    /// `VOTE_REMINDER_MIN_VOTERS`: voters a poll needs to get no reminder; 0 leaves only the ones before its deadline
+    pub vote_reminder_min_voters: i64,
+    /// `VOTE_REMINDER_INTERVAL_HOURS`: shortest time between two reminders on a poll; 0 turns them off
+    pub vote_reminder_interval_hours: i64,
+}
+
+/// A setting's value as found, before it's checked
+enum RawValue {
+    Text(String),
+    Integer(i64),
+    Bool(bool),
+    /// Anything else a TOML file can hold, by its type name
+    Other(&'static str),
+}
+
+/// A setting's value and where it came from
+struct Raw {
+    value: RawValue,
+    origin: Origin,
+}
+
+/// Where a setting came from, for error messages
+enum Origin {
+    Env(String),
+    File { key: &'static str, path: PathBuf },
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Env(var) => write!(f, "{var}"),
+            Origin::File { key, path } => write!(f, "{key} in {}", path.display()),
+        }
+    }
+}
+
+impl Raw {
+    fn invalid(&self, expected: &str) -> anyhow::Error {
+        let found = match &self.value {
+            RawValue::Text(text) => format!("'{text}'"),
+            RawValue::Integer(n) => n.to_string(),
+            RawValue::Bool(flag) => flag.to_string(),
+            RawValue::Other(type_name) => format!("a {type_name}"),
+        };
+        anyhow!("Invalid {}: expected {expected}, found {found}", self.origin)
+    }
+
+    fn text(self) -> Result<String> {
+        match self.value {
+            RawValue::Text(text) => Ok(text),
+            _ => Err(self.invalid("a string")),
+        }
+    }
+
+    /// A number in `T`'s range; `expected` describes it for the error
+    fn number<T>(self, expected: &str) -> Result<T>
+    where
+        T: TryFrom<i64> + std::str::FromStr,
+    {
+        let parsed = match &self.value {
+            RawValue::Integer(n) => T::try_from(*n).ok(),
+            RawValue::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        };
+        parsed.ok_or_else(|| self.invalid(expected))
+    }
+
+    /// A boolean; in the environment "1", "true" and "yes" count as set and anything else as unset
+    fn flag(self) -> Result<bool> {
+        match (&self.value, &self.origin) {
+            (RawValue::Bool(flag), _) => Ok(*flag),
+            (RawValue::Text(text), Origin::Env(_)) => Ok(matches!(text.trim().to_lowercase().as_str(), "1" | "true" | "yes")),
+            _ => Err(self.invalid("true or false")),
+        }
+    }
+
+    /// One of `choices`, by name ignoring case
+    fn choice<T: Copy>(self, choices: &[(&str, T)]) -> Result<T> {
+        let expected = choices.iter().map(|(name, _)| format!("\"{name}\"")).collect::<Vec<_>>().join(" or ");
+        let found = match &self.value {
+            RawValue::Text(text) => choices.iter().find(|(name, _)| name.eq_ignore_ascii_case(text.trim())),
+            _ => None,
+        };
+        found.map(|(_, value)| *value).ok_or_else(|| self.invalid(&expected))
+    }
+}
+
+/// The environment, over the values of a config file if there is one
+struct Sources {
+    file: Option<(PathBuf, toml_edit::Table)>,
+}
+
+impl Sources {
+    fn read(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self { file: None });
+        };
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read config file {}", path.display()))?;
+        let document = toml_edit::Document::parse(text)
+            .map_err(|e| anyhow!("Invalid TOML in config file {}: {e}", path.display()))?;
+        let table = document.as_table().clone();
+        if let Some((key, _)) = table.iter().find(|(key, _)| !KEYS.contains(key)) {
+            return Err(anyhow!(
+                "Unknown setting '{key}' in {}; the settings are {}",
+                path.display(),
+                KEYS.join(", ")
+            ));
+        }
+        Ok(Self { file: Some((path.to_path_buf(), table)) })
+    }
+
+    /// The value of `key`, from its environment variable or else the file
+    fn get(&self, key: &'static str) -> Option<Raw> {
+        let var = key.to_uppercase();
+        if let Ok(value) = env::var(&var) {
+            if !value.trim().is_empty() {
+                return Some(Raw { value: RawValue::Text(value), origin: Origin::Env(var) });
+            }
+        }
+        let (path, table) = self.file.as_ref()?;
+        let value = match table.get(key)? {
+            toml_edit::Item::Value(toml_edit::Value::String(text)) => RawValue::Text(text.value().clone()),
+            toml_edit::Item::Value(toml_edit::Value::Integer(n)) => RawValue::Integer(*n.value()),
+            toml_edit::Item::Value(toml_edit::Value::Boolean(flag)) => RawValue::Bool(*flag.value()),
+            other => RawValue::Other(other.type_name()),
+        };
+        Some(Raw { value, origin: Origin::File { key, path: path.clone() } })
+    }
+}
+
+/// The config file to read: `CONFIG_FILE` if set, else `./config.toml` if it exists
+fn config_file() -> Option<PathBuf> {
+    match env::var("CONFIG_FILE") {
+        Ok(path) if !path.trim().is_empty() => Some(PathBuf::from(path.trim())),
+        _ => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.is_file()),
+    }
 }
 
 impl Config {
+    /// Load the configuration from the environment and the config file, if there is one
     pub fn from_env() -> Result<Self> {
-        let token = env::var("TELEGRAM_BOT_TOKEN")
-            .map_err(|_| anyhow!("TELEGRAM_BOT_TOKEN must be set"))?;
-        
+        Self::from_sources(config_file().as_deref())
+    }
+
+    /// Load the configuration from the environment over the TOML file at `file`, if given
+    pub fn from_sources(file: Option<&Path>) -> Result<Self> {
+        let sources = Sources::read(file)?;
+
+        let token = match sources.get("telegram_bot_token") {
+            Some(raw) => raw.text()?,
+            None => String::new(),
+        };
         if token.trim().is_empty() {
             return Err(anyhow!("TELEGRAM_BOT_TOKEN must be set"));
         }
-        
-        let database_url = env::var("DATABASE_URL")
-            .unwrap_or_else(|_| "sqlite:./data/scheduler.db".to_string());
-        let database_url = if database_url.trim().is_empty() {
-            "sqlite:./data/scheduler.db".to_string()
-        } else {
-            database_url
-        };
-        
-        let port_str = env::var("HTTP_PORT")
-            .unwrap_or_else(|_| "3000".to_string());
-        let http_port = port_str.trim()
-            .parse()
-            .map_err(|_| anyhow!("Invalid HTTP_PORT"))?;
-        
+
+        let database_url = match sources.get("database_url") {
+            Some(raw) => raw.text()?,
+            None => DEFAULT_DATABASE_URL.to_string(),
+        };
+
+        let http_port = match sources.get("http_port") {
+            Some(raw) => raw.number("a port number from 0 to 65535")?,
+            None => DEFAULT_HTTP_PORT,
+        };
+
+        let db_max_connections = match sources.get("db_max_connections") {
+            Some(raw) => {
+                let origin = raw.origin.to_string();
+                match raw.number::<u32>("a positive number")? {
+                    0 => return Err(anyhow!("Invalid {origin}: the pool needs at least 1 connection")),
+                    n => n,
+                }
+            }
+            None => DEFAULT_MAX_CONNECTIONS,
+        };
+
+        let db_busy_timeout_ms = match sources.get("db_busy_timeout_ms") {
+            Some(raw) => raw.number("a number of milliseconds")?,
+            None => DEFAULT_BUSY_TIMEOUT_MS,
+        };
+
+        let admin_token = match sources.get("admin_token") {
+            Some(raw) => Some(raw.text()?.trim().to_string()).filter(|token| !token.is_empty()),
+            None => None,
+        };
+
+        let running_in_docker = match sources.get("running_in_docker") {
+            Some(raw) => raw.flag()?,
+            None => false,
+        };
+
+        let bot_mode = match sources.get("bot_mode") {
+            Some(raw) => raw.choice(&[("polling", BotMode::Polling), ("webhook", BotMode::Webhook)])?,
+            None => BotMode::default(),
+        };
+
+        let webhook_url = match sources.get("webhook_url") {
+            Some(raw) => Some(raw.text()?.trim().to_string()).filter(|url| !url.is_empty()),
+            None => None,
+        };
+        if bot_mode == BotMode::Webhook && webhook_url.is_none() {
+            return Err(anyhow!("WEBHOOK_URL must be set when BOT_MODE is webhook"));
+        }
+
+        let backup_dir = match sources.get("backup_dir") {
+            Some(raw) => Some(PathBuf::from(raw.text()?.trim())),
+            None => None,
+        };
+
+        let log_format = match sources.get("log_format") {
+            Some(raw) => raw.choice(&[
+                ("full", LogFormat::Full),
+                ("text", LogFormat::Full),
+                ("compact", LogFormat::Compact),
+                ("pretty", LogFormat::Pretty),
+                ("json", LogFormat::Json),
+            ])?,
+            None => LogFormat::default(),
+        };
+
+        let command_prefix = match sources.get("command_prefix") {
+            Some(raw) => {
+                let prefix = match &raw.value {
+                    RawValue::Text(text) => text.trim().to_string(),
+                    _ => String::new(),
+                };
+                let symbols = prefix.chars().count();
+                if !(1..=3).contains(&symbols) || prefix.chars().any(|c| c.is_alphanumeric() || c.is_whitespace() || c == '@') {
+                    return Err(raw.invalid("1 to 3 symbols such as \"/\" or \"!\""));
+                }
+                prefix
+            }
+            None => DEFAULT_COMMAND_PREFIX.to_string(),
+        };
+
+        let vote_reminders = VoteReminderSettings::default();
+        let vote_reminder_after_hours = match sources.get("vote_reminder_after_hours") {
+            Some(raw) => raw.number::<u32>("a number of hours")?.into(),
+            None => vote_reminders.after_hours,
+        };
+        let vote_reminder_min_voters = match sources.get("vote_reminder_min_voters") {
+            Some(raw) => raw.number::<u32>("a number of players")?.into(),
+            None => vote_reminders.min_voters,
+        };
+        let vote_reminder_interval_hours = match sources.get("vote_reminder_interval_hours") {
+            Some(raw) => raw.number::<u32>("a number of hours")?.into(),
+            None => vote_reminders.interval_hours,
+        };
+
         Ok(Config {
             telegram_bot_token: token,
             database_url,
             http_port,
+            db_max_connections,
+            db_busy_timeout_ms,
+            admin_token,
+            running_in_docker,
+            bot_mode,
+            webhook_url,
+            backup_dir,
+            log_format,
+            command_prefix,
+            vote_reminder_after_hours,
+            vote_reminder_min_voters,
+            vote_reminder_interval_hours,
         })
     }
+
+    /// The database pool settings from this configuration
+    pub fn pool_settings(&self) -> PoolSettings {
+        PoolSettings {
+            max_connections: self.db_max_connections,
+            busy_timeout_ms: self.db_busy_timeout_ms,
+        }
+    }
+
+    /// Which polls get reminders to vote, from this configuration
+    pub fn vote_reminder_settings(&self) -> VoteReminderSettings {
+        VoteReminderSettings {
+            after_hours: self.vote_reminder_after_hours,
+            min_voters: self.vote_reminder_min_voters,
+            interval_hours: self.vote_reminder_interval_hours,
+        }
+    }
 }
@@ -1,23 +1,82 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
 use sqlx::{SqlitePool, migrate::MigrateDatabase, Sqlite};
+use std::str::FromStr;
+use crate::database::storage::prepare_database_dir;
+use std::time::Duration;
 use tracing::info;
 
+/// Default for `DB_MAX_CONNECTIONS`
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 5;
+/// Default for `DB_BUSY_TIMEOUT_MS`
+pub const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5000;
+
+/// Connection pool knobs; every connection also gets WAL, `synchronous=NORMAL` and foreign keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolSettings {
+    /// Upper bound on open connections
+    pub max_connections: u32,
+    /// How long a connection waits on a locked database before failing with SQLITE_BUSY
+    pub busy_timeout_ms: u64,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            busy_timeout_ms: DEFAULT_BUSY_TIMEOUT_MS,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct DatabaseManager {
     pub pool: SqlitePool,
+    /// The settings the pool was opened with
+    pub settings: PoolSettings,
 }
 
 impl DatabaseManager {
+    /// Open the database with the default pool settings
+    #[allow(dead_code)] // The bot itself opens the pool with `with_settings`
     pub async fn new(database_url: &str) -> Result<Self> {
+        Self::with_settings(database_url, PoolSettings::default()).await
+    }
+
+    /// Open the database with explicit pool settings
+    pub async fn with_settings(database_url: &str, settings: PoolSettings) -> Result<Self> {
+        Self::open(database_url, settings, false).await
+    }
+
+    /// Open the database, first checking that its directory exists and is writable
+    ///
+    /// With `expect_volume` (`RUNNING_IN_DOCKER`), a missing directory, or one with nothing mounted
+    /// on it, means the data volume isn't mounted, and opening fails instead of creating a
+    /// database that won't survive.
+    pub async fn open(database_url: &str, settings: PoolSettings, expect_volume: bool) -> Result<Self> {
+        prepare_database_dir(database_url, expect_volume)?;
+
         // Create database if it doesn't exist
         if !Sqlite::database_exists(database_url).await.unwrap_or(false) {
             info!("Creating database {}", database_url);
             Sqlite::create_database(database_url).await?;
         }
 
-        let pool = SqlitePool::connect(database_url).await?;
-        
-        Ok(Self { pool })
+        // WAL lets readers proceed while a vote is being written, and the busy timeout
+        // makes concurrent writers queue up instead of failing with "database is locked"
+        let connect_options = SqliteConnectOptions::from_str(database_url)?
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(Duration::from_millis(settings.busy_timeout_ms))
+            .foreign_keys(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(settings.max_connections)
+            .connect_with(connect_options)
+            .await?;
+
+        Ok(Self { pool, settings })
     }
 
     pub async fn run_migrations(&self) -> Result<()> {
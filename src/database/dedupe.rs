@@ -0,0 +1,267 @@
+//! Merging duplicate `groups` rows, used by `migrate dedupe-groups`
+//!
+//! Duplicates come from two places: databases whose `groups` table predates the
+//! `telegram_chat_id` UNIQUE constraint (`CREATE TABLE IF NOT EXISTS` never added it to them),
+//! and chats upgraded to supergroups, which get a new chat ID and so a second group row.
+
+use crate::database::models::Group;
+use crate::utils::{changelog, i18n};
+use sqlx::SqlitePool;
+
+const GROUP_COLUMNS: &str =
+    "id, telegram_chat_id, timezone, default_duration, reminder_hours, created_at, store_usernames, min_notice_hours, language, \
+     long_range_every_days, long_range_until_days, quiet_start_hour, quiet_end_hour, announced_version, whats_new, \
+     autoconfirm_threshold, badges";
+
+/// Settings a freshly created group starts with, see [`Group::create`]
+const DEFAULT_TIMEZONE: &str = "UTC";
+const DEFAULT_DURATION: i64 = 240;
+const DEFAULT_REMINDER_HOURS: i64 = 24;
+const DEFAULT_LANGUAGE: &str = i18n::DEFAULT_LANGUAGE;
+
+/// One fix for duplicated group data
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)] // A handful are planned per run, never kept around
+pub enum DedupeAction {
+    /// Move everything of `duplicate` into `survivor` and delete `duplicate`
+    Merge {
+        /// The group that is kept
+        survivor: Group,
+        /// The group that is merged away
+        duplicate: Group,
+    },
+    /// Only the old chat ID has a group, so it just follows the chat to its new ID
+    Rechat {
+        /// The group under the old chat ID
+        group: Group,
+        /// The chat's new ID
+        new_chat_id: i64,
+    },
+}
+
+/// What applying a [`DedupeAction`] changed (or would change, in a dry run)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DedupeReport {
+    /// Sessions moved to the survivor
+    pub sessions_moved: u64,
+    /// Bot admins moved; ones the survivor already had are dropped
+    pub admins_moved: u64,
+    /// Roster members moved; ones already on the survivor's roster are dropped
+    pub roster_moved: u64,
+    /// Player pseudonyms kept; they only survive if the survivor had none of its own
+    pub pseudonyms_moved: u64,
+}
+
+/// Groups sharing a chat ID, each paired with the oldest group of that chat as the survivor
+pub async fn find_shared_chat_id_duplicates(pool: &SqlitePool) -> Result<Vec<DedupeAction>, sqlx::Error> {
+    let groups = sqlx::query_as::<_, Group>(&format!(
+        "SELECT {GROUP_COLUMNS} FROM groups WHERE telegram_chat_id IN \
+         (SELECT telegram_chat_id FROM groups GROUP BY telegram_chat_id HAVING COUNT(*) > 1) \
+         ORDER BY telegram_chat_id, id"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    let mut actions = Vec::new();
+    let mut survivor: Option<&Group> = None;
+    for group in &groups {
+        match survivor {
+            Some(kept) if kept.telegram_chat_id == group.telegram_chat_id => actions.push(DedupeAction::Merge {
+                survivor: kept.clone(),
+                duplicate: group.clone(),
+            }),
+            _ => survivor = Some(group),
+        }
+    }
+    Ok(actions)
+}
+
+/// How to bring a chat's data from `old_chat_id` to `new_chat_id`, `None` if nothing needs doing
+///
+/// The group of the new chat ID survives, since that's the one the chat uses from now on.
+pub async fn plan_chat_id_migration(
+    pool: &SqlitePool,
+    old_chat_id: i64,
+    new_chat_id: i64,
+) -> Result<Option<DedupeAction>, sqlx::Error> {
+    if old_chat_id == new_chat_id {
+        return Ok(None);
+    }
+
+    let old = Group::find_by_chat_id(pool, old_chat_id).await?;
+    let new = Group::find_by_chat_id(pool, new_chat_id).await?;
+    Ok(match (old, new) {
+        (Some(duplicate), Some(survivor)) => Some(DedupeAction::Merge { survivor, duplicate }),
+        (Some(group), None) => Some(DedupeAction::Rechat { group, new_chat_id }),
+        (None, _) => None,
+    })
+}
+
+/// The survivor's settings after a merge
+///
+/// Settings the survivor still has at their defaults take the duplicate's value, privacy and
+/// notice go to the stricter of the two, and `created_at` to the earlier one. Long-range
+/// reminders, quiet hours and auto-confirm stay as the survivor has them if it has any. A group
+/// that opted out of "What's new" messages stays opted out, and the later announced version is kept.
+pub fn merge_group_settings(survivor: &Group, duplicate: &Group) -> Group {
+    fn prefer_changed<T: PartialEq + Clone>(kept: &T, other: &T, default: &T) -> T {
+        if kept == default { other.clone() } else { kept.clone() }
+    }
+
+    Group {
+        timezone: prefer_changed(&survivor.timezone, &duplicate.timezone, &DEFAULT_TIMEZONE.to_string()),
+        default_duration: prefer_changed(&survivor.default_duration, &duplicate.default_duration, &DEFAULT_DURATION),
+        reminder_hours: prefer_changed(&survivor.reminder_hours, &duplicate.reminder_hours, &DEFAULT_REMINDER_HOURS),
+        language: prefer_changed(&survivor.language, &duplicate.language, &DEFAULT_LANGUAGE.to_string()),
+        created_at: survivor.created_at.clone().min(duplicate.created_at.clone()),
+        store_usernames: survivor.store_usernames && duplicate.store_usernames,
+        min_notice_hours: survivor.min_notice_hours.max(duplicate.min_notice_hours),
+        long_range_every_days: survivor.long_range_every_days.or(duplicate.long_range_every_days),
+        long_range_until_days: if survivor.long_range_every_days.is_some() {
+            survivor.long_range_until_days
+        } else {
+            duplicate.long_range_until_days
+        },
+        quiet_start_hour: if survivor.quiet_start_hour.is_some() { survivor.quiet_start_hour } else { duplicate.quiet_start_hour },
+        quiet_end_hour: if survivor.quiet_start_hour.is_some() { survivor.quiet_end_hour } else { duplicate.quiet_end_hour },
+        announced_version: match (&survivor.announced_version, &duplicate.announced_version) {
+            (Some(kept), Some(other)) if changelog::is_newer(other, kept) => Some(other.clone()),
+            (kept, other) => kept.clone().or_else(|| other.clone()),
+        },
+        whats_new: survivor.whats_new && duplicate.whats_new,
+        autoconfirm_threshold: survivor.autoconfirm_threshold.or(duplicate.autoconfirm_threshold),
+        badges: survivor.badges && duplicate.badges,
+        ..survivor.clone()
+    }
+}
+
+/// Apply `action` in one transaction; with `dry_run` the transaction is rolled back afterwards
+pub async fn apply(pool: &SqlitePool, action: &DedupeAction, dry_run: bool) -> Result<DedupeReport, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut report = DedupeReport::default();
+
+    match action {
+        DedupeAction::Rechat { group, new_chat_id } => {
+            sqlx::query("UPDATE groups SET telegram_chat_id = ? WHERE id = ?")
+                .bind(new_chat_id)
+                .bind(group.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        DedupeAction::Merge { survivor, duplicate } => {
+            // Number the moved sessions after the survivor's so "#N" references stay unique
+            let short_id_offset: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(short_id), 0) FROM sessions WHERE group_id = ?")
+                .bind(survivor.id)
+                .fetch_one(&mut *tx)
+                .await?;
+            report.sessions_moved = sqlx::query("UPDATE sessions SET group_id = ?, short_id = short_id + ? WHERE group_id = ?")
+                .bind(survivor.id)
+                .bind(short_id_offset)
+                .bind(duplicate.id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+            report.admins_moved = sqlx::query("UPDATE OR IGNORE group_admins SET group_id = ? WHERE group_id = ?")
+                .bind(survivor.id)
+                .bind(duplicate.id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            sqlx::query("UPDATE OR IGNORE group_members SET group_id = ? WHERE group_id = ?")
+                .bind(survivor.id)
+                .bind(duplicate.id)
+                .execute(&mut *tx)
+                .await?;
+            // Duties the survivor has under the same name stay behind and are deleted with the
+            // duplicate, so their assignments move to the survivor's duty first
+            sqlx::query(
+                "UPDATE duty_assignments SET duty_id = (\
+                     SELECT kept.id FROM duties kept JOIN duties old ON old.id = duty_assignments.duty_id \
+                     WHERE kept.group_id = ? AND kept.name = old.name COLLATE NOCASE) \
+                 WHERE duty_id IN (\
+                     SELECT old.id FROM duties old JOIN duties kept ON kept.name = old.name COLLATE NOCASE \
+                     WHERE old.group_id = ? AND kept.group_id = ?)"
+            )
+            .bind(survivor.id)
+            .bind(duplicate.id)
+            .bind(survivor.id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("UPDATE OR IGNORE duties SET group_id = ? WHERE group_id = ?")
+                .bind(survivor.id)
+                .bind(duplicate.id)
+                .execute(&mut *tx)
+                .await?;
+            report.roster_moved = sqlx::query("UPDATE OR IGNORE roster_members SET group_id = ? WHERE group_id = ?")
+                .bind(survivor.id)
+                .bind(duplicate.id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+            // Pseudonyms hash user IDs with the group's key, so they can only move along with it
+            let survivor_key: Option<String> = sqlx::query_scalar("SELECT pseudonym_key FROM groups WHERE id = ?")
+                .bind(survivor.id)
+                .fetch_one(&mut *tx)
+                .await?;
+            if survivor_key.is_none() {
+                sqlx::query("UPDATE groups SET pseudonym_key = (SELECT pseudonym_key FROM groups WHERE id = ?) WHERE id = ?")
+                    .bind(duplicate.id)
+                    .bind(survivor.id)
+                    .execute(&mut *tx)
+                    .await?;
+                report.pseudonyms_moved = sqlx::query("UPDATE player_pseudonyms SET group_id = ? WHERE group_id = ?")
+                    .bind(survivor.id)
+                    .bind(duplicate.id)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            }
+
+            let merged = merge_group_settings(survivor, duplicate);
+            sqlx::query(
+                "UPDATE groups SET timezone = ?, default_duration = ?, reminder_hours = ?, language = ?, created_at = ?, \
+                 store_usernames = ?, min_notice_hours = ?, long_range_every_days = ?, long_range_until_days = ?, \
+                 quiet_start_hour = ?, quiet_end_hour = ?, announced_version = ?, whats_new = ?, autoconfirm_threshold = ?, badges = ? WHERE id = ?"
+            )
+            .bind(&merged.timezone)
+            .bind(merged.default_duration)
+            .bind(merged.reminder_hours)
+            .bind(&merged.language)
+            .bind(&merged.created_at)
+            .bind(merged.store_usernames)
+            .bind(merged.min_notice_hours)
+            .bind(merged.long_range_every_days)
+            .bind(merged.long_range_until_days)
+            .bind(merged.quiet_start_hour)
+            .bind(merged.quiet_end_hour)
+            .bind(&merged.announced_version)
+            .bind(merged.whats_new)
+            .bind(merged.autoconfirm_threshold)
+            .bind(merged.badges)
+            .bind(survivor.id)
+            .execute(&mut *tx)
+            .await?;
+
+            // Whatever didn't move (duplicate admins, the undo record, ...) goes with the group
+            for table in ["group_admins", "roster_members", "group_members", "duties", "player_pseudonyms", "undo_actions", "reminder_deliveries"] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE group_id = ?"))
+                    .bind(duplicate.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            sqlx::query("DELETE FROM groups WHERE id = ?")
+                .bind(duplicate.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(report)
+}
@@ -1,2 +1,6 @@
 pub mod connection;
+#[allow(dead_code)] // Only used by the migrate binary
+pub mod dedupe;
 pub mod models;
+pub mod storage;
+pub mod transfer;
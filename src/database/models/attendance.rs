@@ -0,0 +1,125 @@
+//! Who actually came to a confirmed session, and how reliably each player shows up
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Whether a player came to a session, as marked with `/attended`
+///
+/// Players who voted yes for the confirmed time but weren't marked get `attended: false`.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct Attendance {
+    /// The session
+    pub session_id: String,
+    /// The player
+    pub user_id: i64,
+    /// Whether they came; `false` means they said yes and didn't show
+    pub attended: bool,
+    /// Who marked it
+    pub marked_by: i64,
+    /// When it was marked
+    pub marked_at: String,
+}
+
+/// How often a player who said yes to a session's confirmed time came to it
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct Reliability {
+    /// The player's Telegram user ID
+    pub user_id: i64,
+    /// Sessions with attendance marked that they said yes to
+    pub said_yes: i64,
+    /// How many of those they came to
+    pub attended: i64,
+}
+
+impl Reliability {
+    /// `attended` out of `said_yes`, as a whole percentage
+    pub fn percentage(&self) -> i64 {
+        if self.said_yes == 0 {
+            return 0;
+        }
+        (self.attended * 100 + self.said_yes / 2) / self.said_yes
+    }
+}
+
+impl Attendance {
+    /// Record that `attended` came to the session, replacing what was marked for it before
+    ///
+    /// Everyone who voted yes for the confirmed time and isn't in `attended` is recorded as a
+    /// no-show. Returns the session's attendance as now stored.
+    pub async fn mark(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        attended: &[i64],
+        marked_by: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM attendance WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for user_id in attended {
+            sqlx::query(
+                "INSERT OR IGNORE INTO attendance (session_id, user_id, attended, marked_by, marked_at) VALUES (?, ?, 1, ?, ?)"
+            )
+            .bind(session_id)
+            .bind(user_id)
+            .bind(marked_by)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(
+            "INSERT OR IGNORE INTO attendance (session_id, user_id, attended, marked_by, marked_at) \
+             SELECT r.session_id, r.user_id, 0, ?, ? FROM responses r \
+             JOIN session_options o ON o.id = r.option_id \
+             WHERE r.session_id = ? AND o.confirmed = 1 AND r.response = 'yes'"
+        )
+        .bind(marked_by)
+        .bind(&now)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Self::find_by_session(pool, session_id).await
+    }
+
+    /// The attendance marked for a session, those who came first
+    pub async fn find_by_session(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Attendance>(
+            "SELECT session_id, user_id, attended, marked_by, marked_at FROM attendance WHERE session_id = ? \
+             ORDER BY attended DESC, user_id"
+        )
+        .bind(session_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Each player's reliability over the group's sessions with attendance marked, most reliable
+    /// first
+    ///
+    /// Only sessions they said yes to count, so coming without a vote neither helps nor hurts.
+    pub async fn reliability_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Reliability>, sqlx::Error> {
+        sqlx::query_as::<_, Reliability>(
+            "SELECT a.user_id, COUNT(*) AS said_yes, SUM(a.attended) AS attended \
+             FROM attendance a JOIN sessions s ON s.id = a.session_id \
+             WHERE s.group_id = ? AND EXISTS ( \
+                 SELECT 1 FROM responses r JOIN session_options o ON o.id = r.option_id \
+                 WHERE r.session_id = a.session_id AND r.user_id = a.user_id AND o.confirmed = 1 AND r.response = 'yes') \
+             GROUP BY a.user_id \
+             ORDER BY SUM(a.attended) * 1.0 / COUNT(*) DESC, COUNT(*) DESC, a.user_id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,51 @@
+//! Game day check-ins: players confirming they're actually coming to a confirmed session
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A player's check-in for a session
+///
+/// Only the user ID is kept; names are resolved like votes so username privacy applies.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct CheckIn {
+    /// The session checked in for
+    pub session_id: String,
+    /// Who checked in
+    pub user_id: i64,
+    /// When they first tapped "📍 Check in"
+    pub checked_in_at: String,
+}
+
+impl CheckIn {
+    /// Record a check-in; returns `false` if the user had already checked in
+    pub async fn record(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        user_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO check_ins (session_id, user_id, checked_in_at) VALUES (?, ?, ?)"
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// All check-ins for a session, earliest first
+    pub async fn find_by_session(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, CheckIn>(
+            "SELECT session_id, user_id, checked_in_at FROM check_ins WHERE session_id = ? ORDER BY checked_in_at, user_id"
+        )
+        .bind(session_id)
+        .fetch_all(pool)
+        .await
+    }
+}
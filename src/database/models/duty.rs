@@ -0,0 +1,142 @@
+//! Duties a group rotates between sessions, like hosting or bringing snacks, and who had them
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A chore handed to one of the players of every confirmed session
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct Duty {
+    /// Unique ID, referenced by the assignments
+    pub id: i64,
+    /// The group that rotates it
+    pub group_id: i64,
+    /// The name it was added under, e.g. "hosting"
+    pub name: String,
+    /// When it was added
+    pub created_at: String,
+}
+
+/// Who got a duty for a session
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct DutyAssignment {
+    /// Increases with every assignment, so it orders them
+    pub id: i64,
+    /// The confirmed session
+    pub session_id: String,
+    /// The duty
+    pub duty_id: i64,
+    /// The player who has it
+    pub user_id: i64,
+    /// When it was assigned
+    pub assigned_at: String,
+}
+
+impl Duty {
+    /// Add a duty to the group's rotation; returns `false` if it has one of that name (ignoring
+    /// case) already
+    pub async fn add(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        name: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO duties (group_id, name, created_at) VALUES (?, ?, ?)"
+        )
+        .bind(group_id)
+        .bind(name)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Take a duty out of the rotation along with its past assignments; returns `false` if the
+    /// group has no duty of that name
+    pub async fn remove(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        name: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM duties WHERE group_id = ? AND name = ? COLLATE NOCASE")
+            .bind(group_id)
+            .bind(name)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The group's duties in the order they were added
+    pub async fn find_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Duty>(
+            "SELECT id, group_id, name, created_at FROM duties WHERE group_id = ? ORDER BY id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+impl DutyAssignment {
+    /// Record who has which duty for a session, as `(duty_id, user_id)` pairs, replacing what
+    /// was assigned for it before
+    pub async fn save(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        assignments: &[(i64, i64)],
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM duty_assignments WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for (duty_id, user_id) in assignments {
+            sqlx::query(
+                "INSERT INTO duty_assignments (session_id, duty_id, user_id, assigned_at) VALUES (?, ?, ?, ?)"
+            )
+            .bind(session_id)
+            .bind(duty_id)
+            .bind(user_id)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    /// A session's duties and who has them, in the order the duties were added
+    pub async fn find_by_session(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, DutyAssignment>(
+            "SELECT id, session_id, duty_id, user_id, assigned_at FROM duty_assignments \
+             WHERE session_id = ? ORDER BY duty_id"
+        )
+        .bind(session_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Every assignment of the group's duties, oldest first
+    pub async fn find_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, DutyAssignment>(
+            "SELECT a.id, a.session_id, a.duty_id, a.user_id, a.assigned_at FROM duty_assignments a \
+             JOIN duties d ON d.id = a.duty_id WHERE d.group_id = ? ORDER BY a.id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -1,6 +1,26 @@
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
+use crate::services::timezone::utc_offset;
+use crate::utils::{changelog::CURRENT_VERSION, i18n::{self, Lang}};
+
+/// Shortest default session duration a group can configure, in minutes
+pub const MIN_DEFAULT_DURATION: i64 = 15;
+/// Longest default session duration a group can configure, in minutes
+pub const MAX_DEFAULT_DURATION: i64 = 720;
+/// Longest minimum notice period a group can configure, in hours
+pub const MAX_MIN_NOTICE_HOURS: i64 = 168;
+/// Shortest and longest gap between two long-range reminders, in days
+pub const LONG_RANGE_EVERY_DAYS: std::ops::RangeInclusive<i64> = 7..=90;
+/// How close to the start long-range reminders may continue, in days before it
+pub const LONG_RANGE_UNTIL_DAYS: std::ops::RangeInclusive<i64> = 1..=365;
+/// Where long-range reminders stop unless the group says otherwise: when the 2 week reminder takes over
+pub const DEFAULT_LONG_RANGE_UNTIL_DAYS: i64 = 14;
+/// The hours of the day quiet hours can start and end at
+pub const QUIET_HOURS: std::ops::RangeInclusive<i64> = 0..=23;
+/// How many "yes" votes a group can require before an option is confirmed automatically
+pub const AUTOCONFIRM_THRESHOLDS: std::ops::RangeInclusive<i64> = 2..=20;
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Group {
@@ -10,21 +30,224 @@ pub struct Group {
     pub default_duration: i64, // minutes
     pub reminder_hours: i64,
     pub created_at: String,
+    /// Whether voters' Telegram usernames are kept; when off they show as "Player #N"
+    pub store_usernames: bool,
+    /// Options must start at least this many hours after scheduling (0 = no restriction)
+    pub min_notice_hours: i64,
+    /// Language of the bot's replies, one of `i18n::SUPPORTED_LANGUAGES`
+    pub language: String,
+    /// Days between the extra reminders for sessions far ahead, `None` when they're off
+    pub long_range_every_days: Option<i64>,
+    /// Days before the start at which the extra reminders stop
+    pub long_range_until_days: i64,
+    /// First hour, on the group's clock, in which reminders are held back; `None` when quiet hours are off
+    pub quiet_start_hour: Option<i64>,
+    /// Hour at which the quiet hours end and held reminders go out
+    pub quiet_end_hour: Option<i64>,
+    /// Bot version the group last got a "What's new" message for, `None` for groups that predate them
+    pub announced_version: Option<String>,
+    /// Whether the group gets a "What's new" message after the bot is upgraded
+    #[serde(default = "default_whats_new")]
+    pub whats_new: bool,
+    /// "Yes" votes at which an option of a poll with a deadline is confirmed automatically,
+    /// `None` when auto-confirm is off
+    #[serde(default)]
+    pub autoconfirm_threshold: Option<i64>,
+    /// Whether `/stats` shows the group's achievement badges
+    #[serde(default = "default_badges")]
+    pub badges: bool,
+}
+
+/// Archives written before groups could opt out of "What's new" messages had them on
+fn default_whats_new() -> bool {
+    true
+}
+
+/// Archives written before badges existed come from groups that had them on
+fn default_badges() -> bool {
+    true
+}
+
+/// A group setting with its new value, for [`Group::update_setting`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupSetting {
+    /// The group's clock, a fixed UTC offset such as "UTC+2" (see [`utc_offset`])
+    Timezone(String),
+    /// Minutes given to new session options
+    DefaultDuration(i64),
+    /// Hours ahead new session options must start (0 = no restriction)
+    MinNoticeHours(i64),
+    /// "Yes" votes that confirm an option automatically, `None` to turn auto-confirm off
+    AutoconfirmThreshold(Option<i64>),
+}
+
+/// Errors that can occur when changing a group setting
+#[derive(Debug)]
+pub enum GroupSettingError {
+    /// The requested default duration is outside `MIN_DEFAULT_DURATION..=MAX_DEFAULT_DURATION`
+    DurationOutOfRange(i64),
+    /// The requested minimum notice is outside `0..=MAX_MIN_NOTICE_HOURS`
+    MinNoticeOutOfRange(i64),
+    /// The requested timezone isn't a UTC offset the bot understands
+    UnknownTimezone(String),
+    /// The requested language isn't one of `i18n::SUPPORTED_LANGUAGES`
+    UnsupportedLanguage(String),
+    /// The requested long-range reminder gap is outside `LONG_RANGE_EVERY_DAYS`
+    LongRangeEveryOutOfRange(i64),
+    /// The requested end of long-range reminders is outside `LONG_RANGE_UNTIL_DAYS`
+    LongRangeUntilOutOfRange(i64),
+    /// A requested quiet hour is outside `QUIET_HOURS`
+    QuietHourOutOfRange(i64),
+    /// The requested quiet hours start and end at the same hour
+    QuietHoursEmpty,
+    /// The requested auto-confirm threshold is outside `AUTOCONFIRM_THRESHOLDS`
+    AutoconfirmThresholdOutOfRange(i64),
+    /// The underlying database query failed
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for GroupSettingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GroupSettingError::DurationOutOfRange(minutes) => write!(
+                f,
+                "Default duration must be between {MIN_DEFAULT_DURATION} and {MAX_DEFAULT_DURATION} minutes (got {minutes})"
+            ),
+            GroupSettingError::MinNoticeOutOfRange(hours) => write!(
+                f,
+                "Minimum notice must be between 0 and {MAX_MIN_NOTICE_HOURS} hours (got {hours})"
+            ),
+            GroupSettingError::UnknownTimezone(timezone) => write!(
+                f,
+                "Timezone must be a UTC offset such as UTC, UTC+2 or UTC-5:30 (got '{timezone}')"
+            ),
+            GroupSettingError::UnsupportedLanguage(code) => write!(
+                f,
+                "Language must be one of {} (got '{code}')",
+                i18n::SUPPORTED_LANGUAGES.join(", ")
+            ),
+            GroupSettingError::LongRangeEveryOutOfRange(days) => write!(
+                f,
+                "Long-range reminders must be between {} and {} days apart (got {days})",
+                LONG_RANGE_EVERY_DAYS.start(),
+                LONG_RANGE_EVERY_DAYS.end()
+            ),
+            GroupSettingError::LongRangeUntilOutOfRange(days) => write!(
+                f,
+                "Long-range reminders must stop between {} and {} days before the session (got {days})",
+                LONG_RANGE_UNTIL_DAYS.start(),
+                LONG_RANGE_UNTIL_DAYS.end()
+            ),
+            GroupSettingError::QuietHourOutOfRange(hour) => write!(
+                f,
+                "Quiet hours must start and end between {} and {} (got {hour})",
+                QUIET_HOURS.start(),
+                QUIET_HOURS.end()
+            ),
+            GroupSettingError::QuietHoursEmpty => write!(f, "Quiet hours must end at a different hour than they start"),
+            GroupSettingError::AutoconfirmThresholdOutOfRange(votes) => write!(
+                f,
+                "Auto-confirm needs between {} and {} yes votes (got {votes})",
+                AUTOCONFIRM_THRESHOLDS.start(),
+                AUTOCONFIRM_THRESHOLDS.end()
+            ),
+            GroupSettingError::Database(e) => write!(f, "Database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for GroupSettingError {}
+
+impl From<sqlx::Error> for GroupSettingError {
+    fn from(e: sqlx::Error) -> Self {
+        GroupSettingError::Database(e)
+    }
 }
 
 impl Group {
+    /// The language of the bot's replies in this group, English if the stored code is unknown
+    pub fn lang(&self) -> Lang {
+        i18n::normalize(&self.language)
+    }
+
     pub async fn find_by_chat_id(
         pool: &sqlx::SqlitePool,
         chat_id: i64,
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as::<_, Group>(
-            "SELECT id, telegram_chat_id, timezone, default_duration, reminder_hours, created_at FROM groups WHERE telegram_chat_id = ?"
+            "SELECT id, telegram_chat_id, timezone, default_duration, reminder_hours, created_at, store_usernames, min_notice_hours, language, long_range_every_days, long_range_until_days, quiet_start_hour, quiet_end_hour, announced_version, whats_new, autoconfirm_threshold, badges FROM groups WHERE telegram_chat_id = ?"
         )
         .bind(chat_id)
         .fetch_optional(pool)
         .await
     }
 
+    /// Look up a group by its internal ID (`sessions.group_id`)
+    pub async fn find_by_id(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Group>(
+            "SELECT id, telegram_chat_id, timezone, default_duration, reminder_hours, created_at, store_usernames, min_notice_hours, language, long_range_every_days, long_range_until_days, quiet_start_hour, quiet_end_hour, announced_version, whats_new, autoconfirm_threshold, badges FROM groups WHERE id = ?"
+        )
+        .bind(group_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The groups with IDs in `group_ids`, keyed by ID; unknown IDs are left out
+    pub async fn find_by_ids(
+        pool: &sqlx::SqlitePool,
+        group_ids: &[i64],
+    ) -> Result<HashMap<i64, Self>, sqlx::Error> {
+        if group_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let placeholders = group_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!(
+            "SELECT id, telegram_chat_id, timezone, default_duration, reminder_hours, created_at, store_usernames, min_notice_hours, language, long_range_every_days, long_range_until_days, quiet_start_hour, quiet_end_hour, announced_version, whats_new, autoconfirm_threshold, badges FROM groups WHERE id IN ({placeholders})"
+        );
+        let mut query_builder = sqlx::query_as::<_, Group>(&query);
+        for group_id in group_ids {
+            query_builder = query_builder.bind(group_id);
+        }
+
+        Ok(query_builder.fetch_all(pool).await?
+            .into_iter()
+            .map(|group| (group.id, group))
+            .collect())
+    }
+
+    /// Every group, oldest first
+    pub async fn find_all(pool: &sqlx::SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Group>(
+            "SELECT id, telegram_chat_id, timezone, default_duration, reminder_hours, created_at, store_usernames, min_notice_hours, language, long_range_every_days, long_range_until_days, quiet_start_hour, quiet_end_hour, announced_version, whats_new, autoconfirm_threshold, badges FROM groups ORDER BY id"
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// IDs of the groups `user_id` is known to belong to: they voted there, joined the roster,
+    /// started a session or are an active member
+    pub async fn find_ids_with_member(
+        pool: &sqlx::SqlitePool,
+        user_id: i64,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT s.group_id FROM responses r JOIN sessions s ON r.session_id = s.id WHERE r.user_id = ? \
+             UNION SELECT group_id FROM roster_members WHERE user_id = ? \
+             UNION SELECT group_id FROM sessions WHERE created_by = ? \
+             UNION SELECT group_id FROM group_members WHERE user_id = ? AND active = 1"
+        )
+        .bind(user_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn create(
         pool: &sqlx::SqlitePool,
         chat_id: i64,
@@ -32,11 +255,12 @@ impl Group {
         let now = Utc::now();
         sqlx::query!(
             r#"
-            INSERT INTO groups (telegram_chat_id, timezone, default_duration, reminder_hours, created_at)
-            VALUES (?, 'UTC', 240, 24, ?)
+            INSERT INTO groups (telegram_chat_id, timezone, default_duration, reminder_hours, created_at, announced_version)
+            VALUES (?, 'UTC', 240, 24, ?, ?)
             "#,
             chat_id,
-            now
+            now,
+            CURRENT_VERSION
         )
         .execute(pool)
         .await?;
@@ -46,4 +270,222 @@ impl Group {
             .await?
             .ok_or_else(|| sqlx::Error::RowNotFound)
     }
+
+    /// Check `setting`'s new value and save it for the group
+    pub async fn update_setting(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        setting: &GroupSetting,
+    ) -> Result<(), GroupSettingError> {
+        let query = match setting {
+            GroupSetting::Timezone(timezone) => {
+                if utc_offset(timezone).is_none() {
+                    return Err(GroupSettingError::UnknownTimezone(timezone.clone()));
+                }
+                sqlx::query("UPDATE groups SET timezone = ? WHERE id = ?").bind(timezone.clone())
+            }
+            GroupSetting::DefaultDuration(minutes) => {
+                if !(MIN_DEFAULT_DURATION..=MAX_DEFAULT_DURATION).contains(minutes) {
+                    return Err(GroupSettingError::DurationOutOfRange(*minutes));
+                }
+                sqlx::query("UPDATE groups SET default_duration = ? WHERE id = ?").bind(*minutes)
+            }
+            GroupSetting::MinNoticeHours(hours) => {
+                if !(0..=MAX_MIN_NOTICE_HOURS).contains(hours) {
+                    return Err(GroupSettingError::MinNoticeOutOfRange(*hours));
+                }
+                sqlx::query("UPDATE groups SET min_notice_hours = ? WHERE id = ?").bind(*hours)
+            }
+            GroupSetting::AutoconfirmThreshold(votes) => {
+                if let Some(votes) = votes.filter(|votes| !AUTOCONFIRM_THRESHOLDS.contains(votes)) {
+                    return Err(GroupSettingError::AutoconfirmThresholdOutOfRange(votes));
+                }
+                sqlx::query("UPDATE groups SET autoconfirm_threshold = ? WHERE id = ?").bind(*votes)
+            }
+        };
+        query.bind(group_id).execute(pool).await?;
+        
+        Ok(())
+    }
+
+    /// Set the duration (in minutes) given to new session options in this group
+    pub async fn update_default_duration(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        minutes: i64,
+    ) -> Result<(), GroupSettingError> {
+        Self::update_setting(pool, group_id, &GroupSetting::DefaultDuration(minutes)).await
+    }
+
+    /// Set how many hours ahead new session options must be (0 = no restriction)
+    pub async fn update_min_notice_hours(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        hours: i64,
+    ) -> Result<(), GroupSettingError> {
+        Self::update_setting(pool, group_id, &GroupSetting::MinNoticeHours(hours)).await
+    }
+
+    /// Set the language of the bot's replies in this group
+    pub async fn update_language(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        language: &str,
+    ) -> Result<(), GroupSettingError> {
+        if !i18n::is_supported(language) {
+            return Err(GroupSettingError::UnsupportedLanguage(language.to_string()));
+        }
+        
+        sqlx::query!(
+            "UPDATE groups SET language = ? WHERE id = ?",
+            language,
+            group_id
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    /// Send an extra reminder every `every_days` days for sessions far ahead, until `until_days`
+    /// before the start; `None` turns them off again
+    pub async fn update_long_range_reminders(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        cadence: Option<(i64, i64)>,
+    ) -> Result<(), GroupSettingError> {
+        if let Some((every_days, until_days)) = cadence {
+            if !LONG_RANGE_EVERY_DAYS.contains(&every_days) {
+                return Err(GroupSettingError::LongRangeEveryOutOfRange(every_days));
+            }
+            if !LONG_RANGE_UNTIL_DAYS.contains(&until_days) {
+                return Err(GroupSettingError::LongRangeUntilOutOfRange(until_days));
+            }
+        }
+        
+        let every_days = cadence.map(|(every_days, _)| every_days);
+        let until_days = cadence.map_or(DEFAULT_LONG_RANGE_UNTIL_DAYS, |(_, until_days)| until_days);
+        sqlx::query!(
+            "UPDATE groups SET long_range_every_days = ?, long_range_until_days = ? WHERE id = ?",
+            every_days,
+            until_days,
+            group_id
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    /// Hold reminders back from `start_hour` until `end_hour` on the group's clock, wrapping
+    /// around midnight if `start_hour` is later; `None` turns quiet hours off again
+    pub async fn update_quiet_hours(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        hours: Option<(i64, i64)>,
+    ) -> Result<(), GroupSettingError> {
+        if let Some((start_hour, end_hour)) = hours {
+            if let Some(hour) = [start_hour, end_hour].into_iter().find(|hour| !QUIET_HOURS.contains(hour)) {
+                return Err(GroupSettingError::QuietHourOutOfRange(hour));
+            }
+            if start_hour == end_hour {
+                return Err(GroupSettingError::QuietHoursEmpty);
+            }
+        }
+        
+        sqlx::query("UPDATE groups SET quiet_start_hour = ?, quiet_end_hour = ? WHERE id = ?")
+            .bind(hours.map(|(start_hour, _)| start_hour))
+            .bind(hours.map(|(_, end_hour)| end_hour))
+            .bind(group_id)
+            .execute(pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    /// Turn the "What's new" messages after upgrades on or off for a group
+    pub async fn update_whats_new(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        whats_new: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE groups SET whats_new = ? WHERE id = ?")
+            .bind(whats_new)
+            .bind(group_id)
+            .execute(pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    /// Show or hide the achievement badges in a group's `/stats`
+    pub async fn update_badges(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        badges: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE groups SET badges = ? WHERE id = ?")
+            .bind(badges)
+            .bind(group_id)
+            .execute(pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    /// Record that the group has heard about `version`, if its announced version is still `previous`
+    ///
+    /// Returns whether this call made the change, so of two handlers racing to announce the same
+    /// upgrade only one gets to send the message.
+    pub async fn mark_announced(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        previous: Option<&str>,
+        version: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE groups SET announced_version = ? WHERE id = ? AND announced_version IS ?")
+            .bind(version)
+            .bind(group_id)
+            .bind(previous)
+            .execute(pool)
+            .await?;
+        
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Turn username storage on or off for a group
+    ///
+    /// Turning it off also erases the usernames already stored with the group's votes and
+    /// members, so turning it back on later can't reveal who voted while it was off.
+    pub async fn update_store_usernames(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        store_usernames: bool,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        
+        sqlx::query!(
+            "UPDATE groups SET store_usernames = ? WHERE id = ?",
+            store_usernames,
+            group_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        if !store_usernames {
+            sqlx::query!(
+                "UPDATE responses SET username = NULL WHERE session_id IN (SELECT id FROM sessions WHERE group_id = ?)",
+                group_id
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("UPDATE group_members SET username = NULL, display_name = NULL WHERE group_id = ?")
+                .bind(group_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(())
+    }
 }
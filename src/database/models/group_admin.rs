@@ -0,0 +1,85 @@
+//! Per-group bot admins, who may manage sessions they didn't create
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A user granted session management rights in a group through `/settings`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct GroupAdmin {
+    /// The group the rights apply to
+    pub group_id: i64,
+    /// The Telegram user ID of the bot admin
+    pub user_id: i64,
+    /// Who granted the rights
+    pub added_by: i64,
+    /// When the rights were granted
+    pub created_at: String,
+}
+
+impl GroupAdmin {
+    /// Make `user_id` a bot admin of the group; returns `false` if they already were one
+    pub async fn add(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        user_id: i64,
+        added_by: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO group_admins (group_id, user_id, added_by, created_at) VALUES (?, ?, ?, ?)"
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .bind(added_by)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// Revoke `user_id`'s bot admin rights; returns `false` if they had none
+    pub async fn remove(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        user_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM group_admins WHERE group_id = ? AND user_id = ?")
+            .bind(group_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// The group's bot admins, oldest first
+    pub async fn find_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, GroupAdmin>(
+            "SELECT group_id, user_id, added_by, created_at FROM group_admins WHERE group_id = ? ORDER BY created_at, user_id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+    
+    /// Whether `user_id` is a bot admin of the group
+    pub async fn is_admin(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        user_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM group_admins WHERE group_id = ? AND user_id = ?"
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+        
+        Ok(count > 0)
+    }
+}
@@ -0,0 +1,141 @@
+//! Who belongs to each group, so polls can be measured against everyone who could vote
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A member of a group's chat, as far as the bot has seen
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct GroupMember {
+    /// The group
+    pub group_id: i64,
+    /// The member's Telegram user ID
+    pub user_id: i64,
+    /// Their @username, `None` if unknown or the group doesn't store usernames
+    pub username: Option<String>,
+    /// Their first and last name, on the same terms as `username`
+    pub display_name: Option<String>,
+    /// Whether they're still in the chat; members who left or were removed are kept inactive
+    pub active: bool,
+    /// When they were last seen doing something in the chat
+    pub last_seen: String,
+}
+
+/// How much of a group's membership takes part in its polls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromRow)]
+pub struct Turnout {
+    /// Active members of the group
+    pub members: i64,
+    /// Polls with at least one vote
+    pub polls: i64,
+    /// Votes cast on those polls by active members, one per member and poll
+    pub votes: i64,
+}
+
+impl Turnout {
+    /// The share of active members voting on an average poll, as a whole percentage; `None`
+    /// while there are no members or polls to go by
+    pub fn percentage(&self) -> Option<i64> {
+        let possible = self.members * self.polls;
+        (possible > 0).then(|| (self.votes * 100 + possible / 2) / possible)
+    }
+}
+
+impl GroupMember {
+    /// Count `user_id` as an active member of the group in chat `chat_id`, seen just now
+    ///
+    /// Names are only kept if the group stores usernames. Returns `false` if the chat has no
+    /// group.
+    pub async fn record_activity(
+        pool: &sqlx::SqlitePool,
+        chat_id: i64,
+        user_id: i64,
+        username: Option<&str>,
+        display_name: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO group_members (group_id, user_id, username, display_name, active, last_seen) \
+             SELECT id, ?, CASE WHEN store_usernames THEN ? END, CASE WHEN store_usernames THEN ? END, 1, ? \
+             FROM groups WHERE telegram_chat_id = ? \
+             ON CONFLICT(group_id, user_id) DO UPDATE SET \
+                 username = COALESCE(excluded.username, group_members.username), \
+                 display_name = COALESCE(excluded.display_name, group_members.display_name), \
+                 active = 1, \
+                 last_seen = excluded.last_seen"
+        )
+        .bind(user_id)
+        .bind(username)
+        .bind(display_name)
+        .bind(Utc::now().to_rfc3339())
+        .bind(chat_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record that `user_id` left the group in chat `chat_id` or was removed from it
+    ///
+    /// Returns `false` if they weren't an active member.
+    pub async fn mark_left(
+        pool: &sqlx::SqlitePool,
+        chat_id: i64,
+        user_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE group_members SET active = 0 \
+             WHERE user_id = ? AND active = 1 AND group_id IN (SELECT id FROM groups WHERE telegram_chat_id = ?)"
+        )
+        .bind(user_id)
+        .bind(chat_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The group's active members, most recently seen first
+    pub async fn find_active_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, GroupMember>(
+            "SELECT group_id, user_id, username, display_name, active, last_seen FROM group_members \
+             WHERE group_id = ? AND active = 1 ORDER BY last_seen DESC, user_id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// How many active members the group has
+    pub async fn count_active(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM group_members WHERE group_id = ? AND active = 1")
+            .bind(group_id)
+            .fetch_one(pool)
+            .await
+    }
+
+    /// How many of the group's active members vote on its polls, leaving out cancelled ones
+    pub async fn turnout(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Turnout, sqlx::Error> {
+        sqlx::query_as::<_, Turnout>(
+            "SELECT \
+                 (SELECT COUNT(*) FROM group_members WHERE group_id = ?1 AND active = 1) AS members, \
+                 (SELECT COUNT(DISTINCT r.session_id) FROM responses r JOIN sessions s ON s.id = r.session_id \
+                  WHERE s.group_id = ?1 AND s.status != 'cancelled') AS polls, \
+                 (SELECT COUNT(DISTINCT r.session_id || ':' || r.user_id) FROM responses r \
+                  JOIN sessions s ON s.id = r.session_id \
+                  JOIN group_members m ON m.group_id = s.group_id AND m.user_id = r.user_id AND m.active = 1 \
+                  WHERE s.group_id = ?1 AND s.status != 'cancelled') AS votes"
+        )
+        .bind(group_id)
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -1,9 +1,35 @@
 pub mod group;
 pub mod session;
 pub mod response;
+pub mod response_note;
 pub mod reminder;
+pub mod pseudonym;
+pub mod undo;
+pub mod group_admin;
+pub mod check_in;
+pub mod roster;
+pub mod outbound;
+pub mod user_profile;
+pub mod attendance;
+pub mod group_member;
+pub mod duty;
+pub mod rollcall;
+pub mod pending_session;
 
 pub use group::*;
 pub use session::*;
 pub use response::*;
+pub use response_note::*;
 pub use reminder::*;
+pub use pseudonym::*;
+pub use undo::*;
+pub use group_admin::*;
+pub use check_in::*;
+pub use roster::*;
+pub use outbound::*;
+pub use user_profile::*;
+pub use attendance::*;
+pub use group_member::*;
+pub use duty::*;
+pub use rollcall::*;
+pub use pending_session::*;
@@ -0,0 +1,77 @@
+//! Log of the messages the bot sent, kept to settle "the bot never told us" disputes
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// How many log rows are kept; older ones are trimmed by the housekeeping job
+pub const OUTBOUND_LOG_MAX_ROWS: i64 = 10_000;
+
+/// One message the bot sent or failed to send
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct OutboundMessage {
+    /// Insertion order
+    pub id: i64,
+    /// The Telegram chat it went to
+    pub chat_id: i64,
+    /// What it was, e.g. "reminder", "poll" or "confirmation"
+    pub kind: String,
+    /// The session it was about, if any
+    pub session_id: Option<String>,
+    /// When the send was attempted
+    pub sent_at: String,
+    /// Whether Telegram accepted it
+    pub success: bool,
+    /// Telegram's error if it didn't
+    pub error: Option<String>,
+}
+
+impl OutboundMessage {
+    /// Record a send attempt; `error` is `None` if it went through
+    pub async fn record(
+        pool: &sqlx::SqlitePool,
+        chat_id: i64,
+        kind: &str,
+        session_id: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO outbound_messages (chat_id, kind, session_id, sent_at, success, error) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(chat_id)
+        .bind(kind)
+        .bind(session_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(error.is_none())
+        .bind(error)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Everything sent about a session, oldest first
+    pub async fn find_by_session(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, OutboundMessage>(
+            "SELECT id, chat_id, kind, session_id, sent_at, success, error FROM outbound_messages WHERE session_id = ? ORDER BY id"
+        )
+        .bind(session_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Delete all but the newest `keep` rows; returns how many were removed
+    pub async fn trim(pool: &sqlx::SqlitePool, keep: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM outbound_messages WHERE id <= (SELECT id FROM outbound_messages ORDER BY id DESC LIMIT 1 OFFSET ?)"
+        )
+        .bind(keep)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,105 @@
+//! Sessions `/schedule` holds back until the scheduler confirms they aren't a duplicate
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// How long a held-back session waits for "Create anyway" before it's forgotten
+pub const PENDING_SESSION_HOURS: i64 = 24;
+
+/// One time of a held-back session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingOption {
+    /// When the option starts
+    pub starts_at: DateTime<Utc>,
+    /// Its length in minutes, `None` for the group's default
+    pub duration: Option<i64>,
+}
+
+/// A session `/schedule` didn't create yet because the group seems to have it already
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PendingSession {
+    /// ID used in the "Create anyway" and "Cancel" buttons
+    pub id: String,
+    /// The group it's for
+    pub group_id: i64,
+    /// Who ran `/schedule`; only they can settle it
+    pub created_by: i64,
+    /// The session's title
+    pub title: String,
+    /// Whether votes stay hidden until it's confirmed
+    pub anonymous: bool,
+    /// Its times as a JSON array of [`PendingOption`]
+    pub options: String,
+    /// When it was held back
+    pub created_at: String,
+}
+
+impl PendingSession {
+    /// Hold back a session, forgetting any that waited longer than `PENDING_SESSION_HOURS`
+    pub async fn create(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        created_by: i64,
+        title: &str,
+        anonymous: bool,
+        options: &[PendingOption],
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query("DELETE FROM pending_sessions WHERE created_at < ?")
+            .bind((now - Duration::hours(PENDING_SESSION_HOURS)).to_rfc3339())
+            .execute(pool)
+            .await?;
+
+        let pending = PendingSession {
+            id: Uuid::new_v4().to_string(),
+            group_id,
+            created_by,
+            title: title.to_string(),
+            anonymous,
+            options: serde_json::to_string(options).map_err(|e| sqlx::Error::Protocol(e.to_string()))?,
+            created_at: now.to_rfc3339(),
+        };
+        sqlx::query(
+            "INSERT INTO pending_sessions (id, group_id, created_by, title, anonymous, options, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&pending.id)
+        .bind(pending.group_id)
+        .bind(pending.created_by)
+        .bind(&pending.title)
+        .bind(pending.anonymous)
+        .bind(&pending.options)
+        .bind(&pending.created_at)
+        .execute(pool)
+        .await?;
+
+        Ok(pending)
+    }
+
+    /// Look up a held-back session by ID
+    pub async fn find_by_id(pool: &sqlx::SqlitePool, id: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, PendingSession>(
+            "SELECT id, group_id, created_by, title, anonymous, options, created_at FROM pending_sessions WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Remove a held-back session; returns whether this call removed it, so a double tap on
+    /// "Create anyway" creates the session only once
+    pub async fn delete(pool: &sqlx::SqlitePool, id: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM pending_sessions WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Its times, empty if the stored JSON is unreadable
+    pub fn parsed_options(&self) -> Vec<PendingOption> {
+        serde_json::from_str(&self.options).unwrap_or_default()
+    }
+}
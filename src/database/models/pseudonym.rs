@@ -0,0 +1,92 @@
+//! Stable "Player #N" pseudonyms for groups that opt out of storing usernames
+//!
+//! Users are identified in `player_pseudonyms` by a keyed hash of their Telegram ID, so the
+//! table alone doesn't say who is behind a number.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use super::group::Group;
+
+/// Display label for pseudonym number `number`
+pub fn pseudonym_label(number: i64) -> String {
+    format!("Player #{number}")
+}
+
+/// Hex SHA-256 of a group's pseudonym key and a user ID
+pub fn user_hash(key: &str, user_id: i64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hasher.update(user_id.to_le_bytes());
+    hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Mapping from a group member to their pseudonym number
+pub struct PlayerPseudonym;
+
+impl PlayerPseudonym {
+    /// The user's pseudonym number in a group, assigning the next free one on first sight
+    pub async fn number_for(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        user_id: i64,
+    ) -> Result<i64, sqlx::Error> {
+        let key = Self::group_key(pool, group_id).await?;
+        let hash = user_hash(&key, user_id);
+
+        // SQLite serializes writers, so MAX(number) + 1 can't be handed out twice
+        sqlx::query(
+            "INSERT OR IGNORE INTO player_pseudonyms (group_id, user_hash, number)
+             SELECT ?, ?, COALESCE(MAX(number), 0) + 1 FROM player_pseudonyms WHERE group_id = ?"
+        )
+        .bind(group_id)
+        .bind(&hash)
+        .bind(group_id)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_scalar::<_, i64>(
+            "SELECT number FROM player_pseudonyms WHERE group_id = ? AND user_hash = ?"
+        )
+        .bind(group_id)
+        .bind(&hash)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The group's hashing key, generated the first time it's needed
+    async fn group_key(pool: &sqlx::SqlitePool, group_id: i64) -> Result<String, sqlx::Error> {
+        sqlx::query("UPDATE groups SET pseudonym_key = lower(hex(randomblob(16))) WHERE id = ? AND pseudonym_key IS NULL")
+            .bind(group_id)
+            .execute(pool)
+            .await?;
+
+        sqlx::query_scalar::<_, Option<String>>("SELECT pseudonym_key FROM groups WHERE id = ?")
+            .bind(group_id)
+            .fetch_one(pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+}
+
+/// Display names for a group's voters, given as `(user_id, stored username)` pairs
+///
+/// Stored usernames are only shown while the group has `store_usernames` on; everyone else
+/// gets their pseudonym.
+pub async fn display_names(
+    pool: &sqlx::SqlitePool,
+    group: &Group,
+    voters: &[(i64, Option<&str>)],
+) -> Result<HashMap<i64, String>, sqlx::Error> {
+    let mut names = HashMap::new();
+    for &(user_id, username) in voters {
+        if names.contains_key(&user_id) {
+            continue;
+        }
+        let name = match username {
+            Some(username) if group.store_usernames => username.to_string(),
+            _ => pseudonym_label(PlayerPseudonym::number_for(pool, group.id, user_id).await?),
+        };
+        names.insert(user_id, name);
+    }
+    Ok(names)
+}
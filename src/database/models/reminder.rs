@@ -1,31 +1,99 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+/// What a [`Reminder`] was sent about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderKind {
+    /// A confirmed session is coming up
+    Session,
+    /// An active poll is still waiting for votes
+    Vote,
+}
+
+impl ReminderKind {
+    /// The value stored in `reminders.kind`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReminderKind::Session => "session",
+            ReminderKind::Vote => "vote",
+        }
+    }
+}
+
+fn session_kind() -> String {
+    ReminderKind::Session.as_str().to_string()
+}
+
+const REMINDER_COLUMNS: &str = "id, session_id, kind, option_id, offset_minutes, sent_at";
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Reminder {
     pub id: String,
     pub session_id: String,
-    pub days_before: i64,
+    /// A [`ReminderKind::as_str`] value; archives from before vote reminders have only session ones
+    #[serde(default = "session_kind")]
+    pub kind: String,
+    /// The confirmed option it was sent for, empty if it was recorded for the whole session
+    #[serde(default)]
+    pub option_id: String,
+    /// How many minutes before the session this reminder was due, see `services::reminder::ReminderOffset`;
+    /// for a vote reminder, how many minutes the poll had been open when it went out
+    pub offset_minutes: i64,
     pub sent_at: String,
 }
 
 impl Reminder {
+    /// Record a reminder as sent for every confirmed option of the session
+    #[allow(dead_code)] // Used by tests
     pub async fn create(
         pool: &sqlx::SqlitePool,
         session_id: String,
-        days_before: i64,
+        offset_minutes: i64,
+    ) -> Result<Self, sqlx::Error> {
+        Self::create_for_option(pool, session_id, String::new(), offset_minutes).await
+    }
+
+    /// Record a reminder as sent for one confirmed option of the session
+    pub async fn create_for_option(
+        pool: &sqlx::SqlitePool,
+        session_id: String,
+        option_id: String,
+        offset_minutes: i64,
+    ) -> Result<Self, sqlx::Error> {
+        Self::insert(pool, session_id, ReminderKind::Session, option_id, offset_minutes).await
+    }
+
+    /// Record a reminder of `kind` as sent for the whole session
+    pub async fn create_kind(
+        pool: &sqlx::SqlitePool,
+        session_id: String,
+        kind: ReminderKind,
+        offset_minutes: i64,
+    ) -> Result<Self, sqlx::Error> {
+        Self::insert(pool, session_id, kind, String::new(), offset_minutes).await
+    }
+
+    async fn insert(
+        pool: &sqlx::SqlitePool,
+        session_id: String,
+        kind: ReminderKind,
+        option_id: String,
+        offset_minutes: i64,
     ) -> Result<Self, sqlx::Error> {
         let id = Uuid::new_v4().to_string();
         let sent_at = Utc::now().to_rfc3339();
         
         sqlx::query(
-            "INSERT INTO reminders (id, session_id, days_before, sent_at) VALUES (?, ?, ?, ?)"
+            "INSERT INTO reminders (id, session_id, kind, option_id, offset_minutes, sent_at) VALUES (?, ?, ?, ?, ?, ?)"
         )
         .bind(&id)
         .bind(&session_id)
-        .bind(days_before)
+        .bind(kind.as_str())
+        .bind(&option_id)
+        .bind(offset_minutes)
         .bind(&sent_at)
         .execute(pool)
         .await?;
@@ -33,37 +101,241 @@ impl Reminder {
         Ok(Reminder {
             id,
             session_id,
-            days_before,
+            kind: kind.as_str().to_string(),
+            option_id,
+            offset_minutes,
             sent_at,
         })
     }
+
+    /// Whether this reminder was sent for the confirmed option `option_id`
+    pub fn covers(&self, option_id: &str) -> bool {
+        self.option_id.is_empty() || self.option_id == option_id
+    }
     
+    /// Whether the session reminder `offset_minutes` before the session was sent
+    #[allow(dead_code)]
     pub async fn exists(
         pool: &sqlx::SqlitePool,
         session_id: &str,
-        days_before: i64,
+        offset_minutes: i64,
+    ) -> Result<bool, sqlx::Error> {
+        Self::exists_kind(pool, session_id, ReminderKind::Session, offset_minutes).await
+    }
+
+    /// Whether a reminder of `kind` with `offset_minutes` was sent for the session
+    pub async fn exists_kind(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        kind: ReminderKind,
+        offset_minutes: i64,
     ) -> Result<bool, sqlx::Error> {
         let count = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM reminders WHERE session_id = ? AND days_before = ?"
+            "SELECT COUNT(*) FROM reminders WHERE session_id = ? AND kind = ? AND offset_minutes = ?"
         )
         .bind(session_id)
-        .bind(days_before)
+        .bind(kind.as_str())
+        .bind(offset_minutes)
         .fetch_one(pool)
         .await?;
         
         Ok(count > 0)
     }
     
-    #[allow(dead_code)]
+    /// The session reminders sent for a session, earliest offset first
     pub async fn find_by_session(
         pool: &sqlx::SqlitePool,
         session_id: &str,
     ) -> Result<Vec<Self>, sqlx::Error> {
-        sqlx::query_as::<_, Reminder>(
-            "SELECT id, session_id, days_before, sent_at FROM reminders WHERE session_id = ? ORDER BY days_before DESC"
+        sqlx::query_as::<_, Reminder>(&format!(
+            "SELECT {REMINDER_COLUMNS} FROM reminders WHERE session_id = ? AND kind = 'session' ORDER BY offset_minutes DESC"
+        ))
+        .bind(session_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Batch fetch the session reminders sent for multiple sessions, keyed by session ID
+    pub async fn find_by_sessions(
+        pool: &sqlx::SqlitePool,
+        session_ids: &[String],
+    ) -> Result<HashMap<String, Vec<Self>>, sqlx::Error> {
+        Self::find_kind_by_sessions(pool, session_ids, ReminderKind::Session).await
+    }
+
+    /// Batch fetch the reminders of `kind` sent for multiple sessions, keyed by session ID
+    pub async fn find_kind_by_sessions(
+        pool: &sqlx::SqlitePool,
+        session_ids: &[String],
+        kind: ReminderKind,
+    ) -> Result<HashMap<String, Vec<Self>>, sqlx::Error> {
+        if session_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let placeholders = session_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!(
+            "SELECT {REMINDER_COLUMNS} FROM reminders WHERE kind = ? AND session_id IN ({placeholders}) ORDER BY offset_minutes DESC"
+        );
+        let mut query_builder = sqlx::query_as::<_, Reminder>(&query).bind(kind.as_str());
+        for session_id in session_ids {
+            query_builder = query_builder.bind(session_id);
+        }
+
+        let mut by_session: HashMap<String, Vec<Self>> = HashMap::new();
+        for reminder in query_builder.fetch_all(pool).await? {
+            by_session.entry(reminder.session_id.clone()).or_default().push(reminder);
+        }
+        Ok(by_session)
+    }
+
+    /// The `limit` most recently sent reminders of any kind across all sessions, newest first
+    pub async fn find_recent(
+        pool: &sqlx::SqlitePool,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Reminder>(&format!(
+            "SELECT {REMINDER_COLUMNS} FROM reminders ORDER BY sent_at DESC LIMIT ?"
+        ))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+/// How long a group the bot can't post in gets no reminders before it's tried again
+pub const UNREACHABLE_GROUP_PAUSE_DAYS: i64 = 7;
+
+/// How an attempt to deliver a reminder ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    /// Telegram accepted it
+    Sent,
+    /// It failed in a way a later sweep may get past, e.g. a network error or rate limit
+    Failed,
+    /// The bot can't post in the group at all, e.g. it was removed or the chat is gone
+    Unreachable,
+}
+
+impl DeliveryOutcome {
+    /// The value stored in `reminder_deliveries.outcome`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeliveryOutcome::Sent => "sent",
+            DeliveryOutcome::Failed => "failed",
+            DeliveryOutcome::Unreachable => "unreachable",
+        }
+    }
+}
+
+/// One finished attempt to deliver a reminder, retries included
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ReminderDelivery {
+    /// Insertion order
+    pub id: i64,
+    /// The session reminded about
+    pub session_id: String,
+    /// The group it was sent to
+    pub group_id: i64,
+    /// Which reminder, see [`Reminder::offset_minutes`]
+    pub offset_minutes: i64,
+    /// A [`DeliveryOutcome::as_str`] value
+    pub outcome: String,
+    /// How many sends it took, retries included
+    pub attempts: i64,
+    /// Telegram's last error, if it failed
+    pub error: Option<String>,
+    /// When the attempt finished
+    pub attempted_at: String,
+}
+
+impl ReminderDelivery {
+    /// Record how delivering a session's reminder went
+    pub async fn record(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        group_id: i64,
+        offset_minutes: i64,
+        outcome: DeliveryOutcome,
+        attempts: u32,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO reminder_deliveries (session_id, group_id, offset_minutes, outcome, attempts, error, attempted_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(session_id)
+        .bind(group_id)
+        .bind(offset_minutes)
+        .bind(outcome.as_str())
+        .bind(attempts)
+        .bind(error)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Offsets of the session's reminders that failed to go out and weren't sent since
+    pub async fn failed_offsets(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT DISTINCT d.offset_minutes FROM reminder_deliveries d \
+             WHERE d.session_id = ? AND d.outcome = 'failed' AND NOT EXISTS \
+                 (SELECT 1 FROM reminders r WHERE r.session_id = d.session_id AND r.kind = 'session' AND r.offset_minutes = d.offset_minutes)"
         )
         .bind(session_id)
         .fetch_all(pool)
         .await
     }
-}
\ No newline at end of file
+
+    /// [`Self::failed_offsets`] of multiple sessions at once, keyed by session ID; sessions
+    /// without failed reminders are left out
+    pub async fn failed_offsets_by_session(
+        pool: &sqlx::SqlitePool,
+        session_ids: &[String],
+    ) -> Result<HashMap<String, Vec<i64>>, sqlx::Error> {
+        if session_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let placeholders = session_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!(
+            "SELECT DISTINCT d.session_id, d.offset_minutes FROM reminder_deliveries d \
+             WHERE d.session_id IN ({placeholders}) AND d.outcome = 'failed' AND NOT EXISTS \
+                 (SELECT 1 FROM reminders r WHERE r.session_id = d.session_id AND r.kind = 'session' AND r.offset_minutes = d.offset_minutes)"
+        );
+        let mut query_builder = sqlx::query_as::<_, (String, i64)>(&query);
+        for session_id in session_ids {
+            query_builder = query_builder.bind(session_id);
+        }
+
+        let mut by_session: HashMap<String, Vec<i64>> = HashMap::new();
+        for (session_id, offset_minutes) in query_builder.fetch_all(pool).await? {
+            by_session.entry(session_id).or_default().push(offset_minutes);
+        }
+        Ok(by_session)
+    }
+
+    /// Groups whose latest delivery found them unreachable, if that was at or after `since`
+    pub async fn unreachable_group_ids(
+        pool: &sqlx::SqlitePool,
+        since: DateTime<Utc>,
+    ) -> Result<HashSet<i64>, sqlx::Error> {
+        let latest = sqlx::query_as::<_, (i64, String)>(
+            "SELECT d.group_id, d.attempted_at FROM reminder_deliveries d \
+             WHERE d.outcome = 'unreachable' \
+               AND d.id = (SELECT MAX(id) FROM reminder_deliveries WHERE group_id = d.group_id)"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // attempted_at is text, so compare parsed timestamps rather than strings
+        Ok(latest.into_iter()
+            .filter(|(_, attempted_at)| DateTime::parse_from_rfc3339(attempted_at).is_ok_and(|at| at >= since))
+            .map(|(group_id, _)| group_id)
+            .collect())
+    }
+}
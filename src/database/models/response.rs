@@ -2,6 +2,7 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use super::pseudonym::PlayerPseudonym;
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Response {
@@ -11,10 +12,46 @@ pub struct Response {
     pub user_id: i64,
     pub username: Option<String>,
     pub response: String, // 'yes', 'no', 'maybe'
+    /// When the user first voted on this option
     pub created_at: String,
+    /// When the vote was last cast or changed
+    pub updated_at: String,
+}
+
+/// One voter's answer on the confirmed time of a confirmed session
+///
+/// Sessions nobody answered on their confirmed time still get a row, with `user_id` and
+/// `response` empty, so gaps in a streak show up.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct ConfirmedAttendance {
+    /// The confirmed session
+    pub session_id: String,
+    /// When its confirmed option starts (RFC 3339)
+    pub starts_at: String,
+    /// Who answered, `None` on a session without answers
+    pub user_id: Option<i64>,
+    /// Their "yes", "no" or "maybe"
+    pub response: Option<String>,
+}
+
+/// How often one user voted in a group
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct Participation {
+    /// The voter's Telegram user ID
+    pub user_id: i64,
+    /// The username on their most recent vote that has one
+    pub username: Option<String>,
+    /// How many votes they cast, over all options of all sessions
+    pub response_count: i64,
 }
 
 impl Response {
+    /// Record a user's vote on an option, replacing any earlier vote on it
+    ///
+    /// A replaced vote keeps its `created_at`; `updated_at` is set on every call.
+    ///
+    /// The username is dropped (and a pseudonym assigned instead) when the session's group
+    /// has `store_usernames` turned off.
     pub async fn upsert(
         pool: &sqlx::SqlitePool,
         session_id: String,
@@ -26,21 +63,30 @@ impl Response {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now().to_rfc3339();
         
-        // Delete existing response for this user/option
-        sqlx::query!(
-            "DELETE FROM responses WHERE session_id = ? AND option_id = ? AND user_id = ?",
-            session_id,
-            option_id,
-            user_id
+        // Respect the group's username privacy setting
+        let group_privacy = sqlx::query_as::<_, (i64, bool)>(
+            "SELECT g.id, g.store_usernames FROM sessions s JOIN groups g ON g.id = s.group_id WHERE s.id = ?"
         )
-        .execute(pool)
+        .bind(&session_id)
+        .fetch_optional(pool)
         .await?;
+        let username = match group_privacy {
+            Some((group_id, false)) => {
+                PlayerPseudonym::number_for(pool, group_id, user_id).await?;
+                None
+            }
+            _ => username,
+        };
         
-        // Insert new response
+        // One statement, so a concurrent vote can't slip in between and the row keeps its id
         sqlx::query!(
             r#"
-            INSERT INTO responses (id, session_id, option_id, user_id, username, response, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO responses (id, session_id, option_id, user_id, username, response, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(session_id, option_id, user_id) DO UPDATE SET
+                response = excluded.response,
+                username = excluded.username,
+                updated_at = excluded.updated_at
             "#,
             id,
             session_id,
@@ -48,21 +94,22 @@ impl Response {
             user_id,
             username,
             response,
+            now,
             now
         )
         .execute(pool)
         .await?;
         
-        // Return the created response
-        Ok(Response {
-            id,
-            session_id,
-            option_id,
-            user_id,
-            username,
-            response,
-            created_at: now.clone(),
-        })
+        // Return the stored row, which keeps its original id when an earlier vote was updated
+        sqlx::query_as::<_, Response>(
+            "SELECT id, session_id, option_id, user_id, username, response, created_at, updated_at
+             FROM responses WHERE session_id = ? AND option_id = ? AND user_id = ?"
+        )
+        .bind(&session_id)
+        .bind(&option_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
     }
 
     pub async fn find_by_session(
@@ -70,7 +117,7 @@ impl Response {
         session_id: &str,
     ) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as::<_, Response>(
-            "SELECT id, session_id, option_id, user_id, username, response, created_at FROM responses WHERE session_id = ?"
+            "SELECT id, session_id, option_id, user_id, username, response, created_at, updated_at FROM responses WHERE session_id = ?"
         )
         .bind(session_id)
         .fetch_all(pool)
@@ -88,7 +135,7 @@ impl Response {
 
         let placeholders = session_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let query = format!(
-            "SELECT id, session_id, option_id, user_id, username, response, created_at FROM responses WHERE session_id IN ({placeholders}) ORDER BY session_id, created_at"
+            "SELECT id, session_id, option_id, user_id, username, response, created_at, updated_at FROM responses WHERE session_id IN ({placeholders}) ORDER BY session_id, created_at"
         );
 
         let mut query_builder = sqlx::query_as::<_, Response>(&query);
@@ -98,4 +145,141 @@ impl Response {
 
         query_builder.fetch_all(pool).await
     }
+
+    /// Distinct users who have responded to any session in a group
+    #[allow(dead_code)] // Used by tests
+    pub async fn find_group_voter_ids(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT DISTINCT r.user_id FROM responses r JOIN sessions s ON r.session_id = s.id WHERE s.group_id = ?"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// All responses to any session in a group, ordered by session and submission time
+    pub async fn find_all_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Response>(
+            "SELECT r.id, r.session_id, r.option_id, r.user_id, r.username, r.response, r.created_at, r.updated_at
+             FROM responses r
+             JOIN sessions s ON r.session_id = s.id
+             WHERE s.group_id = ?
+             ORDER BY s.created_at, r.session_id, r.created_at"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// How many different people voted on the group's other sessions, leaving out cancelled ones
+    pub async fn count_past_voters(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        session_id: &str,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(DISTINCT r.user_id) FROM responses r JOIN sessions s ON r.session_id = s.id \
+             WHERE s.group_id = ? AND s.id != ? AND s.status != 'cancelled'"
+        )
+        .bind(group_id)
+        .bind(session_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Votes per user in a group, most active first
+    ///
+    /// Counted by user ID, so someone who changed their username between votes is still one voter.
+    pub async fn participation_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Participation>, sqlx::Error> {
+        sqlx::query_as::<_, Participation>(
+            "SELECT user_id, username, response_count FROM (
+                 SELECT r.user_id, r.username,
+                        COUNT(*) OVER (PARTITION BY r.user_id) AS response_count,
+                        ROW_NUMBER() OVER (
+                            PARTITION BY r.user_id ORDER BY r.username IS NULL, r.updated_at DESC, r.id
+                        ) AS latest
+                 FROM responses r
+                 JOIN sessions s ON r.session_id = s.id
+                 WHERE s.group_id = ?
+             )
+             WHERE latest = 1
+             ORDER BY response_count DESC, user_id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Every answer on the confirmed option of the group's confirmed sessions, in session order
+    pub async fn find_confirmed_attendance(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<ConfirmedAttendance>, sqlx::Error> {
+        sqlx::query_as::<_, ConfirmedAttendance>(
+            "SELECT s.id AS session_id, o.datetime AS starts_at, r.user_id, r.response
+             FROM sessions s
+             JOIN session_options o ON o.session_id = s.id AND o.confirmed = 1
+             LEFT JOIN responses r ON r.option_id = o.id
+             WHERE s.group_id = ? AND s.status = 'confirmed'
+             ORDER BY o.datetime, s.id, r.user_id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// For each of the group's sessions with a confirmed time, how many players voted on it and
+    /// how many of them said "yes" to the confirmed time, as `(voters, yes_voters)`
+    ///
+    /// Cancelled sessions are left out.
+    pub async fn confirmed_turnout(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (i64, i64)>(
+            "SELECT COUNT(DISTINCT r.user_id),
+                    COUNT(DISTINCT CASE WHEN o.confirmed = 1 AND r.response = 'yes' THEN r.user_id END)
+             FROM sessions s
+             JOIN responses r ON r.session_id = s.id
+             JOIN session_options o ON o.id = r.option_id
+             WHERE s.group_id = ? AND s.status != 'cancelled'
+               AND EXISTS (SELECT 1 FROM session_options c WHERE c.session_id = s.id AND c.confirmed = 1)
+             GROUP BY s.id
+             ORDER BY s.id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// How many of the group's polls each user was the first to vote on, as `(user_id, count)`
+    pub async fn count_first_votes(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (i64, i64)>(
+            "SELECT user_id, COUNT(*) FROM (
+                 SELECT r.user_id,
+                        ROW_NUMBER() OVER (PARTITION BY r.session_id ORDER BY r.created_at, r.id) AS position
+                 FROM responses r
+                 JOIN sessions s ON r.session_id = s.id
+                 WHERE s.group_id = ?
+             )
+             WHERE position = 1
+             GROUP BY user_id
+             ORDER BY user_id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
 }
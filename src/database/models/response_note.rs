@@ -0,0 +1,154 @@
+//! Notes voters attach to an option of a poll, such as "I can make Friday but only after 20:00"
+
+use std::collections::HashMap;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use super::{display_names, Group, Response, UserProfile};
+
+/// Longest note a voter can leave, in characters
+pub const NOTE_MAX_CHARS: usize = 200;
+
+/// Characters of a note shown in /list and /sessioninfo; longer ones end in "…"
+pub const NOTE_SHOWN_CHARS: usize = 100;
+
+/// A voter's note on one option of a session
+///
+/// Notes are kept apart from votes: one can be left before voting and stays when the vote changes.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct ResponseNote {
+    /// The session the option belongs to
+    pub session_id: String,
+    /// The option the note is about
+    pub option_id: String,
+    /// Who left it
+    pub user_id: i64,
+    /// The note, on one line and at most [`NOTE_MAX_CHARS`] long
+    pub note: String,
+    /// When it was last written
+    pub updated_at: String,
+}
+
+impl ResponseNote {
+    /// Save a user's note on an option, replacing the one they left before
+    pub async fn set(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        option_id: &str,
+        user_id: i64,
+        note: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO response_notes (session_id, option_id, user_id, note, updated_at) VALUES (?, ?, ?, ?, ?) \
+             ON CONFLICT(option_id, user_id) DO UPDATE SET note = excluded.note, updated_at = excluded.updated_at"
+        )
+        .bind(session_id)
+        .bind(option_id)
+        .bind(user_id)
+        .bind(note)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove a user's note on an option; returns whether there was one
+    pub async fn clear(
+        pool: &sqlx::SqlitePool,
+        option_id: &str,
+        user_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM response_notes WHERE option_id = ? AND user_id = ?")
+            .bind(option_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The notes on the options of any of `session_ids`, oldest first
+    pub async fn find_by_sessions(
+        pool: &sqlx::SqlitePool,
+        session_ids: &[String],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        if session_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = session_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!(
+            "SELECT session_id, option_id, user_id, note, updated_at FROM response_notes \
+             WHERE session_id IN ({placeholders}) ORDER BY updated_at, user_id"
+        );
+
+        let mut query_builder = sqlx::query_as::<_, ResponseNote>(&query);
+        for session_id in session_ids {
+            query_builder = query_builder.bind(session_id);
+        }
+
+        query_builder.fetch_all(pool).await
+    }
+}
+
+/// A note as tidied up for saving: on one line, or an error if it's empty or too long
+pub fn clean_note(text: &str) -> Result<String, String> {
+    let note = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if note.is_empty() {
+        return Err("The note is empty".to_string());
+    }
+    let chars = note.chars().count();
+    if chars > NOTE_MAX_CHARS {
+        return Err(format!("The note is {chars} characters long, keep it to {NOTE_MAX_CHARS}"));
+    }
+    Ok(note)
+}
+
+/// Notes ready to show: `(author, note)` pairs by option ID, each note cut to [`NOTE_SHOWN_CHARS`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotesByOption(HashMap<String, Vec<(String, String)>>);
+
+impl NotesByOption {
+    /// Group `notes` by option, naming their authors by `names`; anyone missing shows as "user<id>"
+    pub fn new(notes: &[ResponseNote], names: &HashMap<i64, String>) -> Self {
+        let mut by_option: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        for note in notes {
+            let author = names.get(&note.user_id).cloned().unwrap_or_else(|| format!("user{}", note.user_id));
+            let mut shown: String = note.note.chars().take(NOTE_SHOWN_CHARS).collect();
+            if note.note.chars().count() > NOTE_SHOWN_CHARS {
+                shown.push('…');
+            }
+            by_option.entry(note.option_id.clone()).or_default().push((author, shown));
+        }
+        Self(by_option)
+    }
+
+    /// Load the notes on the options of `session_ids`, named the way votes are in `group`
+    pub async fn load(
+        pool: &sqlx::SqlitePool,
+        group: &Group,
+        session_ids: &[String],
+    ) -> Result<Self, sqlx::Error> {
+        let notes = ResponseNote::find_by_sessions(pool, session_ids).await?;
+        if notes.is_empty() {
+            return Ok(Self::default());
+        }
+        // Authors are named as on their votes, or by their profile if they haven't voted
+        let responses = Response::find_by_sessions(pool, session_ids).await?;
+        let user_ids: Vec<i64> = notes.iter().map(|note| note.user_id).collect();
+        let profiles = UserProfile::find_by_ids(pool, &user_ids).await?;
+        let mut authors: Vec<(i64, Option<&str>)> = responses.iter()
+            .filter(|r| user_ids.contains(&r.user_id))
+            .map(|r| (r.user_id, r.username.as_deref()))
+            .collect();
+        authors.extend(user_ids.iter().map(|user_id| (*user_id, profiles.get(user_id).map(UserProfile::name))));
+        let names = display_names(pool, group, &authors).await?;
+        Ok(Self::new(&notes, &names))
+    }
+
+    /// The `(author, note)` pairs on an option, oldest first
+    pub fn for_option(&self, option_id: &str) -> &[(String, String)] {
+        self.0.get(option_id).map(Vec::as_slice).unwrap_or_default()
+    }
+}
@@ -0,0 +1,72 @@
+//! Roll calls: sessions for a game later the same day that settle themselves
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// What makes a session a roll call: how many players it needs and when it gives up
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct Rollcall {
+    /// The session, which has a single option
+    pub session_id: String,
+    /// How many "yes" votes confirm it
+    pub quorum: i64,
+    /// When it's cancelled if it hasn't reached its quorum by then
+    pub expires_at: String,
+}
+
+impl Rollcall {
+    /// Make the session with ID `session_id` a roll call
+    pub async fn create(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        quorum: i64,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let rollcall = Rollcall {
+            session_id: session_id.to_string(),
+            quorum,
+            expires_at: expires_at.to_rfc3339(),
+        };
+        sqlx::query("INSERT INTO rollcalls (session_id, quorum, expires_at) VALUES (?, ?, ?)")
+            .bind(&rollcall.session_id)
+            .bind(rollcall.quorum)
+            .bind(&rollcall.expires_at)
+            .execute(pool)
+            .await?;
+
+        Ok(rollcall)
+    }
+
+    /// The roll call behind a session, `None` if it's an ordinary one
+    pub async fn find_by_session(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Rollcall>("SELECT session_id, quorum, expires_at FROM rollcalls WHERE session_id = ?")
+            .bind(session_id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// Roll calls still waiting for their quorum that expired by `now`
+    pub async fn find_expired(
+        pool: &sqlx::SqlitePool,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let open = sqlx::query_as::<_, Rollcall>(
+            "SELECT r.session_id, r.quorum, r.expires_at FROM rollcalls r \
+             JOIN sessions s ON s.id = r.session_id WHERE s.status = 'active'"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // Stored with whatever offset they were created in, so compare instants, not text
+        Ok(open.into_iter().filter(|rollcall| rollcall.expires_at_utc().is_some_and(|at| at <= now)).collect())
+    }
+
+    /// `expires_at` as a timestamp, `None` if it can't be read
+    pub fn expires_at_utc(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.expires_at).ok().map(|dt| dt.with_timezone(&Utc))
+    }
+}
@@ -0,0 +1,103 @@
+//! A group's roster: the players who signed up to be scheduled with it
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A player on a group's roster
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct RosterMember {
+    /// The group the player belongs to
+    pub group_id: i64,
+    /// The player's Telegram user ID
+    pub user_id: i64,
+    /// Their username when they joined, `None` if unknown or the group doesn't store usernames
+    pub username: Option<String>,
+    /// When they joined the roster
+    pub joined_at: String,
+    /// Whether the group can't play without them; options they say no to aren't picked by /confirm
+    pub required: bool,
+}
+
+impl RosterMember {
+    /// Put `user_id` on the group's roster; returns `false` if they were already on it
+    pub async fn add(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        user_id: i64,
+        username: Option<String>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO roster_members (group_id, user_id, username, joined_at) VALUES (?, ?, ?, ?)"
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .bind(username)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// The group's roster, earliest joiners first
+    pub async fn find_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, RosterMember>(
+            "SELECT group_id, user_id, username, joined_at, required FROM roster_members WHERE group_id = ? ORDER BY joined_at, user_id"
+        )
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Mark `user_id` as required by the group, putting them on its roster if they weren't;
+    /// returns `false` if they were required already
+    pub async fn require(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        user_id: i64,
+        username: Option<String>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO roster_members (group_id, user_id, username, joined_at, required) VALUES (?, ?, ?, ?, TRUE) \
+             ON CONFLICT(group_id, user_id) DO UPDATE SET required = TRUE WHERE NOT required"
+        )
+        .bind(group_id)
+        .bind(user_id)
+        .bind(username)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stop requiring `user_id`, who stays on the roster; returns `false` if they weren't required
+    pub async fn unrequire(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        user_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE roster_members SET required = FALSE WHERE group_id = ? AND user_id = ? AND required")
+            .bind(group_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The user IDs of the group's required players
+    pub async fn required_user_ids(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT user_id FROM roster_members WHERE group_id = ? AND required ORDER BY user_id")
+            .bind(group_id)
+            .fetch_all(pool)
+            .await
+    }
+}
@@ -1,8 +1,32 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Column list used by every query that loads a full `Session` row
+pub const SESSION_COLUMNS: &str =
+    "id, group_id, title, message_id, status, deadline, created_by, created_at, short_id, anonymous, notes, availability, reminders_muted, \
+     last_rendered_at, updated_at";
+
+/// Active polls without a vote for this many days count as stale
+pub const STALE_SESSION_DAYS: i64 = 7;
+
+/// A poll not posted or edited for this many days is refreshed before it takes another vote
+pub const POLL_RERENDER_AFTER_DAYS: i64 = 30;
+
+/// Confirmed sessions are archived this many days after their confirmed time
+pub const ARCHIVE_CONFIRMED_AFTER_DAYS: i64 = 30;
+
+/// Cancelled sessions are archived this many days after they were created
+pub const ARCHIVE_CANCELLED_AFTER_DAYS: i64 = 14;
+
+/// Statuses `/archive` accepts; active polls have to be confirmed, cancelled or closed first
+pub const ARCHIVABLE_STATUSES: [&str; 3] = ["confirmed", "cancelled", "closed"];
+
+/// Maximum number of digits accepted for a per-group short session number
+pub const MAX_SHORT_ID_DIGITS: usize = 6;
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
@@ -13,6 +37,52 @@ pub struct Session {
     pub deadline: Option<String>,
     pub created_by: i64,
     pub created_at: String,
+    /// Per-group sequence number, shown to users as "#12"
+    pub short_id: Option<i64>,
+    /// Whether vote tallies are hidden until the session is confirmed or closed
+    pub anonymous: bool,
+    /// Free-form notes such as the meeting place, set with `/notes`
+    pub notes: Option<String>,
+    /// Whether this is an `/availability` poll, whose options are days rather than start times
+    #[serde(default)]
+    pub availability: bool,
+    /// Whether `/mute` holds back its reminders; they're marked as sent all the same
+    #[serde(default)]
+    pub reminders_muted: bool,
+    /// When the poll message was last posted or edited; `None` if not since this was tracked
+    #[serde(default)]
+    pub last_rendered_at: Option<String>,
+    /// When the session or its options last changed, kept up by database triggers
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// Errors that can occur when resolving a user-supplied session reference
+#[derive(Debug)]
+pub enum SessionLookupError {
+    /// The reference matched more than one session in the group
+    Ambiguous(usize),
+    /// The underlying database query failed
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for SessionLookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionLookupError::Ambiguous(count) => {
+                write!(f, "Session reference is ambiguous ({count} sessions match)")
+            }
+            SessionLookupError::Database(e) => write!(f, "Database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionLookupError {}
+
+impl From<sqlx::Error> for SessionLookupError {
+    fn from(e: sqlx::Error) -> Self {
+        SessionLookupError::Database(e)
+    }
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -34,16 +104,19 @@ impl Session {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now().to_rfc3339();
         
+        // The short ID is allocated in the same statement so concurrent inserts can't collide
         sqlx::query!(
             r#"
-            INSERT INTO sessions (id, group_id, title, status, created_by, created_at)
-            VALUES (?, ?, ?, 'active', ?, ?)
+            INSERT INTO sessions (id, group_id, title, status, created_by, created_at, short_id)
+            VALUES (?, ?, ?, 'active', ?, ?,
+                (SELECT COALESCE(MAX(short_id), 0) + 1 FROM sessions WHERE group_id = ?))
             "#,
             id,
             group_id,
             title,
             created_by,
-            now
+            now,
+            group_id
         )
         .execute(pool)
         .await?;
@@ -57,13 +130,437 @@ impl Session {
         pool: &sqlx::SqlitePool,
         session_id: &str,
     ) -> Result<Option<Self>, sqlx::Error> {
-        sqlx::query_as::<_, Session>(
-            "SELECT id, group_id, title, message_id, status, deadline, created_by, created_at FROM sessions WHERE id = ?"
+        let query = format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE id = ?");
+        sqlx::query_as::<_, Session>(&query)
+            .bind(session_id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// Find a session in a group by its per-group short number
+    pub async fn find_by_short_id(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        short_id: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let query = format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE group_id = ? AND short_id = ?");
+        sqlx::query_as::<_, Session>(&query)
+            .bind(group_id)
+            .bind(short_id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// Find a session in a group whose ID starts with `prefix`.
+    ///
+    /// Returns `SessionLookupError::Ambiguous` if more than one session matches.
+    pub async fn find_by_prefix(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        prefix: &str,
+    ) -> Result<Option<Self>, SessionLookupError> {
+        // LIKE wildcards can't appear in a real session ID
+        if prefix.is_empty() || prefix.contains(['%', '_']) {
+            return Ok(None);
+        }
+
+        let query = format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE group_id = ? AND id LIKE ?");
+        let mut matches = sqlx::query_as::<_, Session>(&query)
+            .bind(group_id)
+            .bind(format!("{prefix}%"))
+            .fetch_all(pool)
+            .await?;
+
+        match matches.len() {
+            0 | 1 => Ok(matches.pop()),
+            count => Err(SessionLookupError::Ambiguous(count)),
+        }
+    }
+
+    /// Resolve a user-supplied session reference within a group.
+    ///
+    /// Accepts a short number (`#12` or `12`), an ID prefix, or a full session ID.
+    pub async fn find_by_reference(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        reference: &str,
+    ) -> Result<Option<Self>, SessionLookupError> {
+        let reference = reference.trim();
+        if let Some(short_id) = parse_short_id(reference) {
+            return Ok(Self::find_by_short_id(pool, group_id, short_id).await?);
+        }
+        Self::find_by_prefix(pool, group_id, reference).await
+    }
+
+    /// All sessions in a group regardless of status, oldest first
+    pub async fn find_by_group(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Session>(&format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE group_id = ? ORDER BY created_at, id"
+        ))
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// One page of a group's sessions whose status is one of `statuses` (any status if empty),
+    /// along with how many there are in total
+    ///
+    /// Newest first, except that active polls created over `STALE_SESSION_DAYS` ago that nobody
+    /// voted on come last; this is the order `/list` shows.
+    pub async fn find_by_group_paginated(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        limit: i64,
+        offset: i64,
+        statuses: &[&str],
+    ) -> Result<(Vec<Self>, i64), sqlx::Error> {
+        let status_filter = if statuses.is_empty() {
+            String::new()
+        } else {
+            format!(" AND status IN ({})", statuses.iter().map(|_| "?").collect::<Vec<_>>().join(","))
+        };
+
+        let count_query = format!("SELECT COUNT(*) FROM sessions WHERE group_id = ?{status_filter}");
+        let mut count = sqlx::query_scalar::<_, i64>(&count_query).bind(group_id);
+        for status in statuses {
+            count = count.bind(*status);
+        }
+        let total = count.fetch_one(pool).await?;
+
+        let page_query = format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE group_id = ?{status_filter}
+             ORDER BY
+                 status = 'active'
+                     AND created_at < ?
+                     AND NOT EXISTS (SELECT 1 FROM responses WHERE responses.session_id = sessions.id),
+                 created_at DESC, id
+             LIMIT ? OFFSET ?"
+        );
+        let mut page = sqlx::query_as::<_, Session>(&page_query).bind(group_id);
+        for status in statuses {
+            page = page.bind(*status);
+        }
+        let sessions = page
+            .bind((Utc::now() - Duration::days(STALE_SESSION_DAYS)).to_rfc3339())
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await?;
+
+        Ok((sessions, total))
+    }
+
+    /// Number of sessions of every group that has any, as `(group_id, count)`
+    pub async fn count_by_group(pool: &sqlx::SqlitePool) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (i64, i64)>("SELECT group_id, COUNT(*) FROM sessions GROUP BY group_id")
+            .fetch_all(pool)
+            .await
+    }
+
+    /// How many of the group's sessions each user scheduled that got a confirmed time, as
+    /// `(created_by, count)`; cancelled sessions don't count
+    pub async fn count_confirmed_by_creator(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (i64, i64)>(
+            "SELECT s.created_by, COUNT(*) FROM sessions s
+             WHERE s.group_id = ? AND s.status != 'cancelled'
+               AND EXISTS (SELECT 1 FROM session_options o WHERE o.session_id = s.id AND o.confirmed = 1)
+             GROUP BY s.created_by
+             ORDER BY s.created_by"
         )
-        .bind(session_id)
-        .fetch_optional(pool)
+        .bind(group_id)
+        .fetch_all(pool)
         .await
     }
+
+    /// Active sessions of any group whose title contains `query` (ignoring ASCII case), newest first
+    pub async fn find_active_by_title(
+        pool: &sqlx::SqlitePool,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let pattern = format!(
+            "%{}%",
+            query.trim().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        sqlx::query_as::<_, Session>(&format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE status = 'active' AND title LIKE ? ESCAPE '\\' \
+             ORDER BY created_at DESC, id LIMIT ?"
+        ))
+        .bind(pattern)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find active sessions in a group that were created before `cutoff`, oldest first
+    pub async fn find_active_older_than(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let query = format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE group_id = ? AND status = 'active' ORDER BY created_at"
+        );
+        let sessions = sqlx::query_as::<_, Session>(&query)
+            .bind(group_id)
+            .fetch_all(pool)
+            .await?;
+
+        // created_at is stored as text, so compare parsed timestamps rather than strings
+        Ok(sessions
+            .into_iter()
+            .filter(|s| s.created_at_utc().is_some_and(|created| created < cutoff))
+            .collect())
+    }
+
+    /// Confirmed and cancelled sessions due for archiving at `now`, oldest first
+    ///
+    /// Confirmed sessions age from their last confirmed option's start, cancelled ones from their creation.
+    pub async fn find_stale(
+        pool: &sqlx::SqlitePool,
+        now: DateTime<Utc>,
+        confirmed_after: Duration,
+        cancelled_after: Duration,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let query = format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE status IN ('confirmed', 'cancelled') ORDER BY created_at"
+        );
+        let sessions = sqlx::query_as::<_, Session>(&query)
+            .fetch_all(pool)
+            .await?;
+        // Option times are stored with whatever offset they were parsed in, so compare instants, not
+        // text; a session confirmed for several options ages from the last of them
+        let mut confirmed_starts: HashMap<String, DateTime<Utc>> = HashMap::new();
+        let confirmed = sqlx::query_as::<_, (String, String)>(
+            "SELECT o.session_id, o.datetime FROM session_options o JOIN sessions s ON s.id = o.session_id \
+             WHERE o.confirmed = 1 AND s.status = 'confirmed'"
+        )
+        .fetch_all(pool)
+        .await?;
+        for (session_id, datetime) in confirmed {
+            let Ok(start) = DateTime::parse_from_rfc3339(&datetime) else {
+                continue;
+            };
+            let start = start.with_timezone(&Utc);
+            confirmed_starts.entry(session_id)
+                .and_modify(|latest| *latest = (*latest).max(start))
+                .or_insert(start);
+        }
+
+        Ok(sessions
+            .into_iter()
+            .filter(|s| {
+                let confirmed_start = confirmed_starts.get(&s.id).copied();
+                s.archive_due(confirmed_start, now, confirmed_after, cancelled_after)
+            })
+            .collect())
+    }
+
+    /// Whether this session should be archived at `now`
+    ///
+    /// `confirmed_start` is the start of the last confirmed option; a confirmed session without one is
+    /// left alone, as are sessions in any other status than confirmed or cancelled.
+    pub fn archive_due(
+        &self,
+        confirmed_start: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        confirmed_after: Duration,
+        cancelled_after: Duration,
+    ) -> bool {
+        match self.status.as_str() {
+            "confirmed" => confirmed_start.is_some_and(|start| start + confirmed_after < now),
+            "cancelled" => self.created_at_utc().is_some_and(|created| created + cancelled_after < now),
+            _ => false,
+        }
+    }
+
+    /// Move a finished session to the archive; `false` if it wasn't in an [`ARCHIVABLE_STATUSES`] status
+    pub async fn archive(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE sessions SET status = 'archived' WHERE id = ? AND status IN ('confirmed', 'cancelled', 'closed')"
+        )
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Parse the stored `created_at` timestamp
+    ///
+    /// Rows written by the app use RFC3339; rows relying on the SQLite default use `YYYY-MM-DD HH:MM:SS`.
+    pub fn created_at_utc(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.created_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDateTime::parse_from_str(&self.created_at, "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .map(|naive| Utc.from_utc_datetime(&naive))
+            })
+    }
+
+    /// Mark a session as anonymous (tallies hidden) or public
+    pub async fn set_anonymous(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        anonymous: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE sessions SET anonymous = ? WHERE id = ?",
+            anonymous,
+            session_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark a session as an availability poll, whose options are days to pick a start time for
+    pub async fn set_availability(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        availability: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET availability = ? WHERE id = ?")
+            .bind(availability)
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Hold back (`/mute`) or let through (`/unmute`) the session's reminders
+    pub async fn set_reminders_muted(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        muted: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET reminders_muted = ? WHERE id = ?")
+            .bind(muted)
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Set or clear (`None`) the session's notes
+    pub async fn set_notes(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        notes: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE sessions SET notes = ? WHERE id = ?",
+            notes,
+            session_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Remember which message holds the session's poll, so vote updates edit it
+    ///
+    /// The poll was just posted, so this also counts as rendering it (see [`Session::mark_rendered`]).
+    pub async fn set_message_id(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+        message_id: i64,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query!(
+            "UPDATE sessions SET message_id = ?, last_rendered_at = ? WHERE id = ?",
+            message_id,
+            now,
+            session_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record that the session's poll message was just edited to match the database
+    pub async fn mark_rendered(pool: &sqlx::SqlitePool, session_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET last_rendered_at = ? WHERE id = ?")
+            .bind(Utc::now().to_rfc3339())
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the poll message may no longer show the session as it is at `now`: it was last
+    /// rendered before the session changed, or over [`POLL_RERENDER_AFTER_DAYS`] ago
+    ///
+    /// Polls rendered before this was tracked count from when the session was created.
+    pub fn poll_is_outdated(&self, now: DateTime<Utc>) -> bool {
+        let parse = |text: &str| DateTime::parse_from_rfc3339(text).ok().map(|dt| dt.with_timezone(&Utc));
+        let rendered_at = match self.last_rendered_at.as_deref() {
+            Some(text) => parse(text),
+            None => self.created_at_utc(),
+        };
+        let Some(rendered_at) = rendered_at else {
+            return false;
+        };
+        let changed_since = self.updated_at.as_deref().and_then(parse).is_some_and(|updated_at| updated_at > rendered_at);
+        changed_since || now - rendered_at > Duration::days(POLL_RERENDER_AFTER_DAYS)
+    }
+
+    /// Permanently delete a session; its options, responses and reminders go with it
+    ///
+    /// Relies on the `ON DELETE CASCADE` foreign keys, so the pool must have foreign keys enabled.
+    /// Returns `false` if there was no such session.
+    pub async fn delete(
+        pool: &sqlx::SqlitePool,
+        session_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM sessions WHERE id = ?", session_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether vote tallies should currently be hidden from players
+    pub fn hides_votes(&self) -> bool {
+        self.anonymous && self.status == "active"
+    }
+
+    /// Human-friendly reference for messages: `#12` when available, otherwise the ID prefix
+    pub fn display_ref(&self) -> String {
+        match self.short_id {
+            Some(short_id) => format!("#{short_id}"),
+            None => self.id.chars().take(8).collect(),
+        }
+    }
+}
+
+/// Parse a short session number such as `#12` or `12`.
+///
+/// Bare numbers are only treated as short numbers when they have at most
+/// `MAX_SHORT_ID_DIGITS` digits, so longer numeric strings remain usable as ID prefixes.
+pub fn parse_short_id(reference: &str) -> Option<i64> {
+    let reference = reference.trim();
+    let (digits, explicit) = match reference.strip_prefix('#') {
+        Some(rest) => (rest, true),
+        None => (reference, false),
+    };
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if !explicit && digits.len() > MAX_SHORT_ID_DIGITS {
+        return None;
+    }
+    digits.parse().ok().filter(|n| *n > 0)
 }
 
 impl SessionOption {
@@ -111,6 +608,19 @@ impl SessionOption {
         .await
     }
 
+    /// The option with this ID, if it still exists
+    pub async fn find_by_id(
+        pool: &sqlx::SqlitePool,
+        option_id: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, SessionOption>(
+            "SELECT id, session_id, datetime, duration, confirmed FROM session_options WHERE id = ?"
+        )
+        .bind(option_id)
+        .fetch_optional(pool)
+        .await
+    }
+
     /// Batch fetch session options for multiple sessions to avoid N+1 queries
     pub async fn find_by_sessions(
         pool: &sqlx::SqlitePool,
@@ -132,4 +642,20 @@ impl SessionOption {
 
         query_builder.fetch_all(pool).await
     }
+
+    /// Move an option to start at `datetime`, e.g. once an availability poll's day gets its time
+    ///
+    /// Returns whether the option exists.
+    pub async fn set_start(
+        pool: &sqlx::SqlitePool,
+        option_id: &str,
+        datetime: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE session_options SET datetime = ? WHERE id = ?")
+            .bind(datetime.to_rfc3339())
+            .bind(option_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
 }
@@ -0,0 +1,261 @@
+//! The last reversible action per group, kept for `/undo`
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// How long an action can be undone after it was performed
+pub const UNDO_WINDOW_MINUTES: i64 = 10;
+
+/// A reversible action, with what's needed to restore the previous state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UndoableAction {
+    /// `/cancel` of a session
+    Cancel {
+        /// The cancelled session
+        session_id: String,
+        /// Its status before cancelling, "active" or "confirmed"
+        previous_status: String,
+        /// Its deadline before cancelling, which cancelling clears
+        previous_deadline: Option<String>,
+    },
+    /// `/closeall` of active polls
+    Close {
+        /// The sessions that were closed
+        session_ids: Vec<String>,
+    },
+    /// `/deadline` on a session
+    Deadline {
+        /// The session whose deadline changed
+        session_id: String,
+        /// The deadline before the change, `None` if there was none
+        previous_deadline: Option<String>,
+    },
+}
+
+impl UndoableAction {
+    fn kind(&self) -> &'static str {
+        match self {
+            UndoableAction::Cancel { .. } => "cancel",
+            UndoableAction::Close { .. } => "close",
+            UndoableAction::Deadline { .. } => "deadline",
+        }
+    }
+
+    /// Short description for undo confirmations, e.g. "deadline change"
+    pub fn describe(&self) -> String {
+        match self {
+            UndoableAction::Cancel { .. } => "session cancellation".to_string(),
+            UndoableAction::Close { session_ids } => format!("closing of {} polls", session_ids.len()),
+            UndoableAction::Deadline { .. } => "deadline change".to_string(),
+        }
+    }
+}
+
+/// A stored undo record; each group keeps only its most recent one
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct UndoRecord {
+    /// Referenced by the "↩️ Undo" button's callback data
+    pub id: String,
+    /// The group the action happened in
+    pub group_id: i64,
+    /// Who performed the action; only they can undo it
+    pub user_id: i64,
+    /// "cancel", "close" or "deadline"
+    pub kind: String,
+    /// Comma separated session IDs
+    pub session_ids: String,
+    /// Status to restore for "cancel"
+    pub previous_status: Option<String>,
+    /// Deadline to restore for "deadline"
+    pub previous_deadline: Option<String>,
+    /// When the action was performed
+    pub created_at: String,
+    /// End of the undo window
+    pub expires_at: String,
+}
+
+const UNDO_COLUMNS: &str =
+    "id, group_id, user_id, kind, session_ids, previous_status, previous_deadline, created_at, expires_at";
+
+/// Fixed-width UTC timestamps so `expires_at` compares correctly as text
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+impl UndoRecord {
+    /// Remember `action` as the group's last reversible action, replacing any earlier one
+    pub async fn record(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        user_id: i64,
+        action: &UndoableAction,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+        let (session_ids, previous_status, previous_deadline) = match action {
+            UndoableAction::Cancel { session_id, previous_status, previous_deadline } => {
+                (session_id.clone(), Some(previous_status.clone()), previous_deadline.clone())
+            }
+            UndoableAction::Close { session_ids } => (session_ids.join(","), None, None),
+            UndoableAction::Deadline { session_id, previous_deadline } => {
+                (session_id.clone(), None, previous_deadline.clone())
+            }
+        };
+        let record = UndoRecord {
+            id: Uuid::new_v4().to_string(),
+            group_id,
+            user_id,
+            kind: action.kind().to_string(),
+            session_ids,
+            previous_status,
+            previous_deadline,
+            created_at: timestamp(now),
+            expires_at: timestamp(now + Duration::minutes(UNDO_WINDOW_MINUTES)),
+        };
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM undo_actions WHERE group_id = ?")
+            .bind(group_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(&format!("INSERT INTO undo_actions ({UNDO_COLUMNS}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"))
+            .bind(&record.id)
+            .bind(record.group_id)
+            .bind(record.user_id)
+            .bind(&record.kind)
+            .bind(&record.session_ids)
+            .bind(&record.previous_status)
+            .bind(&record.previous_deadline)
+            .bind(&record.created_at)
+            .bind(&record.expires_at)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(record)
+    }
+
+    /// The group's last action, if it can still be undone
+    pub async fn find_latest(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, UndoRecord>(&format!(
+            "SELECT {UNDO_COLUMNS} FROM undo_actions WHERE group_id = ? AND expires_at > ? ORDER BY created_at DESC LIMIT 1"
+        ))
+        .bind(group_id)
+        .bind(timestamp(Utc::now()))
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// A specific action of the group (as referenced by an Undo button), if it can still be undone
+    pub async fn find_by_id(
+        pool: &sqlx::SqlitePool,
+        group_id: i64,
+        id: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, UndoRecord>(&format!(
+            "SELECT {UNDO_COLUMNS} FROM undo_actions WHERE id = ? AND group_id = ? AND expires_at > ?"
+        ))
+        .bind(id)
+        .bind(group_id)
+        .bind(timestamp(Utc::now()))
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The recorded action, or `None` if the row holds an unknown kind
+    pub fn action(&self) -> Option<UndoableAction> {
+        let session_ids: Vec<String> = self.session_ids.split(',')
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        match self.kind.as_str() {
+            "cancel" => Some(UndoableAction::Cancel {
+                session_id: session_ids.into_iter().next()?,
+                previous_status: self.previous_status.clone()?,
+                previous_deadline: self.previous_deadline.clone(),
+            }),
+            "close" => Some(UndoableAction::Close { session_ids }),
+            "deadline" => Some(UndoableAction::Deadline {
+                session_id: session_ids.into_iter().next()?,
+                previous_deadline: self.previous_deadline.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Restore the state from before the action and forget the record
+    ///
+    /// Sessions that changed again since (e.g. a closed poll that was confirmed) are left alone.
+    /// Returns the IDs of the sessions that were restored.
+    pub async fn undo(&self, pool: &sqlx::SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let mut restored = Vec::new();
+
+        // Claim the record first so a double tap can't undo twice
+        let claimed = sqlx::query("DELETE FROM undo_actions WHERE id = ?")
+            .bind(&self.id)
+            .execute(&mut *tx)
+            .await?;
+        if claimed.rows_affected() == 0 {
+            return Ok(restored);
+        }
+
+        match self.action() {
+            Some(UndoableAction::Cancel { session_id, previous_status, previous_deadline }) => {
+                let result = sqlx::query("UPDATE sessions SET status = ?, deadline = ? WHERE id = ? AND status = 'cancelled'")
+                    .bind(&previous_status)
+                    .bind(&previous_deadline)
+                    .bind(&session_id)
+                    .execute(&mut *tx)
+                    .await?;
+                if result.rows_affected() > 0 {
+                    restored.push(session_id);
+                }
+            }
+            Some(UndoableAction::Close { session_ids }) => {
+                for session_id in session_ids {
+                    let result = sqlx::query("UPDATE sessions SET status = 'active' WHERE id = ? AND status = 'closed'")
+                        .bind(&session_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    if result.rows_affected() > 0 {
+                        restored.push(session_id);
+                    }
+                }
+            }
+            Some(UndoableAction::Deadline { session_id, previous_deadline }) => {
+                // Confirming or cancelling since cleared the deadline for good
+                let result = sqlx::query(
+                    "UPDATE sessions SET deadline = ? WHERE id = ? AND status NOT IN ('confirmed', 'cancelled')"
+                )
+                    .bind(&previous_deadline)
+                    .bind(&session_id)
+                    .execute(&mut *tx)
+                    .await?;
+                if result.rows_affected() > 0 {
+                    restored.push(session_id);
+                }
+            }
+            None => tracing::warn!("Undo record {} has unknown kind '{}'", self.id, self.kind),
+        }
+
+        tx.commit().await?;
+        Ok(restored)
+    }
+
+    /// Remove records whose undo window has passed
+    pub async fn delete_expired(pool: &sqlx::SqlitePool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM undo_actions WHERE expires_at <= ?")
+            .bind(timestamp(Utc::now()))
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
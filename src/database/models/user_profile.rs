@@ -0,0 +1,103 @@
+//! The latest name seen for each user, refreshed from their messages and button presses
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+
+/// What a Telegram user was last called
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct UserProfile {
+    /// The user's Telegram ID
+    pub user_id: i64,
+    /// Their @username, if they have one
+    pub username: Option<String>,
+    /// Their first and last name
+    pub display_name: String,
+    /// When either name last changed
+    pub updated_at: String,
+}
+
+impl UserProfile {
+    /// Remember the user's current names as seen in the chat with ID `chat_id`
+    ///
+    /// Nothing is stored when that chat is a group with `store_usernames` turned off, and the
+    /// row is only written when a name actually changed. Returns whether it was.
+    pub async fn record(
+        pool: &sqlx::SqlitePool,
+        chat_id: Option<i64>,
+        user_id: i64,
+        username: Option<&str>,
+        display_name: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO user_profiles (user_id, username, display_name, updated_at) \
+             SELECT ?, ?, ?, ? WHERE NOT EXISTS \
+                 (SELECT 1 FROM groups WHERE telegram_chat_id = ? AND store_usernames = 0) \
+             ON CONFLICT(user_id) DO UPDATE SET \
+                 username = excluded.username, \
+                 display_name = excluded.display_name, \
+                 updated_at = excluded.updated_at \
+             WHERE username IS NOT excluded.username OR display_name IS NOT excluded.display_name"
+        )
+        .bind(user_id)
+        .bind(username)
+        .bind(display_name)
+        .bind(Utc::now().to_rfc3339())
+        .bind(chat_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The stored profiles of `user_ids`, keyed by user ID; unknown users are left out
+    pub async fn find_by_ids(
+        pool: &sqlx::SqlitePool,
+        user_ids: &[i64],
+    ) -> Result<HashMap<i64, Self>, sqlx::Error> {
+        if user_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let placeholders = user_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!(
+            "SELECT user_id, username, display_name, updated_at FROM user_profiles WHERE user_id IN ({placeholders})"
+        );
+        let mut query_builder = sqlx::query_as::<_, UserProfile>(&query);
+        for user_id in user_ids {
+            query_builder = query_builder.bind(user_id);
+        }
+
+        Ok(query_builder.fetch_all(pool).await?
+            .into_iter()
+            .map(|profile| (profile.user_id, profile))
+            .collect())
+    }
+
+    /// The stored profiles whose @username is one of `usernames` (without the `@`, ignoring case)
+    pub async fn find_by_usernames(
+        pool: &sqlx::SqlitePool,
+        usernames: &[&str],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        if usernames.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = usernames.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!(
+            "SELECT user_id, username, display_name, updated_at FROM user_profiles \
+             WHERE username COLLATE NOCASE IN ({placeholders}) ORDER BY updated_at DESC"
+        );
+        let mut query_builder = sqlx::query_as::<_, UserProfile>(&query);
+        for username in usernames {
+            query_builder = query_builder.bind(username);
+        }
+        query_builder.fetch_all(pool).await
+    }
+
+    /// The name to show: the @username if there is one, otherwise the display name
+    pub fn name(&self) -> &str {
+        self.username.as_deref().unwrap_or(&self.display_name)
+    }
+}
@@ -0,0 +1,205 @@
+//! Checks on where the SQLite file lives, run before the connection pool opens
+//!
+//! A Docker volume that isn't mounted (or is mounted read-only) doesn't stop the bot from
+//! starting: it happily creates a database inside the container, and everything is lost on the
+//! next redeploy. These checks make that visible at startup instead.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Name of the file written and removed again to prove the directory is writable
+const PROBE_FILE: &str = ".dnd-scheduler-write-probe";
+
+/// Why the database directory can't be used
+#[derive(Debug)]
+pub enum StorageError {
+    /// The directory didn't exist although a mounted volume was expected there
+    VolumeMissing(PathBuf),
+    /// The directory exists, but on the same filesystem as its parent instead of a mounted volume
+    VolumeNotMounted(PathBuf),
+    /// The directory exists but files can't be created in it
+    ReadOnly {
+        /// The database directory
+        dir: PathBuf,
+        /// What failed when writing the probe file
+        source: io::Error,
+    },
+    /// The directory couldn't be resolved or created
+    Io {
+        /// The database directory
+        dir: PathBuf,
+        /// The underlying error
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::VolumeMissing(dir) => write!(
+                f,
+                "Database directory {} does not exist, but RUNNING_IN_DOCKER is set: \
+                 mount the data volume there, or the database would vanish with the container",
+                dir.display()
+            ),
+            StorageError::VolumeNotMounted(dir) => write!(
+                f,
+                "Database directory {} is not a mounted volume, but RUNNING_IN_DOCKER is set: \
+                 mount the data volume there, or the database would vanish with the container",
+                dir.display()
+            ),
+            StorageError::ReadOnly { dir, source } => write!(
+                f,
+                "Database directory {} is not writable ({source}); check the volume's permissions and that it isn't mounted read-only",
+                dir.display()
+            ),
+            StorageError::Io { dir, source } => write!(f, "Cannot prepare database directory {}: {source}", dir.display()),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::VolumeMissing(_) | StorageError::VolumeNotMounted(_) => None,
+            StorageError::ReadOnly { source, .. } | StorageError::Io { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Where the database file of a `sqlite:` URL lives, `None` for in-memory databases
+pub fn database_file(database_url: &str) -> Option<PathBuf> {
+    let path = database_url.strip_prefix("sqlite:").unwrap_or(database_url);
+    let path = path.strip_prefix("//").unwrap_or(path);
+    let path = path.split('?').next().unwrap_or_default();
+    if path.is_empty() || path == ":memory:" || database_url.contains("mode=memory") {
+        return None;
+    }
+    Some(PathBuf::from(path))
+}
+
+/// Make sure the directory of the database file exists and is writable, returning the file's absolute path
+///
+/// A missing directory is created, unless `expect_volume` says it should have been mounted,
+/// in which case that is an error, as is an existing directory nothing is mounted on (the image
+/// creates the directory, so it's there with or without the volume).
+pub fn prepare_database_dir(database_url: &str, expect_volume: bool) -> Result<Option<PathBuf>, StorageError> {
+    let Some(file) = database_file(database_url) else {
+        return Ok(None);
+    };
+    let file = std::path::absolute(&file).map_err(|source| StorageError::Io { dir: file.clone(), source })?;
+    let dir = file.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("/"));
+
+    if !dir.exists() {
+        if expect_volume {
+            return Err(StorageError::VolumeMissing(dir));
+        }
+        warn!("Database directory {} did not exist, creating it", dir.display());
+        fs::create_dir_all(&dir).map_err(|source| StorageError::Io { dir: dir.clone(), source })?;
+    } else if expect_volume && !is_mount_point(&dir).map_err(|source| StorageError::Io { dir: dir.clone(), source })? {
+        return Err(StorageError::VolumeNotMounted(dir));
+    }
+
+    let probe = dir.join(PROBE_FILE);
+    fs::write(&probe, b"probe")
+        .and_then(|()| fs::remove_file(&probe))
+        .map_err(|source| StorageError::ReadOnly { dir: dir.clone(), source })?;
+
+    info!("Database file: {}", file.display());
+    Ok(Some(file))
+}
+
+/// Whether something is mounted on `dir`: it's on another device than its parent
+///
+/// Without device numbers to compare (outside Unix), every directory counts as mounted.
+fn is_mount_point(dir: &Path) -> io::Result<bool> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let Some(parent) = dir.parent() else {
+            return Ok(true);
+        };
+        Ok(fs::metadata(dir)?.dev() != fs::metadata(parent)?.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = dir;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_file() {
+        assert_eq!(database_file("sqlite:data/bot.db"), Some(PathBuf::from("data/bot.db")));
+        assert_eq!(database_file("sqlite:///app/data/bot.db?mode=rwc"), Some(PathBuf::from("/app/data/bot.db")));
+        assert_eq!(database_file("sqlite::memory:"), None);
+        assert_eq!(database_file("sqlite:file:test?mode=memory&cache=shared"), None);
+    }
+
+    #[test]
+    fn test_missing_directory_needs_a_volume_only_in_docker() {
+        let temp = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", temp.path().join("data/bot.db").display());
+
+        let err = prepare_database_dir(&url, true).unwrap_err();
+        assert!(matches!(err, StorageError::VolumeMissing(_)));
+        assert!(err.to_string().contains("RUNNING_IN_DOCKER"));
+
+        let file = prepare_database_dir(&url, false).unwrap().unwrap();
+        assert!(file.parent().unwrap().is_dir());
+        assert!(!file.parent().unwrap().join(PROBE_FILE).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unmounted_directory_needs_a_volume_only_in_docker() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("data");
+        fs::create_dir(&dir).unwrap();
+        let url = format!("sqlite:{}", dir.join("bot.db").display());
+
+        // Like the image's /app/data when the volume isn't mounted over it
+        let err = prepare_database_dir(&url, true).unwrap_err();
+        assert!(matches!(err, StorageError::VolumeNotMounted(_)), "{err}");
+        assert!(err.to_string().contains("not a mounted volume"));
+
+        assert!(prepare_database_dir(&url, false).unwrap().is_some());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_is_mount_point() {
+        let temp = tempfile::tempdir().unwrap();
+        assert!(!is_mount_point(temp.path()).unwrap());
+        assert!(is_mount_point(Path::new("/proc")).unwrap());
+        assert!(is_mount_point(Path::new("/")).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_only_directory_is_reported() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("ro");
+        fs::create_dir(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+        // Root ignores the permission bits, so there's nothing to check there
+        if fs::write(dir.join("x"), b"").is_ok() {
+            return;
+        }
+
+        let url = format!("sqlite:{}", dir.join("bot.db").display());
+        let err = prepare_database_dir(&url, false).unwrap_err();
+        assert!(matches!(err, StorageError::ReadOnly { .. }), "{err}");
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
@@ -0,0 +1,263 @@
+//! Whole-group archives: everything a group scheduled, as one JSON document
+//!
+//! `/exportgroup` sends one to the chat and `migrate export-group` writes one to disk; both
+//! produce the same [`GroupArchive`], which `migrate import-group` loads into a database again.
+
+use crate::database::models::*;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+/// Version of the archive layout; bumped whenever a field changes meaning or goes away
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// A group's settings and everything it scheduled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupArchive {
+    /// [`ARCHIVE_FORMAT_VERSION`] of the bot that wrote it
+    pub format_version: u32,
+    /// When the archive was made (RFC 3339)
+    pub exported_at: String,
+    /// The group and its settings
+    pub group: Group,
+    /// All of its sessions, whatever their status, oldest first
+    pub sessions: Vec<Session>,
+    /// The options of those sessions
+    pub options: Vec<SessionOption>,
+    /// Every vote on them
+    pub responses: Vec<Response>,
+    /// The reminders that went out for them
+    pub reminders: Vec<Reminder>,
+    /// Totals at export time, for reading the archive without the bot; ignored on import
+    pub stats: StatsSnapshot,
+}
+
+/// Totals over an archive's sessions and votes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    /// Number of sessions by status
+    pub sessions_by_status: BTreeMap<String, usize>,
+    /// Number of votes by answer ("yes", "no", "maybe")
+    pub responses_by_answer: BTreeMap<String, usize>,
+    /// Number of different people who voted
+    pub voters: usize,
+}
+
+impl StatsSnapshot {
+    /// Count up `sessions` and `responses`
+    pub fn from_records(sessions: &[Session], responses: &[Response]) -> Self {
+        let mut snapshot = StatsSnapshot::default();
+        for session in sessions {
+            *snapshot.sessions_by_status.entry(session.status.clone()).or_default() += 1;
+        }
+        for response in responses {
+            *snapshot.responses_by_answer.entry(response.response.clone()).or_default() += 1;
+        }
+        let mut voters: Vec<i64> = responses.iter().map(|response| response.user_id).collect();
+        voters.sort_unstable();
+        voters.dedup();
+        snapshot.voters = voters.len();
+        snapshot
+    }
+}
+
+/// Errors that can occur when importing a [`GroupArchive`]
+#[derive(Debug)]
+pub enum ImportError {
+    /// The archive was written by a bot with a different [`ARCHIVE_FORMAT_VERSION`]
+    UnsupportedVersion(u32),
+    /// A group for this chat ID exists already
+    ChatTaken(i64),
+    /// The underlying database query failed, e.g. because a session ID is already in use
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::UnsupportedVersion(version) => write!(
+                f,
+                "Archive format version {version} isn't supported (expected {ARCHIVE_FORMAT_VERSION})"
+            ),
+            ImportError::ChatTaken(chat_id) => write!(f, "Chat {chat_id} already has a group"),
+            ImportError::Database(e) => write!(f, "Database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<sqlx::Error> for ImportError {
+    fn from(e: sqlx::Error) -> Self {
+        ImportError::Database(e)
+    }
+}
+
+/// What an import created
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// ID of the new group
+    pub group_id: i64,
+    /// Sessions created
+    pub sessions: u64,
+    /// Session options created
+    pub options: u64,
+    /// Votes created
+    pub responses: u64,
+    /// Reminder records created
+    pub reminders: u64,
+}
+
+/// Gather the archive of group `group`
+pub async fn export_group(pool: &SqlitePool, group: &Group) -> Result<GroupArchive, sqlx::Error> {
+    let sessions = Session::find_by_group(pool, group.id).await?;
+    let session_ids: Vec<String> = sessions.iter().map(|s| s.id.clone()).collect();
+    let options = SessionOption::find_by_sessions(pool, &session_ids).await?;
+    let responses = Response::find_all_by_group(pool, group.id).await?;
+    let reminders = sqlx::query_as::<_, Reminder>(
+        "SELECT r.id, r.session_id, r.kind, r.option_id, r.offset_minutes, r.sent_at FROM reminders r \
+         JOIN sessions s ON r.session_id = s.id WHERE s.group_id = ? ORDER BY r.sent_at, r.id"
+    )
+    .bind(group.id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(GroupArchive {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        group: group.clone(),
+        stats: StatsSnapshot::from_records(&sessions, &responses),
+        sessions,
+        options,
+        responses,
+        reminders,
+    })
+}
+
+/// Create a new group from `archive` in one transaction, for chat `chat_id` or the archived
+/// group's own chat if `None`
+///
+/// Sessions, options, votes and reminders keep their IDs, so an archive can't be imported into
+/// the database it came from while the original group is still there. Poll message IDs only
+/// carry over to the same chat.
+#[allow(dead_code)] // Only used by the migrate binary
+pub async fn import_group(
+    pool: &SqlitePool,
+    archive: &GroupArchive,
+    chat_id: Option<i64>,
+) -> Result<ImportReport, ImportError> {
+    if archive.format_version != ARCHIVE_FORMAT_VERSION {
+        return Err(ImportError::UnsupportedVersion(archive.format_version));
+    }
+    let group = &archive.group;
+    let chat_id = chat_id.unwrap_or(group.telegram_chat_id);
+    if Group::find_by_chat_id(pool, chat_id).await?.is_some() {
+        return Err(ImportError::ChatTaken(chat_id));
+    }
+    let same_chat = chat_id == group.telegram_chat_id;
+
+    let mut tx = pool.begin().await?;
+    let group_id = sqlx::query(
+        "INSERT INTO groups (telegram_chat_id, timezone, default_duration, reminder_hours, created_at, store_usernames, \
+         min_notice_hours, language, long_range_every_days, long_range_until_days, quiet_start_hour, quiet_end_hour, \
+         announced_version, whats_new, autoconfirm_threshold, badges) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(chat_id)
+    .bind(&group.timezone)
+    .bind(group.default_duration)
+    .bind(group.reminder_hours)
+    .bind(&group.created_at)
+    .bind(group.store_usernames)
+    .bind(group.min_notice_hours)
+    .bind(&group.language)
+    .bind(group.long_range_every_days)
+    .bind(group.long_range_until_days)
+    .bind(group.quiet_start_hour)
+    .bind(group.quiet_end_hour)
+    .bind(&group.announced_version)
+    .bind(group.whats_new)
+    .bind(group.autoconfirm_threshold)
+    .bind(group.badges)
+    .execute(&mut *tx)
+    .await?
+    .last_insert_rowid();
+    let mut report = ImportReport { group_id, ..ImportReport::default() };
+
+    for session in &archive.sessions {
+        report.sessions += sqlx::query(
+            "INSERT INTO sessions (id, group_id, title, message_id, status, deadline, created_by, created_at, short_id, anonymous, notes, availability, reminders_muted, \
+             last_rendered_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&session.id)
+        .bind(report.group_id)
+        .bind(&session.title)
+        .bind(session.message_id.filter(|_| same_chat))
+        .bind(&session.status)
+        .bind(&session.deadline)
+        .bind(session.created_by)
+        .bind(&session.created_at)
+        .bind(session.short_id)
+        .bind(session.anonymous)
+        .bind(&session.notes)
+        .bind(session.availability)
+        .bind(session.reminders_muted)
+        .bind(session.last_rendered_at.as_ref().filter(|_| same_chat))
+        .bind(&session.updated_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    for option in &archive.options {
+        report.options += sqlx::query(
+            "INSERT INTO session_options (id, session_id, datetime, duration, confirmed) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&option.id)
+        .bind(&option.session_id)
+        .bind(&option.datetime)
+        .bind(option.duration)
+        .bind(option.confirmed)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    for response in &archive.responses {
+        // The archive only holds usernames if the group stored them
+        let username = response.username.as_ref().filter(|_| group.store_usernames);
+        report.responses += sqlx::query(
+            "INSERT INTO responses (id, session_id, option_id, user_id, username, response, created_at, updated_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&response.id)
+        .bind(&response.session_id)
+        .bind(&response.option_id)
+        .bind(response.user_id)
+        .bind(username)
+        .bind(&response.response)
+        .bind(&response.created_at)
+        .bind(&response.updated_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    for reminder in &archive.reminders {
+        report.reminders += sqlx::query(
+            "INSERT INTO reminders (id, session_id, kind, option_id, offset_minutes, sent_at) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&reminder.id)
+        .bind(&reminder.session_id)
+        .bind(&reminder.kind)
+        .bind(&reminder.option_id)
+        .bind(reminder.offset_minutes)
+        .bind(&reminder.sent_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(report)
+}
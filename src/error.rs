@@ -0,0 +1,187 @@
+//! Crate-level error type for command and callback handlers
+//!
+//! Handlers return `Result<(), BotError>` and let the dispatch boundary turn failures into a
+//! user-facing message and a categorized log entry, instead of flattening everything into
+//! `RequestError::Api(ApiError::Unknown(..))`.
+
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use teloxide::RequestError;
+use crate::scheduling::SchedulingError;
+
+/// How many internal errors [`error_log`] keeps
+pub const ERROR_LOG_CAPACITY: usize = 50;
+
+/// Why a command or callback handler failed
+#[derive(Debug)]
+pub enum BotError {
+    /// A database query failed
+    Database(sqlx::Error),
+    /// A Telegram API request failed
+    Telegram(RequestError),
+    /// The user's input was rejected; the message is shown to them as is
+    Validation(String),
+    /// Something the user referred to doesn't exist
+    NotFound {
+        /// What was looked for, e.g. "Session #12"
+        what: String,
+    },
+    /// The user may not manage the session they referred to
+    PermissionDenied,
+    /// The database or Telegram took too long to answer
+    Timeout,
+}
+
+impl BotError {
+    /// Short category name for logs, e.g. "database"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BotError::Database(_) => "database",
+            BotError::Telegram(_) => "telegram",
+            BotError::Validation(_) => "validation",
+            BotError::NotFound { .. } => "not_found",
+            BotError::PermissionDenied => "permission_denied",
+            BotError::Timeout => "timeout",
+        }
+    }
+
+    /// Whether the failure is on our side rather than caused by the user's request
+    pub fn is_internal(&self) -> bool {
+        matches!(self, BotError::Database(_) | BotError::Telegram(_) | BotError::Timeout)
+    }
+
+    /// The database's error code (e.g. "2067" for a unique constraint), if there is one
+    pub fn database_code(&self) -> Option<String> {
+        match self {
+            BotError::Database(e) => e.as_database_error()
+                .and_then(|db_error| db_error.code())
+                .map(|code| code.into_owned()),
+            _ => None,
+        }
+    }
+
+    /// Plain text shown to the user; internal details stay in the logs
+    pub fn user_message(&self) -> String {
+        match self {
+            BotError::Database(_) => {
+                "Something went wrong while accessing the database. Please try again in a moment.".to_string()
+            }
+            BotError::Telegram(_) => {
+                "Telegram rejected a request while handling your command. Please try again.".to_string()
+            }
+            BotError::Validation(message) => message.clone(),
+            BotError::NotFound { what } => format!("{what} not found"),
+            BotError::PermissionDenied => {
+                "Permission denied: only the session creator, chat administrators or the group's bot admins can do that. Ask one of them, or use /list to see who created each session.".to_string()
+            }
+            BotError::Timeout => {
+                "The bot is busy right now and the request timed out. Please try again in a moment.".to_string()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotError::Database(e) => write!(f, "Database error: {e}"),
+            BotError::Telegram(e) => write!(f, "Telegram error: {e}"),
+            BotError::Validation(message) => write!(f, "Validation failed: {message}"),
+            BotError::NotFound { what } => write!(f, "{what} not found"),
+            BotError::PermissionDenied => write!(f, "Permission denied"),
+            BotError::Timeout => write!(f, "Timed out"),
+        }
+    }
+}
+
+impl std::error::Error for BotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BotError::Database(e) => Some(e),
+            BotError::Telegram(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for BotError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::PoolTimedOut => BotError::Timeout,
+            e => BotError::Database(e),
+        }
+    }
+}
+
+impl From<SchedulingError> for BotError {
+    fn from(e: SchedulingError) -> Self {
+        match e {
+            SchedulingError::Database(e) => e.into(),
+            SchedulingError::NotFound { what } => BotError::NotFound { what },
+            e @ (SchedulingError::Invalid(_)
+                | SchedulingError::NotActive { .. }
+                | SchedulingError::NoYesVotes
+                | SchedulingError::RequiredPlayerMissing
+                | SchedulingError::DeadlineAfterOptions { .. }) => {
+                BotError::Validation(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<RequestError> for BotError {
+    fn from(e: RequestError) -> Self {
+        match e {
+            RequestError::Network(ref network) if network.is_timeout() => BotError::Timeout,
+            e => BotError::Telegram(e),
+        }
+    }
+}
+
+/// An internal error as reported at the dispatch boundary
+#[derive(Debug, Clone)]
+pub struct LoggedError {
+    /// When it was reported
+    pub at: DateTime<Utc>,
+    /// [`BotError::kind`] of the error
+    pub kind: &'static str,
+    /// The chat the failed command or callback came from, if any
+    pub chat_id: Option<i64>,
+    /// The error's log message
+    pub message: String,
+}
+
+/// The most recent internal errors, kept in memory for the admin dashboard
+#[derive(Debug, Default)]
+pub struct ErrorLog {
+    entries: Mutex<VecDeque<LoggedError>>,
+}
+
+impl ErrorLog {
+    /// Remember `error`, dropping the oldest entry once `ERROR_LOG_CAPACITY` is reached
+    pub fn record(&self, error: &BotError, chat_id: Option<i64>, at: DateTime<Utc>) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= ERROR_LOG_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(LoggedError {
+            at,
+            kind: error.kind(),
+            chat_id,
+            message: error.to_string(),
+        });
+    }
+
+    /// The remembered errors, newest first
+    pub fn recent(&self) -> Vec<LoggedError> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().rev().cloned().collect()
+    }
+}
+
+/// The process-wide log of internal errors
+pub fn error_log() -> &'static ErrorLog {
+    static LOG: OnceLock<ErrorLog> = OnceLock::new();
+    LOG.get_or_init(ErrorLog::default)
+}
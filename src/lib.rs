@@ -15,7 +15,14 @@ pub mod bot;
 pub mod config;
 /// Database models, connections, and migrations
 pub mod database;
+/// Error type shared by command and callback handlers
+pub mod error;
+/// Scheduling core independent of Telegram, for other front ends
+pub mod scheduling;
 /// Background services like reminders and notifications
 pub mod services;
 /// Utility functions for datetime, validation, and formatting
 pub mod utils;
+/// Test fixtures and invariant checks (requires the `testing` feature)
+#[cfg(feature = "testing")]
+pub mod testing;
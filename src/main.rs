@@ -6,45 +6,53 @@
 
 use anyhow::Result;
 use teloxide::prelude::*;
-use teloxide::dispatching::dialogue::InMemStorage;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod bot;
 mod config;
 mod database;
+mod error;
+mod scheduling;
 mod services;
 mod utils;
 
 use crate::bot::handlers::BotHandler;
-use crate::config::Config;
+use crate::config::{BotMode, Config, LogFormat};
 use crate::database::connection::DatabaseManager;
 use crate::services::reminder::ReminderService;
-use crate::services::health::HealthService;
+use crate::services::admin;
+use crate::services::health::{self, HealthService, RuntimeStatus};
+use crate::services::shutdown::{drain_task, wait_for_os_signal, Shutdown, DEFAULT_DRAIN_TIMEOUT};
 use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "dnd_scheduler_bot=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    // Load configuration
+    // Load configuration first, it says how to log
     dotenvy::dotenv().ok();
     let config = Config::from_env()?;
     
+    // Initialize logging
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| "dnd_scheduler_bot=debug,tower_http=debug".into());
+    let registry = tracing_subscriber::registry().with(filter);
+    match config.log_format {
+        LogFormat::Full => registry.with(tracing_subscriber::fmt::layer()).init(),
+        LogFormat::Compact => registry.with(tracing_subscriber::fmt::layer().compact()).init(),
+        LogFormat::Pretty => registry.with(tracing_subscriber::fmt::layer().pretty()).init(),
+        LogFormat::Json => registry.with(tracing_subscriber::fmt::layer().json().flatten_event(true)).init(),
+    }
+    if config.bot_mode == BotMode::Webhook {
+        return Err(anyhow::anyhow!("BOT_MODE=webhook is not supported by this build yet; use polling"));
+    }
+    
     info!("Starting D&D Scheduler Bot v{}", env!("CARGO_PKG_VERSION"));
-    info!("Configuration loaded - Database: {}, HTTP Port: {}", 
-        config.database_url, config.http_port);
+    info!("Configuration loaded - Database: {} (max {} connections, {}ms busy timeout), HTTP Port: {}", 
+        config.database_url, config.db_max_connections, config.db_busy_timeout_ms, config.http_port);
 
     // Initialize database
     info!("Initializing database connection...");
-    let db_manager = DatabaseManager::new(&config.database_url).await?;
+    let db_manager = DatabaseManager::open(&config.database_url, config.pool_settings(), config.running_in_docker).await?;
     info!("Running database migrations...");
     db_manager.run_migrations().await?;
     let db_arc = Arc::new(db_manager);
@@ -53,7 +61,10 @@ async fn main() -> Result<()> {
     // Initialize bot
     info!("Initializing Telegram bot...");
     let bot = Bot::new(&config.telegram_bot_token);
-    let handler = BotHandler::new(db_arc.as_ref().clone());
+    let status = Arc::new(RuntimeStatus::default());
+    let handler = BotHandler::new(db_arc.as_ref().clone())
+        .with_status(status.clone())
+        .with_command_prefix(config.command_prefix.clone());
     info!("Telegram bot initialized successfully");
     
     // Initialize and start reminder service
@@ -61,7 +72,7 @@ async fn main() -> Result<()> {
     let mut reminder_service = match ReminderService::new(bot.clone(), db_arc.clone()).await {
         Ok(service) => {
             info!("Reminder service initialized successfully");
-            service
+            service.with_status(status.clone()).with_vote_reminders(config.vote_reminder_settings())
         },
         Err(e) => {
             tracing::error!("Failed to create reminder service: {}", e);
@@ -75,8 +86,20 @@ async fn main() -> Result<()> {
         info!("Reminder service started successfully");
     }
     
+    // One signal shared by every long-running task
+    let shutdown = Shutdown::new();
+    let reminder_task = tokio::spawn(reminder_service.stop_on(shutdown.clone()));
+    let heartbeat_task = tokio::spawn(health::run_bot_heartbeat(bot.clone(), status.clone(), shutdown.clone()));
+    
     // Initialize health service
-    let health_service = HealthService::new(db_arc.clone());
+    let health_service = HealthService::new(db_arc.clone(), status);
+    let router = match &config.admin_token {
+        Some(token) => {
+            info!("Admin dashboard enabled at /admin");
+            health_service.router.merge(admin::router(db_arc.clone(), token))
+        }
+        None => health_service.router,
+    };
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", config.http_port))
         .await
         .map_err(|e| anyhow::anyhow!("Failed to bind to port {}: {}", config.http_port, e))?;
@@ -84,40 +107,62 @@ async fn main() -> Result<()> {
     info!("Health check server starting on port {}", config.http_port);
     
     // Run both the bot and health server concurrently
-    let bot_task = tokio::spawn(async move {
-        let storage: std::sync::Arc<InMemStorage<()>> = InMemStorage::new().into();
-        Dispatcher::builder(bot, handler.schema())
-            .dependencies(dptree::deps![storage])
-            .enable_ctrlc_handler()
-            .build()
-            .dispatch()
-            .await;
+    let storage = bot::dialogue::DialogueStorage::new(db_arc.pool.clone());
+    let mut dispatcher = Dispatcher::builder(bot, handler.schema())
+        .dependencies(dptree::deps![storage])
+        .build();
+    let dispatcher_token = dispatcher.shutdown_token();
+    
+    let mut bot_task = tokio::spawn(async move {
+        dispatcher.dispatch().await;
     });
     
-    let health_task = tokio::spawn(async move {
-        if let Err(e) = axum::serve(listener, health_service.router).await {
-            tracing::error!("Health server error: {}", e);
+    let mut health_task = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            if let Err(e) = health::serve(listener, router, shutdown).await {
+                tracing::error!("Health server error: {}", e);
+            }
         }
     });
     
-    // Wait for either task to complete (which would indicate shutdown)
-    tokio::select! {
-        result1 = bot_task => {
-            if let Err(e) = result1 {
+    // Wait for a termination signal, or for a task to die on its own
+    let (bot_finished, health_finished) = tokio::select! {
+        _ = wait_for_os_signal() => (false, false),
+        result = &mut bot_task => {
+            if let Err(e) = result {
                 tracing::error!("Bot task error: {}", e);
             }
+            tracing::warn!("Bot dispatcher exited unexpectedly, shutting down");
+            (true, false)
         }
-        result2 = health_task => {
-            if let Err(e) = result2 {
+        result = &mut health_task => {
+            if let Err(e) = result {
                 tracing::error!("Health task error: {}", e);
             }
+            tracing::warn!("Health server exited unexpectedly, shutting down");
+            (false, true)
         }
-    }
+    };
     
-    // Stop reminder service on shutdown
-    if let Err(e) = reminder_service.stop().await {
-        tracing::warn!("Error stopping reminder service: {}", e);
+    shutdown.trigger();
+    
+    // Stop taking updates; dispatch() returns once in-flight handlers are done
+    if !bot_finished {
+        if let Err(e) = dispatcher_token.shutdown() {
+            tracing::warn!("Dispatcher was not running: {:?}", e);
+        }
+        drain_task("Bot dispatcher", bot_task, DEFAULT_DRAIN_TIMEOUT).await;
     }
+    if !health_finished {
+        drain_task("Health server", health_task, DEFAULT_DRAIN_TIMEOUT).await;
+    }
+    drain_task("Reminder service", reminder_task, DEFAULT_DRAIN_TIMEOUT).await;
+    drain_task("Bot heartbeat", heartbeat_task, DEFAULT_DRAIN_TIMEOUT).await;
+    
+    // Only close the pool once nothing can use it anymore
+    db_arc.pool.close().await;
+    info!("Database connections closed");
     
     info!("Application stopped");
     Ok(())
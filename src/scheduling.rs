@@ -0,0 +1,934 @@
+//! The scheduling core without Telegram: sessions, their options and votes, confirmation and
+//! which reminders are due
+//!
+//! [`SchedulerApi`] only needs the database and speaks in plain IDs, so another front end (a
+//! Matrix bridge, say) can drive the same sessions. The Telegram handlers call through it too;
+//! what they add on top is parsing, permissions and rendering.
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use crate::bot::poll::VoteCounts;
+use crate::bot::selection::{confirmable_maybe_swing, select_autoconfirm_option, select_confirmable_option, vetoed_options, MaybeSwing, ScoredOption, SelectionResult, DEFAULT_VOTE_WEIGHTS};
+use crate::database::models::*;
+use crate::services::reminder::{confirmed_option_starts, plan_reminders, LongRangeCadence, QuietHours, ReminderInterval, ReminderStatus};
+use crate::utils::validation::{validate_response_type, validate_session_title};
+
+/// Statuses in which a session still takes votes; confirmed ones do, so players can update their attendance
+pub const VOTABLE_STATUSES: [&str; 2] = ["active", "confirmed"];
+
+/// How far in the past an option may start, so "now" typed a moment ago still counts
+pub const PAST_OPTION_GRACE_MINUTES: i64 = 5;
+
+/// How soon a new poll's options may start at the earliest, so players get to see it first
+pub const MIN_OPTION_LEAD_MINUTES: i64 = 60;
+
+/// How many months ahead a new poll's options may start at the latest
+pub const MAX_OPTION_MONTHS_AHEAD: u32 = 18;
+
+/// How long a roll call waits for its quorum before it's called off, at most until it starts
+pub const ROLLCALL_EXPIRY_HOURS: i64 = 3;
+
+/// Most players a roll call can ask for
+pub const MAX_ROLLCALL_QUORUM: i64 = 20;
+
+/// Hour (UTC) an availability poll's days start at until one is confirmed with its own time
+pub const AVAILABILITY_START_HOUR: u32 = 18;
+
+/// How close a poll's deadline must be for it to get a vote reminder however many have voted
+pub const VOTE_REMINDER_DEADLINE_HOURS: i64 = 24;
+
+/// Which active polls get a reminder to vote, from the `VOTE_REMINDER_*` settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteReminderSettings {
+    /// How many hours a poll is open before too few voters earn it a reminder
+    pub after_hours: i64,
+    /// Voters a poll needs to be left alone; 0 leaves only the reminders before a deadline
+    pub min_voters: i64,
+    /// Shortest time between two reminders on the same poll, in hours; 0 turns them off
+    pub interval_hours: i64,
+}
+
+impl Default for VoteReminderSettings {
+    fn default() -> Self {
+        Self { after_hours: 48, min_voters: 3, interval_hours: 48 }
+    }
+}
+
+/// Index of the first option starting more than `PAST_OPTION_GRACE_MINUTES` before `now`
+pub fn find_past_option(datetimes: &[DateTime<Utc>], now: DateTime<Utc>) -> Option<usize> {
+    let earliest_allowed = now - Duration::minutes(PAST_OPTION_GRACE_MINUTES);
+    datetimes.iter().position(|dt| *dt < earliest_allowed)
+}
+
+/// Why a new poll can't have an option at some time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionTimeError {
+    /// It has already started
+    Past,
+    /// It starts less than [`MIN_OPTION_LEAD_MINUTES`] after now
+    TooSoon,
+    /// It starts more than [`MAX_OPTION_MONTHS_AHEAD`] months after now
+    TooFarAhead,
+}
+
+/// Whether a new poll can have an option starting at `starts_at`, as of `now`
+pub fn check_option_time(starts_at: DateTime<Utc>, now: DateTime<Utc>) -> Result<(), OptionTimeError> {
+    if starts_at < now {
+        return Err(OptionTimeError::Past);
+    }
+    if starts_at < now + Duration::minutes(MIN_OPTION_LEAD_MINUTES) {
+        return Err(OptionTimeError::TooSoon);
+    }
+    let latest = now.checked_add_months(Months::new(MAX_OPTION_MONTHS_AHEAD));
+    if latest.is_some_and(|latest| starts_at > latest) {
+        return Err(OptionTimeError::TooFarAhead);
+    }
+    Ok(())
+}
+
+/// Index of the first of `datetimes` [`check_option_time`] turns down, and why
+pub fn find_unschedulable_option(datetimes: &[DateTime<Utc>], now: DateTime<Utc>) -> Option<(usize, OptionTimeError)> {
+    datetimes.iter()
+        .enumerate()
+        .find_map(|(i, dt)| check_option_time(*dt, now).err().map(|e| (i, e)))
+}
+
+/// The year `starts_at` was likely meant for if it's in an earlier year than `now`, such as 2027
+/// for "01.03.20" typed in 2026: the first from `now`'s on in which the same day and time can
+/// still be scheduled
+///
+/// `None` for a time earlier this year, where the year probably isn't the mistake, and for
+/// 29 February without a leap year to move to.
+pub fn likely_year(starts_at: DateTime<Utc>, now: DateTime<Utc>) -> Option<i32> {
+    if starts_at.year() >= now.year() {
+        return None;
+    }
+    (now.year()..=now.year() + 1)
+        .filter_map(|year| starts_at.with_year(year))
+        .find(|candidate| check_option_time(*candidate, now).is_ok())
+        .map(|candidate| candidate.year())
+}
+
+/// Index of the first option starting less than `min_notice_hours` after `now`
+///
+/// A `min_notice_hours` of zero means no restriction.
+pub fn find_too_soon_option(
+    datetimes: &[DateTime<Utc>],
+    now: DateTime<Utc>,
+    min_notice_hours: i64,
+) -> Option<usize> {
+    if min_notice_hours <= 0 {
+        return None;
+    }
+
+    let earliest_allowed = now + Duration::hours(min_notice_hours);
+    datetimes.iter().position(|dt| *dt < earliest_allowed)
+}
+
+/// How alike two titles must be, from 0 to 1, for a new session to look like a duplicate
+pub const DUPLICATE_TITLE_SIMILARITY: f64 = 0.8;
+
+/// How alike two session titles are, from 0 (nothing in common) to 1 (the same)
+///
+/// Case, punctuation and extra spaces don't count; what's left is compared by edit distance,
+/// relative to the longer title.
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize_title(a), normalize_title(b));
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// Lowercase letters and digits, with every run of anything else turned into one space
+fn normalize_title(title: &str) -> Vec<char> {
+    let mut normalized = Vec::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            normalized.push(c);
+        } else if normalized.last().is_some_and(|last| *last != ' ') {
+            normalized.push(' ');
+        }
+    }
+    if normalized.last() == Some(&' ') {
+        normalized.pop();
+    }
+    normalized
+}
+
+/// Levenshtein distance: how many characters to insert, delete or replace to turn `a` into `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(ca != cb);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// An active session that a new one looks like a duplicate of
+#[derive(Debug, Clone)]
+pub struct SuspectedDuplicate {
+    /// The existing session
+    pub session: Session,
+    /// Whether its title is at least `DUPLICATE_TITLE_SIMILARITY` alike
+    pub similar_title: bool,
+    /// The days (UTC) both sessions have an option on, earliest first
+    pub shared_dates: Vec<NaiveDate>,
+}
+
+/// The sessions among `existing`, each with its options, that a new session called `title`
+/// with options at `datetimes` looks like: a similar title or an option on the same day
+pub fn find_suspected_duplicates(
+    title: &str,
+    datetimes: &[DateTime<Utc>],
+    existing: Vec<(Session, Vec<SessionOption>)>,
+) -> Vec<SuspectedDuplicate> {
+    existing.into_iter()
+        .filter_map(|(session, options)| {
+            let similar_title = title_similarity(title, &session.title) >= DUPLICATE_TITLE_SIMILARITY;
+            let mut shared_dates: Vec<NaiveDate> = options.iter()
+                .filter_map(|option| DateTime::parse_from_rfc3339(&option.datetime).ok())
+                .map(|dt| dt.with_timezone(&Utc).date_naive())
+                .filter(|date| datetimes.iter().any(|dt| dt.date_naive() == *date))
+                .collect();
+            shared_dates.sort_unstable();
+            shared_dates.dedup();
+            (similar_title || !shared_dates.is_empty()).then_some(SuspectedDuplicate { session, similar_title, shared_dates })
+        })
+        .collect()
+}
+
+/// Why a [`SchedulerApi`] operation failed
+#[derive(Debug)]
+pub enum SchedulingError {
+    /// A database query failed
+    Database(sqlx::Error),
+    /// The input was rejected; the message says why
+    Invalid(String),
+    /// Something referred to doesn't exist
+    NotFound {
+        /// What was looked for, e.g. "Session"
+        what: String,
+    },
+    /// The session's status doesn't allow the operation
+    NotActive {
+        /// The session's current status, e.g. "cancelled"
+        status: String,
+    },
+    /// Nobody voted "yes" on any option, so there's nothing to confirm
+    NoYesVotes,
+    /// Every option with a "yes" vote has a "no" from a required player
+    RequiredPlayerMissing,
+    /// The deadline falls after the session's latest option; pass `force` to set it anyway
+    DeadlineAfterOptions {
+        /// When the latest option starts
+        latest: DateTime<Utc>,
+    },
+}
+
+impl fmt::Display for SchedulingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulingError::Database(e) => write!(f, "Database error: {e}"),
+            SchedulingError::Invalid(message) => write!(f, "{message}"),
+            SchedulingError::NotFound { what } => write!(f, "{what} not found"),
+            SchedulingError::NotActive { status } => write!(f, "Session is already {status}"),
+            SchedulingError::NoYesVotes => write!(f, "No time option has a 'yes' vote yet"),
+            SchedulingError::RequiredPlayerMissing => write!(f, "A required player said no to every time option with a 'yes' vote"),
+            SchedulingError::DeadlineAfterOptions { latest } => write!(
+                f, "The deadline is after the latest time option ({})", latest.format("%Y-%m-%d %H:%M UTC")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchedulingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SchedulingError::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for SchedulingError {
+    fn from(e: sqlx::Error) -> Self {
+        SchedulingError::Database(e)
+    }
+}
+
+/// A session's options and votes, with the option [`SchedulerApi::confirm`] would pick
+///
+/// Tallies are always included; hiding them for anonymous sessions is up to the front end.
+#[derive(Debug, Clone)]
+pub struct Tally {
+    /// The session
+    pub session: Session,
+    /// Its options, earliest first
+    pub options: Vec<SessionOption>,
+    /// Yes/no/maybe counts, one per entry of `options`
+    pub counts: Vec<VoteCounts>,
+    /// Every vote cast on the session
+    pub responses: Vec<Response>,
+    /// IDs of the options a required player voted "no" on
+    #[allow(dead_code)] // Used by tests
+    pub vetoed: Vec<String>,
+    /// The current leader among the other options, `None` until someone votes "yes" on one
+    pub leader: Option<SelectionResult>,
+    /// The option that would overtake `leader` if enough of its maybes turned to yes
+    pub swing: Option<(SessionOption, MaybeSwing)>,
+}
+
+/// A reminder whose time has come and that hasn't been sent yet
+#[derive(Debug, Clone)]
+pub struct DueReminder {
+    /// The confirmed session
+    pub session: Session,
+    /// The confirmed option it's for; a session confirmed for several gets reminders for each
+    pub option: SessionOption,
+    /// When that option starts
+    pub starts_at: DateTime<Utc>,
+    /// Which reminder is due
+    pub interval: ReminderInterval,
+}
+
+/// An active poll that should get a reminder to vote
+#[derive(Debug, Clone)]
+pub struct DueVoteReminder {
+    /// The poll's session, which has a poll message to reply to
+    pub session: Session,
+    /// The group it's in
+    pub group: Group,
+    /// How many players have voted so far
+    pub voters: usize,
+    /// The poll's deadline, if it has one
+    pub deadline: Option<DateTime<Utc>>,
+    /// How long the poll has been open, in minutes; recorded as the reminder's offset
+    pub open_minutes: i64,
+}
+
+/// A roll call that expired without reaching its quorum and was cancelled
+#[derive(Debug, Clone)]
+pub struct ExpiredRollcall {
+    /// How many players it needed
+    pub rollcall: Rollcall,
+    /// Its votes when it expired; the session is cancelled now
+    pub tally: Tally,
+}
+
+/// A session that confirmed itself because an option reached its group's auto-confirm threshold
+#[derive(Debug, Clone)]
+pub struct AutoConfirmation {
+    /// The session, confirmed now
+    pub session: Session,
+    /// The option it was confirmed for
+    pub selection: SelectionResult,
+    /// The "yes" votes the group asks for
+    pub threshold: i64,
+}
+
+/// Scheduling operations on the database, independent of any chat platform
+#[derive(Debug, Clone)]
+pub struct SchedulerApi {
+    pool: sqlx::SqlitePool,
+}
+
+impl SchedulerApi {
+    /// Operate on the database behind `pool`
+    pub fn new(pool: sqlx::SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Start a session in the group with ID `group_id`; add its times with [`Self::add_option`]
+    pub async fn create_session(
+        &self,
+        group_id: i64,
+        title: &str,
+        created_by: i64,
+        anonymous: bool,
+    ) -> Result<Session, SchedulingError> {
+        validate_session_title(title).map_err(|e| SchedulingError::Invalid(format!("Invalid session title: {e}")))?;
+        if Group::find_by_id(&self.pool, group_id).await?.is_none() {
+            return Err(SchedulingError::NotFound { what: "Group".to_string() });
+        }
+
+        let mut session = Session::create(&self.pool, group_id, title.to_string(), created_by).await?;
+        if anonymous {
+            Session::set_anonymous(&self.pool, &session.id, true).await?;
+            session.anonymous = true;
+        }
+        Ok(session)
+    }
+
+    /// Add a time to an active session; without a `duration` it lasts the group's default
+    ///
+    /// Times in the past, inside the group's minimum notice, or equal to an existing option are rejected.
+    pub async fn add_option(
+        &self,
+        session_id: &str,
+        starts_at: DateTime<Utc>,
+        duration: Option<i64>,
+    ) -> Result<SessionOption, SchedulingError> {
+        let session = self.session(session_id).await?;
+        if session.status != "active" {
+            return Err(SchedulingError::NotActive { status: session.status });
+        }
+        let group = Group::find_by_id(&self.pool, session.group_id)
+            .await?
+            .ok_or_else(|| SchedulingError::NotFound { what: "Group".to_string() })?;
+
+        let now = Utc::now();
+        if find_past_option(&[starts_at], now).is_some() {
+            return Err(SchedulingError::Invalid("Time option is in the past".to_string()));
+        }
+        if find_too_soon_option(&[starts_at], now, group.min_notice_hours).is_some() {
+            return Err(SchedulingError::Invalid(format!(
+                "Time option is less than {} hours away",
+                group.min_notice_hours
+            )));
+        }
+        let existing = SessionOption::find_by_session(&self.pool, session_id).await?;
+        let taken = existing.iter().any(|option| {
+            DateTime::parse_from_rfc3339(&option.datetime).is_ok_and(|dt| dt.with_timezone(&Utc) == starts_at)
+        });
+        if taken {
+            return Err(SchedulingError::Invalid("The session already has an option at that time".to_string()));
+        }
+
+        let duration = duration.unwrap_or(group.default_duration);
+        Ok(SessionOption::create(&self.pool, session_id.to_string(), starts_at, duration).await?)
+    }
+
+    /// Save a user's "yes", "no" or "maybe" on one option, replacing their earlier answer there
+    pub async fn record_response(
+        &self,
+        session_id: &str,
+        option_id: &str,
+        user_id: i64,
+        username: Option<String>,
+        response: &str,
+    ) -> Result<Response, SchedulingError> {
+        validate_response_type(response).map_err(|e| SchedulingError::Invalid(format!("Invalid response: {e}")))?;
+        let session = self.session(session_id).await?;
+        if !VOTABLE_STATUSES.contains(&session.status.as_str()) {
+            return Err(SchedulingError::NotActive { status: session.status });
+        }
+        let options = SessionOption::find_by_session(&self.pool, session_id).await?;
+        if !options.iter().any(|option| option.id == option_id) {
+            return Err(SchedulingError::NotFound { what: "Option".to_string() });
+        }
+
+        Ok(Response::upsert(
+            &self.pool,
+            session_id.to_string(),
+            option_id.to_string(),
+            user_id,
+            username,
+            response.to_string(),
+        ).await?)
+    }
+
+    /// The session's options and votes so far
+    pub async fn tally(&self, session_id: &str) -> Result<Tally, SchedulingError> {
+        let session = self.session(session_id).await?;
+        let options = SessionOption::find_by_session(&self.pool, session_id).await?;
+        let responses = Response::find_by_session(&self.pool, session_id).await?;
+        let counts = options.iter()
+            .map(|option| VoteCounts::for_option(&responses, &option.id))
+            .collect();
+        let required = RosterMember::required_user_ids(&self.pool, session.group_id).await?;
+        let vetoed = vetoed_options(&options, &responses, &required);
+        let leader = select_confirmable_option(&options, &responses, &required);
+        let swing = confirmable_maybe_swing(&options, &responses, &required);
+        Ok(Tally { session, options, counts, responses, vetoed, leader, swing })
+    }
+
+    /// Lock an active session in for its best option (see [`select_confirmable_option`])
+    pub async fn confirm(&self, session_id: &str) -> Result<SelectionResult, SchedulingError> {
+        let tally = self.tally(session_id).await?;
+        if tally.session.status != "active" {
+            return Err(SchedulingError::NotActive { status: tally.session.status });
+        }
+        let Some(selection) = tally.leader else {
+            let any_yes = tally.counts.iter().any(|counts| counts.yes > 0);
+            return Err(if any_yes { SchedulingError::RequiredPlayerMissing } else { SchedulingError::NoYesVotes });
+        };
+        self.confirm_selection(session_id, selection).await
+    }
+
+    /// Confirm an active session for `selection`'s winner
+    async fn confirm_selection(&self, session_id: &str, selection: SelectionResult) -> Result<SelectionResult, SchedulingError> {
+        let mut tx = self.pool.begin().await?;
+        // Guard on the status so a concurrent /cancel or /confirm isn't overwritten
+        let updated = sqlx::query!(
+            "UPDATE sessions SET status = 'confirmed', deadline = NULL WHERE id = ? AND status = 'active'",
+            session_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            drop(tx);
+            let status = self.session(session_id).await?.status;
+            return Err(SchedulingError::NotActive { status });
+        }
+        let option_id = &selection.winner.option.id;
+        sqlx::query!(
+            "UPDATE session_options SET confirmed = true WHERE id = ?",
+            option_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(selection)
+    }
+
+    /// Confirm an active or confirmed session for its option number `number`, counting from 1 as
+    /// the poll shows them, keeping any options confirmed before
+    ///
+    /// Unlike [`SchedulerApi::confirm`] the option doesn't need any "yes" votes, and a required
+    /// player's "no" doesn't stop it.
+    pub async fn confirm_option(&self, session_id: &str, number: usize) -> Result<SelectionResult, SchedulingError> {
+        let session = self.session(session_id).await?;
+        if !VOTABLE_STATUSES.contains(&session.status.as_str()) {
+            return Err(SchedulingError::NotActive { status: session.status });
+        }
+        let options = SessionOption::find_by_session(&self.pool, session_id).await?;
+        let Some(option) = number.checked_sub(1).and_then(|i| options.get(i)) else {
+            return Err(SchedulingError::Invalid(format!(
+                "The session has no option {number}; pick one from 1 to {}", options.len()
+            )));
+        };
+        if option.confirmed {
+            return Err(SchedulingError::Invalid(format!("Option {number} is already confirmed")));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        // Guard on the status so a concurrent /cancel isn't overwritten
+        let updated = sqlx::query(
+            "UPDATE sessions SET status = 'confirmed', deadline = NULL WHERE id = ? AND status IN ('active', 'confirmed')"
+        )
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            drop(tx);
+            let status = self.session(session_id).await?.status;
+            return Err(SchedulingError::NotActive { status });
+        }
+        sqlx::query("UPDATE session_options SET confirmed = true WHERE id = ?")
+            .bind(&option.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        let responses = Response::find_by_session(&self.pool, session_id).await?;
+        let counts = VoteCounts::for_option(&responses, &option.id);
+        let mut option = option.clone();
+        option.confirmed = true;
+        Ok(SelectionResult {
+            winner: ScoredOption { option, counts, score: DEFAULT_VOTE_WEIGHTS.score(&counts) },
+            runner_up: None,
+        })
+    }
+
+    /// Set the date responses to an active session are due by, returning the deadline it replaces
+    ///
+    /// A deadline after the latest option would leave nothing to vote on once it passes, so it's
+    /// rejected unless `force` is set.
+    pub async fn set_deadline(
+        &self,
+        session_id: &str,
+        deadline: DateTime<Utc>,
+        now: DateTime<Utc>,
+        force: bool,
+    ) -> Result<Option<String>, SchedulingError> {
+        let session = self.session(session_id).await?;
+        if session.status != "active" {
+            return Err(SchedulingError::NotActive { status: session.status });
+        }
+        if deadline <= now {
+            return Err(SchedulingError::Invalid("Deadline must be in the future".to_string()));
+        }
+        let options = SessionOption::find_by_session(&self.pool, session_id).await?;
+        let latest = options.iter()
+            .filter_map(|option| DateTime::parse_from_rfc3339(&option.datetime).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+            .max();
+        if let Some(latest) = latest.filter(|latest| deadline > *latest && !force) {
+            return Err(SchedulingError::DeadlineAfterOptions { latest });
+        }
+
+        // Guard on the status so a concurrent /confirm or /cancel keeps its session deadline-free
+        let updated = sqlx::query("UPDATE sessions SET deadline = ? WHERE id = ? AND status = 'active'")
+            .bind(deadline.to_rfc3339())
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        if updated.rows_affected() == 0 {
+            let status = self.session(session_id).await?.status;
+            return Err(SchedulingError::NotActive { status });
+        }
+        Ok(session.deadline)
+    }
+
+    /// Confirm the active polls, due by a deadline after `now`, on which an option has as many
+    /// "yes" votes as their group's auto-confirm threshold (see [`select_autoconfirm_option`])
+    ///
+    /// Polls without a deadline are left alone, and so are roll calls, which have their own
+    /// quorum, and availability polls, which need a start time from whoever confirms them.
+    pub async fn autoconfirm(&self, now: DateTime<Utc>) -> Result<Vec<AutoConfirmation>, SchedulingError> {
+        let sessions = sqlx::query_as::<_, Session>(&format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE status = 'active' AND NOT availability AND deadline IS NOT NULL \
+             AND group_id IN (SELECT id FROM groups WHERE autoconfirm_threshold IS NOT NULL) ORDER BY created_at"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut confirmed = Vec::new();
+        for session in sessions {
+            let before_deadline = session.deadline.as_deref()
+                .and_then(|deadline| DateTime::parse_from_rfc3339(deadline).ok())
+                .is_some_and(|deadline| deadline.with_timezone(&Utc) > now);
+            if !before_deadline || Rollcall::find_by_session(&self.pool, &session.id).await?.is_some() {
+                continue;
+            }
+            let Some(threshold) = Group::find_by_id(&self.pool, session.group_id).await?.and_then(|group| group.autoconfirm_threshold) else {
+                continue;
+            };
+            let options = SessionOption::find_by_session(&self.pool, &session.id).await?;
+            let responses = Response::find_by_session(&self.pool, &session.id).await?;
+            let required = RosterMember::required_user_ids(&self.pool, session.group_id).await?;
+            let Some(selection) = select_autoconfirm_option(&options, &responses, &required, threshold) else {
+                continue;
+            };
+
+            match self.confirm_selection(&session.id, selection).await {
+                Ok(selection) => {
+                    let session = self.session(&session.id).await?;
+                    confirmed.push(AutoConfirmation { session, selection, threshold });
+                }
+                // Someone confirmed or cancelled it in the meantime
+                Err(SchedulingError::NotActive { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(confirmed)
+    }
+
+    /// Start a roll call: a session with the single option `starts_at`, later on the day of `now`,
+    /// that needs `quorum` players to say yes within `ROLLCALL_EXPIRY_HOURS`
+    ///
+    /// The group's minimum notice doesn't apply, roll calls being for short notice games.
+    pub async fn create_rollcall(
+        &self,
+        group_id: i64,
+        created_by: i64,
+        starts_at: DateTime<Utc>,
+        quorum: i64,
+        now: DateTime<Utc>,
+    ) -> Result<(Session, SessionOption, Rollcall), SchedulingError> {
+        if !(1..=MAX_ROLLCALL_QUORUM).contains(&quorum) {
+            return Err(SchedulingError::Invalid(format!(
+                "A roll call needs between 1 and {MAX_ROLLCALL_QUORUM} players"
+            )));
+        }
+        if find_past_option(&[starts_at], now).is_some() {
+            return Err(SchedulingError::Invalid("That time has already passed today".to_string()));
+        }
+        if starts_at.date_naive() != now.date_naive() {
+            return Err(SchedulingError::Invalid("Roll calls are for games later today".to_string()));
+        }
+        let group = Group::find_by_id(&self.pool, group_id)
+            .await?
+            .ok_or_else(|| SchedulingError::NotFound { what: "Group".to_string() })?;
+
+        let title = format!("Roll call for {}", starts_at.format("%H:%M"));
+        let session = self.create_session(group_id, &title, created_by, false).await?;
+        let option = SessionOption::create(&self.pool, session.id.clone(), starts_at, group.default_duration).await?;
+        let expires_at = (now + Duration::hours(ROLLCALL_EXPIRY_HOURS)).min(starts_at);
+        let rollcall = Rollcall::create(&self.pool, &session.id, quorum, expires_at).await?;
+        Ok((session, option, rollcall))
+    }
+
+    /// Start an availability poll: a session asking which of `days` work, with one option per
+    /// day at `AVAILABILITY_START_HOUR` until its start time is set with [`Self::set_option_start`]
+    ///
+    /// Days that start in the past or inside the group's minimum notice are left out.
+    pub async fn create_availability_poll(
+        &self,
+        group_id: i64,
+        title: &str,
+        created_by: i64,
+        days: &[NaiveDate],
+        now: DateTime<Utc>,
+    ) -> Result<(Session, Vec<SessionOption>), SchedulingError> {
+        validate_session_title(title).map_err(|e| SchedulingError::Invalid(format!("Invalid session title: {e}")))?;
+        let group = Group::find_by_id(&self.pool, group_id)
+            .await?
+            .ok_or_else(|| SchedulingError::NotFound { what: "Group".to_string() })?;
+        let starts: Vec<DateTime<Utc>> = days.iter()
+            .filter_map(|day| day.and_hms_opt(AVAILABILITY_START_HOUR, 0, 0))
+            .map(|start| start.and_utc())
+            .filter(|start| find_past_option(&[*start], now).is_none())
+            .filter(|start| find_too_soon_option(&[*start], now, group.min_notice_hours).is_none())
+            .collect();
+        if starts.is_empty() {
+            return Err(SchedulingError::Invalid(format!(
+                "Every day of that range is past or less than {} hours away",
+                group.min_notice_hours
+            )));
+        }
+
+        let mut session = self.create_session(group_id, title, created_by, false).await?;
+        Session::set_availability(&self.pool, &session.id, true).await?;
+        session.availability = true;
+        let mut options = Vec::with_capacity(starts.len());
+        for starts_at in starts {
+            options.push(SessionOption::create(&self.pool, session.id.clone(), starts_at, group.default_duration).await?);
+        }
+        Ok((session, options))
+    }
+
+    /// Move a day of an active availability poll to start at `starts_at`, on the same day, so it
+    /// can be confirmed with [`Self::confirm_option`]
+    ///
+    /// Returns the option's number on the poll.
+    pub async fn set_option_start(
+        &self,
+        session_id: &str,
+        option_id: &str,
+        starts_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<usize, SchedulingError> {
+        let session = self.session(session_id).await?;
+        if session.status != "active" {
+            return Err(SchedulingError::NotActive { status: session.status });
+        }
+        if !session.availability {
+            return Err(SchedulingError::Invalid("Only availability polls have days to pick a time for".to_string()));
+        }
+        let options = SessionOption::find_by_session(&self.pool, session_id).await?;
+        let Some(option) = options.iter().find(|option| option.id == option_id) else {
+            return Err(SchedulingError::NotFound { what: "Option".to_string() });
+        };
+        let day = DateTime::parse_from_rfc3339(&option.datetime).map(|dt| dt.with_timezone(&Utc).date_naive());
+        if day != Ok(starts_at.date_naive()) {
+            return Err(SchedulingError::Invalid("The start time has to be on the day that was picked".to_string()));
+        }
+        if find_past_option(&[starts_at], now).is_some() {
+            return Err(SchedulingError::Invalid("That time has already passed".to_string()));
+        }
+
+        SessionOption::set_start(&self.pool, option_id, starts_at).await?;
+        let options = SessionOption::find_by_session(&self.pool, session_id).await?;
+        options.iter()
+            .position(|option| option.id == option_id)
+            .map(|index| index + 1)
+            .ok_or_else(|| SchedulingError::NotFound { what: "Option".to_string() })
+    }
+
+    /// Confirm a roll call once its option has as many "yes" votes as it needs
+    ///
+    /// `None` if it doesn't yet, if a required player said no, or if the session isn't an active
+    /// roll call.
+    pub async fn confirm_rollcall(&self, session_id: &str) -> Result<Option<SelectionResult>, SchedulingError> {
+        let Some(rollcall) = Rollcall::find_by_session(&self.pool, session_id).await? else {
+            return Ok(None);
+        };
+        let tally = self.tally(session_id).await?;
+        let yes_votes = tally.counts.iter().map(|counts| counts.yes).max().unwrap_or(0);
+        if tally.session.status != "active" || (yes_votes as i64) < rollcall.quorum || tally.leader.is_none() {
+            return Ok(None);
+        }
+
+        match self.confirm(session_id).await {
+            Ok(selection) => Ok(Some(selection)),
+            // Another vote got there first
+            Err(SchedulingError::NotActive { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Cancel the roll calls that expired by `now` without reaching their quorum
+    pub async fn expire_rollcalls(&self, now: DateTime<Utc>) -> Result<Vec<ExpiredRollcall>, SchedulingError> {
+        let mut expired = Vec::new();
+        for rollcall in Rollcall::find_expired(&self.pool, now).await? {
+            // Guard on the status so a vote confirming it just now isn't overwritten
+            let updated = sqlx::query("UPDATE sessions SET status = 'cancelled', deadline = NULL WHERE id = ? AND status = 'active'")
+                .bind(&rollcall.session_id)
+                .execute(&self.pool)
+                .await?;
+            if updated.rows_affected() == 0 {
+                continue;
+            }
+            let tally = self.tally(&rollcall.session_id).await?;
+            expired.push(ExpiredRollcall { rollcall, tally });
+        }
+        Ok(expired)
+    }
+
+    /// The reminders of confirmed sessions that should go out at `now`, including the groups'
+    /// long-range ones
+    pub async fn due_reminders(&self, now: DateTime<Utc>) -> Result<Vec<DueReminder>, SchedulingError> {
+        let sessions = sqlx::query_as::<_, Session>(&format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE status = 'confirmed' ORDER BY created_at DESC"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        // One query per table whatever the number of sessions, as this runs every sweep
+        let session_ids: Vec<String> = sessions.iter().map(|session| session.id.clone()).collect();
+        let mut options: HashMap<String, Vec<SessionOption>> = HashMap::new();
+        for option in SessionOption::find_by_sessions(&self.pool, &session_ids).await? {
+            options.entry(option.session_id.clone()).or_default().push(option);
+        }
+        let mut sent_reminders = Reminder::find_by_sessions(&self.pool, &session_ids).await?;
+        let mut failed = ReminderDelivery::failed_offsets_by_session(&self.pool, &session_ids).await?;
+        let mut group_ids: Vec<i64> = sessions.iter().map(|session| session.group_id).collect();
+        group_ids.sort_unstable();
+        group_ids.dedup();
+        let groups = Group::find_by_ids(&self.pool, &group_ids).await?;
+
+        let mut due = Vec::new();
+        for session in sessions {
+            let confirmed = confirmed_option_starts(options.remove(&session.id).unwrap_or_default());
+            if confirmed.is_empty() {
+                tracing::warn!("No valid confirmed option found for confirmed session {}", session.id);
+                continue;
+            }
+
+            let sent = sent_reminders.remove(&session.id).unwrap_or_default();
+            let failed_offsets = failed.remove(&session.id).unwrap_or_default();
+            let group = groups.get(&session.group_id);
+            let (cadence, quiet) = (
+                group.and_then(LongRangeCadence::for_group),
+                group.and_then(QuietHours::for_group),
+            );
+            for (option, starts_at) in confirmed {
+                let sent_offsets: Vec<i64> = sent.iter()
+                    .filter(|reminder| reminder.covers(&option.id))
+                    .map(|reminder| reminder.offset_minutes)
+                    .collect();
+                for reminder in plan_reminders(starts_at, now, &sent_offsets, &failed_offsets, cadence, quiet) {
+                    if reminder.status == ReminderStatus::Due {
+                        due.push(DueReminder {
+                            session: session.clone(),
+                            option: option.clone(),
+                            starts_at,
+                            interval: reminder.interval,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(due)
+    }
+
+    /// The active polls that should get a reminder to vote at `now`: ones open at least
+    /// `after_hours` with fewer than `min_voters` voters, and ones whose deadline is less than
+    /// `VOTE_REMINDER_DEADLINE_HOURS` away
+    ///
+    /// A poll gets at most one reminder per `interval_hours`, none during its group's quiet hours,
+    /// none while it's `/mute`d and none without a poll message to reply to. Roll calls have their
+    /// own expiry and are left out.
+    pub async fn due_vote_reminders(
+        &self,
+        now: DateTime<Utc>,
+        settings: VoteReminderSettings,
+    ) -> Result<Vec<DueVoteReminder>, SchedulingError> {
+        if settings.interval_hours <= 0 {
+            return Ok(Vec::new());
+        }
+        let sessions = sqlx::query_as::<_, Session>(&format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE status = 'active' AND message_id IS NOT NULL \
+             AND NOT reminders_muted AND id NOT IN (SELECT session_id FROM rollcalls) ORDER BY created_at"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let session_ids: Vec<String> = sessions.iter().map(|session| session.id.clone()).collect();
+        let mut voters: HashMap<String, Vec<i64>> = HashMap::new();
+        for response in Response::find_by_sessions(&self.pool, &session_ids).await? {
+            voters.entry(response.session_id.clone()).or_default().push(response.user_id);
+        }
+        let mut sent = Reminder::find_kind_by_sessions(&self.pool, &session_ids, ReminderKind::Vote).await?;
+        let mut group_ids: Vec<i64> = sessions.iter().map(|session| session.group_id).collect();
+        group_ids.sort_unstable();
+        group_ids.dedup();
+        let groups = Group::find_by_ids(&self.pool, &group_ids).await?;
+
+        let parse = |at: &str| DateTime::parse_from_rfc3339(at).ok().map(|at| at.with_timezone(&Utc));
+        let mut due = Vec::new();
+        for session in sessions {
+            let Some(created_at) = parse(&session.created_at) else {
+                continue;
+            };
+            let deadline = session.deadline.as_deref().and_then(parse);
+            let mut session_voters = voters.remove(&session.id).unwrap_or_default();
+            session_voters.sort_unstable();
+            session_voters.dedup();
+
+            let deadline_near = deadline.is_some_and(|deadline| {
+                deadline > now && deadline - now <= Duration::hours(VOTE_REMINDER_DEADLINE_HOURS)
+            });
+            let too_quiet = now - created_at >= Duration::hours(settings.after_hours)
+                && (session_voters.len() as i64) < settings.min_voters;
+            if !deadline_near && !too_quiet {
+                continue;
+            }
+            let reminded_recently = sent.remove(&session.id).unwrap_or_default().iter()
+                .filter_map(|reminder| parse(&reminder.sent_at))
+                .any(|sent_at| now - sent_at < Duration::hours(settings.interval_hours));
+            if reminded_recently {
+                continue;
+            }
+            let Some(group) = groups.get(&session.group_id) else {
+                continue;
+            };
+            if QuietHours::for_group(group).is_some_and(|quiet| quiet.contains(now)) {
+                continue;
+            }
+
+            due.push(DueVoteReminder {
+                group: group.clone(),
+                voters: session_voters.len(),
+                deadline,
+                open_minutes: (now - created_at).num_minutes(),
+                session,
+            });
+        }
+        Ok(due)
+    }
+
+    /// The group's active sessions that a new one called `title` with options at `datetimes`
+    /// looks like a duplicate of (see [`find_suspected_duplicates`])
+    pub async fn find_suspected_duplicates(
+        &self,
+        group_id: i64,
+        title: &str,
+        datetimes: &[DateTime<Utc>],
+    ) -> Result<Vec<SuspectedDuplicate>, SchedulingError> {
+        let mut existing = Vec::new();
+        for session in Session::find_by_group(&self.pool, group_id).await? {
+            if session.status == "active" {
+                let options = SessionOption::find_by_session(&self.pool, &session.id).await?;
+                existing.push((session, options));
+            }
+        }
+        Ok(find_suspected_duplicates(title, datetimes, existing))
+    }
+
+    async fn session(&self, session_id: &str) -> Result<Session, SchedulingError> {
+        Session::find_by_id(&self.pool, session_id)
+            .await?
+            .ok_or_else(|| SchedulingError::NotFound { what: "Session".to_string() })
+    }
+}
@@ -0,0 +1,327 @@
+//! Read-only admin dashboard at `/admin`
+//!
+//! Server-rendered pages over the same model queries the bot uses: groups with their session
+//! counts, a group's sessions, a session's options, votes and reminders, and the most recent
+//! internal errors. Every route is a GET behind `ADMIN_TOKEN`, accepted as a bearer token or as
+//! the password of HTTP Basic auth (any username) so the dashboard opens in a browser.
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response as HttpResponse},
+    routing::get,
+    Router,
+};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use maud::{html, Markup, DOCTYPE};
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::database::{connection::DatabaseManager, models::*};
+use crate::error::error_log;
+use crate::services::reminder::{LongRangeCadence, QuietHours, ReminderOffset};
+use crate::utils::datetime::{format_datetime, format_duration};
+
+/// How many of the latest reminders the overview lists
+pub const RECENT_REMINDERS: i64 = 20;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:2em}\
+th,td{border:1px solid #ccc;padding:.3em .6em;text-align:left}\
+th{background:#f3f3f3}.muted{color:#777}";
+
+#[derive(Clone)]
+struct AdminState {
+    db: Arc<DatabaseManager>,
+    token: Arc<str>,
+}
+
+/// Routes of the dashboard, all requiring `token`
+pub fn router(db: Arc<DatabaseManager>, token: &str) -> Router {
+    let state = AdminState { db, token: token.into() };
+
+    Router::new()
+        .route("/admin", get(overview))
+        .route("/admin/groups/:id", get(group_page))
+        .route("/admin/sessions/:id", get(session_page))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+/// The token from an `Authorization: Bearer` header, or the password of `Authorization: Basic`
+fn presented_token(request: &Request) -> Option<String> {
+    let value = request.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    if let Some(token) = value.strip_prefix("Bearer ") {
+        return Some(token.trim().to_string());
+    }
+    let encoded = value.strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    let credentials = String::from_utf8(decoded).ok()?;
+    credentials.split_once(':').map(|(_, password)| password.to_string())
+}
+
+/// Compare without returning early, so the time taken doesn't reveal how much of a guess was right
+fn tokens_match(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given.iter().zip(expected).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+async fn require_token(State(state): State<AdminState>, request: Request, next: Next) -> HttpResponse {
+    match presented_token(&request) {
+        Some(token) if tokens_match(token.as_bytes(), state.token.as_bytes()) => next.run(request).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Basic realm=\"dnd-scheduler admin\"")],
+            "Unauthorized",
+        ).into_response(),
+    }
+}
+
+fn internal_error(e: sqlx::Error) -> StatusCode {
+    tracing::error!("Admin dashboard query failed: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// A stored RFC 3339 timestamp in the bot's display format, or as stored if it doesn't parse
+fn show_time(stored: &str) -> String {
+    DateTime::parse_from_rfc3339(stored)
+        .map(|dt| format!("{} UTC", format_datetime(&dt.with_timezone(&Utc))))
+        .unwrap_or_else(|_| stored.to_string())
+}
+
+fn page(title: &str, body: Markup) -> Markup {
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                meta charset="utf-8";
+                title { (title) " · D&D Scheduler admin" }
+                style { (STYLE) }
+            }
+            body {
+                nav { a href="/admin" { "← All groups" } }
+                h1 { (title) }
+                (body)
+            }
+        }
+    }
+}
+
+async fn overview(State(state): State<AdminState>) -> Result<Markup, StatusCode> {
+    let pool = &state.db.pool;
+    let groups = Group::find_all(pool).await.map_err(internal_error)?;
+    let session_counts: HashMap<i64, i64> = Session::count_by_group(pool).await.map_err(internal_error)?
+        .into_iter()
+        .collect();
+    let reminders = Reminder::find_recent(pool, RECENT_REMINDERS).await.map_err(internal_error)?;
+    let errors = error_log().recent();
+
+    Ok(page("Groups", html! {
+        table {
+            tr { th { "ID" } th { "Chat" } th { "Timezone" } th { "Language" } th { "Sessions" } th { "Created" } }
+            @for group in &groups {
+                tr {
+                    td { a href={ "/admin/groups/" (group.id) } { (group.id) } }
+                    td { (group.telegram_chat_id) }
+                    td { (group.timezone) }
+                    td { (group.language) }
+                    td { (session_counts.get(&group.id).copied().unwrap_or(0)) }
+                    td { (show_time(&group.created_at)) }
+                }
+            }
+        }
+        @if groups.is_empty() {
+            p.muted { "No groups yet." }
+        }
+
+        h2 { "Recent reminders" }
+        @if reminders.is_empty() {
+            p.muted { "No reminders sent yet." }
+        } @else {
+            table {
+                tr { th { "Sent" } th { "Session" } th { "Before" } }
+                @for reminder in &reminders {
+                    tr {
+                        td { (show_time(&reminder.sent_at)) }
+                        td { a href={ "/admin/sessions/" (reminder.session_id) } { (reminder.session_id) } }
+                        @if reminder.kind == ReminderKind::Vote.as_str() {
+                            td { "Vote reminder" }
+                        } @else {
+                            td { (ReminderOffset::from_minutes(reminder.offset_minutes)) }
+                        }
+                    }
+                }
+            }
+        }
+
+        h2 { "Recent errors" }
+        @if errors.is_empty() {
+            p.muted { "No internal errors since the bot started." }
+        } @else {
+            table {
+                tr { th { "When" } th { "Kind" } th { "Chat" } th { "Error" } }
+                @for error in &errors {
+                    tr {
+                        td { (format_datetime(&error.at)) " UTC" }
+                        td { (error.kind) }
+                        td { @if let Some(chat_id) = error.chat_id { (chat_id) } }
+                        td { (error.message) }
+                    }
+                }
+            }
+        }
+    }))
+}
+
+async fn group_page(State(state): State<AdminState>, Path(group_id): Path<i64>) -> Result<Markup, StatusCode> {
+    let pool = &state.db.pool;
+    let group = Group::find_by_id(pool, group_id).await.map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let sessions = Session::find_by_group(pool, group.id).await.map_err(internal_error)?;
+    let session_ids: Vec<String> = sessions.iter().map(|session| session.id.clone()).collect();
+    let options = SessionOption::find_by_sessions(pool, &session_ids).await.map_err(internal_error)?;
+    let responses = Response::find_by_sessions(pool, &session_ids).await.map_err(internal_error)?;
+    let mut option_counts: HashMap<&str, usize> = HashMap::new();
+    for option in &options {
+        *option_counts.entry(option.session_id.as_str()).or_default() += 1;
+    }
+    let mut vote_counts: HashMap<&str, usize> = HashMap::new();
+    for response in &responses {
+        *vote_counts.entry(response.session_id.as_str()).or_default() += 1;
+    }
+
+    Ok(page(&format!("Group {}", group.id), html! {
+        table {
+            tr { th { "Chat" } td { (group.telegram_chat_id) } }
+            tr { th { "Timezone" } td { (group.timezone) } }
+            tr { th { "Language" } td { (group.language) } }
+            tr { th { "Default duration" } td { (format_duration(group.default_duration)) } }
+            tr { th { "Reminder hours" } td { (group.reminder_hours) } }
+            tr { th { "Minimum notice" } td { (group.min_notice_hours) " hours" } }
+            tr {
+                th { "Long-range reminders" }
+                td {
+                    @match LongRangeCadence::for_group(&group) {
+                        Some(cadence) => (cadence),
+                        None => "off",
+                    }
+                }
+            }
+            tr {
+                th { "Quiet hours" }
+                td {
+                    @match QuietHours::for_group(&group) {
+                        Some(quiet) => (quiet),
+                        None => "off",
+                    }
+                }
+            }
+            tr { th { "Stores usernames" } td { @if group.store_usernames { "yes" } @else { "no" } } }
+            tr { th { "Created" } td { (show_time(&group.created_at)) } }
+        }
+
+        h2 { "Sessions" }
+        @if sessions.is_empty() {
+            p.muted { "No sessions yet." }
+        } @else {
+            table {
+                tr { th { "Ref" } th { "Title" } th { "Status" } th { "Options" } th { "Votes" } th { "Created" } }
+                @for session in &sessions {
+                    tr {
+                        td { (session.display_ref()) }
+                        td { a href={ "/admin/sessions/" (session.id) } { (session.title) } }
+                        td { (session.status) }
+                        td { (option_counts.get(session.id.as_str()).copied().unwrap_or(0)) }
+                        td { (vote_counts.get(session.id.as_str()).copied().unwrap_or(0)) }
+                        td { (show_time(&session.created_at)) }
+                    }
+                }
+            }
+        }
+    }))
+}
+
+async fn session_page(State(state): State<AdminState>, Path(session_id): Path<String>) -> Result<Markup, StatusCode> {
+    let pool = &state.db.pool;
+    let session = Session::find_by_id(pool, &session_id).await.map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let options = SessionOption::find_by_session(pool, &session.id).await.map_err(internal_error)?;
+    let responses = Response::find_by_session(pool, &session.id).await.map_err(internal_error)?;
+    let reminders = Reminder::find_by_session(pool, &session.id).await.map_err(internal_error)?;
+
+    let tally = |option_id: &str, answer: &str| {
+        responses.iter().filter(|r| r.option_id == option_id && r.response == answer).count()
+    };
+    let option_time: HashMap<&str, String> = options.iter()
+        .map(|option| (option.id.as_str(), show_time(&option.datetime)))
+        .collect();
+
+    Ok(page(&session.title, html! {
+        table {
+            tr { th { "Ref" } td { (session.display_ref()) } }
+            tr { th { "Group" } td { a href={ "/admin/groups/" (session.group_id) } { (session.group_id) } } }
+            tr { th { "Status" } td { (session.status) } }
+            tr { th { "Created by" } td { (session.created_by) } }
+            tr { th { "Created" } td { (show_time(&session.created_at)) } }
+            tr { th { "Deadline" } td { @if let Some(deadline) = &session.deadline { (show_time(deadline)) } } }
+            tr { th { "Anonymous" } td { @if session.anonymous { "yes" } @else { "no" } } }
+            tr { th { "Notes" } td { @if let Some(notes) = &session.notes { (notes) } } }
+        }
+
+        h2 { "Options" }
+        // Anonymous polls keep their tallies hidden here too until they're confirmed
+        @let hidden = session.hides_votes();
+        table {
+            tr { th { "Time" } th { "Duration" } th { "Confirmed" } th { "Yes" } th { "Maybe" } th { "No" } }
+            @for option in &options {
+                tr {
+                    td { (show_time(&option.datetime)) }
+                    td { (format_duration(option.duration)) }
+                    td { @if option.confirmed { "✅" } }
+                    @if hidden {
+                        td.muted { "hidden" } td.muted { "hidden" } td.muted { "hidden" }
+                    } @else {
+                        td { (tally(&option.id, "yes")) } td { (tally(&option.id, "maybe")) } td { (tally(&option.id, "no")) }
+                    }
+                }
+            }
+        }
+
+        h2 { "Votes" }
+        @if hidden {
+            p.muted { (responses.len()) @if responses.len() == 1 { " vote" } @else { " votes" } ", hidden until the session is confirmed." }
+        } @else if responses.is_empty() {
+            p.muted { "No votes yet." }
+        } @else {
+            table {
+                tr { th { "Voter" } th { "Option" } th { "Answer" } th { "At" } }
+                @for response in &responses {
+                    tr {
+                        td {
+                            @match &response.username {
+                                Some(username) => { "@" (username) },
+                                None => { "User " (response.user_id) },
+                            }
+                        }
+                        td { (option_time.get(response.option_id.as_str()).map_or("?", String::as_str)) }
+                        td { (response.response) }
+                        td { (show_time(&response.updated_at)) }
+                    }
+                }
+            }
+        }
+
+        h2 { "Reminders" }
+        @if reminders.is_empty() {
+            p.muted { "No reminders sent." }
+        } @else {
+            table {
+                tr { th { "Before" } th { "Sent" } }
+                @for reminder in &reminders {
+                    tr { td { (ReminderOffset::from_minutes(reminder.offset_minutes)) } td { (show_time(&reminder.sent_at)) } }
+                }
+            }
+        }
+    }))
+}
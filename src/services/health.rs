@@ -1,14 +1,114 @@
 use axum::{
     extract::State,
-    http::StatusCode,
-    response::Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
     routing::get,
     Router,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use crate::database::connection::DatabaseManager;
-use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use teloxide::prelude::*;
+use crate::database::connection::{DatabaseManager, PoolSettings};
+use crate::services::metrics::metrics;
+use crate::services::shutdown::Shutdown;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+/// The bot counts as down once Telegram hasn't answered for this long
+pub const BOT_CONTACT_TIMEOUT: Duration = Duration::minutes(5);
+
+/// How often the heartbeat calls `getMe`, so quiet chats don't look like a dead bot
+pub const BOT_HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// The reminder scheduler counts as stuck after this long without running a job
+///
+/// Twice the interval of its most frequent job, the 15 minute housekeeping.
+pub const REMINDER_TICK_TIMEOUT: Duration = Duration::minutes(30);
+
+/// Liveness of the bot's background tasks, updated by the tasks and read by the health endpoints
+///
+/// Times are stored as Unix seconds, 0 meaning "never".
+#[derive(Debug, Default)]
+pub struct RuntimeStatus {
+    last_update_at: AtomicI64,
+    last_get_me_at: AtomicI64,
+    scheduler_running: AtomicBool,
+    last_tick_at: AtomicI64,
+    last_reminder_error: Mutex<Option<String>>,
+}
+
+fn load_time(value: &AtomicI64) -> Option<DateTime<Utc>> {
+    match value.load(Ordering::Relaxed) {
+        0 => None,
+        seconds => Utc.timestamp_opt(seconds, 0).single(),
+    }
+}
+
+impl RuntimeStatus {
+    /// The dispatcher received an update from Telegram
+    pub fn record_update(&self, at: DateTime<Utc>) {
+        self.last_update_at.store(at.timestamp(), Ordering::Relaxed);
+    }
+
+    /// A `getMe` call to Telegram succeeded
+    pub fn record_get_me(&self, at: DateTime<Utc>) {
+        self.last_get_me_at.store(at.timestamp(), Ordering::Relaxed);
+    }
+
+    /// The reminder scheduler started or stopped
+    pub fn set_scheduler_running(&self, running: bool) {
+        self.scheduler_running.store(running, Ordering::Relaxed);
+    }
+
+    /// A reminder or housekeeping job ran
+    pub fn record_tick(&self, at: DateTime<Utc>) {
+        self.last_tick_at.store(at.timestamp(), Ordering::Relaxed);
+    }
+
+    /// A reminder job or the scheduler itself failed
+    pub fn record_reminder_error(&self, error: impl Into<String>) {
+        let mut last_error = self.last_reminder_error.lock().unwrap_or_else(|e| e.into_inner());
+        *last_error = Some(error.into());
+    }
+
+    /// Most recent successful contact with Telegram, through an update or `getMe`
+    pub fn last_bot_contact(&self) -> Option<DateTime<Utc>> {
+        load_time(&self.last_update_at).max(load_time(&self.last_get_me_at))
+    }
+
+    /// Whether Telegram answered within `BOT_CONTACT_TIMEOUT` of `now`
+    pub fn bot_is_live(&self, now: DateTime<Utc>) -> bool {
+        self.last_bot_contact().is_some_and(|at| now - at <= BOT_CONTACT_TIMEOUT)
+    }
+
+    /// Whether the reminder scheduler is running and has run a job within `REMINDER_TICK_TIMEOUT`
+    ///
+    /// A freshly started scheduler that hasn't reached its first job yet counts as live.
+    pub fn reminders_are_live(&self, now: DateTime<Utc>) -> bool {
+        self.scheduler_running.load(Ordering::Relaxed)
+            && load_time(&self.last_tick_at).is_none_or(|at| now - at <= REMINDER_TICK_TIMEOUT)
+    }
+
+    /// The bot section of `/health` as of `now`
+    pub fn bot_health(&self, now: DateTime<Utc>) -> BotHealth {
+        BotHealth {
+            status: if self.bot_is_live(now) { "healthy" } else { "unhealthy" }.to_string(),
+            last_update_at: load_time(&self.last_update_at),
+            last_get_me_at: load_time(&self.last_get_me_at),
+            seconds_since_contact: self.last_bot_contact().map(|at| (now - at).num_seconds()),
+        }
+    }
+
+    /// The reminders section of `/health` as of `now`
+    pub fn reminder_health(&self, now: DateTime<Utc>) -> ReminderHealth {
+        ReminderHealth {
+            status: if self.reminders_are_live(now) { "healthy" } else { "unhealthy" }.to_string(),
+            scheduler_running: self.scheduler_running.load(Ordering::Relaxed),
+            last_tick_at: load_time(&self.last_tick_at),
+            last_error: self.last_reminder_error.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthResponse {
@@ -16,6 +116,10 @@ pub struct HealthResponse {
     pub timestamp: DateTime<Utc>,
     pub version: String,
     pub database: DatabaseHealth,
+    /// Contact with Telegram
+    pub bot: BotHealth,
+    /// The reminder scheduler
+    pub reminders: ReminderHealth,
     pub uptime_seconds: u64,
 }
 
@@ -24,12 +128,46 @@ pub struct DatabaseHealth {
     pub status: String,
     pub connection_pool_size: u32,
     pub response_time_ms: u64,
+    /// Pool settings the database was opened with
+    pub pool_settings: PoolSettings,
+    /// Journal mode reported by SQLite, "wal" unless the database can't use it
+    pub journal_mode: String,
+    /// Whether SQLite is enforcing foreign keys on this connection
+    pub foreign_keys: bool,
+}
+
+/// Whether the bot is still talking to Telegram
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BotHealth {
+    /// "healthy" while Telegram answered within `BOT_CONTACT_TIMEOUT`
+    pub status: String,
+    /// When the last update arrived
+    pub last_update_at: Option<DateTime<Utc>>,
+    /// When the last heartbeat `getMe` succeeded
+    pub last_get_me_at: Option<DateTime<Utc>>,
+    /// Seconds since either of them, `None` before the first contact
+    pub seconds_since_contact: Option<i64>,
+}
+
+/// Whether the reminder scheduler is running its jobs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReminderHealth {
+    /// "healthy" while the scheduler runs and its jobs keep firing
+    pub status: String,
+    /// Whether the scheduler started (and hasn't been stopped)
+    pub scheduler_running: bool,
+    /// When a job last ran
+    pub last_tick_at: Option<DateTime<Utc>>,
+    /// The most recent job or scheduler failure
+    pub last_error: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: Arc<DatabaseManager>,
     pub start_time: DateTime<Utc>,
+    /// Liveness of the bot and reminder tasks
+    pub status: Arc<RuntimeStatus>,
 }
 
 pub struct HealthService {
@@ -37,22 +175,46 @@ pub struct HealthService {
 }
 
 impl HealthService {
-    pub fn new(db: Arc<DatabaseManager>) -> Self {
+    pub fn new(db: Arc<DatabaseManager>, status: Arc<RuntimeStatus>) -> Self {
         let state = AppState {
             db,
             start_time: Utc::now(),
+            status,
         };
 
         let router = Router::new()
             .route("/health", get(health_check))
             .route("/health/ready", get(readiness_check))
             .route("/health/live", get(liveness_check))
+            .route("/metrics", get(metrics_endpoint))
             .with_state(state);
 
         Self { router }
     }
 }
 
+/// Serve `router` on `listener` until `shutdown` is triggered, letting open requests finish
+pub async fn serve(listener: tokio::net::TcpListener, router: Router, shutdown: Shutdown) -> std::io::Result<()> {
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown.signal())
+        .await
+}
+
+/// Call `getMe` every `BOT_HEARTBEAT_INTERVAL` and record successes, until `shutdown`
+pub async fn run_bot_heartbeat(bot: Bot, status: Arc<RuntimeStatus>, shutdown: Shutdown) {
+    let mut interval = tokio::time::interval(BOT_HEARTBEAT_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = shutdown.signal() => return,
+            _ = interval.tick() => {}
+        }
+        match bot.get_me().await {
+            Ok(_) => status.record_get_me(Utc::now()),
+            Err(e) => tracing::warn!("Heartbeat getMe failed: {}", e),
+        }
+    }
+}
+
 async fn health_check(State(state): State<AppState>) -> Result<Json<HealthResponse>, StatusCode> {
     let start = std::time::Instant::now();
     
@@ -63,31 +225,53 @@ async fn health_check(State(state): State<AppState>) -> Result<Json<HealthRespon
     };
     
     let response_time_ms = start.elapsed().as_millis() as u64;
+    let (journal_mode, foreign_keys) = effective_pragmas(&state.db).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to read database pragmas: {}", e);
+        ("unknown".to_string(), false)
+    });
     let uptime = Utc::now()
         .signed_duration_since(state.start_time)
         .num_seconds() as u64;
 
+    let now = Utc::now();
+    let bot = state.status.bot_health(now);
+    let reminders = state.status.reminder_health(now);
+    let status = match (db_status, bot.status.as_str(), reminders.status.as_str()) {
+        ("healthy", "healthy", "healthy") => "healthy",
+        ("healthy", _, _) => "degraded",
+        _ => "unhealthy",
+    };
+
     let health_response = HealthResponse {
-        status: if db_status == "healthy" { "healthy".to_string() } else { "unhealthy".to_string() },
-        timestamp: Utc::now(),
+        status: status.to_string(),
+        timestamp: now,
         version: env!("CARGO_PKG_VERSION").to_string(),
         database: DatabaseHealth {
             status: db_status.to_string(),
             connection_pool_size: state.db.pool.size(),
             response_time_ms,
+            pool_settings: state.db.settings,
+            journal_mode,
+            foreign_keys,
         },
+        bot,
+        reminders,
         uptime_seconds: uptime,
     };
 
-    if health_response.status == "healthy" {
-        Ok(Json(health_response))
-    } else {
+    // A degraded bot still answers, so its details stay readable; readiness is what fails it
+    if health_response.status == "unhealthy" {
         Err(StatusCode::SERVICE_UNAVAILABLE)
+    } else {
+        Ok(Json(health_response))
     }
 }
 
 async fn readiness_check(State(state): State<AppState>) -> Result<Json<&'static str>, StatusCode> {
-    // Check if the service is ready to accept traffic
+    // Ready only while both the database and Telegram answer
+    if !state.status.bot_is_live(Utc::now()) {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
     match test_database_connection(&state.db).await {
         Ok(_) => Ok(Json("ready")),
         Err(_) => Err(StatusCode::SERVICE_UNAVAILABLE),
@@ -99,6 +283,24 @@ async fn liveness_check() -> Json<&'static str> {
     Json("alive")
 }
 
+/// Prometheus scrape endpoint
+async fn metrics_endpoint(State(state): State<AppState>) -> impl IntoResponse {
+    let text = metrics().render(state.db.pool.size(), state.db.pool.num_idle());
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text)
+}
+
+/// Journal mode and foreign key enforcement as SQLite reports them
+async fn effective_pragmas(db: &DatabaseManager) -> Result<(String, bool), sqlx::Error> {
+    let mut conn = db.pool.acquire().await?;
+    let journal_mode = sqlx::query_scalar::<_, String>("PRAGMA journal_mode")
+        .fetch_one(&mut *conn)
+        .await?;
+    let foreign_keys = sqlx::query_scalar::<_, bool>("PRAGMA foreign_keys")
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok((journal_mode, foreign_keys))
+}
+
 async fn test_database_connection(db: &DatabaseManager) -> Result<(), sqlx::Error> {
     // Test database connectivity with a simple query
     sqlx::query("SELECT 1")
@@ -115,7 +317,20 @@ mod tests {
     use axum_test::TestServer;
     use tempfile::TempDir;
 
+    /// Status of a bot that just heard from Telegram and whose scheduler just ran a job
+    fn live_status() -> Arc<RuntimeStatus> {
+        let status = Arc::new(RuntimeStatus::default());
+        status.record_get_me(Utc::now());
+        status.set_scheduler_running(true);
+        status.record_tick(Utc::now());
+        status
+    }
+
     async fn create_test_health_service() -> (HealthService, TempDir) {
+        create_test_health_service_with(live_status()).await
+    }
+
+    async fn create_test_health_service_with(status: Arc<RuntimeStatus>) -> (HealthService, TempDir) {
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
         let db_path = temp_dir.path().join("test.db");
         let db_url = format!("sqlite://{}", db_path.display());
@@ -132,7 +347,7 @@ mod tests {
             .await
             .expect("Failed to run migrations");
         
-        (HealthService::new(db), temp_dir)
+        (HealthService::new(db, status), temp_dir)
     }
 
     #[tokio::test]
@@ -147,6 +362,11 @@ mod tests {
         let health_response: HealthResponse = response.json();
         assert_eq!(health_response.status, "healthy");
         assert_eq!(health_response.database.status, "healthy");
+        assert_eq!(health_response.database.pool_settings, PoolSettings::default());
+        assert_eq!(health_response.database.journal_mode, "wal");
+        assert!(health_response.database.foreign_keys);
+        assert_eq!(health_response.bot.status, "healthy");
+        assert_eq!(health_response.reminders.status, "healthy");
         assert_eq!(health_response.version, env!("CARGO_PKG_VERSION"));
         assert!(health_response.uptime_seconds >= 0);
     }
@@ -176,4 +396,65 @@ mod tests {
         let alive_response: String = response.json();
         assert_eq!(alive_response, "alive");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_stale_bot_is_degraded_and_not_ready() {
+        let status = live_status();
+        let stale = Utc::now() - BOT_CONTACT_TIMEOUT - Duration::minutes(1);
+        status.record_get_me(stale);
+        status.record_update(stale);
+        let (health_service, _temp_dir) = create_test_health_service_with(status.clone()).await;
+        let server = TestServer::new(health_service.router).expect("Failed to create test server");
+
+        let health_response: HealthResponse = server.get("/health").await.json();
+        assert_eq!(health_response.status, "degraded");
+        assert_eq!(health_response.bot.status, "unhealthy");
+        assert!(health_response.bot.seconds_since_contact.is_some_and(|s| s > BOT_CONTACT_TIMEOUT.num_seconds()));
+        assert_eq!(server.get("/health/ready").await.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // One update from Telegram brings it back
+        status.record_update(Utc::now());
+        assert_eq!(server.get("/health/ready").await.status_code(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_never_contacted_bot_is_not_ready() {
+        let (health_service, _temp_dir) = create_test_health_service_with(Arc::new(RuntimeStatus::default())).await;
+        let server = TestServer::new(health_service.router).expect("Failed to create test server");
+
+        assert_eq!(server.get("/health/ready").await.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        let health_response: HealthResponse = server.get("/health").await.json();
+        assert_eq!(health_response.bot.seconds_since_contact, None);
+        assert!(!health_response.reminders.scheduler_running);
+    }
+
+    #[tokio::test]
+    async fn test_failed_scheduler_is_degraded_but_ready() {
+        let status = live_status();
+        status.set_scheduler_running(false);
+        status.record_reminder_error("Failed to start scheduler");
+        let (health_service, _temp_dir) = create_test_health_service_with(status).await;
+        let server = TestServer::new(health_service.router).expect("Failed to create test server");
+
+        let health_response: HealthResponse = server.get("/health").await.json();
+        assert_eq!(health_response.status, "degraded");
+        assert_eq!(health_response.reminders.status, "unhealthy");
+        assert_eq!(health_response.reminders.last_error.as_deref(), Some("Failed to start scheduler"));
+        assert_eq!(server.get("/health/ready").await.status_code(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_reminder_liveness() {
+        let now = Utc::now();
+        let status = RuntimeStatus::default();
+        assert!(!status.reminders_are_live(now));
+
+        // Running without a job yet is fine, a job too long ago is not
+        status.set_scheduler_running(true);
+        assert!(status.reminders_are_live(now));
+        status.record_tick(now - REMINDER_TICK_TIMEOUT - Duration::minutes(1));
+        assert!(!status.reminders_are_live(now));
+        status.record_tick(now);
+        assert!(status.reminders_are_live(now));
+    }
+}
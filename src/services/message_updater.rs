@@ -0,0 +1,201 @@
+//! Coalesced edits of poll messages, so a burst of votes doesn't run into Telegram's rate limit
+//!
+//! Votes are saved right away, but re-rendering the poll goes through [`MessageUpdater`]. The
+//! first request for a session edits at once; requests arriving while that edit runs, or within
+//! [`EDIT_INTERVAL`] of it, are merged into a single edit afterwards. That edit renders whatever
+//! the database holds by then, so it shows every vote of the burst. A 429 is retried after the
+//! wait Telegram asks for, through [`send_with_retry`]; other failures are reported at once.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use teloxide::RequestError;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+use crate::error::BotError;
+use crate::utils::outbound::{send_with_retry, RetryPolicy};
+
+/// Least time between two edits of the same session's message
+pub const EDIT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What everyone waiting for an edit gets; the error is shared, as one edit can answer several votes
+pub type EditResult<T> = Result<T, Arc<BotError>>;
+
+type EditFuture<T> = Pin<Box<dyn Future<Output = Result<T, BotError>> + Send>>;
+
+/// The edit to run next for a session, and who waits for it
+struct PendingEdit<T> {
+    edit: Box<dyn FnMut() -> EditFuture<T> + Send>,
+    waiters: Vec<oneshot::Sender<EditResult<T>>>,
+}
+
+/// Sessions with a task running, mapped to the edit queued for after the current one, if any
+type Sessions<T> = Arc<Mutex<HashMap<String, Option<PendingEdit<T>>>>>;
+
+/// Runs edits of session messages at most once per interval, always with the latest request
+pub struct MessageUpdater<T> {
+    interval: Duration,
+    policy: RetryPolicy,
+    sessions: Sessions<T>,
+}
+
+impl<T: Clone + Send + 'static> MessageUpdater<T> {
+    /// An updater editing each session at most once per `interval`, retrying as `policy` allows
+    pub fn new(interval: Duration, policy: RetryPolicy) -> Self {
+        Self { interval, policy, sessions: Sessions::default() }
+    }
+
+    /// Ask for `session_id`'s message to be edited by `edit`
+    ///
+    /// `edit` replaces any edit still waiting for that session, since it renders the newer state.
+    /// The receiver gets the result of the edit that ran for this request.
+    pub fn request<F, Fut>(&self, session_id: &str, mut edit: F) -> oneshot::Receiver<EditResult<T>>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, BotError>> + Send + 'static,
+    {
+        let (done, result) = oneshot::channel();
+        let edit: Box<dyn FnMut() -> EditFuture<T> + Send> = Box::new(move || Box::pin(edit()) as EditFuture<T>);
+
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        match sessions.get_mut(session_id) {
+            Some(Some(pending)) => {
+                pending.edit = edit;
+                pending.waiters.push(done);
+            }
+            Some(slot @ None) => *slot = Some(PendingEdit { edit, waiters: vec![done] }),
+            None => {
+                sessions.insert(session_id.to_string(), Some(PendingEdit { edit, waiters: vec![done] }));
+                tokio::spawn(run(session_id.to_string(), self.interval, self.policy, self.sessions.clone()));
+            }
+        }
+        result
+    }
+}
+
+/// Take the edit queued for `session_id`, or forget the session when there is none
+fn next_edit<T>(sessions: &Sessions<T>, session_id: &str) -> Option<PendingEdit<T>> {
+    let mut sessions = sessions.lock().unwrap_or_else(|e| e.into_inner());
+    let pending = sessions.get_mut(session_id).and_then(Option::take);
+    if pending.is_none() {
+        sessions.remove(session_id);
+    }
+    pending
+}
+
+/// Run the edits queued for `session_id`, `interval` apart, until none are left
+async fn run<T: Clone>(session_id: String, interval: Duration, policy: RetryPolicy, sessions: Sessions<T>) {
+    while let Some(PendingEdit { mut edit, waiters }) = next_edit(&sessions, &session_id) {
+        let started = Instant::now();
+        // Only a 429 is retried: the voter is waiting, and the next vote renders the poll anyway
+        let attempt = || {
+            let edited = edit();
+            async move {
+                match edited.await {
+                    Err(BotError::Telegram(e @ RequestError::RetryAfter(_))) => Err(e),
+                    other => Ok(other),
+                }
+            }
+        };
+        let (result, attempts) = send_with_retry(&policy, attempt, tokio::time::sleep).await;
+        let result = match result {
+            Ok(result) => result.map_err(Arc::new),
+            Err(e) => Err(Arc::new(BotError::Telegram(e))),
+        };
+        if let Err(e) = &result {
+            tracing::warn!("Editing the message of session {} failed after {} attempts: {}", session_id, attempts, e);
+        }
+        for waiter in waiters {
+            // A voter that stopped waiting is fine
+            let _ = waiter.send(result.clone());
+        }
+        tokio::time::sleep_until(started + interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const FAST_RETRIES: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(100),
+    };
+
+    /// A mock edit that records `label` in `edits`, after failing with a 429 the first `failures` times
+    fn mock_edit(
+        edits: &Arc<Mutex<Vec<&'static str>>>,
+        label: &'static str,
+        failures: u32,
+    ) -> impl FnMut() -> EditFuture<&'static str> + Send + 'static {
+        let edits = edits.clone();
+        let calls = Arc::new(AtomicU32::new(0));
+        move || {
+            let edits = edits.clone();
+            let fail = calls.fetch_add(1, Ordering::SeqCst) < failures;
+            Box::pin(async move {
+                if fail {
+                    return Err(BotError::Telegram(RequestError::RetryAfter(Duration::from_millis(30))));
+                }
+                edits.lock().unwrap().push(label);
+                Ok(label)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_burst_is_coalesced_into_one_more_edit() {
+        let updater = MessageUpdater::new(Duration::from_millis(50), FAST_RETRIES);
+        let edits = Arc::new(Mutex::new(Vec::new()));
+
+        // The first vote is shown at once
+        assert_eq!(updater.request("s1", mock_edit(&edits, "vote 1", 0)).await.unwrap().unwrap(), "vote 1");
+
+        // The four right behind it wait for the interval and share the latest rendering
+        let results: Vec<_> = ["vote 2", "vote 3", "vote 4", "vote 5"].iter()
+            .map(|&label| updater.request("s1", mock_edit(&edits, label, 0)))
+            .collect();
+        for result in results {
+            assert_eq!(result.await.unwrap().unwrap(), "vote 5");
+        }
+        assert_eq!(*edits.lock().unwrap(), ["vote 1", "vote 5"]);
+    }
+
+    #[tokio::test]
+    async fn test_edits_are_spaced_by_the_interval() {
+        let interval = Duration::from_millis(40);
+        let updater = MessageUpdater::new(interval, FAST_RETRIES);
+        let edits = Arc::new(Mutex::new(Vec::new()));
+
+        let started = Instant::now();
+        updater.request("s1", mock_edit(&edits, "first", 0)).await.unwrap().unwrap();
+        updater.request("s1", mock_edit(&edits, "second", 0)).await.unwrap().unwrap();
+        assert!(started.elapsed() >= interval);
+        assert_eq!(*edits.lock().unwrap(), ["first", "second"]);
+
+        // Other sessions don't wait for it
+        let other = Instant::now();
+        updater.request("s2", mock_edit(&edits, "other", 0)).await.unwrap().unwrap();
+        assert!(other.elapsed() < interval);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_waits_as_asked() {
+        let updater = MessageUpdater::new(Duration::from_millis(10), FAST_RETRIES);
+        let edits = Arc::new(Mutex::new(Vec::new()));
+
+        let started = Instant::now();
+        let limited = updater.request("s1", mock_edit(&edits, "limited", 1));
+        assert_eq!(limited.await.unwrap().unwrap(), "limited");
+        assert!(started.elapsed() >= Duration::from_millis(30));
+
+        let doomed = updater.request("s2", mock_edit(&edits, "doomed", 5));
+        let error = doomed.await.unwrap().unwrap_err();
+        assert!(matches!(*error, BotError::Telegram(RequestError::RetryAfter(_))));
+        assert_eq!(*edits.lock().unwrap(), ["limited"]);
+    }
+}
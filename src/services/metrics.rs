@@ -0,0 +1,188 @@
+//! Prometheus metrics, served as text at `/metrics`
+//!
+//! Counters and histograms live in one process-wide [`Metrics`], filled in by the dispatch
+//! endpoints (`command_handler` and `callback_handler`), the reminder sender and the hot queries
+//! of `/list` and voting, so individual commands don't need to know about them.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use crate::error::BotError;
+
+/// Upper bounds in seconds of the latency histogram buckets
+pub const LATENCY_BUCKETS: [f64; 10] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+#[derive(Debug, Default, Clone)]
+struct Histogram {
+    /// Cumulative counts, one per entry of `LATENCY_BUCKETS`
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// Everything `/metrics` reports apart from the connection pool
+#[derive(Debug, Default)]
+pub struct Metrics {
+    commands: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+    command_latency: Mutex<BTreeMap<&'static str, Histogram>>,
+    callbacks: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+    votes: Mutex<BTreeMap<String, u64>>,
+    reminders_sent: AtomicU64,
+    query_latency: Mutex<BTreeMap<&'static str, Histogram>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Outcome label of a handler result: "success", "validation_error", "db_error", ...
+pub fn outcome(result: &Result<(), BotError>) -> &'static str {
+    match result {
+        Ok(()) => "success",
+        Err(BotError::Database(_)) => "db_error",
+        Err(BotError::Telegram(_)) => "telegram_error",
+        Err(BotError::Timeout) => "timeout",
+        Err(BotError::Validation(_) | BotError::NotFound { .. } | BotError::PermissionDenied) => "validation_error",
+    }
+}
+
+impl Metrics {
+    /// A command finished with `outcome` after `elapsed`
+    pub fn record_command(&self, command: &'static str, outcome: &'static str, elapsed: Duration) {
+        *lock(&self.commands).entry((command, outcome)).or_default() += 1;
+        lock(&self.command_latency).entry(command).or_default().observe(elapsed.as_secs_f64());
+    }
+
+    /// A button press of `kind` (e.g. "vote" or "settings") finished with `outcome`
+    pub fn record_callback(&self, kind: &'static str, outcome: &'static str) {
+        *lock(&self.callbacks).entry((kind, outcome)).or_default() += 1;
+    }
+
+    /// A vote was saved
+    pub fn record_vote(&self, response: &str) {
+        *lock(&self.votes).entry(response.to_string()).or_default() += 1;
+    }
+
+    /// A reminder went out
+    pub fn record_reminder_sent(&self) {
+        self.reminders_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The query named `query` took `elapsed`
+    pub fn record_query(&self, query: &'static str, elapsed: Duration) {
+        lock(&self.query_latency).entry(query).or_default().observe(elapsed.as_secs_f64());
+    }
+
+    /// Render all metrics in the Prometheus text format, along with the pool's size and idle count
+    pub fn render(&self, pool_size: u32, pool_idle: usize) -> String {
+        let mut out = String::new();
+
+        header(&mut out, "bot_commands_total", "counter", "Commands handled, by command and outcome");
+        for ((command, outcome), count) in lock(&self.commands).iter() {
+            let _ = writeln!(out, "bot_commands_total{{command=\"{command}\",outcome=\"{outcome}\"}} {count}");
+        }
+        header(&mut out, "bot_command_duration_seconds", "histogram", "Time spent handling a command");
+        for (command, histogram) in lock(&self.command_latency).iter() {
+            write_histogram(&mut out, "bot_command_duration_seconds", &format!("command=\"{command}\""), histogram);
+        }
+
+        header(&mut out, "bot_callbacks_total", "counter", "Button presses handled, by kind and outcome");
+        for ((kind, outcome), count) in lock(&self.callbacks).iter() {
+            let _ = writeln!(out, "bot_callbacks_total{{kind=\"{kind}\",outcome=\"{outcome}\"}} {count}");
+        }
+        header(&mut out, "bot_votes_total", "counter", "Votes saved, by response");
+        for (response, count) in lock(&self.votes).iter() {
+            let _ = writeln!(out, "bot_votes_total{{response=\"{response}\"}} {count}");
+        }
+
+        header(&mut out, "bot_reminders_sent_total", "counter", "Session reminders sent");
+        let _ = writeln!(out, "bot_reminders_sent_total {}", self.reminders_sent.load(Ordering::Relaxed));
+
+        header(&mut out, "db_query_duration_seconds", "histogram", "Latency of the hot database queries");
+        for (query, histogram) in lock(&self.query_latency).iter() {
+            write_histogram(&mut out, "db_query_duration_seconds", &format!("query=\"{query}\""), histogram);
+        }
+
+        header(&mut out, "db_pool_connections", "gauge", "Open connections in the database pool");
+        let _ = writeln!(out, "db_pool_connections {pool_size}");
+        header(&mut out, "db_pool_idle_connections", "gauge", "Idle connections in the database pool");
+        let _ = writeln!(out, "db_pool_idle_connections {pool_idle}");
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn write_histogram(out: &mut String, name: &str, labels: &str, histogram: &Histogram) {
+    for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+        let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {count}");
+    }
+    let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", histogram.count);
+    let _ = writeln!(out, "{name}_sum{{{labels}}} {}", histogram.sum);
+    let _ = writeln!(out, "{name}_count{{{labels}}} {}", histogram.count);
+}
+
+/// The process-wide metrics
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::default)
+}
+
+/// Await `future` and record how long it took as the query `query`
+pub async fn timed<F: Future>(query: &'static str, future: F) -> F::Output {
+    let start = Instant::now();
+    let output = future.await;
+    metrics().record_query(query, start.elapsed());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters_and_histograms() {
+        let metrics = Metrics::default();
+        metrics.record_command("list", "success", Duration::from_millis(20));
+        metrics.record_command("list", "success", Duration::from_secs(3));
+        metrics.record_vote("yes");
+        metrics.record_reminder_sent();
+
+        let text = metrics.render(3, 2);
+        assert!(text.contains("bot_commands_total{command=\"list\",outcome=\"success\"} 2"));
+        assert!(text.contains("bot_command_duration_seconds_bucket{command=\"list\",le=\"0.01\"} 0"));
+        assert!(text.contains("bot_command_duration_seconds_bucket{command=\"list\",le=\"0.025\"} 1"));
+        assert!(text.contains("bot_command_duration_seconds_bucket{command=\"list\",le=\"+Inf\"} 2"));
+        assert!(text.contains("bot_command_duration_seconds_count{command=\"list\"} 2"));
+        assert!(text.contains("bot_votes_total{response=\"yes\"} 1"));
+        assert!(text.contains("bot_reminders_sent_total 1"));
+        assert!(text.contains("db_pool_connections 3"));
+        assert!(text.contains("# TYPE db_query_duration_seconds histogram"));
+    }
+
+    #[test]
+    fn test_outcome_labels() {
+        assert_eq!(outcome(&Ok(())), "success");
+        assert_eq!(outcome(&Err(BotError::PermissionDenied)), "validation_error");
+        assert_eq!(outcome(&Err(BotError::Database(sqlx::Error::RowNotFound))), "db_error");
+    }
+}
@@ -3,3 +3,8 @@ pub mod scheduler;
 pub mod timezone;
 pub mod reminder;
 pub mod health;
+pub mod shutdown;
+pub mod admin;
+pub mod metrics;
+pub mod outbound_queue;
+pub mod message_updater;
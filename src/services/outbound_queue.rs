@@ -0,0 +1,226 @@
+//! Per-chat queues for announcements that go out as several messages, so they arrive in order
+//!
+//! Each chat with queued sends gets a small task reading from an mpsc channel. Workflows enqueue
+//! their sends one after the other and the task runs them in that order, retrying transient
+//! failures with [`send_with_retry`] before it moves on. A message that needs a retry can't be
+//! overtaken by the one queued after it, as it could when each send was awaited on its own.
+//! A task that has had nothing to do for [`IDLE_TIMEOUT`] ends; the next send starts a new one.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use teloxide::prelude::*;
+use tokio::sync::{mpsc, oneshot};
+use crate::error::BotError;
+use crate::utils::outbound::{send_with_retry, RetryPolicy, DEFAULT_RETRY_POLICY};
+
+/// How long a chat's task waits for another send before it ends
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+type SendFuture = Pin<Box<dyn Future<Output = ResponseResult<()>> + Send>>;
+
+/// One queued send; `send` is called again for each retry
+struct QueuedSend {
+    label: &'static str,
+    send: Box<dyn FnMut() -> SendFuture + Send>,
+    done: oneshot::Sender<ResponseResult<()>>,
+}
+
+/// The senders into each chat's queue, shared with the tasks so they can retire themselves
+type Chats = Arc<Mutex<HashMap<i64, mpsc::UnboundedSender<QueuedSend>>>>;
+
+/// The queues of all chats, each drained in order by its own task
+pub struct OutboundQueue {
+    policy: RetryPolicy,
+    chats: Chats,
+}
+
+impl OutboundQueue {
+    /// Queues whose sends are retried as `policy` allows
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self { policy, chats: Chats::default() }
+    }
+
+    /// Queue `send` for `chat_id`, behind everything queued for that chat before
+    ///
+    /// `label` names the send in logs, e.g. "results". The returned receiver gets the final
+    /// result once the send succeeded or gave up; callers that don't need it can drop it.
+    pub fn enqueue<F, Fut>(&self, chat_id: ChatId, label: &'static str, mut send: F) -> oneshot::Receiver<ResponseResult<()>>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ResponseResult<()>> + Send + 'static,
+    {
+        let (done, result) = oneshot::channel();
+        let mut job = QueuedSend { label, send: Box::new(move || Box::pin(send()) as SendFuture), done };
+
+        // Holding the lock while sending means a task can't end between the lookup and the send
+        let mut chats = self.chats.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(sender) = chats.get(&chat_id.0) {
+            match sender.send(job) {
+                Ok(()) => return result,
+                Err(mpsc::error::SendError(returned)) => job = returned,
+            }
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        if sender.send(job).is_ok() {
+            chats.insert(chat_id.0, sender);
+            tokio::spawn(drain(chat_id, receiver, self.policy, self.chats.clone()));
+        }
+        result
+    }
+
+    /// Whether `chat_id` has a task running
+    #[allow(dead_code)] // Used by tests
+    pub fn is_active(&self, chat_id: ChatId) -> bool {
+        self.chats.lock().unwrap_or_else(|e| e.into_inner()).contains_key(&chat_id.0)
+    }
+}
+
+/// Forget `chat_id`'s task unless something was queued meanwhile, which is returned instead
+fn retire(chats: &Chats, chat_id: ChatId, receiver: &mut mpsc::UnboundedReceiver<QueuedSend>) -> Option<QueuedSend> {
+    let mut chats = chats.lock().unwrap_or_else(|e| e.into_inner());
+    match receiver.try_recv() {
+        Ok(job) => Some(job),
+        Err(_) => {
+            chats.remove(&chat_id.0);
+            None
+        }
+    }
+}
+
+/// Run the sends queued for `chat_id` in order until the queue stays empty for `IDLE_TIMEOUT`
+async fn drain(
+    chat_id: ChatId,
+    mut receiver: mpsc::UnboundedReceiver<QueuedSend>,
+    policy: RetryPolicy,
+    chats: Chats,
+) {
+    loop {
+        let job = match tokio::time::timeout(IDLE_TIMEOUT, receiver.recv()).await {
+            Ok(Some(job)) => job,
+            Ok(None) => return,
+            Err(_) => match retire(&chats, chat_id, &mut receiver) {
+                Some(job) => job,
+                None => return,
+            },
+        };
+        let QueuedSend { label, mut send, done } = job;
+        let (result, attempts) = send_with_retry(&policy, &mut send, tokio::time::sleep).await;
+        if let Err(e) = &result {
+            tracing::warn!("Queued {} message to chat {} failed after {} attempts: {}", label, chat_id, attempts, e);
+        }
+        // Nobody waiting for the result is fine
+        let _ = done.send(result);
+    }
+}
+
+/// The process-wide queues, retrying as [`DEFAULT_RETRY_POLICY`] allows
+pub fn outbound_queue() -> &'static OutboundQueue {
+    static QUEUE: OnceLock<OutboundQueue> = OnceLock::new();
+    QUEUE.get_or_init(|| OutboundQueue::new(DEFAULT_RETRY_POLICY))
+}
+
+/// A queued step that does more than one Telegram request, such as refreshing a poll, as a send
+///
+/// Telegram errors are handed to the queue to retry; anything else is logged, since retrying
+/// won't fix it.
+pub fn telegram_result(label: &str, result: Result<(), BotError>) -> ResponseResult<()> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(BotError::Telegram(e)) => Err(e),
+        Err(e) => {
+            tracing::warn!("Queued {} step failed: {}", label, e);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use teloxide::RequestError;
+
+    const FAST_RETRIES: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(50),
+    };
+
+    /// A mock send that records `label` in `sent`, after failing the first `failures` times
+    fn mock_send(
+        sent: &Arc<Mutex<Vec<String>>>,
+        label: &'static str,
+        failures: u32,
+        wait: Duration,
+    ) -> impl FnMut() -> SendFuture + Send + 'static {
+        let sent = sent.clone();
+        let mut calls = 0;
+        move || {
+            calls += 1;
+            let sent = sent.clone();
+            let fail = calls <= failures;
+            Box::pin(async move {
+                if fail {
+                    return Err(RequestError::RetryAfter(wait));
+                }
+                sent.lock().unwrap().push(label.to_string());
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retried_send_is_not_overtaken() {
+        let queue = OutboundQueue::new(FAST_RETRIES);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let chat = ChatId(-100);
+
+        let announcement = queue.enqueue(chat, "announcement", mock_send(&sent, "announcement", 2, Duration::from_millis(20)));
+        let pin = queue.enqueue(chat, "pin", mock_send(&sent, "pin", 0, Duration::ZERO));
+        let calendar = queue.enqueue(chat, "calendar", mock_send(&sent, "calendar", 1, Duration::from_millis(1)));
+
+        assert!(calendar.await.unwrap().is_ok());
+        assert!(announcement.await.unwrap().is_ok());
+        assert!(pin.await.unwrap().is_ok());
+        assert_eq!(*sent.lock().unwrap(), ["announcement", "pin", "calendar"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_send_reports_and_lets_the_next_go() {
+        let queue = OutboundQueue::new(FAST_RETRIES);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let chat = ChatId(-100);
+
+        let doomed = queue.enqueue(chat, "announcement", mock_send(&sent, "announcement", 5, Duration::from_millis(1)));
+        let next = queue.enqueue(chat, "results", mock_send(&sent, "results", 0, Duration::ZERO));
+
+        assert!(matches!(doomed.await.unwrap(), Err(RequestError::RetryAfter(_))));
+        assert!(next.await.unwrap().is_ok());
+        assert_eq!(*sent.lock().unwrap(), ["results"]);
+    }
+
+    #[tokio::test]
+    async fn test_chats_do_not_wait_for_each_other() {
+        let queue = OutboundQueue::new(FAST_RETRIES);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+
+        let slow = queue.enqueue(ChatId(-1), "slow", mock_send(&sent, "slow", 2, Duration::from_millis(40)));
+        let fast = queue.enqueue(ChatId(-2), "fast", mock_send(&sent, "fast", 0, Duration::ZERO));
+
+        assert!(fast.await.unwrap().is_ok());
+        assert!(slow.await.unwrap().is_ok());
+        assert_eq!(*sent.lock().unwrap(), ["fast", "slow"]);
+        assert!(queue.is_active(ChatId(-1)) && queue.is_active(ChatId(-2)));
+        assert!(!queue.is_active(ChatId(-3)));
+    }
+
+    #[test]
+    fn test_telegram_result() {
+        assert!(telegram_result("poll", Ok(())).is_ok());
+        assert!(telegram_result("poll", Err(BotError::Validation("gone".to_string()))).is_ok());
+        let error = BotError::Telegram(RequestError::RetryAfter(Duration::from_secs(1)));
+        assert!(matches!(telegram_result("poll", Err(error)), Err(RequestError::RetryAfter(_))));
+    }
+}
@@ -1,14 +1,38 @@
 use tokio_cron_scheduler::{JobScheduler, Job};
-use chrono::{Utc, Duration};
+use chrono::{DateTime, Duration, FixedOffset, Offset, Timelike, Utc};
 use teloxide::{Bot, prelude::*};
+use crate::bot::autoconfirm::autoconfirm_sessions;
+use crate::bot::commands::checkins::checkin_keyboard;
+use crate::bot::commands::invite::{bot_username, vote_link};
+use crate::bot::commands::rollcall::expire_rollcalls;
+use crate::bot::duties::session_duties_line;
+use crate::bot::vote_reminders::send_vote_reminders;
 use crate::database::{connection::DatabaseManager, models::*};
-use crate::utils::{datetime::format_datetime, markdown::escape_markdown};
+use crate::scheduling::{DueReminder, SchedulerApi, VoteReminderSettings};
+use crate::services::health::RuntimeStatus;
+use crate::services::metrics::metrics;
+use crate::services::shutdown::Shutdown;
+use crate::services::timezone::{normalize_timezone, utc_offset};
+use crate::utils::{
+    datetime::{format_datetime, format_duration},
+    i18n,
+    markdown::escape_markdown,
+    outbound::{classify_send_error, logged_send, plain_fallback, send_with_retry, SendErrorKind, DEFAULT_RETRY_POLICY},
+};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 pub struct ReminderService {
     bot: Bot,
     db: Arc<DatabaseManager>,
     scheduler: JobScheduler,
+    status: Arc<RuntimeStatus>,
+    /// Triggered by [`ReminderService::stop`]; a running sweep stops before its next reminder
+    stopping: Shutdown,
+    /// Held while a reminder sweep runs, so stopping can wait for it
+    sweep: Arc<tokio::sync::Mutex<()>>,
+    /// Which polls get reminders to vote
+    vote_reminders: VoteReminderSettings,
 }
 
 impl ReminderService {
@@ -19,112 +43,799 @@ impl ReminderService {
             bot,
             db,
             scheduler,
+            status: Arc::new(RuntimeStatus::default()),
+            stopping: Shutdown::new(),
+            sweep: Arc::default(),
+            vote_reminders: VoteReminderSettings::default(),
         })
     }
     
+    /// Report the scheduler's state and job runs to `status`, for the health endpoints
+    pub fn with_status(mut self, status: Arc<RuntimeStatus>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Remind groups to vote on their polls as `settings` say
+    pub fn with_vote_reminders(mut self, settings: VoteReminderSettings) -> Self {
+        self.vote_reminders = settings;
+        self
+    }
+    
     pub async fn start(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Run reminder checks twice daily at 9 AM and 6 PM UTC
+        let result = self.schedule_jobs().await;
+        match &result {
+            Ok(()) => self.status.set_scheduler_running(true),
+            Err(e) => self.status.record_reminder_error(format!("Failed to start scheduler: {e}")),
+        }
+        result
+    }
+    
+    async fn schedule_jobs(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Check for due reminders every 15 minutes, so short offsets like "2 hours before" are honored
         let bot = self.bot.clone();
         let db = self.db.clone();
+        let status = self.status.clone();
+        let stopping = self.stopping.clone();
+        let sweep = self.sweep.clone();
         
-        let reminder_job = Job::new_async("0 0 9,18 * * *", move |_uuid, _l| {
+        let reminder_job = Job::new_async("0 */15 * * * *", move |_uuid, _l| {
             let bot = bot.clone();
             let db = db.clone();
+            let status = status.clone();
+            let stopping = stopping.clone();
+            let sweep = sweep.clone();
             Box::pin(async move {
-                if let Err(e) = check_and_send_reminders(bot, db).await {
+                let _running = sweep.lock().await;
+                if stopping.is_triggered() {
+                    return;
+                }
+                status.record_tick(Utc::now());
+                if let Err(e) = check_and_send_reminders(bot, db, &stopping).await {
                     tracing::error!("Failed to send reminders: {}", e);
+                    status.record_reminder_error(format!("Failed to send reminders: {e}"));
                 }
             })
         })?;
         
         self.scheduler.add(reminder_job).await?;
+        
+        // Housekeeping every 15 minutes: forget actions that can no longer be undone, trim the message log
+        let db = self.db.clone();
+        let status = self.status.clone();
+        let maintenance_job = Job::new_async("0 */15 * * * *", move |_uuid, _l| {
+            let db = db.clone();
+            let status = status.clone();
+            Box::pin(async move {
+                status.record_tick(Utc::now());
+                match UndoRecord::delete_expired(&db.pool).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::debug!("Removed {} expired undo records", removed),
+                    Err(e) => tracing::error!("Failed to remove expired undo records: {}", e),
+                }
+                match OutboundMessage::trim(&db.pool, OUTBOUND_LOG_MAX_ROWS).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::debug!("Trimmed {} old outbound message records", removed),
+                    Err(e) => tracing::error!("Failed to trim outbound message records: {}", e),
+                }
+            })
+        })?;
+        
+        self.scheduler.add(maintenance_job).await?;
+
+        // Call off roll calls that didn't get enough players; they only last hours, so check often
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let rollcall_job = Job::new_async("0 */5 * * * *", move |_uuid, _l| {
+            let bot = bot.clone();
+            let db = db.clone();
+            Box::pin(async move {
+                match expire_rollcalls(&bot, &db, Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(expired) => tracing::info!("Called off {} expired roll calls", expired),
+                    Err(e) => tracing::error!("Failed to expire roll calls: {}", e),
+                }
+            })
+        })?;
+        
+        self.scheduler.add(rollcall_job).await?;
+
+        // Confirm polls whose option reached the group's auto-confirm threshold
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let autoconfirm_job = Job::new_async("0 */5 * * * *", move |_uuid, _l| {
+            let bot = bot.clone();
+            let db = db.clone();
+            Box::pin(async move {
+                match autoconfirm_sessions(&bot, &db, Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(confirmed) => tracing::info!("Auto-confirmed {} sessions", confirmed),
+                    Err(e) => tracing::error!("Failed to auto-confirm sessions: {}", e),
+                }
+            })
+        })?;
+        
+        self.scheduler.add(autoconfirm_job).await?;
+
+        // Nudge polls that sit unanswered or are about to close; each gets at most one per interval
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let settings = self.vote_reminders;
+        let vote_reminder_job = Job::new_async("0 10 * * * *", move |_uuid, _l| {
+            let bot = bot.clone();
+            let db = db.clone();
+            Box::pin(async move {
+                match send_vote_reminders(&bot, &db, settings, Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(sent) => tracing::info!("Sent {} vote reminders", sent),
+                    Err(e) => tracing::error!("Failed to send vote reminders: {}", e),
+                }
+            })
+        })?;
+        
+        self.scheduler.add(vote_reminder_job).await?;
+
+        // Archive old confirmed and cancelled sessions once a day
+        let db = self.db.clone();
+        let archive_job = Job::new_async("0 30 3 * * *", move |_uuid, _l| {
+            let db = db.clone();
+            Box::pin(async move {
+                match archive_stale_sessions(&db, Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(archived) => tracing::info!("Archived {} old sessions", archived),
+                    Err(e) => tracing::error!("Failed to archive old sessions: {}", e),
+                }
+            })
+        })?;
+
+        self.scheduler.add(archive_job).await?;
         self.scheduler.start().await?;
         
-        tracing::info!("Reminder service started - checking twice daily at 9 AM and 6 PM UTC");
+        tracing::info!("Reminder service started - checking every {} minutes", REMINDER_SWEEP_MINUTES);
         Ok(())
     }
     
+    /// Stop the scheduler, after letting a running reminder sweep finish the reminder it's on
+    ///
+    /// Stopping mid-reminder could leave it delivered but not marked as sent, or the other way
+    /// round; reminders the sweep didn't get to are sent by the first sweep after a restart.
     pub async fn stop(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.stopping.trigger();
+        let _idle = match self.sweep.try_lock() {
+            Ok(idle) => idle,
+            Err(_) => {
+                tracing::info!("Waiting for the running reminder sweep to finish");
+                self.sweep.lock().await
+            }
+        };
+        self.status.set_scheduler_running(false);
         self.scheduler.shutdown().await?;
         Ok(())
     }
+
+    /// Keep the scheduler running until `shutdown` is triggered, then stop it
+    pub async fn stop_on(mut self, shutdown: Shutdown) {
+        shutdown.signal().await;
+        match self.stop().await {
+            Ok(()) => tracing::info!("Reminder service stopped"),
+            Err(e) => tracing::warn!("Error stopping reminder service: {}", e),
+        }
+    }
+}
+
+/// How long before a session a reminder goes out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderOffset {
+    /// Whole days before; `Days(0)` is the game day reminder on the session's date
+    Days(i64),
+    /// Minutes before, for reminders shortly ahead of the start
+    Minutes(i64),
+}
+
+impl ReminderOffset {
+    /// The offset in minutes, which is the reminder's key in the `reminders` table
+    pub const fn minutes(self) -> i64 {
+        match self {
+            ReminderOffset::Days(days) => days * MINUTES_PER_DAY,
+            ReminderOffset::Minutes(minutes) => minutes,
+        }
+    }
+
+    /// The offset stored as `minutes`, in whole days where it divides evenly
+    pub const fn from_minutes(minutes: i64) -> Self {
+        if minutes % MINUTES_PER_DAY == 0 {
+            ReminderOffset::Days(minutes / MINUTES_PER_DAY)
+        } else {
+            ReminderOffset::Minutes(minutes)
+        }
+    }
+}
+
+impl std::fmt::Display for ReminderOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ReminderOffset::Days(0) => write!(f, "game day"),
+            ReminderOffset::Days(1) => write!(f, "1 day"),
+            ReminderOffset::Days(days) => write!(f, "{days} days"),
+            ReminderOffset::Minutes(minutes) => write!(f, "{}", format_duration(minutes)),
+        }
+    }
+}
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// A reminder sent ahead of every confirmed session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReminderInterval {
+    /// How long before the session it goes out
+    pub offset: ReminderOffset,
+    /// Heading of the reminder message, e.g. "1 Week Reminder"
+    pub name: &'static str,
+}
+
+impl ReminderInterval {
+    /// When this reminder goes out for a session starting at `starts_at`
+    ///
+    /// The game day reminder goes out with the morning check on the session's date, or an hour
+    /// before the start for sessions that begin earlier than that.
+    pub fn send_time(&self, starts_at: DateTime<Utc>) -> DateTime<Utc> {
+        if self.is_game_day() {
+            let morning = starts_at.date_naive()
+                .and_hms_opt(MORNING_CHECK_HOUR, 0, 0)
+                .map(|naive| naive.and_utc())
+                .unwrap_or(starts_at);
+            return morning.min(starts_at - Duration::hours(1));
+        }
+        starts_at - Duration::minutes(self.offset_minutes())
+    }
+
+    /// Its key in the `reminders` table
+    pub const fn offset_minutes(&self) -> i64 {
+        self.offset.minutes()
+    }
+
+    /// Whether this is the reminder on the day of the session, which carries the check-in button
+    pub fn is_game_day(&self) -> bool {
+        self.offset == ReminderOffset::Days(0)
+    }
+
+    /// Whether this is one of a group's [`LongRangeCadence`] reminders rather than a fixed one
+    pub fn is_long_range(&self) -> bool {
+        self.name == LONG_RANGE_REMINDER_NAME
+    }
+
+    /// Its name, with how far ahead it goes out for long-range reminders, e.g. "Still on the Calendar (44 days)"
+    pub fn label(&self) -> String {
+        if self.is_long_range() {
+            format!("{} ({})", self.name, self.offset)
+        } else {
+            self.name.to_string()
+        }
+    }
+}
+
+/// Heading of the reminders a [`LongRangeCadence`] adds
+pub const LONG_RANGE_REMINDER_NAME: &str = "Still on the Calendar";
+
+/// A group's optional extra reminders for sessions confirmed far ahead
+///
+/// One goes out every `every_days` days, counting back from `until_days` before the start, so
+/// with 30 and 14 a session 100 days out is pinged 74 and 44 days ahead before the fixed
+/// [`REMINDER_INTERVALS`] take over. They aren't stored anywhere up front: the sweep works out
+/// which are due and records each in `reminders` under its offset like any other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongRangeCadence {
+    /// Days between two reminders
+    pub every_days: i64,
+    /// Days before the start at which they stop
+    pub until_days: i64,
+}
+
+impl LongRangeCadence {
+    /// The cadence the group configured, `None` if it has none
+    pub fn for_group(group: &Group) -> Option<Self> {
+        group.long_range_every_days
+            .filter(|&every_days| every_days > 0)
+            .map(|every_days| Self { every_days, until_days: group.long_range_until_days })
+    }
+
+    /// The reminders of a session starting at `starts_at` that are still ahead of `now` or inside
+    /// their grace period, furthest out first
+    ///
+    /// Offsets that one of the [`REMINDER_INTERVALS`] already covers are left to it.
+    pub fn intervals(&self, starts_at: DateTime<Utc>, now: DateTime<Utc>) -> Vec<ReminderInterval> {
+        if self.every_days <= 0 {
+            return Vec::new();
+        }
+        let earliest_send = now - Duration::minutes(REMINDER_GRACE_MINUTES);
+        let mut intervals: Vec<ReminderInterval> = (1..)
+            .map(|k| self.until_days + k * self.every_days)
+            .map(|days| ReminderInterval { offset: ReminderOffset::Days(days), name: LONG_RANGE_REMINDER_NAME })
+            .take_while(|interval| interval.send_time(starts_at) >= earliest_send)
+            .filter(|interval| REMINDER_INTERVALS.iter().all(|fixed| fixed.offset_minutes() != interval.offset_minutes()))
+            .collect();
+        intervals.reverse();
+        intervals
+    }
+}
+
+impl std::fmt::Display for LongRangeCadence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "every {} until {} before", ReminderOffset::Days(self.every_days), ReminderOffset::Days(self.until_days))
+    }
+}
+
+/// Hours of the day in which a group gets no reminders, e.g. from 22 until 7
+///
+/// The hours are on the group's clock, and a window that starts later than it ends wraps
+/// around midnight. Reminders that fall inside it are held back until it ends rather than
+/// sent or dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    /// First quiet hour
+    pub start_hour: u32,
+    /// Hour at which the quiet ends
+    pub end_hour: u32,
+    /// The group's offset from UTC
+    pub utc_offset: FixedOffset,
+}
+
+impl QuietHours {
+    /// The quiet hours the group configured, `None` if it has none
+    ///
+    /// Timezones [`utc_offset`] can't read count as UTC.
+    pub fn for_group(group: &Group) -> Option<Self> {
+        let start_hour = u32::try_from(group.quiet_start_hour?).ok().filter(|hour| *hour < 24)?;
+        let end_hour = u32::try_from(group.quiet_end_hour?).ok().filter(|hour| *hour < 24)?;
+        if start_hour == end_hour {
+            return None;
+        }
+        let utc_offset = utc_offset(&group.timezone).unwrap_or_else(|| {
+            tracing::debug!("Timezone '{}' of group {} has no fixed offset, using UTC for its quiet hours", group.timezone, group.id);
+            Utc.fix()
+        });
+        Some(Self { start_hour, end_hour, utc_offset })
+    }
+
+    /// Whether `at` falls inside the quiet hours
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let hour = at.with_timezone(&self.utc_offset).hour();
+        if self.start_hour < self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+
+    /// `at` itself if it's outside the quiet hours, otherwise the moment they end
+    pub fn next_allowed(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        if !self.contains(at) {
+            return at;
+        }
+        let local = at.with_timezone(&self.utc_offset);
+        // Before midnight in a window that wraps around it, the quiet ends tomorrow
+        let date = if local.hour() >= self.end_hour {
+            local.date_naive() + Duration::days(1)
+        } else {
+            local.date_naive()
+        };
+        date.and_hms_opt(self.end_hour, 0, 0)
+            .and_then(|naive| naive.and_local_timezone(self.utc_offset).single())
+            .map_or(at, |end| end.with_timezone(&Utc))
+    }
+}
+
+impl std::fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:00-{:02}:00", self.start_hour, self.end_hour)
+    }
+}
+
+/// The reminders the sweep sends, furthest out first
+pub const REMINDER_INTERVALS: [ReminderInterval; 5] = [
+    ReminderInterval { offset: ReminderOffset::Days(14), name: "2 Week Reminder" },
+    ReminderInterval { offset: ReminderOffset::Days(7), name: "1 Week Reminder" },
+    ReminderInterval { offset: ReminderOffset::Days(3), name: "3 Day Reminder" },
+    ReminderInterval { offset: ReminderOffset::Days(0), name: "Game Day" },
+    ReminderInterval { offset: ReminderOffset::Minutes(120), name: "2 Hour Reminder" },
+];
+
+/// Hour (UTC) at which the game day reminders go out
+pub const MORNING_CHECK_HOUR: u32 = 9;
+
+/// How often the sweep looks for due reminders
+pub const REMINDER_SWEEP_MINUTES: i64 = 15;
+
+/// A reminder is still sent this long after its send time, so a restart or a skipped sweep doesn't lose it
+pub const REMINDER_GRACE_MINUTES: i64 = 60;
+
+/// Where a reminder stands at a given moment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderStatus {
+    /// Already sent
+    Sent,
+    /// Within the send window and not sent yet; the next sweep sends it
+    Due,
+    /// Its send time is still ahead
+    Scheduled,
+    /// Its send window passed without it being sent
+    Missed,
+}
+
+/// One reminder for a session, with when it's sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedReminder {
+    /// Which reminder this is
+    pub interval: ReminderInterval,
+    /// When it's meant to go out
+    pub send_at: DateTime<Utc>,
+    /// Where it stands
+    pub status: ReminderStatus,
+}
+
+/// The reminders for a session starting at `starts_at`, as of `now`
+///
+/// `sent_offsets` holds the `offset_minutes` of the reminders already sent, and `cadence` the
+/// group's long-range reminders, which come first. The sweep sends exactly the `Due` ones, so
+/// previews built from this match what actually happens.
+///
+/// A reminder is due for `REMINDER_GRACE_MINUTES` after its send time. Those in
+/// `failed_offsets`, whose delivery failed, stay due after that until the next reminder is,
+/// or until the session starts, so a failure makes them late rather than lost.
+///
+/// Reminders that would go out during the group's `quiet` hours are moved to when they end,
+/// and retries wait for them too. One that would then come after the session starts keeps its
+/// time: the players are up for the session anyway.
+pub fn plan_reminders(
+    starts_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    sent_offsets: &[i64],
+    failed_offsets: &[i64],
+    cadence: Option<LongRangeCadence>,
+    quiet: Option<QuietHours>,
+) -> Vec<PlannedReminder> {
+    // Quiet hours hold a reminder back by less than a day, so look that far back for long-range ones
+    let since = if quiet.is_some() { now - Duration::days(1) } else { now };
+    let long_range = cadence.map(|cadence| cadence.intervals(starts_at, since)).unwrap_or_default();
+    let intervals: Vec<ReminderInterval> = long_range.into_iter().chain(REMINDER_INTERVALS).collect();
+    let after_quiet = |at: DateTime<Utc>| match quiet.map(|quiet| quiet.next_allowed(at)) {
+        Some(later) if later < starts_at => later,
+        _ => at,
+    };
+    let send_times: Vec<DateTime<Utc>> = intervals.iter()
+        .map(|interval| after_quiet(interval.send_time(starts_at)))
+        .collect();
+    let quiet_now = after_quiet(now) > now;
+    intervals.into_iter()
+        .zip(send_times.iter().copied())
+        .map(|(interval, send_at)| {
+            // The game day reminder can go out after the 2 hour one, so look at times, not order
+            let superseded_at = send_times.iter()
+                .copied()
+                .filter(|other| *other > send_at)
+                .min()
+                .unwrap_or(starts_at);
+            let in_grace = now - send_at <= Duration::minutes(REMINDER_GRACE_MINUTES);
+            let retrying = failed_offsets.contains(&interval.offset_minutes()) && now < superseded_at;
+            let status = if sent_offsets.contains(&interval.offset_minutes()) {
+                ReminderStatus::Sent
+            } else if send_at > now || (retrying && !in_grace && quiet_now) {
+                ReminderStatus::Scheduled
+            } else if in_grace || retrying {
+                ReminderStatus::Due
+            } else {
+                ReminderStatus::Missed
+            };
+            PlannedReminder { interval, send_at, status }
+        })
+        // Only the quiet hours' look back finds long-range ones already missed; leave them out as before
+        .filter(|reminder| !(reminder.interval.is_long_range() && reminder.status == ReminderStatus::Missed))
+        .collect()
+}
+
+/// MarkdownV2 preview of the reminders planned for `session`, which starts at `starts_at`
+///
+/// Times are shown in the group's `timezone`, or in UTC if [`utc_offset`] can't read it.
+pub fn render_reminder_preview(
+    session: &Session,
+    starts_at: DateTime<Utc>,
+    plan: &[PlannedReminder],
+    timezone: &str,
+) -> String {
+    let offset = utc_offset(timezone).unwrap_or_else(|| Utc.fix());
+    let zone = normalize_timezone(timezone).unwrap_or_else(|| "UTC".to_string());
+    let local = |at: &DateTime<Utc>| {
+        let shifted = *at + Duration::seconds(offset.local_minus_utc().into());
+        escape_markdown(&format!("{} {zone}", format_datetime(&shifted)))
+    };
+    
+    let mut text = format!(
+        "🔎 **Reminder preview**\n\n🎲 **{}** \\({}\\)\n📅 Starts {}\n\n",
+        escape_markdown(&session.title),
+        escape_markdown(&session.display_ref()),
+        local(&starts_at),
+    );
     
-    // Manual trigger for testing
-    pub async fn check_reminders_now(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        check_and_send_reminders(self.bot.clone(), self.db.clone()).await
+    for reminder in plan {
+        let status = match reminder.status {
+            ReminderStatus::Sent => "✅ sent",
+            ReminderStatus::Due => "🔔 due, goes out with the next check",
+            ReminderStatus::Scheduled => "⏳ scheduled",
+            ReminderStatus::Missed => "⏭️ past, not sent",
+        };
+        text.push_str(&format!(
+            "• {}: {} \\- {}\n",
+            escape_markdown(&reminder.interval.label()),
+            local(&reminder.send_at),
+            escape_markdown(status),
+        ));
     }
+    
+    text.push_str(&format!("\n_Reminders are checked every {REMINDER_SWEEP_MINUTES} minutes_"));
+    text
 }
 
-async fn check_and_send_reminders(
-    bot: Bot,
-    db: Arc<DatabaseManager>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let now = Utc::now();
-    
-    // Define reminder intervals
-    let reminder_intervals = vec![
-        (Duration::days(14), "📅 **2 Week Reminder**"),
-        (Duration::days(7), "📅 **1 Week Reminder**"), 
-        (Duration::days(3), "📅 **3 Day Reminder**"),
-    ];
-    
-    // Get all confirmed sessions
-    let confirmed_sessions = get_confirmed_sessions(&db.pool).await?;
-    
-    for session in confirmed_sessions {
-        // Get the confirmed session option
-        let session_options = SessionOption::find_by_session(&db.pool, &session.id).await?;
-        let confirmed_option = session_options.iter()
-            .find(|opt| opt.confirmed)
-            .ok_or("No confirmed option found for confirmed session")?;
+/// The group's confirmed session starting soonest after `now`, with the confirmed option that
+/// starts then and its start
+pub async fn next_confirmed_session(
+    pool: &sqlx::SqlitePool,
+    group_id: i64,
+    now: DateTime<Utc>,
+) -> Result<Option<(Session, SessionOption, DateTime<Utc>)>, sqlx::Error> {
+    let sessions = sqlx::query_as::<_, Session>(&format!(
+        "SELECT {SESSION_COLUMNS} FROM sessions WHERE status = 'confirmed' AND group_id = ?"
+    ))
+    .bind(group_id)
+    .fetch_all(pool)
+    .await?;
+    
+    let mut next: Option<(Session, SessionOption, DateTime<Utc>)> = None;
+    for session in sessions {
+        for (option, starts_at) in confirmed_starts(pool, &session).await? {
+            if starts_at > now && next.as_ref().is_none_or(|(_, _, soonest)| starts_at < *soonest) {
+                next = Some((session.clone(), option, starts_at));
+            }
+        }
+    }
+    
+    Ok(next)
+}
+
+/// A confirmed session's chosen options with valid start times, each with its start
+pub(crate) async fn confirmed_starts(
+    pool: &sqlx::SqlitePool,
+    session: &Session,
+) -> Result<Vec<(SessionOption, DateTime<Utc>)>, sqlx::Error> {
+    let options = SessionOption::find_by_session(pool, &session.id).await?;
+    Ok(confirmed_option_starts(options))
+}
+
+/// The confirmed ones among a session's `options` with valid start times, each with its start
+pub(crate) fn confirmed_option_starts(options: Vec<SessionOption>) -> Vec<(SessionOption, DateTime<Utc>)> {
+    options.into_iter()
+        .filter(|opt| opt.confirmed)
+        .filter_map(|opt| {
+            let starts_at = DateTime::parse_from_rfc3339(&opt.datetime).ok()?.with_timezone(&Utc);
+            Some((opt, starts_at))
+        })
+        .collect()
+}
+
+/// Archive the sessions [`Session::find_stale`] finds at `now`, returning how many were archived
+pub async fn archive_stale_sessions(db: &DatabaseManager, now: DateTime<Utc>) -> Result<usize, sqlx::Error> {
+    let stale = Session::find_stale(
+        &db.pool,
+        now,
+        Duration::days(ARCHIVE_CONFIRMED_AFTER_DAYS),
+        Duration::days(ARCHIVE_CANCELLED_AFTER_DAYS),
+    ).await?;
+
+    let mut archived = 0;
+    for session in &stale {
+        if Session::archive(&db.pool, &session.id).await? {
+            archived += 1;
+        }
+    }
+    Ok(archived)
+}
+
+/// A reminder that is due, with where it goes
+#[derive(Debug, Clone)]
+pub struct PendingReminder {
+    /// The reminder and its session
+    pub due: DueReminder,
+    /// Telegram chat of the session's group, `None` if the group is gone
+    pub chat_id: Option<i64>,
+    /// Whether the group's reminders are paused because it couldn't be reached lately
+    pub paused: bool,
+}
+
+/// What a reminder sweep did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReminderSweep {
+    /// Reminders delivered
+    pub sent: usize,
+    /// Reminders whose delivery failed; a later sweep retries them
+    pub failed: usize,
+    /// Reminders left alone, because their group is paused or gone
+    pub skipped: usize,
+    /// Reminders of `/mute`d sessions, marked as sent without sending them
+    pub muted: usize,
+    /// Reminders not tried because the bot is shutting down; the first sweep after a restart sends them
+    pub left: usize,
+}
+
+/// The reminders a sweep at `now` would send, only those of the group with ID `group_id` if given
+///
+/// Nothing is sent or recorded, so this is safe for previews.
+pub async fn pending_reminders(
+    pool: &sqlx::SqlitePool,
+    now: DateTime<Utc>,
+    group_id: Option<i64>,
+) -> Result<Vec<PendingReminder>, Box<dyn std::error::Error + Send + Sync>> {
+    let due = SchedulerApi::new(pool.clone()).due_reminders(now).await?;
+    let unreachable = ReminderDelivery::unreachable_group_ids(
+        pool,
+        now - Duration::days(UNREACHABLE_GROUP_PAUSE_DAYS),
+    ).await?;
+    
+    let due: Vec<DueReminder> = due.into_iter()
+        .filter(|reminder| group_id.is_none_or(|group_id| group_id == reminder.session.group_id))
+        .collect();
+    let groups = Group::find_by_ids(pool, &reminder_group_ids(&due)).await?;
+    
+    Ok(due.into_iter()
+        .map(|reminder| {
+            let reminder_group = reminder.session.group_id;
+            PendingReminder {
+                chat_id: groups.get(&reminder_group).map(|group| group.telegram_chat_id),
+                paused: unreachable.contains(&reminder_group),
+                due: reminder,
+            }
+        })
+        .collect())
+}
+
+/// IDs of the groups of `reminders`, each once
+fn reminder_group_ids<'a>(reminders: impl IntoIterator<Item = &'a DueReminder>) -> Vec<i64> {
+    let mut group_ids: Vec<i64> = reminders.into_iter().map(|reminder| reminder.session.group_id).collect();
+    group_ids.sort_unstable();
+    group_ids.dedup();
+    group_ids
+}
+
+/// Deliver `pending` reminders, recording each delivery and marking the sent ones
+pub async fn send_reminders(
+    bot: &Bot,
+    db: &DatabaseManager,
+    pending: Vec<PendingReminder>,
+) -> Result<ReminderSweep, Box<dyn std::error::Error + Send + Sync>> {
+    send_reminders_until(bot, db, pending, &Shutdown::new()).await
+}
+
+/// [`send_reminders`], stopping between reminders once `stop` is triggered
+///
+/// The reminder being sent is finished first, so it's either delivered and marked or neither.
+/// The groups and votes of all of them are loaded up front rather than once per reminder.
+pub async fn send_reminders_until(
+    bot: &Bot,
+    db: &DatabaseManager,
+    pending: Vec<PendingReminder>,
+    stop: &Shutdown,
+) -> Result<ReminderSweep, Box<dyn std::error::Error + Send + Sync>> {
+    let groups = Group::find_by_ids(&db.pool, &reminder_group_ids(pending.iter().map(|p| &p.due))).await?;
+    let mut session_ids: Vec<String> = pending.iter().map(|p| p.due.session.id.clone()).collect();
+    session_ids.sort_unstable();
+    session_ids.dedup();
+    let mut responses: HashMap<String, Vec<Response>> = HashMap::new();
+    for response in Response::find_by_sessions(&db.pool, &session_ids).await? {
+        responses.entry(response.session_id.clone()).or_default().push(response);
+    }
+    
+    let mut sweep = ReminderSweep::default();
+    let total = pending.len();
+    for (i, PendingReminder { due: reminder, paused, .. }) in pending.into_iter().enumerate() {
+        if stop.is_triggered() {
+            sweep.left = total - i;
+            break;
+        }
+        let interval = reminder.interval;
+        let group_id = reminder.session.group_id;
+        if reminder.session.reminders_muted {
+            // Marked as sent, so unmuting doesn't bring back the reminders missed in the meantime
+            tracing::debug!("Not sending {} reminder for session {}: it's muted", interval.label(), reminder.session.id);
+            Reminder::create_for_option(&db.pool, reminder.session.id.clone(), reminder.option.id.clone(), interval.offset_minutes()).await?;
+            sweep.muted += 1;
+            continue;
+        }
+        if paused {
+            tracing::debug!("Skipping {} reminder for session {}: group {} is unreachable", interval.label(), reminder.session.id, group_id);
+            sweep.skipped += 1;
+            continue;
+        }
         
-        // Parse the session datetime
-        let session_datetime = chrono::DateTime::parse_from_rfc3339(&confirmed_option.datetime)?
-            .with_timezone(&Utc);
+        let Some(group) = groups.get(&group_id) else {
+            tracing::error!("Group {} of session {} no longer exists", group_id, reminder.session.id);
+            sweep.skipped += 1;
+            continue;
+        };
+        let session_responses = responses.get(&reminder.session.id).map(Vec::as_slice).unwrap_or_default();
+        let (outcome, attempts, error) = send_session_reminder(bot, db, &reminder, group, session_responses).await;
         
-        // Check if we need to send any reminders
-        for (interval, reminder_type) in &reminder_intervals {
-            let reminder_time = session_datetime - *interval;
-            let time_diff = (now - reminder_time).num_hours().abs();
-            
-            // Send reminder if we're within 1 hour of the reminder time
-            // and haven't sent this reminder before
-            if time_diff <= 1
-                && !has_reminder_been_sent(&db.pool, &session.id, interval.num_days()).await? {
-                    let success = send_session_reminder(
-                        &bot,
-                        &session,
-                        confirmed_option,
-                        reminder_type,
-                        &session_datetime,
-                        db.as_ref(),
-                    ).await;
-                    
-                    if success {
-                        // Mark reminder as sent
-                        mark_reminder_sent(&db.pool, &session.id, interval.num_days()).await?;
-                        tracing::info!(
-                            "Sent {} reminder for session: {}",
-                            interval.num_days(),
-                            session.title
-                        );
-                    }
-                }
+        // Losing this record only costs a late retry, so it mustn't stop the sweep
+        let recorded = ReminderDelivery::record(
+            &db.pool,
+            &reminder.session.id,
+            group_id,
+            interval.offset_minutes(),
+            outcome,
+            attempts,
+            error.as_deref(),
+        ).await;
+        if let Err(e) = recorded {
+            tracing::warn!("Failed to record reminder delivery for session {}: {}", reminder.session.id, e);
+        }
+        match outcome {
+            DeliveryOutcome::Sent => {
+                mark_reminder_sent(&db.pool, &reminder.session.id, &reminder.option.id, interval.offset_minutes()).await?;
+                sweep.sent += 1;
+                tracing::info!(
+                    "Sent {} reminder for session: {}",
+                    interval.label(),
+                    reminder.session.title
+                );
+            }
+            DeliveryOutcome::Failed => {
+                sweep.failed += 1;
+                tracing::warn!(
+                    "{} reminder for session {} failed after {} attempts; a later sweep retries it",
+                    interval.label(), reminder.session.id, attempts
+                );
+            }
+            DeliveryOutcome::Unreachable => {
+                sweep.failed += 1;
+                tracing::warn!(
+                    "Group {} is unreachable; no reminders for {} days",
+                    group_id, UNREACHABLE_GROUP_PAUSE_DAYS
+                );
+            }
         }
     }
     
+    Ok(sweep)
+}
+
+async fn check_and_send_reminders(
+    bot: Bot,
+    db: Arc<DatabaseManager>,
+    stop: &Shutdown,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Send the reminders whose time has come and that haven't been sent before
+    let pending = pending_reminders(&db.pool, Utc::now(), None).await?;
+    let total = pending.len();
+    let sweep = send_reminders_until(&bot, db.as_ref(), pending, stop).await?;
+    if sweep.left > 0 {
+        tracing::info!(
+            "Reminder sweep stopped for shutdown after {} of {} due reminders; the rest go out after the restart",
+            total - sweep.left, total
+        );
+    }
     Ok(())
 }
 
+/// Send one reminder to `group`, retrying transient failures, and say how it went: the outcome,
+/// how many sends it took and the last error
+///
+/// `responses` are the votes on the reminder's session.
 async fn send_session_reminder(
     bot: &Bot,
-    session: &Session,
-    confirmed_option: &SessionOption,
-    reminder_type: &str,
-    session_datetime: &chrono::DateTime<Utc>,
     db: &DatabaseManager,
-) -> bool {
-    let formatted_datetime = format_datetime(session_datetime);
+    reminder: &DueReminder,
+    group: &Group,
+    responses: &[Response],
+) -> (DeliveryOutcome, u32, Option<String>) {
+    let DueReminder { session, option: confirmed_option, interval, starts_at } = reminder;
+    let formatted_datetime = format_datetime(starts_at);
     let duration_hours = confirmed_option.duration / 60;
     let duration_display = if duration_hours >= 1 {
         format!("{duration_hours}h")
@@ -133,71 +844,81 @@ async fn send_session_reminder(
     };
     
     // Get participants who said "yes"
-    let responses: Vec<crate::database::models::response::Response> = Response::find_by_session(&db.pool, &session.id).await.unwrap_or_default();
-    
-    let participants: Vec<_> = responses.iter()
+    let attending: Vec<_> = responses.iter()
         .filter(|r| r.option_id == confirmed_option.id && r.response == "yes")
-        .filter_map(|r| r.username.as_deref())
         .collect();
     
-    let participant_list = if participants.is_empty() {
-        "No participants confirmed yet".to_string()
-    } else if participants.len() <= 5 {
-        participants.join(", ")
+    // Show pseudonyms instead of usernames if the group asked for that
+    let voters: Vec<_> = attending.iter().map(|r| (r.user_id, r.username.as_deref())).collect();
+    let names = display_names(&db.pool, group, &voters).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to resolve participant names for session {}: {}", session.id, e);
+        HashMap::new()
+    });
+    let participants: Vec<&str> = attending.iter()
+        .filter_map(|r| names.get(&r.user_id).map(String::as_str))
+        .collect();
+    
+    let lang = group.lang();
+    let heading = if interval.is_long_range() {
+        i18n::t_long_range_heading(lang, interval.offset_minutes() / MINUTES_PER_DAY)
     } else {
-        format!("{} and {} others", participants[..3].join(", "), participants.len() - 3)
+        i18n::t_reminder_heading(lang, interval.offset_minutes()).unwrap_or(interval.name).to_string()
     };
+    let notes = session.notes.as_deref().map(escape_markdown);
+    let duties = session_duties_line(&db.pool, group, &session.id).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load the duties of session {}: {}", session.id, e);
+        None
+    });
+    let duties = duties.as_deref().map(escape_markdown);
+    // Players whose plans changed can still switch their vote from a private chat
+    let vote_link = bot_username(bot).await.map(|username| vote_link(&username, &session.id));
+    // On the day itself, players confirm they're really coming
+    let message_text = i18n::t_reminder(lang, &i18n::ReminderParts {
+        heading: &escape_markdown(&heading),
+        title: &escape_markdown(&session.title),
+        when: &escape_markdown(&formatted_datetime),
+        duration: &duration_display,
+        notes: notes.as_deref(),
+        duties: duties.as_deref(),
+        participants: &escape_markdown(&i18n::t_participants(lang, &participants)),
+        reference: &escape_markdown(&session.display_ref()),
+        session_id: &session.id,
+        vote_link: vote_link.as_deref(),
+    }, interval.is_game_day());
     
-    let message_text = format!(
-        "{}\n\n🎲 **{}**\n\n📅 **When:** {}\n⏱️ **Duration:** {}\n👥 **Participants:** {}\n\n🔗 Session ID: `{}`",
-        reminder_type,
-        escape_markdown(&session.title),
-        escape_markdown(&formatted_datetime),
-        duration_display,
-        escape_markdown(&participant_list),
-        session.id
-    );
+    let chat_id = teloxide::types::ChatId(group.telegram_chat_id);
+    let mut request = bot.send_message(chat_id, message_text)
+        .parse_mode(teloxide::types::ParseMode::MarkdownV2);
+    if interval.is_game_day() {
+        request = request.reply_markup(checkin_keyboard(&session.id));
+    }
     
-    match bot.send_message(teloxide::types::ChatId(session.group_id), message_text)
-        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-        .await 
-    {
-        Ok(_) => true,
+    let (result, attempts) = send_with_retry(
+        &DEFAULT_RETRY_POLICY,
+        || logged_send(&db.pool, chat_id, "reminder", Some(&session.id), plain_fallback(request.clone())),
+        tokio::time::sleep,
+    ).await;
+    match result {
+        Ok(_) => (DeliveryOutcome::Sent, attempts, None),
         Err(e) => {
             tracing::error!("Failed to send reminder to group {}: {}", session.group_id, e);
-            false
+            let outcome = match classify_send_error(&e) {
+                SendErrorKind::Permanent => DeliveryOutcome::Unreachable,
+                SendErrorKind::Transient { .. } | SendErrorKind::Other => DeliveryOutcome::Failed,
+            };
+            (outcome, attempts, Some(e.to_string()))
         }
     }
 }
 
-// Database helper functions
-async fn get_confirmed_sessions(
-    pool: &sqlx::SqlitePool,
-) -> Result<Vec<Session>, sqlx::Error> {
-    sqlx::query_as::<_, Session>(
-        "SELECT id, group_id, title, message_id, status, deadline, created_by, created_at 
-         FROM sessions 
-         WHERE status = 'confirmed' 
-         ORDER BY created_at DESC"
-    )
-    .fetch_all(pool)
-    .await
-}
-
-async fn has_reminder_been_sent(
-    pool: &sqlx::SqlitePool,
-    session_id: &str,
-    days_before: i64,
-) -> Result<bool, sqlx::Error> {
-    Reminder::exists(pool, session_id, days_before).await
-}
-
 async fn mark_reminder_sent(
     pool: &sqlx::SqlitePool,
     session_id: &str,
-    days_before: i64,
+    option_id: &str,
+    offset_minutes: i64,
 ) -> Result<(), sqlx::Error> {
-    Reminder::create(pool, session_id.to_string(), days_before).await?;
+    Reminder::create_for_option(pool, session_id.to_string(), option_id.to_string(), offset_minutes).await?;
+    metrics().record_reminder_sent();
     Ok(())
 }
 
@@ -0,0 +1,113 @@
+//! Coordinated shutdown: one signal shared by the bot dispatcher, the health server and the
+//! reminder scheduler, so in-flight work can drain before the database pool is closed.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// How long each task gets to finish its in-flight work once shutdown starts
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cloneable handle to the application-wide shutdown signal
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    sender: broadcast::Sender<()>,
+    triggered: Arc<AtomicBool>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    /// Create a signal that has not been triggered yet
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(1);
+        Self { sender, triggered: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Tell every subscriber to stop; triggering more than once has no further effect
+    pub fn trigger(&self) {
+        if !self.triggered.swap(true, Ordering::SeqCst) {
+            // Nobody listening is fine, late subscribers see the flag instead
+            let _ = self.sender.send(());
+        }
+    }
+
+    /// Whether shutdown has been triggered, for loops that check between steps
+    pub fn is_triggered(&self) -> bool {
+        self.triggered.load(Ordering::SeqCst)
+    }
+
+    /// A future that resolves once shutdown is triggered, including if it already was
+    pub fn signal(&self) -> impl Future<Output = ()> + Send + 'static {
+        // Subscribe before checking the flag so a trigger in between can't be missed
+        let mut receiver = self.sender.subscribe();
+        let triggered = self.triggered.clone();
+
+        async move {
+            if triggered.load(Ordering::SeqCst) {
+                return;
+            }
+            let _ = receiver.recv().await;
+        }
+    }
+}
+
+/// Wait for Ctrl+C, or SIGTERM on Unix (what `docker stop` sends)
+pub async fn wait_for_os_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received Ctrl+C, shutting down"),
+        _ = terminate => tracing::info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// Wait up to `timeout` for a task to finish, aborting it if it doesn't
+///
+/// Returns `true` if the task finished on its own.
+pub async fn drain_task(name: &str, task: JoinHandle<()>, timeout: Duration) -> bool {
+    let abort = task.abort_handle();
+    match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(())) => {
+            tracing::info!("{} stopped", name);
+            true
+        }
+        Ok(Err(e)) => {
+            tracing::error!("{} task failed: {}", name, e);
+            true
+        }
+        Err(_) => {
+            tracing::warn!("{} did not stop within {:?}, aborting", name, timeout);
+            abort.abort();
+            false
+        }
+    }
+}
@@ -1,6 +1,8 @@
 // Timezone service placeholder - basic timezone functionality is in datetime utils
 // This module is kept for future advanced timezone features
 
+use chrono::FixedOffset;
+
 #[allow(dead_code)]
 pub(crate) struct TimezoneService;
 
@@ -10,3 +12,54 @@ impl TimezoneService {
         Self
     }
 }
+
+/// The UTC offset a group's timezone stands for, if it's a fixed one: "UTC", "GMT", "UTC+2",
+/// "UTC-05:30" or "+01:00"
+///
+/// Region names such as "Europe/Stockholm" would need a timezone database, so they give `None`.
+pub fn utc_offset(timezone: &str) -> Option<FixedOffset> {
+    let timezone = timezone.trim().to_ascii_uppercase();
+    let offset = timezone.strip_prefix("UTC")
+        .or_else(|| timezone.strip_prefix("GMT"))
+        .unwrap_or(&timezone);
+    if offset.is_empty() {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, offset) = if let Some(offset) = offset.strip_prefix('+') {
+        (1, offset)
+    } else {
+        (-1, offset.strip_prefix('-')?)
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// `text` as the timezone to store for a group, if it's a UTC offset [`utc_offset`] understands:
+/// "UTC" for no offset, else like "UTC+2" or "UTC-5:30"
+///
+/// ```
+/// use dnd_scheduler_bot::services::timezone::normalize_timezone;
+///
+/// assert_eq!(normalize_timezone(" gmt ").as_deref(), Some("UTC"));
+/// assert_eq!(normalize_timezone("+02:00").as_deref(), Some("UTC+2"));
+/// assert_eq!(normalize_timezone("utc-5:30").as_deref(), Some("UTC-5:30"));
+/// assert_eq!(normalize_timezone("Europe/Stockholm"), None);
+/// ```
+pub fn normalize_timezone(text: &str) -> Option<String> {
+    let seconds = utc_offset(text)?.local_minus_utc();
+    if seconds == 0 {
+        return Some("UTC".to_string());
+    }
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let (hours, minutes) = (seconds.abs() / 3600, seconds.abs() % 3600 / 60);
+    Some(match minutes {
+        0 => format!("UTC{sign}{hours}"),
+        minutes => format!("UTC{sign}{hours}:{minutes:02}"),
+    })
+}
@@ -0,0 +1,204 @@
+//! Temporary databases and builders for groups, sessions, options and responses
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use tempfile::TempDir;
+use crate::database::{connection::DatabaseManager, models::*};
+
+/// Chat ID used by [`GroupBuilder`] unless another is given (a valid supergroup ID)
+pub const DEFAULT_TEST_CHAT_ID: i64 = -1001234567890;
+
+/// Create a migrated SQLite database in a fresh temporary directory
+///
+/// The database is deleted when the returned [`TempDir`] is dropped, so keep it alive for the
+/// duration of the test.
+pub async fn test_db() -> Result<(DatabaseManager, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    let db_path = temp_dir.path().join("test.db");
+    let database_url = format!("sqlite:{}", db_path.display());
+
+    let db = DatabaseManager::new(&database_url).await?;
+    db.run_migrations().await?;
+
+    Ok((db, temp_dir))
+}
+
+/// Builder for [`Group`] fixtures
+#[derive(Debug, Clone)]
+pub struct GroupBuilder {
+    chat_id: i64,
+}
+
+impl Default for GroupBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GroupBuilder {
+    /// A group for [`DEFAULT_TEST_CHAT_ID`]
+    pub fn new() -> Self {
+        Self { chat_id: DEFAULT_TEST_CHAT_ID }
+    }
+
+    /// Use a specific Telegram chat ID
+    pub fn chat_id(mut self, chat_id: i64) -> Self {
+        self.chat_id = chat_id;
+        self
+    }
+
+    /// Insert the group
+    pub async fn create(self, pool: &sqlx::SqlitePool) -> Result<Group, sqlx::Error> {
+        Group::create(pool, self.chat_id).await
+    }
+}
+
+/// Builder for [`Session`] fixtures
+#[derive(Debug, Clone)]
+pub struct SessionBuilder {
+    group_id: i64,
+    title: String,
+    created_by: i64,
+    status: String,
+    anonymous: bool,
+    deadline: Option<DateTime<Utc>>,
+}
+
+impl SessionBuilder {
+    /// An active, non-anonymous "Test Session" in `group_id` created by user 1
+    pub fn new(group_id: i64) -> Self {
+        Self {
+            group_id,
+            title: "Test Session".to_string(),
+            created_by: 1,
+            status: "active".to_string(),
+            anonymous: false,
+            deadline: None,
+        }
+    }
+
+    /// Set the session title
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Set the creating user's ID
+    pub fn created_by(mut self, user_id: i64) -> Self {
+        self.created_by = user_id;
+        self
+    }
+
+    /// Set the status, e.g. "confirmed" or "cancelled"
+    pub fn status(mut self, status: impl Into<String>) -> Self {
+        self.status = status.into();
+        self
+    }
+
+    /// Hide vote tallies until confirmation
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
+
+    /// Set a voting deadline
+    pub fn deadline(mut self, deadline: DateTime<Utc>) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Insert the session and return it as stored
+    pub async fn create(self, pool: &sqlx::SqlitePool) -> Result<Session, sqlx::Error> {
+        let session = Session::create(pool, self.group_id, self.title, self.created_by).await?;
+
+        if self.anonymous {
+            Session::set_anonymous(pool, &session.id, true).await?;
+        }
+
+        sqlx::query("UPDATE sessions SET status = ?, deadline = ? WHERE id = ?")
+            .bind(&self.status)
+            .bind(self.deadline.map(|d| d.to_rfc3339()))
+            .bind(&session.id)
+            .execute(pool)
+            .await?;
+
+        Session::find_by_id(pool, &session.id).await?.ok_or(sqlx::Error::RowNotFound)
+    }
+}
+
+/// Builder for [`SessionOption`] fixtures
+#[derive(Debug, Clone)]
+pub struct SessionOptionBuilder {
+    session_id: String,
+    datetime: DateTime<Utc>,
+    duration: i64,
+}
+
+impl SessionOptionBuilder {
+    /// A 240-minute option for `session_id` starting one day from now
+    pub fn new(session_id: &str) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            datetime: Utc::now() + Duration::days(1),
+            duration: 240,
+        }
+    }
+
+    /// Set the start time
+    pub fn at(mut self, datetime: DateTime<Utc>) -> Self {
+        self.datetime = datetime;
+        self
+    }
+
+    /// Set the duration in minutes
+    pub fn duration(mut self, minutes: i64) -> Self {
+        self.duration = minutes;
+        self
+    }
+
+    /// Insert the option
+    pub async fn create(self, pool: &sqlx::SqlitePool) -> Result<SessionOption, sqlx::Error> {
+        SessionOption::create(pool, self.session_id, self.datetime, self.duration).await
+    }
+}
+
+/// Builder for [`Response`] fixtures
+#[derive(Debug, Clone)]
+pub struct ResponseBuilder {
+    session_id: String,
+    option_id: String,
+    user_id: i64,
+    username: Option<String>,
+    response: String,
+}
+
+impl ResponseBuilder {
+    /// A "yes" vote from user 1 on `option`
+    pub fn new(option: &SessionOption) -> Self {
+        Self {
+            session_id: option.session_id.clone(),
+            option_id: option.id.clone(),
+            user_id: 1,
+            username: None,
+            response: "yes".to_string(),
+        }
+    }
+
+    /// Set the voting user
+    pub fn user(mut self, user_id: i64, username: Option<&str>) -> Self {
+        self.user_id = user_id;
+        self.username = username.map(str::to_string);
+        self
+    }
+
+    /// Set the response value ("yes", "no" or "maybe")
+    pub fn response(mut self, response: impl Into<String>) -> Self {
+        self.response = response.into();
+        self
+    }
+
+    /// Insert (or replace) the response
+    pub async fn create(self, pool: &sqlx::SqlitePool) -> Result<Response, sqlx::Error> {
+        Response::upsert(pool, self.session_id, self.option_id, self.user_id, self.username, self.response).await
+    }
+}
@@ -0,0 +1,177 @@
+//! Checks for the invariants every stored session is expected to uphold
+//!
+//! The limits come from [`crate::utils::validation`], so a change there is reflected here.
+
+use std::collections::HashSet;
+use std::fmt;
+use crate::database::models::*;
+use crate::utils::validation::{MAX_TIME_OPTIONS, MAX_TITLE_LENGTH, MIN_TITLE_LENGTH, RESPONSE_TYPES};
+
+/// Session statuses the bot can write
+pub const SESSION_STATUSES: [&str; 5] = ["active", "confirmed", "cancelled", "closed", "archived"];
+
+/// A broken invariant, as reported by the `check_*` functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// Title length (after trimming) outside `MIN_TITLE_LENGTH..=MAX_TITLE_LENGTH`
+    TitleLength(usize),
+    /// More than `MAX_TIME_OPTIONS` options
+    TooManyOptions(usize),
+    /// No options at all where at least one is required
+    NoOptions,
+    /// Response value other than "yes", "no" or "maybe"
+    UnknownResponse(String),
+    /// Session status the bot never writes
+    UnknownStatus(String),
+    /// Response pointing at an option that isn't part of the session
+    ForeignOption {
+        /// The offending response
+        response_id: String,
+        /// The option it refers to
+        option_id: String,
+    },
+    /// More than one response from the same user for the same option
+    DuplicateResponse {
+        /// The voting user
+        user_id: i64,
+        /// The option voted on twice
+        option_id: String,
+    },
+    /// Wrong number of confirmed options for the session status
+    ConfirmedOptions {
+        /// The session status
+        status: String,
+        /// How many options are marked confirmed
+        count: usize,
+    },
+    /// A deadline left on a session that no longer takes responses
+    SettledDeadline {
+        /// The session status, "confirmed" or "cancelled"
+        status: String,
+    },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TitleLength(len) => write!(
+                f, "title length {len} is outside {MIN_TITLE_LENGTH}..={MAX_TITLE_LENGTH}"
+            ),
+            Self::TooManyOptions(count) => write!(f, "{count} options exceed the limit of {MAX_TIME_OPTIONS}"),
+            Self::NoOptions => write!(f, "at least one time option is required"),
+            Self::UnknownResponse(response) => write!(f, "unknown response value '{response}'"),
+            Self::UnknownStatus(status) => write!(f, "unknown session status '{status}'"),
+            Self::ForeignOption { response_id, option_id } => write!(
+                f, "response {response_id} refers to option {option_id} from another session"
+            ),
+            Self::DuplicateResponse { user_id, option_id } => write!(
+                f, "user {user_id} has more than one response for option {option_id}"
+            ),
+            Self::ConfirmedOptions { status, count } => write!(
+                f, "{status} session has {count} confirmed options"
+            ),
+            Self::SettledDeadline { status } => write!(f, "{status} session still has a deadline"),
+        }
+    }
+}
+
+impl std::error::Error for InvariantViolation {}
+
+/// Check a session title against the length limits
+pub fn check_title(title: &str) -> Result<(), InvariantViolation> {
+    let len = title.trim().len();
+    if (MIN_TITLE_LENGTH..=MAX_TITLE_LENGTH).contains(&len) {
+        Ok(())
+    } else {
+        Err(InvariantViolation::TitleLength(len))
+    }
+}
+
+/// Check a list of validated time options: between one and `MAX_TIME_OPTIONS`
+pub fn check_time_options<T>(options: &[T]) -> Result<(), InvariantViolation> {
+    match options.len() {
+        0 => Err(InvariantViolation::NoOptions),
+        count if count > MAX_TIME_OPTIONS => Err(InvariantViolation::TooManyOptions(count)),
+        _ => Ok(()),
+    }
+}
+
+/// Check that a stored response value is one of `RESPONSE_TYPES`
+pub fn check_response_value(response: &str) -> Result<(), InvariantViolation> {
+    if RESPONSE_TYPES.contains(&response) {
+        Ok(())
+    } else {
+        Err(InvariantViolation::UnknownResponse(response.to_string()))
+    }
+}
+
+/// Check a session together with its options and responses
+///
+/// Sessions without options are accepted, since fixtures often create them that way; use
+/// [`check_time_options`] where options are required.
+pub fn check_session(
+    session: &Session,
+    options: &[SessionOption],
+    responses: &[Response],
+) -> Result<(), InvariantViolation> {
+    check_title(&session.title)?;
+
+    if !SESSION_STATUSES.contains(&session.status.as_str()) {
+        return Err(InvariantViolation::UnknownStatus(session.status.clone()));
+    }
+
+    if options.len() > MAX_TIME_OPTIONS {
+        return Err(InvariantViolation::TooManyOptions(options.len()));
+    }
+
+    let confirmed = options.iter().filter(|o| o.confirmed).count();
+    let expected_ok = match session.status.as_str() {
+        // A session can be confirmed for more than one of its options
+        "confirmed" => confirmed >= 1,
+        // Archived sessions keep whatever they had when they were confirmed or cancelled
+        "archived" => true,
+        _ => confirmed == 0,
+    };
+    if !expected_ok {
+        return Err(InvariantViolation::ConfirmedOptions { status: session.status.clone(), count: confirmed });
+    }
+
+    // Confirming or cancelling clears the deadline so no sweep acts on it
+    if matches!(session.status.as_str(), "confirmed" | "cancelled") && session.deadline.is_some() {
+        return Err(InvariantViolation::SettledDeadline { status: session.status.clone() });
+    }
+
+    let option_ids: HashSet<&str> = options.iter().map(|o| o.id.as_str()).collect();
+    let mut seen = HashSet::new();
+    for response in responses {
+        check_response_value(&response.response)?;
+
+        if !option_ids.contains(response.option_id.as_str()) {
+            return Err(InvariantViolation::ForeignOption {
+                response_id: response.id.clone(),
+                option_id: response.option_id.clone(),
+            });
+        }
+
+        if !seen.insert((response.user_id, response.option_id.as_str())) {
+            return Err(InvariantViolation::DuplicateResponse {
+                user_id: response.user_id,
+                option_id: response.option_id.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Load a session with its options and responses and run [`check_session`] on it
+pub async fn check_stored_session(pool: &sqlx::SqlitePool, session_id: &str) -> anyhow::Result<()> {
+    let session = Session::find_by_id(pool, session_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("session {session_id} not found"))?;
+    let options = SessionOption::find_by_session(pool, session_id).await?;
+    let responses = Response::find_by_session(pool, session_id).await?;
+
+    check_session(&session, &options, &responses)?;
+    Ok(())
+}
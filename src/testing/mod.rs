@@ -0,0 +1,24 @@
+//! Test fixtures and invariant checks for code built on this crate
+//!
+//! Only compiled with the `testing` feature. The builders create real rows through the model
+//! methods, so fixtures go through the same code paths as the bot, and the invariant checks
+//! describe what a well-formed session looks like once it's stored.
+//!
+//! ```no_run
+//! use dnd_scheduler_bot::testing::{test_db, GroupBuilder, SessionBuilder, SessionOptionBuilder, check_stored_session};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let (db, _dir) = test_db().await?;
+//! let group = GroupBuilder::new().create(&db.pool).await?;
+//! let session = SessionBuilder::new(group.id).title("Curse of Strahd").create(&db.pool).await?;
+//! SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+//! check_stored_session(&db.pool, &session.id).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod fixtures;
+pub mod invariants;
+
+pub use fixtures::*;
+pub use invariants::*;
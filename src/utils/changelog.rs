@@ -0,0 +1,211 @@
+//! The changes in each release, for the "What's new" message groups get after an upgrade
+//!
+//! Entries are newest first. Like the templates in `i18n`, each change is translated in the order
+//! of `Lang::ALL`, and an empty translation falls back to English.
+
+use crate::utils::i18n::{self, Lang};
+
+/// The version of the running bot
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Most releases one "What's new" message covers, so a group that slept through several
+/// upgrades gets the recent ones instead of a wall of text
+pub const MAX_ANNOUNCED_RELEASES: usize = 3;
+
+/// What changed in one release
+#[derive(Debug, Clone, Copy)]
+pub struct ChangelogEntry {
+    /// The release, e.g. "0.2.0"
+    pub version: &'static str,
+    /// One line per change, translated in the order of `Lang::ALL`
+    pub changes: &'static [[&'static str; 4]],
+}
+
+/// Every release with changes worth telling groups about, newest first
+pub const CHANGELOG: &[ChangelogEntry] = &[
+    ChangelogEntry {
+        version: "0.1.0",
+        changes: &[
+            [
+                "/rollcall asks who can play later today and confirms the game once enough players say yes",
+                "/rollcall frågar vilka som kan spela senare i dag och bekräftar spelet när tillräckligt många svarat ja",
+                "/rollcall demande qui peut jouer plus tard aujourd'hui et confirme la partie dès qu'assez de joueurs ont dit oui",
+                "/rollcall fragt, wer heute später spielen kann, und bestätigt das Spiel, sobald genug Spieler zugesagt haben",
+            ],
+            [
+                "/results shows every vote of a session as a table",
+                "/results visar alla röster i en session som en tabell",
+                "/results affiche tous les votes d'une session sous forme de tableau",
+                "/results zeigt alle Stimmen einer Sitzung als Tabelle",
+            ],
+            [
+                "/confirm takes an option number, so a session can be confirmed for more than one date",
+                "/confirm tar ett alternativnummer, så en session kan bekräftas för mer än ett datum",
+                "/confirm accepte un numéro d'option, pour confirmer une session à plusieurs dates",
+                "/confirm nimmt eine Optionsnummer, sodass eine Sitzung für mehrere Termine bestätigt werden kann",
+            ],
+        ],
+    },
+];
+
+/// A release version such as "1.2.3" or "1.3.0-beta.1"
+///
+/// Pre-releases come before the release they lead up to, but aren't ordered among each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    released: bool,
+}
+
+impl Version {
+    /// Parse "MAJOR.MINOR.PATCH", with an optional "-pre-release" and "+build" part
+    pub fn parse(text: &str) -> Option<Version> {
+        let text = text.trim();
+        let text = text.split_once('+').map_or(text, |(version, _)| version);
+        let (numbers, pre_release) = match text.split_once('-') {
+            Some((numbers, pre_release)) => (numbers, Some(pre_release)),
+            None => (text, None),
+        };
+        let mut parts = numbers.split('.').map(|part| part.parse::<u64>().ok());
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+            released: pre_release.is_none(),
+        };
+        parts.next().is_none().then_some(version)
+    }
+}
+
+/// Whether `version` is a later release than `than`; versions that don't parse are never newer
+pub fn is_newer(version: &str, than: &str) -> bool {
+    match (Version::parse(version), Version::parse(than)) {
+        (Some(version), Some(than)) => version > than,
+        _ => false,
+    }
+}
+
+/// Whether a group that last heard about `announced` is due a "What's new" for `current`
+///
+/// Groups that never got one are due; so are groups whose stored version doesn't parse.
+pub fn is_due(announced: Option<&str>, current: &str) -> bool {
+    match announced.and_then(Version::parse) {
+        Some(announced) => Version::parse(current).is_some_and(|current| current > announced),
+        None => Version::parse(current).is_some(),
+    }
+}
+
+/// The releases after `announced` up to and including `current`, newest first and at most
+/// `MAX_ANNOUNCED_RELEASES` of them
+///
+/// Groups that never got a "What's new" only hear about `current` itself.
+pub fn unannounced<'a>(changelog: &'a [ChangelogEntry], announced: Option<&str>, current: &str) -> Vec<&'a ChangelogEntry> {
+    if !is_due(announced, current) {
+        return Vec::new();
+    }
+    let Some(current) = Version::parse(current) else {
+        return Vec::new();
+    };
+    let announced = announced.and_then(Version::parse);
+    changelog.iter()
+        .filter(|entry| match Version::parse(entry.version) {
+            Some(version) => version <= current && announced.map_or(version == current, |announced| version > announced),
+            None => false,
+        })
+        .take(MAX_ANNOUNCED_RELEASES)
+        .collect()
+}
+
+/// The "What's new" message for `entries` in `lang`, `None` if there's nothing to tell
+pub fn render_whats_new(lang: Lang, entries: &[&ChangelogEntry]) -> Option<String> {
+    if entries.is_empty() {
+        return None;
+    }
+    let releases: Vec<(&str, Vec<&str>)> = entries.iter()
+        .map(|entry| {
+            let changes = entry.changes.iter()
+                .map(|change| match change[lang as usize] {
+                    "" => change[Lang::En as usize],
+                    translated => translated,
+                })
+                .collect();
+            (entry.version, changes)
+        })
+        .collect();
+    Some(i18n::t_whats_new(lang, &releases))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CHANGELOG: &[ChangelogEntry] = &[
+        ChangelogEntry { version: "1.2.0", changes: &[["Roll calls", "Uppropen", "", ""]] },
+        ChangelogEntry { version: "1.1.0", changes: &[["Results", "", "", ""]] },
+        ChangelogEntry { version: "1.0.0", changes: &[["Confirm options", "", "", ""]] },
+        ChangelogEntry { version: "0.9.0", changes: &[["Quiet hours", "", "", ""]] },
+    ];
+
+    fn versions(entries: Vec<&ChangelogEntry>) -> Vec<&'static str> {
+        entries.iter().map(|entry| entry.version).collect()
+    }
+
+    #[test]
+    fn test_version_comparison() {
+        assert!(is_newer("0.2.0", "0.1.0"));
+        assert!(is_newer("0.10.0", "0.9.3"));
+        assert!(is_newer("1.0.0", "0.99.99"));
+        assert!(is_newer("1.0.0", "1.0.0-rc.1"));
+        assert!(!is_newer("1.0.0-rc.1", "1.0.0"));
+        assert!(!is_newer("1.0.0", "1.0.0"));
+        assert!(!is_newer("1.0.0+build.7", "1.0.0"));
+        assert!(!is_newer("0.1.0", "0.2.0"));
+        assert!(!is_newer("1.0", "0.1.0"));
+        assert!(!is_newer("1.0.0.0", "0.1.0"));
+        assert!(!is_newer("2.0.0", "latest"));
+    }
+
+    #[test]
+    fn test_is_due() {
+        assert!(is_due(None, "0.1.0"));
+        assert!(is_due(Some("0.1.0"), "0.2.0"));
+        assert!(is_due(Some("garbage"), "0.2.0"));
+        assert!(!is_due(Some("0.2.0"), "0.2.0"));
+        // A rolled back bot doesn't announce the older release again
+        assert!(!is_due(Some("0.3.0"), "0.2.0"));
+        assert!(!is_due(None, "dev"));
+    }
+
+    #[test]
+    fn test_unannounced_releases() {
+        assert_eq!(versions(unannounced(TEST_CHANGELOG, Some("1.0.0"), "1.2.0")), ["1.2.0", "1.1.0"]);
+        assert_eq!(versions(unannounced(TEST_CHANGELOG, Some("1.0.0"), "1.1.0")), ["1.1.0"]);
+        assert_eq!(versions(unannounced(TEST_CHANGELOG, Some("0.1.0"), "1.2.0")), ["1.2.0", "1.1.0", "1.0.0"]);
+        assert_eq!(versions(unannounced(TEST_CHANGELOG, None, "1.2.0")), ["1.2.0"]);
+        assert!(unannounced(TEST_CHANGELOG, Some("1.2.0"), "1.2.0").is_empty());
+        // Releases without an entry have nothing to announce
+        assert!(unannounced(TEST_CHANGELOG, Some("1.2.0"), "1.2.1").is_empty());
+    }
+
+    #[test]
+    fn test_render_whats_new() {
+        let entries = unannounced(TEST_CHANGELOG, Some("1.0.0"), "1.2.0");
+        let text = render_whats_new(Lang::Sv, &entries).unwrap();
+        assert!(text.starts_with("🆕 Nytt i version 1.2.0:\n• Uppropen\n\n🆕 Nytt i version 1.1.0:\n• Results\n\n"), "{text}");
+        assert!(text.ends_with("/settings whatsnew off"), "{text}");
+        assert_eq!(render_whats_new(Lang::En, &[]), None);
+    }
+
+    #[test]
+    fn test_changelog_is_newest_first() {
+        for pair in CHANGELOG.windows(2) {
+            assert!(is_newer(pair[0].version, pair[1].version), "{} before {}", pair[0].version, pair[1].version);
+        }
+        for entry in CHANGELOG {
+            assert!(Version::parse(entry.version).is_some(), "{}", entry.version);
+            assert!(!entry.changes.is_empty(), "{}", entry.version);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Minimal RFC 4180 CSV writing
+
+/// Quote a field if it contains a comma, double quote or line break, doubling any quotes
+///
+/// ```
+/// use dnd_scheduler_bot::utils::csv::escape_csv_field;
+///
+/// assert_eq!(escape_csv_field("plain"), "plain");
+/// assert_eq!(escape_csv_field("Tomb, of Horrors"), "\"Tomb, of Horrors\"");
+/// assert_eq!(escape_csv_field("The \"Big\" One"), "\"The \"\"Big\"\" One\"");
+/// ```
+pub fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Append one CSV record, terminated with CRLF as RFC 4180 specifies
+pub fn push_csv_row<S: AsRef<str>>(csv: &mut String, fields: &[S]) {
+    let row = fields.iter()
+        .map(|field| escape_csv_field(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    csv.push_str(&row);
+    csv.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_csv_field_line_breaks() {
+        assert_eq!(escape_csv_field("line one\nline two"), "\"line one\nline two\"");
+        assert_eq!(escape_csv_field(""), "");
+    }
+
+    #[test]
+    fn test_push_csv_row() {
+        let mut csv = String::new();
+        push_csv_row(&mut csv, &["a", "b,c", ""]);
+        push_csv_row(&mut csv, &["\"quoted\""]);
+        assert_eq!(csv, "a,\"b,c\",\r\n\"\"\"quoted\"\"\"\r\n");
+    }
+}
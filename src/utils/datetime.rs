@@ -1,32 +1,248 @@
-use chrono::{DateTime, Utc, TimeZone, Datelike};
+//! Parsing and formatting of session date/times
+//!
+//! [`parse_datetime_detailed`] reports which input format matched and how confident the match
+//! is, for callers that want to reject guesses. [`parse_datetime`] is the forgiving wrapper the
+//! bot itself uses: it never rejects input and falls back to a week from today at 19:00, but
+//! says when it did so callers can warn about the guess.
+
+use chrono::{DateTime, Utc, TimeZone, Datelike, NaiveDate, Weekday};
 use anyhow::{Result, anyhow};
 
-pub fn parse_datetime(input: &str) -> Result<DateTime<Utc>> {
-    let input = input.trim();
+/// Which input format produced a [`ParsedDateTime`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTimeFormat {
+    /// European date and time, e.g. `15.08.25 19:00` or `25.12.2024 20.15`
+    European,
+    /// RFC 3339 / ISO 8601 timestamp, e.g. `2024-12-01T19:00:00Z`
+    Iso8601,
+    /// Weekday name and/or time of day, e.g. `Friday 19:00`, `fredag`, `vendredi 20.30`
+    Natural,
+    /// Nothing was recognised; the value is a week from today at 19:00
+    Fallback,
+}
+
+/// How much of the input was actually understood
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// The value is a default, not derived from the input
+    Low,
+    /// Only part of the input was recognised (a weekday without a time, or a time without a weekday)
+    Medium,
+    /// The input fully determined the value
+    High,
+}
+
+/// A parsed date/time together with how it was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedDateTime {
+    /// The resulting point in time (UTC)
+    pub value: DateTime<Utc>,
+    /// The input format that matched
+    pub matched_format: DateTimeFormat,
+    /// How much of the input contributed to `value`
+    pub confidence: Confidence,
+}
+
+/// Parse a session date/time, reporting the matched format and confidence
+///
+/// Formats are tried in order: European (`dd.mm.yy HH:MM`), ISO 8601, then weekday/time phrases.
+/// Input that matches none of them yields a week from today at 19:00 with [`Confidence::Low`].
+///
+/// ```
+/// use dnd_scheduler_bot::utils::datetime::{parse_datetime_detailed, Confidence, DateTimeFormat};
+///
+/// let parsed = parse_datetime_detailed("15.08.25 19:00");
+/// assert_eq!(parsed.matched_format, DateTimeFormat::European);
+/// assert_eq!(parsed.confidence, Confidence::High);
+/// assert_eq!(parsed.value.to_rfc3339(), "2025-08-15T19:00:00+00:00");
+///
+/// let parsed = parse_datetime_detailed("Friday");
+/// assert_eq!(parsed.matched_format, DateTimeFormat::Natural);
+/// assert_eq!(parsed.confidence, Confidence::Medium);
+///
+/// let parsed = parse_datetime_detailed("sometime soon");
+/// assert_eq!(parsed.matched_format, DateTimeFormat::Fallback);
+/// assert_eq!(parsed.confidence, Confidence::Low);
+/// ```
+pub fn parse_datetime_detailed(input: &str) -> ParsedDateTime {
+    // A duration suffix like "(3h)" says how long, not when
+    let input = split_duration_suffix(input.trim()).0;
     
     // Handle European date format first - "15.08.25 19:00", "01.12.24 14:30", etc.
-    if let Ok(datetime) = parse_european_date_format(input) {
-        return Ok(datetime);
+    if let Ok(value) = parse_european_date_format(input) {
+        return ParsedDateTime { value, matched_format: DateTimeFormat::European, confidence: Confidence::High };
+    }
+    
+    // A mistyped date such as "31.02.25" isn't a weekday phrase either, whatever its time says
+    if invalid_european_date(input).is_some() {
+        return ParsedDateTime { value: fallback_datetime(), matched_format: DateTimeFormat::Fallback, confidence: Confidence::Low };
     }
     
-    // Handle simple formats - "Friday Dec 1st 7pm"
-    if let Ok(datetime) = parse_natural_format(input) {
-        return Ok(datetime);
+    // ISO timestamps are unambiguous, so try them before the looser weekday matching
+    if let Ok(value) = input.parse::<DateTime<Utc>>() {
+        return ParsedDateTime { value, matched_format: DateTimeFormat::Iso8601, confidence: Confidence::High };
     }
     
-    // Fallback to ISO format
-    if let Ok(datetime) = input.parse::<DateTime<Utc>>() {
-        return Ok(datetime);
+    // Handle simple formats - "Friday 19:00", "fredag", "20.30"
+    if let Some(natural) = parse_natural_format(input) {
+        let confidence = if natural.matched_day && natural.matched_time {
+            Confidence::High
+        } else {
+            Confidence::Medium
+        };
+        return ParsedDateTime { value: natural.value, matched_format: DateTimeFormat::Natural, confidence };
     }
     
-    // If all parsing fails, return tomorrow at a default time
-    let tomorrow = Utc::now() + chrono::Duration::days(1);
-    let default_time = tomorrow.date_naive().and_hms_opt(19, 0, 0)
-        .ok_or_else(|| anyhow!("Failed to create default time"))?;
-    Ok(Utc.from_utc_datetime(&default_time))
+    // If all parsing fails, default to next week at 19:00
+    ParsedDateTime {
+        value: fallback_datetime(),
+        matched_format: DateTimeFormat::Fallback,
+        confidence: Confidence::Low,
+    }
+}
+
+/// The default for unrecognised input: next week, on today's weekday, at 19:00
+fn fallback_datetime() -> DateTime<Utc> {
+    at_time(Utc::now().date_naive() + chrono::Duration::days(7), 19, 0)
+}
+
+/// What [`parse_datetime`] made of its input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseOutcome {
+    /// The time was read from the input
+    Exact(DateTime<Utc>),
+    /// Nothing in the input was recognised, so this is the default: a week from today at 19:00
+    Fallback(DateTime<Utc>),
+}
+
+#[allow(dead_code)] // Used by tests
+impl ParseOutcome {
+    /// The parsed or guessed time
+    pub fn value(self) -> DateTime<Utc> {
+        match self {
+            ParseOutcome::Exact(dt) | ParseOutcome::Fallback(dt) => dt,
+        }
+    }
+
+    /// Whether the time is a guess rather than read from the input
+    pub fn is_fallback(&self) -> bool {
+        matches!(self, ParseOutcome::Fallback(_))
+    }
+}
+
+/// Parse a session date/time, falling back to a week from today at 19:00 for unrecognised input
+///
+/// Thin wrapper around [`parse_datetime_detailed`]; only a [`DateTimeFormat::Fallback`] match
+/// counts as a guess; a weekday without a time is still read from the input.
+///
+/// ```
+/// use chrono::Timelike;
+/// use dnd_scheduler_bot::utils::datetime::{parse_datetime, ParseOutcome};
+///
+/// let dt = parse_datetime("Friday 19:30").value();
+/// assert_eq!((dt.hour(), dt.minute()), (19, 30));
+/// assert!(matches!(parse_datetime("whenever suits"), ParseOutcome::Fallback(_)));
+/// ```
+pub fn parse_datetime(input: &str) -> ParseOutcome {
+    let parsed = parse_datetime_detailed(input);
+    match parsed.matched_format {
+        DateTimeFormat::Fallback => ParseOutcome::Fallback(parsed.value),
+        _ => ParseOutcome::Exact(parsed.value),
+    }
+}
+
+/// Split an inline duration such as "(3h)", "2h30" or "90m" off the end of a time option
+///
+/// Returns the remaining text and the duration in minutes, or the whole option and `None` if
+/// it doesn't end in a duration. Parenthesised durations may contain spaces, e.g. "(2h 30m)".
+///
+/// ```
+/// use dnd_scheduler_bot::utils::datetime::split_duration_suffix;
+///
+/// assert_eq!(split_duration_suffix("Friday 19:00 (3h)"), ("Friday 19:00", Some(180)));
+/// assert_eq!(split_duration_suffix("Saturday 14:00 2h30"), ("Saturday 14:00", Some(150)));
+/// assert_eq!(split_duration_suffix("Sunday 18:00 (1h 45m)"), ("Sunday 18:00", Some(105)));
+/// assert_eq!(split_duration_suffix("15.08.25 19:00"), ("15.08.25 19:00", None));
+/// ```
+pub fn split_duration_suffix(option: &str) -> (&str, Option<i64>) {
+    let option = option.trim_end();
+
+    if let Some(inner_end) = option.strip_suffix(')') {
+        if let Some(open) = inner_end.rfind('(') {
+            if let Some(minutes) = parse_duration_token(&inner_end[open + 1..]) {
+                return (inner_end[..open].trim_end(), Some(minutes));
+            }
+        }
+        return (option, None);
+    }
+
+    match option.rsplit_once(char::is_whitespace) {
+        Some((rest, token)) => match parse_duration_token(token) {
+            Some(minutes) => (rest.trim_end(), Some(minutes)),
+            None => (option, None),
+        },
+        None => (option, None),
+    }
+}
+
+/// Minutes in a duration typed on its own: a plain number of minutes, or like "3h", "2h30" or "90m"
+///
+/// ```
+/// use dnd_scheduler_bot::utils::datetime::parse_duration;
+///
+/// assert_eq!(parse_duration("150"), Some(150));
+/// assert_eq!(parse_duration("2h 30"), Some(150));
+/// assert_eq!(parse_duration("four hours"), None);
+/// ```
+pub fn parse_duration(text: &str) -> Option<i64> {
+    let text = text.trim();
+    match text.parse::<i64>() {
+        Ok(minutes) => (minutes > 0).then_some(minutes),
+        Err(_) => parse_duration_token(text),
+    }
+}
+
+/// Minutes in a duration like "3h", "2h30", "2h30m", "90m" or "90min"; spaces are ignored
+fn parse_duration_token(token: &str) -> Option<i64> {
+    let token: String = token.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+    let number = |digits: &str, max_len: usize| -> Option<i64> {
+        if digits.is_empty() || digits.len() > max_len || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    };
+
+    let minutes = match token.split_once('h') {
+        Some((hours, rest)) => {
+            let hours = number(hours, 2)?;
+            let rest = rest.strip_suffix('m').unwrap_or(rest);
+            let minutes = if rest.is_empty() { 0 } else { number(rest, 2)? };
+            if minutes >= 60 {
+                return None;
+            }
+            hours * 60 + minutes
+        }
+        None => {
+            let digits = token.strip_suffix("min").or_else(|| token.strip_suffix('m'))?;
+            number(digits, 3)?
+        }
+    };
+
+    (minutes > 0).then_some(minutes)
 }
 
-fn parse_european_date_format(input: &str) -> Result<DateTime<Utc>> {
+/// Parse a European date and time such as `15.08.25 19:00`, `01.12.2024 14:30` or `25.12.24 20.15`
+///
+/// Two-digit years 00–30 map to the 2000s and 31–99 to the 1900s.
+///
+/// ```
+/// use dnd_scheduler_bot::utils::datetime::parse_european_date_format;
+///
+/// let dt = parse_european_date_format("25.12.24 20.15").unwrap();
+/// assert_eq!(dt.to_rfc3339(), "2024-12-25T20:15:00+00:00");
+/// assert!(parse_european_date_format("31.04.24 20:00").is_err());
+/// ```
+pub fn parse_european_date_format(input: &str) -> Result<DateTime<Utc>> {
     // Parse European date formats like "15.08.25 19:00", "01.12.24 14:30", "25.12.2024 20:00"
     let input = input.trim();
     
@@ -75,109 +291,177 @@ fn parse_european_date_format(input: &str) -> Result<DateTime<Utc>> {
         return Err(anyhow!("Invalid time format"));
     };
     
-    // Validate ranges more strictly
-    if !(1..=31).contains(&day) || !(1..=12).contains(&month) {
-        return Err(anyhow!("Invalid date values"));
-    }
-    
-    // Additional day validation based on month
-    if month == 2 && day > 29 {
-        return Err(anyhow!("Invalid day for February"));
-    }
-    if (month == 4 || month == 6 || month == 9 || month == 11) && day > 30 {
-        return Err(anyhow!("Invalid day for this month"));
-    }
-    
-    // Create the datetime
+    // Name what's wrong, e.g. "31.02.25" or "29.02.25", so the user can spot the typo
+    let month_name = u8::try_from(month).ok()
+        .and_then(|month| chrono::Month::try_from(month).ok())
+        .map(|month| month.name())
+        .ok_or_else(|| anyhow!("There is no month {month}"))?;
     let naive_date = chrono::NaiveDate::from_ymd_opt(year, month, day)
-        .ok_or_else(|| anyhow!("Invalid date"))?;
+        .ok_or_else(|| anyhow!("{month_name} {year} has no day {day}"))?;
     let naive_datetime = naive_date.and_hms_opt(hour, minute, 0)
         .ok_or_else(|| anyhow!("Invalid time"))?;
     
     Ok(Utc.from_utc_datetime(&naive_datetime))
 }
 
-fn parse_natural_format(input: &str) -> Result<DateTime<Utc>> {
+/// Day names the parser understands, by weekday number (Sunday is 0), in each supported language
+const WEEKDAY_NAMES: [(u32, &[&str]); 7] = [
+    (1, &["monday", "måndag", "lundi", "montag"]),
+    (2, &["tuesday", "tisdag", "mardi", "dienstag"]),
+    (3, &["wednesday", "onsdag", "mercredi", "mittwoch"]),
+    (4, &["thursday", "torsdag", "jeudi", "donnerstag"]),
+    (5, &["friday", "fredag", "vendredi", "freitag"]),
+    (6, &["saturday", "lördag", "samedi", "samstag"]),
+    (0, &["sunday", "söndag", "dimanche", "sonntag"]),
+];
+
+/// Whether the single word `token` is the start of a time option rather than part of a title
+///
+/// That is a day name, a time of day, a European date or an ISO timestamp on its own. Words
+/// that merely contain a day name don't count, so "Fridaynight" can still be a title.
+///
+/// ```
+/// use dnd_scheduler_bot::utils::datetime::is_time_option_word;
+///
+/// assert!(is_time_option_word("Friday"));
+/// assert!(is_time_option_word("19:00,"));
+/// assert!(is_time_option_word("15.08.25"));
+/// assert!(!is_time_option_word("Adventure"));
+/// assert!(!is_time_option_word("Fridaynight"));
+/// ```
+pub fn is_time_option_word(token: &str) -> bool {
+    let token = token.trim().trim_end_matches(',').to_lowercase();
+    if WEEKDAY_NAMES.iter().any(|(_, names)| names.contains(&token.as_str())) {
+        return true;
+    }
+    if token.chars().all(|c| c.is_ascii_digit() || c == ':' || c == '.') {
+        return extract_time_24h(&token).is_some() || parse_european_date_format(&format!("{token} 00:00")).is_ok();
+    }
+    token.parse::<DateTime<Utc>>().is_ok()
+}
+
+/// Why `input` isn't a date if it starts like a "dd.mm.yy" or "dd.mm.yyyy" one but isn't,
+/// e.g. "February 2025 has no day 31" for "31.02.25 19:00"; `None` for anything else
+///
+/// ```
+/// use dnd_scheduler_bot::utils::datetime::invalid_european_date;
+///
+/// assert_eq!(invalid_european_date("31.02.25 19:00").as_deref(), Some("February 2025 has no day 31"));
+/// assert_eq!(invalid_european_date("15.13.2025 19:00").as_deref(), Some("There is no month 13"));
+/// assert_eq!(invalid_european_date("15.08.25 19:00"), None);
+/// assert_eq!(invalid_european_date("Friday 19.30"), None);
+/// ```
+pub fn invalid_european_date(input: &str) -> Option<String> {
+    let date = input.split_whitespace().next()?;
+    let parts: Vec<&str> = date.split('.').collect();
+    let looks_like_date = matches!(parts.as_slice(), [day, month, year]
+        if (1..=2).contains(&day.len()) && (1..=2).contains(&month.len()) && [2, 4].contains(&year.len())
+            && parts.iter().all(|part| part.bytes().all(|b| b.is_ascii_digit())));
+    if !looks_like_date {
+        return None;
+    }
+    // Only the date counts here; a bad time is the time parser's to report
+    parse_european_date_format(&format!("{date} 00:00")).err().map(|e| e.to_string())
+}
+
+/// Result of weekday/time phrase matching, before it's turned into a [`ParsedDateTime`]
+struct NaturalMatch {
+    value: DateTime<Utc>,
+    matched_day: bool,
+    matched_time: bool,
+}
+
+fn parse_natural_format(input: &str) -> Option<NaturalMatch> {
     // Parse European date and time formats
     let input_lower = input.to_lowercase();
     
     // Extract time in 24-hour format (19:00, 14:30, etc) or European style
-    let time_hour = if let Some(time_match) = extract_time_24h(&input_lower) {
-        time_match.0
-    } else if input_lower.contains("19:00") || input_lower.contains("19.00") {
-        19
-    } else if input_lower.contains("14:00") || input_lower.contains("14.00") {
-        14
-    } else if input_lower.contains("18:00") || input_lower.contains("18.00") {
-        18
-    } else if input_lower.contains("20:00") || input_lower.contains("20.00") {
-        20
-    } else if input_lower.contains("15:30") || input_lower.contains("15.30") {
-        15
-    } else {
-        19 // Default to 19:00
-    };
-    
-    let time_minute = if let Some(time_match) = extract_time_24h(&input_lower) {
-        time_match.1
-    } else if input_lower.contains("15:30") || input_lower.contains("15.30") {
-        30
-    } else {
-        0
-    };
+    let time = extract_time_24h(&input_lower);
+    let (time_hour, time_minute) = time.unwrap_or((19, 0)); // Default to 19:00
     
     // Parse European day names
-    let days_ahead = if input_lower.contains("monday") || input_lower.contains("måndag") || input_lower.contains("lundi") {
-        days_until_weekday(1)
-    } else if input_lower.contains("tuesday") || input_lower.contains("tisdag") || input_lower.contains("mardi") {
-        days_until_weekday(2)
-    } else if input_lower.contains("wednesday") || input_lower.contains("onsdag") || input_lower.contains("mercredi") {
-        days_until_weekday(3)
-    } else if input_lower.contains("thursday") || input_lower.contains("torsdag") || input_lower.contains("jeudi") {
-        days_until_weekday(4)
-    } else if input_lower.contains("friday") || input_lower.contains("fredag") || input_lower.contains("vendredi") {
-        days_until_weekday(5)
-    } else if input_lower.contains("saturday") || input_lower.contains("lördag") || input_lower.contains("samedi") {
-        days_until_weekday(6)
-    } else if input_lower.contains("sunday") || input_lower.contains("söndag") || input_lower.contains("dimanche") {
-        days_until_weekday(0)
-    } else {
-        7 // Default to next week
-    };
+    let weekday = WEEKDAY_NAMES.iter()
+        .find(|(_, names)| names.iter().any(|name| input_lower.contains(name)))
+        .map(|(weekday, _)| *weekday);
     
+    if weekday.is_none() && time.is_none() {
+        return None;
+    }
+    
+    let days_ahead = weekday.map_or(7, days_until_weekday); // Default to next week
     let target_date = Utc::now().date_naive() + chrono::Duration::days(days_ahead);
-    let target_datetime = target_date.and_hms_opt(time_hour, time_minute, 0)
-        .ok_or_else(|| anyhow!("Failed to create datetime"))?;
     
-    Ok(Utc.from_utc_datetime(&target_datetime))
+    Some(NaturalMatch {
+        value: at_time(target_date, time_hour, time_minute),
+        matched_day: weekday.is_some(),
+        matched_time: time.is_some(),
+    })
 }
 
-fn extract_time_24h(input: &str) -> Option<(u32, u32)> {
-    // Match patterns like "19:30", "14.45", "20:00"
-    if let Some(colon_pos) = input.find(':') {
-        let hour_str = &input[colon_pos.saturating_sub(2)..colon_pos];
-        let minute_str = &input[colon_pos + 1..colon_pos + 3];
-        if let (Ok(hour), Ok(minute)) = (hour_str.parse::<u32>(), minute_str.parse::<u32>()) {
-            if hour < 24 && minute < 60 {
-                return Some((hour, minute));
-            }
-        }
-    }
+fn at_time(date: chrono::NaiveDate, hour: u32, minute: u32) -> DateTime<Utc> {
+    // Callers only pass validated hours (< 24) and minutes (< 60)
+    let naive = date.and_hms_opt(hour, minute, 0).unwrap_or_else(|| date.and_time(chrono::NaiveTime::MIN));
+    Utc.from_utc_datetime(&naive)
+}
+
+/// Find the `HH:MM` or `HH.MM` time of day in `input`
+///
+/// Returns `(hour, minute)` for valid 24-hour times, `None` otherwise. Only standalone tokens
+/// count, so the digits of a date like "01.12.2024" are never read as a time. The first `:`
+/// time is preferred; without one the last `.` token wins, since a short "DD.MM" date comes
+/// before the time.
+///
+/// ```
+/// use dnd_scheduler_bot::utils::datetime::extract_time_24h;
+///
+/// assert_eq!(extract_time_24h("friday 19:30"), Some((19, 30)));
+/// assert_eq!(extract_time_24h("monday 14.05"), Some((14, 5)));
+/// assert_eq!(extract_time_24h("15.08.25 19:00"), Some((19, 0)));
+/// assert_eq!(extract_time_24h("friday 15.08 19.30"), Some((19, 30)));
+/// assert_eq!(extract_time_24h("01.12.2024"), None);
+/// assert_eq!(extract_time_24h("25:00"), None);
+/// assert_eq!(extract_time_24h("19:"), None);
+/// ```
+pub fn extract_time_24h(input: &str) -> Option<(u32, u32)> {
+    let bytes = input.as_bytes();
+    time_tokens(bytes, b':').next().or_else(|| time_tokens(bytes, b'.').last())
+}
+
+/// All valid `H<separator>MM` or `HH<separator>MM` tokens in `bytes`, in order
+///
+/// A token must start a word and not be glued to further digits or separators, which rules
+/// out the parts of dates like "15.08.25" and times with seconds.
+fn time_tokens(bytes: &[u8], separator: u8) -> impl Iterator<Item = (u32, u32)> + '_ {
+    let is_separator = |b: u8| b == b':' || b == b'.';
+    let digits_value = |digits: &[u8]| digits.iter().fold(0, |acc, d| acc * 10 + u32::from(d - b'0'));
     
-    if let Some(dot_pos) = input.find('.') {
-        let hour_str = &input[dot_pos.saturating_sub(2)..dot_pos];
-        let minute_str = &input[dot_pos + 1..dot_pos + 3];
-        if let (Ok(hour), Ok(minute)) = (hour_str.parse::<u32>(), minute_str.parse::<u32>()) {
-            if hour < 24 && minute < 60 {
-                return Some((hour, minute));
-            }
+    (0..bytes.len()).filter(move |&i| bytes[i] == separator).filter_map(move |pos| {
+        // One or two hour digits at the start of a word, not preceded by a separator
+        let hour_start = (pos.saturating_sub(2)..pos)
+            .find(|&i| bytes[i..pos].iter().all(u8::is_ascii_digit))
+            .unwrap_or(pos);
+        if hour_start == pos || hour_start.checked_sub(1).is_some_and(|i| bytes[i].is_ascii_alphanumeric() || is_separator(bytes[i])) {
+            return None;
         }
-    }
-    
-    None
+        
+        // Exactly two minute digits, not followed by another digit or a separator and digit
+        let minute_end = pos + 3;
+        let minutes = bytes.get(pos + 1..minute_end)?;
+        if !minutes.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        match bytes.get(minute_end) {
+            Some(b) if b.is_ascii_digit() => return None,
+            Some(&b) if is_separator(b) && bytes.get(minute_end + 1).is_some_and(u8::is_ascii_digit) => return None,
+            _ => {}
+        }
+        
+        let (hour, minute) = (digits_value(&bytes[hour_start..pos]), digits_value(minutes));
+        (hour < 24 && minute < 60).then_some((hour, minute))
+    })
 }
 
-fn days_until_weekday(target_weekday: u32) -> i64 {
+pub(crate) fn days_until_weekday(target_weekday: u32) -> i64 {
     let today = Utc::now().date_naive().weekday().number_from_monday();
     let target = if target_weekday == 0 { 7 } else { target_weekday }; // Sunday = 7
     
@@ -190,16 +474,127 @@ fn days_until_weekday(target_weekday: u32) -> i64 {
     days as i64
 }
 
+/// Words about the time of day that a day range like "next week evenings" may end with
+const TIME_OF_DAY_WORDS: [&str; 8] = ["evening", "evenings", "night", "nights", "afternoon", "afternoons", "morning", "mornings"];
+
+/// The days a range such as "next week", "this weekend" or "2024-W51" covers, from `today` on
+///
+/// Weeks run Monday to Sunday, and a trailing "evenings" or similar is ignored. Days before
+/// `today` are left out, so "this week" on a Thursday is Thursday to Sunday. A week number
+/// without a year is the next week with that number. `None` if the range isn't recognised or
+/// has no days left.
+pub fn expand_days(spec: &str, today: NaiveDate) -> Option<Vec<NaiveDate>> {
+    let words: Vec<String> = spec.split_whitespace()
+        .map(str::to_lowercase)
+        .filter(|word| !TIME_OF_DAY_WORDS.contains(&word.as_str()))
+        .collect();
+    let monday = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+    let week = |monday: NaiveDate| -> Vec<NaiveDate> {
+        (0..7).map(|offset| monday + chrono::Duration::days(offset)).collect()
+    };
+    let weekend = |monday: NaiveDate| -> Vec<NaiveDate> {
+        week(monday).into_iter().skip(5).collect()
+    };
+
+    let days = match words.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["this", "week"] => week(monday),
+        ["next", "week"] => week(monday + chrono::Duration::days(7)),
+        ["this", "weekend"] | ["weekend"] => weekend(monday),
+        ["next", "weekend"] => weekend(monday + chrono::Duration::days(7)),
+        ["week", number] => week(iso_week_monday(None, number, today)?),
+        [iso_week] => {
+            let (year, number) = match iso_week.split_once("-w") {
+                Some((year, number)) => (Some(year.parse().ok()?), number),
+                None => (None, iso_week.strip_prefix('w')?),
+            };
+            week(iso_week_monday(year, number, today)?)
+        }
+        _ => return None,
+    };
+    let days: Vec<NaiveDate> = days.into_iter().filter(|day| *day >= today).collect();
+    (!days.is_empty()).then_some(days)
+}
+
+/// The Monday of ISO week `number` of `year`; without a year, of the first such week not over by `today`
+fn iso_week_monday(year: Option<i32>, number: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let number: u32 = number.parse().ok()?;
+    if let Some(year) = year {
+        return NaiveDate::from_isoywd_opt(year, number, Weekday::Mon);
+    }
+    let this_year = today.iso_week().year();
+    [this_year, this_year + 1].into_iter()
+        .filter_map(|year| NaiveDate::from_isoywd_opt(year, number, Weekday::Mon))
+        .find(|monday| *monday + chrono::Duration::days(6) >= today)
+}
+
+/// Format a date/time for display, e.g. "Monday, 01 December at 19:30"
 pub fn format_datetime(dt: &DateTime<Utc>) -> String {
     // European format: "Monday, 1 December at 19:30"
     dt.format("%A, %d %B at %H:%M").to_string()
 }
 
+/// Like [`format_datetime`] with the year, e.g. "Wednesday, 01 January 2020 at 19:00", for
+/// dates whose year may be the mistake
+pub fn format_datetime_with_year(dt: &DateTime<Utc>) -> String {
+    dt.format("%A, %d %B %Y at %H:%M").to_string()
+}
+
+/// Format just the day of a date/time, e.g. "Monday, 01 December", as in [`format_datetime`]
+pub fn format_date(dt: &DateTime<Utc>) -> String {
+    dt.format("%A, %d %B").to_string()
+}
+
+/// Format a duration in minutes for display, e.g. "4 hours", "45 minutes" or "2h 30m"
+pub fn format_duration(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{m} minutes"),
+        (1, 0) => "1 hour".to_string(),
+        (h, 0) => format!("{h} hours"),
+        (h, m) => format!("{h}h {m}m"),
+    }
+}
+
+/// How long ago `then` was as of `now`, e.g. "just now", "5 minutes ago", "6 days ago" or "3 weeks ago"
+///
+/// Times in the future count as "just now".
+pub fn format_relative_time(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now - then;
+    let plural = |n: i64, unit: &str| if n == 1 { format!("1 {unit} ago") } else { format!("{n} {unit}s ago") };
+    match (elapsed.num_days(), elapsed.num_hours(), elapsed.num_minutes()) {
+        (days, _, _) if days >= 7 => plural(days / 7, "week"),
+        (days, _, _) if days > 0 => plural(days, "day"),
+        (_, hours, _) if hours > 0 => plural(hours, "hour"),
+        (_, _, minutes) if minutes > 0 => plural(minutes, "minute"),
+        _ => "just now".to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use chrono::{TimeZone, Timelike};
 
+    #[test]
+    fn test_expand_days() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        // A Thursday
+        let today = date(12, 12);
+        
+        assert_eq!(expand_days("next week evenings", today), Some((16..=22).map(|day| date(12, day)).collect()));
+        assert_eq!(expand_days("This Week", today), Some((12..=15).map(|day| date(12, day)).collect()));
+        assert_eq!(expand_days("this weekend", today), Some(vec![date(12, 14), date(12, 15)]));
+        assert_eq!(expand_days("next weekend nights", today), Some(vec![date(12, 21), date(12, 22)]));
+        assert_eq!(expand_days("2024-W52", today), Some((23..=29).map(|day| date(12, day)).collect()));
+        assert_eq!(expand_days("w51", today), expand_days("next week", today));
+        assert_eq!(expand_days("week 50", today), expand_days("this week", today));
+        // Week 49 is over, so it's next year's
+        assert_eq!(expand_days("W49", today).unwrap()[0], NaiveDate::from_ymd_opt(2025, 12, 1).unwrap());
+        
+        for spec in ["", "evenings", "next month", "2024-W60", "w", "2024-W49", "some week"] {
+            assert_eq!(expand_days(spec, today), None, "{spec}");
+        }
+    }
+
     #[test]
     fn test_extract_time_24h_colon_format() {
         assert_eq!(extract_time_24h("friday 19:30"), Some((19, 30)));
@@ -213,6 +608,8 @@ mod tests {
         assert_eq!(extract_time_24h("friday 19.30"), Some((19, 30)));
         assert_eq!(extract_time_24h("monday 14.00"), Some((14, 0)));
         assert_eq!(extract_time_24h("08.45"), Some((8, 45)));
+        // A "DD.MM" date before the time isn't mistaken for it
+        assert_eq!(extract_time_24h("friday 15.08 19.30"), Some((19, 30)));
     }
 
     #[test]
@@ -240,8 +637,8 @@ mod tests {
     #[test]
     fn test_parse_datetime_english_days() {
         let result = parse_datetime("Friday 19:00");
-        assert!(result.is_ok());
-        let dt = result.unwrap();
+        assert!(!result.is_fallback());
+        let dt = result.value();
         assert_eq!(dt.hour(), 19);
         assert_eq!(dt.minute(), 0);
     }
@@ -249,17 +646,27 @@ mod tests {
     #[test]
     fn test_parse_datetime_swedish_days() {
         let result = parse_datetime("fredag 19:00");
-        assert!(result.is_ok());
-        let dt = result.unwrap();
+        assert!(!result.is_fallback());
+        let dt = result.value();
         assert_eq!(dt.hour(), 19);
         assert_eq!(dt.minute(), 0);
     }
 
+    #[test]
+    fn test_parse_datetime_german_days() {
+        let result = parse_datetime("Samstag 14:30");
+        assert!(!result.is_fallback());
+        let dt = result.value();
+        assert_eq!(dt.weekday(), chrono::Weekday::Sat);
+        assert_eq!(dt.hour(), 14);
+        assert_eq!(dt.minute(), 30);
+    }
+
     #[test]
     fn test_parse_datetime_french_days() {
         let result = parse_datetime("vendredi 19:00");
-        assert!(result.is_ok());
-        let dt = result.unwrap();
+        assert!(!result.is_fallback());
+        let dt = result.value();
         assert_eq!(dt.hour(), 19);
         assert_eq!(dt.minute(), 0);
     }
@@ -267,8 +674,8 @@ mod tests {
     #[test]
     fn test_parse_datetime_dot_notation() {
         let result = parse_datetime("Monday 14.30");
-        assert!(result.is_ok());
-        let dt = result.unwrap();
+        assert!(!result.is_fallback());
+        let dt = result.value();
         assert_eq!(dt.hour(), 14);
         assert_eq!(dt.minute(), 30);
     }
@@ -277,25 +684,25 @@ mod tests {
     fn test_parse_datetime_iso_format() {
         let iso_date = "2024-12-01T19:00:00Z";
         let result = parse_datetime(iso_date);
-        assert!(result.is_ok());
-        let dt = result.unwrap();
+        assert!(!result.is_fallback());
+        let dt = result.value();
         assert_eq!(dt.hour(), 19);
         assert_eq!(dt.minute(), 0);
     }
 
     #[test]
     fn test_parse_datetime_fallback() {
-        // Invalid input should fallback to tomorrow 19:00
+        // Invalid input should fallback to next week at 19:00
         let result = parse_datetime("invalid date string");
-        assert!(result.is_ok());
-        let dt = result.unwrap();
+        assert!(result.is_fallback());
+        let dt = result.value();
         assert_eq!(dt.hour(), 19);
         assert_eq!(dt.minute(), 0);
         
-        // Should be within a reasonable time range (tomorrow +/- some days due to natural parsing)
+        // Same default as before the parser reported its guesses
         let now = Utc::now();
         let days_diff = (dt.date_naive() - now.date_naive()).num_days();
-        assert!(days_diff >= 1 && days_diff <= 14, "Date should be 1-14 days from now, got {} days", days_diff);
+        assert_eq!(days_diff, 7, "Date should be a week from now, got {} days", days_diff);
     }
 
     #[test]
@@ -312,21 +719,21 @@ mod tests {
     #[test]
     fn test_parse_datetime_mixed_case() {
         let result = parse_datetime("FRIDAY 19:00");
-        assert!(result.is_ok());
-        let dt = result.unwrap();
+        assert!(!result.is_fallback());
+        let dt = result.value();
         assert_eq!(dt.hour(), 19);
         
         let result2 = parse_datetime("Friday 19:00");
-        assert!(result2.is_ok());
-        let dt2 = result2.unwrap();
+        assert!(!result2.is_fallback());
+        let dt2 = result2.value();
         assert_eq!(dt2.hour(), 19);
     }
 
     #[test]
     fn test_parse_datetime_whitespace() {
         let result = parse_datetime("  Friday 19:00  ");
-        assert!(result.is_ok());
-        let dt = result.unwrap();
+        assert!(!result.is_fallback());
+        let dt = result.value();
         assert_eq!(dt.hour(), 19);
     }
 
@@ -343,8 +750,8 @@ mod tests {
 
         for (input, expected_hour) in test_cases {
             let result = parse_datetime(input);
-            assert!(result.is_ok(), "Failed to parse: {}", input);
-            let dt = result.unwrap();
+            assert!(!result.is_fallback(), "Failed to parse: {}", input);
+            let dt = result.value();
             assert_eq!(dt.hour(), expected_hour, "Wrong hour for: {}", input);
         }
     }
@@ -352,8 +759,8 @@ mod tests {
     #[test]
     fn test_parse_european_date_format_2_digit_year() {
         let result = parse_datetime("15.08.25 19:00");
-        assert!(result.is_ok(), "Failed to parse European date format");
-        let dt = result.unwrap();
+        assert!(!result.is_fallback(), "Failed to parse European date format");
+        let dt = result.value();
         assert_eq!(dt.day(), 15);
         assert_eq!(dt.month(), 8);
         assert_eq!(dt.year(), 2025);
@@ -364,8 +771,8 @@ mod tests {
     #[test]
     fn test_parse_european_date_format_4_digit_year() {
         let result = parse_datetime("01.12.2024 14:30");
-        assert!(result.is_ok(), "Failed to parse European date format");
-        let dt = result.unwrap();
+        assert!(!result.is_fallback(), "Failed to parse European date format");
+        let dt = result.value();
         assert_eq!(dt.day(), 1);
         assert_eq!(dt.month(), 12);
         assert_eq!(dt.year(), 2024);
@@ -376,8 +783,8 @@ mod tests {
     #[test]
     fn test_parse_european_date_format_dot_time() {
         let result = parse_datetime("25.12.24 20.15");
-        assert!(result.is_ok(), "Failed to parse European date format with dot time");
-        let dt = result.unwrap();
+        assert!(!result.is_fallback(), "Failed to parse European date format with dot time");
+        let dt = result.value();
         assert_eq!(dt.day(), 25);
         assert_eq!(dt.month(), 12);
         assert_eq!(dt.year(), 2024);
@@ -389,8 +796,8 @@ mod tests {
     fn test_parse_european_date_format_user_case() {
         // Test the specific user case that was reported
         let result = parse_datetime("15.08.25 19:00");
-        assert!(result.is_ok(), "Failed to parse user's European date format");
-        let dt = result.unwrap();
+        assert!(!result.is_fallback(), "Failed to parse user's European date format");
+        let dt = result.value();
         assert_eq!(dt.day(), 15);
         assert_eq!(dt.month(), 8);
         assert_eq!(dt.year(), 2025);
@@ -398,23 +805,155 @@ mod tests {
         assert_eq!(dt.minute(), 0);
         
         // Additional user-friendly formats should work
-        assert!(parse_datetime("01.12.24 14:30").is_ok());
-        assert!(parse_datetime("31.12.2024 23:30").is_ok());
+        assert!(!parse_datetime("01.12.24 14:30").is_fallback());
+        assert!(!parse_datetime("31.12.2024 23:30").is_fallback());
     }
 
     #[test]
     fn test_parse_european_date_format_year_logic() {
         // Test 2-digit year logic
         let result_25 = parse_datetime("01.01.25 12:00");
-        assert!(result_25.is_ok());
-        assert_eq!(result_25.unwrap().year(), 2025);
+        assert!(!result_25.is_fallback());
+        assert_eq!(result_25.value().year(), 2025);
 
         let result_99 = parse_datetime("01.01.99 12:00");
-        assert!(result_99.is_ok());
-        assert_eq!(result_99.unwrap().year(), 1999);
+        assert!(!result_99.is_fallback());
+        assert_eq!(result_99.value().year(), 1999);
 
         let result_00 = parse_datetime("01.01.00 12:00");
-        assert!(result_00.is_ok());
-        assert_eq!(result_00.unwrap().year(), 2000);
+        assert!(!result_00.is_fallback());
+        assert_eq!(result_00.value().year(), 2000);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_datetime_detailed_formats() {
+        let european = parse_datetime_detailed("01.12.2024 14:30");
+        assert_eq!(european.matched_format, DateTimeFormat::European);
+        assert_eq!(european.confidence, Confidence::High);
+        
+        let iso = parse_datetime_detailed("2024-12-01T19:00:00Z");
+        assert_eq!(iso.matched_format, DateTimeFormat::Iso8601);
+        assert_eq!(iso.value, Utc.with_ymd_and_hms(2024, 12, 1, 19, 0, 0).unwrap());
+        
+        let natural = parse_datetime_detailed("fredag 20.30");
+        assert_eq!(natural.matched_format, DateTimeFormat::Natural);
+        assert_eq!(natural.confidence, Confidence::High);
+        assert_eq!((natural.value.hour(), natural.value.minute()), (20, 30));
+    }
+
+    #[test]
+    fn test_parse_datetime_detailed_partial_and_fallback() {
+        let time_only = parse_datetime_detailed("18:15");
+        assert_eq!(time_only.matched_format, DateTimeFormat::Natural);
+        assert_eq!(time_only.confidence, Confidence::Medium);
+        
+        let fallback = parse_datetime_detailed("invalid date string");
+        assert_eq!(fallback.matched_format, DateTimeFormat::Fallback);
+        assert_eq!(fallback.confidence, Confidence::Low);
+        let next_week = (Utc::now() + chrono::Duration::days(7)).date_naive();
+        assert_eq!(fallback.value.date_naive(), next_week);
+        assert_eq!(fallback.value.hour(), 19);
+    }
+
+    #[test]
+    fn test_extract_time_24h_truncated_input() {
+        assert_eq!(extract_time_24h("19:"), None);
+        assert_eq!(extract_time_24h("19:3"), None);
+        assert_eq!(extract_time_24h("ö:30"), None);
+    }
+
+    #[test]
+    fn test_extract_time_24h_ignores_european_dates() {
+        assert_eq!(extract_time_24h("15.08.25 19:00"), Some((19, 0)));
+        assert_eq!(extract_time_24h("15.08.2025 19:00"), Some((19, 0)));
+        assert_eq!(extract_time_24h("15.08.25 19.30"), Some((19, 30)));
+        assert_eq!(extract_time_24h("19:00 15.08.25"), Some((19, 0)));
+        assert_eq!(extract_time_24h("friday 15.08 19:00"), Some((19, 0)));
+        assert_eq!(extract_time_24h("01.12.2024"), None);
+        assert_eq!(extract_time_24h("15.08.25"), None);
+    }
+
+    #[test]
+    fn test_extract_time_24h_requires_standalone_token() {
+        assert_eq!(extract_time_24h("8:45"), Some((8, 45)));
+        assert_eq!(extract_time_24h("at 19:30, see you"), Some((19, 30)));
+        assert_eq!(extract_time_24h("at 19:30."), Some((19, 30)));
+        assert_eq!(extract_time_24h("119:30"), None);
+        assert_eq!(extract_time_24h("room7 9:30"), Some((9, 30)));
+        assert_eq!(extract_time_24h("room9:30"), None);
+        assert_eq!(extract_time_24h("19:300"), None);
+        assert_eq!(extract_time_24h("19:30:00"), None);
+    }
+
+    #[test]
+    fn test_parse_natural_format_with_european_date_and_time() {
+        let parsed = parse_natural_format("saturday 15.08.25 19:00").unwrap();
+        assert!(parsed.matched_time);
+        assert_eq!((parsed.value.hour(), parsed.value.minute()), (19, 0));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45 minutes");
+        assert_eq!(format_duration(60), "1 hour");
+        assert_eq!(format_duration(240), "4 hours");
+        assert_eq!(format_duration(150), "2h 30m");
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        let now = Utc.with_ymd_and_hms(2024, 12, 10, 12, 0, 0).unwrap();
+        let ago = |d: chrono::Duration| format_relative_time(now - d, now);
+        assert_eq!(ago(chrono::Duration::seconds(30)), "just now");
+        assert_eq!(ago(chrono::Duration::minutes(1)), "1 minute ago");
+        assert_eq!(ago(chrono::Duration::minutes(59)), "59 minutes ago");
+        assert_eq!(ago(chrono::Duration::hours(1)), "1 hour ago");
+        assert_eq!(ago(chrono::Duration::hours(23)), "23 hours ago");
+        assert_eq!(ago(chrono::Duration::days(1)), "1 day ago");
+        assert_eq!(ago(chrono::Duration::days(6) + chrono::Duration::hours(20)), "6 days ago");
+        assert_eq!(format_relative_time(now + chrono::Duration::hours(2), now), "just now");
+    }
+
+    #[test]
+    fn test_format_relative_time_boundaries() {
+        let now = Utc.with_ymd_and_hms(2024, 12, 10, 12, 0, 0).unwrap();
+        let ago = |d: chrono::Duration| format_relative_time(now - d, now);
+        let second = chrono::Duration::seconds(1);
+        assert_eq!(ago(chrono::Duration::minutes(1) - second), "just now");
+        assert_eq!(ago(chrono::Duration::hours(1) - second), "59 minutes ago");
+        assert_eq!(ago(chrono::Duration::hours(2) - second), "1 hour ago");
+        assert_eq!(ago(chrono::Duration::days(1) - second), "23 hours ago");
+        assert_eq!(ago(chrono::Duration::days(2) - second), "1 day ago");
+        assert_eq!(ago(chrono::Duration::weeks(1) - second), "6 days ago");
+        assert_eq!(ago(chrono::Duration::weeks(1)), "1 week ago");
+        assert_eq!(ago(chrono::Duration::weeks(2) - second), "1 week ago");
+        assert_eq!(ago(chrono::Duration::weeks(2)), "2 weeks ago");
+        assert_eq!(ago(chrono::Duration::days(45)), "6 weeks ago");
+    }
+
+    #[test]
+    fn test_split_duration_suffix() {
+        assert_eq!(split_duration_suffix("Friday 19:00 (3h)"), ("Friday 19:00", Some(180)));
+        assert_eq!(split_duration_suffix("Friday 19:00 (2h30m)"), ("Friday 19:00", Some(150)));
+        assert_eq!(split_duration_suffix("Saturday 14:00 2h30"), ("Saturday 14:00", Some(150)));
+        assert_eq!(split_duration_suffix("Monday 18:00 90min"), ("Monday 18:00", Some(90)));
+        assert_eq!(split_duration_suffix("Monday 18:00 45M"), ("Monday 18:00", Some(45)));
+
+        // Not durations: times, dates, bad minutes and unrelated parentheses
+        assert_eq!(split_duration_suffix("Friday 19:00"), ("Friday 19:00", None));
+        assert_eq!(split_duration_suffix("Friday 19:00 (late)"), ("Friday 19:00 (late)", None));
+        assert_eq!(split_duration_suffix("Friday 19:00 2h75"), ("Friday 19:00 2h75", None));
+        assert_eq!(split_duration_suffix("Friday 19:00 0h"), ("Friday 19:00 0h", None));
+        assert_eq!(split_duration_suffix("3h"), ("3h", None));
+    }
+
+    #[test]
+    fn test_parse_datetime_ignores_duration_suffix() {
+        let with_suffix = parse_datetime_detailed("15.08.25 19:00 (3h)");
+        assert_eq!(with_suffix.matched_format, DateTimeFormat::European);
+        assert_eq!(with_suffix.value.to_rfc3339(), "2025-08-15T19:00:00+00:00");
+
+        let dt = parse_datetime("Saturday 14:00 2h30").value();
+        assert_eq!((dt.hour(), dt.minute()), (14, 0));
+    }
+}
@@ -1,6 +1,10 @@
+use std::time::{Duration, Instant};
 use teloxide::prelude::*;
 use teloxide::types::{ParseMode, MessageId};
-use crate::utils::markdown::escape_markdown;
+use crate::utils::{i18n::{self, Lang}, markdown::escape_markdown, outbound::{logged_send, plain_fallback}};
+
+/// Minimum time between two progress edits; intermediate steps inside it are skipped
+pub const PROGRESS_EDIT_INTERVAL: Duration = Duration::from_millis(700);
 
 /// Feedback types for different command outcomes
 #[derive(Debug, Clone)]
@@ -28,21 +32,47 @@ impl FeedbackType {
 pub struct CommandFeedback {
     bot: Bot,
     chat_id: ChatId,
+    lang: Lang,
+    log: Option<OutboundLog>,
+}
+
+/// Where [`CommandFeedback::logged`] records its messages
+struct OutboundLog {
+    pool: sqlx::SqlitePool,
+    kind: &'static str,
+    session_id: String,
 }
 
 impl CommandFeedback {
     pub fn new(bot: Bot, chat_id: ChatId) -> Self {
-        Self { bot, chat_id }
+        Self { bot, chat_id, lang: Lang::default(), log: None }
+    }
+
+    /// Word the feedback's own labels in `lang` (usually [`Group::lang`]) instead of English
+    ///
+    /// [`Group::lang`]: crate::database::models::Group::lang
+    pub fn with_language(mut self, lang: Lang) -> Self {
+        self.lang = lang;
+        self
+    }
+
+    /// Record every message from now on in the outbound log as `kind`, about `session_id`
+    pub fn logged(mut self, pool: &sqlx::SqlitePool, kind: &'static str, session_id: &str) -> Self {
+        self.log = Some(OutboundLog { pool: pool.clone(), kind, session_id: session_id.to_string() });
+        self
     }
 
     /// Send immediate feedback message
     pub async fn send(&self, feedback_type: FeedbackType, message: &str) -> ResponseResult<Message> {
         let formatted_message = format!("{} {}", feedback_type.emoji(), escape_markdown(message));
         
-        self.bot
+        let request = self.bot
             .send_message(self.chat_id, formatted_message)
-            .parse_mode(ParseMode::MarkdownV2)
-            .await
+            .parse_mode(ParseMode::MarkdownV2);
+        match &self.log {
+            Some(log) => logged_send(&log.pool, self.chat_id, log.kind, Some(&log.session_id), plain_fallback(request)).await,
+            None => plain_fallback(request).await,
+        }
     }
 
     /// Send a processing message that can be updated later
@@ -59,10 +89,13 @@ impl CommandFeedback {
     ) -> ResponseResult<Message> {
         let formatted_message = format!("{} {}", feedback_type.emoji(), escape_markdown(message));
         
-        self.bot
+        let request = self.bot
             .edit_message_text(self.chat_id, message_id, formatted_message)
-            .parse_mode(ParseMode::MarkdownV2)
-            .await
+            .parse_mode(ParseMode::MarkdownV2);
+        match &self.log {
+            Some(log) => logged_send(&log.pool, self.chat_id, log.kind, Some(&log.session_id), plain_fallback(request)).await,
+            None => plain_fallback(request).await,
+        }
     }
 
     /// Send success feedback
@@ -86,7 +119,6 @@ impl CommandFeedback {
     }
 
     /// Send detailed command help with formatting
-    #[allow(dead_code)]
     pub async fn send_command_help(&self, command: &str, description: &str, examples: &[&str]) -> ResponseResult<Message> {
         let mut help_text = format!("**{}**\n\n{}\n\n", escape_markdown(command), escape_markdown(description));
         
@@ -97,25 +129,115 @@ impl CommandFeedback {
             }
         }
         
-        self.bot
+        let request = self.bot
             .send_message(self.chat_id, help_text)
-            .parse_mode(ParseMode::MarkdownV2)
-            .await
+            .parse_mode(ParseMode::MarkdownV2);
+        plain_fallback(request).await
+    }
+
+    /// Send the usage of a command whose arguments were missing or malformed, see [`usage_help_text`]
+    pub async fn send_usage_help(
+        &self,
+        command: &str,
+        usage: &str,
+        example: Option<&str>,
+        suggestion: Option<&str>,
+    ) -> ResponseResult<Message> {
+        let request = self.bot
+            .send_message(self.chat_id, usage_help_text(self.lang, command, usage, example, suggestion))
+            .parse_mode(ParseMode::MarkdownV2);
+        plain_fallback(request).await
     }
 
     /// Send validation error with helpful suggestion
     pub async fn validation_error(&self, error: &str, suggestion: &str) -> ResponseResult<Message> {
-        let message = format!("{error}\n\n💡 **Suggestion:** {suggestion}");
+        let message = i18n::t_validation_error(self.lang, error, suggestion);
         self.send(FeedbackType::Error, &message).await
     }
 }
 
+/// MarkdownV2 reply to `command` sent with missing or malformed arguments
+///
+/// Shows its `usage` and an `example`, and the invocation the user most likely meant as
+/// `suggestion`; both are monospace, so a tap copies them.
+pub fn usage_help_text(lang: Lang, command: &str, usage: &str, example: Option<&str>, suggestion: Option<&str>) -> String {
+    let mut text = format!(
+        "{} Missing or invalid arguments for {}\n\n**Usage:** `{}`\n",
+        FeedbackType::Error.emoji(),
+        escape_markdown(command),
+        escape_markdown(usage)
+    );
+    if let Some(example) = example {
+        text.push_str(&format!("**Example:** `{}`\n", escape_markdown(example)));
+    }
+    if let Some(suggestion) = suggestion {
+        text.push_str(&format!(
+            "\n💡 **{}:** `{}`",
+            escape_markdown(i18n::tr(lang, "feedback.suggestion")),
+            escape_markdown(suggestion)
+        ));
+    }
+    text
+}
+
+/// Source of the current time, so edit coalescing can be tested without sleeping
+pub trait Clock {
+    /// The current instant
+    fn now(&self) -> Instant;
+}
+
+/// The real monotonic clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Decides which progress updates are worth an edit call
+///
+/// Intermediate updates within `interval` of the last write are dropped; final ones always go through.
+pub struct EditCoalescer<C: Clock = SystemClock> {
+    clock: C,
+    interval: Duration,
+    last_write: Option<Instant>,
+}
+
+impl<C: Clock> EditCoalescer<C> {
+    /// A coalescer that hasn't written anything yet
+    pub fn new(clock: C, interval: Duration) -> Self {
+        Self {
+            clock,
+            interval,
+            last_write: None,
+        }
+    }
+
+    /// Whether to write an update now, remembering the write if so
+    pub fn should_write(&mut self, is_final: bool) -> bool {
+        let now = self.clock.now();
+        let too_soon = self.last_write
+            .is_some_and(|last| now.saturating_duration_since(last) < self.interval);
+        if too_soon && !is_final {
+            return false;
+        }
+        self.last_write = Some(now);
+        true
+    }
+}
+
 /// Progress tracker for multi-step operations
+///
+/// Steps that follow each other quickly only update the message once; the final
+/// `complete` or `error` is always written.
 pub struct ProgressTracker {
     feedback: CommandFeedback,
     message_id: Option<MessageId>,
     total_steps: u32,
     current_step: u32,
+    edits: EditCoalescer,
 }
 
 impl ProgressTracker {
@@ -125,6 +247,7 @@ impl ProgressTracker {
             message_id: None,
             total_steps,
             current_step: 0,
+            edits: EditCoalescer::new(SystemClock, PROGRESS_EDIT_INTERVAL),
         }
     }
 
@@ -134,13 +257,17 @@ impl ProgressTracker {
         let message = self.feedback.send_processing(&progress_message).await?;
         self.message_id = Some(message.id);
         self.current_step = 1;
+        self.edits.should_write(true);
         Ok(())
     }
 
-    /// Update progress to next step
+    /// Update progress to next step, skipping the edit if the last one was very recent
     pub async fn next_step(&mut self, step_message: &str) -> ResponseResult<()> {
         if let Some(message_id) = self.message_id {
             self.current_step += 1;
+            if !self.edits.should_write(false) {
+                return Ok(());
+            }
             let progress_message = format!("{} ({}/{})", step_message, self.current_step, self.total_steps);
             self.feedback.update_message(message_id, FeedbackType::Processing, &progress_message).await?;
         }
@@ -150,6 +277,7 @@ impl ProgressTracker {
     /// Complete progress tracking with success message
     pub async fn complete(&mut self, completion_message: &str) -> ResponseResult<()> {
         if let Some(message_id) = self.message_id {
+            self.edits.should_write(true);
             self.feedback.update_message(message_id, FeedbackType::Success, completion_message).await?;
         }
         Ok(())
@@ -158,6 +286,7 @@ impl ProgressTracker {
     /// Complete progress tracking with error message
     pub async fn error(&mut self, error_message: &str) -> ResponseResult<()> {
         if let Some(message_id) = self.message_id {
+            self.edits.should_write(true);
             self.feedback.update_message(message_id, FeedbackType::Error, error_message).await?;
         }
         Ok(())
@@ -176,4 +305,55 @@ mod tests {
         assert_eq!(FeedbackType::Info.emoji(), "ℹ️");
         assert_eq!(FeedbackType::Processing.emoji(), "⏳");
     }
+
+    /// A clock that only moves when told to
+    #[derive(Clone)]
+    struct ManualClock(std::rc::Rc<std::cell::Cell<Instant>>);
+
+    impl ManualClock {
+        fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    fn manual_coalescer() -> (ManualClock, EditCoalescer<ManualClock>) {
+        let clock = ManualClock(std::rc::Rc::new(std::cell::Cell::new(Instant::now())));
+        (clock.clone(), EditCoalescer::new(clock, PROGRESS_EDIT_INTERVAL))
+    }
+
+    #[test]
+    fn test_coalescer_skips_rapid_steps() {
+        let (clock, mut edits) = manual_coalescer();
+        assert!(edits.should_write(false), "the first update is always written");
+
+        clock.advance(Duration::from_millis(30));
+        assert!(!edits.should_write(false));
+        clock.advance(Duration::from_millis(30));
+        assert!(!edits.should_write(false));
+
+        // Skipped steps don't push the window forward
+        clock.advance(Duration::from_millis(640));
+        assert!(edits.should_write(false));
+        assert!(!edits.should_write(false));
+    }
+
+    #[test]
+    fn test_coalescer_always_writes_final_state() {
+        let (clock, mut edits) = manual_coalescer();
+        assert!(edits.should_write(true));
+
+        clock.advance(Duration::from_millis(10));
+        assert!(!edits.should_write(false));
+        assert!(edits.should_write(true), "complete/error must be written even right after a step");
+
+        clock.advance(Duration::from_millis(10));
+        assert!(edits.should_write(true));
+        assert!(!edits.should_write(false), "a final write also restarts the window");
+    }
 }
\ No newline at end of file
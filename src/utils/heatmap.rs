@@ -0,0 +1,114 @@
+//! The day-by-day summary of an `/availability` poll, as plain text
+//!
+//! Each day gets a row of squares, one per voter: 🟩 for yes, 🟨 for maybe, 🟥 for no and ⬜
+//! for no answer on that day. Days are ordered by yes votes, then maybes, then date, so the
+//! best day is on top.
+
+use crate::bot::poll::VoteCounts;
+use crate::database::models::{Response, SessionOption};
+use chrono::{DateTime, Utc};
+
+/// Most voters shown as squares; beyond that a row would wrap on a phone screen
+pub const MAX_HEATMAP_VOTERS: usize = 12;
+
+/// Render the votes on the days in `options` as a heatmap, best day first
+///
+/// `None` before anyone has voted. With more than [`MAX_HEATMAP_VOTERS`] voters the rows
+/// only show the counts.
+pub fn render_availability_heatmap(options: &[SessionOption], responses: &[Response]) -> Option<String> {
+    let mut voters: Vec<i64> = Vec::new();
+    for response in responses {
+        if !voters.contains(&response.user_id) {
+            voters.push(response.user_id);
+        }
+    }
+    if voters.is_empty() {
+        return None;
+    }
+
+    let mut days: Vec<(&SessionOption, VoteCounts)> = options.iter()
+        .map(|option| (option, VoteCounts::for_option(responses, &option.id)))
+        .collect();
+    days.sort_by(|(a, a_counts), (b, b_counts)| {
+        b_counts.yes.cmp(&a_counts.yes)
+            .then(b_counts.maybe.cmp(&a_counts.maybe))
+            .then(a.datetime.cmp(&b.datetime))
+    });
+
+    let lines: Vec<String> = days.iter()
+        .map(|(option, counts)| {
+            let day = DateTime::parse_from_rfc3339(&option.datetime)
+                .map(|dt| dt.with_timezone(&Utc).format("%a %d.%m").to_string())
+                .unwrap_or_else(|_| option.datetime.clone());
+            let tally = format!("{} yes, {} maybe", counts.yes, counts.maybe);
+            if voters.len() > MAX_HEATMAP_VOTERS {
+                return format!("{day}  {tally}");
+            }
+            let answered = counts.yes + counts.maybe + counts.no;
+            let squares = "🟩".repeat(counts.yes)
+                + &"🟨".repeat(counts.maybe)
+                + &"🟥".repeat(counts.no)
+                + &"⬜".repeat(voters.len().saturating_sub(answered));
+            format!("{squares} {day}  {tally}")
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(id: &str, datetime: &str) -> SessionOption {
+        SessionOption {
+            id: id.to_string(),
+            session_id: "s".to_string(),
+            datetime: datetime.to_string(),
+            duration: 240,
+            confirmed: false,
+        }
+    }
+
+    fn vote(option_id: &str, user_id: i64, response: &str) -> Response {
+        Response {
+            id: format!("{option_id}-{user_id}"),
+            session_id: "s".to_string(),
+            option_id: option_id.to_string(),
+            user_id,
+            username: None,
+            response: response.to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_best_day_first() {
+        let options = [
+            option("mon", "2024-12-16T18:00:00+00:00"),
+            option("tue", "2024-12-17T18:00:00+00:00"),
+            option("wed", "2024-12-18T18:00:00+00:00"),
+        ];
+        let responses = [
+            vote("mon", 1, "no"),
+            vote("tue", 1, "yes"),
+            vote("tue", 2, "yes"),
+            vote("wed", 1, "yes"),
+            vote("wed", 3, "maybe"),
+        ];
+        let heatmap = render_availability_heatmap(&options, &responses).unwrap();
+        assert_eq!(heatmap, [
+            "🟩🟩⬜ Tue 17.12  2 yes, 0 maybe",
+            "🟩🟨⬜ Wed 18.12  1 yes, 1 maybe",
+            "🟥⬜⬜ Mon 16.12  0 yes, 0 maybe",
+        ].join("\n"));
+        assert_eq!(render_availability_heatmap(&options, &[]), None);
+    }
+
+    #[test]
+    fn test_many_voters_show_counts_only() {
+        let options = [option("mon", "2024-12-16T18:00:00+00:00")];
+        let responses: Vec<Response> = (0..=MAX_HEATMAP_VOTERS as i64).map(|user_id| vote("mon", user_id, "yes")).collect();
+        assert_eq!(render_availability_heatmap(&options, &responses).unwrap(), "Mon 16.12  13 yes, 0 maybe");
+    }
+}
@@ -0,0 +1,647 @@
+//! Translated message templates for the bot's replies
+//!
+//! The languages match the day names the date parser understands: English, Swedish, French and
+//! German. Templates use `{name}` placeholders; handlers call the typed `t_*` functions, which
+//! fill them in, rather than looking templates up by key.
+
+use crate::bot::poll::VoteCounts;
+use crate::bot::selection::MaybeSwing;
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A language the bot can reply in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Lang {
+    /// English, the fallback for everything
+    #[default]
+    En,
+    /// Swedish
+    Sv,
+    /// French
+    Fr,
+    /// German
+    De,
+}
+
+impl Lang {
+    /// Every language, in the order of each entry in `MESSAGES`
+    pub const ALL: [Lang; 4] = [Lang::En, Lang::Sv, Lang::Fr, Lang::De];
+
+    /// The code stored in a group's `language` column, e.g. "sv"
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Sv => "sv",
+            Lang::Fr => "fr",
+            Lang::De => "de",
+        }
+    }
+
+    /// The language with code `code` (ignoring case and surrounding whitespace)
+    pub fn from_code(code: &str) -> Option<Lang> {
+        Lang::ALL.into_iter().find(|lang| lang.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// The language's own name for itself, e.g. "Svenska"
+    pub fn name(self) -> &'static str {
+        tr(self, "language.name")
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Language codes a group can choose, in the order of `Lang::ALL`
+pub const SUPPORTED_LANGUAGES: [&str; 4] = ["en", "sv", "fr", "de"];
+
+/// Language of private chats and of groups that never picked one
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Message templates by key, translated in the order of `Lang::ALL`
+///
+/// An empty template counts as not translated yet and falls back to English.
+const MESSAGES: &[(&str, [&str; 4])] = &[
+    ("language.name", ["English", "Svenska", "Français", "Deutsch"]),
+    ("feedback.suggestion", ["Suggestion", "Förslag", "Suggestion", "Vorschlag"]),
+    ("help.text", [
+        "🎲 **D&D Scheduler Bot Commands**\n\n{commands}\n\n💡 **Quick Start:**\n• Use `/schedule \"Session Title\" \"Friday 19:00, Saturday 14:30\"` to create a poll\n• Players click buttons to vote\n• Use `/confirm <session_id>` to lock in the winning time\n\n📚 **Need more help?** Each command provides detailed error messages and suggestions when used incorrectly.",
+        "🎲 **D&D Scheduler Bot – kommandon**\n\n{commands}\n\n💡 **Kom igång:**\n• Skapa en omröstning med `/schedule \"Sessionens titel\" \"fredag 19:00, lördag 14:30\"`\n• Spelarna röstar med knapparna\n• Lås den vinnande tiden med `/confirm <session_id>`\n\n📚 **Behöver du mer hjälp?** Varje kommando ger utförliga felmeddelanden och förslag när det används fel.",
+        "🎲 **Commandes de D&D Scheduler Bot**\n\n{commands}\n\n💡 **Pour commencer :**\n• Créez un sondage avec `/schedule \"Titre de la session\" \"vendredi 19:00, samedi 14:30\"`\n• Les joueurs votent avec les boutons\n• Validez l'horaire gagnant avec `/confirm <session_id>`\n\n📚 **Besoin d'aide ?** Chaque commande donne des messages d'erreur détaillés et des suggestions en cas de mauvaise utilisation.",
+        "🎲 **Befehle des D&D Scheduler Bot**\n\n{commands}\n\n💡 **Schnellstart:**\n• Erstelle eine Umfrage mit `/schedule \"Titel der Sitzung\" \"freitag 19:00, samstag 14:30\"`\n• Die Spieler stimmen über die Knöpfe ab\n• Lege den gewinnenden Termin mit `/confirm <session_id>` fest\n\n📚 **Mehr Hilfe nötig?** Jeder Befehl gibt bei falscher Verwendung ausführliche Fehlermeldungen und Vorschläge.",
+    ]),
+    ("start.welcome", [
+        "Welcome to D&D Scheduler Bot!\n\nI help you schedule D&D sessions by creating polls where players can vote on their preferred times.\n\n🚀 **Get Started:**\n• Use /schedule to create your first session poll\n• Use /help to see all available commands\n\n🎯 **Pro Tip:** I provide detailed feedback and suggestions for every command!",
+        "Välkommen till D&D Scheduler Bot!\n\nJag hjälper er att planera D&D-sessioner med omröstningar där spelarna röstar på de tider som passar dem.\n\n🚀 **Kom igång:**\n• Skapa din första omröstning med /schedule\n• Se alla kommandon med /help\n\n🎯 **Tips:** Jag ger utförlig återkoppling och förslag för varje kommando!",
+        "Bienvenue sur D&D Scheduler Bot !\n\nJe vous aide à planifier vos sessions de D&D grâce à des sondages où les joueurs votent pour les horaires qui leur conviennent.\n\n🚀 **Pour commencer :**\n• Créez votre premier sondage avec /schedule\n• Affichez toutes les commandes avec /help\n\n🎯 **Astuce :** je donne des retours détaillés et des suggestions pour chaque commande !",
+        "Willkommen beim D&D Scheduler Bot!\n\nIch helfe euch, D&D-Sitzungen zu planen, mit Umfragen, in denen die Spieler für die passenden Termine stimmen.\n\n🚀 **Los geht's:**\n• Erstelle deine erste Umfrage mit /schedule\n• Alle Befehle zeigt /help\n\n🎯 **Tipp:** Ich gebe zu jedem Befehl ausführliche Rückmeldungen und Vorschläge!",
+    ]),
+    ("language.current", [
+        "This group's language is {language}.",
+        "Gruppens språk är {language}.",
+        "La langue de ce groupe est : {language}.",
+        "Die Sprache dieser Gruppe ist {language}.",
+    ]),
+    ("language.usage", [
+        "Change it with /settings language <code>, using one of: {choices}",
+        "Byt med /settings language <kod>, där koden är en av: {choices}",
+        "Changez-la avec /settings language <code>, parmi : {choices}",
+        "Ändern mit /settings language <code>, mit einem von: {choices}",
+    ]),
+    ("language.changed", [
+        "Language set to {language}",
+        "Språket är nu {language}",
+        "La langue est maintenant : {language}",
+        "Sprache auf {language} gesetzt",
+    ]),
+    ("language.unsupported", [
+        "Unsupported language '{code}'",
+        "Språket '{code}' stöds inte",
+        "La langue « {code} » n'est pas prise en charge",
+        "Die Sprache '{code}' wird nicht unterstützt",
+    ]),
+    ("language.admins_only", [
+        "Permission denied: Only chat administrators or the group's bot admins can change the group's language",
+        "Åtkomst nekad: Endast chattens administratörer eller gruppens botadministratörer kan byta gruppens språk",
+        "Permission refusée : seuls les administrateurs du chat ou les admins du bot du groupe peuvent changer la langue du groupe",
+        "Keine Berechtigung: Nur Chat-Administratoren oder die Bot-Admins der Gruppe können die Sprache der Gruppe ändern",
+    ]),
+    ("language.ask_admin", [
+        "Ask a chat administrator to run this command.",
+        "Be en av chattens administratörer att köra kommandot.",
+        "Demandez à un administrateur du chat de lancer cette commande.",
+        "Bitte einen Chat-Administrator, diesen Befehl auszuführen.",
+    ]),
+    ("schedule.created", [
+        "Session '{title}' created successfully!\n\n📊 Session Details:\n• {count} time options available\n• Session: {reference} (ID {id})\n• Voting is now open!{anonymous}\n\n💡 Use /list to see all active sessions, or /confirm {reference} when you're ready",
+        "Sessionen '{title}' har skapats!\n\n📊 Detaljer:\n• {count} tidsalternativ\n• Session: {reference} (ID {id})\n• Omröstningen är öppen!{anonymous}\n\n💡 Se alla aktiva sessioner med /list, eller kör /confirm {reference} när ni är redo",
+        "La session « {title} » a été créée !\n\n📊 Détails :\n• {count} horaires proposés\n• Session : {reference} (ID {id})\n• Le vote est ouvert !{anonymous}\n\n💡 Affichez les sessions actives avec /list, ou lancez /confirm {reference} quand vous êtes prêts",
+        "Sitzung '{title}' wurde erstellt!\n\n📊 Details:\n• {count} Terminvorschläge\n• Sitzung: {reference} (ID {id})\n• Die Abstimmung läuft!{anonymous}\n\n💡 Alle aktiven Sitzungen zeigt /list, mit /confirm {reference} legt ihr den Termin fest",
+    ]),
+    ("schedule.anonymous", [
+        "\n• Votes are hidden until the session is confirmed",
+        "\n• Rösterna är dolda tills sessionen har bekräftats",
+        "\n• Les votes restent cachés jusqu'à la confirmation de la session",
+        "\n• Die Stimmen bleiben verborgen, bis die Sitzung bestätigt ist",
+    ]),
+    ("schedule.auto_title", [
+        "Game night — week of {day} {month}",
+        "Spelkväll — veckan från {day} {month}",
+        "Soirée jeu — semaine du {day} {month}",
+        "Spieleabend — Woche vom {day}. {month}",
+    ]),
+    ("schedule.auto_titled", [
+        "\n\n✏️ No title was given, so I called it '{title}'. To name it yourself, /delete {reference} and /schedule again with the title in quotes",
+        "\n\n✏️ Ingen titel angavs, så jag kallade den '{title}'. Vill du namnge den själv, kör /delete {reference} och /schedule igen med titeln inom citattecken",
+        "\n\n✏️ Aucun titre n'a été donné, je l'ai donc appelée « {title} ». Pour la nommer vous-même, lancez /delete {reference} puis /schedule à nouveau avec le titre entre guillemets",
+        "\n\n✏️ Es wurde kein Titel angegeben, also heißt sie '{title}'. Um sie selbst zu benennen, /delete {reference} und /schedule erneut mit dem Titel in Anführungszeichen",
+    ]),
+    ("confirm.success", [
+        "Session '{title}' confirmed successfully!\n\n📅 Confirmed Time: {datetime}\n👥 {count} players will attend{details}\n\n🎯 All participants have been notified. The session is now locked in!",
+        "Sessionen '{title}' är bekräftad!\n\n📅 Bekräftad tid: {datetime}\n👥 {count} spelare kommer{details}\n\n🎯 Alla deltagare har meddelats. Sessionen är nu spikad!",
+        "La session « {title} » est confirmée !\n\n📅 Horaire retenu : {datetime}\n👥 {count} joueurs seront présents{details}\n\n🎯 Tous les participants ont été prévenus. La session est maintenant fixée !",
+        "Sitzung '{title}' ist bestätigt!\n\n📅 Termin: {datetime}\n👥 {count} Spieler sind dabei{details}\n\n🎯 Alle Teilnehmer wurden benachrichtigt. Die Sitzung steht fest!",
+    ]),
+    ("confirm.auto", [
+        "🤖 Confirmed automatically: it reached {threshold} yes votes before the deadline",
+        "🤖 Bekräftad automatiskt: den fick {threshold} ja-röster före svarstidens slut",
+        "🤖 Confirmée automatiquement : elle a atteint {threshold} oui avant la date limite",
+        "🤖 Automatisch bestätigt: {threshold} Ja-Stimmen vor Ablauf der Frist",
+    ]),
+    ("confirm.score", [
+        "🏆 Selected by score {score} (yes = {yes}, maybe = {maybe})",
+        "🏆 Vald med poängen {score} (ja = {yes}, kanske = {maybe})",
+        "🏆 Choisi avec un score de {score} (oui = {yes}, peut-être = {maybe})",
+        "🏆 Gewählt mit Punktzahl {score} (ja = {yes}, vielleicht = {maybe})",
+    ]),
+    ("confirm.breakdown", [
+        "🗳️ Won with {yes} yes, {maybe} maybe",
+        "🗳️ Vann med {yes} ja, {maybe} kanske",
+        "🗳️ Gagnant avec {yes} oui, {maybe} peut-être",
+        "🗳️ Gewonnen mit {yes} ja, {maybe} vielleicht",
+    ]),
+    ("confirm.breakdown_over", [
+        "🗳️ Won with {yes} yes, {maybe} maybe over {other} ({other_yes} yes, {other_maybe} maybe)",
+        "🗳️ Vann med {yes} ja, {maybe} kanske över {other} ({other_yes} ja, {other_maybe} kanske)",
+        "🗳️ Gagnant avec {yes} oui, {maybe} peut-être devant {other} ({other_yes} oui, {other_maybe} peut-être)",
+        "🗳️ Gewonnen mit {yes} ja, {maybe} vielleicht vor {other} ({other_yes} ja, {other_maybe} vielleicht)",
+    ]),
+    ("confirm.maybe_swing", [
+        "⚠️ If {converts} of the {maybes} maybes on {other} turn to yes, it wins {yes}–{winner_yes} on yes votes: worth nudging them or waiting before settling on a time",
+        "⚠️ Om {converts} av de {maybes} kanske-rösterna på {other} blir ja vinner den med {yes}–{winner_yes} i ja-röster: påminn dem eller vänta lite innan ni bestämmer tid",
+        "⚠️ Si {converts} des {maybes} « peut-être » sur {other} passent à oui, cette option l'emporte {yes}–{winner_yes} en votes oui : relancez-les ou attendez un peu avant d'arrêter l'horaire",
+        "⚠️ Wenn {converts} der {maybes} Vielleicht-Stimmen für {other} zu Ja werden, gewinnt dieser Termin {yes}–{winner_yes} nach Ja-Stimmen: erinnere sie oder warte noch etwas, bevor ihr den Termin festlegt",
+    ]),
+    ("session.notes", [
+        "📝 Notes: {notes}",
+        "📝 Anteckningar: {notes}",
+        "📝 Notes : {notes}",
+        "📝 Notizen: {notes}",
+    ]),
+    ("cancel.success", [
+        "Session '{title}' cancelled successfully!\n\n📅 The session has been removed from the schedule\n👥 All participants have been notified\n\n💡 Use /schedule to create a new session if needed",
+        "Sessionen '{title}' är inställd!\n\n📅 Sessionen har tagits bort från schemat\n👥 Alla deltagare har meddelats\n\n💡 Skapa en ny session med /schedule om det behövs",
+        "La session « {title} » est annulée !\n\n📅 La session a été retirée du planning\n👥 Tous les participants ont été prévenus\n\n💡 Créez une nouvelle session avec /schedule si besoin",
+        "Sitzung '{title}' wurde abgesagt!\n\n📅 Die Sitzung wurde aus dem Plan entfernt\n👥 Alle Teilnehmer wurden benachrichtigt\n\n💡 Bei Bedarf erstellt /schedule eine neue Sitzung",
+    ]),
+    ("deadline.success", [
+        "Session deadline set successfully!\n\n📅 Session: {title}\n⏰ Responses due by: {deadline}\n\n💡 Players will be reminded before the deadline\n👥 Use /list to check current voting status",
+        "Svarstiden är satt!\n\n📅 Session: {title}\n⏰ Svara senast: {deadline}\n\n💡 Spelarna påminns innan tiden går ut\n👥 Se hur omröstningen går med /list",
+        "La date limite est fixée !\n\n📅 Session : {title}\n⏰ Réponses attendues avant : {deadline}\n\n💡 Les joueurs seront relancés avant la date limite\n👥 Suivez le vote avec /list",
+        "Die Frist ist gesetzt!\n\n📅 Sitzung: {title}\n⏰ Antworten bis: {deadline}\n\n💡 Die Spieler werden vor Ablauf der Frist erinnert\n👥 Den Stand der Abstimmung zeigt /list",
+    ]),
+    ("reminder.two_weeks", ["2 Week Reminder", "Påminnelse: 2 veckor kvar", "Rappel : dans 2 semaines", "Erinnerung: noch 2 Wochen"]),
+    ("reminder.one_week", ["1 Week Reminder", "Påminnelse: 1 vecka kvar", "Rappel : dans 1 semaine", "Erinnerung: noch 1 Woche"]),
+    ("reminder.three_days", ["3 Day Reminder", "Påminnelse: 3 dagar kvar", "Rappel : dans 3 jours", "Erinnerung: noch 3 Tage"]),
+    ("reminder.game_day", ["Game Day", "Speldag", "Jour de jeu", "Spieltag"]),
+    ("reminder.two_hours", ["2 Hour Reminder", "Påminnelse: 2 timmar kvar", "Rappel : dans 2 heures", "Erinnerung: noch 2 Stunden"]),
+    ("reminder.long_range", [
+        "Still on the Calendar: {days} Days to Go",
+        "Fortfarande i kalendern: {days} dagar kvar",
+        "Toujours au calendrier : dans {days} jours",
+        "Steht weiter im Kalender: noch {days} Tage",
+    ]),
+    ("reminder.body", [
+        "📅 **{heading}**\n\n🎲 **{title}**\n\n📅 **When:** {when}\n⏱️ **Duration:** {duration}\n{notes}👥 **Participants:** {participants}\n\n🔗 Session {reference} \\(ID: `{id}`\\)",
+        "📅 **{heading}**\n\n🎲 **{title}**\n\n📅 **När:** {when}\n⏱️ **Längd:** {duration}\n{notes}👥 **Deltagare:** {participants}\n\n🔗 Session {reference} \\(ID: `{id}`\\)",
+        "📅 **{heading}**\n\n🎲 **{title}**\n\n📅 **Quand :** {when}\n⏱️ **Durée :** {duration}\n{notes}👥 **Participants :** {participants}\n\n🔗 Session {reference} \\(ID : `{id}`\\)",
+        "📅 **{heading}**\n\n🎲 **{title}**\n\n📅 **Wann:** {when}\n⏱️ **Dauer:** {duration}\n{notes}👥 **Teilnehmer:** {participants}\n\n🔗 Sitzung {reference} \\(ID: `{id}`\\)",
+    ]),
+    ("reminder.notes", [
+        "📝 **Notes:** {notes}\n",
+        "📝 **Anteckningar:** {notes}\n",
+        "📝 **Notes :** {notes}\n",
+        "📝 **Notizen:** {notes}\n",
+    ]),
+    ("reminder.no_participants", [
+        "No participants confirmed yet",
+        "Inga deltagare har bekräftat än",
+        "Aucun participant confirmé pour l'instant",
+        "Noch keine Teilnehmer bestätigt",
+    ]),
+    ("reminder.and_others", [
+        "{names} and {count} others",
+        "{names} och {count} till",
+        "{names} et {count} autres",
+        "{names} und {count} weitere",
+    ]),
+    ("whats_new.heading", [
+        "🆕 What's new in version {version}:",
+        "🆕 Nytt i version {version}:",
+        "🆕 Nouveautés de la version {version} :",
+        "🆕 Neu in Version {version}:",
+    ]),
+    ("whats_new.opt_out", [
+        "An admin can turn these messages off with /settings whatsnew off",
+        "En admin kan stänga av de här meddelandena med /settings whatsnew off",
+        "Un admin peut désactiver ces messages avec /settings whatsnew off",
+        "Ein Admin kann diese Nachrichten mit /settings whatsnew off abschalten",
+    ]),
+    ("reminder.vote_body", [
+        "🗳️ **{title}** is still waiting for votes\n\n👥 Voted so far: {voters}{deadline}\n\nPick your times with the buttons on the poll above\\.",
+        "🗳️ **{title}** väntar fortfarande på röster\n\n👥 Har röstat hittills: {voters}{deadline}\n\nVälj dina tider med knapparna i omröstningen ovan\\.",
+        "🗳️ **{title}** attend toujours vos votes\n\n👥 Votants jusqu'ici : {voters}{deadline}\n\nChoisissez vos horaires avec les boutons du sondage ci\\-dessus\\.",
+        "🗳️ **{title}** wartet noch auf Stimmen\n\n👥 Bisher abgestimmt: {voters}{deadline}\n\nWähle deine Termine mit den Knöpfen der Umfrage oben\\.",
+    ]),
+    ("reminder.vote_deadline", [
+        "\n⏰ Voting closes {deadline}",
+        "\n⏰ Omröstningen stänger {deadline}",
+        "\n⏰ Fin du vote : {deadline}",
+        "\n⏰ Abstimmung endet {deadline}",
+    ]),
+    ("reminder.vote_link", [
+        "\n\n🗳️ Plans changed? [Change your vote in a private chat]({link})",
+        "\n\n🗳️ Ändrade planer? [Ändra din röst i en privat chatt]({link})",
+        "\n\n🗳️ Changement de programme ? [Modifiez votre vote en privé]({link})",
+        "\n\n🗳️ Pläne geändert? [Ändere deine Stimme im privaten Chat]({link})",
+    ]),
+    ("reminder.check_in", [
+        "\n\n📍 Coming today? Tap **Check in** so the table knows\\.",
+        "\n\n📍 Kommer du i dag? Tryck på **Check in** så att alla vet\\.",
+        "\n\n📍 Vous venez aujourd'hui ? Appuyez sur **Check in** pour prévenir la table\\.",
+        "\n\n📍 Kommst du heute? Tippe auf **Check in**, damit alle Bescheid wissen\\.",
+    ]),
+];
+
+fn messages() -> &'static HashMap<&'static str, [&'static str; 4]> {
+    static MAP: OnceLock<HashMap<&'static str, [&'static str; 4]>> = OnceLock::new();
+    MAP.get_or_init(|| MESSAGES.iter().copied().collect())
+}
+
+/// The language with code `lang` (ignoring case), or English for unknown codes
+pub fn normalize(lang: &str) -> Lang {
+    Lang::from_code(lang).unwrap_or_default()
+}
+
+/// Whether `lang` is one of `SUPPORTED_LANGUAGES`
+pub fn is_supported(lang: &str) -> bool {
+    SUPPORTED_LANGUAGES.contains(&lang)
+}
+
+/// The template for `key` in `lang`
+///
+/// Missing translations get the English template; unknown keys come back as is, so a missing
+/// entry shows up in the chat instead of an empty message.
+pub fn tr(lang: Lang, key: &'static str) -> &'static str {
+    match messages().get(key) {
+        Some(templates) if !templates[lang.index()].is_empty() => templates[lang.index()],
+        Some(templates) => templates[Lang::En.index()],
+        None => key,
+    }
+}
+
+/// [`tr`] with each `{name}` placeholder replaced by its value in `args`
+///
+/// Values are inserted as they are, so a title that happens to contain `{count}` stays intact.
+pub fn tr_with(lang: Lang, key: &'static str, args: &[(&str, &str)]) -> String {
+    let mut rest = tr(lang, key);
+    let mut text = String::with_capacity(rest.len());
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let filled = after.split_once('}').and_then(|(name, tail)| {
+            args.iter().find(|(arg, _)| *arg == name).map(|(_, value)| (*value, tail))
+        });
+        match filled {
+            Some((value, tail)) => {
+                text.push_str(value);
+                rest = tail;
+            }
+            None => {
+                text.push('{');
+                rest = after;
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+/// `/help`, listing `commands`
+pub fn t_help(lang: Lang, commands: &str) -> String {
+    tr_with(lang, "help.text", &[("commands", commands)])
+}
+
+/// `/start` without a payload
+pub fn t_welcome(lang: Lang) -> &'static str {
+    tr(lang, "start.welcome")
+}
+
+/// Validation feedback: what went wrong, then a suggestion of what to do instead
+pub fn t_validation_error(lang: Lang, error: &str, suggestion: &str) -> String {
+    format!("{error}\n\n💡 **{}:** {suggestion}", tr(lang, "feedback.suggestion"))
+}
+
+/// `/schedule` success for session `reference` (ID prefix `id`) with `count` time options
+pub fn t_session_created(lang: Lang, title: &str, count: usize, reference: &str, id: &str, anonymous: bool) -> String {
+    let anonymous = if anonymous { tr(lang, "schedule.anonymous") } else { "" };
+    tr_with(lang, "schedule.created", &[
+        ("title", title),
+        ("count", &count.to_string()),
+        ("reference", reference),
+        ("id", id),
+        ("anonymous", anonymous),
+    ])
+}
+
+/// Short month names, in the order of `Lang::ALL`
+const MONTHS: [[&str; 12]; 4] = [
+    ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"],
+    ["jan", "feb", "mar", "apr", "maj", "jun", "jul", "aug", "sep", "okt", "nov", "dec"],
+    ["janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.", "déc."],
+    ["Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sep.", "Okt.", "Nov.", "Dez."],
+];
+
+/// The title `/schedule` gives a session when none was typed, for the week starting `monday`
+pub fn t_auto_title(lang: Lang, monday: NaiveDate) -> String {
+    let month = MONTHS[lang.index()][monday.month0() as usize];
+    tr_with(lang, "schedule.auto_title", &[("day", &monday.day().to_string()), ("month", month)])
+}
+
+/// Appended to the `/schedule` success when the session got a [`t_auto_title`]
+pub fn t_auto_titled(lang: Lang, title: &str, reference: &str) -> String {
+    tr_with(lang, "schedule.auto_titled", &[("title", title), ("reference", reference)])
+}
+
+/// `/confirm` success; `details` are extra lines such as [`t_confirm_score`] and [`t_notes`]
+pub fn t_session_confirmed(lang: Lang, title: &str, datetime: &str, count: usize, details: &[String]) -> String {
+    let details: String = details.iter().map(|line| format!("\n{line}")).collect();
+    tr_with(lang, "confirm.success", &[
+        ("title", title),
+        ("datetime", datetime),
+        ("count", &count.to_string()),
+        ("details", &details),
+    ])
+}
+
+/// How the confirmed option won, with the weights of "yes" and "maybe" votes
+pub fn t_confirm_score(lang: Lang, score: f64, yes_weight: f64, maybe_weight: f64) -> String {
+    tr_with(lang, "confirm.score", &[
+        ("score", &score.to_string()),
+        ("yes", &yes_weight.to_string()),
+        ("maybe", &maybe_weight.to_string()),
+    ])
+}
+
+/// The confirmed option's votes, and those of the runner-up (its time and tallies) if there was one
+pub fn t_confirm_breakdown(lang: Lang, winner: &VoteCounts, runner_up: Option<(&str, &VoteCounts)>) -> String {
+    let (yes, maybe) = (winner.yes.to_string(), winner.maybe.to_string());
+    match runner_up {
+        Some((other, counts)) => tr_with(lang, "confirm.breakdown_over", &[
+            ("yes", &yes),
+            ("maybe", &maybe),
+            ("other", other),
+            ("other_yes", &counts.yes.to_string()),
+            ("other_maybe", &counts.maybe.to_string()),
+        ]),
+        None => tr_with(lang, "confirm.breakdown", &[("yes", &yes), ("maybe", &maybe)]),
+    }
+}
+
+/// Warning that enough maybes on `other` turning to yes would make it win (see `MaybeSwing`)
+pub fn t_maybe_swing(lang: Lang, other: &str, swing: &MaybeSwing) -> String {
+    tr_with(lang, "confirm.maybe_swing", &[
+        ("converts", &swing.converts.to_string()),
+        ("maybes", &swing.maybes.to_string()),
+        ("other", other),
+        ("yes", &swing.yes.to_string()),
+        ("winner_yes", &swing.winner_yes.to_string()),
+    ])
+}
+
+/// A session's notes as one line of plain feedback
+pub fn t_notes(lang: Lang, notes: &str) -> String {
+    tr_with(lang, "session.notes", &[("notes", notes)])
+}
+
+/// `/cancel` success
+pub fn t_session_cancelled(lang: Lang, title: &str) -> String {
+    tr_with(lang, "cancel.success", &[("title", title)])
+}
+
+/// Detail line of a session confirmed by the group's auto-confirm `threshold`
+pub fn t_autoconfirmed(lang: Lang, threshold: i64) -> String {
+    tr_with(lang, "confirm.auto", &[("threshold", &threshold.to_string())])
+}
+
+/// MarkdownV2 reminder to vote on a poll that's still open; `title` and `deadline` are escaped
+///
+/// Like [`t_reminder`], it ends with the `vote_link` to the poll in a private chat if there is one.
+pub fn t_vote_reminder(lang: Lang, title: &str, voters: usize, deadline: Option<&str>, vote_link: Option<&str>) -> String {
+    let deadline = deadline
+        .map(|deadline| tr_with(lang, "reminder.vote_deadline", &[("deadline", deadline)]))
+        .unwrap_or_default();
+    let mut text = tr_with(lang, "reminder.vote_body", &[("title", title), ("voters", &voters.to_string()), ("deadline", &deadline)]);
+    if let Some(link) = vote_link {
+        text.push_str(&vote_link_line(lang, link));
+    }
+    text
+}
+
+/// `/deadline` success
+pub fn t_deadline_set(lang: Lang, title: &str, deadline: &str) -> String {
+    tr_with(lang, "deadline.success", &[("title", title), ("deadline", deadline)])
+}
+
+/// Heading of the reminder sent `offset_minutes` before a session, `None` for intervals without a translation
+pub fn t_reminder_heading(lang: Lang, offset_minutes: i64) -> Option<&'static str> {
+    let key = match offset_minutes {
+        20_160 => "reminder.two_weeks",
+        10_080 => "reminder.one_week",
+        4_320 => "reminder.three_days",
+        0 => "reminder.game_day",
+        120 => "reminder.two_hours",
+        _ => return None,
+    };
+    Some(tr(lang, key))
+}
+
+/// Heading of a long-range reminder going out `days` days before the session
+pub fn t_long_range_heading(lang: Lang, days: i64) -> String {
+    tr_with(lang, "reminder.long_range", &[("days", &days.to_string())])
+}
+
+/// The parts of a reminder message, each already escaped for MarkdownV2
+#[derive(Debug, Clone, Copy)]
+pub struct ReminderParts<'a> {
+    /// The reminder's heading, e.g. from [`t_reminder_heading`]
+    pub heading: &'a str,
+    /// The session title
+    pub title: &'a str,
+    /// When the session starts
+    pub when: &'a str,
+    /// How long it lasts
+    pub duration: &'a str,
+    /// The session's notes, if any
+    pub notes: Option<&'a str>,
+    /// Who has which duty, e.g. "🏠 Hosting: alice", if the group rotates any
+    pub duties: Option<&'a str>,
+    /// Who is coming, e.g. from [`t_participants`]
+    pub participants: &'a str,
+    /// The session's display reference, e.g. "#12"
+    pub reference: &'a str,
+    /// The full session ID
+    pub session_id: &'a str,
+    /// Deep link that opens the poll in a private chat, if the bot's username is known
+    pub vote_link: Option<&'a str>,
+}
+
+/// A session reminder in MarkdownV2; game day reminders ask players to check in
+pub fn t_reminder(lang: Lang, parts: &ReminderParts, game_day: bool) -> String {
+    let mut notes = parts.notes
+        .map(|notes| tr_with(lang, "reminder.notes", &[("notes", notes)]))
+        .unwrap_or_default();
+    // Duty names are the group's own, so the line needs no translation
+    if let Some(duties) = parts.duties {
+        notes.push_str(&format!("{duties}\n"));
+    }
+    let mut text = tr_with(lang, "reminder.body", &[
+        ("heading", parts.heading),
+        ("title", parts.title),
+        ("when", parts.when),
+        ("duration", parts.duration),
+        ("notes", &notes),
+        ("participants", parts.participants),
+        ("reference", parts.reference),
+        ("id", parts.session_id),
+    ]);
+    if game_day {
+        text.push_str(tr(lang, "reminder.check_in"));
+    }
+    if let Some(link) = parts.vote_link {
+        text.push_str(&vote_link_line(lang, link));
+    }
+    text
+}
+
+/// The line offering to change a vote through `link`, escaped for a MarkdownV2 link target
+fn vote_link_line(lang: Lang, link: &str) -> String {
+    let link = link.replace('\\', "\\\\").replace(')', "\\)");
+    tr_with(lang, "reminder.vote_link", &[("link", &link)])
+}
+
+/// The participants line of a reminder: up to five names, or three and a count of the rest
+pub fn t_participants(lang: Lang, names: &[&str]) -> String {
+    match names.len() {
+        0 => tr(lang, "reminder.no_participants").to_string(),
+        1..=5 => names.join(", "),
+        n => tr_with(lang, "reminder.and_others", &[("names", &names[..3].join(", ")), ("count", &(n - 3).to_string())]),
+    }
+}
+
+/// The "What's new" message: each release's version as a heading over its changes, newest first
+pub fn t_whats_new(lang: Lang, releases: &[(&str, Vec<&str>)]) -> String {
+    let mut text = String::new();
+    for (version, changes) in releases {
+        text.push_str(&tr_with(lang, "whats_new.heading", &[("version", version)]));
+        for change in changes {
+            text.push_str(&format!("\n• {change}"));
+        }
+        text.push_str("\n\n");
+    }
+    text.push_str(tr(lang, "whats_new.opt_out"));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<String> {
+        text.split('{').skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_every_key_is_translated() {
+        for (key, templates) in MESSAGES {
+            for (lang, template) in Lang::ALL.iter().zip(templates) {
+                assert!(!template.is_empty(), "'{key}' is empty in {}", lang.code());
+            }
+            // Placeholders must be the same in every language
+            for template in &templates[1..] {
+                assert_eq!(placeholders(template), placeholders(templates[0]), "placeholders of '{key}' differ");
+            }
+        }
+    }
+
+    #[test]
+    fn test_typed_messages_fill_every_placeholder() {
+        let parts = ReminderParts {
+            heading: "Game Day",
+            title: "Curse of Strahd",
+            when: "Friday 19:00",
+            duration: "4h",
+            notes: Some("At Sam's"),
+            duties: Some("🏠 Hosting: alice"),
+            participants: "alice, bob",
+            reference: "\\#3",
+            session_id: "abc",
+            vote_link: Some("https://t.me/bot?start=vote_abc"),
+        };
+        let counts = VoteCounts { yes: 3, no: 1, maybe: 2 };
+        for lang in Lang::ALL {
+            let rendered = [
+                t_help(lang, "/list"),
+                t_welcome(lang).to_string(),
+                t_validation_error(lang, "Bad", "Try again"),
+                t_session_created(lang, "Raid", 3, "#3", "abcdef12", true),
+                t_auto_title(lang, NaiveDate::from_ymd_opt(2024, 8, 12).unwrap()),
+                t_auto_titled(lang, "Game night", "#3"),
+                t_session_confirmed(lang, "Raid", "Friday 19:00", 4, &[t_confirm_score(lang, 4.5, 1.0, 0.5), t_notes(lang, "Bring dice")]),
+                t_confirm_breakdown(lang, &counts, None),
+                t_autoconfirmed(lang, 4),
+                t_confirm_breakdown(lang, &counts, Some(("Saturday 19:00", &counts))),
+                t_maybe_swing(lang, "Sunday 19:00", &MaybeSwing { option: 1, converts: 2, maybes: 2, yes: 5, winner_yes: 4 }),
+                t_session_cancelled(lang, "Raid"),
+                t_deadline_set(lang, "Raid", "Friday 19:00"),
+                t_reminder(lang, &parts, true),
+                t_vote_reminder(lang, "Raid", 3, Some("Friday 19:00"), parts.vote_link),
+                t_participants(lang, &["a", "b", "c", "d", "e", "f"]),
+                t_participants(lang, &[]),
+                t_whats_new(lang, &[("0.2.0", vec!["Roll calls"])]),
+            ];
+            for text in rendered {
+                assert!(!text.is_empty());
+                assert!(placeholders(&text).is_empty(), "unfilled placeholder in {}: {text}", lang.code());
+            }
+            for interval in crate::services::reminder::REMINDER_INTERVALS {
+                assert!(t_reminder_heading(lang, interval.offset_minutes()).is_some(), "{}", interval.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_title_names_the_week() {
+        let monday = NaiveDate::from_ymd_opt(2024, 8, 12).unwrap();
+        assert_eq!(t_auto_title(Lang::En, monday), "Game night — week of 12 Aug");
+        assert_eq!(t_auto_title(Lang::Fr, monday), "Soirée jeu — semaine du 12 août");
+        assert_eq!(t_auto_title(Lang::De, monday), "Spieleabend — Woche vom 12. Aug.");
+    }
+
+    #[test]
+    fn test_tr_falls_back_to_english() {
+        assert_eq!(tr(Lang::Sv, "feedback.suggestion"), "Förslag");
+        assert_eq!(tr(normalize("FR"), "language.name"), "Français");
+        assert_eq!(tr(normalize("nl"), "feedback.suggestion"), "Suggestion");
+        assert_eq!(tr(Lang::Sv, "no.such.key"), "no.such.key");
+    }
+
+    #[test]
+    fn test_tr_with_fills_placeholders() {
+        assert_eq!(tr_with(Lang::Sv, "language.changed", &[("language", "Svenska")]), "Språket är nu Svenska");
+        assert_eq!(tr_with(Lang::En, "language.unsupported", &[("code", "nl")]), "Unsupported language 'nl'");
+        assert_eq!(t_participants(Lang::De, &["a", "b", "c", "d", "e", "f"]), "a, b, c und 3 weitere");
+        assert!(t_session_cancelled(Lang::En, "{title} {count}").starts_with("Session '{title} {count}' cancelled"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" SV "), Lang::Sv);
+        assert_eq!(normalize("klingon"), Lang::En);
+        assert!(is_supported("fr"));
+        assert!(!is_supported("FR"));
+        assert_eq!(Lang::ALL.map(Lang::code), SUPPORTED_LANGUAGES);
+        assert_eq!(Lang::De.name(), "Deutsch");
+    }
+}
@@ -1,85 +1,54 @@
-use tracing::{error, info, warn, debug};
+//! Logging helpers with structured fields
+//!
+//! The helpers record what they're told as fields (`command`, `user_id`, `chat_id`, ...) next to
+//! a fixed message, so JSON output can be filtered on them in a log aggregator.
+
+use tracing::{debug, error, info, warn};
 
 /// Logs command start with consistent format
 #[allow(dead_code)]
 pub fn log_command_start(command: &str, user: &str, user_id: i64, chat_id: i64, details: Option<&str>) {
-    match details {
-        Some(d) => info!(
-            "CMD_START: {} by {}({}) in chat {} - {}",
-            command, user, user_id, chat_id, d
-        ),
-        None => info!(
-            "CMD_START: {} by {}({}) in chat {}",
-            command, user, user_id, chat_id
-        ),
-    }
+    info!(command, user, user_id, chat_id, details, "Command started");
 }
 
 /// Logs command completion with consistent format
 #[allow(dead_code)]
 pub fn log_command_success(command: &str, user: &str, user_id: i64, chat_id: i64, details: Option<&str>) {
-    match details {
-        Some(d) => info!(
-            "CMD_SUCCESS: {} by {}({}) in chat {} - {}",
-            command, user, user_id, chat_id, d
-        ),
-        None => info!(
-            "CMD_SUCCESS: {} by {}({}) in chat {}",
-            command, user, user_id, chat_id
-        ),
-    }
+    info!(command, user, user_id, chat_id, details, "Command succeeded");
 }
 
 /// Logs command errors with consistent format
 #[allow(dead_code)]
 pub fn log_command_error(command: &str, user: &str, user_id: i64, chat_id: i64, error: &str) {
-    error!(
-        "CMD_ERROR: {} by {}({}) in chat {} - {}",
-        command, user, user_id, chat_id, error
-    );
+    error!(command, user, user_id, chat_id, error, "Command failed");
 }
 
 /// Logs validation errors with consistent format
 #[allow(dead_code)]
 pub fn log_validation_error(command: &str, field: &str, value: &str, error: &str, user: &str, user_id: i64, chat_id: i64) {
-    warn!(
-        "VALIDATION_ERROR: {} - {} field '{}' invalid: {} - user {}({}) in chat {}",
-        command, field, value, error, user, user_id, chat_id
-    );
+    warn!(command, field, value, error, user, user_id, chat_id, "Invalid command argument");
 }
 
 /// Logs database operations with consistent format
 #[allow(dead_code)]
 pub fn log_database_operation(operation: &str, table: &str, details: Option<&str>) {
-    match details {
-        Some(d) => debug!("DB_OP: {} on {} - {}", operation, table, d),
-        None => debug!("DB_OP: {} on {}", operation, table),
-    }
+    debug!(operation, table, details, "Database operation");
 }
 
 /// Logs database errors with consistent format
 #[allow(dead_code)]
 pub fn log_database_error(operation: &str, table: &str, error: &str, details: Option<&str>) {
-    match details {
-        Some(d) => error!("DB_ERROR: {} on {} failed: {} - {}", operation, table, error, d),
-        None => error!("DB_ERROR: {} on {} failed: {}", operation, table, error),
-    }
+    error!(operation, table, error, details, "Database operation failed");
 }
 
 /// Logs timeout events with consistent format
 #[allow(dead_code)]
 pub fn log_timeout(operation: &str, duration_secs: u64, details: Option<&str>) {
-    match details {
-        Some(d) => warn!("TIMEOUT: {} after {}s - {}", operation, duration_secs, d),
-        None => warn!("TIMEOUT: {} after {}s", operation, duration_secs),
-    }
+    warn!(operation, duration_secs, details, "Timed out");
 }
 
 /// Logs system events with consistent format
 #[allow(dead_code)]
 pub fn log_system_event(event: &str, details: Option<&str>) {
-    match details {
-        Some(d) => info!("SYSTEM: {} - {}", event, d),
-        None => info!("SYSTEM: {}", event),
-    }
-}
\ No newline at end of file
+    info!(event, details, "System event");
+}
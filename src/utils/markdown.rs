@@ -42,6 +42,55 @@ pub fn escape_markdown(text: &str) -> String {
         .replace('!', "\\!")
 }
 
+/// Wraps text in a MarkdownV2 code block, escaping the only characters special inside one
+///
+/// ```
+/// use dnd_scheduler_bot::utils::markdown::code_block;
+///
+/// assert_eq!(code_block("a`b\\c"), "```\na\\`b\\\\c\n```");
+/// ```
+pub fn code_block(text: &str) -> String {
+    format!("```\n{}\n```", text.replace('\\', "\\\\").replace('`', "\\`"))
+}
+
+/// Turn MarkdownV2 back into plain text: escapes are undone and formatting markers dropped
+///
+/// For resending a message Telegram couldn't parse, so it's forgiving: unbalanced markers go
+/// too, and links keep their address in brackets after the text.
+///
+/// ```
+/// use dnd_scheduler_bot::utils::markdown::strip_markdown;
+///
+/// assert_eq!(strip_markdown("**Lost Mines** \\(#12\\)"), "Lost Mines (#12)");
+/// assert_eq!(strip_markdown("[poll](https://t.me/c/1/2)"), "poll (https://t.me/c/1/2)");
+/// ```
+pub fn strip_markdown(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => plain.push(chars.next().unwrap_or(c)),
+            '*' | '_' | '~' | '|' | '`' | '[' => {}
+            // A link's address is taken as it is, markers and all
+            ']' if chars.peek() == Some(&'(') => {
+                chars.next();
+                plain.push_str(" (");
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => plain.push(chars.next().unwrap_or(c)),
+                        ')' => break,
+                        _ => plain.push(c),
+                    }
+                }
+                plain.push(')');
+            }
+            ']' => {}
+            _ => plain.push(c),
+        }
+    }
+    plain
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,4 +134,19 @@ mod tests {
         let expected = "Session: \\*D&D Night\\* \\[2024\\-01\\-01\\] \\(5 players\\) \\- Confirmed\\!";
         assert_eq!(escape_markdown(input), expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_strip_markdown() {
+        assert_eq!(strip_markdown(""), "");
+        assert_eq!(strip_markdown("plain text"), "plain text");
+        // Whatever escape_markdown does is undone
+        let input = "Session: *D&D Night* [2024-01-01] (5 players) - Confirmed!";
+        assert_eq!(strip_markdown(&escape_markdown(input)), input);
+        assert_eq!(strip_markdown("🎲 **Weekly Game**\n\n__Underlined__ ~gone~ ||spoiler||"), "🎲 Weekly Game\n\nUnderlined gone spoiler");
+        assert_eq!(strip_markdown("```\nYes  \\` \\\\ \n```"), "\nYes  ` \\ \n");
+        assert_eq!(strip_markdown("[Vote privately](https://t.me/bot?start=vote_1)"), "Vote privately (https://t.me/bot?start=vote_1)");
+        // The usual slip: an unescaped dot or dash stays, a stray marker goes
+        assert_eq!(strip_markdown("Starts 19.00 - bring *dice"), "Starts 19.00 - bring dice");
+        assert_eq!(strip_markdown("trailing \\"), "trailing \\");
+    }
+}
@@ -3,3 +3,9 @@ pub mod markdown;
 pub mod validation;
 pub mod feedback;
 pub mod logging;
+pub mod csv;
+pub mod i18n;
+pub mod outbound;
+pub mod results_table;
+pub mod changelog;
+pub mod heatmap;
@@ -0,0 +1,203 @@
+//! Recording outgoing messages in the `outbound_messages` log, and retrying failed sends
+
+use std::future::{Future, IntoFuture};
+use std::time::Duration;
+use teloxide::payloads::{EditMessageText, SendMessage};
+use teloxide::prelude::*;
+use teloxide::requests::{HasPayload, JsonRequest, Output};
+use teloxide::{ApiError, RequestError};
+use crate::database::models::OutboundMessage;
+use crate::utils::markdown::strip_markdown;
+
+/// Await `send` and record the attempt as a `kind` message to `chat_id`
+///
+/// The send's result is returned unchanged: if the log insert fails, that's only a warning.
+pub async fn logged_send<T>(
+    pool: &sqlx::SqlitePool,
+    chat_id: ChatId,
+    kind: &str,
+    session_id: Option<&str>,
+    send: impl IntoFuture<Output = ResponseResult<T>>,
+) -> ResponseResult<T> {
+    let result = send.await;
+    let error = result.as_ref().err().map(|e| e.to_string());
+    if let Err(e) = OutboundMessage::record(pool, chat_id.0, kind, session_id, error.as_deref()).await {
+        tracing::warn!("Failed to log {} message to chat {}: {}", kind, chat_id, e);
+    }
+    result
+}
+
+/// A message request with formatted text, which [`send_with_plain_fallback`] can resend plainly
+pub trait FormattedRequest: Clone {
+    /// The text as it would be sent
+    fn text(&self) -> &str;
+    /// The same request with `text` in place of the current one and no formatting
+    fn into_plain(self, text: String) -> Self;
+}
+
+impl FormattedRequest for JsonRequest<SendMessage> {
+    fn text(&self) -> &str {
+        &self.payload_ref().text
+    }
+
+    fn into_plain(mut self, text: String) -> Self {
+        let payload = self.payload_mut();
+        payload.text = text;
+        payload.parse_mode = None;
+        payload.entities = None;
+        self
+    }
+}
+
+impl FormattedRequest for JsonRequest<EditMessageText> {
+    fn text(&self) -> &str {
+        &self.payload_ref().text
+    }
+
+    fn into_plain(mut self, text: String) -> Self {
+        let payload = self.payload_mut();
+        payload.text = text;
+        payload.parse_mode = None;
+        payload.entities = None;
+        self
+    }
+}
+
+/// Whether Telegram rejected a message because its formatting doesn't parse
+///
+/// Telegram says where it gave up after the usual text, which teloxide then doesn't recognise.
+pub fn is_entity_parse_error(error: &RequestError) -> bool {
+    match error {
+        RequestError::Api(ApiError::CantParseEntities) => true,
+        RequestError::Api(ApiError::Unknown(message)) => message.starts_with("Bad Request: can't parse entities"),
+        _ => false,
+    }
+}
+
+/// Send `request` with `send`, and if Telegram can't parse its formatting, once more as plain
+/// text with the formatting stripped
+///
+/// A formatting slip costs the message its looks rather than the whole message. The rejected
+/// text is logged, to find the slip by.
+pub async fn send_with_plain_fallback<R, T, F, Fut>(request: R, send: F) -> ResponseResult<T>
+where
+    R: FormattedRequest,
+    F: Fn(R) -> Fut,
+    Fut: IntoFuture<Output = ResponseResult<T>>,
+{
+    match send(request.clone()).await {
+        Err(e) if is_entity_parse_error(&e) => {
+            tracing::warn!("Telegram couldn't parse a message ({}), sending it as plain text instead: {:?}", e, request.text());
+            let plain = strip_markdown(request.text());
+            send(request.into_plain(plain)).await
+        }
+        result => result,
+    }
+}
+
+/// [`send_with_plain_fallback`] for a request to Telegram
+pub async fn plain_fallback<R>(request: R) -> ResponseResult<Output<R>>
+where
+    R: FormattedRequest + Request<Err = RequestError>,
+{
+    send_with_plain_fallback(request, |request| request.send()).await
+}
+
+/// What a failed send says about trying again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendErrorKind {
+    /// Worth retrying soon: a network hiccup, or a rate limit saying how long to wait
+    Transient {
+        /// How long Telegram asked us to wait, if it did
+        retry_after: Option<Duration>,
+    },
+    /// The chat can't be posted in at all until someone fixes it, e.g. the bot was removed
+    Permanent,
+    /// Anything else, e.g. a rejected message; retrying right away won't help
+    Other,
+}
+
+/// Sort a send error into a [`SendErrorKind`]
+pub fn classify_send_error(error: &RequestError) -> SendErrorKind {
+    match error {
+        RequestError::RetryAfter(wait) => SendErrorKind::Transient { retry_after: Some(*wait) },
+        RequestError::Network(_) | RequestError::Io(_) => SendErrorKind::Transient { retry_after: None },
+        // The group became a supergroup; its old chat ID is gone for good
+        RequestError::MigrateToChatId(_) => SendErrorKind::Permanent,
+        RequestError::Api(
+            ApiError::BotKicked
+            | ApiError::BotKickedFromSupergroup
+            | ApiError::BotBlocked
+            | ApiError::ChatNotFound
+            | ApiError::GroupDeactivated
+            | ApiError::UserDeactivated
+            | ApiError::CantInitiateConversation
+            | ApiError::NotEnoughRightsToPostMessages,
+        ) => SendErrorKind::Permanent,
+        _ => SendErrorKind::Other,
+    }
+}
+
+/// How often and how patiently a send is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Sends in total, the first one included
+    pub max_attempts: u32,
+    /// Wait before the first retry; it doubles with each further one
+    pub base_delay: Duration,
+    /// Longest single wait; a rate limit asking for more is left to a later run
+    pub max_delay: Duration,
+}
+
+/// Three sends, waiting 2 and then 4 seconds, and at most 30 seconds for a rate limit
+pub const DEFAULT_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_attempts: 3,
+    base_delay: Duration::from_secs(2),
+    max_delay: Duration::from_secs(30),
+};
+
+impl RetryPolicy {
+    /// How long to wait before send number `attempt + 1` after a transient failure, `None` to give up
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        match retry_after {
+            Some(wait) => (wait <= self.max_delay).then_some(wait),
+            None => Some(self.base_delay.saturating_mul(1 << (attempt - 1).min(16)).min(self.max_delay)),
+        }
+    }
+}
+
+/// Call `send` until it succeeds, retrying transient failures as `policy` allows
+///
+/// `sleep` does the waiting, so tests can skip it. Returns the last result and how many sends it took.
+pub async fn send_with_retry<T, F, Fut, S, SleepFut>(
+    policy: &RetryPolicy,
+    mut send: F,
+    mut sleep: S,
+) -> (ResponseResult<T>, u32)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ResponseResult<T>>,
+    S: FnMut(Duration) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+{
+    let mut attempt = 1;
+    loop {
+        let error = match send().await {
+            Ok(value) => return (Ok(value), attempt),
+            Err(error) => error,
+        };
+        let delay = match classify_send_error(&error) {
+            SendErrorKind::Transient { retry_after } => policy.delay(attempt, retry_after),
+            SendErrorKind::Permanent | SendErrorKind::Other => None,
+        };
+        let Some(delay) = delay else {
+            return (Err(error), attempt);
+        };
+        tracing::debug!("Send attempt {} failed ({}), retrying in {:?}", attempt, error, delay);
+        sleep(delay).await;
+        attempt += 1;
+    }
+}
@@ -0,0 +1,300 @@
+//! Vote tables for a session's results, as plain text for a monospace code block
+//!
+//! Up to [`MAX_TABLE_VOTERS`] voters get a grid with one row per option and one column per voter.
+//! Beyond that the columns wouldn't fit a phone screen, so each option lists its voters by answer
+//! instead. Columns are aligned by display width, counting CJK characters and emoji as two.
+
+use crate::database::models::{Response, SessionOption};
+use chrono::{DateTime, Utc};
+
+/// Most voters shown as columns; more switch to the per-option layout
+pub const MAX_TABLE_VOTERS: usize = 8;
+
+/// Widest a voter's name is shown, in columns, before it's cut short with "…"
+pub const MAX_NAME_WIDTH: usize = 10;
+
+/// Longest table, in characters, so it fits a Telegram message with room to spare
+pub const MAX_RESULTS_LENGTH: usize = 3500;
+
+/// Render the votes on `options` as a table, naming voters by their response's username
+///
+/// Voters are in the order they first voted. Options that are confirmed are marked with "*",
+/// and the times of those in `vetoed`, which a required player said no to, are struck through.
+pub fn render_results_table(options: &[SessionOption], responses: &[Response], vetoed: &[String]) -> String {
+    let mut voters: Vec<(i64, String)> = Vec::new();
+    for response in responses {
+        if !voters.iter().any(|(user_id, _)| *user_id == response.user_id) {
+            let name = response.username.clone().unwrap_or_else(|| format!("User {}", response.user_id));
+            voters.push((response.user_id, truncate_to_width(&name, MAX_NAME_WIDTH)));
+        }
+    }
+    if voters.is_empty() {
+        return "No votes yet".to_string();
+    }
+
+    let labels: Vec<String> = options.iter().enumerate()
+        .map(|(i, option)| option_label(i, option, vetoed.contains(&option.id)))
+        .collect();
+    let mark = |option: &SessionOption, user_id: i64| {
+        responses.iter()
+            .find(|r| r.option_id == option.id && r.user_id == user_id)
+            .map_or("·", |r| match r.response.as_str() {
+                "yes" => "✓",
+                "no" => "✗",
+                _ => "?",
+            })
+    };
+
+    let mut lines = Vec::new();
+    if voters.len() <= MAX_TABLE_VOTERS {
+        let label_width = labels.iter().map(|label| display_width(label)).max().unwrap_or(0);
+        let mut header = pad(String::new(), label_width);
+        for (_, name) in &voters {
+            header.push(' ');
+            header.push_str(name);
+        }
+        lines.push(header.trim_end().to_string());
+        for (option, label) in options.iter().zip(&labels) {
+            let mut row = pad(label.clone(), label_width);
+            for (user_id, name) in &voters {
+                row.push(' ');
+                row.push_str(&pad(mark(option, *user_id).to_string(), display_width(name)));
+            }
+            lines.push(row.trim_end().to_string());
+        }
+    } else {
+        for (option, label) in options.iter().zip(&labels) {
+            lines.push(label.clone());
+            for (symbol, answer) in [("✓", "yes"), ("?", "maybe"), ("✗", "no")] {
+                let names: Vec<&str> = voters.iter()
+                    .filter(|(user_id, _)| mark(option, *user_id) == symbol)
+                    .map(|(_, name)| name.as_str())
+                    .collect();
+                if !names.is_empty() {
+                    lines.push(format!("    {symbol} {} {answer}: {}", names.len(), names.join(", ")));
+                }
+            }
+        }
+    }
+
+    lines.push(String::new());
+    let confirmed = options.iter().any(|option| option.confirmed);
+    let struck = options.iter().any(|option| vetoed.contains(&option.id));
+    lines.push(format!(
+        "✓ yes  ✗ no  ? maybe  · no vote{}{}",
+        if confirmed { "  * confirmed" } else { "" },
+        if struck { format!("  {} a required player said no", strike_through("struck")) } else { String::new() },
+    ));
+    fit_length(lines)
+}
+
+/// " 1  Fri 12.06 19:00", or " 1* ..." for a confirmed option, with the time struck through if `struck`
+fn option_label(index: usize, option: &SessionOption, struck: bool) -> String {
+    let mut when = DateTime::parse_from_rfc3339(&option.datetime)
+        .map(|dt| dt.with_timezone(&Utc).format("%a %d.%m %H:%M").to_string())
+        .unwrap_or_else(|_| option.datetime.clone());
+    if struck {
+        when = strike_through(&when);
+    }
+    format!("{:>2}{} {when}", index + 1, if option.confirmed { "*" } else { " " })
+}
+
+/// `text` with a combining long stroke over each character, which takes no columns of its own
+fn strike_through(text: &str) -> String {
+    text.chars().flat_map(|c| [c, '\u{0336}']).collect()
+}
+
+/// Join `lines`, leaving out whole lines from the end to stay under `MAX_RESULTS_LENGTH`
+fn fit_length(lines: Vec<String>) -> String {
+    let total = lines.len();
+    let mut text = String::new();
+    for (i, line) in lines.into_iter().enumerate() {
+        if text.chars().count() + line.chars().count() + 1 > MAX_RESULTS_LENGTH - 40 {
+            text.push_str(&format!("… {} more lines not shown", total - i));
+            return text;
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text.trim_end().to_string()
+}
+
+/// `text` padded with spaces to `width` columns
+fn pad(mut text: String, width: usize) -> String {
+    let missing = width.saturating_sub(display_width(&text));
+    text.push_str(&" ".repeat(missing));
+    text
+}
+
+/// `text` cut to at most `width` columns, ending in "…" if anything was cut
+fn truncate_to_width(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let c_width = char_width(c);
+        if used + c_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        used += c_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// How many monospace columns `text` takes
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Columns a character takes: none for combining marks and joiners, two for wide ones
+fn char_width(c: char) -> usize {
+    match u32::from(c) {
+        0x0300..=0x036F | 0x200B..=0x200F | 0x20D0..=0x20FF | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(id: &str, datetime: &str, confirmed: bool) -> SessionOption {
+        SessionOption {
+            id: id.to_string(),
+            session_id: "s".to_string(),
+            datetime: datetime.to_string(),
+            duration: 240,
+            confirmed,
+        }
+    }
+
+    fn vote(option_id: &str, user_id: i64, username: &str, response: &str) -> Response {
+        Response {
+            id: format!("{option_id}-{user_id}"),
+            session_id: "s".to_string(),
+            option_id: option_id.to_string(),
+            user_id,
+            username: Some(username.to_string()),
+            response: response.to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn two_options() -> Vec<SessionOption> {
+        vec![
+            option("a", "2026-06-12T19:00:00+00:00", false),
+            option("b", "2026-06-13T14:30:00+00:00", true),
+        ]
+    }
+
+    #[test]
+    fn test_grid_aligns_marks_under_names() {
+        let responses = [
+            vote("a", 1, "alice", "yes"),
+            vote("b", 1, "alice", "no"),
+            vote("a", 2, "bo", "maybe"),
+        ];
+        let table = render_results_table(&two_options(), &responses, &[]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], format!("{}alice bo", " ".repeat(20)));
+        assert_eq!(lines[1], " 1  Fri 12.06 19:00 ✓     ?");
+        assert_eq!(lines[2], " 2* Sat 13.06 14:30 ✗     ·");
+        assert_eq!(lines[4], "✓ yes  ✗ no  ? maybe  · no vote  * confirmed");
+    }
+
+    #[test]
+    fn test_unicode_names_keep_columns_aligned() {
+        let responses = [
+            vote("a", 1, "雪菜", "yes"),
+            vote("a", 2, "José", "no"),
+            vote("a", 3, "🐉dragon", "maybe"),
+        ];
+        let table = render_results_table(&two_options(), &responses, &[]);
+        let lines: Vec<&str> = table.lines().take(3).collect();
+        // Each voter's mark starts in the column where their name does
+        let header = lines[0];
+        for (name, mark) in [("雪菜", "✓"), ("José", "✗"), ("🐉dragon", "?")] {
+            let column = display_width(&header[..header.find(name).unwrap()]);
+            let row = lines[1];
+            let mark_at = display_width(&row[..row.find(mark).unwrap()]);
+            assert_eq!(column, mark_at, "{name}: {table}");
+        }
+    }
+
+    #[test]
+    fn test_long_names_are_truncated() {
+        let responses = [vote("a", 1, "bartholomew_the_bard", "yes")];
+        let table = render_results_table(&two_options(), &responses, &[]);
+        assert!(table.lines().next().unwrap().ends_with("bartholom…"), "{table}");
+        assert_eq!(truncate_to_width("天気予報士です", 7), "天気予…");
+        assert_eq!(display_width(&truncate_to_width("天気予報士です", 7)), 7);
+        assert_eq!(truncate_to_width("short", MAX_NAME_WIDTH), "short");
+    }
+
+    #[test]
+    fn test_many_voters_switch_to_compact_layout() {
+        let mut responses = Vec::new();
+        for user_id in 1..=(MAX_TABLE_VOTERS as i64 + 1) {
+            let answer = if user_id % 3 == 0 { "no" } else { "yes" };
+            responses.push(vote("a", user_id, &format!("player{user_id}"), answer));
+        }
+        let table = render_results_table(&two_options(), &responses, &[]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], " 1  Fri 12.06 19:00");
+        assert_eq!(lines[1], "    ✓ 6 yes: player1, player2, player4, player5, player7, player8");
+        assert_eq!(lines[2], "    ✗ 3 no: player3, player6, player9");
+        assert_eq!(lines[3], " 2* Sat 13.06 14:30");
+        assert_eq!(lines[4], "");
+    }
+
+    #[test]
+    fn test_huge_results_stay_under_the_limit() {
+        let options: Vec<SessionOption> = (0..10)
+            .map(|i| option(&format!("o{i}"), &format!("2026-06-{:02}T19:00:00+00:00", i + 10), false))
+            .collect();
+        let mut responses = Vec::new();
+        for user_id in 1..=200 {
+            for option in &options {
+                responses.push(vote(&option.id, user_id, &format!("adventurer{user_id}"), "yes"));
+            }
+        }
+        let table = render_results_table(&options, &responses, &[]);
+        assert!(table.chars().count() <= MAX_RESULTS_LENGTH, "{}", table.chars().count());
+        assert!(table.ends_with("more lines not shown"), "{table}");
+    }
+
+    #[test]
+    fn test_vetoed_options_are_struck_through() {
+        let responses = [vote("a", 1, "alice", "no"), vote("b", 1, "alice", "yes")];
+        let table = render_results_table(&two_options(), &responses, &["a".to_string()]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[1], format!(" 1  {} ✗", strike_through("Fri 12.06 19:00")));
+        assert_eq!(display_width(lines[1]), display_width(lines[2]), "{table}");
+        assert!(lines[4].ends_with("a required player said no"), "{table}");
+        assert!(!render_results_table(&two_options(), &responses, &[]).contains("required"));
+    }
+
+    #[test]
+    fn test_no_votes() {
+        assert_eq!(render_results_table(&two_options(), &[], &[]), "No votes yet");
+    }
+}
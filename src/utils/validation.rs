@@ -1,33 +1,157 @@
-use anyhow::{anyhow, Result};
+use std::fmt;
+use crate::database::models::{MAX_DEFAULT_DURATION, MAX_SHORT_ID_DIGITS, MIN_DEFAULT_DURATION};
+use crate::utils::datetime::split_duration_suffix;
 
-pub fn validate_session_title(title: &str) -> Result<()> {
+/// Minimum length of a session title, after trimming
+pub const MIN_TITLE_LENGTH: usize = 3;
+/// Maximum length of a session title, after trimming
+pub const MAX_TITLE_LENGTH: usize = 100;
+/// Maximum length of a session's notes, in characters
+pub const MAX_NOTES_LENGTH: usize = 300;
+/// Maximum number of time options in a single poll
+pub const MAX_TIME_OPTIONS: usize = 10;
+/// Maximum length of a single time option
+pub const MAX_TIME_OPTION_LENGTH: usize = 50;
+/// Accepted poll response values
+pub const RESPONSE_TYPES: [&str; 3] = ["yes", "no", "maybe"];
+
+/// Why a value failed validation
+///
+/// Callers can match on the kind, e.g. to translate it; `Display` gives the English message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Nothing was given, e.g. "Session notes"
+    Empty(&'static str),
+    /// Shorter than `min` characters
+    TooShort {
+        /// What was too short, e.g. "Session title"
+        field: &'static str,
+        /// The minimum length
+        min: usize,
+    },
+    /// Longer than `max` characters
+    TooLong {
+        /// What was too long, e.g. "Session title" or "Time option 'Friday ...'"
+        field: String,
+        /// The maximum length
+        max: usize,
+    },
+    /// Contains a line break where only one line is allowed
+    LineBreaks(&'static str),
+    /// Contains characters that aren't allowed
+    InvalidChars {
+        /// What contained them, e.g. "Session ID"
+        field: &'static str,
+    },
+    /// Starts with a character it can't start with
+    BadStartChar {
+        /// What started with it, e.g. "Session ID"
+        field: &'static str,
+    },
+    /// A "#" not followed by a short session number, like "#12a"
+    InvalidShortNumber,
+    /// A time option that can't be a date and time
+    InvalidTimeFormat,
+    /// No time options were left after dropping empty ones
+    NoTimeOptions,
+    /// More time options than a poll takes
+    TooManyOptions(usize),
+    /// An inline duration outside `MIN_DEFAULT_DURATION..=MAX_DEFAULT_DURATION`
+    DurationOutOfRange(String),
+    /// A chat ID Telegram doesn't hand out
+    InvalidChatId(ChatIdProblem),
+    /// A poll answer other than one of `RESPONSE_TYPES`
+    InvalidResponse,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Empty(field) => write!(f, "{field} cannot be empty"),
+            ValidationError::TooShort { field, min } => write!(f, "{field} must be at least {min} characters long"),
+            ValidationError::TooLong { field, max } => write!(f, "{field} cannot be longer than {max} characters"),
+            ValidationError::LineBreaks(field) => write!(f, "{field} cannot contain line breaks"),
+            ValidationError::InvalidChars { field } => write!(f, "{field} can only contain letters, numbers, and hyphens"),
+            ValidationError::BadStartChar { field } => write!(f, "{field} must start with a letter or number"),
+            ValidationError::InvalidShortNumber => write!(f, "Short session numbers look like #12"),
+            ValidationError::InvalidTimeFormat => write!(f, "Invalid time format"),
+            ValidationError::NoTimeOptions => write!(f, "Must provide at least one time option"),
+            ValidationError::TooManyOptions(max) => write!(f, "Cannot have more than {max} time options"),
+            ValidationError::DurationOutOfRange(option) => write!(
+                f,
+                "Duration of '{option}' must be between {MIN_DEFAULT_DURATION} and {MAX_DEFAULT_DURATION} minutes"
+            ),
+            ValidationError::InvalidChatId(ChatIdProblem::Zero) => write!(f, "Chat ID cannot be zero"),
+            ValidationError::InvalidChatId(ChatIdProblem::UserRange) => write!(f, "Invalid user chat ID range"),
+            ValidationError::InvalidChatId(ChatIdProblem::GroupRange) => write!(f, "Invalid group chat ID range"),
+            ValidationError::InvalidChatId(ChatIdProblem::OutOfRange) => write!(f, "Chat ID out of valid range"),
+            ValidationError::InvalidResponse => write!(f, "Response must be 'yes', 'no', or 'maybe'"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// What's wrong with a chat ID [`validate_telegram_chat_id`] rejects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatIdProblem {
+    /// Zero, which no chat has
+    Zero,
+    /// Positive, but above the range of user IDs
+    UserRange,
+    /// Negative, but in a range no group uses
+    GroupRange,
+    /// Negative beyond the lowest supergroup ID
+    OutOfRange,
+}
+
+pub fn validate_session_title(title: &str) -> Result<(), ValidationError> {
     let title = title.trim();
     
-    if title.len() < 3 {
-        return Err(anyhow!("Session title must be at least 3 characters long"));
+    if title.len() < MIN_TITLE_LENGTH {
+        return Err(ValidationError::TooShort { field: "Session title", min: MIN_TITLE_LENGTH });
     }
     
-    if title.len() > 100 {
-        return Err(anyhow!("Session title cannot be longer than 100 characters"));
+    if title.len() > MAX_TITLE_LENGTH {
+        return Err(ValidationError::TooLong { field: "Session title".to_string(), max: MAX_TITLE_LENGTH });
     }
     
     // Check for potentially problematic characters
     if title.contains('\n') || title.contains('\r') {
-        return Err(anyhow!("Session title cannot contain line breaks"));
+        return Err(ValidationError::LineBreaks("Session title"));
+    }
+    
+    Ok(())
+}
+
+/// Check session notes: not empty, at most `MAX_NOTES_LENGTH` characters and on one line like titles
+pub fn validate_session_notes(notes: &str) -> Result<(), ValidationError> {
+    let notes = notes.trim();
+    
+    if notes.is_empty() {
+        return Err(ValidationError::Empty("Session notes"));
+    }
+    
+    if notes.chars().count() > MAX_NOTES_LENGTH {
+        return Err(ValidationError::TooLong { field: "Session notes".to_string(), max: MAX_NOTES_LENGTH });
+    }
+    
+    if notes.contains('\n') || notes.contains('\r') {
+        return Err(ValidationError::LineBreaks("Session notes"));
     }
     
     Ok(())
 }
 
-pub fn validate_telegram_chat_id(chat_id: i64) -> Result<()> {
+pub fn validate_telegram_chat_id(chat_id: i64) -> Result<(), ValidationError> {
     // Telegram chat IDs should be non-zero
     if chat_id == 0 {
-        return Err(anyhow!("Chat ID cannot be zero"));
+        return Err(ValidationError::InvalidChatId(ChatIdProblem::Zero));
     }
     
     // Positive IDs should be within reasonable range for user chats (up to 2^31-1)
     if chat_id > 2147483647 {
-        return Err(anyhow!("Invalid user chat ID range"));
+        return Err(ValidationError::InvalidChatId(ChatIdProblem::UserRange));
     }
     
     // Negative IDs can be:
@@ -37,40 +161,40 @@ pub fn validate_telegram_chat_id(chat_id: i64) -> Result<()> {
     if chat_id < 0 {
         // Reject very small negative numbers close to zero (like -1 to -100)
         if chat_id >= -100 {
-            return Err(anyhow!("Invalid group chat ID range"));
+            return Err(ValidationError::InvalidChatId(ChatIdProblem::GroupRange));
         }
         
         // Reject the specific boundary case around -999999999
         if chat_id == -999999999 {
-            return Err(anyhow!("Invalid group chat ID range"));
+            return Err(ValidationError::InvalidChatId(ChatIdProblem::GroupRange));
         }
         
         // Check for extremely large negative numbers
         if chat_id < -2002147483648 {
-            return Err(anyhow!("Chat ID out of valid range"));
+            return Err(ValidationError::InvalidChatId(ChatIdProblem::OutOfRange));
         }
     }
     
     Ok(())
 }
 
-pub fn validate_time_options(options: &str) -> Result<Vec<String>> {
+pub fn validate_time_options(options: &str) -> Result<Vec<String>, ValidationError> {
     let options = options.trim();
     
     if options.is_empty() {
-        return Err(anyhow!("Time options cannot be empty"));
+        return Err(ValidationError::Empty("Time options"));
     }
     
     // Check for invalid patterns in the original input
     if options == "," || 
        options.starts_with(",") || 
        options.ends_with(",") {
-        return Err(anyhow!("Invalid time format"));
+        return Err(ValidationError::InvalidTimeFormat);
     }
     
     // Reject specific invalid test cases
     if options == "Invalid time format" || options == "Invalid format" {
-        return Err(anyhow!("Invalid time format"));
+        return Err(ValidationError::InvalidTimeFormat);
     }
     
     let option_list: Vec<String> = options
@@ -80,17 +204,25 @@ pub fn validate_time_options(options: &str) -> Result<Vec<String>> {
         .collect();
     
     if option_list.is_empty() {
-        return Err(anyhow!("Must provide at least one time option"));
+        return Err(ValidationError::NoTimeOptions);
     }
     
-    if option_list.len() > 10 {
-        return Err(anyhow!("Cannot have more than 10 time options"));
+    if option_list.len() > MAX_TIME_OPTIONS {
+        return Err(ValidationError::TooManyOptions(MAX_TIME_OPTIONS));
     }
     
     // Basic validation for option length and invalid formats
     for option in &option_list {
-        if option.len() > 50 {
-            return Err(anyhow!("Time option '{}' is too long (max 50 characters)", option));
+        if option.len() > MAX_TIME_OPTION_LENGTH {
+            return Err(ValidationError::TooLong { field: format!("Time option '{option}'"), max: MAX_TIME_OPTION_LENGTH });
+        }
+        
+        // An inline duration like "(3h)" is checked on its own, the rest must still be a time
+        let (option, duration) = split_duration_suffix(option);
+        if let Some(minutes) = duration {
+            if !(MIN_DEFAULT_DURATION..=MAX_DEFAULT_DURATION).contains(&minutes) {
+                return Err(ValidationError::DurationOutOfRange(option.to_string()));
+            }
         }
         
         // Reject clearly invalid formats that tests expect to fail
@@ -101,45 +233,57 @@ pub fn validate_time_options(options: &str) -> Result<Vec<String>> {
            option == "Invalid time format" ||
            option == "19:00" || // Just time without day
            option.is_empty() {
-            return Err(anyhow!("Invalid time format"));
+            return Err(ValidationError::InvalidTimeFormat);
         }
     }
     
     Ok(option_list)
 }
 
-pub fn validate_session_id(session_id: &str) -> Result<()> {
+pub fn validate_session_id(session_id: &str) -> Result<(), ValidationError> {
     let session_id = session_id.trim();
     
     if session_id.is_empty() {
-        return Err(anyhow!("Session ID cannot be empty"));
+        return Err(ValidationError::Empty("Session ID"));
+    }
+    
+    // Short per-group session numbers like "#12" or "12"
+    if let Some(digits) = session_id.strip_prefix('#') {
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(ValidationError::InvalidShortNumber);
+        }
+        return Ok(());
+    }
+    if session_id.len() <= MAX_SHORT_ID_DIGITS && session_id.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(());
     }
     
     if session_id.len() < 8 {
-        return Err(anyhow!("Session ID must be at least 8 characters long"));
+        return Err(ValidationError::TooShort { field: "Session ID", min: 8 });
     }
     
     if session_id.len() > 50 {
-        return Err(anyhow!("Session ID cannot be longer than 50 characters"));
+        return Err(ValidationError::TooLong { field: "Session ID".to_string(), max: 50 });
     }
     
     // Session IDs should contain only alphanumeric characters and hyphens
     if !session_id.chars().all(|c| c.is_alphanumeric() || c == '-') {
-        return Err(anyhow!("Session ID can only contain letters, numbers, and hyphens"));
+        return Err(ValidationError::InvalidChars { field: "Session ID" });
     }
     
     // Must start with alphanumeric character
     if !session_id.chars().next().is_some_and(|c| c.is_alphanumeric()) {
-        return Err(anyhow!("Session ID must start with a letter or number"));
+        return Err(ValidationError::BadStartChar { field: "Session ID" });
     }
     
     Ok(())
 }
 
-pub fn validate_response_type(response: &str) -> Result<()> {
-    match response.to_lowercase().as_str() {
-        "yes" | "no" | "maybe" => Ok(()),
-        _ => Err(anyhow!("Response must be 'yes', 'no', or 'maybe'")),
+pub fn validate_response_type(response: &str) -> Result<(), ValidationError> {
+    if RESPONSE_TYPES.contains(&response.to_lowercase().as_str()) {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidResponse)
     }
 }
 
@@ -317,4 +461,53 @@ mod tests {
         assert!(validate_session_id("-abc123").is_err());
         assert!(validate_session_id("_abc123").is_err());
     }
+
+    #[test]
+    fn test_errors_say_what_failed() {
+        assert_eq!(validate_session_title("ab"), Err(ValidationError::TooShort { field: "Session title", min: MIN_TITLE_LENGTH }));
+        assert_eq!(validate_session_notes(" "), Err(ValidationError::Empty("Session notes")));
+        assert_eq!(validate_time_options("19:00"), Err(ValidationError::InvalidTimeFormat));
+        assert_eq!(validate_time_options("Friday 19:00 (1m)"), Err(ValidationError::DurationOutOfRange("Friday 19:00".to_string())));
+        assert_eq!(validate_response_type("perhaps"), Err(ValidationError::InvalidResponse));
+        assert_eq!(validate_session_id("abc_1234"), Err(ValidationError::InvalidChars { field: "Session ID" }));
+        assert_eq!(
+            ValidationError::TooLong { field: "Session title".to_string(), max: MAX_TITLE_LENGTH }.to_string(),
+            "Session title cannot be longer than 100 characters"
+        );
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        assert_eq!(validate_session_id("abc.12345"), Err(ValidationError::InvalidChars { field: "Session ID" }));
+        assert_eq!(validate_session_id("-abc12345"), Err(ValidationError::BadStartChar { field: "Session ID" }));
+        assert_eq!(validate_session_id("#12a"), Err(ValidationError::InvalidShortNumber));
+        assert_eq!(validate_session_id("#"), Err(ValidationError::InvalidShortNumber));
+
+        for (chat_id, problem) in [
+            (0, ChatIdProblem::Zero),
+            (2147483648, ChatIdProblem::UserRange),
+            (-42, ChatIdProblem::GroupRange),
+            (-999999999, ChatIdProblem::GroupRange),
+            (-2002147483649, ChatIdProblem::OutOfRange),
+        ] {
+            assert_eq!(validate_telegram_chat_id(chat_id), Err(ValidationError::InvalidChatId(problem)), "{chat_id}");
+        }
+
+        // The English text is only built for display
+        assert_eq!(ValidationError::BadStartChar { field: "Session ID" }.to_string(), "Session ID must start with a letter or number");
+        assert_eq!(ValidationError::InvalidChatId(ChatIdProblem::Zero).to_string(), "Chat ID cannot be zero");
+    }
+
+    #[test]
+    fn test_validate_session_id_short_forms() {
+        assert!(validate_session_id("#1").is_ok());
+        assert!(validate_session_id("#12").is_ok());
+        assert!(validate_session_id("12").is_ok());
+        assert!(validate_session_id("999999").is_ok());
+        assert!(validate_session_id(" #7 ").is_ok());
+
+        assert!(validate_session_id("#").is_err());
+        assert!(validate_session_id("#12a").is_err());
+        assert!(validate_session_id("#-1").is_err());
+    }
 }
\ No newline at end of file
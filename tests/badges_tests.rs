@@ -0,0 +1,105 @@
+#![allow(clippy::unwrap_used)]
+
+use anyhow::Result;
+use dnd_scheduler_bot::bot::badges::*;
+use dnd_scheduler_bot::database::models::*;
+use dnd_scheduler_bot::testing::*;
+
+fn award(badge: Badge, user_id: Option<i64>, count: i64) -> Award {
+    Award { badge, user_id, count }
+}
+
+#[test]
+fn test_full_house_needs_everyone_to_say_yes() {
+    // Everyone yes, one "no", too few voters, everyone yes again
+    let tallies = BadgeTallies {
+        confirmed_turnout: vec![(4, 4), (4, 3), (2, 2), (3, 3)],
+        ..BadgeTallies::default()
+    };
+    assert_eq!(full_houses(&tallies.confirmed_turnout), 2);
+    assert_eq!(award_badges(&tallies), vec![award(Badge::FullHouse, None, 2)]);
+
+    let tallies = BadgeTallies { confirmed_turnout: vec![(5, 4)], ..BadgeTallies::default() };
+    assert!(award_badges(&tallies).is_empty());
+}
+
+#[test]
+fn test_iron_dm_needs_ten_confirmed_sessions() {
+    let tallies = BadgeTallies {
+        confirmed_by_creator: vec![(1, IRON_DM_SESSIONS - 1), (2, IRON_DM_SESSIONS), (3, 14)],
+        ..BadgeTallies::default()
+    };
+    assert_eq!(award_badges(&tallies), vec![
+        award(Badge::IronDm, Some(3), 14),
+        award(Badge::IronDm, Some(2), IRON_DM_SESSIONS),
+    ]);
+}
+
+#[test]
+fn test_early_bird_needs_five_first_votes() {
+    let tallies = BadgeTallies {
+        first_votes: vec![(7, 5), (8, 4), (6, 5)],
+        ..BadgeTallies::default()
+    };
+    // Ties go to the lower user ID
+    assert_eq!(award_badges(&tallies), vec![
+        award(Badge::EarlyBird, Some(6), EARLY_BIRD_POLLS),
+        award(Badge::EarlyBird, Some(7), EARLY_BIRD_POLLS),
+    ]);
+}
+
+#[test]
+fn test_badges_come_in_a_fixed_order() {
+    let tallies = BadgeTallies {
+        confirmed_turnout: vec![(3, 3)],
+        confirmed_by_creator: vec![(1, 10)],
+        first_votes: vec![(2, 6)],
+    };
+    let badges: Vec<Badge> = award_badges(&tallies).iter().map(|award| award.badge).collect();
+    assert_eq!(badges, [Badge::FullHouse, Badge::IronDm, Badge::EarlyBird]);
+    assert!(award_badges(&BadgeTallies::default()).is_empty());
+}
+
+#[tokio::test]
+async fn test_badge_tallies_from_the_database() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let other_group = GroupBuilder::new().chat_id(-2).create(&db.pool).await?;
+
+    // Two confirmed sessions by the DM: everyone said yes to the first, one voter said no to the second
+    let mut confirmed = Vec::new();
+    for _ in 0..2 {
+        let session = SessionBuilder::new(group.id).created_by(100).status("confirmed").create(&db.pool).await?;
+        let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+        let other = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+        sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?").bind(&option.id).execute(&db.pool).await?;
+        confirmed.push((option, other));
+    }
+    for user_id in [1, 2, 3] {
+        ResponseBuilder::new(&confirmed[0].0).user(user_id, None).create(&db.pool).await?;
+    }
+    // A "yes" on another option doesn't make up for a "no" on the confirmed one
+    ResponseBuilder::new(&confirmed[1].0).user(2, None).create(&db.pool).await?;
+    ResponseBuilder::new(&confirmed[1].0).user(1, None).create(&db.pool).await?;
+    ResponseBuilder::new(&confirmed[1].1).user(3, None).create(&db.pool).await?;
+    ResponseBuilder::new(&confirmed[1].0).user(3, None).response("no").create(&db.pool).await?;
+
+    // An active poll counts for first votes, but not for confirmed sessions
+    let active = SessionBuilder::new(group.id).created_by(100).create(&db.pool).await?;
+    let active_option = SessionOptionBuilder::new(&active.id).create(&db.pool).await?;
+    ResponseBuilder::new(&active_option).user(3, None).create(&db.pool).await?;
+
+    // Another group's sessions stay out of it
+    let elsewhere = SessionBuilder::new(other_group.id).created_by(100).status("confirmed").create(&db.pool).await?;
+    let elsewhere_option = SessionOptionBuilder::new(&elsewhere.id).create(&db.pool).await?;
+    sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?").bind(&elsewhere_option.id).execute(&db.pool).await?;
+    ResponseBuilder::new(&elsewhere_option).user(1, None).create(&db.pool).await?;
+
+    let mut turnout = Response::confirmed_turnout(&db.pool, group.id).await?;
+    turnout.sort();
+    assert_eq!(turnout, vec![(3, 2), (3, 3)]);
+    assert_eq!(Session::count_confirmed_by_creator(&db.pool, group.id).await?, vec![(100, 2)]);
+    assert_eq!(Response::count_first_votes(&db.pool, group.id).await?, vec![(1, 1), (2, 1), (3, 1)]);
+
+    Ok(())
+}
@@ -1,69 +1,39 @@
 use dnd_scheduler_bot::{
-    database::{
-        connection::DatabaseManager,
-        models::{Group, Session, SessionOption, Response},
-    },
+    database::models::{Session, SessionOption, Response, SESSION_COLUMNS},
+    testing::{check_stored_session, test_db, GroupBuilder, ResponseBuilder, SessionBuilder, SessionOptionBuilder},
 };
-use tempfile::TempDir;
 use chrono::{Utc, Duration};
 
-/// Helper function to create a test database
-async fn create_test_db() -> (DatabaseManager, TempDir) {
-    let temp_dir = TempDir::new().expect("Failed to create temp directory");
-    let db_path = temp_dir.path().join("test.db");
-    let db_url = format!("sqlite://{}", db_path.display());
-    
-    let db = DatabaseManager::new(&db_url)
-        .await
-        .expect("Failed to create test database");
-    
-    // Run migrations
-    sqlx::migrate!("./migrations")
-        .run(&db.pool)
-        .await
-        .expect("Failed to run migrations");
-    
-    (db, temp_dir)
-}
-
 // Helper functions removed to focus on database integration testing
 
 #[tokio::test]
 async fn test_schedule_command_database_operations() {
-    let (db, _temp_dir) = create_test_db().await;
+    let (db, _temp_dir) = test_db().await.expect("Failed to create test database");
     let chat_id = -1001234567890_i64;
     let user_id = 123456789_u64;
     
     // Create a test group in the database
-    let group = Group::create(&db.pool, chat_id)
+    let group = GroupBuilder::new().chat_id(chat_id)
+        .create(&db.pool)
         .await
         .expect("Failed to create test group");
     
     // Test the database operations that would be performed by schedule command
-    let session = Session::create(
-        &db.pool,
-        group.id,
-        "Test Session".to_string(),
-        user_id as i64,
-    ).await.expect("Failed to create session");
+    let session = SessionBuilder::new(group.id)
+        .created_by(user_id as i64)
+        .create(&db.pool)
+        .await
+        .expect("Failed to create session");
     
     // Create session options
-    let datetime1 = Utc::now() + Duration::days(1);
-    let datetime2 = Utc::now() + Duration::days(7);
-    
-    let _option1 = SessionOption::create(
-        &db.pool,
-        session.id.clone(),
-        datetime1,
-        180, // 3 hours
-    ).await.expect("Failed to create option 1");
-    
-    let _option2 = SessionOption::create(
-        &db.pool,
-        session.id.clone(),
-        datetime2,
-        180,
-    ).await.expect("Failed to create option 2");
+    for days in [1, 7] {
+        SessionOptionBuilder::new(&session.id)
+            .at(Utc::now() + Duration::days(days))
+            .duration(180) // 3 hours
+            .create(&db.pool)
+            .await
+            .expect("Failed to create option");
+    }
     
     // Verify session was created
     let found_session = Session::find_by_id(&db.pool, &session.id)
@@ -73,61 +43,55 @@ async fn test_schedule_command_database_operations() {
     
     assert_eq!(found_session.title, "Test Session");
     assert_eq!(found_session.status, "active");
+    check_stored_session(&db.pool, &session.id).await.expect("Session invariants violated");
 }
 
 #[tokio::test]
 async fn test_list_command_database_operations() {
-    let (db, _temp_dir) = create_test_db().await;
+    let (db, _temp_dir) = test_db().await.expect("Failed to create test database");
     let chat_id = -1001234567890_i64;
     let user_id = 123456789_u64;
     
     // Create test group and session
-    let group = Group::create(&db.pool, chat_id)
+    let group = GroupBuilder::new().chat_id(chat_id)
+        .create(&db.pool)
         .await
         .expect("Failed to create test group");
     
-    let session = Session::create(
-        &db.pool,
-        group.id,
-        "Test Session".to_string(),
-        user_id as i64,
-    ).await.expect("Failed to create session");
+    let session = SessionBuilder::new(group.id)
+        .created_by(user_id as i64)
+        .create(&db.pool)
+        .await
+        .expect("Failed to create session");
     
     // Create session options
-    let datetime = Utc::now() + Duration::days(1);
-    let option = SessionOption::create(
-        &db.pool,
-        session.id.clone(),
-        datetime,
-        180,
-    ).await.expect("Failed to create session option");
+    let option = SessionOptionBuilder::new(&session.id)
+        .duration(180)
+        .create(&db.pool)
+        .await
+        .expect("Failed to create session option");
     
     // Create some responses
-    let _response1 = Response::upsert(
-        &db.pool,
-        session.id.clone(),
-        option.id.clone(),
-        user_id as i64,
-        Some("testuser".to_string()),
-        "yes".to_string(),
-    ).await.expect("Failed to create response");
+    ResponseBuilder::new(&option)
+        .user(user_id as i64, Some("testuser"))
+        .create(&db.pool)
+        .await
+        .expect("Failed to create response");
     
-    let _response2 = Response::upsert(
-        &db.pool,
-        session.id.clone(),
-        option.id.clone(),
-        (user_id + 1) as i64,
-        Some("testuser2".to_string()),
-        "maybe".to_string(),
-    ).await.expect("Failed to create response 2");
+    ResponseBuilder::new(&option)
+        .user((user_id + 1) as i64, Some("testuser2"))
+        .response("maybe")
+        .create(&db.pool)
+        .await
+        .expect("Failed to create response 2");
     
     // Test the database queries used by list command
-    let sessions = sqlx::query_as::<_, Session>(
-        "SELECT id, group_id, title, message_id, status, deadline, created_by, created_at 
+    let sessions = sqlx::query_as::<_, Session>(&format!(
+        "SELECT {SESSION_COLUMNS} 
          FROM sessions 
          WHERE group_id = ? AND status IN ('active', 'confirmed') 
          ORDER BY created_at DESC"
-    )
+    ))
     .bind(group.id)
     .fetch_all(&db.pool)
     .await
@@ -155,29 +119,27 @@ async fn test_list_command_database_operations() {
 
 #[tokio::test]
 async fn test_callback_handler_integration() {
-    let (db, _temp_dir) = create_test_db().await;
+    let (db, _temp_dir) = test_db().await.expect("Failed to create test database");
     let chat_id = -1001234567890_i64;
     let user_id = 123456789_u64;
     
     // Create test data
-    let group = Group::create(&db.pool, chat_id)
+    let group = GroupBuilder::new().chat_id(chat_id)
+        .create(&db.pool)
         .await
         .expect("Failed to create test group");
     
-    let session = Session::create(
-        &db.pool,
-        group.id,
-        "Test Session".to_string(),
-        user_id as i64,
-    ).await.expect("Failed to create session");
+    let session = SessionBuilder::new(group.id)
+        .created_by(user_id as i64)
+        .create(&db.pool)
+        .await
+        .expect("Failed to create session");
     
-    let datetime = Utc::now() + Duration::days(1);
-    let option = SessionOption::create(
-        &db.pool,
-        session.id.clone(),
-        datetime,
-        180,
-    ).await.expect("Failed to create session option");
+    let option = SessionOptionBuilder::new(&session.id)
+        .duration(180)
+        .create(&db.pool)
+        .await
+        .expect("Failed to create session option");
     
     // Test callback data parsing and response creation
     let callback_data = format!("{}:{}:yes", session.id, option.id);
@@ -227,21 +189,21 @@ async fn test_callback_handler_integration() {
 
 #[tokio::test]
 async fn test_session_management_integration() {
-    let (db, _temp_dir) = create_test_db().await;
+    let (db, _temp_dir) = test_db().await.expect("Failed to create test database");
     let chat_id = -1001234567890_i64;
     let user_id = 123456789_u64;
     
     // Create test data
-    let group = Group::create(&db.pool, chat_id)
+    let group = GroupBuilder::new().chat_id(chat_id)
+        .create(&db.pool)
         .await
         .expect("Failed to create test group");
     
-    let session = Session::create(
-        &db.pool,
-        group.id,
-        "Test Session".to_string(),
-        user_id as i64,
-    ).await.expect("Failed to create session");
+    let session = SessionBuilder::new(group.id)
+        .created_by(user_id as i64)
+        .create(&db.pool)
+        .await
+        .expect("Failed to create session");
     
     // Test session confirmation
     sqlx::query!(
@@ -299,33 +261,33 @@ async fn test_session_management_integration() {
 
 #[tokio::test]
 async fn test_batch_query_performance() {
-    let (db, _temp_dir) = create_test_db().await;
+    let (db, _temp_dir) = test_db().await.expect("Failed to create test database");
     let chat_id = -1001234567890_i64;
     let user_id = 123456789_u64;
     
     // Create test group
-    let group = Group::create(&db.pool, chat_id)
+    let group = GroupBuilder::new().chat_id(chat_id)
+        .create(&db.pool)
         .await
         .expect("Failed to create test group");
     
     // Create multiple sessions for batch testing
     let mut session_ids = Vec::new();
     for i in 0..5 {
-        let session = Session::create(
-            &db.pool,
-            group.id,
-            format!("Test Session {}", i + 1),
-            user_id as i64,
-        ).await.expect("Failed to create session");
+        let session = SessionBuilder::new(group.id)
+            .title(format!("Test Session {}", i + 1))
+            .created_by(user_id as i64)
+            .create(&db.pool)
+            .await
+            .expect("Failed to create session");
         
         // Create options for each session
-        let datetime = Utc::now() + Duration::days(i + 1);
-        let _option = SessionOption::create(
-            &db.pool,
-            session.id.clone(),
-            datetime,
-            180,
-        ).await.expect("Failed to create session option");
+        SessionOptionBuilder::new(&session.id)
+            .at(Utc::now() + Duration::days(i + 1))
+            .duration(180)
+            .create(&db.pool)
+            .await
+            .expect("Failed to create session option");
         
         session_ids.push(session.id);
     }
@@ -363,50 +325,46 @@ async fn test_batch_query_performance() {
 
 #[tokio::test]
 async fn test_database_indexes_usage() {
-    let (db, _temp_dir) = create_test_db().await;
+    let (db, _temp_dir) = test_db().await.expect("Failed to create test database");
     let chat_id = -1001234567890_i64;
     let user_id = 123456789_u64;
     
     // Create test data to exercise the indexes
-    let group = Group::create(&db.pool, chat_id)
+    let group = GroupBuilder::new().chat_id(chat_id)
+        .create(&db.pool)
         .await
         .expect("Failed to create test group");
     
-    let session = Session::create(
-        &db.pool,
-        group.id,
-        "Test Session".to_string(),
-        user_id as i64,
-    ).await.expect("Failed to create session");
+    let session = SessionBuilder::new(group.id)
+        .created_by(user_id as i64)
+        .create(&db.pool)
+        .await
+        .expect("Failed to create session");
     
-    let datetime = Utc::now() + Duration::days(1);
-    let option = SessionOption::create(
-        &db.pool,
-        session.id.clone(),
-        datetime,
-        180,
-    ).await.expect("Failed to create session option");
+    let option = SessionOptionBuilder::new(&session.id)
+        .duration(180)
+        .create(&db.pool)
+        .await
+        .expect("Failed to create session option");
     
     // Create multiple responses to test indexes
     for i in 0..10 {
-        let _response = Response::upsert(
-            &db.pool,
-            session.id.clone(),
-            option.id.clone(),
-            (user_id + i) as i64,
-            Some(format!("user{}", i)),
-            if i % 3 == 0 { "yes" } else if i % 3 == 1 { "no" } else { "maybe" }.to_string(),
-        ).await.expect("Failed to create response");
+        ResponseBuilder::new(&option)
+            .user((user_id + i) as i64, Some(&format!("user{}", i)))
+            .response(if i % 3 == 0 { "yes" } else if i % 3 == 1 { "no" } else { "maybe" })
+            .create(&db.pool)
+            .await
+            .expect("Failed to create response");
     }
     
     // Test queries that should benefit from indexes
     
     // 1. Find sessions by group_id (uses idx_sessions_group_id)
-    let sessions = sqlx::query_as::<_, Session>(
-        "SELECT id, group_id, title, message_id, status, deadline, created_by, created_at 
+    let sessions = sqlx::query_as::<_, Session>(&format!(
+        "SELECT {SESSION_COLUMNS} 
          FROM sessions 
          WHERE group_id = ?"
-    )
+    ))
     .bind(group.id)
     .fetch_all(&db.pool)
     .await
@@ -429,7 +387,7 @@ async fn test_database_indexes_usage() {
     
     // 3. Find responses by session_id (uses idx_responses_session_id)
     let responses = sqlx::query_as::<_, Response>(
-        "SELECT id, session_id, option_id, user_id, username, response, created_at 
+        "SELECT id, session_id, option_id, user_id, username, response, created_at, updated_at 
          FROM responses 
          WHERE session_id = ?"
     )
@@ -442,7 +400,7 @@ async fn test_database_indexes_usage() {
     
     // 4. Find responses by user_id (uses idx_responses_user_id)
     let user_responses = sqlx::query_as::<_, Response>(
-        "SELECT id, session_id, option_id, user_id, username, response, created_at 
+        "SELECT id, session_id, option_id, user_id, username, response, created_at, updated_at 
          FROM responses 
          WHERE user_id = ?"
     )
@@ -454,11 +412,11 @@ async fn test_database_indexes_usage() {
     assert_eq!(user_responses.len(), 1);
     
     // 5. Composite query for sessions by group_id and status (uses idx_sessions_group_status)
-    let active_sessions = sqlx::query_as::<_, Session>(
-        "SELECT id, group_id, title, message_id, status, deadline, created_by, created_at 
+    let active_sessions = sqlx::query_as::<_, Session>(&format!(
+        "SELECT {SESSION_COLUMNS} 
          FROM sessions 
          WHERE group_id = ? AND status = ?"
-    )
+    ))
     .bind(group.id)
     .bind("active")
     .fetch_all(&db.pool)
@@ -1,3 +1,5 @@
+#![allow(clippy::unwrap_used)]
+
 use dnd_scheduler_bot::utils::markdown::escape_markdown;
 
 #[test]
@@ -36,4 +38,420 @@ fn test_escape_markdown_multiple_chars() {
     let input = "Test_*[all]-(special)+chars!";
     let expected = "Test\\_\\*\\[all\\]\\-\\(special\\)\\+chars\\!";
     assert_eq!(escape_markdown(input), expected);
-}
\ No newline at end of file
+}
+mod list_pagination {
+    use dnd_scheduler_bot::bot::commands::list::{
+        list_page_keyboard, load_list_page, paginate, render_list_page, LIST_PAGE_SIZE, TELEGRAM_MESSAGE_LIMIT,
+    };
+    use chrono::{Duration, Utc};
+    use dnd_scheduler_bot::database::models::{NotesByOption, Response, Session, SessionOption};
+    use dnd_scheduler_bot::testing::*;
+    use teloxide::types::InlineKeyboardButtonKind;
+
+    fn callback_data(page: usize, total_pages: usize) -> Vec<String> {
+        list_page_keyboard(page, total_pages)
+            .map(|keyboard| {
+                keyboard.inline_keyboard.concat().into_iter()
+                    .filter_map(|button| match button.kind {
+                        InlineKeyboardButtonKind::CallbackData(data) => Some(data),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_paginate_splits_sessions_into_pages() {
+        let total = LIST_PAGE_SIZE * 2 + 1;
+        assert_eq!(paginate(total, 0), (0, 3, 0..LIST_PAGE_SIZE));
+        assert_eq!(paginate(total, 2), (2, 3, LIST_PAGE_SIZE * 2..total));
+    }
+
+    #[test]
+    fn test_paginate_clamps_out_of_range_pages() {
+        assert_eq!(paginate(LIST_PAGE_SIZE + 1, 9), (1, 2, LIST_PAGE_SIZE..LIST_PAGE_SIZE + 1));
+        assert_eq!(paginate(0, 3), (0, 1, 0..0));
+    }
+
+    #[test]
+    fn test_list_page_keyboard_buttons() {
+        assert!(list_page_keyboard(0, 1).is_none());
+        assert_eq!(callback_data(0, 3), vec!["list:page:1"]);
+        assert_eq!(callback_data(1, 3), vec!["list:page:0", "list:page:2"]);
+        assert_eq!(callback_data(2, 3), vec!["list:page:1"]);
+    }
+
+    async fn seed_sessions(
+        db: &dnd_scheduler_bot::database::connection::DatabaseManager,
+        sessions: usize,
+        options_per_session: usize,
+    ) -> (Vec<Session>, Vec<SessionOption>, Vec<Response>) {
+        let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+        let mut all_sessions = Vec::new();
+        let mut all_options = Vec::new();
+        let mut all_responses = Vec::new();
+        for i in 0..sessions {
+            let session = SessionBuilder::new(group.id)
+                .title(format!("The Very Long Campaign Arc Number {i}: Return to the Sunless Citadel"))
+                .create(&db.pool)
+                .await
+                .unwrap();
+            for _ in 0..options_per_session {
+                let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await.unwrap();
+                all_responses.push(ResponseBuilder::new(&option).user(1, Some("alice")).create(&db.pool).await.unwrap());
+                all_options.push(option);
+            }
+            all_sessions.push(session);
+        }
+        (all_sessions, all_options, all_responses)
+    }
+
+    #[tokio::test]
+    async fn test_list_pages_fit_in_a_message() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let (sessions, options, responses) = seed_sessions(&db, 8, 6).await;
+        
+        let (_, total_pages, _) = paginate(sessions.len(), 0);
+        assert_eq!(total_pages, 2);
+        for page in 0..total_pages {
+            let (page, total_pages, range) = paginate(sessions.len(), page);
+            let (text, keyboard) = render_list_page(&sessions[range.clone()], &options, &responses, &NotesByOption::default(), 0, &[], page, total_pages, sessions.len());
+            
+            assert!(text.encode_utf16().count() <= TELEGRAM_MESSAGE_LIMIT);
+            assert!(!text.contains("more options"));
+            for session in &sessions[range] {
+                assert!(text.contains(&session.id));
+            }
+            assert_eq!(keyboard.inline_keyboard.concat().len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_page_shows_escaped_notes() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let (mut sessions, options, responses) = seed_sessions(&db, 1, 1).await;
+        sessions[0].notes = Some("Roll20 (link pinned)".to_string());
+        
+        let (text, _keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.contains("📝 Notes: Roll20 \\(link pinned\\)\n"));
+    }
+
+    #[tokio::test]
+    async fn test_list_page_shows_last_vote() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let (mut sessions, options, mut responses) = seed_sessions(&db, 1, 1).await;
+        
+        let (text, _keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ Last vote just now\n"));
+        
+        // A vote first cast long ago but changed just now is recent activity
+        let long_ago = (Utc::now() - Duration::days(8) - Duration::hours(1)).to_rfc3339();
+        sessions[0].created_at = long_ago.clone();
+        responses[0].created_at = long_ago.clone();
+        let (text, _keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ Last vote just now\n"));
+        
+        responses[0].updated_at = long_ago;
+        let (text, _keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ ⚠️ Last vote 1 week ago\n"));
+        
+        // Confirmed sessions don't need votes anymore
+        sessions[0].status = "confirmed".to_string();
+        let (text, _keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ Last vote 1 week ago\n"));
+    }
+
+    #[tokio::test]
+    async fn test_list_page_shows_how_many_members_voted() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let (mut sessions, options, responses) = seed_sessions(&db, 1, 1).await;
+        
+        let (text, _keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 6, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ 1/6 voted, last vote just now\n"), "{text}");
+        
+        // Without a member count the share is left out
+        let (text, _keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ Last vote just now\n"), "{text}");
+        
+        sessions[0].status = "confirmed".to_string();
+        let (text, _keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 6, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ Last vote just now\n"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn test_list_page_flags_polls_without_votes() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let (mut sessions, options, _responses) = seed_sessions(&db, 1, 1).await;
+        
+        let (text, _keyboard) = render_list_page(&sessions, &options, &[], &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ No votes yet\n"));
+        
+        sessions[0].created_at = (Utc::now() - Duration::days(10) - Duration::hours(1)).to_rfc3339();
+        let (text, _keyboard) = render_list_page(&sessions, &options, &[], &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.contains("🗳️ ⚠️ No votes yet, created 1 week ago\n"));
+    }
+
+    #[tokio::test]
+    async fn test_stale_polls_without_votes_sort_last() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+        let stale = SessionBuilder::new(group.id).title("Stale").create(&db.pool).await.unwrap();
+        let voted = SessionBuilder::new(group.id).title("Old but voted").create(&db.pool).await.unwrap();
+        SessionBuilder::new(group.id).title("Fresh").create(&db.pool).await.unwrap();
+        
+        let long_ago = (Utc::now() - Duration::days(10)).to_rfc3339();
+        for session in [&stale, &voted] {
+            sqlx::query("UPDATE sessions SET created_at = ? WHERE id = ?")
+                .bind(&long_ago)
+                .bind(&session.id)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+        let option = SessionOptionBuilder::new(&voted.id).create(&db.pool).await.unwrap();
+        ResponseBuilder::new(&option).user(1, Some("alice")).create(&db.pool).await.unwrap();
+        
+        let titles: Vec<String> = load_list_page(&db.pool, group.id, 0).await.unwrap().0
+            .into_iter()
+            .map(|session| session.title)
+            .collect();
+        assert_eq!(titles, vec!["Fresh", "Old but voted", "Stale"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_page_truncates_oversized_session() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let (sessions, options, responses) = seed_sessions(&db, 1, 120).await;
+        
+        let (text, keyboard) = render_list_page(&sessions, &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(text.encode_utf16().count() <= TELEGRAM_MESSAGE_LIMIT);
+        assert!(text.contains("  1\\. "));
+        assert!(!text.contains("  120\\. "));
+        assert!(text.contains("more options, see the poll message"));
+        assert!(text.contains("💡 **Commands:**"));
+        assert!(keyboard.inline_keyboard.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_page_crowns_the_leader() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+        let mut session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+        let first = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(3)).create(&db.pool).await.unwrap();
+        let second = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(4)).create(&db.pool).await.unwrap();
+        ResponseBuilder::new(&first).user(1, None).response("yes").create(&db.pool).await.unwrap();
+        ResponseBuilder::new(&second).user(1, None).response("yes").create(&db.pool).await.unwrap();
+        ResponseBuilder::new(&second).user(2, None).response("maybe").create(&db.pool).await.unwrap();
+        let options = SessionOption::find_by_session(&db.pool, &session.id).await.unwrap();
+        let responses = Response::find_by_session(&db.pool, &session.id).await.unwrap();
+        
+        let (text, _keyboard) = render_list_page(std::slice::from_ref(&session), &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert_eq!(text.matches("👑").count(), 1);
+        assert!(text.contains("  2\\. "));
+        assert!(text.lines().any(|line| line.starts_with("  2\\. ") && line.ends_with(" 👑")), "{text}");
+        
+        // Without votes, or once confirmed, nobody is crowned
+        let (text, _keyboard) = render_list_page(std::slice::from_ref(&session), &options, &[], &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(!text.contains("👑"));
+        session.status = "confirmed".to_string();
+        let (text, _keyboard) = render_list_page(std::slice::from_ref(&session), &options, &responses, &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert!(!text.contains("👑"));
+    }
+    
+    #[tokio::test]
+    async fn test_list_groups_options_on_the_same_day() {
+        use chrono::TimeZone;
+        
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+        let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+        for (day, hour) in [(14, 14), (14, 19), (15, 18)] {
+            let at = Utc.with_ymd_and_hms(2030, 12, day, hour, 0, 0).unwrap();
+            SessionOptionBuilder::new(&session.id).at(at).create(&db.pool).await.unwrap();
+        }
+        let options = SessionOption::find_by_session(&db.pool, &session.id).await.unwrap();
+        
+        let (text, _keyboard) = render_list_page(std::slice::from_ref(&session), &options, &[], &NotesByOption::default(), 0, &[], 0, 1, 1);
+        assert_eq!(text.matches("📅 Saturday, 14 December").count(), 1, "{text}");
+        assert!(text.contains("  📅 Saturday, 14 December\n    1\\. 14:00 ⏱️"), "{text}");
+        assert!(text.contains("\n    2\\. 19:00 ⏱️"), "{text}");
+        assert!(text.contains("\n  3\\. Sunday, 15 December at 18:00 ⏱️"), "{text}");
+    }
+}
+
+mod deleted_sessions {
+    use dnd_scheduler_bot::bot::handlers::callback::{callback_handler, seen_callbacks, SeenCallbacks, CALLBACK_DEDUP_CAPACITY, CALLBACK_DEDUP_WINDOW};
+    use dnd_scheduler_bot::bot::poll::VOTE_COOLDOWN;
+    use chrono::{Duration, Utc};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+    use dnd_scheduler_bot::database::models::{PendingOption, PendingSession, Response, Session, SessionOption};
+    use dnd_scheduler_bot::testing::*;
+    use teloxide::types::{CallbackQuery, User, UserId};
+    use teloxide::Bot;
+
+    /// A bot whose requests fail straight away, as its API URL points at a closed local port
+    fn offline_bot() -> Bot {
+        let bot = Bot::new("123:test");
+        let mut url = bot.api_url();
+        url.set_scheme("http").unwrap();
+        url.set_host(Some("127.0.0.1")).unwrap();
+        url.set_port(Some(9)).unwrap();
+        bot.set_api_url(url)
+    }
+
+    /// A press of the button with callback `data`, each with its own query ID
+    fn button_press(data: &str) -> CallbackQuery {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+        CallbackQuery {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed).to_string(),
+            from: User {
+                id: UserId(42),
+                is_bot: false,
+                first_name: "Tester".to_string(),
+                last_name: None,
+                username: Some("tester".to_string()),
+                language_code: None,
+                is_premium: false,
+                added_to_attachment_menu: false,
+            },
+            message: None,
+            inline_message_id: None,
+            chat_instance: "test".to_string(),
+            data: Some(data.to_string()),
+            game_short_name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vote_on_deleted_session_writes_nothing() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+        let live = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+        let live_option = SessionOptionBuilder::new(&live.id).create(&db.pool).await.unwrap();
+        let deleted = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+        let deleted_option = SessionOptionBuilder::new(&deleted.id).create(&db.pool).await.unwrap();
+        assert!(Session::delete(&db.pool, &deleted.id).await.unwrap());
+        
+        // A vote on a live session is saved even though Telegram can't be reached
+        let data = format!("{}:{}:yes", live.id, live_option.id);
+        callback_handler(offline_bot(), button_press(&data), db.clone()).await.unwrap();
+        assert_eq!(Response::find_by_session(&db.pool, &live.id).await.unwrap().len(), 1);
+        
+        // One on the deleted session's old poll isn't
+        let data = format!("{}:{}:yes", deleted.id, deleted_option.id);
+        callback_handler(offline_bot(), button_press(&data), db.clone()).await.unwrap();
+        assert!(Response::find_by_session(&db.pool, &deleted.id).await.unwrap().is_empty());
+        let votes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM responses").fetch_one(&db.pool).await.unwrap();
+        assert_eq!(votes, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_rapid_votes_are_throttled() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+        let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+        let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await.unwrap();
+        
+        // The second tap comes within the cooldown, so the first answer stands
+        for response in ["yes", "no"] {
+            let data = format!("{}:{}:{response}", session.id, option.id);
+            callback_handler(offline_bot(), button_press(&data), db.clone()).await.unwrap();
+        }
+        let responses = Response::find_by_session(&db.pool, &session.id).await.unwrap();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].response, "yes");
+    }
+    
+    #[tokio::test]
+    async fn test_redelivered_vote_is_handled_once() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+        let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+        let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await.unwrap();
+        let press = button_press(&format!("{}:{}:yes", session.id, option.id));
+        
+        callback_handler(offline_bot(), press.clone(), db.clone()).await.unwrap();
+        assert!(!seen_callbacks().first_delivery(&press.id, Instant::now()));
+        
+        // The voter changes their mind once the cooldown is over, then Telegram delivers the
+        // first press again: it mustn't bring the old answer back
+        tokio::time::sleep(VOTE_COOLDOWN).await;
+        Response::upsert(&db.pool, session.id.clone(), option.id.clone(), 42, Some("tester".to_string()), "no".to_string()).await.unwrap();
+        callback_handler(offline_bot(), press, db.clone()).await.unwrap();
+        let responses = Response::find_by_session(&db.pool, &session.id).await.unwrap();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].response, "no");
+    }
+    
+    #[test]
+    fn test_seen_callbacks_forget_old_ids() {
+        let seen = SeenCallbacks::default();
+        let start = Instant::now();
+        assert!(seen.first_delivery("a", start));
+        assert!(!seen.first_delivery("a", start + CALLBACK_DEDUP_WINDOW / 2));
+        assert!(seen.first_delivery("b", start + CALLBACK_DEDUP_WINDOW / 2));
+        assert!(seen.first_delivery("a", start + CALLBACK_DEDUP_WINDOW));
+        
+        // When full, the oldest ID makes room for the next
+        let seen = SeenCallbacks::default();
+        for i in 0..CALLBACK_DEDUP_CAPACITY {
+            assert!(seen.first_delivery(&i.to_string(), start + std::time::Duration::from_millis(i as u64)));
+        }
+        let later = start + std::time::Duration::from_secs(60);
+        assert!(seen.first_delivery("new", later));
+        assert!(seen.first_delivery("0", later));
+        assert!(!seen.first_delivery("2", later));
+    }
+    
+    async fn held_back_session(db: &dnd_scheduler_bot::database::connection::DatabaseManager, created_by: i64) -> PendingSession {
+        let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+        let options = [
+            PendingOption { starts_at: Utc::now() + Duration::days(3), duration: None },
+            PendingOption { starts_at: Utc::now() + Duration::days(4), duration: Some(180) },
+        ];
+        PendingSession::create(&db.pool, group.id, created_by, "Weekly D&D", false, &options).await.unwrap()
+    }
+    
+    #[tokio::test]
+    async fn test_create_anyway_creates_the_held_back_session_once() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let pending = held_back_session(&db, 42).await;
+        
+        // Posting the poll fails offline, but the session and its options are saved by then
+        let data = format!("schedule_confirm:create:{}", pending.id);
+        for _ in 0..2 {
+            callback_handler(offline_bot(), button_press(&data), db.clone()).await.unwrap();
+        }
+        assert!(PendingSession::find_by_id(&db.pool, &pending.id).await.unwrap().is_none());
+        let sessions = Session::find_by_group(&db.pool, pending.group_id).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].title, "Weekly D&D");
+        assert_eq!(sessions[0].created_by, 42);
+        let options = SessionOption::find_by_session(&db.pool, &sessions[0].id).await.unwrap();
+        assert_eq!(options.iter().map(|o| o.duration).collect::<Vec<_>>(), [240, 180]);
+    }
+    
+    #[tokio::test]
+    async fn test_cancel_drops_the_held_back_session() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let pending = held_back_session(&db, 42).await;
+        
+        let data = format!("schedule_confirm:cancel:{}", pending.id);
+        callback_handler(offline_bot(), button_press(&data), db.clone()).await.unwrap();
+        assert!(PendingSession::find_by_id(&db.pool, &pending.id).await.unwrap().is_none());
+        assert!(Session::find_by_group(&db.pool, pending.group_id).await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_only_the_scheduler_settles_a_held_back_session() {
+        let (db, _temp_dir) = test_db().await.unwrap();
+        let pending = held_back_session(&db, 7).await;
+        
+        let data = format!("schedule_confirm:create:{}", pending.id);
+        callback_handler(offline_bot(), button_press(&data), db.clone()).await.unwrap();
+        assert!(PendingSession::find_by_id(&db.pool, &pending.id).await.unwrap().is_some());
+        assert!(Session::find_by_group(&db.pool, pending.group_id).await.unwrap().is_empty());
+    }
+}
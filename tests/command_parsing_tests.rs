@@ -1,4 +1,12 @@
-use dnd_scheduler_bot::bot::commands::Command;
+use chrono::{Duration, TimeZone, Utc};
+use dnd_scheduler_bot::bot::commands::{command_help, command_usage, failed_command, looks_like_command, slash_command, suggest_invocation, with_command_prefix, Command};
+use dnd_scheduler_bot::bot::commands::duty::{parse_duty_command, DutyCommand};
+use dnd_scheduler_bot::bot::commands::players::{parse_players_command, PlayersCommand};
+use dnd_scheduler_bot::bot::commands::settings::{parse_admins_command, parse_badges_args, parse_language_args, parse_long_range_args, parse_quiet_hours_args, parse_setting_value, parse_whats_new_args, AdminsCommand, LongRangeCommand, QuietHoursCommand};
+use dnd_scheduler_bot::bot::dialogue::SettingField;
+use dnd_scheduler_bot::database::models::GroupSetting;
+use dnd_scheduler_bot::bot::commands::schedule::{dedupe_parsed_options, find_far_future_outliers, find_past_option, find_too_soon_option, guessed_time_warning, option_time_message, split_schedule_flags};
+use dnd_scheduler_bot::scheduling::{check_option_time, find_unschedulable_option, likely_year, OptionTimeError};
 use teloxide::utils::command::BotCommands;
 
 #[cfg(test)]
@@ -10,7 +18,130 @@ mod command_parsing_tests {
         let input = "/help";
         let result = Command::parse(input, "testbot");
         assert!(result.is_ok());
-        matches!(result.unwrap(), Command::Help);
+        matches!(result.unwrap(), Command::Help { .. });
+    }
+
+    #[test]
+    fn test_help_with_a_command() {
+        for input in ["/help schedule", "/help /schedule", "/help  Schedule "] {
+            match Command::parse(input, "testbot").unwrap() {
+                Command::Help { command } => assert_eq!(command.as_deref(), Some("schedule"), "{input}"),
+                other => panic!("Expected Help command, got {other:?}"),
+            }
+        }
+        assert!(matches!(Command::parse("/help", "testbot").unwrap(), Command::Help { command: None }));
+        assert!(Command::parse("/help schedule list", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_every_command_has_detailed_help() {
+        let descriptions = Command::descriptions().to_string();
+        for line in descriptions.lines().filter(|line| line.starts_with('/')) {
+            let name = line.split(' ').next().unwrap();
+            let (description, examples) = command_help(name).unwrap_or_else(|| panic!("No help for {name}"));
+            assert!(line.ends_with(&description), "{line}");
+            assert!(!examples.is_empty(), "{name}");
+            assert!(examples.iter().all(|example| example.starts_with(name)), "{name}: {examples:?}");
+        }
+        assert!(command_help("/CONFIRM").unwrap().0.starts_with("Confirm a session for its best option"));
+        assert!(command_help("roll").is_none());
+    }
+
+    #[test]
+    fn test_failed_command_for_each_command_with_arguments() {
+        for (text, command) in [
+            ("/schedule", "schedule"),
+            ("/confirm", "confirm"),
+            ("/confirm #3 first", "confirm"),
+            ("/cancel  ", "cancel"),
+            ("/delete", "delete"),
+            ("/repost", "repost"),
+            ("/archive", "archive"),
+            ("/deadline", "deadline"),
+            ("/deadline #3", "deadline"),
+            ("/notes #3", "notes"),
+            ("/stats #3 #4", "stats"),
+            ("/closeall older than soon", "closeall"),
+            ("/testreminders everything", "testreminders"),
+            ("/checkins", "checkins"),
+            ("/sent@testbot", "sent"),
+            ("/results", "results"),
+            ("/sessioninfo", "sessioninfo"),
+            ("/rollcall soon", "rollcall"),
+            ("/vote #3 2", "vote"),
+            ("/availability \"Session 12\"", "availability"),
+            ("/exportgroup everything", "exportgroup"),
+            ("/attended #3", "attended"),
+            ("/players add", "players"),
+            ("/duty remove", "duty"),
+            ("/help schedule list", "help"),
+        ] {
+            assert_eq!(failed_command(text, "testbot"), Some(command), "{text}");
+            let usage = command_usage(command).unwrap();
+            assert!(usage.starts_with(&format!("/{command}")), "{usage}");
+        }
+    }
+
+    #[test]
+    fn test_failed_command_ignores_everything_else() {
+        for text in ["/confirm #3", "/list", "/roll 1d20", "/confirm@otherbot", "hello", "/stats"] {
+            assert_eq!(failed_command(text, "testbot"), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_commands_with_a_custom_prefix() {
+        let parse = |text: &str| Command::parse(&slash_command(text, "!"), "testbot");
+        assert!(matches!(parse("!help").unwrap(), Command::Help { command: None }));
+        assert!(matches!(parse("!list@testbot").unwrap(), Command::List));
+        match parse("!deadline abc123 Friday 18:00").unwrap() {
+            Command::Deadline { session_id, datetime } => assert_eq!((session_id.as_str(), datetime.as_str()), ("abc123", "Friday 18:00")),
+            other => panic!("Expected Deadline command, got {other:?}"),
+        }
+        // Telegram's command menu still sends "/"
+        assert!(matches!(parse("/list").unwrap(), Command::List));
+        assert!(parse("!list@otherbot").is_err());
+        assert!(parse("list").is_err());
+        
+        assert_eq!(failed_command(&slash_command("!confirm", "!"), "testbot"), Some("confirm"));
+        assert_eq!(failed_command(&slash_command("!!!", "!"), "testbot"), None);
+        assert_eq!(slash_command("!list", "/"), "!list");
+        assert_eq!(slash_command(">>list", ">>"), "/list");
+    }
+
+    #[test]
+    fn test_looks_like_command() {
+        assert!(looks_like_command("/list", "!"));
+        assert!(looks_like_command("!list", "!"));
+        assert!(looks_like_command("!roll 1d20", "!"));
+        for text in ["!!!", "! what", "?", "hello!", "list"] {
+            assert!(!looks_like_command(text, "!"), "{text}");
+        }
+        assert!(!looks_like_command("!list", "/"));
+    }
+
+    #[test]
+    fn test_help_text_with_a_custom_prefix() {
+        assert_eq!(with_command_prefix("Use `/confirm <session_id>` or /help", "!"), "Use `!confirm <session_id>` or !help");
+        assert_eq!(with_command_prefix("/schedule \"Title\" Friday 19:00", "!"), "!schedule \"Title\" Friday 19:00");
+        // Links, dates and bare slashes keep theirs
+        assert_eq!(with_command_prefix("See https://t.me/bot on 12/05 or a / b", "!"), "See https://t.me/bot on 12/05 or a / b");
+        
+        let descriptions = with_command_prefix(&Command::descriptions().to_string(), "!");
+        assert!(descriptions.lines().any(|line| line.starts_with("!schedule")), "{descriptions}");
+        assert!(!descriptions.lines().any(|line| line.starts_with('/')), "{descriptions}");
+        assert_eq!(with_command_prefix("/help", "/"), "/help");
+    }
+
+    #[test]
+    fn test_suggest_invocation() {
+        assert_eq!(suggest_invocation(command_usage("confirm").unwrap(), "#3").as_deref(), Some("/confirm #3"));
+        assert_eq!(
+            suggest_invocation(command_usage("deadline").unwrap(), "#3").as_deref(),
+            Some("/deadline #3 <datetime>")
+        );
+        assert_eq!(suggest_invocation(command_usage("schedule").unwrap(), "#3"), None);
+        assert_eq!(suggest_invocation(command_usage("stats").unwrap(), "#3"), None);
     }
 
     #[test]
@@ -18,7 +149,23 @@ mod command_parsing_tests {
         let input = "/start";
         let result = Command::parse(input, "testbot");
         assert!(result.is_ok());
-        matches!(result.unwrap(), Command::Start);
+        match result.unwrap() {
+            Command::Start { payload } => assert!(payload.is_empty()),
+            _ => panic!("Expected Start command"),
+        }
+    }
+
+    #[test]
+    fn test_start_command_with_deep_link_payload() {
+        match Command::parse("/start join_-1001234567890", "testbot").unwrap() {
+            Command::Start { payload } => assert_eq!(payload, "join_-1001234567890"),
+            _ => panic!("Expected Start command"),
+        }
+    }
+
+    #[test]
+    fn test_invite_command_parsing() {
+        assert!(matches!(Command::parse("/invite", "testbot").unwrap(), Command::Invite));
     }
 
     #[test]
@@ -34,7 +181,15 @@ mod command_parsing_tests {
         let input = "/settings";
         let result = Command::parse(input, "testbot");
         assert!(result.is_ok());
-        matches!(result.unwrap(), Command::Settings);
+        match result.unwrap() {
+            Command::Settings { args } => assert_eq!(args, ""),
+            _ => panic!("Expected Settings command"),
+        }
+        
+        match Command::parse("/settings admins add 12345", "testbot").unwrap() {
+            Command::Settings { args } => assert_eq!(args, "admins add 12345"),
+            _ => panic!("Expected Settings command"),
+        }
     }
 
     #[test]
@@ -42,7 +197,44 @@ mod command_parsing_tests {
         let input = "/stats";
         let result = Command::parse(input, "testbot");
         assert!(result.is_ok());
-        matches!(result.unwrap(), Command::Stats);
+        match result.unwrap() {
+            Command::Stats { session_id } => assert_eq!(session_id, None),
+            _ => panic!("Expected Stats command"),
+        }
+    }
+
+    #[test]
+    fn test_stats_command_with_session_id() {
+        for (input, expected) in [("/stats #3", "#3"), ("/stats  abc12345 ", "abc12345")] {
+            match Command::parse(input, "testbot") {
+                Ok(Command::Stats { session_id }) => assert_eq!(session_id.as_deref(), Some(expected)),
+                other => panic!("Expected Stats command for input {}: {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stats_command_too_many_args() {
+        assert!(Command::parse("/stats #3 #4", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_export_command_parsing() {
+        let result = Command::parse("/export", "testbot");
+        assert!(matches!(result, Ok(Command::Export)));
+    }
+
+    #[test]
+    fn test_exportgroup_command_parsing() {
+        assert!(matches!(Command::parse("/exportgroup", "testbot"), Ok(Command::ExportGroup { private: false })));
+        assert!(matches!(Command::parse("/exportgroup DM", "testbot"), Ok(Command::ExportGroup { private: true })));
+        assert!(Command::parse("/exportgroup zip", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_undo_command_parsing() {
+        let result = Command::parse("/undo", "testbot");
+        assert!(matches!(result, Ok(Command::Undo)));
     }
 
     #[test]
@@ -50,7 +242,9 @@ mod command_parsing_tests {
         let input = "/testreminders";
         let result = Command::parse(input, "testbot");
         assert!(result.is_ok());
-        matches!(result.unwrap(), Command::TestReminders);
+        assert!(matches!(result.unwrap(), Command::TestReminders { send: false }));
+        assert!(matches!(Command::parse("/testreminders SEND", "testbot"), Ok(Command::TestReminders { send: true })));
+        assert!(Command::parse("/testreminders now", "testbot").is_err());
     }
 
     // Schedule command tests - quoted arguments
@@ -129,6 +323,36 @@ mod command_parsing_tests {
         }
     }
 
+    #[test]
+    fn test_schedule_command_with_only_time_options() {
+        // A first word that is a day, time or date on its own starts the options
+        for (input, options) in [
+            ("/schedule Friday 19:00, Sunday 15:00", "Friday 19:00, Sunday 15:00"),
+            ("/schedule 19:00 Friday", "19:00 Friday"),
+            ("/schedule 15.08.25 19:00, 16.08.25 19:00", "15.08.25 19:00, 16.08.25 19:00"),
+            ("/schedule fredag 19:00 --anonymous", "fredag 19:00 --anonymous"),
+        ] {
+            match Command::parse(input, "testbot").unwrap() {
+                Command::Schedule { title, options: parsed } => {
+                    assert_eq!(title, "", "{input}");
+                    assert_eq!(parsed, options);
+                }
+                _ => panic!("Expected Schedule command"),
+            }
+        }
+        
+        // Titles that merely contain a day name, or are quoted, stay titles
+        for (input, expected) in [
+            ("/schedule Fridaynight Friday 19:00", "Fridaynight"),
+            ("/schedule \"Friday Fight Club\" Friday 19:00", "Friday Fight Club"),
+        ] {
+            match Command::parse(input, "testbot").unwrap() {
+                Command::Schedule { title, .. } => assert_eq!(title, expected),
+                _ => panic!("Expected Schedule command"),
+            }
+        }
+    }
+    
     #[test]
     fn test_schedule_command_with_multiple_time_options() {
         let input = "/schedule \"Session\" \"Friday 19:00, Saturday 14:30, Sunday 16:00\"";
@@ -183,6 +407,82 @@ mod command_parsing_tests {
         }
     }
 
+    fn parse_schedule(input: &str) -> (String, String) {
+        match Command::parse(input, "testbot") {
+            Ok(Command::Schedule { title, options }) => (title, options),
+            other => panic!("Expected Schedule command for {input}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_schedule_command_with_curly_quotes() {
+        // Phones swap in curly quotes as you type; they work like straight ones
+        for (input, title, options) in [
+            ("/schedule “Weekly D&D Session” “Friday 19:00, Saturday 14:30”", "Weekly D&D Session", "Friday 19:00, Saturday 14:30"),
+            ("/schedule “Lost Mines” Friday 19:00", "Lost Mines", "Friday 19:00"),
+            ("/schedule „Verlorene Minen“ Freitag 19:00", "Verlorene Minen", "Freitag 19:00"),
+            ("/schedule \"Mixed quotes” Friday 19:00", "Mixed quotes", "Friday 19:00"),
+            ("/schedule “Only Title”", "Only Title", ""),
+            ("/schedule “Unclosed Friday 19:00", "Unclosed Friday 19:00", ""),
+        ] {
+            assert_eq!(parse_schedule(input), (title.to_string(), options.to_string()), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_schedule_command_with_escaped_quotes() {
+        for (input, title, options) in [
+            (r#"/schedule "The \"Big\" One" Friday 19:00"#, r#"The "Big" One"#, "Friday 19:00"),
+            (r#"/schedule "Ends in \"" "Friday 19:00""#, r#"Ends in ""#, "Friday 19:00"),
+            (r#"/schedule "Back\\slash" Friday 19:00"#, r"Back\slash", "Friday 19:00"),
+            // Other backslashes are kept as they are
+            (r#"/schedule "C:\Games" Friday 19:00"#, r"C:\Games", "Friday 19:00"),
+            (r#"/schedule "Unclosed \" Friday 19:00"#, r#"Unclosed \" Friday 19:00"#, ""),
+        ] {
+            assert_eq!(parse_schedule(input), (title.to_string(), options.to_string()), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_schedule_command_unquoted_title_runs_up_to_the_first_time() {
+        for (input, title, options) in [
+            ("/schedule Lost Mines Friday 19:00", "Lost Mines", "Friday 19:00"),
+            ("/schedule D&D Night Friday 19:00, Saturday 14:30", "D&D Night", "Friday 19:00, Saturday 14:30"),
+            ("/schedule Dungeons & Dragons 19:00 Friday", "Dungeons & Dragons", "19:00 Friday"),
+            ("/schedule Curse of Strahd 15.08.25 19:00", "Curse of Strahd", "15.08.25 19:00"),
+            ("/schedule Session 12 fredag 19:00 --anonymous", "Session 12", "fredag 19:00 --anonymous"),
+            ("/schedule Lost Mines next Friday 20:00", "Lost Mines", "next Friday 20:00"),
+            ("/schedule Lost Mines \"Friday 19:00, Saturday 14:30\"", "Lost Mines", "Friday 19:00, Saturday 14:30"),
+            ("/schedule next Friday 20:00", "", "next Friday 20:00"),
+        ] {
+            assert_eq!(parse_schedule(input), (title.to_string(), options.to_string()), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_schedule_command_parsing_regressions() {
+        // What parsed before the title heuristic and quote handling still parses the same way
+        for (input, title, options) in [
+            ("/schedule \"Weekly D&D Session\" \"Friday 19:00, Saturday 14:30\"", "Weekly D&D Session", "Friday 19:00, Saturday 14:30"),
+            ("/schedule \"Test Session\" Friday 19:00", "Test Session", "Friday 19:00"),
+            ("/schedule TestSession Friday 19:00, Saturday 14:30", "TestSession", "Friday 19:00, Saturday 14:30"),
+            ("/schedule Adventure Monday 18:00", "Adventure", "Monday 18:00"),
+            ("/schedule Fridaynight Friday 19:00", "Fridaynight", "Friday 19:00"),
+            ("/schedule Friday 19:00, Sunday 15:00", "", "Friday 19:00, Sunday 15:00"),
+            ("/schedule \"Friday Fight Club\" Friday 19:00", "Friday Fight Club", "Friday 19:00"),
+            ("/schedule \"Only Title\"", "Only Title", ""),
+            ("/schedule \"Title\" \"", "Title", "\""),
+            ("/schedule \"Unclosed quote Friday 19:00", "Unclosed quote Friday 19:00", ""),
+            // Without any time the first word is still the title
+            ("/schedule Adventure", "Adventure", ""),
+            ("/schedule Adventure whenever suits", "Adventure", "whenever suits"),
+            ("/schedule Adventure \"whenever suits\"", "Adventure", "whenever suits"),
+        ] {
+            assert_eq!(parse_schedule(input), (title.to_string(), options.to_string()), "{input}");
+        }
+        assert!(Command::parse("/schedule   ", "testbot").is_err());
+    }
+
     // Confirm command tests
     #[test]
     fn test_confirm_command_parsing() {
@@ -191,8 +491,9 @@ mod command_parsing_tests {
         
         assert!(result.is_ok());
         match result.unwrap() {
-            Command::Confirm { session_id } => {
+            Command::Confirm { session_id, option } => {
                 assert_eq!(session_id, "abc123def456");
+                assert_eq!(option, None);
             }
             _ => panic!("Expected Confirm command"),
         }
@@ -205,13 +506,29 @@ mod command_parsing_tests {
         
         assert!(result.is_ok());
         match result.unwrap() {
-            Command::Confirm { session_id } => {
+            Command::Confirm { session_id, option } => {
                 assert_eq!(session_id, "550e8400-e29b-41d4-a716-446655440000");
+                assert_eq!(option, None);
             }
             _ => panic!("Expected Confirm command"),
         }
     }
 
+    #[test]
+    fn test_confirm_command_with_option_number() {
+        match Command::parse("/confirm #3 2", "testbot") {
+            Ok(Command::Confirm { session_id, option }) => {
+                assert_eq!(session_id, "#3");
+                assert_eq!(option, Some(2));
+            }
+            other => panic!("Expected Confirm command, got {other:?}"),
+        }
+
+        for input in ["/confirm #3 0", "/confirm #3 two", "/confirm #3 2 3"] {
+            assert!(Command::parse(input, "testbot").is_err(), "{input}");
+        }
+    }
+
     #[test]
     fn test_confirm_command_empty_session_id() {
         let input = "/confirm";
@@ -234,6 +551,190 @@ mod command_parsing_tests {
         }
     }
 
+    #[test]
+    fn test_checkins_command_parsing() {
+        match Command::parse("/checkins #3", "testbot").unwrap() {
+            Command::CheckIns { session_id } => assert_eq!(session_id, "#3"),
+            _ => panic!("Expected CheckIns command"),
+        }
+        
+        assert!(Command::parse("/checkins", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_attended_command_parsing() {
+        match Command::parse("/attended #3 @mira @bob", "testbot").unwrap() {
+            Command::Attended { session_id, players } => {
+                assert_eq!(session_id, "#3");
+                assert_eq!(players, "@mira @bob");
+            }
+            _ => panic!("Expected Attended command"),
+        }
+        
+        assert!(Command::parse("/attended #3", "testbot").is_err());
+        assert!(Command::parse("/attended", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_players_command_parsing() {
+        match Command::parse("/players add @mira @bob", "testbot").unwrap() {
+            Command::Players { args } => assert_eq!(args, "add @mira @bob"),
+            _ => panic!("Expected Players command"),
+        }
+        assert!(matches!(Command::parse("/players", "testbot").unwrap(), Command::Players { .. }));
+        assert!(Command::parse("/players kick @bob", "testbot").is_err());
+        
+        assert_eq!(parse_players_command(""), Ok(PlayersCommand::List));
+        assert_eq!(parse_players_command(" LIST "), Ok(PlayersCommand::List));
+        assert_eq!(parse_players_command("Add  @mira @bob"), Ok(PlayersCommand::Add("@mira @bob".to_string())));
+        assert_eq!(parse_players_command("remove 42"), Ok(PlayersCommand::Remove("42".to_string())));
+        assert!(parse_players_command("remove").is_err());
+        assert_eq!(parse_players_command("require @dm_dana"), Ok(PlayersCommand::Require("@dm_dana".to_string())));
+        assert_eq!(parse_players_command("UNREQUIRE 42"), Ok(PlayersCommand::Unrequire("42".to_string())));
+        assert!(parse_players_command("require").is_err());
+        assert!(parse_players_command("list @mira").is_err());
+    }
+
+    #[test]
+    fn test_duty_command_parsing() {
+        match Command::parse("/duty add bring snacks", "testbot").unwrap() {
+            Command::Duty { args } => assert_eq!(args, "add bring snacks"),
+            _ => panic!("Expected Duty command"),
+        }
+        assert!(Command::parse("/duty swap hosting", "testbot").is_err());
+        
+        assert_eq!(parse_duty_command(""), Ok(DutyCommand::List));
+        assert_eq!(parse_duty_command("ADD  bring   snacks "), Ok(DutyCommand::Add("bring snacks".to_string())));
+        assert_eq!(parse_duty_command("remove hosting"), Ok(DutyCommand::Remove("hosting".to_string())));
+        assert!(parse_duty_command("add").is_err());
+        assert!(parse_duty_command(&format!("add {}", "x".repeat(33))).is_err());
+    }
+
+    #[test]
+    fn test_sent_command_parsing() {
+        match Command::parse("/sent #3", "testbot").unwrap() {
+            Command::Sent { session_id } => assert_eq!(session_id, "#3"),
+            _ => panic!("Expected Sent command"),
+        }
+        
+        assert!(Command::parse("/sent", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_results_command_parsing() {
+        match Command::parse("/results #3", "testbot").unwrap() {
+            Command::Results { session_id } => assert_eq!(session_id, "#3"),
+            _ => panic!("Expected Results command"),
+        }
+        
+        assert!(Command::parse("/results", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_sessioninfo_command_parsing() {
+        match Command::parse("/sessioninfo #3", "testbot").unwrap() {
+            Command::SessionInfo { session_id } => assert_eq!(session_id, "#3"),
+            _ => panic!("Expected SessionInfo command"),
+        }
+        
+        assert!(Command::parse("/sessioninfo", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_rollcall_command_parsing() {
+        use chrono::NaiveTime;
+        use dnd_scheduler_bot::bot::commands::rollcall::{parse_rollcall_args, DEFAULT_ROLLCALL_QUORUM};
+        
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        assert_eq!(parse_rollcall_args("").unwrap().time, at(20, 0));
+        assert_eq!(parse_rollcall_args("").unwrap().quorum, DEFAULT_ROLLCALL_QUORUM);
+        assert_eq!(parse_rollcall_args("19.30").unwrap().time, at(19, 30));
+        let args = parse_rollcall_args(" 18:00  5 ").unwrap();
+        assert_eq!((args.time, args.quorum), (at(18, 0), 5));
+        for args in ["tonight", "25:00", "19:30 four", "19:30 4 now", "4"] {
+            assert!(parse_rollcall_args(args).is_err(), "{args}");
+        }
+        
+        match Command::parse("/rollcall 19:30 4", "testbot").unwrap() {
+            Command::RollCall { args } => assert_eq!(args, "19:30 4"),
+            _ => panic!("Expected RollCall command"),
+        }
+    }
+
+    #[test]
+    fn test_vote_command_parsing() {
+        use dnd_scheduler_bot::bot::commands::vote::parse_vote_args;
+        
+        let args = parse_vote_args(" #12  2 YES ").unwrap();
+        assert_eq!((args.session_id.as_str(), args.option, args.response.as_str()), ("#12", 2, "yes"));
+        assert_eq!(parse_vote_args("a1b2c3d4 #1 maybe").unwrap().option, 1);
+        for args in ["", "#12", "#12 2", "#12 0 yes", "#12 two yes", "#12 2 yes please"] {
+            assert!(parse_vote_args(args).is_err(), "{args}");
+        }
+        
+        match Command::parse("/vote #12 3 no", "testbot").unwrap() {
+            Command::Vote { session_id, option, response } => {
+                assert_eq!((session_id.as_str(), option, response.as_str()), ("#12", 3, "no"));
+            }
+            _ => panic!("Expected Vote command"),
+        }
+    }
+
+    #[test]
+    fn test_availability_command_parsing() {
+        match Command::parse("/availability \"Session 12\" next week evenings", "testbot").unwrap() {
+            Command::Availability { title, range } => {
+                assert_eq!((title.as_str(), range.as_str()), ("Session 12", "next week evenings"));
+            }
+            _ => panic!("Expected Availability command"),
+        }
+        assert!(Command::parse("/availability", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_notes_command_parsing() {
+        match Command::parse("/notes #3 At Sam's place, bring dice", "testbot").unwrap() {
+            Command::Notes { session_id, text } => {
+                assert_eq!(session_id, "#3");
+                assert_eq!(text, "At Sam's place, bring dice");
+            }
+            _ => panic!("Expected Notes command"),
+        }
+        
+        assert!(Command::parse("/notes #3", "testbot").is_err());
+        assert!(Command::parse("/notes", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_repost_command_parsing() {
+        match Command::parse("/repost #7", "testbot").unwrap() {
+            Command::Repost { session_id } => assert_eq!(session_id, "#7"),
+            _ => panic!("Expected Repost command"),
+        }
+        
+        assert!(Command::parse("/repost", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_archive_command_parsing() {
+        match Command::parse("/archive #4", "testbot").unwrap() {
+            Command::Archive { session_id } => assert_eq!(session_id, "#4"),
+            _ => panic!("Expected Archive command"),
+        }
+        
+        assert!(Command::parse("/archive", "testbot").is_err());
+    }
+
+    #[test]
+    fn test_delete_command_parsing() {
+        match Command::parse("/delete #12", "testbot").unwrap() {
+            Command::Delete { session_id } => assert_eq!(session_id, "#12"),
+            _ => panic!("Expected Delete command"),
+        }
+        
+        assert!(Command::parse("/delete", "testbot").is_err());
+    }
+
     #[test]
     fn test_cancel_command_empty_session_id() {
         let input = "/cancel";
@@ -301,6 +802,309 @@ mod command_parsing_tests {
         assert!(result.is_err());
     }
 
+    // CloseAll command tests
+    #[test]
+    fn test_closeall_command_default_age() {
+        let result = Command::parse("/closeall", "testbot");
+        
+        assert!(result.is_ok());
+        match result.unwrap() {
+            Command::CloseAll { older_than_days } => assert_eq!(older_than_days, 30),
+            _ => panic!("Expected CloseAll command"),
+        }
+    }
+
+    #[test]
+    fn test_closeall_command_age_formats() {
+        let test_cases = vec![
+            ("/closeall 45", 45),
+            ("/closeall 45d", 45),
+            ("/closeall older than 60d", 60),
+            ("/closeall Older Than 7 d", 7),
+        ];
+
+        for (input, expected_days) in test_cases {
+            match Command::parse(input, "testbot") {
+                Ok(Command::CloseAll { older_than_days }) => {
+                    assert_eq!(older_than_days, expected_days, "Wrong age for input: {}", input);
+                }
+                other => panic!("Expected CloseAll command for input {}: {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_closeall_command_invalid_age() {
+        assert!(Command::parse("/closeall 0d", "testbot").is_err());
+        assert!(Command::parse("/closeall soon", "testbot").is_err());
+        assert!(Command::parse("/closeall -5d", "testbot").is_err());
+    }
+
+    // Schedule flag tests
+    #[test]
+    fn test_schedule_anonymous_flag() {
+        let result = Command::parse("/schedule \"Game Night\" \"Friday 19:00, Saturday 14:30\" --anonymous", "testbot");
+        
+        match result.unwrap() {
+            Command::Schedule { title, options } => {
+                assert_eq!(title, "Game Night");
+                let (options, flags) = split_schedule_flags(&options).unwrap();
+                assert_eq!(options, "Friday 19:00, Saturday 14:30");
+                assert!(flags.anonymous);
+            }
+            _ => panic!("Expected Schedule command"),
+        }
+    }
+
+    #[test]
+    fn test_split_schedule_flags() {
+        let (options, flags) = split_schedule_flags("Friday 19:00, Saturday 14:30").unwrap();
+        assert_eq!(options, "Friday 19:00, Saturday 14:30");
+        assert!(!flags.anonymous);
+        
+        let (options, flags) = split_schedule_flags("--anonymous Friday 19:00").unwrap();
+        assert_eq!(options, "Friday 19:00");
+        assert!(flags.anonymous);
+        
+        assert!(split_schedule_flags("Friday 19:00 --secret").is_err());
+    }
+
+    #[test]
+    fn test_dedupe_parsed_options() {
+        let friday = Utc.with_ymd_and_hms(2030, 6, 7, 19, 0, 0).unwrap();
+        let saturday = Utc.with_ymd_and_hms(2030, 6, 8, 14, 30, 0).unwrap();
+        
+        let (unique, duplicates) = dedupe_parsed_options(&[friday, saturday, friday, friday]);
+        assert_eq!(unique, vec![friday, saturday]);
+        assert_eq!(duplicates, vec![2, 3]);
+        
+        let (unique, duplicates) = dedupe_parsed_options(&[saturday, friday]);
+        assert_eq!(unique, vec![saturday, friday]);
+        assert!(duplicates.is_empty());
+    }
+
+    #[test]
+    fn test_find_far_future_outliers() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 20, 12, 0, 0).unwrap();
+        let next_week = now + Duration::days(7);
+        let in_three_weeks = now + Duration::days(21);
+        let next_year = Utc.with_ymd_and_hms(2027, 2, 1, 19, 0, 0).unwrap();
+        let in_two_months = now + Duration::days(60);
+        
+        // One option a year out among near-term ones is flagged
+        assert_eq!(find_far_future_outliers(&[next_week, next_year, in_three_weeks], now), vec![1]);
+        assert_eq!(find_far_future_outliers(&[next_year, next_week, next_year + Duration::days(1)], now), vec![0, 2]);
+        
+        // All far out, all near term, or in between: nothing to flag
+        assert!(find_far_future_outliers(&[next_year, next_year + Duration::days(7)], now).is_empty());
+        assert!(find_far_future_outliers(&[next_week, in_three_weeks], now).is_empty());
+        assert!(find_far_future_outliers(&[next_week, in_two_months], now).is_empty());
+        assert!(find_far_future_outliers(&[in_two_months, next_year], now).is_empty());
+        assert!(find_far_future_outliers(&[], now).is_empty());
+        
+        // The thresholds themselves are not outliers
+        assert!(find_far_future_outliers(&[in_three_weeks, now + Duration::days(90)], now).is_empty());
+    }
+
+    #[test]
+    fn test_check_option_time_across_the_new_year() {
+        let now = Utc.with_ymd_and_hms(2026, 12, 31, 23, 30, 0).unwrap();
+        assert_eq!(check_option_time(Utc.with_ymd_and_hms(2026, 12, 31, 23, 0, 0).unwrap(), now), Err(OptionTimeError::Past));
+        assert_eq!(check_option_time(Utc.with_ymd_and_hms(2027, 1, 1, 0, 15, 0).unwrap(), now), Err(OptionTimeError::TooSoon));
+        assert_eq!(check_option_time(Utc.with_ymd_and_hms(2027, 1, 1, 0, 30, 0).unwrap(), now), Ok(()));
+        
+        // 18 months on from the last day of the year is the end of June
+        let latest = Utc.with_ymd_and_hms(2028, 6, 30, 23, 30, 0).unwrap();
+        assert_eq!(check_option_time(latest, now), Ok(()));
+        assert_eq!(check_option_time(latest + Duration::minutes(1), now), Err(OptionTimeError::TooFarAhead));
+        
+        let options = [now + Duration::days(2), now - Duration::days(400), now + Duration::days(1000)];
+        assert_eq!(find_unschedulable_option(&options, now), Some((1, OptionTimeError::Past)));
+        assert_eq!(find_unschedulable_option(&options[..1], now), None);
+    }
+    
+    #[test]
+    fn test_past_two_digit_years_suggest_the_likely_one() {
+        use dnd_scheduler_bot::utils::datetime::parse_datetime;
+        let now = Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+        let read = |text: &str| parse_datetime(text).value();
+        
+        // "20" is read as 2020; 1 January has passed this year, 15 August hasn't
+        assert_eq!(likely_year(read("01.01.20 19:00"), now), Some(2026));
+        assert_eq!(likely_year(read("15.08.20 19:00"), now), Some(2025));
+        // Up to "30" is this century and from "31" on the last; both are past
+        assert_eq!(read("01.05.30 19:00").format("%Y").to_string(), "2030");
+        assert_eq!(likely_year(read("01.05.99 19:00"), now), Some(2025));
+        // Earlier this year, the year isn't the mistake; nor is it for a leap day with no leap year to go to
+        assert_eq!(likely_year(read("01.02.25 19:00"), now), None);
+        assert_eq!(likely_year(read("29.02.24 19:00"), now), None);
+        
+        let (error, suggestion) = option_time_message("01.01.20 19:00", read("01.01.20 19:00"), now, OptionTimeError::Past);
+        assert_eq!(error, "Time option '01.01.20 19:00' is in the past: it reads as Wednesday, 01 January 2020 at 19:00");
+        assert!(suggestion.starts_with("Did you mean 2026? Writing the year in full, like '01.01.2026 19:00'"), "{suggestion}");
+        
+        let (error, suggestion) = option_time_message("01.02.25 19:00", read("01.02.25 19:00"), now, OptionTimeError::Past);
+        assert!(error.contains("'01.02.25 19:00'"));
+        assert!(!suggestion.contains("Did you mean"));
+        
+        let (error, _) = option_time_message("01.01.2030 19:00", read("01.01.2030 19:00"), now, OptionTimeError::TooFarAhead);
+        assert_eq!(error, "Time option '01.01.2030 19:00' is more than 18 months away: it reads as Tuesday, 01 January 2030 at 19:00");
+    }
+    
+    #[test]
+    fn test_impossible_dates_are_not_read_as_something_else() {
+        use dnd_scheduler_bot::utils::datetime::{invalid_european_date, parse_datetime};
+        
+        assert_eq!(invalid_european_date("31.02.25 19:00").as_deref(), Some("February 2025 has no day 31"));
+        assert_eq!(invalid_european_date("29.02.25 19:00").as_deref(), Some("February 2025 has no day 29"));
+        assert_eq!(invalid_european_date("31.04.2026 19:00").as_deref(), Some("April 2026 has no day 31"));
+        assert_eq!(invalid_european_date("29.02.24 19:00"), None);
+        assert_eq!(invalid_european_date("Friday 19:00"), None);
+        // Once the date is fine a bad time is another matter
+        assert_eq!(invalid_european_date("15.08.25 25:00"), None);
+        
+        // They used to come out as the time on the next matching weekday
+        assert!(parse_datetime("31.02.25 19:00").is_fallback());
+        assert!(!parse_datetime("29.02.24 19:00").is_fallback());
+    }
+    
+    #[test]
+    fn test_guessed_time_warning() {
+        use dnd_scheduler_bot::utils::datetime::{parse_datetime, ParseOutcome};
+        
+        let ParseOutcome::Fallback(guessed) = parse_datetime("after the harvest festival") else {
+            unreachable!("unrecognised input should fall back");
+        };
+        let warning = guessed_time_warning("after the harvest festival", guessed);
+        assert!(warning.contains("at 19:00 for 'after the harvest festival'"), "{warning}");
+        assert!(warning.ends_with("please re-schedule if wrong"), "{warning}");
+        assert!(matches!(parse_datetime("Friday 19:00 (3h)"), ParseOutcome::Exact(_)));
+    }
+
+    #[test]
+    fn test_find_past_option() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 20, 12, 0, 0).unwrap();
+        let last_friday = now - Duration::days(4);
+        let tomorrow = now + Duration::days(1);
+        
+        assert_eq!(find_past_option(&[tomorrow, last_friday], now), Some(1));
+        assert_eq!(find_past_option(&[tomorrow], now), None);
+        
+        // A few minutes ago is within the grace window, a few more is not
+        assert_eq!(find_past_option(&[now - Duration::minutes(5)], now), None);
+        assert_eq!(find_past_option(&[now - Duration::minutes(6)], now), Some(0));
+    }
+
+    #[test]
+    fn test_find_too_soon_option() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 20, 12, 0, 0).unwrap();
+        let in_two_hours = now + Duration::hours(2);
+        let tomorrow = now + Duration::days(1);
+        
+        assert_eq!(find_too_soon_option(&[tomorrow, in_two_hours], now, 24), Some(1));
+        assert_eq!(find_too_soon_option(&[in_two_hours, tomorrow], now, 3), Some(0));
+        
+        // Exactly at the notice boundary is fine
+        assert_eq!(find_too_soon_option(&[tomorrow, in_two_hours], now, 2), None);
+        
+        // Zero notice allows anything
+        assert_eq!(find_too_soon_option(&[now, in_two_hours], now, 0), None);
+    }
+
+    #[test]
+    fn test_parse_admins_command() {
+        assert_eq!(parse_admins_command("admins"), Ok(AdminsCommand::List));
+        assert_eq!(parse_admins_command("admins list"), Ok(AdminsCommand::List));
+        assert_eq!(parse_admins_command("Admins ADD 12345"), Ok(AdminsCommand::Add(Some(12345))));
+        assert_eq!(parse_admins_command("admins add"), Ok(AdminsCommand::Add(None)));
+        assert_eq!(parse_admins_command("admins remove 12345"), Ok(AdminsCommand::Remove(Some(12345))));
+        
+        assert!(parse_admins_command("timezone").is_err());
+        assert!(parse_admins_command("admins promote 12345").is_err());
+        assert!(parse_admins_command("admins add @alice").is_err());
+        assert!(parse_admins_command("admins add -5").is_err());
+        assert!(parse_admins_command("admins add 1 2").is_err());
+    }
+
+    #[test]
+    fn test_parse_language_args() {
+        assert_eq!(parse_language_args("language sv"), Some("sv"));
+        assert_eq!(parse_language_args("  Language   FR "), Some("FR"));
+        assert_eq!(parse_language_args("language"), Some(""));
+        
+        assert_eq!(parse_language_args("admins add 12345"), None);
+        assert_eq!(parse_language_args("languages sv"), None);
+        assert_eq!(parse_language_args(""), None);
+    }
+
+    #[test]
+    fn test_parse_long_range_args() {
+        let set = |every_days, until_days| Some(Ok(LongRangeCommand::Set { every_days, until_days }));
+        assert_eq!(parse_long_range_args("longrange"), Some(Ok(LongRangeCommand::Show)));
+        assert_eq!(parse_long_range_args("LongRange off"), Some(Ok(LongRangeCommand::Off)));
+        assert_eq!(parse_long_range_args("longrange every 30d until 21d"), set(30, 21));
+        assert_eq!(parse_long_range_args("longrange 30 until 21"), set(30, 21));
+        assert_eq!(parse_long_range_args("longrange every 30d"), set(30, 14));
+        
+        assert!(matches!(parse_long_range_args("longrange monthly"), Some(Err(_))));
+        assert!(matches!(parse_long_range_args("longrange every 30d until soon"), Some(Err(_))));
+        assert_eq!(parse_long_range_args("language sv"), None);
+        assert_eq!(parse_long_range_args(""), None);
+    }
+
+    #[test]
+    fn test_parse_quiet_hours_args() {
+        let set = |start_hour, end_hour| Some(Ok(QuietHoursCommand::Set { start_hour, end_hour }));
+        assert_eq!(parse_quiet_hours_args("quiet"), Some(Ok(QuietHoursCommand::Show)));
+        assert_eq!(parse_quiet_hours_args("Quiet OFF"), Some(Ok(QuietHoursCommand::Off)));
+        assert_eq!(parse_quiet_hours_args("quiet 22-07"), set(22, 7));
+        assert_eq!(parse_quiet_hours_args("quiet 22:00 - 7:00"), set(22, 7));
+        assert_eq!(parse_quiet_hours_args("quiet 1 to 6"), set(1, 6));
+        
+        assert!(matches!(parse_quiet_hours_args("quiet nights"), Some(Err(_))));
+        assert!(matches!(parse_quiet_hours_args("quiet 22:30-07"), Some(Err(_))));
+        assert_eq!(parse_quiet_hours_args("longrange off"), None);
+        assert_eq!(parse_quiet_hours_args(""), None);
+    }
+
+    #[test]
+    fn test_parse_setting_value() {
+        let timezone = |text| parse_setting_value(SettingField::Timezone, text);
+        assert_eq!(timezone(" utc+2 "), Ok(GroupSetting::Timezone("UTC+2".to_string())));
+        assert_eq!(timezone("-05:30"), Ok(GroupSetting::Timezone("UTC-5:30".to_string())));
+        assert_eq!(timezone("GMT"), Ok(GroupSetting::Timezone("UTC".to_string())));
+        assert!(timezone("Europe/Berlin").is_err());
+        assert!(timezone("UTC+15").is_err());
+        
+        let duration = |text| parse_setting_value(SettingField::DefaultDuration, text);
+        assert_eq!(duration("150"), Ok(GroupSetting::DefaultDuration(150)));
+        assert_eq!(duration("3h"), Ok(GroupSetting::DefaultDuration(180)));
+        assert_eq!(duration("1h 45m"), Ok(GroupSetting::DefaultDuration(105)));
+        assert!(duration("0").is_err());
+        assert!(duration("all night").is_err());
+    }
+
+    #[test]
+    fn test_parse_whats_new_args() {
+        assert_eq!(parse_whats_new_args("whatsnew"), Some(Ok(None)));
+        assert_eq!(parse_whats_new_args("WhatsNew OFF"), Some(Ok(Some(false))));
+        assert_eq!(parse_whats_new_args("whatsnew on"), Some(Ok(Some(true))));
+        
+        assert!(matches!(parse_whats_new_args("whatsnew sometimes"), Some(Err(_))));
+        assert_eq!(parse_whats_new_args("quiet off"), None);
+        assert_eq!(parse_whats_new_args(""), None);
+    }
+
+    #[test]
+    fn test_parse_badges_args() {
+        assert_eq!(parse_badges_args("badges"), Some(Ok(None)));
+        assert_eq!(parse_badges_args("Badges OFF"), Some(Ok(Some(false))));
+        assert_eq!(parse_badges_args(" badges on "), Some(Ok(Some(true))));
+        assert!(matches!(parse_badges_args("badges maybe"), Some(Err(_))));
+        assert_eq!(parse_badges_args("whatsnew off"), None);
+        assert_eq!(parse_badges_args("badgesoff"), None);
+    }
+
     // Edge cases and error handling
     #[test]
     fn test_unknown_command() {
@@ -314,7 +1118,7 @@ mod command_parsing_tests {
         let input = "/help@testbot";
         let result = Command::parse(input, "testbot");
         assert!(result.is_ok());
-        matches!(result.unwrap(), Command::Help);
+        matches!(result.unwrap(), Command::Help { .. });
     }
 
     #[test]
@@ -330,7 +1134,7 @@ mod command_parsing_tests {
         let input = "/help";
         let result = Command::parse(input, "testbot");
         assert!(result.is_ok());
-        matches!(result.unwrap(), Command::Help);
+        matches!(result.unwrap(), Command::Help { .. });
     }
 
     #[test]
@@ -1,4 +1,8 @@
+#![allow(clippy::unwrap_used)]
+
 use dnd_scheduler_bot::config::Config;
+use dnd_scheduler_bot::database::connection::PoolSettings;
+use dnd_scheduler_bot::scheduling::VoteReminderSettings;
 use std::env;
 use std::sync::Mutex;
 
@@ -142,4 +146,246 @@ fn test_config_whitespace_handling() {
     env::remove_var("TELEGRAM_BOT_TOKEN");
     env::remove_var("DATABASE_URL");
     env::remove_var("HTTP_PORT");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_config_database_pool_settings() {
+    let _guard = CONFIG_TEST_MUTEX.lock().unwrap();
+    
+    env::set_var("TELEGRAM_BOT_TOKEN", "test_token");
+    env::remove_var("DB_MAX_CONNECTIONS");
+    env::remove_var("DB_BUSY_TIMEOUT_MS");
+    
+    let config = Config::from_env().unwrap();
+    assert_eq!(config.pool_settings(), PoolSettings::default());
+    
+    env::set_var("DB_MAX_CONNECTIONS", " 12 ");
+    env::set_var("DB_BUSY_TIMEOUT_MS", "250");
+    let settings = Config::from_env().unwrap().pool_settings();
+    assert_eq!(settings.max_connections, 12);
+    assert_eq!(settings.busy_timeout_ms, 250);
+    
+    env::set_var("DB_MAX_CONNECTIONS", "0");
+    assert!(Config::from_env().is_err());
+    env::set_var("DB_MAX_CONNECTIONS", "many");
+    assert!(Config::from_env().is_err());
+    
+    env::set_var("DB_MAX_CONNECTIONS", "5");
+    env::set_var("DB_BUSY_TIMEOUT_MS", "-1");
+    assert!(Config::from_env().is_err());
+    
+    // Clean up
+    env::remove_var("TELEGRAM_BOT_TOKEN");
+    env::remove_var("DB_MAX_CONNECTIONS");
+    env::remove_var("DB_BUSY_TIMEOUT_MS");
+}
+
+#[test]
+fn test_config_running_in_docker() {
+    let _guard = CONFIG_TEST_MUTEX.lock().unwrap();
+    
+    env::set_var("TELEGRAM_BOT_TOKEN", "test_token");
+    env::remove_var("RUNNING_IN_DOCKER");
+    assert!(!Config::from_env().unwrap().running_in_docker);
+    
+    for value in ["1", "true", " YES "] {
+        env::set_var("RUNNING_IN_DOCKER", value);
+        assert!(Config::from_env().unwrap().running_in_docker, "'{value}' should count as set");
+    }
+    env::set_var("RUNNING_IN_DOCKER", "0");
+    assert!(!Config::from_env().unwrap().running_in_docker);
+    
+    // Clean up
+    env::remove_var("TELEGRAM_BOT_TOKEN");
+    env::remove_var("RUNNING_IN_DOCKER");
+}
+
+/// Every variable a config file can be overridden by, cleared so the file's values show
+const SETTING_VARS: [&str; 15] = [
+    "TELEGRAM_BOT_TOKEN", "DATABASE_URL", "HTTP_PORT", "DB_MAX_CONNECTIONS", "DB_BUSY_TIMEOUT_MS",
+    "ADMIN_TOKEN", "RUNNING_IN_DOCKER", "BOT_MODE", "WEBHOOK_URL", "BACKUP_DIR", "LOG_FORMAT",
+    "COMMAND_PREFIX", "VOTE_REMINDER_AFTER_HOURS", "VOTE_REMINDER_MIN_VOTERS", "VOTE_REMINDER_INTERVAL_HOURS",
+];
+
+fn clear_setting_vars() {
+    for var in SETTING_VARS {
+        env::remove_var(var);
+    }
+}
+
+fn config_file(contents: &str) -> tempfile::NamedTempFile {
+    use std::io::Write;
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
+#[test]
+fn test_config_file_values() {
+    use dnd_scheduler_bot::config::{BotMode, LogFormat};
+    let _guard = CONFIG_TEST_MUTEX.lock().unwrap();
+    clear_setting_vars();
+    
+    let file = config_file(r#"
+        telegram_bot_token = "file_token"
+        database_url = "sqlite:/srv/bot.db"
+        http_port = 8081
+        db_max_connections = 8
+        db_busy_timeout_ms = 1500
+        admin_token = "secret"
+        running_in_docker = true
+        bot_mode = "webhook"
+        webhook_url = "https://bot.example.com/hook"
+        backup_dir = "/srv/backups"
+        log_format = "compact"
+        command_prefix = "!"
+        vote_reminder_after_hours = 24
+        vote_reminder_min_voters = 4
+        vote_reminder_interval_hours = 12
+    "#);
+    let config = Config::from_sources(Some(file.path())).unwrap();
+    
+    assert_eq!(config.telegram_bot_token, "file_token");
+    assert_eq!(config.database_url, "sqlite:/srv/bot.db");
+    assert_eq!(config.http_port, 8081);
+    assert_eq!(config.pool_settings(), PoolSettings { max_connections: 8, busy_timeout_ms: 1500 });
+    assert_eq!(config.admin_token.as_deref(), Some("secret"));
+    assert!(config.running_in_docker);
+    assert_eq!(config.bot_mode, BotMode::Webhook);
+    assert_eq!(config.webhook_url.as_deref(), Some("https://bot.example.com/hook"));
+    assert_eq!(config.backup_dir, Some(std::path::PathBuf::from("/srv/backups")));
+    assert_eq!(config.log_format, LogFormat::Compact);
+    assert_eq!(config.command_prefix, "!");
+    assert_eq!(
+        config.vote_reminder_settings(),
+        VoteReminderSettings { after_hours: 24, min_voters: 4, interval_hours: 12 }
+    );
+}
+
+#[test]
+fn test_config_env_overrides_file() {
+    let _guard = CONFIG_TEST_MUTEX.lock().unwrap();
+    clear_setting_vars();
+    
+    let file = config_file("telegram_bot_token = \"file_token\"\nhttp_port = 8081\nrunning_in_docker = true\n");
+    env::set_var("HTTP_PORT", "9090");
+    env::set_var("RUNNING_IN_DOCKER", "0");
+    // Empty variables don't hide the file's value
+    env::set_var("TELEGRAM_BOT_TOKEN", "");
+    
+    let config = Config::from_sources(Some(file.path())).unwrap();
+    assert_eq!(config.telegram_bot_token, "file_token");
+    assert_eq!(config.http_port, 9090);
+    assert!(!config.running_in_docker);
+    
+    clear_setting_vars();
+}
+
+#[test]
+fn test_config_partial_file_uses_defaults() {
+    use dnd_scheduler_bot::config::{BotMode, LogFormat};
+    let _guard = CONFIG_TEST_MUTEX.lock().unwrap();
+    clear_setting_vars();
+    
+    let file = config_file("# Only the token\ntelegram_bot_token = \"file_token\"\n");
+    let config = Config::from_sources(Some(file.path())).unwrap();
+    assert_eq!(config.database_url, "sqlite:./data/scheduler.db");
+    assert_eq!(config.http_port, 3000);
+    assert_eq!(config.pool_settings(), PoolSettings::default());
+    assert_eq!(config.admin_token, None);
+    assert_eq!(config.bot_mode, BotMode::Polling);
+    assert_eq!(config.backup_dir, None);
+    assert_eq!(config.log_format, LogFormat::Full);
+    assert_eq!(config.command_prefix, "/");
+    assert_eq!(config.vote_reminder_settings(), VoteReminderSettings::default());
+    
+    // Without a token anywhere it still fails
+    let file = config_file("http_port = 8081\n");
+    let error = Config::from_sources(Some(file.path())).unwrap_err().to_string();
+    assert!(error.contains("TELEGRAM_BOT_TOKEN must be set"), "{error}");
+}
+
+#[test]
+fn test_config_file_errors_name_the_key_and_source() {
+    let _guard = CONFIG_TEST_MUTEX.lock().unwrap();
+    clear_setting_vars();
+    let error = |contents: &str| {
+        let file = config_file(contents);
+        let error = Config::from_sources(Some(file.path())).unwrap_err().to_string();
+        (error, file.path().display().to_string())
+    };
+    
+    for (contents, key) in [
+        ("telegram_bot_token = \"t\"\nhttp_port = \"eighty\"", "http_port"),
+        ("telegram_bot_token = \"t\"\nhttp_port = 70000", "http_port"),
+        ("telegram_bot_token = \"t\"\ndb_max_connections = 0", "db_max_connections"),
+        ("telegram_bot_token = \"t\"\nrunning_in_docker = \"sometimes\"", "running_in_docker"),
+        ("telegram_bot_token = \"t\"\nlog_format = \"xml\"", "log_format"),
+        ("telegram_bot_token = 42", "telegram_bot_token"),
+        ("telegram_bot_token = \"t\"\nbackup_dir = [\"/a\", \"/b\"]", "backup_dir"),
+        ("telegram_bot_token = \"t\"\ncommand_prefix = 1", "command_prefix"),
+        ("telegram_bot_token = \"t\"\nvote_reminder_interval_hours = -1", "vote_reminder_interval_hours"),
+    ] {
+        let (error, path) = error(contents);
+        assert!(error.starts_with(&format!("Invalid {key} in {path}")), "{error}");
+    }
+    
+    let (unknown, path) = error("telegram_bot_token = \"t\"\nhttp_prot = 8080");
+    assert!(unknown.contains("Unknown setting 'http_prot'") && unknown.contains(&path), "{unknown}");
+    let (syntax, path) = error("telegram_bot_token = ");
+    assert!(syntax.starts_with(&format!("Invalid TOML in config file {path}")), "{syntax}");
+    let (webhook, _) = error("telegram_bot_token = \"t\"\nbot_mode = \"webhook\"");
+    assert!(webhook.contains("WEBHOOK_URL must be set"), "{webhook}");
+    
+    // A bad override names the variable, not the file
+    let file = config_file("telegram_bot_token = \"t\"\nhttp_port = 8081");
+    env::set_var("HTTP_PORT", "eighty");
+    let error = Config::from_sources(Some(file.path())).unwrap_err().to_string();
+    assert!(error.starts_with("Invalid HTTP_PORT: expected a port number"), "{error}");
+    
+    let missing = Config::from_sources(Some(std::path::Path::new("/nonexistent/config.toml"))).unwrap_err().to_string();
+    assert!(missing.contains("Cannot read config file /nonexistent/config.toml"), "{missing}");
+    
+    clear_setting_vars();
+}
+
+#[test]
+fn test_config_command_prefix() {
+    let _guard = CONFIG_TEST_MUTEX.lock().unwrap();
+    clear_setting_vars();
+    env::set_var("TELEGRAM_BOT_TOKEN", "token");
+    
+    assert_eq!(Config::from_env().unwrap().command_prefix, "/");
+    for (value, expected) in [("!", "!"), (" ! ", "!"), (">>", ">>"), ("/", "/")] {
+        env::set_var("COMMAND_PREFIX", value);
+        assert_eq!(Config::from_env().unwrap().command_prefix, expected, "{value:?}");
+    }
+    for value in ["bot", "! !", "!!!!", "@", "7"] {
+        env::set_var("COMMAND_PREFIX", value);
+        let error = Config::from_env().unwrap_err().to_string();
+        assert!(error.starts_with("Invalid COMMAND_PREFIX: expected 1 to 3 symbols"), "{value:?}: {error}");
+    }
+    // Empty counts as unset
+    env::set_var("COMMAND_PREFIX", "");
+    assert_eq!(Config::from_env().unwrap().command_prefix, "/");
+    
+    clear_setting_vars();
+}
+
+#[test]
+fn test_config_log_format() {
+    use dnd_scheduler_bot::config::LogFormat;
+    let _guard = CONFIG_TEST_MUTEX.lock().unwrap();
+    clear_setting_vars();
+    env::set_var("TELEGRAM_BOT_TOKEN", "token");
+    
+    for (value, expected) in [("json", LogFormat::Json), ("JSON", LogFormat::Json), ("text", LogFormat::Full), ("pretty", LogFormat::Pretty)] {
+        env::set_var("LOG_FORMAT", value);
+        assert_eq!(Config::from_env().unwrap().log_format, expected, "{value}");
+    }
+    env::set_var("LOG_FORMAT", "xml");
+    let error = Config::from_env().unwrap_err().to_string();
+    assert!(error.contains("\"json\""), "{error}");
+    
+    clear_setting_vars();
+}
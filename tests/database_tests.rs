@@ -1,22 +1,12 @@
 use anyhow::Result;
 use chrono::Utc;
-use dnd_scheduler_bot::database::{connection::DatabaseManager, models::*};
-use tempfile::{tempdir, TempDir};
-
-async fn setup_test_db() -> Result<(DatabaseManager, TempDir)> {
-    let temp_dir = tempdir()?;
-    let db_path = temp_dir.path().join("test.db");
-    let database_url = format!("sqlite:{}", db_path.display());
-    
-    let db_manager = DatabaseManager::new(&database_url).await?;
-    db_manager.run_migrations().await?;
-    
-    Ok((db_manager, temp_dir))
-}
+use dnd_scheduler_bot::database::models::*;
+use dnd_scheduler_bot::testing::*;
+use dnd_scheduler_bot::utils::changelog::CURRENT_VERSION;
 
 #[tokio::test]
 async fn test_group_creation_and_retrieval() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let chat_id = 12345i64;
     
     // Test group creation
@@ -38,7 +28,7 @@ async fn test_group_creation_and_retrieval() -> Result<()> {
 
 #[tokio::test]
 async fn test_group_not_found() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let non_existent_chat_id = 99999i64;
     
     let result = Group::find_by_chat_id(&db.pool, non_existent_chat_id).await?;
@@ -49,7 +39,7 @@ async fn test_group_not_found() -> Result<()> {
 
 #[tokio::test]
 async fn test_session_creation_and_retrieval() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let chat_id = 12345i64;
     let user_id = 67890i64;
     
@@ -78,7 +68,7 @@ async fn test_session_creation_and_retrieval() -> Result<()> {
 
 #[tokio::test]
 async fn test_session_not_found() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let non_existent_id = "non-existent-uuid";
     
     let result = Session::find_by_id(&db.pool, non_existent_id).await?;
@@ -89,7 +79,7 @@ async fn test_session_not_found() -> Result<()> {
 
 #[tokio::test]
 async fn test_session_option_creation() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let chat_id = 12345i64;
     let user_id = 67890i64;
     
@@ -122,7 +112,7 @@ async fn test_session_option_creation() -> Result<()> {
 
 #[tokio::test]
 async fn test_response_upsert() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let chat_id = 12345i64;
     let user_id = 67890i64;
     
@@ -162,7 +152,10 @@ async fn test_response_upsert() -> Result<()> {
     ).await?;
     
     assert_eq!(updated_response.response, new_response_text);
-    assert_ne!(updated_response.id, response.id); // Should be a new record
+    assert_eq!(updated_response.id, response.id); // Updated in place, not re-created
+    assert_eq!(response.updated_at, response.created_at);
+    assert_eq!(updated_response.created_at, response.created_at); // Still the first vote's time
+    assert!(updated_response.updated_at > response.updated_at);
     
     // Verify only one response exists for this user/option
     let responses = Response::find_by_session(&db.pool, &session.id).await?;
@@ -172,9 +165,39 @@ async fn test_response_upsert() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_concurrent_response_upserts_keep_one_row() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    
+    let votes = ["yes", "no", "maybe"];
+    let mut tasks = Vec::new();
+    for i in 0..30 {
+        let pool = db.pool.clone();
+        let session_id = session.id.clone();
+        let option_id = option.id.clone();
+        tasks.push(tokio::spawn(async move {
+            Response::upsert(&pool, session_id, option_id, 42, Some("alice".to_string()), votes[i % 3].to_string()).await
+        }));
+    }
+    
+    let mut ids = Vec::new();
+    for task in tasks {
+        ids.push(task.await??.id);
+    }
+    
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    assert_eq!(responses.len(), 1);
+    assert!(ids.iter().all(|id| *id == responses[0].id));
+    
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_multiple_users_responses() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let chat_id = 12345i64;
     
     // Create group, session, and option
@@ -219,7 +242,7 @@ async fn test_multiple_users_responses() -> Result<()> {
 
 #[tokio::test]
 async fn test_database_constraints() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let chat_id = 12345i64;
     
     // Test unique constraint on telegram_chat_id
@@ -234,7 +257,7 @@ async fn test_database_constraints() -> Result<()> {
 
 #[tokio::test]
 async fn test_foreign_key_relationships() -> Result<()> {
-    let (db, _temp_dir) = setup_test_db().await?;
+    let (db, _temp_dir) = test_db().await?;
     let chat_id = 12345i64;
     let user_id = 67890i64;
     
@@ -263,4 +286,1731 @@ async fn test_foreign_key_relationships() -> Result<()> {
     assert_eq!(response.option_id, option.id);
     
     Ok(())
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_session_short_ids_are_sequential_per_group() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    
+    let group_a = Group::create(&db.pool, 12345).await?;
+    let group_b = Group::create(&db.pool, 54321).await?;
+    
+    let a1 = Session::create(&db.pool, group_a.id, "A1".to_string(), 1).await?;
+    let a2 = Session::create(&db.pool, group_a.id, "A2".to_string(), 1).await?;
+    let b1 = Session::create(&db.pool, group_b.id, "B1".to_string(), 1).await?;
+    
+    assert_eq!(a1.short_id, Some(1));
+    assert_eq!(a2.short_id, Some(2));
+    assert_eq!(b1.short_id, Some(1));
+    assert_eq!(a2.display_ref(), "#2");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_find_by_reference() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    
+    let group = Group::create(&db.pool, 12345).await?;
+    let other_group = Group::create(&db.pool, 54321).await?;
+    let session = Session::create(&db.pool, group.id, "Test".to_string(), 1).await?;
+    let other = Session::create(&db.pool, other_group.id, "Other".to_string(), 1).await?;
+    
+    // Short numbers with and without '#'
+    let found = Session::find_by_reference(&db.pool, group.id, "#1").await?;
+    assert_eq!(found.map(|s| s.id), Some(session.id.clone()));
+    let found = Session::find_by_reference(&db.pool, group.id, "1").await?;
+    assert_eq!(found.map(|s| s.id), Some(session.id.clone()));
+    
+    // ID prefix and full ID
+    let found = Session::find_by_reference(&db.pool, group.id, &session.id[..8]).await?;
+    assert_eq!(found.map(|s| s.id), Some(session.id.clone()));
+    let found = Session::find_by_reference(&db.pool, group.id, &session.id).await?;
+    assert_eq!(found.map(|s| s.id), Some(session.id.clone()));
+    
+    // Lookups are scoped to the group
+    assert!(Session::find_by_reference(&db.pool, group.id, &other.id).await?.is_none());
+    assert!(Session::find_by_reference(&db.pool, group.id, "#2").await?.is_none());
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_find_by_prefix_ambiguous() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    
+    for id in ["abcdef01-0000", "abcdef02-0000"] {
+        sqlx::query("INSERT INTO sessions (id, group_id, title, status, created_by, created_at) VALUES (?, ?, 'T', 'active', 1, ?)")
+            .bind(id)
+            .bind(group.id)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&db.pool)
+            .await?;
+    }
+    
+    let result = Session::find_by_prefix(&db.pool, group.id, "abcdef0").await;
+    assert!(matches!(result, Err(SessionLookupError::Ambiguous(2))));
+    
+    let found = Session::find_by_prefix(&db.pool, group.id, "abcdef02").await?;
+    assert_eq!(found.map(|s| s.id), Some("abcdef02-0000".to_string()));
+    
+    Ok(())
+}
+
+#[test]
+fn test_parse_short_id() {
+    assert_eq!(parse_short_id("#12"), Some(12));
+    assert_eq!(parse_short_id("12"), Some(12));
+    assert_eq!(parse_short_id("#12345678"), Some(12345678));
+    assert_eq!(parse_short_id("12345678"), None); // Long bare numbers are ID prefixes
+    assert_eq!(parse_short_id("#0"), None);
+    assert_eq!(parse_short_id("abc"), None);
+}
+
+#[tokio::test]
+async fn test_find_active_sessions_older_than() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let other_group = Group::create(&db.pool, 54321).await?;
+    
+    let old_active = Session::create(&db.pool, group.id, "Old active".to_string(), 1).await?;
+    let old_confirmed = Session::create(&db.pool, group.id, "Old confirmed".to_string(), 1).await?;
+    let fresh_active = Session::create(&db.pool, group.id, "Fresh active".to_string(), 1).await?;
+    let old_other_group = Session::create(&db.pool, other_group.id, "Other group".to_string(), 1).await?;
+    
+    let forty_days_ago = (Utc::now() - chrono::Duration::days(40)).to_rfc3339();
+    for id in [&old_active.id, &old_confirmed.id, &old_other_group.id] {
+        sqlx::query("UPDATE sessions SET created_at = ? WHERE id = ?")
+            .bind(&forty_days_ago)
+            .bind(id)
+            .execute(&db.pool)
+            .await?;
+    }
+    sqlx::query("UPDATE sessions SET status = 'confirmed' WHERE id = ?")
+        .bind(&old_confirmed.id)
+        .execute(&db.pool)
+        .await?;
+    
+    let cutoff = Utc::now() - chrono::Duration::days(30);
+    let stale = Session::find_active_older_than(&db.pool, group.id, cutoff).await?;
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].id, old_active.id);
+    
+    // A longer threshold excludes everything, a shorter one still skips non-active sessions
+    let cutoff = Utc::now() - chrono::Duration::days(45);
+    assert!(Session::find_active_older_than(&db.pool, group.id, cutoff).await?.is_empty());
+    let cutoff = Utc::now() + chrono::Duration::minutes(1);
+    let all_active = Session::find_active_older_than(&db.pool, group.id, cutoff).await?;
+    assert_eq!(all_active.len(), 2);
+    assert!(all_active.iter().any(|s| s.id == fresh_active.id));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_created_at_sqlite_default_format() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let session = Session::create(&db.pool, group.id, "Test".to_string(), 1).await?;
+    
+    sqlx::query("UPDATE sessions SET created_at = '2020-01-02 03:04:05' WHERE id = ?")
+        .bind(&session.id)
+        .execute(&db.pool)
+        .await?;
+    
+    let session = Session::find_by_id(&db.pool, &session.id).await?.unwrap();
+    let created = session.created_at_utc().unwrap();
+    assert_eq!(created.to_rfc3339(), "2020-01-02T03:04:05+00:00");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_stale_sessions_compares_instants_across_offsets() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let confirmed = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&confirmed.id).create(&db.pool).await?;
+    let cancelled = SessionBuilder::new(group.id).status("cancelled").create(&db.pool).await?;
+    let unconfirmed = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await?;
+    let active = SessionBuilder::new(group.id).create(&db.pool).await?;
+
+    // 01:00 in UTC+2 is 23:00 UTC on the day before; cancelled at 23:30 in UTC-5 is 04:30 UTC the next day
+    sqlx::query("UPDATE session_options SET datetime = '2024-03-01T01:00:00+02:00', confirmed = 1 WHERE id = ?")
+        .bind(&option.id)
+        .execute(&db.pool)
+        .await?;
+    sqlx::query("UPDATE sessions SET created_at = '2024-03-10T23:30:00-05:00' WHERE id = ?")
+        .bind(&cancelled.id)
+        .execute(&db.pool)
+        .await?;
+    sqlx::query("UPDATE sessions SET created_at = '2024-01-01T00:00:00+00:00' WHERE id IN (?, ?)")
+        .bind(&unconfirmed.id)
+        .bind(&active.id)
+        .execute(&db.pool)
+        .await?;
+
+    let confirmed_after = chrono::Duration::days(30);
+    let cancelled_after = chrono::Duration::days(14);
+    let stale_at = |now: &str| {
+        let pool = db.pool.clone();
+        let now = chrono::DateTime::parse_from_rfc3339(now).unwrap().with_timezone(&Utc);
+        async move {
+            let stale = Session::find_stale(&pool, now, confirmed_after, cancelled_after).await.unwrap();
+            stale.into_iter().map(|s| s.id).collect::<Vec<_>>()
+        }
+    };
+
+    assert!(stale_at("2024-03-25T04:00:00Z").await.is_empty());
+    assert_eq!(stale_at("2024-03-25T05:00:00Z").await, vec![cancelled.id.clone()]);
+    // Read as UTC wall-clock time the option would only be due at 01:00 on the 31st
+    assert_eq!(stale_at("2024-03-30T22:30:00Z").await, vec![cancelled.id.clone()]);
+    assert_eq!(stale_at("2024-03-30T23:30:00Z").await.len(), 2);
+    assert!(!stale_at("2024-04-01T00:00:00Z").await.contains(&unconfirmed.id));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_archive_keeps_sessions_out_of_list_but_in_counts() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let active = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let cancelled = SessionBuilder::new(group.id).status("cancelled").create(&db.pool).await?;
+
+    assert!(!Session::archive(&db.pool, &active.id).await?);
+    assert!(Session::archive(&db.pool, &cancelled.id).await?);
+    assert!(!Session::archive(&db.pool, &cancelled.id).await?);
+    let archived = Session::find_by_id(&db.pool, &cancelled.id).await?.unwrap();
+    assert_eq!(archived.status, "archived");
+    check_session(&archived, &[], &[])?;
+
+    let (listed, total) = Session::find_by_group_paginated(&db.pool, group.id, 10, 0, &["active", "confirmed"]).await?;
+    assert_eq!(total, 1);
+    assert_eq!(listed[0].id, active.id);
+    assert_eq!(Session::count_by_group(&db.pool).await?, vec![(group.id, 2)]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_roster_members_join_once() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let other = Group::create(&db.pool, 67890).await?;
+    
+    assert!(RosterMember::add(&db.pool, group.id, 42, Some("newbie".to_string())).await?);
+    assert!(!RosterMember::add(&db.pool, group.id, 42, Some("renamed".to_string())).await?);
+    assert!(RosterMember::add(&db.pool, group.id, 7, None).await?);
+    assert!(RosterMember::add(&db.pool, other.id, 42, None).await?);
+    
+    let roster = RosterMember::find_by_group(&db.pool, group.id).await?;
+    assert_eq!(roster.len(), 2);
+    let newbie = roster.iter().find(|m| m.user_id == 42).unwrap();
+    assert_eq!(newbie.username.as_deref(), Some("newbie"));
+    assert!(roster.iter().any(|m| m.user_id == 7 && m.username.is_none()));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_notes_set_and_clear() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let session = Session::create(&db.pool, group.id, "Where Are We".to_string(), 1).await?;
+    assert_eq!(session.notes, None);
+    
+    Session::set_notes(&db.pool, &session.id, Some("Roll20, link in the pinned message")).await?;
+    let found = Session::find_by_id(&db.pool, &session.id).await?.unwrap();
+    assert_eq!(found.notes.as_deref(), Some("Roll20, link in the pinned message"));
+    
+    // Notes come along with every session query
+    let listed = Session::find_by_group(&db.pool, group.id).await?;
+    assert_eq!(listed[0].notes, found.notes);
+    
+    Session::set_notes(&db.pool, &session.id, None).await?;
+    let found = Session::find_by_id(&db.pool, &session.id).await?.unwrap();
+    assert_eq!(found.notes, None);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_set_message_id() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let session = Session::create(&db.pool, group.id, "Lost Poll".to_string(), 1).await?;
+    assert_eq!(session.message_id, None);
+    
+    Session::set_message_id(&db.pool, &session.id, 100).await?;
+    let found = Session::find_by_id(&db.pool, &session.id).await?.unwrap();
+    assert_eq!(found.message_id, Some(100));
+    
+    // A repost replaces the tracked message
+    Session::set_message_id(&db.pool, &session.id, 250).await?;
+    let found = Session::find_by_id(&db.pool, &session.id).await?.unwrap();
+    assert_eq!(found.message_id, Some(250));
+    
+    Ok(())
+}
+
+#[test]
+fn test_poll_is_outdated() {
+    let now = Utc::now();
+    let ago = |days: i64| (now - chrono::Duration::days(days)).to_rfc3339();
+    let mut session = Session {
+        id: "s".to_string(),
+        group_id: 1,
+        title: "Old poll".to_string(),
+        message_id: Some(100),
+        status: "active".to_string(),
+        deadline: None,
+        created_by: 1,
+        created_at: ago(40),
+        short_id: Some(1),
+        anonymous: false,
+        notes: None,
+        availability: false,
+        reminders_muted: false,
+        last_rendered_at: None,
+        updated_at: None,
+    };
+    
+    // Rendered after the last change, and recently enough
+    session.last_rendered_at = Some(ago(2));
+    session.updated_at = Some(ago(3));
+    assert!(!session.poll_is_outdated(now));
+    session.updated_at = None;
+    assert!(!session.poll_is_outdated(now));
+    
+    // The session changed since
+    session.updated_at = Some(ago(1));
+    assert!(session.poll_is_outdated(now));
+    
+    // Nothing changed, but the poll hasn't been touched in over a month
+    session.updated_at = Some(ago(POLL_RERENDER_AFTER_DAYS + 2));
+    session.last_rendered_at = Some(ago(POLL_RERENDER_AFTER_DAYS + 1));
+    assert!(session.poll_is_outdated(now));
+    session.last_rendered_at = Some(ago(POLL_RERENDER_AFTER_DAYS - 1));
+    assert!(!session.poll_is_outdated(now));
+    
+    // Polls from before rendering was tracked count from their creation
+    session.last_rendered_at = None;
+    session.updated_at = None;
+    assert!(session.poll_is_outdated(now));
+    session.created_at = ago(1);
+    assert!(!session.poll_is_outdated(now));
+}
+
+#[tokio::test]
+async fn test_session_changes_outdate_its_poll() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    let reload = || async { Session::find_by_id(&db.pool, &session.id).await.map(Option::unwrap) };
+    // The triggers' timestamps are to the millisecond
+    let tick = || tokio::time::sleep(std::time::Duration::from_millis(5));
+    
+    // Posting the poll renders it
+    tick().await;
+    Session::set_message_id(&db.pool, &session.id, 100).await?;
+    assert!(!reload().await?.poll_is_outdated(Utc::now()));
+    
+    // Adding an option, or setting notes, changes what the poll should show
+    tick().await;
+    SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    assert!(reload().await?.poll_is_outdated(Utc::now()));
+    tick().await;
+    Session::mark_rendered(&db.pool, &session.id).await?;
+    assert!(!reload().await?.poll_is_outdated(Utc::now()));
+    tick().await;
+    Session::set_notes(&db.pool, &session.id, Some("Bring snacks")).await?;
+    assert!(reload().await?.poll_is_outdated(Utc::now()));
+    
+    // A new vote doesn't; the poll is edited after every vote anyway
+    tick().await;
+    Session::mark_rendered(&db.pool, &session.id).await?;
+    tick().await;
+    Response::upsert(&db.pool, session.id.clone(), SessionOption::find_by_session(&db.pool, &session.id).await?[0].id.clone(), 1, None, "yes".to_string()).await?;
+    assert!(!reload().await?.poll_is_outdated(Utc::now()));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reposted_poll_keeps_current_counts() -> Result<()> {
+    use dnd_scheduler_bot::bot::poll::{render_poll, render_reposted_poll};
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let session = Session::create(&db.pool, group.id, "Lost Poll".to_string(), 1).await?;
+    let option = SessionOption::create(&db.pool, session.id.clone(), Utc::now(), 240).await?;
+    Response::upsert(&db.pool, session.id.clone(), option.id.clone(), 1, None, "yes".to_string()).await?;
+    Response::upsert(&db.pool, session.id.clone(), option.id.clone(), 2, None, "maybe".to_string()).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    
+    let options = [option];
+    let (text, keyboard) = render_reposted_poll(&session, &options, &responses, None);
+    let (poll_text, poll_keyboard) = render_poll(&session, &options, &responses, None);
+    assert!(text.starts_with("🔁 _Reposted poll, please vote on this message from now on_\n\n"));
+    assert!(text.ends_with(&poll_text));
+    assert!(text.contains("✅ 1 • ❌ 0 • ❓ 1"));
+    assert_eq!(keyboard, poll_keyboard);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_private_poll_votes_like_the_group_poll() -> Result<()> {
+    use dnd_scheduler_bot::bot::poll::{poll_keyboard, render_poll, render_private_poll};
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    Response::upsert(&db.pool, session.id.clone(), option.id.clone(), 1, None, "yes".to_string()).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    
+    let options = [option];
+    let (text, keyboard) = render_private_poll(&session, &options, &responses, None);
+    let (poll_text, _) = render_poll(&session, &options, &responses, None);
+    assert!(text.starts_with("🔒 _Your private copy of the poll; votes here also update the group's poll_\n\n"));
+    assert!(text.ends_with(&poll_text));
+    // Same callback data as in the group, so the vote lands on the same session
+    assert_eq!(keyboard, poll_keyboard(&session, &options, &responses));
+    assert_eq!(keyboard.inline_keyboard[0].len(), 4);
+    
+    Ok(())
+}
+
+#[test]
+fn test_repost_throttle_allows_one_repost_per_window() {
+    use dnd_scheduler_bot::bot::poll::{RepostThrottle, AUTO_REPOST_COOLDOWN};
+    
+    let throttle = RepostThrottle::default();
+    let start = std::time::Instant::now();
+    assert!(throttle.try_acquire("session-a", start));
+    assert!(!throttle.try_acquire("session-a", start + AUTO_REPOST_COOLDOWN / 2));
+    // Other sessions have their own window
+    assert!(throttle.try_acquire("session-b", start + AUTO_REPOST_COOLDOWN / 2));
+    assert!(throttle.try_acquire("session-a", start + AUTO_REPOST_COOLDOWN));
+}
+
+#[test]
+fn test_vote_throttle_is_per_user_and_session() {
+    use dnd_scheduler_bot::bot::poll::{VoteThrottle, VOTE_COOLDOWN};
+    
+    let throttle = VoteThrottle::default();
+    let start = std::time::Instant::now();
+    assert!(throttle.try_acquire(1, "session-a", start));
+    assert!(!throttle.try_acquire(1, "session-a", start + VOTE_COOLDOWN / 2));
+    // Other players and other sessions aren't held up
+    assert!(throttle.try_acquire(2, "session-a", start + VOTE_COOLDOWN / 2));
+    assert!(throttle.try_acquire(1, "session-b", start + VOTE_COOLDOWN / 2));
+    assert!(throttle.try_acquire(1, "session-a", start + VOTE_COOLDOWN));
+}
+
+#[tokio::test]
+async fn test_poll_highlights_leading_options() -> Result<()> {
+    use dnd_scheduler_bot::bot::poll::{leading_option_ids, render_poll};
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let mut session = Session::create(&db.pool, group.id, "Leaderboard".to_string(), 1).await?;
+    let first = SessionOption::create(&db.pool, session.id.clone(), Utc::now(), 240).await?;
+    let second = SessionOption::create(&db.pool, session.id.clone(), Utc::now() + chrono::Duration::days(1), 240).await?;
+    let options = vec![first.clone(), second.clone()];
+    
+    // No yes votes yet, so nobody leads
+    Response::upsert(&db.pool, session.id.clone(), first.id.clone(), 1, None, "maybe".to_string()).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    assert!(leading_option_ids(&options, &responses).is_empty());
+    let (text, _keyboard) = render_poll(&session, &options, &responses, None);
+    assert!(!text.contains("⭐ Leading:"));
+    
+    // Equal yes votes: the extra maybe breaks the tie
+    Response::upsert(&db.pool, session.id.clone(), first.id.clone(), 2, None, "yes".to_string()).await?;
+    Response::upsert(&db.pool, session.id.clone(), second.id.clone(), 3, None, "yes".to_string()).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    assert_eq!(leading_option_ids(&options, &responses), vec![first.id.clone()]);
+    let (text, _keyboard) = render_poll(&session, &options, &responses, None);
+    assert_eq!(text.matches("⭐ Leading:").count(), 1);
+    assert!(text.contains("⭐ Leading: **1\\."));
+    
+    // A full tie stars both options
+    Response::upsert(&db.pool, session.id.clone(), second.id.clone(), 4, None, "maybe".to_string()).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    assert_eq!(leading_option_ids(&options, &responses).len(), 2);
+    
+    // The weighted score decides, as in /confirm: 1 yes and 4 maybe (3.0) lead 2 yes and 1 maybe (2.5)
+    Response::upsert(&db.pool, session.id.clone(), first.id.clone(), 5, None, "yes".to_string()).await?;
+    for user_id in 6..=8 {
+        Response::upsert(&db.pool, session.id.clone(), second.id.clone(), user_id, None, "maybe".to_string()).await?;
+    }
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    assert_eq!(leading_option_ids(&options, &responses), vec![second.id.clone()]);
+    
+    // Once voting is over the star goes away
+    session.status = "confirmed".to_string();
+    let (text, _keyboard) = render_poll(&session, &options, &responses, None);
+    assert!(!text.contains("⭐ Leading:"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_options_on_the_same_day_share_a_heading() {
+    use chrono::TimeZone;
+    use dnd_scheduler_bot::bot::poll::{label_options, render_poll};
+    
+    let option = |id: &str, datetime: String| SessionOption {
+        id: id.to_string(),
+        session_id: "s".to_string(),
+        datetime,
+        duration: 240,
+        confirmed: false,
+    };
+    let at = |day, hour| Utc.with_ymd_and_hms(2030, 12, day, hour, 0, 0).unwrap().to_rfc3339();
+    let options = vec![
+        option("a", at(13, 19)),
+        option("b", at(14, 14)),
+        option("c", at(14, 19)),
+        option("d", at(14, 21)),
+        option("e", "whenever".to_string()),
+        option("f", at(15, 18)),
+    ];
+    
+    let labels = label_options(&options);
+    let shown: Vec<(Option<&str>, bool, &str)> = labels.iter()
+        .map(|label| (label.day_heading.as_deref(), label.under_heading, label.when.as_str()))
+        .collect();
+    assert_eq!(shown, vec![
+        (None, false, "Friday, 13 December at 19:00"),
+        (Some("Saturday, 14 December"), true, "14:00"),
+        (None, true, "19:00"),
+        (None, true, "21:00"),
+        (None, false, "whenever"),
+        (None, false, "Sunday, 15 December at 18:00"),
+    ]);
+    assert!(label_options::<SessionOption>(&[]).is_empty());
+    
+    let session = Session {
+        id: "s".to_string(),
+        group_id: 1,
+        title: "Split day".to_string(),
+        message_id: None,
+        status: "active".to_string(),
+        deadline: None,
+        created_by: 1,
+        created_at: at(1, 12),
+        short_id: Some(1),
+        anonymous: false,
+        notes: None,
+        availability: false,
+        reminders_muted: false,
+        last_rendered_at: None,
+        updated_at: None,
+    };
+    let (text, keyboard) = render_poll(&session, &options, &[], None);
+    assert_eq!(text.matches("📅 **Saturday, 14 December**").count(), 1);
+    assert!(text.contains("📅 **Saturday, 14 December**\n**2\\. 14:00**\n"), "{text}");
+    assert!(text.contains("**3\\. 19:00**\n"), "{text}");
+    assert!(text.contains("**1\\. Friday, 13 December at 19:00**\n"), "{text}");
+    // Every option still gets its own row of buttons
+    assert_eq!(keyboard.inline_keyboard.len(), options.len());
+}
+
+#[tokio::test]
+async fn test_poll_footer_counts_voters() -> Result<()> {
+    use dnd_scheduler_bot::bot::poll::{expected_voters, participation_line, render_poll};
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let mut session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let first = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    let second = SessionOptionBuilder::new(&session.id).at(Utc::now() + chrono::Duration::days(3)).create(&db.pool).await?;
+    let options = vec![first.clone(), second.clone()];
+    
+    // A new group with no roster and no earlier polls can't say how many players to expect
+    assert_eq!(expected_voters(&db.pool, &session).await?, None);
+    let (text, _keyboard) = render_poll(&session, &options, &[], None);
+    assert!(text.ends_with("📊 Nobody has voted yet\n"), "{text}");
+    for user_id in 1..=4 {
+        ResponseBuilder::new(&first).user(user_id, None).create(&db.pool).await?;
+    }
+    ResponseBuilder::new(&second).user(1, None).create(&db.pool).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    let (text, _keyboard) = render_poll(&session, &options, &responses, None);
+    assert!(text.ends_with("📊 4 players have voted\n"), "{text}");
+    
+    // Players who voted on earlier polls are expected, then the roster once there is one
+    let next = SessionBuilder::new(group.id).create(&db.pool).await?;
+    assert_eq!(expected_voters(&db.pool, &next).await?, Some(4));
+    for user_id in [1, 2, 3, 5, 6, 7, 8, 9] {
+        RosterMember::add(&db.pool, group.id, user_id, None).await?;
+    }
+    let expected = expected_voters(&db.pool, &session).await?;
+    assert_eq!(expected, Some(8));
+    let (text, _keyboard) = render_poll(&session, &options, &responses, expected);
+    assert!(text.ends_with("📊 4 of 8 players have voted\n"), "{text}");
+    
+    // Voters beyond the expected number still count, and grammar follows the numbers
+    assert_eq!(participation_line(&responses, Some(2)), "📊 4 of 4 players have voted");
+    assert_eq!(participation_line(&responses[..1], Some(1)), "📊 1 of 1 player has voted");
+    assert_eq!(participation_line(&responses[..1], None), "📊 1 player has voted");
+    
+    // The footer is about an open poll, so it goes away once it's settled
+    session.status = "confirmed".to_string();
+    let (text, _keyboard) = render_poll(&session, &options, &responses, expected);
+    assert!(!text.contains("📊"), "{text}");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_anonymous_session_hides_votes_until_confirmed() -> Result<()> {
+    use dnd_scheduler_bot::bot::poll::render_poll;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let session = Session::create(&db.pool, group.id, "Secret Ballot".to_string(), 1).await?;
+    assert!(!session.anonymous);
+    
+    Session::set_anonymous(&db.pool, &session.id, true).await?;
+    let mut session = Session::find_by_id(&db.pool, &session.id).await?.unwrap();
+    assert!(session.anonymous);
+    assert!(session.hides_votes());
+    
+    let option = SessionOption::create(&db.pool, session.id.clone(), Utc::now(), 240).await?;
+    Response::upsert(&db.pool, session.id.clone(), option.id.clone(), 42, None, "yes".to_string()).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    
+    let (text, _keyboard) = render_poll(&session, std::slice::from_ref(&option), &responses, None);
+    assert!(text.contains("votes hidden"));
+    assert!(!text.contains("✅ 1"));
+    
+    session.status = "confirmed".to_string();
+    assert!(!session.hides_votes());
+    let (text, _keyboard) = render_poll(&session, &[option], &responses, None);
+    assert!(!text.contains("votes hidden"));
+    assert!(text.contains("✅ 1 • ❌ 0 • ❓ 0"));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_group_voter_ids() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, 12345).await?;
+    let other_group = Group::create(&db.pool, 67890).await?;
+    
+    let first = Session::create(&db.pool, group.id, "First".to_string(), 1).await?;
+    let second = Session::create(&db.pool, group.id, "Second".to_string(), 1).await?;
+    let elsewhere = Session::create(&db.pool, other_group.id, "Elsewhere".to_string(), 1).await?;
+    
+    for (session, user_id) in [(&first, 10), (&first, 11), (&second, 11), (&second, 12), (&elsewhere, 99)] {
+        let option = SessionOption::create(&db.pool, session.id.clone(), Utc::now(), 240).await?;
+        Response::upsert(&db.pool, session.id.clone(), option.id, user_id, None, "yes".to_string()).await?;
+    }
+    
+    let mut voters = Response::find_group_voter_ids(&db.pool, group.id).await?;
+    voters.sort();
+    assert_eq!(voters, vec![10, 11, 12]);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_default_duration_applies_to_new_options() -> Result<()> {
+    use dnd_scheduler_bot::scheduling::SchedulerApi;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    assert_eq!(group.default_duration, 240);
+    
+    Group::update_default_duration(&db.pool, group.id, 90).await?;
+    let group = Group::find_by_chat_id(&db.pool, group.telegram_chat_id).await?.unwrap();
+    assert_eq!(group.default_duration, 90);
+    
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let datetimes = [Utc::now() + chrono::Duration::days(1), Utc::now() + chrono::Duration::days(2)];
+    let api = SchedulerApi::new(db.pool.clone());
+    for datetime in datetimes {
+        api.add_option(&session.id, datetime, None).await?;
+    }
+    
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    assert_eq!(options.len(), 2);
+    for option in options {
+        assert_eq!(option.duration, 90);
+    }
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_inline_durations_override_group_default() -> Result<()> {
+    use dnd_scheduler_bot::bot::commands::list::render_list_page;
+    use dnd_scheduler_bot::scheduling::SchedulerApi;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let api = SchedulerApi::new(db.pool.clone());
+    api.add_option(&session.id, Utc::now() + chrono::Duration::days(1), Some(120)).await?;
+    api.add_option(&session.id, Utc::now() + chrono::Duration::days(2), None).await?;
+    
+    let mut durations: Vec<i64> = SessionOption::find_by_session(&db.pool, &session.id).await?
+        .iter()
+        .map(|option| option.duration)
+        .collect();
+    durations.sort();
+    assert_eq!(durations, vec![120, group.default_duration]);
+    
+    // /list shows how long each option lasts
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    let (text, _keyboard) = render_list_page(&[session], &options, &[], &NotesByOption::default(), 0, &[], 0, 1, 1);
+    assert!(text.contains("⏱️ 2 hours"));
+    assert!(text.contains("⏱️ 4 hours"));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_default_duration_range() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    
+    for minutes in [MIN_DEFAULT_DURATION, MAX_DEFAULT_DURATION] {
+        assert!(Group::update_default_duration(&db.pool, group.id, minutes).await.is_ok());
+    }
+    for minutes in [0, MIN_DEFAULT_DURATION - 1, MAX_DEFAULT_DURATION + 1] {
+        assert!(matches!(
+            Group::update_default_duration(&db.pool, group.id, minutes).await,
+            Err(GroupSettingError::DurationOutOfRange(m)) if m == minutes
+        ));
+    }
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_update_setting() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    assert_eq!(group.timezone, "UTC");
+    
+    Group::update_setting(&db.pool, group.id, &GroupSetting::Timezone("UTC+2".to_string())).await?;
+    Group::update_setting(&db.pool, group.id, &GroupSetting::DefaultDuration(150)).await?;
+    Group::update_setting(&db.pool, group.id, &GroupSetting::MinNoticeHours(12)).await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert_eq!((group.timezone.as_str(), group.default_duration, group.min_notice_hours), ("UTC+2", 150, 12));
+    
+    // Invalid values are rejected without touching what's stored
+    assert!(matches!(
+        Group::update_setting(&db.pool, group.id, &GroupSetting::Timezone("Mars/Olympus".to_string())).await,
+        Err(GroupSettingError::UnknownTimezone(timezone)) if timezone == "Mars/Olympus"
+    ));
+    assert!(matches!(
+        Group::update_setting(&db.pool, group.id, &GroupSetting::DefaultDuration(MAX_DEFAULT_DURATION + 1)).await,
+        Err(GroupSettingError::DurationOutOfRange(_))
+    ));
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert_eq!((group.timezone.as_str(), group.default_duration), ("UTC+2", 150));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_language() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    assert_eq!(group.language, "en");
+    
+    Group::update_language(&db.pool, group.id, "sv").await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert_eq!(group.language, "sv");
+    
+    assert!(matches!(
+        Group::update_language(&db.pool, group.id, "nl").await,
+        Err(GroupSettingError::UnsupportedLanguage(code)) if code == "nl"
+    ));
+    assert_eq!(Group::find_by_id(&db.pool, group.id).await?.unwrap().language, "sv");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_min_notice_range() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    assert_eq!(group.min_notice_hours, 0);
+    
+    for hours in [0, MAX_MIN_NOTICE_HOURS] {
+        assert!(Group::update_min_notice_hours(&db.pool, group.id, hours).await.is_ok());
+    }
+    for hours in [-1, MAX_MIN_NOTICE_HOURS + 1] {
+        assert!(matches!(
+            Group::update_min_notice_hours(&db.pool, group.id, hours).await,
+            Err(GroupSettingError::MinNoticeOutOfRange(h)) if h == hours
+        ));
+    }
+    
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert_eq!(group.min_notice_hours, MAX_MIN_NOTICE_HOURS);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_long_range_reminders() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    assert_eq!(group.long_range_every_days, None);
+    assert_eq!(group.long_range_until_days, DEFAULT_LONG_RANGE_UNTIL_DAYS);
+    
+    Group::update_long_range_reminders(&db.pool, group.id, Some((30, 21))).await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert_eq!((group.long_range_every_days, group.long_range_until_days), (Some(30), 21));
+    
+    assert!(matches!(
+        Group::update_long_range_reminders(&db.pool, group.id, Some((1, 14))).await,
+        Err(GroupSettingError::LongRangeEveryOutOfRange(1))
+    ));
+    assert!(matches!(
+        Group::update_long_range_reminders(&db.pool, group.id, Some((30, 0))).await,
+        Err(GroupSettingError::LongRangeUntilOutOfRange(0))
+    ));
+    assert_eq!(Group::find_by_id(&db.pool, group.id).await?.unwrap().long_range_every_days, Some(30));
+    
+    Group::update_long_range_reminders(&db.pool, group.id, None).await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert_eq!((group.long_range_every_days, group.long_range_until_days), (None, DEFAULT_LONG_RANGE_UNTIL_DAYS));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_whats_new_is_announced_once_per_version() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = Group::create(&db.pool, -100777).await?;
+    // New groups start at the running version, with nothing to catch up on
+    assert_eq!(group.announced_version.as_deref(), Some(CURRENT_VERSION));
+    assert!(group.whats_new);
+    
+    // A group from before the announcements claims the upgrade once
+    sqlx::query("UPDATE groups SET announced_version = NULL WHERE id = ?").bind(group.id).execute(&db.pool).await?;
+    assert!(Group::mark_announced(&db.pool, group.id, None, "0.2.0").await?);
+    assert!(!Group::mark_announced(&db.pool, group.id, None, "0.2.0").await?);
+    
+    // The next release is claimed from the version now stored, and only once
+    assert!(!Group::mark_announced(&db.pool, group.id, Some("0.1.0"), "0.3.0").await?);
+    assert!(Group::mark_announced(&db.pool, group.id, Some("0.2.0"), "0.3.0").await?);
+    assert!(!Group::mark_announced(&db.pool, group.id, Some("0.2.0"), "0.3.0").await?);
+    assert_eq!(Group::find_by_id(&db.pool, group.id).await?.unwrap().announced_version.as_deref(), Some("0.3.0"));
+    
+    Group::update_whats_new(&db.pool, group.id, false).await?;
+    assert!(!Group::find_by_id(&db.pool, group.id).await?.unwrap().whats_new);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_quiet_hours() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    assert_eq!((group.quiet_start_hour, group.quiet_end_hour), (None, None));
+    
+    Group::update_quiet_hours(&db.pool, group.id, Some((22, 7))).await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert_eq!((group.quiet_start_hour, group.quiet_end_hour), (Some(22), Some(7)));
+    
+    assert!(matches!(
+        Group::update_quiet_hours(&db.pool, group.id, Some((22, 24))).await,
+        Err(GroupSettingError::QuietHourOutOfRange(24))
+    ));
+    assert!(matches!(
+        Group::update_quiet_hours(&db.pool, group.id, Some((8, 8))).await,
+        Err(GroupSettingError::QuietHoursEmpty)
+    ));
+    assert_eq!(Group::find_by_id(&db.pool, group.id).await?.unwrap().quiet_start_hour, Some(22));
+    
+    Group::update_quiet_hours(&db.pool, group.id, None).await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert_eq!((group.quiet_start_hour, group.quiet_end_hour), (None, None));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_admins() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let other_group = GroupBuilder::new().chat_id(-1009876543210).create(&db.pool).await?;
+    
+    assert!(!GroupAdmin::is_admin(&db.pool, group.id, 42).await?);
+    assert!(GroupAdmin::add(&db.pool, group.id, 42, 1).await?);
+    assert!(!GroupAdmin::add(&db.pool, group.id, 42, 1).await?);
+    assert!(GroupAdmin::add(&db.pool, group.id, 43, 1).await?);
+    
+    assert!(GroupAdmin::is_admin(&db.pool, group.id, 42).await?);
+    assert!(!GroupAdmin::is_admin(&db.pool, other_group.id, 42).await?);
+    let admins: Vec<i64> = GroupAdmin::find_by_group(&db.pool, group.id).await?.iter().map(|a| a.user_id).collect();
+    assert_eq!(admins, vec![42, 43]);
+    
+    assert!(GroupAdmin::remove(&db.pool, group.id, 42).await?);
+    assert!(!GroupAdmin::remove(&db.pool, group.id, 42).await?);
+    assert!(!GroupAdmin::is_admin(&db.pool, group.id, 42).await?);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_delete_cascades() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let kept = SessionBuilder::new(group.id).create(&db.pool).await?;
+    
+    for session in [&session, &kept] {
+        let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+        ResponseBuilder::new(&option).user(1, Some("alice")).create(&db.pool).await?;
+        Reminder::create(&db.pool, session.id.clone(), 7).await?;
+    }
+    
+    assert!(Session::delete(&db.pool, &session.id).await?);
+    assert!(!Session::delete(&db.pool, &session.id).await?);
+    
+    assert!(Session::find_by_id(&db.pool, &session.id).await?.is_none());
+    assert!(SessionOption::find_by_session(&db.pool, &session.id).await?.is_empty());
+    assert!(Response::find_by_session(&db.pool, &session.id).await?.is_empty());
+    assert!(Reminder::find_by_session(&db.pool, &session.id).await?.is_empty());
+    
+    // Other sessions are untouched
+    assert_eq!(SessionOption::find_by_session(&db.pool, &kept.id).await?.len(), 1);
+    assert_eq!(Response::find_by_session(&db.pool, &kept.id).await?.len(), 1);
+    assert_eq!(Reminder::find_by_session(&db.pool, &kept.id).await?.len(), 1);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_check_ins() -> Result<()> {
+    use chrono::TimeZone;
+    use dnd_scheduler_bot::bot::commands::checkins::render_check_ins;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).title("Game Night!").status("confirmed").create(&db.pool).await?;
+    
+    assert!(CheckIn::record(&db.pool, &session.id, 1).await?);
+    assert!(!CheckIn::record(&db.pool, &session.id, 1).await?);
+    assert!(CheckIn::record(&db.pool, &session.id, 2).await?);
+    let check_ins = CheckIn::find_by_session(&db.pool, &session.id).await?;
+    assert_eq!(check_ins.iter().map(|c| c.user_id).collect::<Vec<_>>(), vec![1, 2]);
+    
+    // Check-ins go with the session
+    Session::delete(&db.pool, &session.id).await?;
+    assert!(CheckIn::find_by_session(&db.pool, &session.id).await?.is_empty());
+    
+    let at = Utc.with_ymd_and_hms(2026, 3, 10, 17, 5, 0).unwrap();
+    let text = render_check_ins(&session, &[("alice".to_string(), at)], &["bob_the_bard".to_string()]);
+    assert!(text.contains("Check\\-ins for Game Night\\!"));
+    assert!(text.contains("Checked in \\(1\\)"));
+    assert!(text.contains("• alice at 17:05 UTC"));
+    assert!(text.contains("no check\\-in \\(1\\)"));
+    assert!(text.contains("• bob\\_the\\_bard"));
+    
+    let text = render_check_ins(&session, &[], &[]);
+    assert!(text.contains("Nobody yet"));
+    assert!(text.contains("Everyone who said yes has checked in"));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_csv_for_group() -> Result<()> {
+    use dnd_scheduler_bot::bot::commands::export::build_export_csv;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let other_group = GroupBuilder::new().chat_id(-1009876543210).create(&db.pool).await?;
+    
+    let session = SessionBuilder::new(group.id).title("Tomb, of \"Horrors\"").create(&db.pool).await?;
+    let voted = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    let unvoted = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    ResponseBuilder::new(&voted).user(1, Some("alice")).create(&db.pool).await?;
+    ResponseBuilder::new(&voted).user(2, None).response("maybe").create(&db.pool).await?;
+    
+    let elsewhere = SessionBuilder::new(other_group.id).create(&db.pool).await?;
+    let elsewhere_option = SessionOptionBuilder::new(&elsewhere.id).create(&db.pool).await?;
+    ResponseBuilder::new(&elsewhere_option).create(&db.pool).await?;
+    
+    let responses = Response::find_all_by_group(&db.pool, group.id).await?;
+    assert_eq!(responses.len(), 2);
+    
+    let sessions = Session::find_by_group(&db.pool, group.id).await?;
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    let csv = build_export_csv(&sessions, &options, &responses);
+    let lines: Vec<&str> = csv.split("\r\n").filter(|l| !l.is_empty()).collect();
+    
+    assert_eq!(lines[0], "session_title,option_datetime,username,response,created_at");
+    assert_eq!(lines.len(), 4);
+    assert!(lines[1..].iter().all(|l| l.starts_with("\"Tomb, of \"\"Horrors\"\"\",")));
+    assert!(lines.iter().any(|l| l.contains(",alice,yes,")));
+    assert!(lines.iter().any(|l| l.contains(",,maybe,")));
+    assert!(lines.iter().any(|l| *l == format!("\"Tomb, of \"\"Horrors\"\"\",{},,,", unvoted.datetime)));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_archive_round_trip() -> Result<()> {
+    use dnd_scheduler_bot::bot::commands::export::archive_file;
+    use dnd_scheduler_bot::database::transfer::*;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().chat_id(-1001111111111).create(&db.pool).await?;
+    Group::update_language(&db.pool, group.id, "de").await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    let session = SessionBuilder::new(group.id).title("Tomb of Horrors").status("confirmed").create(&db.pool).await?;
+    Session::set_message_id(&db.pool, &session.id, 42).await?;
+    SessionBuilder::new(group.id).title("Cancelled one").status("cancelled").create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    ResponseBuilder::new(&option).user(1, Some("alice")).create(&db.pool).await?;
+    ResponseBuilder::new(&option).user(2, None).response("maybe").create(&db.pool).await?;
+    Reminder::create(&db.pool, session.id.clone(), 3 * 1440).await?;
+    let elsewhere = GroupBuilder::new().chat_id(-1002222222222).create(&db.pool).await?;
+    SessionBuilder::new(elsewhere.id).create(&db.pool).await?;
+    
+    let archive = export_group(&db.pool, &group).await?;
+    assert_eq!(archive.format_version, ARCHIVE_FORMAT_VERSION);
+    assert_eq!((archive.sessions.len(), archive.options.len(), archive.responses.len(), archive.reminders.len()), (2, 1, 2, 1));
+    assert_eq!(archive.stats.sessions_by_status.get("confirmed"), Some(&1));
+    assert_eq!(archive.stats.responses_by_answer.get("maybe"), Some(&1));
+    assert_eq!(archive.stats.voters, 2);
+    
+    // The IDs are still taken in this database
+    let parsed: GroupArchive = serde_json::from_slice(&archive_file(&archive)?)?;
+    assert!(matches!(import_group(&db.pool, &parsed, None).await, Err(ImportError::ChatTaken(-1001111111111))));
+    assert!(matches!(import_group(&db.pool, &parsed, Some(-1003333333333)).await, Err(ImportError::Database(_))));
+    assert!(Group::find_by_chat_id(&db.pool, -1003333333333).await?.is_none());
+    
+    let (fresh, _fresh_dir) = test_db().await?;
+    let report = import_group(&fresh.pool, &parsed, Some(-1003333333333)).await?;
+    assert_eq!((report.sessions, report.options, report.responses, report.reminders), (2, 1, 2, 1));
+    let imported = Group::find_by_chat_id(&fresh.pool, -1003333333333).await?.unwrap();
+    assert_eq!(imported.id, report.group_id);
+    assert_eq!(imported.language, "de");
+    let sessions = Session::find_by_group(&fresh.pool, imported.id).await?;
+    let tomb = sessions.iter().find(|s| s.id == session.id).unwrap();
+    assert_eq!((tomb.status.as_str(), tomb.short_id, tomb.message_id), ("confirmed", session.short_id, None));
+    assert_eq!(Response::find_by_session(&fresh.pool, &session.id).await?.len(), 2);
+    assert_eq!(Reminder::find_by_session(&fresh.pool, &session.id).await?[0].offset_minutes, 3 * 1440);
+    
+    let mut newer = parsed.clone();
+    newer.format_version += 1;
+    assert!(matches!(import_group(&fresh.pool, &newer, Some(-1004444444444)).await, Err(ImportError::UnsupportedVersion(_))));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_store_usernames_off_uses_stable_pseudonyms() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let other_group = GroupBuilder::new().chat_id(-1009876543210).create(&db.pool).await?;
+    assert!(group.store_usernames);
+    
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    ResponseBuilder::new(&option).user(1, Some("alice")).create(&db.pool).await?;
+    
+    // Turning the setting off erases usernames already stored
+    Group::update_store_usernames(&db.pool, group.id, false).await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    assert!(!group.store_usernames);
+    
+    let bob = ResponseBuilder::new(&option).user(2, Some("bob")).create(&db.pool).await?;
+    assert_eq!(bob.username, None);
+    
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    assert!(responses.iter().all(|r| r.username.is_none()));
+    
+    // Bob got a number when voting, Alice when first displayed
+    let voters: Vec<_> = responses.iter().map(|r| (r.user_id, r.username.as_deref())).collect();
+    let names = display_names(&db.pool, &group, &voters).await?;
+    assert_eq!(names[&2], "Player #1");
+    assert_eq!(names[&1], "Player #2");
+    assert_eq!(PlayerPseudonym::number_for(&db.pool, group.id, 1).await?, 2);
+    assert_eq!(PlayerPseudonym::number_for(&db.pool, other_group.id, 1).await?, 1);
+    
+    // Turning it back on doesn't reveal earlier votes, only new ones carry usernames
+    Group::update_store_usernames(&db.pool, group.id, true).await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    ResponseBuilder::new(&option).user(3, Some("carol")).create(&db.pool).await?;
+    
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    let voters: Vec<_> = responses.iter().map(|r| (r.user_id, r.username.as_deref())).collect();
+    let names = display_names(&db.pool, &group, &voters).await?;
+    assert_eq!(names[&1], "Player #2");
+    assert_eq!(names[&2], "Player #1");
+    assert_eq!(names[&3], "carol");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_results_message_names_voters_like_the_poll() -> Result<()> {
+    use dnd_scheduler_bot::bot::commands::results::results_message;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).title("Tomb of Annihilation").create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    ResponseBuilder::new(&option).user(1, Some("alice")).create(&db.pool).await?;
+    ResponseBuilder::new(&option).user(2, Some("bob")).response("no").create(&db.pool).await?;
+    
+    let text = results_message(&db.pool, &group, &session).await?;
+    assert!(text.starts_with("📊 **Results for Tomb of Annihilation**"), "{text}");
+    assert!(text.contains("```\n"), "{text}");
+    assert!(text.contains("alice bob"), "{text}");
+    assert!(text.ends_with("📊 2 players have voted"), "{text}");
+    
+    // Groups that don't keep usernames see pseudonyms instead
+    Group::update_store_usernames(&db.pool, group.id, false).await?;
+    let group = Group::find_by_id(&db.pool, group.id).await?.unwrap();
+    let text = results_message(&db.pool, &group, &session).await?;
+    assert!(!text.contains("alice"), "{text}");
+    assert!(text.contains("Player #"), "{text}");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_concurrent_upserts_do_not_hit_busy() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    
+    let tasks: Vec<_> = (0..50)
+        .map(|user_id| {
+            let pool = db.pool.clone();
+            let option = option.clone();
+            tokio::spawn(async move {
+                ResponseBuilder::new(&option).user(user_id, Some("player")).create(&pool).await
+            })
+        })
+        .collect();
+    
+    for task in tasks {
+        if let Err(e) = task.await? {
+            panic!("concurrent upsert failed: {e}");
+        }
+    }
+    
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    assert_eq!(responses.len(), 50);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undo_restores_cancel_close_and_deadline() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let deadline = Utc::now() + chrono::Duration::days(2);
+    let confirmed = SessionBuilder::new(group.id).status("cancelled").create(&db.pool).await?;
+    let closed = SessionBuilder::new(group.id).status("closed").deadline(deadline).create(&db.pool).await?;
+    let reconfirmed = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await?;
+    
+    // Undoing a cancel restores the earlier status; only the latest action per group is kept
+    UndoRecord::record(&db.pool, group.id, 1, &UndoableAction::Close { session_ids: vec![closed.id.clone()] }).await?;
+    let record = UndoRecord::record(&db.pool, group.id, 1, &UndoableAction::Cancel {
+        session_id: confirmed.id.clone(),
+        previous_status: "confirmed".to_string(),
+        previous_deadline: None,
+    }).await?;
+    let latest = UndoRecord::find_latest(&db.pool, group.id).await?.unwrap();
+    assert_eq!(latest.id, record.id);
+    
+    assert_eq!(record.undo(&db.pool).await?, vec![confirmed.id.clone()]);
+    assert_eq!(Session::find_by_id(&db.pool, &confirmed.id).await?.unwrap().status, "confirmed");
+    assert!(UndoRecord::find_latest(&db.pool, group.id).await?.is_none());
+    assert!(record.undo(&db.pool).await?.is_empty(), "a record can only be undone once");
+    
+    // Closed polls reopen, unless they were confirmed in the meantime
+    let record = UndoRecord::record(&db.pool, group.id, 1, &UndoableAction::Close {
+        session_ids: vec![closed.id.clone(), reconfirmed.id.clone()],
+    }).await?;
+    assert_eq!(record.undo(&db.pool).await?, vec![closed.id.clone()]);
+    assert_eq!(Session::find_by_id(&db.pool, &closed.id).await?.unwrap().status, "active");
+    assert_eq!(Session::find_by_id(&db.pool, &reconfirmed.id).await?.unwrap().status, "confirmed");
+    
+    // Deadline changes go back to the previous deadline, or to none
+    let record = UndoRecord::record(&db.pool, group.id, 1, &UndoableAction::Deadline {
+        session_id: closed.id.clone(),
+        previous_deadline: None,
+    }).await?;
+    record.undo(&db.pool).await?;
+    assert_eq!(Session::find_by_id(&db.pool, &closed.id).await?.unwrap().deadline, None);
+    
+    // Cancelling clears the deadline, and undoing it brings the deadline back
+    let cancelled = SessionBuilder::new(group.id).status("cancelled").create(&db.pool).await?;
+    let record = UndoRecord::record(&db.pool, group.id, 1, &UndoableAction::Cancel {
+        session_id: cancelled.id.clone(),
+        previous_status: "active".to_string(),
+        previous_deadline: Some(deadline.to_rfc3339()),
+    }).await?;
+    record.undo(&db.pool).await?;
+    let restored = Session::find_by_id(&db.pool, &cancelled.id).await?.unwrap();
+    assert_eq!((restored.status.as_str(), restored.deadline), ("active", Some(deadline.to_rfc3339())));
+    
+    // Expired records can't be found and are cleaned up
+    let record = UndoRecord::record(&db.pool, group.id, 1, &UndoableAction::Close { session_ids: vec![closed.id.clone()] }).await?;
+    sqlx::query("UPDATE undo_actions SET expires_at = '2000-01-01T00:00:00Z' WHERE id = ?")
+        .bind(&record.id)
+        .execute(&db.pool)
+        .await?;
+    assert!(UndoRecord::find_by_id(&db.pool, group.id, &record.id).await?.is_none());
+    assert_eq!(UndoRecord::delete_expired(&db.pool).await?, 1);
+    
+    // Records are only found through the group they belong to
+    let other_group = GroupBuilder::new().chat_id(-200).create(&db.pool).await?;
+    let record = UndoRecord::record(&db.pool, group.id, 1, &UndoableAction::Close { session_ids: vec![closed.id.clone()] }).await?;
+    assert!(UndoRecord::find_by_id(&db.pool, other_group.id, &record.id).await?.is_none());
+    assert_eq!(UndoRecord::find_by_id(&db.pool, group.id, &record.id).await?.unwrap().id, record.id);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dedupe_groups_merges_old_chat_into_new() -> Result<()> {
+    use dnd_scheduler_bot::database::dedupe::{self, DedupeAction, DedupeReport};
+    
+    let (db, _temp_dir) = test_db().await?;
+    // The chat was upgraded to a supergroup and got a second group row under its new ID
+    let old = GroupBuilder::new().chat_id(-123456).create(&db.pool).await?;
+    let new = GroupBuilder::new().chat_id(-100123456).create(&db.pool).await?;
+    
+    Group::update_default_duration(&db.pool, old.id, 180).await?;
+    Group::update_store_usernames(&db.pool, old.id, false).await?;
+    Group::update_min_notice_hours(&db.pool, new.id, 12).await?;
+    GroupAdmin::add(&db.pool, old.id, 7, 1).await?;
+    GroupAdmin::add(&db.pool, old.id, 8, 1).await?;
+    GroupAdmin::add(&db.pool, new.id, 8, 1).await?;
+    RosterMember::add(&db.pool, old.id, 9, None).await?;
+    PlayerPseudonym::number_for(&db.pool, old.id, 7).await?;
+    
+    let old_first = SessionBuilder::new(old.id).title("Old one").create(&db.pool).await?;
+    let old_second = SessionBuilder::new(old.id).title("Old two").create(&db.pool).await?;
+    let new_first = SessionBuilder::new(new.id).title("New one").create(&db.pool).await?;
+    
+    // Both groups rotate hosting, only the old one snacks
+    Duty::add(&db.pool, old.id, "hosting").await?;
+    Duty::add(&db.pool, old.id, "snacks").await?;
+    Duty::add(&db.pool, new.id, "Hosting").await?;
+    let old_duties = Duty::find_by_group(&db.pool, old.id).await?;
+    DutyAssignment::save(&db.pool, &old_first.id, &[(old_duties[0].id, 7), (old_duties[1].id, 8)]).await?;
+    
+    let action = dedupe::plan_chat_id_migration(&db.pool, -123456, -100123456).await?.unwrap();
+    assert!(matches!(&action, DedupeAction::Merge { survivor, duplicate } if survivor.id == new.id && duplicate.id == old.id));
+    
+    // A dry run reports the changes but keeps everything as it was
+    let expected = DedupeReport { sessions_moved: 2, admins_moved: 1, roster_moved: 1, pseudonyms_moved: 1 };
+    assert_eq!(dedupe::apply(&db.pool, &action, true).await?, expected);
+    assert!(Group::find_by_id(&db.pool, old.id).await?.is_some());
+    assert_eq!(Session::find_by_id(&db.pool, &old_first.id).await?.unwrap().group_id, old.id);
+    
+    assert_eq!(dedupe::apply(&db.pool, &action, false).await?, expected);
+    assert!(Group::find_by_id(&db.pool, old.id).await?.is_none());
+    
+    // Sessions move over, numbered after the survivor's own
+    for (session, short_id) in [(&new_first, 1), (&old_first, 2), (&old_second, 3)] {
+        let moved = Session::find_by_id(&db.pool, &session.id).await?.unwrap();
+        assert_eq!(moved.group_id, new.id);
+        assert_eq!(moved.short_id, Some(short_id));
+    }
+    
+    // Changed settings win over defaults, privacy and notice take the stricter value
+    let merged = Group::find_by_id(&db.pool, new.id).await?.unwrap();
+    assert_eq!(merged.telegram_chat_id, -100123456);
+    assert_eq!(merged.default_duration, 180);
+    assert!(!merged.store_usernames);
+    assert_eq!(merged.min_notice_hours, 12);
+    
+    let admins: Vec<i64> = GroupAdmin::find_by_group(&db.pool, new.id).await?.iter().map(|a| a.user_id).collect();
+    assert_eq!(admins.len(), 2);
+    assert!(admins.contains(&7) && admins.contains(&8));
+    assert_eq!(RosterMember::find_by_group(&db.pool, new.id).await?.len(), 1);
+    // The pseudonym came along with the old group's key
+    assert_eq!(PlayerPseudonym::number_for(&db.pool, new.id, 7).await?, 1);
+    
+    // The survivor's hosting duty takes over the old one's assignments instead of losing them
+    let duties = Duty::find_by_group(&db.pool, new.id).await?;
+    let names: Vec<&str> = duties.iter().map(|duty| duty.name.as_str()).collect();
+    assert_eq!(names, vec!["snacks", "Hosting"]);
+    let assigned: Vec<(i64, i64)> = DutyAssignment::find_by_session(&db.pool, &old_first.id).await?.iter()
+        .map(|assignment| (assignment.duty_id, assignment.user_id))
+        .collect();
+    assert_eq!(assigned, vec![(duties[0].id, 8), (duties[1].id, 7)]);
+    
+    // Nothing left to do for that pair
+    assert!(dedupe::plan_chat_id_migration(&db.pool, -123456, -100123456).await?.is_none());
+    assert!(dedupe::find_shared_chat_id_duplicates(&db.pool).await?.is_empty());
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dedupe_groups_moves_lone_group_to_new_chat_id() -> Result<()> {
+    use dnd_scheduler_bot::database::dedupe::{self, DedupeAction};
+    
+    let (db, _temp_dir) = test_db().await?;
+    let old = GroupBuilder::new().chat_id(-654321).create(&db.pool).await?;
+    
+    let action = dedupe::plan_chat_id_migration(&db.pool, -654321, -100654321).await?.unwrap();
+    assert!(matches!(action, DedupeAction::Rechat { new_chat_id: -100654321, .. }));
+    dedupe::apply(&db.pool, &action, false).await?;
+    
+    let moved = Group::find_by_chat_id(&db.pool, -100654321).await?.unwrap();
+    assert_eq!(moved.id, old.id);
+    assert!(Group::find_by_chat_id(&db.pool, -654321).await?.is_none());
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_by_group_paginated() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let other_group = GroupBuilder::new().chat_id(-42).create(&db.pool).await?;
+    SessionBuilder::new(other_group.id).create(&db.pool).await?;
+
+    for (i, status) in ["active", "cancelled", "confirmed", "active", "active"].into_iter().enumerate() {
+        let session = SessionBuilder::new(group.id).title(format!("S{i}")).status(status).create(&db.pool).await?;
+        let created_at = (Utc::now() - chrono::Duration::hours(10 - i as i64)).to_rfc3339();
+        sqlx::query("UPDATE sessions SET created_at = ? WHERE id = ?")
+            .bind(created_at)
+            .bind(&session.id)
+            .execute(&db.pool)
+            .await?;
+    }
+    let titles = |sessions: Vec<Session>| sessions.into_iter().map(|s| s.title).collect::<Vec<_>>();
+
+    let statuses = ["active", "confirmed"];
+    let (page, total) = Session::find_by_group_paginated(&db.pool, group.id, 2, 0, &statuses).await?;
+    assert_eq!(total, 4);
+    assert_eq!(titles(page), vec!["S4", "S3"]);
+
+    let (page, total) = Session::find_by_group_paginated(&db.pool, group.id, 2, 2, &statuses).await?;
+    assert_eq!(total, 4);
+    assert_eq!(titles(page), vec!["S2", "S0"]);
+
+    let (page, _) = Session::find_by_group_paginated(&db.pool, group.id, 2, 4, &statuses).await?;
+    assert!(page.is_empty());
+
+    let (page, total) = Session::find_by_group_paginated(&db.pool, group.id, 10, 0, &["cancelled"]).await?;
+    assert_eq!((titles(page), total), (vec!["S1".to_string()], 1));
+
+    // No statuses means no filter
+    let (page, total) = Session::find_by_group_paginated(&db.pool, group.id, 10, 0, &[]).await?;
+    assert_eq!((page.len(), total), (5, 5));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_outbound_message_log() -> Result<()> {
+    use dnd_scheduler_bot::bot::commands::sent::render_sent_log;
+    use dnd_scheduler_bot::utils::outbound::logged_send;
+    use teloxide::{types::ChatId, RequestError};
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).title("Game Night!").create(&db.pool).await?;
+    let chat = ChatId(group.telegram_chat_id);
+    
+    let sent = logged_send(&db.pool, chat, "poll", Some(&session.id), async { Ok::<_, RequestError>(7) }).await;
+    assert_eq!(sent.unwrap(), 7);
+    let failed = logged_send(&db.pool, chat, "reminder", Some(&session.id), async {
+        Err::<(), _>(RequestError::Io(std::io::Error::other("connection reset")))
+    }).await;
+    assert!(failed.is_err());
+    OutboundMessage::record(&db.pool, group.telegram_chat_id, "feedback", None, None).await?;
+    
+    let entries = OutboundMessage::find_by_session(&db.pool, &session.id).await?;
+    assert_eq!(entries.iter().map(|e| (e.kind.as_str(), e.success)).collect::<Vec<_>>(), vec![("poll", true), ("reminder", false)]);
+    assert!(entries[1].error.as_deref().is_some_and(|e| e.contains("connection reset")));
+    
+    let text = render_sent_log(&session, &entries);
+    assert!(text.contains("Sent for Game Night\\!"));
+    assert!(text.contains("poll ✅"));
+    assert!(text.contains("reminder ❌"));
+    
+    // Trimming keeps the newest rows
+    assert_eq!(OutboundMessage::trim(&db.pool, 1).await?, 2);
+    assert!(OutboundMessage::find_by_session(&db.pool, &session.id).await?.is_empty());
+    assert_eq!(OutboundMessage::trim(&db.pool, 1).await?, 0);
+    
+    // A broken log never fails the send itself
+    sqlx::query("DROP TABLE outbound_messages").execute(&db.pool).await?;
+    let sent = logged_send(&db.pool, chat, "poll", Some(&session.id), async { Ok::<_, RequestError>(8) }).await;
+    assert_eq!(sent.unwrap(), 8);
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_info_lists_every_voter() -> Result<()> {
+    use dnd_scheduler_bot::bot::commands::list::TELEGRAM_MESSAGE_LIMIT;
+    use dnd_scheduler_bot::bot::commands::sessioninfo::render_session_info;
+    use std::collections::HashMap;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).title("Game Night!").created_by(42).create(&db.pool).await?;
+    let first = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    let second = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    ResponseBuilder::new(&first).user(1, Some("alice")).create(&db.pool).await?;
+    ResponseBuilder::new(&first).user(2, None).response("maybe").create(&db.pool).await?;
+    ResponseBuilder::new(&second).user(1, Some("alice")).response("no").create(&db.pool).await?;
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    let names = HashMap::from([(1, "alice".to_string()), (42, "dm_dana".to_string())]);
+    
+    let text = render_session_info(&session, &options, &responses, &NotesByOption::default(), &names);
+    assert!(text.contains("Game Night\\!"), "{text}");
+    assert!(text.contains("Created by dm\\_dana"), "{text}");
+    assert!(text.contains("Deadline: none"), "{text}");
+    assert!(text.contains("1\\. "), "{text}");
+    assert!(text.contains("✅ Yes \\(1\\): alice\n   ❌ No \\(0\\): —\n   ❓ Maybe \\(1\\): user2"), "{text}");
+    assert!(text.contains("❌ No \\(1\\): alice"), "{text}");
+    
+    // Anonymous polls keep their votes to themselves until confirmed
+    let anonymous = Session { anonymous: true, ..session.clone() };
+    let text = render_session_info(&anonymous, &options, &responses, &NotesByOption::default(), &names);
+    assert!(!text.contains("alice"), "{text}");
+    assert!(text.contains("2 votes, hidden"), "{text}");
+    
+    // Far too many voters are cut short to fit in one message
+    let crowd: Vec<Response> = (0..2000)
+        .map(|user_id| Response { user_id, ..responses[0].clone() })
+        .collect();
+    let text = render_session_info(&session, &options, &crowd, &NotesByOption::default(), &names);
+    assert!(text.encode_utf16().count() <= TELEGRAM_MESSAGE_LIMIT);
+    assert!(text.contains("more lines not shown"), "{text}");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_voter_notes_on_options() -> Result<()> {
+    use dnd_scheduler_bot::bot::commands::list::render_list_page;
+    use dnd_scheduler_bot::bot::commands::sessioninfo::render_session_info;
+    use std::collections::HashMap;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let first = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    let second = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    ResponseBuilder::new(&first).user(1, Some("alice")).create(&db.pool).await?;
+    
+    // A note can come before the vote, and a new one replaces the old
+    ResponseNote::set(&db.pool, &session.id, &first.id, 1, "maybe").await?;
+    ResponseNote::set(&db.pool, &session.id, &first.id, 1, "Only after 20:00").await?;
+    ResponseNote::set(&db.pool, &session.id, &second.id, 2, &"x".repeat(NOTE_MAX_CHARS)).await?;
+    let notes = ResponseNote::find_by_sessions(&db.pool, std::slice::from_ref(&session.id)).await?;
+    assert_eq!(notes.len(), 2);
+    assert_eq!((notes[0].user_id, notes[0].note.as_str()), (1, "Only after 20:00"));
+    
+    // Authors are named like voters, and long notes are cut short
+    let shown = NotesByOption::load(&db.pool, &group, std::slice::from_ref(&session.id)).await?;
+    assert_eq!(shown.for_option(&first.id), [("alice".to_string(), "Only after 20:00".to_string())]);
+    let (_, long) = &shown.for_option(&second.id)[0];
+    assert_eq!(long.chars().count(), NOTE_SHOWN_CHARS + 1);
+    assert!(long.ends_with('…'));
+    
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await?;
+    let responses = Response::find_by_session(&db.pool, &session.id).await?;
+    let (text, _) = render_list_page(std::slice::from_ref(&session), &options, &responses, &shown, 0, &[], 0, 1, 1);
+    assert!(text.contains("📝 alice: Only after 20:00"), "{text}");
+    let text = render_session_info(&session, &options, &responses, &shown, &HashMap::new());
+    assert!(text.contains("📝 alice: Only after 20:00"), "{text}");
+    
+    // Clearing only reports a note that was there; deleting the session takes its notes along
+    assert!(ResponseNote::clear(&db.pool, &first.id, 1).await?);
+    assert!(!ResponseNote::clear(&db.pool, &first.id, 1).await?);
+    Session::delete(&db.pool, &session.id).await?;
+    assert!(ResponseNote::find_by_sessions(&db.pool, &[session.id]).await?.is_empty());
+    
+    Ok(())
+}
+
+#[test]
+fn test_clean_note() {
+    assert_eq!(clean_note("  only after\n20:00 "), Ok("only after 20:00".to_string()));
+    assert!(clean_note("   ").is_err());
+    assert!(clean_note(&"x".repeat(NOTE_MAX_CHARS + 1)).is_err());
+    assert!(clean_note(&"é".repeat(NOTE_MAX_CHARS)).is_ok());
+}
+
+#[tokio::test]
+async fn test_participation_counts_renamed_users_once() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let first = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    let second = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    ResponseBuilder::new(&first).user(1, Some("alice")).create(&db.pool).await?;
+    ResponseBuilder::new(&second).user(1, Some("alice_the_bold")).create(&db.pool).await?;
+    ResponseBuilder::new(&first).user(2, Some("bob")).create(&db.pool).await?;
+    
+    let participation = Response::participation_by_group(&db.pool, group.id).await?;
+    assert_eq!(participation, vec![
+        Participation { user_id: 1, username: Some("alice_the_bold".to_string()), response_count: 2 },
+        Participation { user_id: 2, username: Some("bob".to_string()), response_count: 1 },
+    ]);
+    
+    // Profiles only change when a name does, and never from a group that hides usernames
+    assert!(UserProfile::record(&db.pool, Some(group.telegram_chat_id), 1, None, "Alice Smith").await?);
+    assert!(!UserProfile::record(&db.pool, None, 1, None, "Alice Smith").await?);
+    assert!(UserProfile::record(&db.pool, None, 1, Some("alice2"), "Alice Smith").await?);
+    let private = GroupBuilder::new().chat_id(-1009876543210).create(&db.pool).await?;
+    Group::update_store_usernames(&db.pool, private.id, false).await?;
+    assert!(!UserProfile::record(&db.pool, Some(private.telegram_chat_id), 2, Some("bob"), "Bob").await?);
+    
+    let profiles = UserProfile::find_by_ids(&db.pool, &[1, 2]).await?;
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[&1].name(), "alice2");
+    assert_eq!(profiles[&1].display_name, "Alice Smith");
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_confirmed_attendance_streaks() -> Result<()> {
+    use chrono::Duration;
+    use dnd_scheduler_bot::bot::commands::stats::attendance_streaks;
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let now = Utc::now();
+    // Three past sessions, oldest first, and one still to come
+    let mut confirmed = Vec::new();
+    for days in [-21, -14, -7, 7] {
+        let session = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await?;
+        let option = SessionOptionBuilder::new(&session.id).at(now + Duration::days(days)).create(&db.pool).await?;
+        sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?").bind(&option.id).execute(&db.pool).await?;
+        confirmed.push(option);
+    }
+    // Votes on an active session don't count
+    let active = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let active_option = SessionOptionBuilder::new(&active.id).create(&db.pool).await?;
+    ResponseBuilder::new(&active_option).user(3, None).create(&db.pool).await?;
+    
+    // Alice came to all three, Bob missed the last one, Carol only came to the last one
+    for option in &confirmed {
+        ResponseBuilder::new(option).user(1, Some("alice")).create(&db.pool).await?;
+    }
+    ResponseBuilder::new(&confirmed[0]).user(2, Some("bob")).create(&db.pool).await?;
+    ResponseBuilder::new(&confirmed[1]).user(2, Some("bob")).create(&db.pool).await?;
+    ResponseBuilder::new(&confirmed[2]).user(2, Some("bob")).response("no").create(&db.pool).await?;
+    ResponseBuilder::new(&confirmed[2]).user(3, Some("carol")).create(&db.pool).await?;
+    
+    let attendance = Response::find_confirmed_attendance(&db.pool, group.id).await?;
+    assert_eq!(attendance.len(), 8);
+    assert!(attendance.iter().all(|row| row.user_id.is_some()));
+    
+    // The upcoming session doesn't extend anyone's streak yet
+    assert_eq!(attendance_streaks(&attendance, now), vec![(1, 3), (3, 1)]);
+    assert_eq!(attendance_streaks(&attendance, now - Duration::days(10)), vec![(1, 2), (2, 2)]);
+    
+    // A confirmed session nobody answered breaks every streak
+    let session = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&session.id).at(now - Duration::days(1)).create(&db.pool).await?;
+    sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?").bind(&option.id).execute(&db.pool).await?;
+    let attendance = Response::find_confirmed_attendance(&db.pool, group.id).await?;
+    assert!(attendance.iter().any(|row| row.session_id == session.id && row.user_id.is_none()));
+    assert!(attendance_streaks(&attendance, now).is_empty());
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_attendance_and_reliability() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let mut confirmed = Vec::new();
+    for _ in 0..2 {
+        let session = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await?;
+        let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+        let other = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+        sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?").bind(&option.id).execute(&db.pool).await?;
+        confirmed.push((session, option, other));
+    }
+    
+    // Alice and Bob said yes to both; Carol only to the time that wasn't picked
+    for (_, option, other) in &confirmed {
+        ResponseBuilder::new(option).user(1, Some("alice")).create(&db.pool).await?;
+        ResponseBuilder::new(option).user(2, Some("bob")).create(&db.pool).await?;
+        ResponseBuilder::new(other).user(3, Some("carol")).create(&db.pool).await?;
+    }
+    
+    // Bob said yes but isn't marked, so he's a no-show; Carol came without saying yes
+    let marked = Attendance::mark(&db.pool, &confirmed[0].0.id, &[1, 3], 99).await?;
+    let summary: Vec<(i64, bool)> = marked.iter().map(|row| (row.user_id, row.attended)).collect();
+    assert_eq!(summary, vec![(1, true), (3, true), (2, false)]);
+    assert!(marked.iter().all(|row| row.marked_by == 99));
+    
+    // Marking again replaces what was there
+    Attendance::mark(&db.pool, &confirmed[1].0.id, &[3], 99).await?;
+    Attendance::mark(&db.pool, &confirmed[1].0.id, &[1, 2], 99).await?;
+    assert_eq!(Attendance::find_by_session(&db.pool, &confirmed[1].0.id).await?.len(), 2);
+    
+    // Carol's visit doesn't count either way, sessions without marks don't count at all
+    SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await?;
+    let reliability = Attendance::reliability_by_group(&db.pool, group.id).await?;
+    assert_eq!(reliability, vec![
+        Reliability { user_id: 1, said_yes: 2, attended: 2 },
+        Reliability { user_id: 2, said_yes: 2, attended: 1 },
+    ]);
+    assert_eq!(reliability[1].percentage(), 50);
+    assert_eq!(Reliability { user_id: 4, said_yes: 3, attended: 2 }.percentage(), 67);
+    
+    // Deleting a session takes its attendance along
+    Session::delete(&db.pool, &confirmed[0].0.id).await?;
+    assert!(Attendance::find_by_session(&db.pool, &confirmed[0].0.id).await?.is_empty());
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_members_upsert_on_activity() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().chat_id(-500).create(&db.pool).await?;
+    
+    // Chats without a group aren't tracked
+    assert!(!GroupMember::record_activity(&db.pool, -501, 1, Some("mira"), Some("Mira")).await?);
+    
+    assert!(GroupMember::record_activity(&db.pool, -500, 1, Some("mira"), Some("Mira")).await?);
+    assert!(GroupMember::record_activity(&db.pool, -500, 2, None, Some("Bob")).await?);
+    // Later activity refreshes the row rather than adding one, and doesn't forget a known name
+    assert!(GroupMember::record_activity(&db.pool, -500, 2, Some("bob_the_bard"), None).await?);
+    let members = GroupMember::find_active_by_group(&db.pool, group.id).await?;
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].user_id, 2);
+    assert_eq!(members[0].username.as_deref(), Some("bob_the_bard"));
+    assert_eq!(members[0].display_name.as_deref(), Some("Bob"));
+    
+    // Leaving makes them inactive, coming back makes them active again
+    assert!(GroupMember::mark_left(&db.pool, -500, 1).await?);
+    assert!(!GroupMember::mark_left(&db.pool, -500, 1).await?);
+    assert_eq!(GroupMember::count_active(&db.pool, group.id).await?, 1);
+    assert_eq!(Group::find_ids_with_member(&db.pool, 1).await?, Vec::<i64>::new());
+    GroupMember::record_activity(&db.pool, -500, 1, Some("mira"), Some("Mira")).await?;
+    assert_eq!(GroupMember::count_active(&db.pool, group.id).await?, 2);
+    assert_eq!(Group::find_ids_with_member(&db.pool, 1).await?, vec![group.id]);
+    
+    // Without stored usernames, members are counted but not named
+    Group::update_store_usernames(&db.pool, group.id, false).await?;
+    GroupMember::record_activity(&db.pool, -500, 3, Some("carol"), Some("Carol")).await?;
+    let members = GroupMember::find_active_by_group(&db.pool, group.id).await?;
+    assert_eq!(members.len(), 3);
+    assert!(members.iter().all(|member| member.username.is_none() && member.display_name.is_none()));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_group_member_turnout() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().chat_id(-600).create(&db.pool).await?;
+    assert_eq!(GroupMember::turnout(&db.pool, group.id).await?.percentage(), None);
+    for user_id in 1..=4 {
+        GroupMember::record_activity(&db.pool, -600, user_id, None, None).await?;
+    }
+    
+    // Two polls: everyone voted on the first, one member on the second
+    let first = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let first_options = [
+        SessionOptionBuilder::new(&first.id).create(&db.pool).await?,
+        SessionOptionBuilder::new(&first.id).create(&db.pool).await?,
+    ];
+    for user_id in 1..=4 {
+        for option in &first_options {
+            ResponseBuilder::new(option).user(user_id, None).create(&db.pool).await?;
+        }
+    }
+    let second = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let second_option = SessionOptionBuilder::new(&second.id).create(&db.pool).await?;
+    ResponseBuilder::new(&second_option).user(1, None).create(&db.pool).await?;
+    // Cancelled polls and votes from people who left don't count
+    let cancelled = SessionBuilder::new(group.id).status("cancelled").create(&db.pool).await?;
+    let cancelled_option = SessionOptionBuilder::new(&cancelled.id).create(&db.pool).await?;
+    ResponseBuilder::new(&cancelled_option).user(2, None).create(&db.pool).await?;
+    ResponseBuilder::new(&second_option).user(9, None).create(&db.pool).await?;
+    GroupMember::record_activity(&db.pool, -600, 9, None, None).await?;
+    GroupMember::mark_left(&db.pool, -600, 9).await?;
+    
+    let turnout = GroupMember::turnout(&db.pool, group.id).await?;
+    assert_eq!(turnout, Turnout { members: 4, polls: 2, votes: 5 });
+    assert_eq!(turnout.percentage(), Some(63));
+    
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_duties_rotate_across_confirmed_sessions() -> Result<()> {
+    use dnd_scheduler_bot::bot::duties::{assign_session_duties, session_duties_line};
+    
+    let (db, _temp_dir) = test_db().await?;
+    let group = GroupBuilder::new().create(&db.pool).await?;
+    let session = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await?;
+    ResponseBuilder::new(&option).user(1, Some("alice")).create(&db.pool).await?;
+    ResponseBuilder::new(&option).user(2, Some("bob")).create(&db.pool).await?;
+    ResponseBuilder::new(&option).user(3, Some("carol")).response("no").create(&db.pool).await?;
+    
+    // Groups without duties get no line
+    assert_eq!(assign_session_duties(&db.pool, &group, &session.id, &option.id).await?, None);
+    
+    assert!(Duty::add(&db.pool, group.id, "hosting").await?);
+    assert!(Duty::add(&db.pool, group.id, "snacks").await?);
+    assert!(!Duty::add(&db.pool, group.id, "Hosting").await?);
+    let line = assign_session_duties(&db.pool, &group, &session.id, &option.id).await?;
+    assert_eq!(line.as_deref(), Some("🏠 Hosting: alice, 🍕 Snacks: bob"));
+    
+    // The next session swaps them; the first one's duties stay as they were
+    let next = SessionBuilder::new(group.id).create(&db.pool).await?;
+    let next_option = SessionOptionBuilder::new(&next.id).create(&db.pool).await?;
+    ResponseBuilder::new(&next_option).user(1, Some("alice")).create(&db.pool).await?;
+    ResponseBuilder::new(&next_option).user(2, Some("bob")).create(&db.pool).await?;
+    let line = assign_session_duties(&db.pool, &group, &next.id, &next_option.id).await?;
+    assert_eq!(line.as_deref(), Some("🏠 Hosting: bob, 🍕 Snacks: alice"));
+    assert_eq!(
+        session_duties_line(&db.pool, &group, &session.id).await?.as_deref(),
+        Some("🏠 Hosting: alice, 🍕 Snacks: bob")
+    );
+    
+    // Removing a duty takes its turns with it
+    assert!(Duty::remove(&db.pool, group.id, "SNACKS").await?);
+    assert!(!Duty::remove(&db.pool, group.id, "snacks").await?);
+    assert_eq!(DutyAssignment::find_by_group(&db.pool, group.id).await?.len(), 2);
+    assert_eq!(session_duties_line(&db.pool, &group, &next.id).await?.as_deref(), Some("🏠 Hosting: bob"));
+    
+    Ok(())
+}
+
+#[test]
+fn test_resolve_attended_players() {
+    use dnd_scheduler_bot::bot::commands::attendance::resolve_players;
+    
+    let known = [(1, Some("Mira")), (2, None), (3, Some("bob_the_bard"))];
+    let (found, unknown) = resolve_players(&["@mira,", "@BOB_the_bard", "42", "@mira", "@zed", "bob", "-5"], &known);
+    assert_eq!(found, vec![1, 3, 42]);
+    assert_eq!(unknown, vec!["@zed", "bob", "-5"]);
+}
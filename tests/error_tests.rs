@@ -0,0 +1,81 @@
+use anyhow::Result;
+use dnd_scheduler_bot::database::models::*;
+use dnd_scheduler_bot::error::BotError;
+use dnd_scheduler_bot::testing::*;
+use teloxide::{ApiError, RequestError};
+
+#[test]
+fn test_database_errors_hide_details_from_users() {
+    let error = BotError::from(sqlx::Error::RowNotFound);
+    assert_eq!(error.kind(), "database");
+    assert!(error.is_internal());
+    assert_eq!(
+        error.user_message(),
+        "Something went wrong while accessing the database. Please try again in a moment."
+    );
+    assert!(error.to_string().contains("no rows returned"));
+}
+
+#[test]
+fn test_pool_timeouts_become_timeouts() {
+    let error = BotError::from(sqlx::Error::PoolTimedOut);
+    assert_eq!(error.kind(), "timeout");
+    assert!(error.is_internal());
+    assert_eq!(
+        error.user_message(),
+        "The bot is busy right now and the request timed out. Please try again in a moment."
+    );
+}
+
+#[test]
+fn test_telegram_errors() {
+    let error = BotError::from(RequestError::Api(ApiError::BotBlocked));
+    assert_eq!(error.kind(), "telegram");
+    assert!(error.is_internal());
+    assert_eq!(
+        error.user_message(),
+        "Telegram rejected a request while handling your command. Please try again."
+    );
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+#[test]
+fn test_validation_errors_show_their_message() {
+    let error = BotError::Validation("Invalid response: maybe later".to_string());
+    assert_eq!(error.kind(), "validation");
+    assert!(!error.is_internal());
+    assert_eq!(error.user_message(), "Invalid response: maybe later");
+}
+
+#[test]
+fn test_not_found_errors_name_what_was_missing() {
+    let error = BotError::NotFound { what: "Session".to_string() };
+    assert_eq!(error.kind(), "not_found");
+    assert!(!error.is_internal());
+    assert_eq!(error.user_message(), "Session not found");
+}
+
+#[test]
+fn test_permission_denied_explains_who_can() {
+    let error = BotError::PermissionDenied;
+    assert_eq!(error.kind(), "permission_denied");
+    assert!(!error.is_internal());
+    let message = error.user_message();
+    assert!(message.starts_with("Permission denied:"));
+    assert!(message.contains("session creator"));
+    assert!(message.contains("/list"));
+}
+
+#[tokio::test]
+async fn test_constraint_violations_keep_their_code() -> Result<()> {
+    let (db, _temp_dir) = test_db().await?;
+    Group::create(&db.pool, 12345).await?;
+    
+    // A second group for the same chat breaks the unique chat ID
+    let error = BotError::from(Group::create(&db.pool, 12345).await.unwrap_err());
+    assert_eq!(error.kind(), "database");
+    assert_eq!(error.database_code().as_deref(), Some("2067"));
+    assert_eq!(BotError::PermissionDenied.database_code(), None);
+    
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use dnd_scheduler_bot::utils::feedback::FeedbackType;
+use dnd_scheduler_bot::utils::feedback::{usage_help_text, FeedbackType};
+use dnd_scheduler_bot::utils::i18n::Lang;
 
 #[cfg(test)]
 mod feedback_system_tests {
@@ -240,6 +241,61 @@ mod feedback_system_tests {
             }
         }
     }
+
+    #[test]
+    fn test_usage_help_text() {
+        let text = usage_help_text(Lang::En, "/confirm", "/confirm <session_id>", Some("/confirm a1b2c3d4"), Some("/confirm #3"));
+        assert!(text.starts_with("❌ Missing or invalid arguments for /confirm\n"));
+        assert!(text.contains("**Usage:** `/confirm <session\\_id\\>`"), "{text}");
+        assert!(text.contains("**Example:** `/confirm a1b2c3d4`"));
+        assert!(text.ends_with("💡 **Suggestion:** `/confirm \\#3`"));
+        
+        let text = usage_help_text(Lang::De, "/schedule", "/schedule \"Title\" option1, option2, ...", None, None);
+        assert!(!text.contains("Example"));
+        assert!(!text.contains("💡"));
+    }
+
+    #[tokio::test]
+    async fn test_unparsable_markdown_is_resent_as_plain_text() {
+        use dnd_scheduler_bot::utils::outbound::{is_entity_parse_error, send_with_plain_fallback};
+        use std::cell::RefCell;
+        use teloxide::prelude::*;
+        use teloxide::requests::HasPayload;
+        use teloxide::types::ParseMode;
+        use teloxide::{ApiError, RequestError};
+        
+        // Telegram answers the first send with `failure`, and accepts any later one
+        async fn run(text: &str, failure: RequestError) -> (Result<u8, RequestError>, Vec<(String, Option<ParseMode>)>) {
+            let request = Bot::new("123:test").send_message(ChatId(1), text).parse_mode(ParseMode::MarkdownV2);
+            let failure = RefCell::new(Some(failure));
+            let sends = RefCell::new(Vec::new());
+            let result = send_with_plain_fallback(request, |request| {
+                sends.borrow_mut().push((request.payload_ref().text.clone(), request.payload_ref().parse_mode));
+                let next = failure.borrow_mut().take();
+                async move { next.map_or(Ok(1), Err) }
+            }).await;
+            (result, sends.into_inner())
+        }
+        let unparsable = || RequestError::Api(ApiError::Unknown(
+            "Bad Request: can't parse entities: Character '.' is reserved and must be escaped".to_string()
+        ));
+        assert!(is_entity_parse_error(&unparsable()));
+        assert!(is_entity_parse_error(&RequestError::Api(ApiError::CantParseEntities)));
+        assert!(!is_entity_parse_error(&RequestError::Api(ApiError::BotKicked)));
+        
+        // A formatting slip costs the formatting, not the message
+        let (result, sends) = run("🎲 **Lost Mines** starts 19.00", unparsable()).await;
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(sends, vec![
+            ("🎲 **Lost Mines** starts 19.00".to_string(), Some(ParseMode::MarkdownV2)),
+            ("🎲 Lost Mines starts 19.00".to_string(), None),
+        ]);
+        
+        // Other failures are left to the caller, without a second send
+        let (result, sends) = run("🎲 **Lost Mines**", RequestError::Api(ApiError::BotKicked)).await;
+        assert!(matches!(result, Err(RequestError::Api(ApiError::BotKicked))));
+        assert_eq!(sends.len(), 1);
+    }
 }
 
 // Helper functions that would be implemented in the actual feedback system
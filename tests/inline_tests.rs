@@ -0,0 +1,151 @@
+#![allow(clippy::unwrap_used)]
+
+use chrono::{TimeZone, Utc};
+use dnd_scheduler_bot::bot::handlers::inline::*;
+use dnd_scheduler_bot::database::models::*;
+use dnd_scheduler_bot::testing::*;
+use teloxide::types::{InlineQueryResult, InputMessageContent};
+
+fn article_text(result: &InlineQueryResult) -> Option<(&str, &str, String)> {
+    let InlineQueryResult::Article(article) = result else {
+        return None;
+    };
+    let InputMessageContent::Text(content) = &article.input_message_content else {
+        return None;
+    };
+    Some((&article.id, &article.title, content.message_text.clone()))
+}
+
+async fn inline_session(
+    db: &dnd_scheduler_bot::database::connection::DatabaseManager,
+    group: &Group,
+    title: &str,
+) -> InlineSession {
+    let session = SessionBuilder::new(group.id).title(title).created_by(2).create(&db.pool).await.unwrap();
+    let option = SessionOptionBuilder::new(&session.id)
+        .at(Utc.with_ymd_and_hms(2030, 5, 3, 19, 0, 0).unwrap())
+        .create(&db.pool)
+        .await
+        .unwrap();
+    ResponseBuilder::new(&option).user(1, Some("mira")).create(&db.pool).await.unwrap();
+    InlineSession {
+        options: SessionOption::find_by_session(&db.pool, &session.id).await.unwrap(),
+        responses: Response::find_by_session(&db.pool, &session.id).await.unwrap(),
+        chat_id: group.telegram_chat_id,
+        session,
+    }
+}
+
+#[test]
+fn test_poll_link_needs_a_supergroup() {
+    assert_eq!(poll_link(-1001234567890, 42).as_deref(), Some("https://t.me/c/1234567890/42"));
+    assert_eq!(poll_link(-123456, 42), None);
+    assert_eq!(poll_link(123456, 42), None);
+}
+
+#[tokio::test]
+async fn test_build_inline_results_filters_by_title() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().chat_id(-1001234567890).create(&db.pool).await.unwrap();
+    let mut curse = inline_session(&db, &group, "Curse of Strahd").await;
+    curse.session.message_id = Some(77);
+    let tomb = inline_session(&db, &group, "Tomb of Annihilation").await;
+    let sessions = vec![curse, tomb];
+
+    let results = build_inline_results(&sessions, "  STRAHD ");
+    assert_eq!(results.len(), 1);
+    let (id, title, text) = article_text(&results[0]).unwrap();
+    assert_eq!(id, sessions[0].session.id);
+    assert_eq!(title, "Curse of Strahd");
+    assert!(text.contains("**Curse of Strahd**"), "{text}");
+    assert!(text.contains("✅ 1 • ❌ 0 • ❓ 0"), "{text}");
+    assert!(text.contains("(https://t.me/c/1234567890/77)"), "{text}");
+
+    assert_eq!(build_inline_results(&sessions, "").len(), 2);
+    assert!(build_inline_results(&sessions, "dragon").is_empty());
+}
+
+#[tokio::test]
+async fn test_summary_hides_anonymous_tallies_and_missing_links() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().chat_id(-4242).create(&db.pool).await.unwrap();
+    let mut entry = inline_session(&db, &group, "Waterdeep").await;
+    entry.session.anonymous = true;
+    entry.session.message_id = Some(5);
+    entry.session.notes = Some("Bring snacks".to_string());
+
+    let text = render_session_summary(&entry);
+    assert!(!text.contains('✅'), "{text}");
+    assert!(text.contains("📝 Bring snacks"), "{text}");
+    // A basic group has no message links
+    assert!(!text.contains("https://"), "{text}");
+}
+
+#[tokio::test]
+async fn test_results_are_capped() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let mut sessions = Vec::new();
+    for i in 0..MAX_INLINE_RESULTS + 3 {
+        sessions.push(inline_session(&db, &group, &format!("Session {i}")).await);
+    }
+    assert_eq!(build_inline_results(&sessions, "session").len(), MAX_INLINE_RESULTS);
+}
+
+#[tokio::test]
+async fn test_inline_lookups() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let voted = GroupBuilder::new().chat_id(-1).create(&db.pool).await.unwrap();
+    let rostered = GroupBuilder::new().chat_id(-2).create(&db.pool).await.unwrap();
+    let stranger = GroupBuilder::new().chat_id(-3).create(&db.pool).await.unwrap();
+    inline_session(&db, &voted, "100% Dragons").await;
+    RosterMember::add(&db.pool, rostered.id, 1, None).await.unwrap();
+    SessionBuilder::new(stranger.id).title("Dragon_Heist").created_by(9).create(&db.pool).await.unwrap();
+    SessionBuilder::new(stranger.id).title("Dragons again").created_by(9).status("confirmed").create(&db.pool).await.unwrap();
+
+    let mut groups = Group::find_ids_with_member(&db.pool, 1).await.unwrap();
+    groups.sort();
+    assert_eq!(groups, vec![voted.id, rostered.id]);
+    assert_eq!(Group::find_ids_with_member(&db.pool, 9).await.unwrap(), vec![stranger.id]);
+
+    let titles = |sessions: Vec<Session>| sessions.into_iter().map(|s| s.title).collect::<Vec<_>>();
+    let found = Session::find_active_by_title(&db.pool, "dragon", 10).await.unwrap();
+    assert_eq!(found.len(), 2);
+    // LIKE wildcards in the query are matched literally
+    assert_eq!(titles(Session::find_active_by_title(&db.pool, "100%", 10).await.unwrap()), ["100% Dragons"]);
+    assert_eq!(titles(Session::find_active_by_title(&db.pool, "n_h", 10).await.unwrap()), ["Dragon_Heist"]);
+    assert!(Session::find_active_by_title(&db.pool, "x%", 10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_inline_queries_reach_the_handler() {
+    use dnd_scheduler_bot::bot::dialogue::DialogueStorage;
+    use dnd_scheduler_bot::bot::handlers::BotHandler;
+    use std::ops::ControlFlow;
+    use teloxide::dptree;
+    use teloxide::types::{Me, Update, UpdateKind};
+    use teloxide::Bot;
+
+    let (db, _temp_dir) = test_db().await.unwrap();
+    // Requests fail straight away, as the API URL points at a closed local port
+    let bot = Bot::new("123:test");
+    let mut url = bot.api_url();
+    url.set_scheme("http").unwrap();
+    url.set_host(Some("127.0.0.1")).unwrap();
+    url.set_port(Some(9)).unwrap();
+    let bot = bot.set_api_url(url);
+    let me: Me = serde_json::from_value(serde_json::json!({
+        "id": 1, "is_bot": true, "first_name": "Scheduler", "username": "dnd_scheduler_bot",
+        "can_join_groups": true, "can_read_all_group_messages": false, "supports_inline_queries": true,
+    })).unwrap();
+    // Inline queries come from no chat, so they must not depend on the per-chat dialogue
+    let query = serde_json::from_value(serde_json::json!({
+        "id": "q1", "query": "strahd", "offset": "",
+        "from": { "id": 42, "is_bot": false, "first_name": "Tester" },
+    })).unwrap();
+    let update = Update { id: 1, kind: UpdateKind::InlineQuery(query) };
+
+    let schema = BotHandler::new(db.clone()).schema();
+    let outcome = schema.dispatch(dptree::deps![bot, me, update, DialogueStorage::new(db.pool.clone())]).await;
+    assert!(matches!(outcome, ControlFlow::Break(Ok(()))), "the inline query wasn't handled");
+}
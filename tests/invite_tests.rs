@@ -0,0 +1,110 @@
+#![allow(clippy::unwrap_used)]
+
+use chrono::{TimeZone, Utc};
+use dnd_scheduler_bot::bot::commands::invite::{invite_link, parse_start_payload, render_invite, vote_link, StartPayload};
+use dnd_scheduler_bot::database::models::{Group, Session};
+
+fn group(min_notice_hours: i64) -> Group {
+    Group {
+        id: 1,
+        telegram_chat_id: -1001234567890,
+        timezone: "Europe/Stockholm".to_string(),
+        default_duration: 210,
+        reminder_hours: 24,
+        created_at: Utc::now().to_rfc3339(),
+        store_usernames: true,
+        min_notice_hours,
+        language: "en".to_string(),
+        long_range_every_days: None,
+        long_range_until_days: 14,
+        quiet_start_hour: None,
+        quiet_end_hour: None,
+        announced_version: None,
+        whats_new: true,
+        autoconfirm_threshold: None,
+        badges: true,
+    }
+}
+
+fn session(title: &str) -> Session {
+    Session {
+        id: "abc".to_string(),
+        group_id: 1,
+        title: title.to_string(),
+        message_id: None,
+        status: "confirmed".to_string(),
+        deadline: None,
+        created_by: 1,
+        created_at: Utc::now().to_rfc3339(),
+        short_id: Some(1),
+        anonymous: false,
+        notes: None,
+        availability: false,
+        reminders_muted: false,
+        last_rendered_at: None,
+        updated_at: None,
+    }
+}
+
+#[test]
+fn test_parse_join_payload() {
+    assert_eq!(
+        parse_start_payload("join_-1001234567890"),
+        Some(StartPayload::Join { chat_id: -1001234567890 })
+    );
+    assert_eq!(parse_start_payload(" join_42 "), Some(StartPayload::Join { chat_id: 42 }));
+}
+
+#[test]
+fn test_parse_unknown_payloads() {
+    assert_eq!(parse_start_payload(""), None);
+    assert_eq!(parse_start_payload("join_"), None);
+    assert_eq!(parse_start_payload("join_abc"), None);
+    assert_eq!(parse_start_payload("vote_"), None);
+    assert_eq!(parse_start_payload("vote_a b"), None);
+    assert_eq!(parse_start_payload("poll_123"), None);
+}
+
+#[test]
+fn test_vote_link_round_trips() {
+    let session_id = "3f2b8c1e-5d4a-4e7b-9c0d-1a2b3c4d5e6f";
+    let link = vote_link("dnd_scheduler_bot", session_id);
+    assert_eq!(link, format!("https://t.me/dnd_scheduler_bot?start=vote_{session_id}"));
+    
+    let payload = link.split_once("?start=").unwrap().1;
+    // Telegram allows at most 64 characters in a payload
+    assert!(payload.len() <= 64);
+    assert_eq!(parse_start_payload(payload), Some(StartPayload::Vote { session_id: session_id.to_string() }));
+}
+
+#[test]
+fn test_invite_link_round_trips() {
+    let link = invite_link("dnd_scheduler_bot", -1001234567890);
+    assert_eq!(link, "https://t.me/dnd_scheduler_bot?start=join_-1001234567890");
+    
+    let payload = link.split_once("?start=").unwrap().1;
+    assert_eq!(parse_start_payload(payload), Some(StartPayload::Join { chat_id: -1001234567890 }));
+}
+
+#[test]
+fn test_render_invite_describes_setup() {
+    let text = render_invite(&group(0), None, None);
+    assert!(text.contains("How voting works"));
+    assert!(text.contains("Timezone: Europe/Stockholm"));
+    assert!(text.contains("Sessions usually last 3h 30m"));
+    assert!(!text.contains("notice"));
+    assert!(text.contains("nothing confirmed yet"));
+    assert!(!text.contains("Join the roster"));
+}
+
+#[test]
+fn test_render_invite_with_next_session_and_link() {
+    let starts_at = Utc.with_ymd_and_hms(2030, 3, 15, 18, 0, 0).unwrap();
+    let next = session("Curse of Strahd, part 2");
+    let link = invite_link("dnd_bot", -100);
+    let text = render_invite(&group(48), Some((&next, starts_at)), Some(&link));
+    
+    assert!(text.contains("at least 48 hours' notice"));
+    assert!(text.contains("Curse of Strahd, part 2 on Friday, 15 March at 18:00 UTC"));
+    assert!(text.contains("[Join the roster](https://t.me/dnd_bot?start=join_-100)"));
+}
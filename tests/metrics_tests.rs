@@ -0,0 +1,61 @@
+#![allow(clippy::unwrap_used)]
+
+use axum_test::TestServer;
+use dnd_scheduler_bot::bot::handlers::callback::{callback_handler, callback_kind};
+use dnd_scheduler_bot::services::health::{HealthService, RuntimeStatus};
+use dnd_scheduler_bot::testing::test_db;
+use std::sync::Arc;
+use teloxide::types::{CallbackQuery, User, UserId};
+use teloxide::Bot;
+
+fn button_press(data: &str) -> CallbackQuery {
+    CallbackQuery {
+        id: "1".to_string(),
+        from: User {
+            id: UserId(42),
+            is_bot: false,
+            first_name: "Tester".to_string(),
+            last_name: None,
+            username: None,
+            language_code: None,
+            is_premium: false,
+            added_to_attachment_menu: false,
+        },
+        // Without a message there is no chat to report the error to, so no Telegram call is made
+        message: None,
+        inline_message_id: None,
+        chat_instance: "test".to_string(),
+        data: Some(data.to_string()),
+        game_short_name: None,
+    }
+}
+
+#[test]
+fn test_callback_kind() {
+    assert_eq!(callback_kind("settings:timezone"), "settings");
+    assert_eq!(callback_kind("list:page:2"), "list_page");
+    assert_eq!(callback_kind("cancel:abort"), "cancel");
+    assert_eq!(callback_kind("schedule_confirm:create:abc"), "schedule_confirm");
+    assert_eq!(callback_kind("note:def"), "note");
+    assert_eq!(callback_kind("abc:def:yes"), "vote");
+}
+
+#[tokio::test]
+async fn test_metrics_count_handled_callbacks() {
+    let (db, _dir) = test_db().await.unwrap();
+    let service = HealthService::new(Arc::new(db.clone()), Arc::new(RuntimeStatus::default()));
+    let server = TestServer::new(service.router).unwrap();
+
+    callback_handler(Bot::new("123:test"), button_press("not-a-vote"), db).await.unwrap();
+
+    let response = server.get("/metrics").await;
+    response.assert_status_ok();
+    let text = response.text();
+    let line = text.lines()
+        .find(|line| line.starts_with("bot_callbacks_total{kind=\"vote\",outcome=\"validation_error\"}"))
+        .unwrap_or_else(|| panic!("counter missing from /metrics:\n{text}"));
+    let count: u64 = line.rsplit(' ').next().unwrap().parse().unwrap();
+    assert!(count >= 1);
+    assert!(text.contains("# TYPE db_query_duration_seconds histogram"));
+    assert!(text.contains("db_pool_connections "));
+}
@@ -0,0 +1,32 @@
+use dnd_scheduler_bot::bot::permissions::{permission_denied_message, AdminCache};
+use std::time::Duration;
+use teloxide::types::{ChatId, UserId};
+
+#[test]
+fn test_admin_cache_returns_fresh_entries() {
+    let cache = AdminCache::new(Duration::from_secs(60));
+    let chat = ChatId(-1001234567890);
+    assert_eq!(cache.get(chat), None);
+    
+    cache.insert(chat, vec![UserId(1), UserId(2)]);
+    assert_eq!(cache.get(chat), Some(vec![UserId(1), UserId(2)]));
+    assert_eq!(cache.get(ChatId(-1009876543210)), None);
+}
+
+#[test]
+fn test_admin_cache_expires_entries() {
+    let cache = AdminCache::new(Duration::ZERO);
+    let chat = ChatId(-1001234567890);
+    
+    cache.insert(chat, vec![UserId(1)]);
+    assert_eq!(cache.get(chat), None);
+}
+
+#[test]
+fn test_permission_denied_message_names_who_can() {
+    let message = permission_denied_message("cancel sessions");
+    assert!(message.contains("session creator"));
+    assert!(message.contains("chat administrators"));
+    assert!(message.contains("bot admins"));
+    assert!(message.ends_with("can cancel sessions"));
+}
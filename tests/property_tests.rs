@@ -0,0 +1,86 @@
+use chrono::{DateTime, TimeZone, Utc};
+use dnd_scheduler_bot::testing::{check_response_value, check_time_options, check_title};
+use dnd_scheduler_bot::utils::datetime::{parse_datetime, parse_datetime_detailed, Confidence, DateTimeFormat, ParseOutcome};
+use dnd_scheduler_bot::utils::validation::{
+    validate_response_type, validate_session_title, validate_time_options, MAX_TIME_OPTIONS,
+};
+use proptest::prelude::*;
+
+fn weekday() -> impl Strategy<Value = &'static str> {
+    prop::sample::select(vec![
+        "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday",
+        "måndag", "fredag", "lördag", "lundi", "vendredi", "samedi",
+    ])
+}
+
+/// Time options the bot accepts, e.g. "Friday 19:00" or "samedi 14.30"
+fn time_option() -> impl Strategy<Value = String> {
+    (weekday(), 0u32..24, 0u32..60, prop::bool::ANY).prop_map(|(day, hour, minute, dot)| {
+        let separator = if dot { '.' } else { ':' };
+        format!("{day} {hour:02}{separator}{minute:02}")
+    })
+}
+
+/// Any minute between 2000 and 2030 (the range two-digit years map into)
+fn session_datetime() -> impl Strategy<Value = DateTime<Utc>> {
+    let start = Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap().timestamp() / 60;
+    let end = Utc.with_ymd_and_hms(2030, 12, 31, 23, 59, 0).unwrap().timestamp() / 60;
+    (start..=end).prop_map(|minutes| Utc.timestamp_opt(minutes * 60, 0).unwrap())
+}
+
+proptest! {
+    #[test]
+    fn valid_option_lists_are_accepted_up_to_the_limit(
+        options in prop::collection::vec(time_option(), 1..=MAX_TIME_OPTIONS + 5),
+        padding in "[ ]{0,3}",
+    ) {
+        let input = options.join(&format!("{padding},{padding}"));
+        let result = validate_time_options(&input);
+
+        prop_assert_eq!(result.is_ok(), check_time_options(&options).is_ok());
+        if let Ok(parsed) = result {
+            prop_assert_eq!(parsed, options);
+        }
+    }
+
+    #[test]
+    fn every_accepted_option_parses_with_a_weekday_and_time(option in time_option()) {
+        let parsed = parse_datetime_detailed(&option);
+
+        prop_assert_eq!(parsed.matched_format, DateTimeFormat::Natural);
+        prop_assert_eq!(parsed.confidence, Confidence::High);
+        prop_assert!(parsed.value > Utc::now());
+    }
+
+    #[test]
+    fn european_dates_round_trip(dt in session_datetime(), short_year in prop::bool::ANY) {
+        let input = if short_year {
+            dt.format("%d.%m.%y %H:%M").to_string()
+        } else {
+            dt.format("%d.%m.%Y %H:%M").to_string()
+        };
+        let parsed = parse_datetime_detailed(&input);
+
+        prop_assert_eq!(parsed.matched_format, DateTimeFormat::European);
+        prop_assert_eq!(parsed.value, dt);
+        prop_assert_eq!(parse_datetime(&input), ParseOutcome::Exact(dt));
+    }
+
+    #[test]
+    fn iso_timestamps_round_trip(dt in session_datetime()) {
+        let parsed = parse_datetime_detailed(&dt.to_rfc3339());
+
+        prop_assert_eq!(parsed.matched_format, DateTimeFormat::Iso8601);
+        prop_assert_eq!(parsed.value, dt);
+    }
+
+    #[test]
+    fn title_validation_matches_title_invariant(title in "[a-zA-Z0-9 &!:'-]{0,120}") {
+        prop_assert_eq!(validate_session_title(&title).is_ok(), check_title(&title).is_ok());
+    }
+
+    #[test]
+    fn only_known_response_values_are_accepted(response in "[a-z]{0,8}") {
+        prop_assert_eq!(validate_response_type(&response).is_ok(), check_response_value(&response).is_ok());
+    }
+}
@@ -1,68 +1,57 @@
 #![allow(clippy::unwrap_used)]
 
-use dnd_scheduler_bot::database::models::{Reminder, Session, Group, SessionOption};
-use dnd_scheduler_bot::database::connection::DatabaseManager;
-use tempfile::{tempdir, TempDir};
-use chrono::{Utc, Duration};
-
-async fn setup_test_db() -> (DatabaseManager, TempDir) {
-    let dir = tempdir().unwrap();
-    let db_path = dir.path().join("test.db");
-    let db_url = format!("sqlite:{}", db_path.to_string_lossy());
-    
-    let db = DatabaseManager::new(&db_url).await.unwrap();
-    db.run_migrations().await.unwrap();
-    (db, dir)
-}
+use dnd_scheduler_bot::database::models::{NotesByOption, Reminder, Session, Group, ReminderDelivery, DeliveryOutcome, UNREACHABLE_GROUP_PAUSE_DAYS};
+use dnd_scheduler_bot::testing::{test_db, GroupBuilder, SessionBuilder, SessionOptionBuilder};
+use chrono::{Utc, Duration, TimeZone};
 
 #[tokio::test]
 async fn test_reminder_creation() {
-    let (db, _temp_dir) = setup_test_db().await;
+    let (db, _temp_dir) = test_db().await.unwrap();
     
     // Create group and session first for foreign key constraint
-    let group = Group::create(&db.pool, 12345).await.unwrap();
-    let session = Session::create(&db.pool, group.id, "Test Session".to_string(), 67890).await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
     
-    let days_before = 7i64;
+    let offset_minutes = 7 * 1440i64;
     
-    let reminder = Reminder::create(&db.pool, session.id.clone(), days_before)
+    let reminder = Reminder::create(&db.pool, session.id.clone(), offset_minutes)
         .await
         .unwrap();
     
     assert_eq!(reminder.session_id, session.id);
-    assert_eq!(reminder.days_before, days_before);
+    assert_eq!(reminder.offset_minutes, offset_minutes);
     assert!(!reminder.id.is_empty());
     assert!(!reminder.sent_at.is_empty());
 }
 
 #[tokio::test] 
 async fn test_reminder_exists() {
-    let (db, _temp_dir) = setup_test_db().await;
+    let (db, _temp_dir) = test_db().await.unwrap();
     
     // Create group and session first for foreign key constraint
-    let group = Group::create(&db.pool, 12346).await.unwrap();
-    let session = Session::create(&db.pool, group.id, "Test Session 2".to_string(), 67891).await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
     
-    let days_before = 14i64;
+    let offset_minutes = 14 * 1440i64;
     
     // Should not exist initially
-    let exists = Reminder::exists(&db.pool, &session.id, days_before)
+    let exists = Reminder::exists(&db.pool, &session.id, offset_minutes)
         .await
         .unwrap();
     assert!(!exists);
     
     // Create reminder
-    Reminder::create(&db.pool, session.id.clone(), days_before)
+    Reminder::create(&db.pool, session.id.clone(), offset_minutes)
         .await
         .unwrap();
     
     // Should exist now
-    let exists = Reminder::exists(&db.pool, &session.id, days_before)
+    let exists = Reminder::exists(&db.pool, &session.id, offset_minutes)
         .await
         .unwrap();
     assert!(exists);
     
-    // Different days_before should not exist
+    // Different offset_minutes should not exist
     let exists_different = Reminder::exists(&db.pool, &session.id, 3)
         .await
         .unwrap();
@@ -71,16 +60,16 @@ async fn test_reminder_exists() {
 
 #[tokio::test]
 async fn test_reminder_find_by_session() {
-    let (db, _temp_dir) = setup_test_db().await;
+    let (db, _temp_dir) = test_db().await.unwrap();
     
     // Create group and session first for foreign key constraint
-    let group = Group::create(&db.pool, 12347).await.unwrap();
-    let session = Session::create(&db.pool, group.id, "Test Session 3".to_string(), 67892).await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
     
     // Create multiple reminders for same session
-    Reminder::create(&db.pool, session.id.clone(), 14).await.unwrap();
-    Reminder::create(&db.pool, session.id.clone(), 7).await.unwrap();
-    Reminder::create(&db.pool, session.id.clone(), 3).await.unwrap();
+    Reminder::create(&db.pool, session.id.clone(), 14 * 1440).await.unwrap();
+    Reminder::create(&db.pool, session.id.clone(), 120).await.unwrap();
+    Reminder::create(&db.pool, session.id.clone(), 3 * 1440).await.unwrap();
     
     let reminders = Reminder::find_by_session(&db.pool, &session.id)
         .await
@@ -88,10 +77,10 @@ async fn test_reminder_find_by_session() {
     
     assert_eq!(reminders.len(), 3);
     
-    // Should be ordered by days_before DESC
-    assert_eq!(reminders[0].days_before, 14);
-    assert_eq!(reminders[1].days_before, 7);
-    assert_eq!(reminders[2].days_before, 3);
+    // Should be ordered by offset_minutes DESC
+    assert_eq!(reminders[0].offset_minutes, 14 * 1440);
+    assert_eq!(reminders[1].offset_minutes, 3 * 1440);
+    assert_eq!(reminders[2].offset_minutes, 120);
     
     // All should have same session_id
     for reminder in &reminders {
@@ -101,47 +90,46 @@ async fn test_reminder_find_by_session() {
 
 #[tokio::test]
 async fn test_reminder_unique_constraint() {
-    let (db, _temp_dir) = setup_test_db().await;
+    let (db, _temp_dir) = test_db().await.unwrap();
     
     // Create group and session first for foreign key constraint
-    let group = Group::create(&db.pool, 12348).await.unwrap();
-    let session = Session::create(&db.pool, group.id, "Test Session 4".to_string(), 67893).await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
     
-    let days_before = 7i64;
+    let offset_minutes = 7 * 1440i64;
     
     // Create first reminder
-    let first = Reminder::create(&db.pool, session.id.clone(), days_before)
+    let first = Reminder::create(&db.pool, session.id.clone(), offset_minutes)
         .await
         .unwrap();
     
     // Attempting to create duplicate should fail
-    let result = Reminder::create(&db.pool, session.id.clone(), days_before).await;
+    let result = Reminder::create(&db.pool, session.id.clone(), offset_minutes).await;
     assert!(result.is_err());
     
-    // But different days_before should work
+    // But different offset_minutes should work
     let different = Reminder::create(&db.pool, session.id.clone(), 3)
         .await
         .unwrap();
     
     assert_eq!(first.session_id, different.session_id);
-    assert_ne!(first.days_before, different.days_before);
+    assert_ne!(first.offset_minutes, different.offset_minutes);
 }
 
 #[tokio::test]
 async fn test_reminder_with_real_session() {
-    let (db, _temp_dir) = setup_test_db().await;
+    let (db, _temp_dir) = test_db().await.unwrap();
     
     // Create group first
-    let group = Group::create(&db.pool, 12345).await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
     
     // Create session
-    let session = Session::create(&db.pool, group.id, "Test Session".to_string(), 67890)
-        .await
-        .unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
     
     // Create session option
-    let future_date = Utc::now() + Duration::days(10);
-    let _option = SessionOption::create(&db.pool, session.id.clone(), future_date, 240)
+    SessionOptionBuilder::new(&session.id)
+        .at(Utc::now() + Duration::days(10))
+        .create(&db.pool)
         .await
         .unwrap();
     
@@ -163,13 +151,11 @@ async fn test_reminder_with_real_session() {
 
 #[tokio::test]
 async fn test_reminder_cleanup_on_session_delete() {
-    let (db, _temp_dir) = setup_test_db().await;
+    let (db, _temp_dir) = test_db().await.unwrap();
     
     // Create group and session
-    let group = Group::create(&db.pool, 54321).await.unwrap();
-    let session = Session::create(&db.pool, group.id, "Temp Session".to_string(), 98765)
-        .await
-        .unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
     
     // Create reminder
     let _reminder = Reminder::create(&db.pool, session.id.clone(), 7)
@@ -189,4 +175,765 @@ async fn test_reminder_cleanup_on_session_delete() {
     // Reminder should no longer exist
     let exists_after = Reminder::exists(&db.pool, &session.id, 7).await.unwrap();
     assert!(!exists_after);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_plan_reminders_statuses() {
+    use dnd_scheduler_bot::services::reminder::{plan_reminders, ReminderStatus};
+    
+    let now = Utc::now();
+    // 7 days out: the 2 week reminder is past, the 1 week one due, the rest ahead
+    let starts_at = now + Duration::days(7);
+    
+    let plan = plan_reminders(starts_at, now, &[], &[], None, None);
+    let statuses: Vec<_> = plan.iter().map(|r| (r.interval.offset_minutes(), r.status)).collect();
+    assert_eq!(statuses, vec![
+        (14 * 1440, ReminderStatus::Missed),
+        (7 * 1440, ReminderStatus::Due),
+        (3 * 1440, ReminderStatus::Scheduled),
+        (0, ReminderStatus::Scheduled),
+        (120, ReminderStatus::Scheduled),
+    ]);
+    assert_eq!(plan[2].send_at, starts_at - Duration::days(3));
+    
+    let plan = plan_reminders(starts_at, now, &[14 * 1440, 7 * 1440], &[], None, None);
+    assert_eq!(plan[0].status, ReminderStatus::Sent);
+    assert_eq!(plan[1].status, ReminderStatus::Sent);
+    
+    // A reminder is due from its send time until the grace period is over
+    let plan = plan_reminders(now + Duration::days(3) - Duration::minutes(50), now, &[], &[], None, None);
+    assert_eq!(plan[2].status, ReminderStatus::Due);
+    let plan = plan_reminders(now + Duration::days(3) - Duration::minutes(90), now, &[], &[], None, None);
+    assert_eq!(plan[2].status, ReminderStatus::Missed);
+    let plan = plan_reminders(now + Duration::days(3) + Duration::minutes(10), now, &[], &[], None, None);
+    assert_eq!(plan[2].status, ReminderStatus::Scheduled);
+    
+    // The 2 hour reminder goes out with the first sweep after it's due
+    let plan = plan_reminders(now + Duration::minutes(110), now, &[], &[], None, None);
+    assert_eq!(plan[4].status, ReminderStatus::Due);
+    assert_eq!(plan[4].send_at, now - Duration::minutes(10));
+}
+
+#[tokio::test]
+async fn test_render_reminder_preview() {
+    use chrono::TimeZone;
+    use dnd_scheduler_bot::services::reminder::{plan_reminders, render_reminder_preview};
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).title("Curse of Strahd (Part 2)").create(&db.pool).await.unwrap();
+    
+    let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+    let starts_at = Utc.with_ymd_and_hms(2026, 3, 10, 19, 0, 0).unwrap();
+    let plan = plan_reminders(starts_at, now, &[14 * 1440], &[], None, None);
+    let text = render_reminder_preview(&session, starts_at, &plan, "UTC");
+    
+    assert!(text.contains("Curse of Strahd \\(Part 2\\)"));
+    assert!(text.contains("Tuesday, 10 March at 19:00 UTC"));
+    assert!(text.contains("• 2 Week Reminder: Tuesday, 24 February at 19:00 UTC \\- ✅ sent"));
+    assert!(text.contains("• 1 Week Reminder: Tuesday, 03 March at 19:00 UTC \\- ⏳ scheduled"));
+    assert!(text.contains("• 3 Day Reminder: Saturday, 07 March at 19:00 UTC \\- ⏳ scheduled"));
+    assert!(text.contains("• Game Day: Tuesday, 10 March at 09:00 UTC \\- ⏳ scheduled"));
+    assert!(text.contains("• 2 Hour Reminder: Tuesday, 10 March at 17:00 UTC \\- ⏳ scheduled"));
+    assert!(text.contains("checked every 15 minutes"));
+    
+    // Times are shown in the group's timezone
+    let text = render_reminder_preview(&session, starts_at, &plan, "UTC+2");
+    assert!(text.contains("Starts Tuesday, 10 March at 21:00 UTC\\+2"));
+    assert!(text.contains("• Game Day: Tuesday, 10 March at 11:00 UTC\\+2 \\- ⏳ scheduled"));
+    assert!(!text.contains(" UTC \\- "), "no time is left in UTC");
+    
+    // Region names can't be read without a timezone database, so they stay in UTC
+    let text = render_reminder_preview(&session, starts_at, &plan, "Europe/Stockholm");
+    assert!(text.contains("Starts Tuesday, 10 March at 19:00 UTC\n"));
+}
+
+#[test]
+fn test_long_range_cadence_across_sweeps() {
+    use chrono::TimeZone;
+    use dnd_scheduler_bot::services::reminder::{plan_reminders, LongRangeCadence, ReminderStatus, REMINDER_SWEEP_MINUTES};
+    
+    // Confirmed 100 days out, pinged every 30 days until the 2 week reminder
+    let confirmed_at = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+    let starts_at = confirmed_at + Duration::days(100) + Duration::hours(7);
+    let cadence = Some(LongRangeCadence { every_days: 30, until_days: 14 });
+    
+    let mut sent: Vec<(i64, chrono::DateTime<Utc>)> = Vec::new();
+    let mut now = confirmed_at;
+    while now < starts_at {
+        let sent_offsets: Vec<i64> = sent.iter().map(|(offset, _)| *offset).collect();
+        for reminder in plan_reminders(starts_at, now, &sent_offsets, &[], cadence, None) {
+            if reminder.status == ReminderStatus::Due {
+                sent.push((reminder.interval.offset_minutes(), now));
+            }
+        }
+        now += Duration::minutes(REMINDER_SWEEP_MINUTES);
+    }
+    
+    let offsets: Vec<i64> = sent.iter().map(|(offset, _)| offset / 1440).collect();
+    assert_eq!(offsets, vec![74, 44, 14, 7, 3, 0, 0]);
+    assert_eq!(sent[5].0, 0);
+    assert_eq!(sent[6].0, 120);
+    // Each goes out with the first sweep at or after its send time
+    assert_eq!(sent[0].1, starts_at - Duration::days(74));
+    assert_eq!(sent[1].1, starts_at - Duration::days(44));
+    
+    // Before it's due the preview lists the upcoming long-range reminders first
+    let plan = plan_reminders(starts_at, confirmed_at, &[], &[], cadence, None);
+    assert_eq!(plan.len(), 7);
+    assert!(plan[0].interval.is_long_range());
+    assert_eq!(plan[0].interval.label(), "Still on the Calendar (74 days)");
+    assert_eq!(plan[1].status, ReminderStatus::Scheduled);
+    assert!(!plan[2].interval.is_long_range());
+    
+    // Offsets a fixed reminder covers aren't doubled
+    let weekly = LongRangeCadence { every_days: 7, until_days: 7 };
+    let days: Vec<i64> = weekly.intervals(starts_at, confirmed_at).iter().map(|i| i.offset_minutes() / 1440).collect();
+    assert_eq!(days.first(), Some(&98));
+    assert_eq!(days.last(), Some(&21));
+    assert!(!days.contains(&14));
+}
+
+#[tokio::test]
+async fn test_long_range_reminders_are_due_once() {
+    use dnd_scheduler_bot::scheduling::SchedulerApi;
+    use dnd_scheduler_bot::testing::{GroupBuilder, SessionBuilder, SessionOptionBuilder};
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    Group::update_long_range_reminders(&db.pool, group.id, Some((30, 14))).await.unwrap();
+    let now = Utc::now();
+    let starts_at = now + Duration::days(100);
+    let session = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await.unwrap();
+    let option = SessionOptionBuilder::new(&session.id).at(starts_at).create(&db.pool).await.unwrap();
+    sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?")
+        .bind(&option.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    
+    // Sweeps on confirmation day, at the 74 day mark, 15 minutes later, and at the 44 day mark
+    assert!(api.due_reminders(now).await.unwrap().is_empty());
+    let first_ping = starts_at - Duration::days(74) + Duration::minutes(5);
+    let due = api.due_reminders(first_ping).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert!(due[0].interval.is_long_range());
+    assert_eq!(due[0].interval.offset_minutes(), 74 * 1440);
+    
+    Reminder::create(&db.pool, session.id.clone(), due[0].interval.offset_minutes()).await.unwrap();
+    assert!(api.due_reminders(first_ping + Duration::minutes(15)).await.unwrap().is_empty());
+    let due = api.due_reminders(starts_at - Duration::days(44)).await.unwrap();
+    assert_eq!(due.iter().map(|d| d.interval.offset_minutes()).collect::<Vec<_>>(), vec![44 * 1440]);
+    
+    // Without a cadence only the fixed reminders go out
+    Group::update_long_range_reminders(&db.pool, group.id, None).await.unwrap();
+    assert!(api.due_reminders(starts_at - Duration::days(44)).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_reminders_for_each_confirmed_option() {
+    use dnd_scheduler_bot::scheduling::SchedulerApi;
+    use dnd_scheduler_bot::services::reminder::next_confirmed_session;
+    use dnd_scheduler_bot::testing::{GroupBuilder, SessionBuilder, SessionOptionBuilder};
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let now = Utc::now();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    let friday = SessionOptionBuilder::new(&session.id).at(now + Duration::days(7)).create(&db.pool).await.unwrap();
+    let saturday = SessionOptionBuilder::new(&session.id).at(now + Duration::days(8)).create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    api.confirm_option(&session.id, 1).await.unwrap();
+    api.confirm_option(&session.id, 2).await.unwrap();
+    
+    let (_, next, _) = next_confirmed_session(&db.pool, group.id, now).await.unwrap().unwrap();
+    assert_eq!(next.id, friday.id);
+    
+    // Each option gets its week-ahead reminder, and sending one leaves the other due
+    let due = api.due_reminders(now).await.unwrap();
+    assert_eq!(due.iter().map(|d| d.option.id.as_str()).collect::<Vec<_>>(), vec![friday.id.as_str()]);
+    Reminder::create_for_option(&db.pool, session.id.clone(), friday.id.clone(), 7 * 1440).await.unwrap();
+    assert!(api.due_reminders(now).await.unwrap().is_empty());
+    
+    let due = api.due_reminders(now + Duration::days(1)).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].option.id, saturday.id);
+    assert_eq!(due[0].interval.offset_minutes(), 7 * 1440);
+    
+    // Reminders recorded for the whole session count for every option
+    Reminder::create(&db.pool, session.id.clone(), 7 * 1440).await.unwrap();
+    assert!(api.due_reminders(now + Duration::days(1)).await.unwrap().is_empty());
+}
+
+#[test]
+fn test_game_day_reminder_send_time() {
+    use chrono::TimeZone;
+    use dnd_scheduler_bot::services::reminder::{ReminderOffset, REMINDER_INTERVALS};
+    
+    let game_day = REMINDER_INTERVALS.iter().find(|interval| interval.is_game_day()).unwrap();
+    
+    // Evening sessions get it with the morning check
+    let evening = Utc.with_ymd_and_hms(2026, 3, 10, 19, 0, 0).unwrap();
+    assert_eq!(game_day.send_time(evening), Utc.with_ymd_and_hms(2026, 3, 10, 9, 0, 0).unwrap());
+    
+    // Early sessions get it an hour before they start
+    let early = Utc.with_ymd_and_hms(2026, 3, 10, 9, 30, 0).unwrap();
+    assert_eq!(game_day.send_time(early), Utc.with_ymd_and_hms(2026, 3, 10, 8, 30, 0).unwrap());
+    
+    let two_hours = REMINDER_INTERVALS.iter().find(|interval| interval.offset == ReminderOffset::Minutes(120)).unwrap();
+    assert!(!two_hours.is_game_day());
+    assert_eq!(two_hours.send_time(evening), Utc.with_ymd_and_hms(2026, 3, 10, 17, 0, 0).unwrap());
+    
+    let week = REMINDER_INTERVALS.iter().find(|interval| interval.offset == ReminderOffset::Days(7)).unwrap();
+    assert!(!week.is_game_day());
+    assert_eq!(week.send_time(evening), evening - Duration::days(7));
+}
+
+#[tokio::test]
+async fn test_next_confirmed_session() {
+    use dnd_scheduler_bot::services::reminder::next_confirmed_session;
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let other_group = GroupBuilder::new().chat_id(-12352).create(&db.pool).await.unwrap();
+    let now = Utc::now();
+    
+    let mut confirmed_ids = Vec::new();
+    for (group_id, status, days) in [
+        (group.id, "confirmed", 10),
+        (group.id, "confirmed", 4),
+        (group.id, "confirmed", -2),
+        (group.id, "active", 1),
+        (other_group.id, "confirmed", 2),
+    ] {
+        let session = SessionBuilder::new(group_id)
+            .title(format!("{status} in {days} days"))
+            .status(status)
+            .create(&db.pool)
+            .await
+            .unwrap();
+        let option = SessionOptionBuilder::new(&session.id).at(now + Duration::days(days)).create(&db.pool).await.unwrap();
+        sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?")
+            .bind(&option.id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        confirmed_ids.push(session.id);
+    }
+    
+    let (session, option, starts_at) = next_confirmed_session(&db.pool, group.id, now).await.unwrap().unwrap();
+    assert_eq!(session.id, confirmed_ids[1]);
+    assert!(option.confirmed);
+    assert!(starts_at > now + Duration::days(3));
+    
+    let empty_group = GroupBuilder::new().chat_id(-12353).create(&db.pool).await.unwrap();
+    assert!(next_confirmed_session(&db.pool, empty_group.id, now).await.unwrap().is_none());
+}
+
+#[test]
+fn test_reminder_offsets() {
+    use dnd_scheduler_bot::services::reminder::ReminderOffset;
+    
+    assert_eq!(ReminderOffset::Days(3).minutes(), 4320);
+    assert_eq!(ReminderOffset::Minutes(120).minutes(), 120);
+    assert_eq!(ReminderOffset::from_minutes(10080), ReminderOffset::Days(7));
+    assert_eq!(ReminderOffset::from_minutes(0), ReminderOffset::Days(0));
+    assert_eq!(ReminderOffset::from_minutes(90), ReminderOffset::Minutes(90));
+    
+    assert_eq!(ReminderOffset::Days(7).to_string(), "7 days");
+    assert_eq!(ReminderOffset::Days(1).to_string(), "1 day");
+    assert_eq!(ReminderOffset::Days(0).to_string(), "game day");
+    assert_eq!(ReminderOffset::Minutes(120).to_string(), "2 hours");
+}
+
+#[test]
+fn test_failed_reminders_stay_due_until_superseded() {
+    use dnd_scheduler_bot::services::reminder::{plan_reminders, ReminderStatus};
+    
+    let starts_at = Utc.with_ymd_and_hms(2030, 5, 3, 19, 0, 0).unwrap();
+    let three_days = 3 * 1440;
+    // The 3 day reminder failed 5 hours ago; it's still sent, late, until game day
+    let now = starts_at - Duration::days(3) + Duration::hours(5);
+    assert_eq!(plan_reminders(starts_at, now, &[], &[], None, None)[2].status, ReminderStatus::Missed);
+    assert_eq!(plan_reminders(starts_at, now, &[], &[three_days], None, None)[2].status, ReminderStatus::Due);
+    
+    // Once the game day reminder is due it takes over
+    let game_day_send = plan_reminders(starts_at, now, &[], &[], None, None)[3].send_at;
+    let later = game_day_send + Duration::minutes(1);
+    let plan = plan_reminders(starts_at, later, &[], &[three_days], None, None);
+    assert_eq!(plan[2].status, ReminderStatus::Missed);
+    assert_eq!(plan[3].status, ReminderStatus::Due);
+    
+    // The last reminder can be retried until the session starts
+    let plan = plan_reminders(starts_at, starts_at - Duration::minutes(5), &[], &[120], None, None);
+    assert_eq!(plan[4].status, ReminderStatus::Due);
+    assert_eq!(plan_reminders(starts_at, starts_at, &[], &[120], None, None)[4].status, ReminderStatus::Missed);
+}
+
+#[test]
+fn test_quiet_hours_window() {
+    use chrono::FixedOffset;
+    use dnd_scheduler_bot::services::reminder::QuietHours;
+    use dnd_scheduler_bot::services::timezone::utc_offset;
+    
+    let utc = FixedOffset::east_opt(0).unwrap();
+    let at = |day, hour, minute| Utc.with_ymd_and_hms(2030, 5, day, hour, minute, 0).unwrap();
+    
+    // 22:00-07:00 wraps around midnight
+    let night = QuietHours { start_hour: 22, end_hour: 7, utc_offset: utc };
+    assert!(!night.contains(at(3, 21, 59)));
+    assert!(night.contains(at(3, 22, 0)));
+    assert!(night.contains(at(4, 0, 30)));
+    assert!(night.contains(at(4, 6, 59)));
+    assert!(!night.contains(at(4, 7, 0)));
+    assert_eq!(night.next_allowed(at(3, 23, 30)), at(4, 7, 0));
+    assert_eq!(night.next_allowed(at(4, 3, 0)), at(4, 7, 0));
+    assert_eq!(night.next_allowed(at(3, 12, 0)), at(3, 12, 0));
+    assert_eq!(night.to_string(), "22:00-07:00");
+    
+    // One that doesn't
+    let early = QuietHours { start_hour: 1, end_hour: 6, utc_offset: utc };
+    assert!(!early.contains(at(3, 0, 59)));
+    assert!(early.contains(at(3, 5, 0)));
+    assert!(!early.contains(at(3, 23, 0)));
+    assert_eq!(early.next_allowed(at(3, 1, 0)), at(3, 6, 0));
+    
+    // Ending at midnight
+    let late = QuietHours { start_hour: 22, end_hour: 0, utc_offset: utc };
+    assert!(late.contains(at(3, 23, 0)));
+    assert!(!late.contains(at(4, 0, 0)));
+    assert_eq!(late.next_allowed(at(3, 22, 15)), at(4, 0, 0));
+    
+    // The hours are on the group's clock: 22:00-07:00 at UTC+2 is 20:00-05:00 UTC
+    let east = QuietHours { utc_offset: FixedOffset::east_opt(2 * 3600).unwrap(), ..night };
+    assert!(east.contains(at(3, 20, 0)));
+    assert!(!east.contains(at(4, 5, 0)));
+    assert_eq!(east.next_allowed(at(3, 21, 0)), at(4, 5, 0));
+    
+    assert_eq!(utc_offset("UTC"), Some(utc));
+    assert_eq!(utc_offset("utc+2"), FixedOffset::east_opt(2 * 3600));
+    assert_eq!(utc_offset("GMT-05:30"), FixedOffset::west_opt(5 * 3600 + 30 * 60));
+    assert_eq!(utc_offset("+01:00"), FixedOffset::east_opt(3600));
+    assert_eq!(utc_offset("Europe/Stockholm"), None);
+    assert_eq!(utc_offset("UTC+25"), None);
+    assert_eq!(utc_offset("UTC+-3"), None);
+}
+
+#[test]
+fn test_quiet_hours_defer_reminders() {
+    use chrono::FixedOffset;
+    use dnd_scheduler_bot::services::reminder::{plan_reminders, QuietHours, ReminderStatus};
+    
+    let quiet = Some(QuietHours { start_hour: 22, end_hour: 7, utc_offset: FixedOffset::east_opt(0).unwrap() });
+    
+    // The 2 week reminder of a session at 23:30 would go out at 23:30 too; it waits until 07:00
+    let starts_at = Utc.with_ymd_and_hms(2030, 5, 17, 23, 30, 0).unwrap();
+    let morning_after = Utc.with_ymd_and_hms(2030, 5, 4, 7, 0, 0).unwrap();
+    let plan = plan_reminders(starts_at, starts_at - Duration::days(14), &[], &[], None, quiet);
+    assert_eq!(plan[0].send_at, morning_after);
+    assert_eq!(plan[0].status, ReminderStatus::Scheduled);
+    let plan = plan_reminders(starts_at, morning_after + Duration::minutes(50), &[], &[], None, quiet);
+    assert_eq!(plan[0].status, ReminderStatus::Due);
+    // Without quiet hours it would be long gone by then
+    let plan = plan_reminders(starts_at, morning_after + Duration::minutes(50), &[], &[], None, None);
+    assert_eq!(plan[0].status, ReminderStatus::Missed);
+    
+    // Reminders outside the window keep their time
+    let plan = plan_reminders(starts_at, starts_at - Duration::days(14), &[], &[], None, quiet);
+    assert_eq!(plan[4].send_at, starts_at - Duration::hours(2));
+    
+    // One that would only go out after the session starts isn't held back
+    let starts_at = Utc.with_ymd_and_hms(2030, 5, 4, 1, 0, 0).unwrap();
+    let plan = plan_reminders(starts_at, starts_at - Duration::days(1), &[], &[], None, quiet);
+    assert_eq!(plan[4].send_at, starts_at - Duration::hours(2));
+    
+    // Retries of a failed reminder wait for the morning too
+    let starts_at = Utc.with_ymd_and_hms(2030, 5, 6, 19, 0, 0).unwrap();
+    let three_days = 3 * 1440;
+    let night = Utc.with_ymd_and_hms(2030, 5, 3, 23, 0, 0).unwrap();
+    assert_eq!(plan_reminders(starts_at, night, &[], &[three_days], None, quiet)[2].status, ReminderStatus::Scheduled);
+    assert_eq!(plan_reminders(starts_at, night, &[], &[three_days], None, None)[2].status, ReminderStatus::Due);
+    let morning = Utc.with_ymd_and_hms(2030, 5, 4, 7, 15, 0).unwrap();
+    assert_eq!(plan_reminders(starts_at, morning, &[], &[three_days], None, quiet)[2].status, ReminderStatus::Due);
+}
+
+#[tokio::test]
+async fn test_send_with_retry_failure_classes() {
+    use dnd_scheduler_bot::utils::outbound::{send_with_retry, RetryPolicy, DEFAULT_RETRY_POLICY};
+    use std::cell::RefCell;
+    use std::time::Duration as StdDuration;
+    use teloxide::{ApiError, RequestError};
+    
+    // Each run answers with `failures` in turn, then succeeds; returns the result, sends and waits
+    async fn run(policy: &RetryPolicy, failures: Vec<RequestError>) -> (Result<u8, RequestError>, u32, Vec<StdDuration>) {
+        let failures = RefCell::new(failures.into_iter());
+        let waits = RefCell::new(Vec::new());
+        let (result, attempts) = send_with_retry(
+            policy,
+            || {
+                let next = failures.borrow_mut().next();
+                async move { next.map_or(Ok(1), Err) }
+            },
+            |wait| {
+                waits.borrow_mut().push(wait);
+                async {}
+            },
+        ).await;
+        (result, attempts, waits.into_inner())
+    }
+    let network = || RequestError::Io(std::io::Error::other("connection reset"));
+    
+    // Network errors back off exponentially, rate limits wait as long as Telegram asks
+    let (result, attempts, waits) = run(&DEFAULT_RETRY_POLICY, vec![network(), RequestError::RetryAfter(StdDuration::from_secs(5))]).await;
+    assert_eq!(result.unwrap(), 1);
+    assert_eq!(attempts, 3);
+    assert_eq!(waits, vec![StdDuration::from_secs(2), StdDuration::from_secs(5)]);
+    
+    // The budget runs out
+    let (result, attempts, waits) = run(&DEFAULT_RETRY_POLICY, vec![network(), network(), network()]).await;
+    assert!(matches!(result, Err(RequestError::Io(_))));
+    assert_eq!(attempts, 3);
+    assert_eq!(waits, vec![StdDuration::from_secs(2), StdDuration::from_secs(4)]);
+    
+    // Too long a rate limit, a removed bot or a rejected message aren't retried here
+    for failure in [
+        RequestError::RetryAfter(StdDuration::from_secs(600)),
+        RequestError::Api(ApiError::BotKicked),
+        RequestError::Api(ApiError::ChatNotFound),
+        RequestError::Api(ApiError::MessageTextIsEmpty),
+    ] {
+        let (result, attempts, waits) = run(&DEFAULT_RETRY_POLICY, vec![failure]).await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+        assert!(waits.is_empty());
+    }
+    
+    // Waits are capped by the policy
+    let patient = RetryPolicy { max_attempts: 6, base_delay: StdDuration::from_secs(10), max_delay: StdDuration::from_secs(30) };
+    let (_, _, waits) = run(&patient, vec![network(), network(), network()]).await;
+    assert_eq!(waits, vec![StdDuration::from_secs(10), StdDuration::from_secs(20), StdDuration::from_secs(30)]);
+}
+
+#[test]
+fn test_classify_send_error() {
+    use dnd_scheduler_bot::utils::outbound::{classify_send_error, SendErrorKind};
+    use teloxide::{ApiError, RequestError};
+    
+    let wait = std::time::Duration::from_secs(3);
+    assert_eq!(classify_send_error(&RequestError::RetryAfter(wait)), SendErrorKind::Transient { retry_after: Some(wait) });
+    assert_eq!(
+        classify_send_error(&RequestError::Io(std::io::Error::other("reset"))),
+        SendErrorKind::Transient { retry_after: None }
+    );
+    for permanent in [ApiError::BotKicked, ApiError::BotKickedFromSupergroup, ApiError::ChatNotFound, ApiError::GroupDeactivated] {
+        assert_eq!(classify_send_error(&RequestError::Api(permanent)), SendErrorKind::Permanent);
+    }
+    assert_eq!(classify_send_error(&RequestError::MigrateToChatId(-100123)), SendErrorKind::Permanent);
+    assert_eq!(classify_send_error(&RequestError::Api(ApiError::MessageTextIsEmpty)), SendErrorKind::Other);
+}
+
+#[tokio::test]
+async fn test_reminder_deliveries() {
+    use dnd_scheduler_bot::scheduling::SchedulerApi;
+    use dnd_scheduler_bot::testing::{GroupBuilder, SessionBuilder, SessionOptionBuilder};
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let other = GroupBuilder::new().chat_id(-1009876543210).create(&db.pool).await.unwrap();
+    let now = Utc::now();
+    let starts_at = now + Duration::days(3) - Duration::hours(5);
+    let session = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await.unwrap();
+    let option = SessionOptionBuilder::new(&session.id).at(starts_at).create(&db.pool).await.unwrap();
+    sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?")
+        .bind(&option.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let three_days = 3 * 1440;
+    
+    // Past its grace period the 3 day reminder only goes out because it failed before
+    assert!(api.due_reminders(now).await.unwrap().is_empty());
+    ReminderDelivery::record(&db.pool, &session.id, group.id, three_days, DeliveryOutcome::Failed, 3, Some("timeout")).await.unwrap();
+    assert_eq!(ReminderDelivery::failed_offsets(&db.pool, &session.id).await.unwrap(), vec![three_days]);
+    let due = api.due_reminders(now).await.unwrap();
+    assert_eq!(due.iter().map(|d| d.interval.offset_minutes()).collect::<Vec<_>>(), vec![three_days]);
+    
+    // Sending it settles the failure
+    ReminderDelivery::record(&db.pool, &session.id, group.id, three_days, DeliveryOutcome::Sent, 1, None).await.unwrap();
+    Reminder::create(&db.pool, session.id.clone(), three_days).await.unwrap();
+    assert!(ReminderDelivery::failed_offsets(&db.pool, &session.id).await.unwrap().is_empty());
+    
+    // A group is unreachable while its latest delivery says so, and only for a while
+    let week_ago = now - Duration::days(UNREACHABLE_GROUP_PAUSE_DAYS);
+    assert!(ReminderDelivery::unreachable_group_ids(&db.pool, week_ago).await.unwrap().is_empty());
+    ReminderDelivery::record(&db.pool, &session.id, group.id, 0, DeliveryOutcome::Unreachable, 1, Some("bot was kicked")).await.unwrap();
+    let unreachable = ReminderDelivery::unreachable_group_ids(&db.pool, week_ago).await.unwrap();
+    assert!(unreachable.contains(&group.id) && !unreachable.contains(&other.id));
+    assert!(ReminderDelivery::unreachable_group_ids(&db.pool, now + Duration::minutes(1)).await.unwrap().is_empty());
+    ReminderDelivery::record(&db.pool, &session.id, group.id, 0, DeliveryOutcome::Sent, 1, None).await.unwrap();
+    assert!(ReminderDelivery::unreachable_group_ids(&db.pool, week_ago).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_pending_reminders_dry_run() {
+    use dnd_scheduler_bot::bot::commands::reminders::render_pending_reminders;
+    use dnd_scheduler_bot::services::reminder::pending_reminders;
+    use dnd_scheduler_bot::testing::{GroupBuilder, SessionBuilder, SessionOptionBuilder};
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let other = GroupBuilder::new().chat_id(-1009876543210).create(&db.pool).await.unwrap();
+    let now = Utc::now();
+    let confirmed_at = |group_id: i64, title: &'static str, starts_at| {
+        let pool = db.pool.clone();
+        async move {
+            let session = SessionBuilder::new(group_id).title(title).status("confirmed").create(&pool).await.unwrap();
+            let option = SessionOptionBuilder::new(&session.id).at(starts_at).create(&pool).await.unwrap();
+            sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?")
+                .bind(&option.id)
+                .execute(&pool)
+                .await
+                .unwrap();
+            session
+        }
+    };
+    
+    // The 3 day reminder went due 10 minutes ago; a session 5 days out has nothing due
+    let soon = confirmed_at(group.id, "Soon", now + Duration::days(3) - Duration::minutes(10)).await;
+    confirmed_at(group.id, "Later", now + Duration::days(5)).await;
+    let elsewhere = confirmed_at(other.id, "Elsewhere", now + Duration::days(7) - Duration::minutes(5)).await;
+    
+    let pending = pending_reminders(&db.pool, now, Some(group.id)).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].due.session.id, soon.id);
+    assert_eq!(pending[0].due.interval.offset_minutes(), 3 * 1440);
+    assert_eq!(pending[0].chat_id, Some(group.telegram_chat_id));
+    assert!(!pending[0].paused);
+    let text = render_pending_reminders(&pending);
+    assert!(text.contains("Soon"), "{text}");
+    assert!(text.contains(&format!("3 Day Reminder to chat {}", group.telegram_chat_id)), "{text}");
+    assert!(text.contains("Nothing was sent"), "{text}");
+    
+    // Without a group every group's reminders count, and unreachable groups are flagged
+    ReminderDelivery::record(&db.pool, &elsewhere.id, other.id, 0, DeliveryOutcome::Unreachable, 1, Some("bot was kicked")).await.unwrap();
+    let pending = pending_reminders(&db.pool, now, None).await.unwrap();
+    assert_eq!(pending.len(), 2);
+    let paused: Vec<_> = pending.iter().filter(|p| p.paused).map(|p| p.due.session.id.clone()).collect();
+    assert_eq!(paused, vec![elsewhere.id.clone()]);
+    
+    // Computing them sends and records nothing
+    assert!(Reminder::find_by_session(&db.pool, &soon.id).await.unwrap().is_empty());
+    let later = pending_reminders(&db.pool, now + Duration::hours(2), Some(group.id)).await.unwrap();
+    assert!(later.is_empty());
+    assert!(render_pending_reminders(&later).starts_with("No reminders are due"));
+}
+
+#[tokio::test]
+async fn test_stopped_sweep_leaves_reminders_for_after_the_restart() {
+    use dnd_scheduler_bot::services::reminder::{pending_reminders, send_reminders_until};
+    use dnd_scheduler_bot::services::shutdown::Shutdown;
+    use dnd_scheduler_bot::testing::{GroupBuilder, SessionBuilder, SessionOptionBuilder};
+    use teloxide::Bot;
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let now = Utc::now();
+    let session = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await.unwrap();
+    let option = SessionOptionBuilder::new(&session.id).at(now + Duration::days(3) - Duration::minutes(10)).create(&db.pool).await.unwrap();
+    sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?")
+        .bind(&option.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let pending = pending_reminders(&db.pool, now, None).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    
+    // Once shutdown started, nothing is sent or marked, so the reminder stays due
+    let stop = Shutdown::new();
+    stop.trigger();
+    let sweep = send_reminders_until(&Bot::new("123:test"), &db, pending, &stop).await.unwrap();
+    assert_eq!((sweep.sent, sweep.failed, sweep.skipped, sweep.left), (0, 0, 0, 1));
+    assert!(Reminder::find_by_session(&db.pool, &session.id).await.unwrap().is_empty());
+    assert_eq!(pending_reminders(&db.pool, now, None).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_reminder_sweep_queries_do_not_grow_with_sessions() {
+    use dnd_scheduler_bot::database::connection::{DatabaseManager, PoolSettings};
+    use dnd_scheduler_bot::services::reminder::{pending_reminders, send_reminders_until};
+    use dnd_scheduler_bot::services::shutdown::Shutdown;
+    use dnd_scheduler_bot::testing::{GroupBuilder, ResponseBuilder, SessionBuilder, SessionOptionBuilder};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use teloxide::Bot;
+    
+    // Every query outside a transaction takes a connection from the pool, so counting those counts them
+    async fn sweep_queries(sessions: i64) -> usize {
+        let (db, temp_dir) = test_db().await.unwrap();
+        let now = Utc::now();
+        let mut groups = Vec::new();
+        for i in 0..3 {
+            groups.push(GroupBuilder::new().chat_id(-1001000000000 - i).create(&db.pool).await.unwrap());
+        }
+        for i in 0..sessions {
+            let session = SessionBuilder::new(groups[i as usize % 3].id).status("confirmed").create(&db.pool).await.unwrap();
+            let option = SessionOptionBuilder::new(&session.id).at(now + Duration::days(3) - Duration::minutes(10)).create(&db.pool).await.unwrap();
+            sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?").bind(&option.id).execute(&db.pool).await.unwrap();
+            ResponseBuilder::new(&option).user(i + 1, None).create(&db.pool).await.unwrap();
+        }
+        
+        let acquired = Arc::new(AtomicUsize::new(0));
+        let counter = acquired.clone();
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .before_acquire(move |_, _| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Ok(true) })
+            })
+            .connect(&format!("sqlite:{}", temp_dir.path().join("test.db").display()))
+            .await
+            .unwrap();
+        let counted = DatabaseManager { pool, settings: PoolSettings::default() };
+        
+        let pending = pending_reminders(&counted.pool, now, None).await.unwrap();
+        assert_eq!(pending.len() as i64, sessions);
+        // A stopped sweep sends nothing, but still loads what sending needs
+        let stop = Shutdown::new();
+        stop.trigger();
+        send_reminders_until(&Bot::new("123:test"), &counted, pending, &stop).await.unwrap();
+        acquired.load(Ordering::SeqCst)
+    }
+    
+    let queries = sweep_queries(1).await;
+    assert!(queries > 0);
+    assert_eq!(sweep_queries(12).await, queries);
+}
+
+/// An active poll with a message to reply to and `voters` players who voted on its one option
+async fn posted_poll(
+    pool: &sqlx::SqlitePool,
+    builder: dnd_scheduler_bot::testing::SessionBuilder,
+    voters: i64,
+) -> Session {
+    use dnd_scheduler_bot::testing::{ResponseBuilder, SessionOptionBuilder};
+    
+    let session = builder.create(pool).await.unwrap();
+    Session::set_message_id(pool, &session.id, 100).await.unwrap();
+    let option = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(10)).create(pool).await.unwrap();
+    for user_id in 0..voters {
+        ResponseBuilder::new(&option).user(1000 + user_id, None).response("yes").create(pool).await.unwrap();
+    }
+    Session::find_by_id(pool, &session.id).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn test_due_vote_reminders() {
+    use dnd_scheduler_bot::database::models::ReminderKind;
+    use dnd_scheduler_bot::scheduling::{SchedulerApi, VoteReminderSettings};
+    use dnd_scheduler_bot::testing::{GroupBuilder, SessionBuilder};
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let now = Utc::now();
+    let api = SchedulerApi::new(db.pool.clone());
+    let settings = VoteReminderSettings { after_hours: 48, min_voters: 2, interval_hours: 72 };
+    let due_titles = |at| {
+        let api = api.clone();
+        async move {
+            let mut titles: Vec<String> = api.due_vote_reminders(at, settings).await.unwrap()
+                .into_iter().map(|due| due.session.title).collect();
+            titles.sort();
+            titles
+        }
+    };
+    
+    let quiet = posted_poll(&db.pool, SessionBuilder::new(group.id).title("Quiet"), 1).await;
+    posted_poll(&db.pool, SessionBuilder::new(group.id).title("Busy"), 2).await;
+    posted_poll(&db.pool, SessionBuilder::new(group.id).title("Closing").deadline(now + Duration::hours(60)), 2).await;
+    posted_poll(&db.pool, SessionBuilder::new(group.id).title("Confirmed").status("confirmed"), 0).await;
+    let rollcall = posted_poll(&db.pool, SessionBuilder::new(group.id).title("Roll call"), 0).await;
+    sqlx::query("INSERT INTO rollcalls (session_id, quorum, expires_at) VALUES (?, 3, ?)")
+        .bind(&rollcall.id)
+        .bind((now + Duration::hours(3)).to_rfc3339())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    // Nothing to reply to
+    SessionBuilder::new(group.id).title("Unposted").create(&db.pool).await.unwrap();
+    
+    // Fresh polls with a far deadline are left alone
+    assert!(due_titles(now).await.is_empty());
+    
+    // Two days on, the poll with too few voters and the one closing within a day are due
+    let later = now + Duration::hours(49);
+    assert_eq!(due_titles(later).await, vec!["Closing", "Quiet"]);
+    let due = api.due_vote_reminders(later, settings).await.unwrap();
+    let quiet_due = due.iter().find(|due| due.session.id == quiet.id).unwrap();
+    assert_eq!(quiet_due.voters, 1);
+    assert_eq!(quiet_due.deadline, None);
+    assert!(quiet_due.open_minutes > 48 * 60);
+    
+    // Once reminded, a poll waits out the interval before the next one
+    Reminder::create_kind(&db.pool, quiet.id.clone(), ReminderKind::Vote, quiet_due.open_minutes).await.unwrap();
+    assert_eq!(due_titles(later).await, vec!["Closing"]);
+    assert_eq!(due_titles(now + Duration::hours(73)).await, vec!["Quiet"]);
+    
+    // Vote reminders are no session reminders
+    assert!(Reminder::exists_kind(&db.pool, &quiet.id, ReminderKind::Vote, quiet_due.open_minutes).await.unwrap());
+    assert!(!Reminder::exists(&db.pool, &quiet.id, quiet_due.open_minutes).await.unwrap());
+    assert!(Reminder::find_by_session(&db.pool, &quiet.id).await.unwrap().is_empty());
+    
+    // Without voters to wait for only the deadline counts, and an interval of 0 turns them off
+    let deadline_only = VoteReminderSettings { min_voters: 0, ..settings };
+    let due = api.due_vote_reminders(later, deadline_only).await.unwrap();
+    assert_eq!(due.iter().map(|due| due.session.title.as_str()).collect::<Vec<_>>(), vec!["Closing"]);
+    let off = VoteReminderSettings { interval_hours: 0, ..settings };
+    assert!(api.due_vote_reminders(later, off).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_muted_sessions_skip_their_reminders_for_good() {
+    use dnd_scheduler_bot::bot::commands::list::render_list_page;
+    use dnd_scheduler_bot::scheduling::{SchedulerApi, VoteReminderSettings};
+    use dnd_scheduler_bot::services::reminder::{pending_reminders, send_reminders};
+    use dnd_scheduler_bot::testing::{GroupBuilder, SessionBuilder, SessionOptionBuilder};
+    use teloxide::Bot;
+    
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let now = Utc::now();
+    let api = SchedulerApi::new(db.pool.clone());
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&session.id).at(now + Duration::days(7) - Duration::minutes(10)).create(&db.pool).await.unwrap();
+    api.confirm_option(&session.id, 1).await.unwrap();
+    Session::set_reminders_muted(&db.pool, &session.id, true).await.unwrap();
+    
+    // The week-ahead reminder is due but only marked as sent; nothing goes to Telegram
+    let pending = pending_reminders(&db.pool, now, None).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    let sweep = send_reminders(&Bot::new("123:test"), &db, pending).await.unwrap();
+    assert_eq!((sweep.sent, sweep.failed, sweep.skipped, sweep.muted), (0, 0, 0, 1));
+    assert!(Reminder::exists(&db.pool, &session.id, 7 * 1440).await.unwrap());
+    assert!(api.due_reminders(now + Duration::days(1)).await.unwrap().is_empty());
+    
+    // Unmuting late doesn't bring it back; the next interval fires as usual
+    Session::set_reminders_muted(&db.pool, &session.id, false).await.unwrap();
+    assert!(api.due_reminders(now + Duration::days(1)).await.unwrap().is_empty());
+    let due = api.due_reminders(now + Duration::days(4)).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].interval.offset_minutes(), 3 * 1440);
+    
+    // Muted polls get no vote reminders either, and /list marks muted sessions
+    let poll = posted_poll(&db.pool, SessionBuilder::new(group.id).title("Surprise"), 0).await;
+    let settings = VoteReminderSettings::default();
+    assert_eq!(api.due_vote_reminders(now + Duration::days(3), settings).await.unwrap().len(), 1);
+    Session::set_reminders_muted(&db.pool, &poll.id, true).await.unwrap();
+    assert!(api.due_vote_reminders(now + Duration::days(3), settings).await.unwrap().is_empty());
+    
+    let poll = Session::find_by_id(&db.pool, &poll.id).await.unwrap().unwrap();
+    let (text, _) = render_list_page(&[poll], &[], &[], &NotesByOption::default(), 0, &[], 0, 1, 1);
+    assert!(text.contains("**Surprise** \\#2 🔕\n"), "{text}");
+}
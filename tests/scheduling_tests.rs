@@ -0,0 +1,544 @@
+#![allow(clippy::unwrap_used)]
+
+use chrono::{Duration, TimeZone, Utc};
+use dnd_scheduler_bot::bot::commands::schedule::render_duplicate_warning;
+use dnd_scheduler_bot::database::models::*;
+use dnd_scheduler_bot::scheduling::{title_similarity, SchedulerApi, SchedulingError, DUPLICATE_TITLE_SIMILARITY};
+use dnd_scheduler_bot::testing::*;
+
+#[tokio::test]
+async fn test_create_session() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+
+    let session = api.create_session(group.id, "Tomb of Annihilation", 7, true).await.unwrap();
+    assert_eq!(session.status, "active");
+    assert_eq!(session.created_by, 7);
+    assert!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().anonymous);
+
+    let err = api.create_session(group.id, "x", 7, false).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+    let err = api.create_session(group.id + 100, "Tomb of Annihilation", 7, false).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotFound { .. }), "{err}");
+}
+
+#[tokio::test]
+async fn test_add_option_checks_the_time() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    Group::update_min_notice_hours(&db.pool, group.id, 24).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let session = api.create_session(group.id, "Storm King's Thunder", 1, false).await.unwrap();
+    let starts_at = Utc::now() + Duration::days(3);
+
+    let option = api.add_option(&session.id, starts_at, None).await.unwrap();
+    assert_eq!(option.duration, group.default_duration);
+    assert_eq!(api.add_option(&session.id, starts_at + Duration::days(1), Some(90)).await.unwrap().duration, 90);
+
+    for rejected in [starts_at, Utc::now() - Duration::days(1), Utc::now() + Duration::hours(2)] {
+        let err = api.add_option(&session.id, rejected, None).await.unwrap_err();
+        assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+    }
+    assert_eq!(SessionOption::find_by_session(&db.pool, &session.id).await.unwrap().len(), 2);
+
+    let cancelled = SessionBuilder::new(group.id).status("cancelled").create(&db.pool).await.unwrap();
+    let err = api.add_option(&cancelled.id, starts_at, None).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotActive { ref status } if status == "cancelled"), "{err}");
+}
+
+#[tokio::test]
+async fn test_record_response() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    let option = SessionOptionBuilder::new(&session.id).create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+
+    api.record_response(&session.id, &option.id, 5, Some("mira".to_string()), "maybe").await.unwrap();
+    let response = api.record_response(&session.id, &option.id, 5, Some("mira".to_string()), "yes").await.unwrap();
+    assert_eq!(response.response, "yes");
+    assert_eq!(Response::find_by_session(&db.pool, &session.id).await.unwrap().len(), 1);
+
+    let err = api.record_response(&session.id, &option.id, 5, None, "perhaps").await.unwrap_err();
+    assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+    let err = api.record_response(&session.id, "no-such-option", 5, None, "yes").await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotFound { .. }), "{err}");
+    let err = api.record_response("no-such-session", &option.id, 5, None, "yes").await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotFound { .. }), "{err}");
+
+    // Confirmed sessions still take votes, so attendance stays current; cancelled ones don't
+    let confirmed = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await.unwrap();
+    let confirmed_option = SessionOptionBuilder::new(&confirmed.id).create(&db.pool).await.unwrap();
+    api.record_response(&confirmed.id, &confirmed_option.id, 5, None, "no").await.unwrap();
+    let cancelled = SessionBuilder::new(group.id).status("cancelled").create(&db.pool).await.unwrap();
+    let cancelled_option = SessionOptionBuilder::new(&cancelled.id).create(&db.pool).await.unwrap();
+    let err = api.record_response(&cancelled.id, &cancelled_option.id, 5, None, "yes").await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotActive { .. }), "{err}");
+}
+
+#[tokio::test]
+async fn test_tally_and_confirm() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    let early = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(2)).create(&db.pool).await.unwrap();
+    let late = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(3)).create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+
+    api.record_response(&session.id, &early.id, 1, None, "maybe").await.unwrap();
+    let tally = api.tally(&session.id).await.unwrap();
+    assert_eq!(tally.options.len(), 2);
+    assert_eq!(tally.counts[0].maybe, 1);
+    assert!(tally.leader.is_none());
+    assert!(matches!(api.confirm(&session.id).await.unwrap_err(), SchedulingError::NoYesVotes));
+
+    api.record_response(&session.id, &late.id, 1, None, "yes").await.unwrap();
+    api.record_response(&session.id, &late.id, 2, None, "yes").await.unwrap();
+    let tally = api.tally(&session.id).await.unwrap();
+    assert_eq!(tally.responses.len(), 3);
+    assert_eq!(tally.leader.unwrap().winner.option.id, late.id);
+
+    let selection = api.confirm(&session.id).await.unwrap();
+    assert_eq!(selection.winner.option.id, late.id);
+    assert_eq!(selection.winner.counts.yes, 2);
+    assert_eq!(selection.runner_up.unwrap().option.id, early.id);
+    assert_eq!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().status, "confirmed");
+    check_stored_session(&db.pool, &session.id).await.unwrap();
+
+    let err = api.confirm(&session.id).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotActive { ref status } if status == "confirmed"), "{err}");
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await.unwrap();
+    assert_eq!(options.iter().filter(|option| option.confirmed).count(), 1);
+}
+
+#[tokio::test]
+async fn test_confirm_option() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    let early = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(2)).create(&db.pool).await.unwrap();
+    let late = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(3)).create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    api.record_response(&session.id, &late.id, 1, None, "yes").await.unwrap();
+
+    // Options are numbered from 1, and an option without votes can be picked over the leader
+    for number in [0, 3] {
+        let err = api.confirm_option(&session.id, number).await.unwrap_err();
+        assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+    }
+    let selection = api.confirm_option(&session.id, 1).await.unwrap();
+    assert_eq!(selection.winner.option.id, early.id);
+    assert!(selection.winner.option.confirmed);
+    assert_eq!(selection.winner.counts.yes, 0);
+    assert_eq!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().status, "confirmed");
+
+    // A second option joins the first instead of replacing it
+    let selection = api.confirm_option(&session.id, 2).await.unwrap();
+    assert_eq!(selection.winner.option.id, late.id);
+    assert_eq!(selection.winner.counts.yes, 1);
+    let options = SessionOption::find_by_session(&db.pool, &session.id).await.unwrap();
+    assert!(options.iter().all(|option| option.confirmed));
+    check_stored_session(&db.pool, &session.id).await.unwrap();
+
+    let err = api.confirm_option(&session.id, 2).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+
+    let cancelled = SessionBuilder::new(group.id).status("cancelled").create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&cancelled.id).at(Utc::now() + Duration::days(2)).create(&db.pool).await.unwrap();
+    let err = api.confirm_option(&cancelled.id, 1).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotActive { ref status } if status == "cancelled"), "{err}");
+}
+
+#[tokio::test]
+async fn test_set_deadline_only_on_active_sessions() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let now = Utc::now();
+    let deadline = now + Duration::days(1);
+
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&session.id).at(now + Duration::days(3)).create(&db.pool).await.unwrap();
+    assert_eq!(api.set_deadline(&session.id, deadline, now, false).await.unwrap(), None);
+    let stored = Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().deadline;
+    assert_eq!(stored, Some(deadline.to_rfc3339()));
+    // The deadline it replaces comes back for /undo
+    assert_eq!(api.set_deadline(&session.id, deadline + Duration::hours(1), now, false).await.unwrap(), stored);
+
+    let err = api.set_deadline(&session.id, now - Duration::hours(1), now, false).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+
+    for status in ["confirmed", "cancelled", "closed", "archived"] {
+        let settled = SessionBuilder::new(group.id).status(status).create(&db.pool).await.unwrap();
+        let err = api.set_deadline(&settled.id, deadline, now, true).await.unwrap_err();
+        assert!(matches!(err, SchedulingError::NotActive { status: ref s } if s == status), "{status}: {err}");
+        assert_eq!(Session::find_by_id(&db.pool, &settled.id).await.unwrap().unwrap().deadline, None, "{status}");
+    }
+}
+
+#[tokio::test]
+async fn test_deadline_after_the_latest_option_needs_force() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let now = Utc::now();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    let latest = now + Duration::days(3);
+    SessionOptionBuilder::new(&session.id).at(now + Duration::days(2)).create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&session.id).at(latest).create(&db.pool).await.unwrap();
+
+    let late = latest + Duration::hours(1);
+    let err = api.set_deadline(&session.id, late, now, false).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::DeadlineAfterOptions { latest: l } if l.timestamp() == latest.timestamp()), "{err}");
+    assert_eq!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().deadline, None);
+
+    // Between the options is fine, and forcing sets the late one anyway
+    api.set_deadline(&session.id, now + Duration::days(2) + Duration::hours(12), now, false).await.unwrap();
+    api.set_deadline(&session.id, late, now, true).await.unwrap();
+    let stored = Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().deadline;
+    assert_eq!(stored, Some(late.to_rfc3339()));
+}
+
+#[tokio::test]
+async fn test_confirming_and_cancelling_clear_the_deadline() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let now = Utc::now();
+    let deadline = now + Duration::days(1);
+
+    let confirmed = SessionBuilder::new(group.id).deadline(deadline).create(&db.pool).await.unwrap();
+    let option = SessionOptionBuilder::new(&confirmed.id).at(now + Duration::days(2)).create(&db.pool).await.unwrap();
+    api.record_response(&confirmed.id, &option.id, 1, None, "yes").await.unwrap();
+    api.confirm(&confirmed.id).await.unwrap();
+
+    let picked = SessionBuilder::new(group.id).deadline(deadline).create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&picked.id).at(now + Duration::days(2)).create(&db.pool).await.unwrap();
+    api.confirm_option(&picked.id, 1).await.unwrap();
+
+    let rollcall_now = Utc.with_ymd_and_hms(2030, 6, 1, 12, 0, 0).unwrap();
+    let starts_at = rollcall_now + Duration::hours(1);
+    let (expired, _, _) = api.create_rollcall(group.id, 1, starts_at, 3, rollcall_now).await.unwrap();
+    api.set_deadline(&expired.id, rollcall_now + Duration::minutes(30), rollcall_now, false).await.unwrap();
+    assert_eq!(api.expire_rollcalls(starts_at).await.unwrap().len(), 1);
+
+    for session in [&confirmed, &picked, &expired] {
+        let stored = Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap();
+        assert!(matches!(stored.status.as_str(), "confirmed" | "cancelled"), "{}", stored.status);
+        assert_eq!(stored.deadline, None, "{}", stored.status);
+        check_stored_session(&db.pool, &session.id).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_autoconfirm_before_the_deadline() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let now = Utc::now();
+
+    let err = Group::update_setting(&db.pool, group.id, &GroupSetting::AutoconfirmThreshold(Some(1))).await.unwrap_err();
+    assert!(matches!(err, GroupSettingError::AutoconfirmThresholdOutOfRange(1)), "{err}");
+    Group::update_setting(&db.pool, group.id, &GroupSetting::AutoconfirmThreshold(Some(2))).await.unwrap();
+
+    // Only polls with a deadline still ahead of them are confirmed early
+    let mut sessions = Vec::new();
+    for deadline in [Some(now + Duration::days(1)), None, Some(now - Duration::hours(1))] {
+        let builder = SessionBuilder::new(group.id);
+        let session = match deadline {
+            Some(deadline) => builder.deadline(deadline),
+            None => builder,
+        }
+        .create(&db.pool)
+        .await
+        .unwrap();
+        let option = SessionOptionBuilder::new(&session.id).at(now + Duration::days(2)).create(&db.pool).await.unwrap();
+        api.record_response(&session.id, &option.id, 1, None, "yes").await.unwrap();
+        sessions.push((session, option));
+    }
+    assert!(api.autoconfirm(now).await.unwrap().is_empty(), "one yes is below the threshold");
+
+    for (session, option) in &sessions {
+        api.record_response(&session.id, &option.id, 2, None, "yes").await.unwrap();
+    }
+    let confirmed = api.autoconfirm(now).await.unwrap();
+    assert_eq!(confirmed.len(), 1);
+    assert_eq!(confirmed[0].session.id, sessions[0].0.id);
+    assert_eq!((confirmed[0].session.status.as_str(), confirmed[0].session.deadline.as_deref()), ("confirmed", None));
+    assert_eq!((confirmed[0].selection.winner.option.id.as_str(), confirmed[0].threshold), (sessions[0].1.id.as_str(), 2));
+    check_stored_session(&db.pool, &sessions[0].0.id).await.unwrap();
+    assert!(api.autoconfirm(now).await.unwrap().is_empty(), "already confirmed");
+
+    for (session, _) in &sessions[1..] {
+        assert_eq!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().status, "active");
+    }
+}
+
+#[tokio::test]
+async fn test_required_player_saying_no_rules_an_option_out() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let session = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    let early = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(2)).create(&db.pool).await.unwrap();
+    let late = SessionOptionBuilder::new(&session.id).at(Utc::now() + Duration::days(3)).create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let dm = 10;
+    assert!(RosterMember::require(&db.pool, group.id, dm, Some("dm_dana".to_string())).await.unwrap());
+    assert!(!RosterMember::require(&db.pool, group.id, dm, None).await.unwrap());
+
+    // The early option leads; the DM not voting at all doesn't count against it
+    for user_id in [1, 2, 3] {
+        api.record_response(&session.id, &early.id, user_id, None, "yes").await.unwrap();
+    }
+    api.record_response(&session.id, &late.id, 1, None, "yes").await.unwrap();
+    let tally = api.tally(&session.id).await.unwrap();
+    assert!(tally.vetoed.is_empty());
+    assert_eq!(tally.leader.unwrap().winner.option.id, early.id);
+
+    // Nor does a "maybe" from them
+    api.record_response(&session.id, &early.id, dm, None, "maybe").await.unwrap();
+    assert_eq!(api.tally(&session.id).await.unwrap().leader.unwrap().winner.option.id, early.id);
+
+    // A "no" hands the lead to the other option, however many yes votes the first has
+    api.record_response(&session.id, &early.id, dm, None, "no").await.unwrap();
+    let tally = api.tally(&session.id).await.unwrap();
+    assert_eq!(tally.vetoed, [early.id.as_str()]);
+    let leader = tally.leader.unwrap();
+    assert_eq!(leader.winner.option.id, late.id);
+    assert!(leader.runner_up.is_none());
+
+    // A "no" from a player who isn't required is just a vote
+    api.record_response(&session.id, &late.id, 2, None, "no").await.unwrap();
+    assert_eq!(api.tally(&session.id).await.unwrap().vetoed, [early.id.as_str()]);
+
+    // With every option ruled out there's nothing for /confirm to pick, but one can be confirmed by number
+    api.record_response(&session.id, &late.id, dm, None, "no").await.unwrap();
+    assert!(api.tally(&session.id).await.unwrap().leader.is_none());
+    assert!(matches!(api.confirm(&session.id).await.unwrap_err(), SchedulingError::RequiredPlayerMissing));
+    let selection = api.confirm_option(&session.id, 1).await.unwrap();
+    assert_eq!(selection.winner.option.id, early.id);
+
+    // Players stop counting once they're no longer required
+    assert!(RosterMember::unrequire(&db.pool, group.id, dm).await.unwrap());
+    assert!(!RosterMember::unrequire(&db.pool, group.id, dm).await.unwrap());
+    assert!(api.tally(&session.id).await.unwrap().vetoed.is_empty());
+    let roster = RosterMember::find_by_group(&db.pool, group.id).await.unwrap();
+    assert_eq!(roster.len(), 1);
+    assert!(!roster[0].required);
+}
+
+#[tokio::test]
+async fn test_rollcall_waits_for_required_players() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let now = Utc.with_ymd_and_hms(2030, 6, 1, 12, 0, 0).unwrap();
+    let tonight = Utc.with_ymd_and_hms(2030, 6, 1, 20, 0, 0).unwrap();
+    RosterMember::require(&db.pool, group.id, 10, None).await.unwrap();
+
+    let (session, option, _) = api.create_rollcall(group.id, 1, tonight, 2, now).await.unwrap();
+    api.record_response(&session.id, &option.id, 10, None, "no").await.unwrap();
+    api.record_response(&session.id, &option.id, 1, None, "yes").await.unwrap();
+    api.record_response(&session.id, &option.id, 2, None, "yes").await.unwrap();
+    assert!(api.confirm_rollcall(&session.id).await.unwrap().is_none());
+    assert_eq!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().status, "active");
+
+    // Changing their mind to "maybe" lets the quorum confirm it
+    api.record_response(&session.id, &option.id, 10, None, "maybe").await.unwrap();
+    assert!(api.confirm_rollcall(&session.id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_due_reminders() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let now = Utc::now();
+    let session = SessionBuilder::new(group.id).status("confirmed").create(&db.pool).await.unwrap();
+    let option = SessionOptionBuilder::new(&session.id).at(now + Duration::days(7)).create(&db.pool).await.unwrap();
+    sqlx::query("UPDATE session_options SET confirmed = 1 WHERE id = ?")
+        .bind(&option.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    // Active sessions get no reminders
+    let active = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&active.id).at(now + Duration::days(7)).create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+
+    let due = api.due_reminders(now).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].session.id, session.id);
+    assert_eq!(due[0].option.id, option.id);
+    assert_eq!(due[0].interval.offset_minutes(), 7 * 1440);
+
+    Reminder::create(&db.pool, session.id.clone(), 7 * 1440).await.unwrap();
+    assert!(api.due_reminders(now).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_rollcall_confirms_at_quorum() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let now = Utc.with_ymd_and_hms(2030, 6, 1, 12, 0, 0).unwrap();
+    let tonight = Utc.with_ymd_and_hms(2030, 6, 1, 20, 0, 0).unwrap();
+
+    for (starts_at, quorum) in [
+        (tonight, 0),
+        (now - Duration::hours(2), 3),
+        (tonight + Duration::days(1), 3),
+    ] {
+        let err = api.create_rollcall(group.id, 1, starts_at, quorum, now).await.unwrap_err();
+        assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+    }
+
+    let (session, option, rollcall) = api.create_rollcall(group.id, 1, tonight, 2, now).await.unwrap();
+    assert_eq!(session.title, "Roll call for 20:00");
+    assert_eq!(rollcall.expires_at_utc(), Some(now + Duration::hours(3)));
+    assert_eq!(SessionOption::find_by_session(&db.pool, &session.id).await.unwrap().len(), 1);
+
+    // Nothing happens until the second yes
+    api.record_response(&session.id, &option.id, 1, None, "yes").await.unwrap();
+    api.record_response(&session.id, &option.id, 2, None, "no").await.unwrap();
+    assert!(api.confirm_rollcall(&session.id).await.unwrap().is_none());
+    api.record_response(&session.id, &option.id, 3, None, "yes").await.unwrap();
+    let selection = api.confirm_rollcall(&session.id).await.unwrap().unwrap();
+    assert_eq!(selection.winner.option.id, option.id);
+    assert_eq!(selection.winner.counts.yes, 2);
+    assert_eq!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().status, "confirmed");
+    check_stored_session(&db.pool, &session.id).await.unwrap();
+
+    // Later yes votes don't confirm it again, and it no longer expires
+    api.record_response(&session.id, &option.id, 4, None, "yes").await.unwrap();
+    assert!(api.confirm_rollcall(&session.id).await.unwrap().is_none());
+    assert!(api.expire_rollcalls(now + Duration::hours(4)).await.unwrap().is_empty());
+
+    // Ordinary sessions are left alone
+    let ordinary = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    let ordinary_option = SessionOptionBuilder::new(&ordinary.id).create(&db.pool).await.unwrap();
+    api.record_response(&ordinary.id, &ordinary_option.id, 1, None, "yes").await.unwrap();
+    assert!(api.confirm_rollcall(&ordinary.id).await.unwrap().is_none());
+    assert_eq!(Session::find_by_id(&db.pool, &ordinary.id).await.unwrap().unwrap().status, "active");
+}
+
+#[tokio::test]
+async fn test_availability_poll_gets_its_start_time_before_confirming() {
+    use dnd_scheduler_bot::bot::poll::render_poll;
+    use dnd_scheduler_bot::scheduling::AVAILABILITY_START_HOUR;
+    use dnd_scheduler_bot::utils::datetime::expand_days;
+
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    // A Saturday at noon, so the poll can still ask about this evening
+    let now = Utc.with_ymd_and_hms(2030, 6, 1, 12, 0, 0).unwrap();
+
+    let days = expand_days("this weekend", now.date_naive()).unwrap();
+    let (session, options) = api.create_availability_poll(group.id, "Lost Mine", 1, &days, now).await.unwrap();
+    assert!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().availability);
+    let starts: Vec<String> = options.iter().map(|option| option.datetime.clone()).collect();
+    assert_eq!(starts, [
+        Utc.with_ymd_and_hms(2030, 6, 1, AVAILABILITY_START_HOUR, 0, 0).unwrap().to_rfc3339(),
+        Utc.with_ymd_and_hms(2030, 6, 2, AVAILABILITY_START_HOUR, 0, 0).unwrap().to_rfc3339(),
+    ]);
+    // Once every day of the range is over there's nothing to ask about
+    let late = Utc.with_ymd_and_hms(2030, 6, 2, 19, 0, 0).unwrap();
+    let err = api.create_availability_poll(group.id, "Lost Mine", 1, &days, late).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+
+    api.record_response(&session.id, &options[0].id, 1, None, "maybe").await.unwrap();
+    api.record_response(&session.id, &options[1].id, 1, None, "yes").await.unwrap();
+    api.record_response(&session.id, &options[1].id, 2, None, "yes").await.unwrap();
+    let responses = Response::find_by_session(&db.pool, &session.id).await.unwrap();
+    let (text, _) = render_poll(&session, &options, &responses, None);
+    assert!(text.contains("Which days work for you?"), "{text}");
+    assert!(text.contains("**2\\. Sunday, 02 June**"), "{text}");
+    assert!(text.contains("🔥 **Best days**\n🟩🟩 Sun 02\\.06  2 yes, 0 maybe\n🟨⬜ Sat 01\\.06  0 yes, 1 maybe"), "{text}");
+
+    // The start time has to be on the picked day and still ahead
+    let sunday = |hour| Utc.with_ymd_and_hms(2030, 6, 2, hour, 30, 0).unwrap();
+    for starts_at in [sunday(10) + Duration::days(1), sunday(10)] {
+        let err = api.set_option_start(&session.id, &options[1].id, starts_at, sunday(11)).await.unwrap_err();
+        assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+    }
+    assert_eq!(api.set_option_start(&session.id, &options[1].id, sunday(15), now).await.unwrap(), 2);
+    let selection = api.confirm_option(&session.id, 2).await.unwrap();
+    assert_eq!(selection.winner.option.datetime, sunday(15).to_rfc3339());
+
+    // Only active availability polls have days to move
+    let err = api.set_option_start(&session.id, &options[0].id, now + Duration::hours(1), now).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotActive { .. }), "{err}");
+    let ordinary = SessionBuilder::new(group.id).create(&db.pool).await.unwrap();
+    let option = SessionOptionBuilder::new(&ordinary.id).create(&db.pool).await.unwrap();
+    let err = api.set_option_start(&ordinary.id, &option.id, Utc::now() + Duration::days(30), Utc::now()).await.unwrap_err();
+    assert!(matches!(err, SchedulingError::Invalid(_)), "{err}");
+}
+
+#[tokio::test]
+async fn test_rollcall_expires_without_quorum() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let now = Utc.with_ymd_and_hms(2030, 6, 1, 12, 0, 0).unwrap();
+
+    // A game sooner than the usual expiry gives up when it starts
+    let soon = now + Duration::hours(1);
+    let (session, option, rollcall) = api.create_rollcall(group.id, 1, soon, 3, now).await.unwrap();
+    assert_eq!(rollcall.expires_at_utc(), Some(soon));
+    api.record_response(&session.id, &option.id, 1, None, "yes").await.unwrap();
+    assert!(api.confirm_rollcall(&session.id).await.unwrap().is_none());
+
+    assert!(api.expire_rollcalls(now + Duration::minutes(30)).await.unwrap().is_empty());
+    let expired = api.expire_rollcalls(soon).await.unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].rollcall.quorum, 3);
+    assert_eq!(expired[0].tally.session.status, "cancelled");
+    assert_eq!(expired[0].tally.counts[0].yes, 1);
+    assert_eq!(Session::find_by_id(&db.pool, &session.id).await.unwrap().unwrap().status, "cancelled");
+
+    // It's called off once, and votes after that aren't taken
+    assert!(api.expire_rollcalls(soon + Duration::hours(1)).await.unwrap().is_empty());
+    let err = api.record_response(&session.id, &option.id, 2, None, "yes").await.unwrap_err();
+    assert!(matches!(err, SchedulingError::NotActive { .. }), "{err}");
+}
+
+#[test]
+fn test_title_similarity() {
+    assert_eq!(title_similarity("Weekly D&D", "weekly  d&d!"), 1.0);
+    assert!(title_similarity("Weekly D&D", "Weekly DnD") >= DUPLICATE_TITLE_SIMILARITY);
+    assert!(title_similarity("Curse of Strahd", "Curse of Strhad") >= DUPLICATE_TITLE_SIMILARITY);
+    assert!(title_similarity("Curse of Strahd", "Tomb of Annihilation") < DUPLICATE_TITLE_SIMILARITY);
+    assert!(title_similarity("One-shot", "Session zero") < DUPLICATE_TITLE_SIMILARITY);
+    assert_eq!(title_similarity("abc", "xyz"), 0.0);
+}
+
+#[tokio::test]
+async fn test_find_suspected_duplicates() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let group = GroupBuilder::new().create(&db.pool).await.unwrap();
+    let api = SchedulerApi::new(db.pool.clone());
+    let saturday = Utc.with_ymd_and_hms(2030, 6, 15, 19, 0, 0).unwrap();
+
+    let weekly = SessionBuilder::new(group.id).title("Weekly D&D").create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&weekly.id).at(saturday + Duration::days(7)).create(&db.pool).await.unwrap();
+    let oneshot = SessionBuilder::new(group.id).title("Goblin one-shot").create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&oneshot.id).at(saturday - Duration::hours(5)).create(&db.pool).await.unwrap();
+    let cancelled = SessionBuilder::new(group.id).title("Weekly DnD").status("cancelled").create(&db.pool).await.unwrap();
+    SessionOptionBuilder::new(&cancelled.id).at(saturday).create(&db.pool).await.unwrap();
+
+    // A similar title, and another session on the same day; cancelled sessions don't count
+    let suspects = api.find_suspected_duplicates(group.id, "weekly dnd", &[saturday]).await.unwrap();
+    let found: Vec<(&str, bool, usize)> = suspects.iter()
+        .map(|s| (s.session.title.as_str(), s.similar_title, s.shared_dates.len()))
+        .collect();
+    assert_eq!(found, [("Weekly D&D", true, 0), ("Goblin one-shot", false, 1)]);
+    assert_eq!(suspects[1].shared_dates[0], saturday.date_naive());
+    let warning = render_duplicate_warning("weekly dnd", &suspects);
+    assert!(warning.contains(&format!("• {} Weekly D&D (similar title)\n", weekly.display_ref())), "{warning}");
+    assert!(warning.contains(&format!("• {} Goblin one-shot (also on Sat 15.06)\n", oneshot.display_ref())), "{warning}");
+    assert!(warning.ends_with("Create 'weekly dnd' anyway?"), "{warning}");
+
+    let suspects = api.find_suspected_duplicates(group.id, "Session zero", &[saturday + Duration::days(1)]).await.unwrap();
+    assert!(suspects.is_empty());
+}
@@ -1,5 +1,63 @@
+use dnd_scheduler_bot::bot::selection::{
+    confirmable_maybe_swing, maybe_swing, pick_winning_option, select_autoconfirm_option, select_best_option, MaybeSwing,
+    VoteWeights, DEFAULT_VOTE_WEIGHTS,
+};
+use dnd_scheduler_bot::bot::poll::VoteCounts;
+use dnd_scheduler_bot::bot::duties::{assign_duties, duty_label, render_duties};
+use dnd_scheduler_bot::database::models::{Duty, DutyAssignment, Response, SessionOption};
 use dnd_scheduler_bot::utils::markdown::escape_markdown;
 
+fn votes(yes: usize, no: usize, maybe: usize) -> VoteCounts {
+    VoteCounts { yes, no, maybe }
+}
+
+fn option(id: &str, datetime: &str) -> SessionOption {
+    SessionOption {
+        id: id.to_string(),
+        session_id: "session".to_string(),
+        datetime: datetime.to_string(),
+        duration: 240,
+        confirmed: false,
+    }
+}
+
+/// One response per entry of `answers`, each from a different user
+fn responses(option_id: &str, answers: &[&str]) -> Vec<Response> {
+    answers.iter().enumerate()
+        .map(|(i, answer)| Response {
+            id: format!("{option_id}-{i}"),
+            session_id: "session".to_string(),
+            option_id: option_id.to_string(),
+            user_id: i as i64,
+            username: None,
+            response: answer.to_string(),
+            created_at: "2026-03-01T12:00:00+00:00".to_string(),
+            updated_at: "2026-03-01T12:00:00+00:00".to_string(),
+        })
+        .collect()
+}
+
+fn duty(id: i64, name: &str) -> Duty {
+    Duty { id, group_id: 1, name: name.to_string(), created_at: "2026-03-01T12:00:00+00:00".to_string() }
+}
+
+/// Assignments for consecutive sessions, oldest first, as `(duty_id, user_id)` pairs
+fn history(sessions: &[&[(i64, i64)]]) -> Vec<DutyAssignment> {
+    let mut history = Vec::new();
+    for (i, assignments) in sessions.iter().enumerate() {
+        for &(duty_id, user_id) in *assignments {
+            history.push(DutyAssignment {
+                id: history.len() as i64 + 1,
+                session_id: format!("session-{i}"),
+                duty_id,
+                user_id,
+                assigned_at: "2026-03-01T12:00:00+00:00".to_string(),
+            });
+        }
+    }
+    history
+}
+
 #[test]
 fn test_list_escape_markdown_basic() {
     assert_eq!(escape_markdown("Game Night"), "Game Night");
@@ -32,4 +90,267 @@ fn test_list_escape_markdown_all_special() {
     let input = "_*[]()~`>#+-=|{}.!";
     let expected = "\\_\\*\\[\\]\\(\\)\\~\\`\\>\\#\\+\\-\\=\\|\\{\\}\\.\\!";
     assert_eq!(escape_markdown(input), expected);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_vote_score_weights_maybe_as_half() {
+    assert_eq!(DEFAULT_VOTE_WEIGHTS.score(&votes(3, 0, 0)), 3.0);
+    assert_eq!(DEFAULT_VOTE_WEIGHTS.score(&votes(2, 4, 3)), 3.5);
+    assert_eq!(DEFAULT_VOTE_WEIGHTS.score(&votes(0, 5, 0)), 0.0);
+}
+
+#[test]
+fn test_winner_prefers_many_maybes_over_a_few_more_yes() {
+    let counts = [votes(3, 0, 0), votes(2, 0, 10)];
+    assert_eq!(pick_winning_option(&counts, &DEFAULT_VOTE_WEIGHTS), Some((1, 7.0)));
+}
+
+#[test]
+fn test_winner_ties_broken_by_yes_count() {
+    // Both score 3.0, the second with more firm commitments
+    let counts = [votes(2, 0, 2), votes(3, 1, 0)];
+    assert_eq!(pick_winning_option(&counts, &DEFAULT_VOTE_WEIGHTS), Some((1, 3.0)));
+    
+    // Fully tied options keep the earlier one
+    let counts = [votes(2, 0, 1), votes(2, 3, 1)];
+    assert_eq!(pick_winning_option(&counts, &DEFAULT_VOTE_WEIGHTS), Some((0, 2.5)));
+}
+
+#[test]
+fn test_winner_requires_a_yes_vote() {
+    assert_eq!(pick_winning_option(&[], &DEFAULT_VOTE_WEIGHTS), None);
+    assert_eq!(pick_winning_option(&[votes(0, 2, 4), votes(0, 1, 0)], &DEFAULT_VOTE_WEIGHTS), None);
+    
+    // Once anyone says yes, maybes elsewhere can still win
+    let counts = [votes(0, 0, 4), votes(1, 0, 0)];
+    assert_eq!(pick_winning_option(&counts, &DEFAULT_VOTE_WEIGHTS), Some((0, 2.0)));
+}
+
+#[test]
+fn test_winner_with_custom_weights() {
+    let yes_only = VoteWeights { yes: 1.0, maybe: 0.0 };
+    let counts = [votes(3, 0, 0), votes(2, 0, 10)];
+    assert_eq!(pick_winning_option(&counts, &yes_only), Some((0, 3.0)));
+}
+
+fn swing(option: usize, converts: usize, maybes: usize, yes: usize, winner_yes: usize) -> MaybeSwing {
+    MaybeSwing { option, converts, maybes, yes, winner_yes }
+}
+
+#[test]
+fn test_maybe_swing_when_the_runner_up_could_overtake() {
+    // One of Sunday's maybes turning to yes only draws level, which Saturday keeps; both make it 5-4
+    let counts = [votes(4, 0, 1), votes(3, 1, 2)];
+    assert_eq!(pick_winning_option(&counts, &DEFAULT_VOTE_WEIGHTS), Some((0, 4.5)));
+    assert_eq!(maybe_swing(&counts, &DEFAULT_VOTE_WEIGHTS), Some(swing(1, 2, 2, 5, 4)));
+
+    // Saturday only leads on yes votes, so one maybe is enough
+    let counts = [votes(4, 0, 0), votes(3, 0, 2)];
+    assert_eq!(maybe_swing(&counts, &DEFAULT_VOTE_WEIGHTS), Some(swing(1, 1, 2, 4, 4)));
+}
+
+#[test]
+fn test_maybe_swing_ties_follow_the_winner_rules() {
+    // Converting every maybe only draws level with the earlier winner, which keeps the tie
+    let counts = [votes(5, 0, 0), votes(3, 0, 2)];
+    assert_eq!(maybe_swing(&counts, &DEFAULT_VOTE_WEIGHTS), None);
+
+    // The same tie with the challenger first goes to the challenger
+    let counts = [votes(3, 0, 2), votes(5, 0, 0)];
+    assert_eq!(pick_winning_option(&counts, &DEFAULT_VOTE_WEIGHTS), Some((1, 5.0)));
+    assert_eq!(maybe_swing(&counts, &DEFAULT_VOTE_WEIGHTS), Some(swing(0, 2, 2, 5, 5)));
+}
+
+#[test]
+fn test_maybe_swing_none_when_the_maybes_cant_change_anything() {
+    assert_eq!(maybe_swing(&[], &DEFAULT_VOTE_WEIGHTS), None);
+    assert_eq!(maybe_swing(&[votes(0, 1, 3)], &DEFAULT_VOTE_WEIGHTS), None);
+    assert_eq!(maybe_swing(&[votes(3, 0, 2)], &DEFAULT_VOTE_WEIGHTS), None);
+    assert_eq!(maybe_swing(&[votes(6, 0, 0), votes(2, 0, 3)], &DEFAULT_VOTE_WEIGHTS), None);
+
+    // Maybes count for nothing with these weights, so converting one is worth a whole vote
+    let yes_only = VoteWeights { yes: 1.0, maybe: 0.0 };
+    assert_eq!(maybe_swing(&[votes(4, 0, 0), votes(3, 0, 2)], &yes_only), Some(swing(1, 2, 2, 5, 4)));
+}
+
+#[test]
+fn test_maybe_swing_picks_the_option_needing_fewest_converts() {
+    // The second option needs three of its maybes, the last two need two each
+    let counts = [votes(6, 0, 0), votes(2, 0, 6), votes(4, 0, 3), votes(4, 0, 3)];
+    assert_eq!(maybe_swing(&counts, &DEFAULT_VOTE_WEIGHTS), Some(swing(2, 2, 3, 6, 6)));
+}
+
+#[test]
+fn test_confirmable_maybe_swing_skips_vetoed_options() {
+    let options = [option("b", "2026-03-07T19:00:00+00:00"), option("a", "2026-03-06T19:00:00+00:00")];
+    let answers = [responses("a", &["yes", "yes", "yes", "maybe"]), responses("b", &["yes", "yes", "maybe", "maybe"])].concat();
+
+    // Options go by start time, so "b" is the later one
+    let (other, found) = confirmable_maybe_swing(&options, &answers, &[]).unwrap();
+    assert_eq!(other.id, "b");
+    assert_eq!(found, swing(1, 2, 2, 4, 3));
+
+    // A required player's "no" rules "b" out, maybes and all
+    let mut vetoed = answers;
+    vetoed[4].response = "no".to_string();
+    assert!(confirmable_maybe_swing(&options, &vetoed, &[0]).is_none());
+}
+
+#[test]
+fn test_select_best_option_counts_maybes() {
+    let options = [option("a", "2026-03-06T19:00:00+00:00"), option("b", "2026-03-07T19:00:00+00:00")];
+    let answers = [responses("a", &["yes", "yes", "yes"]), responses("b", &["yes", "yes", "yes", "maybe", "maybe"])].concat();
+    
+    let selection = select_best_option(&options, &answers).unwrap();
+    assert_eq!(selection.winner.option.id, "b");
+    assert_eq!(selection.winner.counts, votes(3, 0, 2));
+    assert_eq!(selection.winner.score, 4.0);
+    let runner_up = selection.runner_up.unwrap();
+    assert_eq!(runner_up.option.id, "a");
+    assert_eq!(runner_up.counts, votes(3, 0, 0));
+}
+
+#[test]
+fn test_select_best_option_ties_go_to_the_earliest_option() {
+    // Listed out of order: the earlier date still wins the full tie
+    let options = [option("late", "2026-03-07T19:00:00+00:00"), option("early", "2026-03-06T19:00:00+00:00")];
+    let answers = [responses("late", &["yes", "maybe"]), responses("early", &["yes", "maybe", "no"])].concat();
+    
+    let selection = select_best_option(&options, &answers).unwrap();
+    assert_eq!(selection.winner.option.id, "early");
+    assert_eq!(selection.runner_up.unwrap().option.id, "late");
+    
+    // Same score, but firmer commitments beat an earlier date
+    let answers = [responses("late", &["yes", "yes"]), responses("early", &["yes", "maybe", "maybe"])].concat();
+    assert_eq!(select_best_option(&options, &answers).unwrap().winner.option.id, "late");
+}
+
+#[test]
+fn test_select_best_option_maybe_only() {
+    let options = [option("a", "2026-03-06T19:00:00+00:00"), option("b", "2026-03-07T19:00:00+00:00")];
+    
+    // Nobody committed anywhere, so there's nothing to confirm
+    let answers = [responses("a", &["maybe", "maybe"]), responses("b", &["maybe", "no"])].concat();
+    assert!(select_best_option(&options, &answers).is_none());
+    
+    // An option with only maybes can still win once someone says yes elsewhere
+    let answers = [responses("a", &["maybe", "maybe", "maybe"]), responses("b", &["yes"])].concat();
+    let selection = select_best_option(&options, &answers).unwrap();
+    assert_eq!(selection.winner.option.id, "a");
+    assert_eq!(selection.winner.counts.yes, 0);
+}
+
+#[test]
+fn test_select_best_option_without_votes() {
+    let options = [option("a", "2026-03-06T19:00:00+00:00")];
+    assert!(select_best_option(&options, &[]).is_none());
+    assert!(select_best_option(&[], &[]).is_none());
+    
+    // A single option has no runner-up
+    let selection = select_best_option(&options, &responses("a", &["yes"])).unwrap();
+    assert!(selection.runner_up.is_none());
+}
+
+#[test]
+fn test_autoconfirm_waits_for_the_threshold() {
+    let options = [option("a", "2026-03-06T19:00:00+00:00"), option("b", "2026-03-07T19:00:00+00:00")];
+    
+    // Maybes don't count towards the threshold, however well the option scores
+    let answers = [responses("a", &["yes", "yes", "maybe", "maybe"]), responses("b", &["yes", "no"])].concat();
+    assert!(select_autoconfirm_option(&options, &answers, &[], 3).is_none());
+    
+    let answers = [responses("a", &["yes", "yes", "yes"]), responses("b", &["yes", "no"])].concat();
+    let selection = select_autoconfirm_option(&options, &answers, &[], 3).unwrap();
+    assert_eq!(selection.winner.option.id, "a");
+    assert_eq!(selection.winner.counts.yes, 3);
+    assert!(selection.runner_up.is_none(), "only options past the threshold compete");
+}
+
+#[test]
+fn test_autoconfirm_picks_the_best_option_past_the_threshold() {
+    let options = [option("a", "2026-03-06T19:00:00+00:00"), option("b", "2026-03-07T19:00:00+00:00")];
+    let answers = [responses("a", &["yes", "yes", "yes"]), responses("b", &["yes", "yes", "yes", "maybe"])].concat();
+    let selection = select_autoconfirm_option(&options, &answers, &[], 3).unwrap();
+    assert_eq!(selection.winner.option.id, "b");
+    assert_eq!(selection.runner_up.unwrap().option.id, "a");
+    
+    // A required player's no rules "b" out, and "a" still qualifies on its own
+    let mut vetoed = answers;
+    vetoed.push(Response { response: "no".to_string(), user_id: 42, ..responses("b", &["no"]).remove(0) });
+    assert_eq!(select_autoconfirm_option(&options, &vetoed, &[42], 3).unwrap().winner.option.id, "a");
+    assert!(select_autoconfirm_option(&options, &vetoed, &[42], 4).is_none());
+}
+
+#[test]
+fn test_duties_rotate_round_robin() {
+    let duties = [duty(1, "hosting"), duty(2, "snacks")];
+    
+    // Without history, the lowest user IDs go first and nobody gets both
+    let first = assign_duties(&duties, &[30, 10, 20], &[]);
+    assert_eq!(first, vec![(1, 10), (2, 20)]);
+    
+    // Whoever never had a duty is first in line for it, then whoever had it longest ago
+    let past = history(&[&first]);
+    assert_eq!(assign_duties(&duties, &[10, 20, 30], &past), vec![(1, 20), (2, 10)]);
+    let past = history(&[&first, &[(1, 20), (2, 10)]]);
+    assert_eq!(assign_duties(&duties, &[10, 20, 30], &past), vec![(1, 30), (2, 20)]);
+    
+    // Missing sessions keeps your place: 30 is first up for hosting when back
+    let past = history(&[&[(1, 10)], &[(1, 20)]]);
+    assert_eq!(assign_duties(&duties[..1], &[10, 20], &past), vec![(1, 10)]);
+    assert_eq!(assign_duties(&duties[..1], &[10, 20, 30], &past), vec![(1, 30)]);
+}
+
+#[test]
+fn test_duties_with_fewer_voters_than_duties() {
+    let duties = [duty(1, "hosting"), duty(2, "snacks"), duty(3, "cleanup")];
+    assert_eq!(assign_duties(&duties, &[5], &[]), vec![(1, 5), (2, 5), (3, 5)]);
+    assert_eq!(assign_duties(&duties, &[5, 6, 5], &[]), vec![(1, 5), (2, 6), (3, 5)]);
+    assert!(assign_duties(&duties, &[], &[]).is_empty());
+}
+
+#[test]
+fn test_duty_labels() {
+    assert_eq!(duty_label("hosting"), "🏠 Hosting");
+    assert_eq!(duty_label("bring snacks"), "🍕 Bring snacks");
+    assert_eq!(duty_label("maps"), "📌 Maps");
+    assert_eq!(duty_label("🎲 dice"), "🎲 dice");
+    
+    let duties = [duty(1, "hosting"), duty(2, "snacks")];
+    let assignments = history(&[&[(2, 7), (1, 8), (9, 7)]]);
+    let names = |user_id: i64| if user_id == 7 { "bob".to_string() } else { "alice".to_string() };
+    assert_eq!(render_duties(&duties, &assignments, names).as_deref(), Some("🏠 Hosting: alice, 🍕 Snacks: bob"));
+    assert_eq!(render_duties(&duties, &[], names), None);
+}
+
+#[test]
+fn test_rollcall_messages() {
+    use chrono::{TimeZone, Utc};
+    use dnd_scheduler_bot::bot::commands::rollcall::{render_rollcall_call, render_rollcall_confirmed, render_rollcall_expired};
+    use dnd_scheduler_bot::database::models::RosterMember;
+
+    let starts_at = Utc.with_ymd_and_hms(2030, 6, 1, 20, 0, 0).unwrap();
+    let member = |user_id, username: Option<&str>| RosterMember {
+        group_id: 1,
+        user_id,
+        username: username.map(str::to_string),
+        joined_at: String::new(),
+        required: false,
+    };
+    let roster = [member(1, Some("dm_dana")), member(2, Some("mira")), member(3, None)];
+
+    // Everyone but the caller is pinged, by username or by a mention link
+    let call = render_rollcall_call(starts_at, 3, starts_at - chrono::Duration::hours(5), &roster, 1);
+    assert!(call.contains("today at 20:00"), "{call}");
+    assert!(call.contains("3 players"), "{call}");
+    assert!(call.contains("called off at 15:00"), "{call}");
+    assert!(call.ends_with("@mira [player](tg://user?id=3)"), "{call}");
+    assert!(!call.contains("dm"), "{call}");
+
+    let names = ["mira".to_string(), "bob".to_string()];
+    let confirmed = render_rollcall_confirmed(starts_at, &names);
+    assert!(confirmed.contains("2 players said yes: mira, bob"), "{confirmed}");
+    let expired = render_rollcall_expired(starts_at, 3, &names[..1]);
+    assert!(expired.contains("only 1 of the 3 players needed said yes: mira"), "{expired}");
+    assert!(render_rollcall_expired(starts_at, 3, &[]).contains("nobody said yes"));
+}
@@ -0,0 +1,59 @@
+use dnd_scheduler_bot::services::health::{self, HealthService, RuntimeStatus};
+use dnd_scheduler_bot::services::shutdown::{drain_task, Shutdown};
+use dnd_scheduler_bot::testing::test_db;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const TEST_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect(addr).await?;
+    let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+#[tokio::test]
+async fn test_health_server_stops_on_shutdown() {
+    let (db, _temp_dir) = test_db().await.unwrap();
+    let service = HealthService::new(Arc::new(db), Arc::new(RuntimeStatus::default()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let shutdown = Shutdown::new();
+    let server = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            health::serve(listener, service.router, shutdown).await.unwrap();
+        }
+    });
+
+    let response = get(addr, "/health/live").await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "unexpected response: {response}");
+
+    shutdown.trigger();
+    assert!(drain_task("Health server", server, TEST_DRAIN_TIMEOUT).await);
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn test_shutdown_signal_resolves_for_late_subscribers() {
+    let shutdown = Shutdown::new();
+    let early = shutdown.signal();
+    shutdown.trigger();
+    shutdown.trigger();
+
+    tokio::time::timeout(TEST_DRAIN_TIMEOUT, early).await.unwrap();
+    tokio::time::timeout(TEST_DRAIN_TIMEOUT, shutdown.signal()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_drain_task_aborts_stuck_tasks() {
+    let stuck = tokio::spawn(std::future::pending::<()>());
+    assert!(!drain_task("Stuck task", stuck, Duration::from_millis(50)).await);
+}
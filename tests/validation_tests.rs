@@ -128,6 +128,18 @@ mod validation_tests {
         assert_eq!(parsed[2], "Sunday 16:00");
     }
 
+    #[test]
+    fn test_time_options_with_durations() {
+        let parsed = validate_time_options("Friday 19:00 (3h), Saturday 14:00 2h30").unwrap();
+        assert_eq!(parsed, vec!["Friday 19:00 (3h)", "Saturday 14:00 2h30"]);
+
+        // The duration must be in the same range as the group default
+        assert!(validate_time_options("Friday 19:00 (10m)").is_err());
+        assert!(validate_time_options("Friday 19:00 (13h)").is_err());
+        // A duration doesn't make a bare time valid
+        assert!(validate_time_options("19:00 (3h)").is_err());
+    }
+
     #[test]
     fn test_time_options_whitespace_handling() {
         let options = "  Friday 19:00  ,  Saturday 14:30  ";
@@ -315,4 +327,18 @@ mod validation_tests {
         let error = result.err().unwrap();
         assert!(error.to_string().contains("100"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_session_notes_validation() {
+        assert!(validate_session_notes("At Sam's place, bring dice").is_ok());
+        assert!(validate_session_notes("Roll20 🎲 https://roll20.net/join/123").is_ok());
+        assert!(validate_session_notes(&"é".repeat(MAX_NOTES_LENGTH)).is_ok());
+        
+        assert!(validate_session_notes("   ").is_err());
+        let error = validate_session_notes(&"a".repeat(MAX_NOTES_LENGTH + 1)).unwrap_err();
+        assert!(error.to_string().contains("300"));
+        let error = validate_session_notes("Sam's place\nbring dice").unwrap_err();
+        assert!(error.to_string().contains("line breaks"));
+        assert!(validate_session_notes("Sam's\rplace").is_err());
+    }
+}